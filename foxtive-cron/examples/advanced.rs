@@ -110,6 +110,29 @@ impl JobEventListener for LoggerListener {
                     id, scheduled_time
                 )
             }
+            JobEvent::BackingOff {
+                id,
+                consecutive_failures,
+                delay,
+                ..
+            } => {
+                println!(
+                    "EVENT: Job '{}' backing off for {:?} after {} failures",
+                    id, delay, consecutive_failures
+                )
+            }
+            JobEvent::Paused {
+                id,
+                consecutive_failures,
+                ..
+            } => {
+                println!(
+                    "EVENT: Job '{}' paused after {} failures",
+                    id, consecutive_failures
+                )
+            }
+            JobEvent::Resumed { id, .. } => println!("EVENT: Job '{}' resumed", id),
+            JobEvent::Skipped { id, .. } => println!("EVENT: Job '{}' skipped", id),
         }
     }
 }
@@ -40,6 +40,76 @@ pub enum RetryPolicy {
     },
 }
 
+impl RetryPolicy {
+    /// Delay before the given retry (1-based): the interval of a fixed policy, or the
+    /// initial interval doubled for each retry after the first, capped at the maximum.
+    pub fn delay_for(&self, attempt: usize) -> Duration {
+        match self {
+            Self::None => Duration::ZERO,
+            Self::Fixed { interval, .. } => *interval,
+            Self::Exponential {
+                initial_interval,
+                max_interval,
+                ..
+            } => {
+                let exponent = attempt.saturating_sub(1).min(31) as u32;
+                initial_interval
+                    .saturating_mul(2u32.pow(exponent))
+                    .min(*max_interval)
+            }
+        }
+    }
+}
+
+/// Policies for jobs that keep failing across scheduled runs.
+///
+/// A run counts as failed once its retries, if any, are exhausted. A successful
+/// run resets the count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FailurePolicy {
+    /// Keep running on schedule, however many runs failed.
+    #[default]
+    Continue,
+    /// After `threshold` consecutive failed runs, skip scheduled runs until a delay
+    /// has passed since the last failure, doubling from `initial` up to `max` as
+    /// [`RetryPolicy::Exponential`] does between retries.
+    Backoff {
+        threshold: usize,
+        initial: Duration,
+        max: Duration,
+    },
+    /// After `threshold` consecutive failed runs, stop scheduling the job until
+    /// it's resumed with [`Cron::resume_job`](crate::Cron::resume_job).
+    Pause { threshold: usize },
+}
+
+impl FailurePolicy {
+    /// Delay before running again after `consecutive_failures` failed runs,
+    /// `None` if the job isn't held back.
+    pub fn backoff_delay(&self, consecutive_failures: usize) -> Option<Duration> {
+        match *self {
+            Self::Backoff {
+                threshold,
+                initial,
+                max,
+            } if consecutive_failures >= threshold.max(1) => {
+                let backoff = RetryPolicy::Exponential {
+                    max_retries: usize::MAX,
+                    initial_interval: initial,
+                    max_interval: max,
+                };
+                Some(backoff.delay_for(consecutive_failures - threshold.max(1) + 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the job is paused after `consecutive_failures` failed runs.
+    pub fn pauses_after(&self, consecutive_failures: usize) -> bool {
+        matches!(*self, Self::Pause { threshold } if consecutive_failures >= threshold.max(1))
+    }
+}
+
 /// Events emitted by the scheduler during the job lifecycle.
 #[derive(Debug, Clone)]
 pub enum JobEvent {
@@ -64,6 +134,23 @@ pub enum JobEvent {
         attempt: usize,
         delay: Duration,
     },
+    /// Emitted when a failing job's next run is held back by its [`FailurePolicy`].
+    BackingOff {
+        id: String,
+        name: String,
+        consecutive_failures: usize,
+        delay: Duration,
+    },
+    /// Emitted when a failing job is paused by its [`FailurePolicy`].
+    Paused {
+        id: String,
+        name: String,
+        consecutive_failures: usize,
+    },
+    /// Emitted when a paused job is resumed.
+    Resumed { id: String, name: String },
+    /// Emitted when a due run is skipped because the scheduler's [`RunCondition`] disallowed it.
+    Skipped { id: String, name: String },
     /// Emitted when a scheduled job misfires.
     Misfired {
         id: String,
@@ -93,6 +180,35 @@ pub trait MetricsExporter: Send + Sync {
     fn record_misfire(&self, id: &str, name: &str);
 }
 
+/// Source of wall-clock time for the scheduler.
+///
+/// Defaults to [`SystemClock`]; a custom clock lets tests simulate clock jumps
+/// such as NTP corrections or suspend/resume.
+pub trait Clock: Send + Sync {
+    /// The current wall-clock time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A [`Clock`] reading the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Decides whether due jobs may run, e.g. not while the application is in maintenance.
+///
+/// Consulted every time a job is due; a disallowed run is skipped and the job waits
+/// for its next scheduled time. One-time jobs are checked again a second later instead.
+#[async_trait::async_trait]
+pub trait RunCondition: Send + Sync {
+    /// Whether the due job may run now.
+    async fn allows(&self, id: &str, name: &str) -> bool;
+}
+
 /// Information about a job's execution state for persistence.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct JobState {
@@ -299,6 +415,11 @@ pub trait JobContract: Send + Sync {
         RetryPolicy::default()
     }
 
+    /// Defines how the scheduler behaves if runs keep failing.
+    fn failure_policy(&self) -> FailurePolicy {
+        FailurePolicy::default()
+    }
+
     /// Called just before [`run`](Self::run) is invoked.
     ///
     /// Useful for metrics, logging, or pre-flight checks.
@@ -115,6 +115,44 @@ impl FnJob {
         })
     }
 
+    /// Creates a new `FnJob` from an async closure receiving a shared context.
+    ///
+    /// The context (a database pool, a cache client, ...) is handed to every run,
+    /// so jobs don't have to reach for global state and can be tested in isolation.
+    ///
+    /// # Errors
+    /// Returns an error if `schedule_expr` is not a valid cron expression.
+    ///
+    /// # Example
+    /// ```rust
+    /// use foxtive_cron::FnJob;
+    /// use std::sync::Arc;
+    ///
+    /// struct AppContext {
+    ///     greeting: String,
+    /// }
+    ///
+    /// let ctx = Arc::new(AppContext { greeting: "hello".into() });
+    /// let job = FnJob::new_with_context("greet", "Greet", "*/5 * * * * * *", ctx, |ctx| async move {
+    ///     println!("{}", ctx.greeting);
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn new_with_context<C, F, Fut>(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        schedule_expr: &str,
+        context: Arc<C>,
+        func: F,
+    ) -> CronResult<Self>
+    where
+        C: Send + Sync + 'static,
+        F: Fn(Arc<C>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = CronResult<()>> + Send + 'static,
+    {
+        Self::new(id, name, schedule_expr, move || func(context.clone()))
+    }
+
     /// Creates a new `FnJob` from a **blocking** function.
     ///
     /// The function will be run inside `tokio::task::spawn_blocking` to avoid
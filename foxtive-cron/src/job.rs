@@ -1,11 +1,12 @@
 use crate::contracts::{
-    JobContract, JobEvent, JobEventListener, JobState, JobStore, JobType, MetricsExporter,
-    RetryPolicy,
+    FailurePolicy, JobContract, JobEvent, JobEventListener, JobState, JobStore, JobType,
+    MetricsExporter, RetryPolicy,
 };
 use crate::{CronError, CronResult};
 use chrono::{DateTime, Utc};
 use std::borrow::Cow;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::time::{sleep, timeout};
 
 /// An internal wrapper around a `JobContract` that caches the parsed schedule
@@ -19,6 +20,29 @@ pub struct JobItem {
     listeners: Vec<Arc<dyn JobEventListener>>,
     metrics_exporter: Option<Arc<dyn MetricsExporter>>,
     job_store: Option<Arc<dyn JobStore>>,
+    /// Failed runs in a row, shared by all clones of the item
+    failures: Arc<Mutex<FailureStreak>>,
+}
+
+#[derive(Debug, Default)]
+struct FailureStreak {
+    count: usize,
+    last_failure: Option<DateTime<Utc>>,
+}
+
+/// What the job's [`FailurePolicy`] makes of a due run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum FailureAction {
+    Run,
+    /// Hold the job back until `until`
+    Delay {
+        until: DateTime<Utc>,
+        delay: Duration,
+        consecutive_failures: usize,
+    },
+    Pause {
+        consecutive_failures: usize,
+    },
 }
 
 impl std::fmt::Debug for JobItem {
@@ -52,6 +76,7 @@ impl JobItem {
             listeners,
             metrics_exporter,
             job_store,
+            failures: Arc::new(Mutex::new(FailureStreak::default())),
         })
     }
 
@@ -128,6 +153,58 @@ impl JobItem {
         self.job.misfire_policy()
     }
 
+    /// Returns the job's failure policy.
+    pub fn failure_policy(&self) -> FailurePolicy {
+        self.job.failure_policy()
+    }
+
+    /// Number of runs in a row that failed.
+    pub fn consecutive_failures(&self) -> usize {
+        self.failures.lock().unwrap().count
+    }
+
+    /// Forgets past failures, e.g. when a paused job is resumed.
+    pub(crate) fn reset_failures(&self) {
+        *self.failures.lock().unwrap() = FailureStreak::default();
+    }
+
+    /// Applies the failure policy to a run due at `now`.
+    pub(crate) fn failure_action(&self, now: DateTime<Utc>) -> FailureAction {
+        let policy = self.failure_policy();
+        let streak = self.failures.lock().unwrap();
+
+        if policy.pauses_after(streak.count) {
+            return FailureAction::Pause {
+                consecutive_failures: streak.count,
+            };
+        }
+
+        if let (Some(delay), Some(last_failure)) =
+            (policy.backoff_delay(streak.count), streak.last_failure)
+        {
+            let until = last_failure + chrono::Duration::from_std(delay).unwrap_or_default();
+            if until > now {
+                return FailureAction::Delay {
+                    until,
+                    delay,
+                    consecutive_failures: streak.count,
+                };
+            }
+        }
+
+        FailureAction::Run
+    }
+
+    fn record_outcome(&self, success: bool) {
+        let mut streak = self.failures.lock().unwrap();
+        if success {
+            *streak = FailureStreak::default();
+        } else {
+            streak.count += 1;
+            streak.last_failure = Some(Utc::now());
+        }
+    }
+
     async fn emit_event(&self, event: JobEvent) {
         for listener in &self.listeners {
             listener.on_event(event.clone()).await;
@@ -197,6 +274,7 @@ impl JobItem {
                         exporter.record_completion(&id, &name, duration);
                     }
 
+                    self.record_outcome(true);
                     self.job.on_complete().await;
                     return Ok(());
                 }
@@ -215,21 +293,7 @@ impl JobItem {
                     };
 
                     if should_retry {
-                        let delay = match &retry_policy {
-                            RetryPolicy::None => std::time::Duration::from_secs(0),
-                            RetryPolicy::Fixed { interval, .. } => *interval,
-                            RetryPolicy::Exponential {
-                                initial_interval,
-                                max_interval,
-                                ..
-                            } => {
-                                // Cap the exponent to prevent f64 overflow (2^1023 is near f64::MAX)
-                                let exponent = ((attempts as i32 - 1) as u32).min(1023);
-                                let backoff =
-                                    initial_interval.as_secs_f64() * (2.0f64.powi(exponent as i32));
-                                std::time::Duration::from_secs_f64(backoff).min(*max_interval)
-                            }
-                        };
+                        let delay = retry_policy.delay_for(attempts);
 
                         self.emit_event(JobEvent::Retrying {
                             id: id.clone(),
@@ -265,6 +329,7 @@ impl JobItem {
                             exporter.record_failure(&id, &name);
                         }
 
+                        self.record_outcome(false);
                         self.job.on_error(&err).await;
                         return Err(err);
                     }
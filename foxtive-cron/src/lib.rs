@@ -1,13 +1,17 @@
 use crate::contracts::{
-    JobContract, JobEvent, JobEventListener, JobStore, JobType, MetricsExporter, MisfirePolicy,
+    Clock, JobContract, JobEvent, JobEventListener, JobStore, JobType, MetricsExporter,
+    MisfirePolicy, RunCondition, SystemClock,
 };
+use crate::job::FailureAction;
 pub use crate::job::JobItem;
 use chrono::{DateTime, Utc};
+use std::any::{Any, TypeId};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::Semaphore;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc};
 use tokio::task::JoinSet;
 use tokio::time::{Instant, sleep_until};
 use tokio_util::sync::CancellationToken;
@@ -21,6 +25,13 @@ mod job;
 pub use builder::CronExpression;
 pub use fn_job::FnJob;
 
+/// How late a run may start before it counts as a misfire.
+const DRIFT_TOLERANCE: Duration = Duration::from_secs(1);
+
+/// Longest the scheduler sleeps before re-reading the wall clock, so jumps
+/// (NTP corrections, suspend/resume) are noticed within a second.
+const MAX_SLEEP: Duration = Duration::from_secs(1);
+
 /// Custom error types for the `foxtive-cron` library.
 #[derive(Debug, Error)]
 pub enum CronError {
@@ -44,6 +55,9 @@ pub enum CronError {
 
     #[error("Persistence error: {0}")]
     PersistenceError(String),
+
+    #[error("No context of type {0} registered with the scheduler")]
+    MissingContext(&'static str),
 }
 
 /// A type alias for results returned by cron jobs, using [`CronError`].
@@ -100,6 +114,8 @@ impl Default for Cron {
 /// - Adding fully custom jobs via the [`JobContract`] trait.
 /// - Registering async closures using [`add_job_fn`](Self::add_job_fn).
 /// - Registering blocking closures using [`add_blocking_job_fn`](Self::add_blocking_job_fn).
+/// - Registering async closures receiving a shared context using
+///   [`add_job_fn_with_context`](Self::add_job_fn_with_context).
 ///
 /// Jobs are executed concurrently in separate Tokio tasks and automatically
 /// rescheduled after each execution according to their cron schedule.
@@ -107,6 +123,8 @@ pub struct Cron {
     queue: BinaryHeap<ScheduledJob>,
     registry: HashMap<String, JobItem>,
     global_concurrency_limit: Option<Arc<Semaphore>>,
+    /// Slots a job must hold before it's spawned, bounding the number of job tasks
+    max_concurrency: Option<Arc<Semaphore>>,
     per_job_semaphores: HashMap<String, Arc<Semaphore>>,
     listeners: Vec<Arc<dyn JobEventListener>>,
     metrics_exporter: Option<Arc<dyn MetricsExporter>>,
@@ -115,6 +133,15 @@ pub struct Cron {
     tasks: JoinSet<()>,
     /// Track which jobs have been removed but may still be running
     removed_jobs: HashSet<String>,
+    /// Jobs paused by their failure policy, waiting to be resumed, shared with [`CronHandle`]s
+    paused_jobs: Arc<Mutex<HashSet<String>>>,
+    /// Resumptions requested through [`CronHandle`]s, applied by the scheduler loop
+    resume_tx: mpsc::UnboundedSender<String>,
+    resume_rx: mpsc::UnboundedReceiver<String>,
+    /// Contexts handed to jobs, keyed by their type
+    contexts: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    clock: Arc<dyn Clock>,
+    run_condition: Option<Arc<dyn RunCondition>>,
 }
 
 impl std::fmt::Debug for Cron {
@@ -126,6 +153,13 @@ impl std::fmt::Debug for Cron {
                 "global_concurrency_limit",
                 &self.global_concurrency_limit.is_some(),
             )
+            .field(
+                "max_concurrency",
+                &self
+                    .max_concurrency
+                    .as_ref()
+                    .map(|sem| sem.available_permits()),
+            )
             .field("per_job_semaphores_len", &self.per_job_semaphores.len())
             .field("listeners_len", &self.listeners.len())
             .field("metrics_exporter", &self.metrics_exporter.is_some())
@@ -135,26 +169,78 @@ impl std::fmt::Debug for Cron {
                 &self.shutdown_token.is_cancelled(),
             )
             .field("removed_jobs_count", &self.removed_jobs.len())
+            .field("paused_jobs", &*self.paused_jobs.lock().unwrap())
+            .field("contexts_len", &self.contexts.len())
+            .field("run_condition", &self.run_condition.is_some())
             .finish()
     }
 }
 
+/// A cloneable handle to a [`Cron`] scheduler, usable while [`Cron::run`] holds it.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example(mut cron: foxtive_cron::Cron) {
+/// let handle = cron.handle();
+/// tokio::spawn(async move { cron.run().await });
+///
+/// if handle.is_job_paused("reports") {
+///     handle.resume_job("reports");
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CronHandle {
+    paused_jobs: Arc<Mutex<HashSet<String>>>,
+    resume_tx: mpsc::UnboundedSender<String>,
+}
+
+impl CronHandle {
+    /// Whether the job is paused by its failure policy.
+    pub fn is_job_paused(&self, id: &str) -> bool {
+        self.paused_jobs.lock().unwrap().contains(id)
+    }
+
+    /// Resumes a job paused by its [`FailurePolicy`](contracts::FailurePolicy), like
+    /// [`Cron::resume_job`]; the running scheduler schedules its next run.
+    ///
+    /// Returns whether the job was paused.
+    pub fn resume_job(&self, id: &str) -> bool {
+        if !self.paused_jobs.lock().unwrap().remove(id) {
+            return false;
+        }
+
+        // the receiver lives as long as the scheduler, which resumes nothing once dropped
+        let _ = self.resume_tx.send(id.to_string());
+        true
+    }
+}
+
 /// A builder for the `Cron` scheduler.
 #[derive(Default)]
 pub struct CronBuilder {
     global_concurrency_limit: Option<usize>,
+    max_concurrency: Option<usize>,
     listeners: Vec<Arc<dyn JobEventListener>>,
     metrics_exporter: Option<Arc<dyn MetricsExporter>>,
     job_store: Option<Arc<dyn JobStore>>,
+    contexts: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    clock: Option<Arc<dyn Clock>>,
+    run_condition: Option<Arc<dyn RunCondition>>,
 }
 
 impl std::fmt::Debug for CronBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("CronBuilder")
             .field("global_concurrency_limit", &self.global_concurrency_limit)
+            .field("max_concurrency", &self.max_concurrency)
             .field("listeners_len", &self.listeners.len())
             .field("metrics_exporter", &self.metrics_exporter.is_some())
             .field("job_store", &self.job_store.is_some())
+            .field("contexts_len", &self.contexts.len())
+            .field("clock", &self.clock.is_some())
+            .field("run_condition", &self.run_condition.is_some())
             .finish()
     }
 }
@@ -171,6 +257,14 @@ impl CronBuilder {
         self
     }
 
+    /// Sets the maximum number of jobs running at once across the scheduler.
+    ///
+    /// See [`Cron::with_max_concurrency`].
+    pub fn with_max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = Some(limit);
+        self
+    }
+
     /// Adds an event listener to the scheduler.
     pub fn with_listener(mut self, listener: Arc<dyn JobEventListener>) -> Self {
         self.listeners.push(listener);
@@ -189,15 +283,41 @@ impl CronBuilder {
         self
     }
 
+    /// Registers a context handed to jobs, see [`Cron::with_context`].
+    pub fn with_context<C: Send + Sync + 'static>(mut self, context: Arc<C>) -> Self {
+        self.contexts.insert(TypeId::of::<C>(), context);
+        self
+    }
+
+    /// Sets the clock the scheduler reads wall-clock time from, see [`Cron::with_clock`].
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Sets the condition due jobs must meet to run, see [`Cron::with_run_condition`].
+    pub fn with_run_condition(mut self, condition: Arc<dyn RunCondition>) -> Self {
+        self.run_condition = Some(condition);
+        self
+    }
+
     /// Builds the `Cron` scheduler.
     pub fn build(self) -> Cron {
         let mut cron = Cron::new();
         if let Some(limit) = self.global_concurrency_limit {
             cron = cron.with_global_concurrency_limit(limit);
         }
+        if let Some(limit) = self.max_concurrency {
+            cron = cron.with_max_concurrency(limit);
+        }
         cron.listeners = self.listeners;
         cron.metrics_exporter = self.metrics_exporter;
         cron.job_store = self.job_store;
+        cron.contexts = self.contexts;
+        if let Some(clock) = self.clock {
+            cron.clock = clock;
+        }
+        cron.run_condition = self.run_condition;
         cron
     }
 }
@@ -205,10 +325,12 @@ impl CronBuilder {
 impl Cron {
     /// Creates a new empty `Cron` scheduler.
     pub fn new() -> Self {
+        let (resume_tx, resume_rx) = mpsc::unbounded_channel();
         Self {
             queue: BinaryHeap::new(),
             registry: HashMap::new(),
             global_concurrency_limit: None,
+            max_concurrency: None,
             per_job_semaphores: HashMap::new(),
             listeners: Vec::new(),
             metrics_exporter: None,
@@ -216,6 +338,12 @@ impl Cron {
             shutdown_token: CancellationToken::new(),
             tasks: JoinSet::new(),
             removed_jobs: HashSet::new(),
+            paused_jobs: Arc::default(),
+            resume_tx,
+            resume_rx,
+            contexts: HashMap::new(),
+            clock: Arc::new(SystemClock),
+            run_condition: None,
         }
     }
 
@@ -230,6 +358,52 @@ impl Cron {
         self
     }
 
+    /// Sets the maximum number of jobs running at once across the scheduler.
+    ///
+    /// Unlike [`with_global_concurrency_limit`](Self::with_global_concurrency_limit),
+    /// where due jobs are spawned and then wait for a permit, the scheduler waits for a
+    /// free slot before spawning, so a burst of due jobs never spawns more than `limit`
+    /// tasks. Jobs due while all slots are taken run late, as misfires.
+    ///
+    /// A limit of `0` is treated as `1`.
+    pub fn with_max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = Some(Arc::new(Semaphore::new(limit.max(1))));
+        self
+    }
+
+    /// Registers a context (database pool, cache client, ...) for jobs added with
+    /// [`add_job_fn_with_context`](Self::add_job_fn_with_context).
+    ///
+    /// One context is kept per type; registering another `Arc<C>` replaces the previous one.
+    pub fn with_context<C: Send + Sync + 'static>(mut self, context: Arc<C>) -> Self {
+        self.contexts.insert(TypeId::of::<C>(), context);
+        self
+    }
+
+    /// Sets the clock the scheduler reads wall-clock time from.
+    ///
+    /// Defaults to [`SystemClock`]. Mostly useful to simulate clock jumps in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the condition due jobs must meet to run, such as the application not being
+    /// in maintenance.
+    ///
+    /// Disallowed runs are skipped with a [`JobEvent::Skipped`], see [`RunCondition`].
+    pub fn with_run_condition(mut self, condition: Arc<dyn RunCondition>) -> Self {
+        self.run_condition = Some(condition);
+        self
+    }
+
+    /// Returns the registered context of type `C`, if any.
+    pub fn context<C: Send + Sync + 'static>(&self) -> Option<Arc<C>> {
+        self.contexts
+            .get(&TypeId::of::<C>())
+            .and_then(|context| context.clone().downcast::<C>().ok())
+    }
+
     /// Adds an event listener to the scheduler.
     pub fn add_listener(&mut self, listener: Arc<dyn JobEventListener>) {
         self.listeners.push(listener);
@@ -265,7 +439,7 @@ impl Cron {
                 .insert(id.clone(), Arc::new(Semaphore::new(limit)));
         }
 
-        if let Some(next_run) = job_item.next_run_time() {
+        if let Some(next_run) = job_item.next_run_after(self.clock.now()) {
             self.queue.push(ScheduledJob {
                 next_run,
                 priority: job_item.priority(),
@@ -293,6 +467,47 @@ impl Cron {
         self.add_job(job)
     }
 
+    /// Adds a job from an async closure receiving the context registered with
+    /// [`with_context`](Self::with_context).
+    ///
+    /// # Errors
+    /// Returns [`CronError::MissingContext`] if no context of type `C` is registered,
+    /// or an error if `schedule_expr` is invalid.
+    ///
+    /// # Example
+    /// ```rust
+    /// use foxtive_cron::Cron;
+    /// use std::sync::Arc;
+    ///
+    /// struct AppContext {
+    ///     name: String,
+    /// }
+    ///
+    /// let mut cron = Cron::new().with_context(Arc::new(AppContext { name: "app".into() }));
+    /// cron.add_job_fn_with_context("report", "Report", "0 0 * * * * *", |ctx: Arc<AppContext>| async move {
+    ///     println!("Reporting for {}", ctx.name);
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn add_job_fn_with_context<C, F, Fut>(
+        &mut self,
+        id: impl Into<String>,
+        name: impl Into<String>,
+        schedule_expr: &str,
+        func: F,
+    ) -> CronResult<()>
+    where
+        C: Send + Sync + 'static,
+        F: Fn(Arc<C>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = CronResult<()>> + Send + 'static,
+    {
+        let context = self
+            .context::<C>()
+            .ok_or(CronError::MissingContext(std::any::type_name::<C>()))?;
+        let job = FnJob::new_with_context(id, name, schedule_expr, context, func)?;
+        self.add_job(job)
+    }
+
     /// Adds a job from a **blocking** closure or function.
     pub fn add_blocking_job_fn<F>(
         &mut self,
@@ -316,11 +531,66 @@ impl Cron {
     pub fn remove_job(&mut self, id: &str) -> Option<JobItem> {
         // Mark as removed so we know not to reschedule it
         self.removed_jobs.insert(id.to_string());
+        self.paused_jobs.lock().unwrap().remove(id);
 
         // Remove from registry but keep semaphore for running instances
         self.registry.remove(id)
     }
 
+    /// Resumes a job paused by its [`FailurePolicy`](contracts::FailurePolicy),
+    /// forgetting its past failures and scheduling its next run.
+    ///
+    /// Does nothing if the job isn't paused. Use a [`CronHandle`] to resume jobs
+    /// while [`run`](Self::run) is active.
+    pub async fn resume_job(&mut self, id: &str) -> CronResult<()> {
+        if !self.registry.contains_key(id) {
+            return Err(CronError::JobNotFound(id.to_string()));
+        }
+
+        if self.paused_jobs.lock().unwrap().remove(id) {
+            self.reschedule_resumed(id).await;
+        }
+        Ok(())
+    }
+
+    /// Whether the job is paused by its failure policy.
+    pub fn is_job_paused(&self, id: &str) -> bool {
+        self.paused_jobs.lock().unwrap().contains(id)
+    }
+
+    /// Returns a handle pausing state can be queried and jobs resumed through while
+    /// the scheduler is running.
+    pub fn handle(&self) -> CronHandle {
+        CronHandle {
+            paused_jobs: self.paused_jobs.clone(),
+            resume_tx: self.resume_tx.clone(),
+        }
+    }
+
+    /// Schedules the next run of a job taken out of the paused set
+    async fn reschedule_resumed(&mut self, id: &str) {
+        let Some(job_item) = self.registry.get(id) else {
+            return;
+        };
+
+        job_item.reset_failures();
+        if let Some(next_run) = job_item.next_run_after(self.clock.now()) {
+            self.queue.push(ScheduledJob {
+                next_run,
+                priority: job_item.priority(),
+                id: id.to_string(),
+            });
+        }
+
+        let name = job_item.name().to_string();
+        info!("[{name}] Job resumed");
+        self.emit_event(JobEvent::Resumed {
+            id: id.to_string(),
+            name,
+        })
+        .await;
+    }
+
     async fn emit_event(&self, event: JobEvent) {
         for listener in &self.listeners {
             listener.on_event(event.clone()).await;
@@ -341,10 +611,12 @@ impl Cron {
 
             let global_semaphore = self.global_concurrency_limit.clone();
             let job_semaphore = self.per_job_semaphores.get(id).cloned();
+            let slot = self.acquire_slot().await?;
 
             let _scheduler_weak = Arc::new(()); // Dummy for now, we need a real weak ref to Cron if we want to remove Once jobs properly
 
             self.tasks.spawn(async move {
+                let _slot = slot;
                 let _global_permit = match global_semaphore {
                     Some(sem) => match sem.acquire_owned().await {
                         Ok(permit) => Some(permit),
@@ -388,6 +660,19 @@ impl Cron {
         self.queue.peek().map(|j| j.id.clone())
     }
 
+    /// Waits for a free [`max_concurrency`](Self::with_max_concurrency) slot,
+    /// giving up if the scheduler shuts down meanwhile.
+    async fn acquire_slot(&self) -> CronResult<Option<OwnedSemaphorePermit>> {
+        let Some(sem) = self.max_concurrency.clone() else {
+            return Ok(None);
+        };
+
+        tokio::select! {
+            permit = sem.acquire_owned() => permit.map(Some).map_err(|_| CronError::ShuttingDown),
+            _ = self.shutdown_token.cancelled() => Err(CronError::ShuttingDown),
+        }
+    }
+
     /// Signals the scheduler to stop and waits for all active jobs to finish.
     pub async fn shutdown(&mut self) {
         info!("Shutting down cron scheduler...");
@@ -413,9 +698,22 @@ impl Cron {
             // Clean up semaphores for removed jobs that are no longer running
             self.cleanup_removed_job_semaphores();
 
+            // Apply the resumptions requested through handles
+            while let Ok(id) = self.resume_rx.try_recv() {
+                self.reschedule_resumed(&id).await;
+            }
+
             // Peek at the next job without removing it.
             let (next_run, id) = match self.queue.peek() {
                 Some(scheduled) => (scheduled.next_run, scheduled.id.clone()),
+                // Paused jobs may still be resumed through a handle
+                None if !self.paused_jobs.lock().unwrap().is_empty() => {
+                    tokio::select! {
+                        Some(id) = self.resume_rx.recv() => self.reschedule_resumed(&id).await,
+                        _ = self.shutdown_token.cancelled() => return,
+                    }
+                    continue;
+                }
                 None => {
                     warn!("Cron queue is empty, scheduler exiting");
                     return;
@@ -428,15 +726,19 @@ impl Cron {
                 continue;
             }
 
-            let now = Utc::now();
+            // Sleep in bounded steps and re-check the wall clock after each, so a clock
+            // jumping forward or back moves the wake-up time with it.
+            let now = self.clock.now();
             if next_run > now {
-                let delay = (next_run - now).to_std().unwrap_or_default();
+                let delay = (next_run - now).to_std().unwrap_or_default().min(MAX_SLEEP);
                 tokio::select! {
                     _ = sleep_until(Instant::now() + delay) => {}
+                    Some(id) = self.resume_rx.recv() => self.reschedule_resumed(&id).await,
                     _ = self.shutdown_token.cancelled() => {
                         return;
                     }
                 }
+                continue;
             }
 
             if self.shutdown_token.is_cancelled() {
@@ -444,7 +746,6 @@ impl Cron {
             }
 
             // Drain all jobs that are due now (handles multiple jobs at the same tick).
-            let now = Utc::now();
             let mut due_jobs = Vec::new();
             while let Some(scheduled) = self.queue.peek() {
                 if scheduled.next_run <= now {
@@ -462,8 +763,82 @@ impl Cron {
                     let job_item_to_spawn = job_item.clone();
                     let name = job_item.name().to_string();
 
+                    // Hold back jobs that keep failing, as configured by their failure policy
+                    match job_item.failure_action(Utc::now()) {
+                        FailureAction::Run => {}
+                        FailureAction::Delay {
+                            until,
+                            delay,
+                            consecutive_failures,
+                        } => {
+                            warn!(
+                                "[{name}] Job failed {consecutive_failures} times in a row, backing off for {delay:?}"
+                            );
+                            self.emit_event(JobEvent::BackingOff {
+                                id: scheduled.id.clone(),
+                                name,
+                                consecutive_failures,
+                                delay,
+                            })
+                            .await;
+
+                            if let Some(next_run) = job_item.next_run_after(until) {
+                                self.queue.push(ScheduledJob {
+                                    next_run,
+                                    priority: job_item.priority(),
+                                    id: scheduled.id,
+                                });
+                            }
+                            continue;
+                        }
+                        FailureAction::Pause {
+                            consecutive_failures,
+                        } => {
+                            warn!(
+                                "[{name}] Job failed {consecutive_failures} times in a row, pausing it"
+                            );
+                            self.paused_jobs
+                                .lock()
+                                .unwrap()
+                                .insert(scheduled.id.clone());
+                            self.emit_event(JobEvent::Paused {
+                                id: scheduled.id,
+                                name,
+                                consecutive_failures,
+                            })
+                            .await;
+                            continue;
+                        }
+                    }
+
+                    if let Some(condition) = &self.run_condition
+                        && !condition.allows(&scheduled.id, &name).await
+                    {
+                        info!("[{name}] Run skipped, disallowed by the run condition");
+                        let next_run = match job_item.job_type() {
+                            JobType::Once => Some(now + MAX_SLEEP),
+                            _ => job_item.next_run_after(now),
+                        };
+                        if let Some(next_run) = next_run {
+                            self.queue.push(ScheduledJob {
+                                next_run,
+                                priority: job_item.priority(),
+                                id: scheduled.id.clone(),
+                            });
+                        }
+                        self.emit_event(JobEvent::Skipped {
+                            id: scheduled.id,
+                            name,
+                        })
+                        .await;
+                        continue;
+                    }
+
                     let global_semaphore = self.global_concurrency_limit.clone();
                     let job_semaphore = self.per_job_semaphores.get(&scheduled.id).cloned();
+                    let Ok(slot) = self.acquire_slot().await else {
+                        return;
+                    };
 
                     let scheduled_time = scheduled.next_run;
 
@@ -472,6 +847,7 @@ impl Cron {
                     let is_once_job = job_item.job_type() == JobType::Once;
 
                     self.tasks.spawn(async move {
+                        let _slot = slot;
                         let _global_permit = match global_semaphore {
                             Some(sem) => match sem.acquire_owned().await {
                                 Ok(permit) => Some(permit),
@@ -504,10 +880,12 @@ impl Cron {
                     }
 
                     // Re-schedule based on misfire policy
-                    let now = Utc::now();
+                    let now = self.clock.now();
                     let misfire_policy = job_item.misfire_policy();
+                    let drift = (now - scheduled_time).to_std().unwrap_or_default();
 
-                    if scheduled_time < now {
+                    if drift > DRIFT_TOLERANCE {
+                        warn!("[{name_cloned}] Job started {drift:?} after its scheduled time");
                         self.emit_event(JobEvent::Misfired {
                             id: scheduled.id.clone(),
                             name: name_cloned.clone(),
@@ -520,20 +898,21 @@ impl Cron {
                         }
                     }
 
+                    // The next occurrence is computed from the intended fire time rather than
+                    // from now, so time spent waking up or awaiting doesn't shift the schedule.
+                    let anchored = job_item.next_run_after(scheduled_time);
+
                     match misfire_policy {
-                        MisfirePolicy::Skip => {
-                            if let Some(next_run) = job_item.next_run_time() {
-                                self.queue.push(ScheduledJob {
-                                    next_run,
-                                    priority: job_item.priority(),
-                                    id: scheduled.id,
-                                });
-                            }
-                        }
-                        MisfirePolicy::FireOnce => {
-                            // If we're behind schedule, fire once as soon as possible.
-                            // The next execution after 'now' will resume regular schedule.
-                            if let Some(next_run) = job_item.next_run_time() {
+                        MisfirePolicy::Skip | MisfirePolicy::FireOnce => {
+                            // If we're behind schedule, skip occurrences missed by more than the
+                            // tolerance; the next execution after 'now' resumes the regular schedule.
+                            let next_run = match anchored {
+                                Some(next) if next + DRIFT_TOLERANCE < now => {
+                                    job_item.next_run_after(now)
+                                }
+                                next => next,
+                            };
+                            if let Some(next_run) = next_run {
                                 self.queue.push(ScheduledJob {
                                     next_run,
                                     priority: job_item.priority(),
@@ -543,7 +922,7 @@ impl Cron {
                         }
                         MisfirePolicy::FireAll => {
                             // Find the very next occurrence after the one we just processed
-                            if let Some(next) = job_item.next_run_after(scheduled_time) {
+                            if let Some(next) = anchored {
                                 self.queue.push(ScheduledJob {
                                     next_run: next,
                                     priority: job_item.priority(),
@@ -0,0 +1,148 @@
+mod common;
+use chrono::{DateTime, TimeZone, Utc};
+use common::*;
+use foxtive_cron::Cron;
+use foxtive_cron::contracts::{Clock, MisfirePolicy};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A clock that only moves when told to.
+struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    fn at(now: DateTime<Utc>) -> Arc<Self> {
+        Arc::new(Self {
+            now: Mutex::new(now),
+        })
+    }
+
+    fn advance(&self, by: chrono::Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+fn start_of_2030() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap() + chrono::Duration::milliseconds(500)
+}
+
+/// Time for the scheduler loop to notice a clock change.
+const SETTLE: Duration = Duration::from_millis(1500);
+
+mod clock_jumps {
+    use super::*;
+
+    #[tokio::test]
+    async fn forward_jump_fires_due_job() {
+        let clock = ManualClock::at(start_of_2030());
+        let job = MockJob::new("minutely", "0 * * * * * *");
+        let run_count = job.run_count.clone();
+
+        let mut cron = Cron::new().with_clock(clock.clone());
+        cron.add_job(job).unwrap();
+        let handle = tokio::spawn(async move { cron.run().await });
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(run_count.load(Ordering::SeqCst), 0);
+
+        // e.g. the machine resumed from suspend a minute later
+        clock.advance(chrono::Duration::seconds(60));
+        tokio::time::sleep(SETTLE).await;
+        handle.abort();
+
+        assert_eq!(run_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn backward_jump_holds_job_until_its_time() {
+        let clock = ManualClock::at(start_of_2030() + chrono::Duration::seconds(59));
+        let job = MockJob::new("minutely", "0 * * * * * *");
+        let run_count = job.run_count.clone();
+
+        let mut cron = Cron::new().with_clock(clock.clone());
+        cron.add_job(job).unwrap();
+
+        // e.g. an NTP correction sets the clock back
+        clock.advance(chrono::Duration::minutes(-10));
+        let handle = tokio::spawn(async move { cron.run().await });
+
+        tokio::time::sleep(SETTLE).await;
+        assert_eq!(run_count.load(Ordering::SeqCst), 0);
+
+        // Back past the original time, the run is finally due
+        clock.advance(chrono::Duration::minutes(10) + chrono::Duration::seconds(1));
+        tokio::time::sleep(SETTLE).await;
+        handle.abort();
+
+        assert_eq!(run_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn fire_all_catches_up_every_missed_run() {
+        let clock = ManualClock::at(start_of_2030());
+        let job =
+            MockJob::new("minutely", "0 * * * * * *").with_misfire_policy(MisfirePolicy::FireAll);
+        let run_count = job.run_count.clone();
+
+        let mut cron = Cron::new().with_clock(clock.clone());
+        cron.add_job(job).unwrap();
+        let handle = tokio::spawn(async move { cron.run().await });
+
+        clock.advance(chrono::Duration::minutes(3));
+        tokio::time::sleep(SETTLE).await;
+        handle.abort();
+
+        assert_eq!(run_count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn skip_runs_once_after_missed_runs() {
+        let clock = ManualClock::at(start_of_2030());
+        let job = MockJob::new("minutely", "0 * * * * * *");
+        let run_count = job.run_count.clone();
+
+        let mut cron = Cron::new().with_clock(clock.clone());
+        cron.add_job(job).unwrap();
+        let handle = tokio::spawn(async move { cron.run().await });
+
+        clock.advance(chrono::Duration::minutes(3));
+        tokio::time::sleep(SETTLE).await;
+        handle.abort();
+
+        assert_eq!(run_count.load(Ordering::SeqCst), 1);
+    }
+}
+
+mod drift {
+    use super::*;
+
+    #[tokio::test]
+    async fn late_start_within_tolerance_keeps_next_occurrence() {
+        let clock = ManualClock::at(start_of_2030());
+        let job = MockJob::new("secondly", "* * * * * * *");
+        let run_count = job.run_count.clone();
+
+        let mut cron = Cron::new().with_clock(clock.clone());
+        cron.add_job(job).unwrap();
+        let handle = tokio::spawn(async move { cron.run().await });
+
+        // Fire the 00:00:01 run 0.8s late; the 00:00:02 run must still be due
+        clock.advance(chrono::Duration::milliseconds(1300));
+        tokio::time::sleep(SETTLE).await;
+        assert_eq!(run_count.load(Ordering::SeqCst), 1);
+
+        clock.advance(chrono::Duration::milliseconds(500));
+        tokio::time::sleep(SETTLE).await;
+        handle.abort();
+
+        assert_eq!(run_count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use foxtive_cron::contracts::{
-    JobContract, MisfirePolicy, RetryPolicy, Schedule, ValidatedSchedule,
+    FailurePolicy, JobContract, MisfirePolicy, RetryPolicy, Schedule, ValidatedSchedule,
 };
 use foxtive_cron::{CronError, CronResult};
 use std::borrow::Cow;
@@ -29,6 +29,7 @@ pub struct MockJob {
     pub concurrency_limit: Option<usize>,
     pub misfire_policy: MisfirePolicy,
     pub retry_policy: RetryPolicy,
+    pub failure_policy: FailurePolicy,
 }
 
 #[allow(dead_code)]
@@ -49,6 +50,7 @@ impl MockJob {
             concurrency_limit: None,
             misfire_policy: MisfirePolicy::Skip,
             retry_policy: RetryPolicy::None,
+            failure_policy: FailurePolicy::Continue,
         }
     }
 
@@ -77,6 +79,11 @@ impl MockJob {
         self
     }
 
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
     pub fn failing(id: impl Into<String>, schedule_expr: &str) -> Self {
         Self {
             should_fail: true,
@@ -131,6 +138,10 @@ impl JobContract for MockJob {
         self.retry_policy.clone()
     }
 
+    fn failure_policy(&self) -> FailurePolicy {
+        self.failure_policy
+    }
+
     async fn on_start(&self) {
         self.start_count
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -1,6 +1,6 @@
 mod common;
 use common::*;
-use foxtive_cron::Cron;
+use foxtive_cron::{Cron, CronBuilder};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
@@ -138,4 +138,80 @@ mod concurrency {
             "per-job concurrency limit exceeded"
         );
     }
+
+    #[tokio::test]
+    async fn max_concurrency_is_enforced() {
+        let run_count = Arc::new(AtomicUsize::new(0));
+        let active_count = Arc::new(AtomicUsize::new(0));
+        let max_active = Arc::new(AtomicUsize::new(0));
+
+        let mut cron = CronBuilder::new().with_max_concurrency(2).build();
+
+        for i in 0..6 {
+            let run_count = run_count.clone();
+            let active_count = active_count.clone();
+            let max_active = max_active.clone();
+
+            cron.add_job_fn(format!("job-{}", i), "Job", "*/1 * * * * * *", move || {
+                let run_count = run_count.clone();
+                let active_count = active_count.clone();
+                let max_active = max_active.clone();
+                async move {
+                    let current = active_count.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_active.fetch_max(current, Ordering::SeqCst);
+
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    run_count.fetch_add(1, Ordering::SeqCst);
+                    active_count.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .unwrap();
+        }
+
+        let handle = tokio::spawn(async move {
+            cron.run().await;
+        });
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        handle.abort();
+
+        assert_eq!(
+            max_active.load(Ordering::SeqCst),
+            2,
+            "max concurrency not reached or exceeded"
+        );
+        assert!(run_count.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test]
+    async fn max_concurrency_bounds_manual_triggers() {
+        let active_count = Arc::new(AtomicUsize::new(0));
+        let max_active = Arc::new(AtomicUsize::new(0));
+
+        let mut cron = Cron::new().with_max_concurrency(1);
+        {
+            let active_count = active_count.clone();
+            let max_active = max_active.clone();
+            cron.add_job_fn("manual", "Manual", "0 0 0 1 1 * 2099", move || {
+                let active_count = active_count.clone();
+                let max_active = max_active.clone();
+                async move {
+                    let current = active_count.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_active.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    active_count.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .unwrap();
+        }
+
+        for _ in 0..3 {
+            cron.trigger_job("manual").await.unwrap();
+        }
+        cron.shutdown().await;
+
+        assert_eq!(max_active.load(Ordering::SeqCst), 1);
+    }
 }
@@ -0,0 +1,159 @@
+mod common;
+use async_trait::async_trait;
+use common::*;
+use foxtive_cron::contracts::{FailurePolicy, JobEvent, JobEventListener};
+use foxtive_cron::{Cron, JobItem};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct CollectingListener {
+    events: Arc<Mutex<Vec<JobEvent>>>,
+}
+
+#[async_trait]
+impl JobEventListener for CollectingListener {
+    async fn on_event(&self, event: JobEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+mod policy {
+    use super::*;
+
+    #[test]
+    fn continue_never_holds_jobs_back() {
+        let policy = FailurePolicy::default();
+        assert_eq!(policy, FailurePolicy::Continue);
+        assert_eq!(policy.backoff_delay(100), None);
+        assert!(!policy.pauses_after(100));
+    }
+
+    #[test]
+    fn backoff_doubles_from_threshold_up_to_max() {
+        let policy = FailurePolicy::Backoff {
+            threshold: 2,
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(5),
+        };
+
+        assert_eq!(policy.backoff_delay(1), None);
+        assert_eq!(policy.backoff_delay(2), Some(Duration::from_secs(1)));
+        assert_eq!(policy.backoff_delay(3), Some(Duration::from_secs(2)));
+        assert_eq!(policy.backoff_delay(4), Some(Duration::from_secs(4)));
+        assert_eq!(policy.backoff_delay(5), Some(Duration::from_secs(5)));
+        assert_eq!(policy.backoff_delay(1000), Some(Duration::from_secs(5)));
+        assert!(!policy.pauses_after(1000));
+    }
+
+    #[test]
+    fn pause_applies_from_threshold() {
+        let policy = FailurePolicy::Pause { threshold: 3 };
+        assert!(!policy.pauses_after(2));
+        assert!(policy.pauses_after(3));
+        assert_eq!(policy.backoff_delay(3), None);
+    }
+}
+
+mod consecutive_failures {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_runs_are_counted() {
+        let job = Arc::new(MockJob::failing("failing", "*/1 * * * * * *"));
+        let item = JobItem::new(job, vec![], None, None).unwrap();
+
+        assert!(item.run().await.is_err());
+        assert!(item.run().await.is_err());
+        assert_eq!(item.consecutive_failures(), 2);
+    }
+
+    #[tokio::test]
+    async fn successful_run_resets_the_count() {
+        let job = Arc::new(MockJob::new("passing", "*/1 * * * * * *"));
+        let item = JobItem::new(job, vec![], None, None).unwrap();
+
+        assert!(item.run().await.is_ok());
+        assert_eq!(item.consecutive_failures(), 0);
+    }
+}
+
+mod scheduler {
+    use super::*;
+
+    #[tokio::test]
+    async fn failing_job_is_paused_and_can_be_resumed() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let job = MockJob::failing("flaky", "*/1 * * * * * *")
+            .with_failure_policy(FailurePolicy::Pause { threshold: 1 });
+        let run_count = job.run_count.clone();
+
+        let mut cron = Cron::builder()
+            .with_listener(Arc::new(CollectingListener {
+                events: events.clone(),
+            }))
+            .build();
+        cron.add_job(job).unwrap();
+
+        let handle = tokio::spawn(async move {
+            let _ = tokio::time::timeout(Duration::from_millis(3500), cron.run()).await;
+            cron
+        });
+        let mut cron = handle.await.unwrap();
+
+        assert_eq!(run_count.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(cron.is_job_paused("flaky"));
+        assert!(events.lock().unwrap().iter().any(|event| matches!(
+            event,
+            JobEvent::Paused { id, consecutive_failures: 1, .. } if id == "flaky"
+        )));
+
+        cron.resume_job("flaky").await.unwrap();
+        assert!(!cron.is_job_paused("flaky"));
+        assert!(
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|event| matches!(event, JobEvent::Resumed { id, .. } if id == "flaky"))
+        );
+    }
+
+    async fn wait_until(condition: impl Fn() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !condition() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("condition not met in time");
+    }
+
+    #[tokio::test]
+    async fn paused_job_can_be_resumed_while_running() {
+        let job = MockJob::failing("flaky", "*/1 * * * * * *")
+            .with_failure_policy(FailurePolicy::Pause { threshold: 1 });
+        let run_count = job.run_count.clone();
+
+        let mut cron = Cron::new();
+        cron.add_job(job).unwrap();
+        let handle = cron.handle();
+        let running = tokio::spawn(async move { cron.run().await });
+
+        wait_until(|| handle.is_job_paused("flaky")).await;
+        assert_eq!(run_count.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // the scheduler waits for resumptions instead of exiting with only paused jobs
+        assert!(handle.resume_job("flaky"));
+        assert!(!handle.resume_job("flaky"));
+        wait_until(|| run_count.load(std::sync::atomic::Ordering::SeqCst) == 2).await;
+
+        assert!(!running.is_finished());
+        running.abort();
+    }
+
+    #[tokio::test]
+    async fn resuming_unknown_job_fails() {
+        let mut cron = Cron::new();
+        assert!(cron.resume_job("missing").await.is_err());
+    }
+}
@@ -101,3 +101,85 @@ mod fn_job {
         assert!(job.run().await.is_err());
     }
 }
+
+mod context {
+    use super::*;
+    use foxtive_cron::contracts::JobContract;
+    use foxtive_cron::{Cron, CronBuilder};
+    use std::sync::atomic::AtomicUsize;
+
+    struct AppContext {
+        runs: AtomicUsize,
+    }
+
+    #[tokio::test]
+    async fn new_with_context_passes_context_to_each_run() {
+        let ctx = Arc::new(AppContext {
+            runs: AtomicUsize::new(0),
+        });
+
+        let job = FnJob::new_with_context(
+            "id",
+            "Name",
+            "*/1 * * * * * *",
+            ctx.clone(),
+            |ctx| async move {
+                ctx.runs.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        job.run().await.unwrap();
+        job.run().await.unwrap();
+        assert_eq!(ctx.runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn context_is_looked_up_by_type() {
+        let ctx = Arc::new(AppContext {
+            runs: AtomicUsize::new(0),
+        });
+        let cron = CronBuilder::new().with_context(ctx.clone()).build();
+
+        assert!(Arc::ptr_eq(&cron.context::<AppContext>().unwrap(), &ctx));
+        assert!(cron.context::<String>().is_none());
+    }
+
+    #[tokio::test]
+    async fn add_job_fn_with_context_runs_with_registered_context() {
+        let ctx = Arc::new(AppContext {
+            runs: AtomicUsize::new(0),
+        });
+        let mut cron = Cron::new().with_context(ctx.clone());
+
+        cron.add_job_fn_with_context(
+            "ctx-job",
+            "Ctx Job",
+            "0 0 0 1 1 * 2099",
+            |ctx: Arc<AppContext>| async move {
+                ctx.runs.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        cron.trigger_job("ctx-job").await.unwrap();
+        cron.shutdown().await;
+        assert_eq!(ctx.runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn add_job_fn_with_context_fails_without_context() {
+        let mut cron = Cron::new();
+        let result = cron.add_job_fn_with_context(
+            "ctx-job",
+            "Ctx Job",
+            "*/1 * * * * * *",
+            |_ctx: Arc<AppContext>| async { Ok(()) },
+        );
+
+        assert!(matches!(result, Err(CronError::MissingContext(_))));
+        assert!(cron.list_job_ids().is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+mod common;
+use async_trait::async_trait;
+use common::*;
+use foxtive_cron::Cron;
+use foxtive_cron::contracts::{JobEvent, JobEventListener, RunCondition};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct CollectingListener {
+    events: Arc<Mutex<Vec<JobEvent>>>,
+}
+
+#[async_trait]
+impl JobEventListener for CollectingListener {
+    async fn on_event(&self, event: JobEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+/// Disallows every run while `maintenance` is set
+struct Maintenance {
+    maintenance: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl RunCondition for Maintenance {
+    async fn allows(&self, _id: &str, _name: &str) -> bool {
+        !self.maintenance.load(Ordering::SeqCst)
+    }
+}
+
+#[tokio::test]
+async fn disallowed_runs_are_skipped() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let maintenance = Arc::new(AtomicBool::new(true));
+    let job = MockJob::new("report", "*/1 * * * * * *");
+    let run_count = job.run_count.clone();
+
+    let mut cron = Cron::builder()
+        .with_listener(Arc::new(CollectingListener {
+            events: events.clone(),
+        }))
+        .with_run_condition(Arc::new(Maintenance {
+            maintenance: maintenance.clone(),
+        }))
+        .build();
+    cron.add_job(job).unwrap();
+
+    let handle = tokio::spawn(async move {
+        let _ = tokio::time::timeout(Duration::from_millis(2500), cron.run()).await;
+    });
+    handle.await.unwrap();
+
+    assert_eq!(run_count.load(Ordering::SeqCst), 0);
+    assert!(
+        events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|event| matches!(event, JobEvent::Skipped { id, .. } if id == "report"))
+    );
+}
+
+#[tokio::test]
+async fn runs_resume_once_allowed_again() {
+    let maintenance = Arc::new(AtomicBool::new(true));
+    let job = MockJob::new("report", "*/1 * * * * * *");
+    let run_count = job.run_count.clone();
+
+    let mut cron = Cron::new().with_run_condition(Arc::new(Maintenance {
+        maintenance: maintenance.clone(),
+    }));
+    cron.add_job(job).unwrap();
+
+    let handle = tokio::spawn(async move {
+        let _ = tokio::time::timeout(Duration::from_millis(3500), cron.run()).await;
+    });
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(run_count.load(Ordering::SeqCst), 0);
+
+    maintenance.store(false, Ordering::SeqCst);
+    handle.await.unwrap();
+    assert!(run_count.load(Ordering::SeqCst) >= 1);
+}
@@ -0,0 +1,96 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Index, LitInt, LitStr, parse_macro_input};
+
+/// Options set with `#[cache_key(...)]` on the struct
+struct CacheKeyConfig {
+    prefix: String,
+    version: u32,
+}
+
+/// Derive `foxtive::cache::CacheKey`, building `prefix:v{version}:{field}:{field}...`
+/// from the fields marked `#[cache_key]`, or all of them when none is marked, with `:`
+/// and `\` escaped in the field values
+pub fn derive_cache_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut config = CacheKeyConfig {
+        prefix: input.ident.to_string().to_lowercase(),
+        version: 1,
+    };
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cache_key"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                config.prefix = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("version") {
+                config.version = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            } else {
+                return Err(meta.error("expected `prefix` or `version`"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "CacheKey can only be derived for structs",
+        ));
+    };
+
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => vec![],
+    };
+
+    let marked: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.attrs.iter().any(|a| a.path().is_ident("cache_key")))
+        .collect();
+
+    let selected = match marked.is_empty() {
+        true => fields.iter().enumerate().collect(),
+        false => marked,
+    };
+
+    // values are escaped so a `:` in a field can't be taken for the separator
+    let accessors = selected.iter().map(|(index, field)| match &field.ident {
+        Some(ident) => quote! { ::foxtive::cache::escape_key_part(&self.#ident) },
+        None => {
+            let index = Index::from(*index);
+            quote! { ::foxtive::cache::escape_key_part(&self.#index) }
+        }
+    });
+
+    // braces in the prefix must not be taken for placeholders
+    let mut format = format!("{}:v{}", config.prefix, config.version)
+        .replace('{', "{{")
+        .replace('}', "}}");
+    format.push_str(&":{}".repeat(selected.len()));
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::foxtive::cache::CacheKey for #name #ty_generics #where_clause {
+            fn cache_key(&self) -> String {
+                format!(#format, #(#accessors),*)
+            }
+        }
+    })
+}
@@ -1,16 +1,53 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Ident, parse_macro_input};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitBool, Token, parse_macro_input};
+
+/// `EnumName`, optionally followed by `, serde = false`
+struct CommonTraitsInput {
+    enum_name: Ident,
+    serde: bool,
+}
+
+impl Parse for CommonTraitsInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let enum_name: Ident = input.parse()?;
+        let mut serde = true;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let option: Ident = input.parse()?;
+            if option != "serde" {
+                return Err(syn::Error::new(option.span(), "expected `serde`"));
+            }
+
+            input.parse::<Token![=]>()?;
+            serde = input.parse::<LitBool>()?.value;
+        }
+
+        Ok(CommonTraitsInput { enum_name, serde })
+    }
+}
 
 pub fn impl_enum_common_traits(input: TokenStream) -> TokenStream {
-    let variant_name = parse_macro_input!(input as Ident);
+    let CommonTraitsInput {
+        enum_name: variant_name,
+        serde,
+    } = parse_macro_input!(input as CommonTraitsInput);
 
-    let expanded = quote! {
+    let debug_trait = quote! {
         impl std::fmt::Debug for #variant_name {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 f.write_str(&self.to_string())
             }
         }
+    };
+
+    if !serde {
+        return debug_trait.into();
+    }
+
+    let expanded = quote! {
+        #debug_trait
 
         impl serde::Serialize for #variant_name {
             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
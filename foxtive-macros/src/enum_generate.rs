@@ -1,46 +1,222 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{
-    Ident, Token, Variant, braced,
+    Attribute, Expr, Ident, LitBool, LitStr, Token, Variant, braced, parenthesized,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
 };
 
 /// Struct to parse macro input
 struct EnumInput {
+    attrs: Vec<Attribute>,
+    config: EnumConfig,
     enum_name: Ident,
     variants: Punctuated<Variant, Token![,]>,
 }
 
+/// Options set with `#[enum_config(...)]`
+struct EnumConfig {
+    /// String representation of the variants, any strum `serialize_all` case
+    rename_all: String,
+    /// Whether to implement `Serialize` and `Deserialize`
+    serde: bool,
+}
+
+impl Default for EnumConfig {
+    fn default() -> Self {
+        EnumConfig {
+            rename_all: "SCREAMING_SNAKE_CASE".to_string(),
+            serde: true,
+        }
+    }
+}
+
 impl Parse for EnumInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?; // Parse the attributes
+        let mut config = EnumConfig::default();
+
+        // Only `enum_config` is ours, the other attributes are forwarded to the enum
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("enum_config"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    config.rename_all = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("serde") {
+                    config.serde = meta.value()?.parse::<LitBool>()?.value;
+                } else {
+                    return Err(meta.error("expected `rename_all` or `serde`"));
+                }
+
+                Ok(())
+            })?;
+        }
+        attrs.retain(|attr| !attr.path().is_ident("enum_config"));
+
         let enum_name: Ident = input.parse()?; // Parse the enum name
         let content;
         braced!(content in input); // Parse variants inside `{}`
 
         let variants = Punctuated::<Variant, Token![,]>::parse_terminated(&content)?;
         Ok(EnumInput {
+            attrs,
+            config,
             enum_name,
             variants,
         })
     }
 }
 
+/// Strings a variant is parsed from besides its `Display`, as `strum::EnumString` reads them
+#[derive(Default)]
+struct ParseOptions {
+    /// `serialize` and `to_string` values
+    aliases: Vec<LitStr>,
+    ascii_case_insensitive: bool,
+}
+
+impl ParseOptions {
+    /// Reads the `#[strum(...)]` attributes, rejecting those `FromStr` can't honour
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = ParseOptions::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("strum")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("serialize") || meta.path.is_ident("to_string") {
+                    options.aliases.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("ascii_case_insensitive") {
+                    options.ascii_case_insensitive = match meta.input.peek(Token![=]) {
+                        true => meta.value()?.parse::<LitBool>()?.value,
+                        false => true,
+                    };
+                } else if meta.path.is_ident("disabled") || meta.path.is_ident("default") {
+                    return Err(meta.error("not supported by `generate_enum`"));
+                } else if meta.input.peek(Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    parenthesized!(_content in meta.input);
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(options)
+    }
+}
+
 /// Procedural macro to generate a simple Rust enum with Strum traits
 pub fn generate_enum(input: TokenStream) -> TokenStream {
     let EnumInput {
+        attrs,
+        config,
         enum_name,
         variants,
     } = syn::parse_macro_input!(input as EnumInput);
 
+    let case_insensitive = match ParseOptions::from_attrs(&attrs) {
+        Ok(options) => options.ascii_case_insensitive,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut parse_arms = Vec::new();
+    for variant in &variants {
+        let options = match ParseOptions::from_attrs(&variant.attrs) {
+            Ok(options) => options,
+            Err(err) => return err.to_compile_error().into(),
+        };
+
+        let ident = &variant.ident;
+        let aliases = options.aliases;
+        let insensitive = case_insensitive || options.ascii_case_insensitive;
+        parse_arms.push(quote! {
+            if matches(&Self::#ident.to_string(), #insensitive)
+                #(|| matches(#aliases, #insensitive))*
+            {
+                return Ok(Self::#ident);
+            }
+        });
+    }
+
+    let rename_all = config.rename_all;
+    let variant_names = variants.iter().map(|variant| &variant.ident);
+    let error_name = format_ident!("{}ParseError", enum_name);
+    let error_doc = format!("Returned when a string matches no variant of [`{enum_name}`]");
+
+    let common_traits = match config.serde {
+        true => quote! { foxtive_macros::impl_enum_common_traits!(#enum_name); },
+        false => quote! { foxtive_macros::impl_enum_common_traits!(#enum_name, serde = false); },
+    };
+
     let expanded = quote! {
-        #[derive(strum_macros::EnumString, strum_macros::Display, Copy, Clone, Eq, PartialEq)]
-        #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+        #[derive(strum_macros::Display, Copy, Clone, Eq, PartialEq)]
+        #[strum(serialize_all = #rename_all)]
+        #(#attrs)*
         pub enum #enum_name {
             #variants
         }
 
-        foxtive_macros::impl_enum_common_traits!(#enum_name);
+        impl #enum_name {
+            /// Every variant, in declaration order
+            pub const fn variants() -> &'static [Self] {
+                &[#(Self::#variant_names),*]
+            }
+
+            /// Iterates over every variant, in declaration order
+            pub fn iter() -> impl Iterator<Item = Self> {
+                Self::variants().iter().copied()
+            }
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #error_name {
+            pub value: String,
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let expected: Vec<String> = #enum_name::iter().map(|v| v.to_string()).collect();
+                write!(
+                    f,
+                    "invalid {} '{}', expected one of: {}",
+                    stringify!(#enum_name),
+                    self.value,
+                    expected.join(", ")
+                )
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        impl std::str::FromStr for #enum_name {
+            type Err = #error_name;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                let matches = |name: &str, insensitive: bool| match insensitive {
+                    true => name.eq_ignore_ascii_case(value),
+                    false => name == value,
+                };
+
+                #(#parse_arms)*
+
+                Err(#error_name {
+                    value: value.to_string(),
+                })
+            }
+        }
+
+        impl TryFrom<&str> for #enum_name {
+            type Error = #error_name;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                value.parse()
+            }
+        }
+
+        #common_traits
     };
 
     TokenStream::from(expanded)
@@ -1,21 +1,63 @@
 use proc_macro::TokenStream;
 
+mod cache_key;
 mod enum_common;
 #[cfg(feature = "database")]
 mod enum_diesel;
 mod enum_diesel_generate;
 mod enum_generate;
 
+/// Generate an enum with `Display`, `FromStr`, `TryFrom<&str>`, `Serialize` and `Deserialize`,
+/// plus `variants()` and `iter()`.
+///
+/// Variants are represented in `SCREAMING_SNAKE_CASE` unless configured otherwise:
+///
+/// ```ignore
+/// generate_enum!(
+///     #[enum_config(rename_all = "kebab-case", serde = true)]
+///     OrderStatus { Pending, InTransit }
+/// );
+///
+/// assert_eq!(OrderStatus::InTransit.to_string(), "in-transit");
+/// assert!(OrderStatus::try_from("lost").is_err()); // OrderStatusParseError
+/// ```
 #[proc_macro]
 pub fn generate_enum(input: TokenStream) -> TokenStream {
     enum_generate::generate_enum(input)
 }
 
+/// Implement `Debug`, `Serialize` and `Deserialize` from `Display` and `FromStr`,
+/// `impl_enum_common_traits!(Name, serde = false)` only implements `Debug`
 #[proc_macro]
 pub fn impl_enum_common_traits(input: TokenStream) -> TokenStream {
     enum_common::impl_enum_common_traits(input)
 }
 
+/// Derive `foxtive::cache::CacheKey`, building a stable, versioned key from the struct fields
+///
+/// ```ignore
+/// #[derive(CacheKey)]
+/// #[cache_key(prefix = "user", version = 2)]
+/// struct UserKey {
+///     #[cache_key]
+///     id: u64,
+///     #[cache_key]
+///     locale: String,
+///     requested_by: String,
+/// }
+///
+/// // "user:v2:42:en"
+/// let key = UserKey { id: 42, locale: "en".into(), requested_by: "admin".into() }.cache_key();
+/// ```
+///
+/// Only the fields marked `#[cache_key]` are used, all of them when none is marked. The prefix
+/// defaults to the lowercased struct name and the version to 1. A `:` or `\` in a field value
+/// is escaped with `\`, so fields containing the separator can't collide.
+#[proc_macro_derive(CacheKey, attributes(cache_key))]
+pub fn derive_cache_key(input: TokenStream) -> TokenStream {
+    cache_key::derive_cache_key(input)
+}
+
 #[proc_macro]
 pub fn impl_enum_display_trait(input: TokenStream) -> TokenStream {
     enum_common::impl_enum_display_trait(input)
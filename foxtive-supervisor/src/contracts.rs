@@ -1,5 +1,6 @@
 use crate::enums::{
-    BackoffStrategy, CircuitBreakerConfig, HealthStatus, RestartPolicy, SupervisorEvent, TaskState,
+    BackoffStrategy, CircuitBreakerConfig, HealthStatus, ResourceLimits, RestartPolicy,
+    SetupRetryPolicy, SupervisorEvent, TaskState,
 };
 use std::time::Duration;
 
@@ -50,12 +51,37 @@ pub trait SupervisedTask: Send + Sync {
         None
     }
 
+    /// Named concurrency group the task belongs to
+    /// The group's limit is declared on the supervisor with `with_concurrency_group()`
+    fn concurrency_group(&self) -> Option<&'static str> {
+        None
+    }
+
     /// Configuration for the task's circuit breaker
     /// If `None`, the circuit breaker is disabled for this task
     fn circuit_breaker(&self) -> Option<CircuitBreakerConfig> {
         None
     }
 
+    /// Retry policy for a failing `setup()`
+    /// If `None`, the first setup failure ends the task with `SetupFailed`
+    fn setup_retry_policy(&self) -> Option<SetupRetryPolicy> {
+        None
+    }
+
+    /// CPU and process memory limits watched while the task runs
+    /// If `None`, the task's resource usage isn't sampled
+    fn resource_limits(&self) -> Option<ResourceLimits> {
+        None
+    }
+
+    /// Longest the task may go without ticking its [`Heartbeat`](crate::Heartbeat)
+    /// If exceeded, the attempt is considered stalled, aborted and handled as a failure.
+    /// If `None`, the task isn't checked for stalls.
+    fn heartbeat_timeout(&self) -> Option<Duration> {
+        None
+    }
+
     /// Maximum time to wait for the task to finish its `run()` and `on_shutdown()`
     /// before forced termination during supervisor shutdown.
     fn shutdown_timeout(&self) -> Duration {
@@ -68,6 +94,22 @@ pub trait SupervisedTask: Send + Sync {
         None
     }
 
+    /// Optional delay before the task starts, setup included
+    ///
+    /// Unlike `initial_delay()`, the task's setup and thus its dependents wait too,
+    /// which makes it suitable for ordering warm-ups.
+    fn start_delay(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Optional time at which the task starts, setup included
+    ///
+    /// Takes precedence over `start_delay()`. A time in the past starts the task immediately.
+    #[cfg(feature = "chrono")]
+    fn start_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        None
+    }
+
     /// Optional initial delay before the first execution
     ///
     /// This is useful for staggering task startup to prevent thundering herd problems
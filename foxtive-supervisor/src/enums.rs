@@ -17,6 +17,17 @@ pub enum SupervisionStatus {
     DependencyFailed,
     /// Task was stopped because the circuit breaker opened
     CircuitBreakerOpened,
+    /// The supervision loop itself was aborted before reporting a status
+    Aborted,
+    /// The supervision loop itself panicked, e.g. in a lifecycle hook
+    SupervisorPanicked,
+}
+
+impl SupervisionStatus {
+    /// Whether the task ended without completing or being stopped
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, Self::CompletedNormally | Self::ManuallyStopped)
+    }
 }
 
 /// Defines when and how often a task should restart after failure
@@ -82,6 +93,14 @@ pub enum SupervisorEvent {
         name: String,
         error: String,
     },
+    /// A task setup failed and will be retried after `delay`
+    TaskSetupRetrying {
+        id: String,
+        name: String,
+        attempt: usize,
+        error: String,
+        delay: Duration,
+    },
     /// A task's execution started (or restarted)
     TaskStarted {
         id: String,
@@ -108,6 +127,13 @@ pub enum SupervisorEvent {
         attempt: usize,
         panic_info: String,
     },
+    /// A task didn't tick its heartbeat within its timeout and was aborted
+    TaskStalled {
+        id: String,
+        name: String,
+        attempt: usize,
+        timeout: Duration,
+    },
     /// A task is entering backoff before restart
     TaskBackoff {
         id: String,
@@ -145,6 +171,17 @@ pub enum SupervisorEvent {
     CircuitBreakerReset { id: String, name: String },
     /// A task's circuit breaker entered half-open state
     CircuitBreakerHalfOpen { id: String, name: String },
+    /// A task's resource usage went above one of its limits
+    ResourceThresholdExceeded {
+        id: String,
+        name: String,
+        /// `"cpu"` or `"memory"`
+        resource: String,
+        usage: f64,
+        limit: f64,
+    },
+    /// A task's resource usage is back within its limits
+    ResourceUsageRecovered { id: String, name: String },
     /// Supervisor is shutting down
     SupervisorShutdownStarted,
     /// Supervisor completed shutdown
@@ -194,6 +231,66 @@ impl Default for CircuitBreakerConfig {
     }
 }
 
+/// How a task's failing `setup()` is retried before giving up with `SetupFailed`
+///
+/// Useful when setup depends on services that may not be ready yet, e.g. a broker
+/// still booting alongside the application.
+#[derive(Debug, Clone)]
+pub struct SetupRetryPolicy {
+    /// Delay before each retry, `attempt` being the number of failed setups so far
+    pub backoff: BackoffStrategy,
+    /// How long setup may keep retrying, measured from the first attempt
+    pub max_duration: Duration,
+}
+
+impl SetupRetryPolicy {
+    pub fn new(backoff: BackoffStrategy, max_duration: Duration) -> Self {
+        Self {
+            backoff,
+            max_duration,
+        }
+    }
+}
+
+impl Default for SetupRetryPolicy {
+    fn default() -> Self {
+        Self {
+            backoff: BackoffStrategy::exponential_custom(
+                Duration::from_secs(1),
+                Duration::from_secs(10),
+            ),
+            max_duration: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Resource expectations of a task, checked by the supervisor's watchdog
+///
+/// When a sample exceeds a limit the task is reported as `Degraded` and a
+/// [`SupervisorEvent::ResourceThresholdExceeded`] is emitted; the task keeps running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Share of one core the task's `run()` may keep busy, e.g. `0.5`.
+    /// Measured as the time spent polling the task's future.
+    pub max_cpu: Option<f64>,
+    /// Resident memory of the whole process, not of the task: tasks share the
+    /// process heap, so every task declaring this limit is degraded together
+    /// when the process goes above it. Only sampled on Linux.
+    pub max_process_memory_bytes: Option<u64>,
+    /// How often usage is sampled
+    pub sample_interval: Duration,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_cpu: None,
+            max_process_memory_bytes: None,
+            sample_interval: Duration::from_secs(5),
+        }
+    }
+}
+
 /// Runtime configuration for a supervised task that can be hot-reloaded
 #[derive(Debug, Clone)]
 pub struct TaskConfig {
@@ -0,0 +1,128 @@
+//! Liveness signal for long-running tasks.
+//!
+//! A task declaring [`SupervisedTask::heartbeat_timeout`](crate::SupervisedTask::heartbeat_timeout)
+//! must tick its [`Heartbeat`] at least once per timeout. When it goes silent for longer,
+//! the supervisor considers the attempt stalled, aborts it and applies the restart policy.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static CURRENT: Heartbeat;
+}
+
+/// Handle a running task ticks to signal it's still making progress
+///
+/// # Example
+///
+/// ```rust
+/// use foxtive_supervisor::{Heartbeat, SupervisedTask};
+/// use std::time::Duration;
+///
+/// struct Consumer;
+///
+/// #[async_trait::async_trait]
+/// impl SupervisedTask for Consumer {
+///     fn id(&self) -> &'static str { "consumer" }
+///
+///     fn heartbeat_timeout(&self) -> Option<Duration> {
+///         Some(Duration::from_secs(30))
+///     }
+///
+///     async fn run(&self) -> anyhow::Result<()> {
+///         let heartbeat = Heartbeat::current().expect("run by the supervisor");
+///         loop {
+///             // receive and process a message...
+///             heartbeat.tick();
+///             # return Ok(());
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    origin: Instant,
+    /// Time of the last tick, as nanoseconds since `origin`
+    last_tick: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    pub(crate) fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            last_tick: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Heartbeat of the attempt being run, `None` outside a supervised `run()`.
+    ///
+    /// The handle can be cloned into tasks spawned by `run()`.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Signals the task is alive
+    pub fn tick(&self) {
+        let nanos = u64::try_from(self.origin.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.last_tick.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    /// Time since the last tick, or since the attempt started if it never ticked
+    pub fn elapsed(&self) -> Duration {
+        let last_tick = Duration::from_nanos(self.last_tick.load(Ordering::Relaxed));
+        self.origin.elapsed().saturating_sub(last_tick)
+    }
+
+    /// Runs `future` with this heartbeat as [`Heartbeat::current`]
+    pub(crate) fn scope<F: Future>(self, future: F) -> impl Future<Output = F::Output> {
+        CURRENT.scope(self, future)
+    }
+
+    /// Resolves once the heartbeat has been silent for `timeout`
+    pub(crate) async fn stalled(&self, timeout: Duration) {
+        loop {
+            let silent = self.elapsed();
+            if silent >= timeout {
+                return;
+            }
+            tokio::time::sleep(timeout - silent).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_current_is_scoped() {
+        assert!(Heartbeat::current().is_none());
+
+        let heartbeat = Heartbeat::new();
+        let inside = heartbeat
+            .clone()
+            .scope(async { Heartbeat::current().is_some() })
+            .await;
+        assert!(inside);
+    }
+
+    #[tokio::test]
+    async fn test_tick_resets_elapsed() {
+        let heartbeat = Heartbeat::new();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(heartbeat.elapsed() >= Duration::from_millis(20));
+
+        heartbeat.tick();
+        assert!(heartbeat.elapsed() < Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_stalled_waits_for_silence() {
+        let heartbeat = Heartbeat::new();
+        let started = Instant::now();
+        heartbeat.stalled(Duration::from_millis(30)).await;
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+}
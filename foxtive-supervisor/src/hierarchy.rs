@@ -2,12 +2,18 @@
 //!
 //! This module provides the ability to create parent-child relationships between supervisors,
 //! enabling hierarchical task management and organized supervision trees.
+//!
+//! [`SupervisorHierarchy`] starts and stops independent supervisors together, while
+//! [`Supervisor::add_supervisor`] runs a supervisor as a task of its parent, so a failing
+//! branch is restarted as a whole under the parent's restart policy.
 
 use crate::Supervisor;
+use crate::contracts::SupervisedTask;
+use crate::enums::ControlMessage;
 use crate::runtime::TaskRuntime;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::info;
+use tokio::sync::{Mutex, broadcast};
+use tracing::{info, warn};
 
 /// Builder for constructing supervisor hierarchies
 pub struct SupervisorHierarchy {
@@ -178,6 +184,78 @@ impl HierarchyRuntime {
     }
 }
 
+/// A supervisor supervised as a task of its parent
+pub(crate) struct ChildSupervisor {
+    id: &'static str,
+    /// Registered tasks and settings, instantiated afresh on every run
+    template: Mutex<TaskRuntime>,
+}
+
+impl ChildSupervisor {
+    pub(crate) fn new(id: &'static str, supervisor: Supervisor) -> Self {
+        Self {
+            id,
+            template: Mutex::new(supervisor.runtime()),
+        }
+    }
+}
+
+/// Stops the children of a branch when its run ends or is aborted
+struct StopChildren(Vec<broadcast::Sender<ControlMessage>>);
+
+impl Drop for StopChildren {
+    fn drop(&mut self) {
+        for control_tx in &self.0 {
+            let _ = control_tx.send(ControlMessage::Stop);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SupervisedTask for ChildSupervisor {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    /// Runs until all children finish, failing as soon as one of them fails.
+    async fn run(&self) -> anyhow::Result<()> {
+        let mut runtime = self.template.lock().await.instantiate();
+        runtime.start_all().await?;
+
+        let _stop_children = StopChildren(runtime.control_senders());
+        let mut results = Vec::new();
+
+        while runtime.task_count() > 0 {
+            let result = runtime.wait_any().await;
+            if result.final_status.is_failure() {
+                warn!(supervisor = self.id, child = %result.task_id, status = ?result.final_status, "Child failed, stopping branch");
+                runtime.shutdown().await;
+
+                let completed: Vec<_> = results
+                    .iter()
+                    .map(|result: &crate::SupervisionResult| result.task_id.as_str())
+                    .collect();
+                anyhow::bail!(
+                    "Child '{}' of supervisor '{}' ended with {:?} after {} attempts (completed: {:?})",
+                    result.task_id,
+                    self.id,
+                    result.final_status,
+                    result.total_attempts,
+                    completed
+                );
+            }
+            results.push(result);
+        }
+
+        info!(
+            supervisor = self.id,
+            children = results.len(),
+            "All children completed"
+        );
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
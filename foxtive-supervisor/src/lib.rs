@@ -14,6 +14,7 @@
 pub mod contracts;
 pub mod enums;
 pub mod error;
+pub mod heartbeat;
 pub mod hierarchy;
 pub mod persistence;
 pub mod runtime;
@@ -24,10 +25,12 @@ pub mod distributed;
 
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub use crate::contracts::{SupervisedTask, SupervisorEventListener};
 pub use crate::enums::TaskConfig;
 pub use crate::error::{SupervisorError, ValidationError};
+pub use crate::heartbeat::Heartbeat;
 pub use crate::persistence::TaskStateStore;
 pub use crate::runtime::{SupervisionResult, TaskRuntime, spawn_supervised, spawn_supervised_many};
 
@@ -78,6 +81,27 @@ impl Supervisor {
         self
     }
 
+    /// Add a task that starts `delay` after the supervisor, setup included.
+    ///
+    /// Overrides the task's own `start_delay()`, e.g. to start workers once caches warmed up.
+    pub fn add_delayed<T: SupervisedTask + 'static>(mut self, task: T, delay: Duration) -> Self {
+        self.runtime.register_delayed(task, delay);
+        self
+    }
+
+    /// Add a task that starts at `at`, setup included, e.g. a maintenance-window worker.
+    ///
+    /// Overrides the task's own `start_at()`. A time in the past starts the task immediately.
+    #[cfg(feature = "chrono")]
+    pub fn add_at<T: SupervisedTask + 'static>(
+        mut self,
+        task: T,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        self.runtime.register_at(task, at);
+        self
+    }
+
     /// Add multiple tasks of the same type.
     pub fn add_many<T: SupervisedTask + 'static>(mut self, tasks: Vec<T>) -> Self {
         self.runtime.register_many(tasks);
@@ -100,6 +124,44 @@ impl Supervisor {
         self
     }
 
+    /// Add another supervisor as a task, creating a supervision tree.
+    ///
+    /// The child's tasks run as a branch identified by `id`. The branch completes once
+    /// all of its children completed and fails as soon as one of them fails, e.g. by
+    /// reaching its max attempts. Restarting the branch, by its restart policy or with
+    /// `restart_task(id)`, restarts all of its children.
+    ///
+    /// The child's prerequisites only run on the branch's first start.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use foxtive_supervisor::{Supervisor, SupervisedTask};
+    ///
+    /// struct Consumer;
+    /// #[async_trait::async_trait]
+    /// impl SupervisedTask for Consumer {
+    ///     fn id(&self) -> &'static str { "consumer" }
+    ///     async fn run(&self) -> anyhow::Result<()> { Ok(()) }
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let messaging = Supervisor::new().add(Consumer);
+    ///
+    ///     Supervisor::new()
+    ///         .add_supervisor("messaging", messaging)
+    ///         .start_and_wait_any()
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn add_supervisor(mut self, id: &'static str, supervisor: Supervisor) -> Self {
+        self.runtime
+            .register(crate::hierarchy::ChildSupervisor::new(id, supervisor));
+        self
+    }
+
     /// Set a global concurrency limit for the supervisor.
     ///
     /// This limits how many tasks can be in their `run()` loop simultaneously.
@@ -109,6 +171,18 @@ impl Supervisor {
         self
     }
 
+    /// Declare a named concurrency group.
+    ///
+    /// At most `limit` of the tasks whose `concurrency_group()` returns `name` run at
+    /// the same time, the others wait for a slot. Useful when many workers share a
+    /// dependency that can't take them all at once, e.g. a database at startup.
+    ///
+    /// Starting fails if a task names a group that wasn't declared.
+    pub fn with_concurrency_group(mut self, name: &'static str, limit: usize) -> Self {
+        self.runtime.with_concurrency_group(name, limit);
+        self
+    }
+
     /// Register an event listener to observe lifecycle events.
     ///
     /// Event listeners receive notifications for task starts, failures, restarts, etc.
@@ -4,9 +4,13 @@
 //! for managing and orchestrating supervised tasks. It handles task registration,
 //! dependency resolution, prerequisite execution, and the spawning of supervision loops.
 
+use super::spawn::spawn_named;
 use super::supervision::{SupervisionParams, supervise};
-use super::types::{DepSetupReceivers, PrerequisiteFuture, SupervisionResult, TaskEntry};
+use super::types::{
+    DepSetupReceivers, PrerequisiteFuture, StartSchedule, SupervisionResult, TaskEntry,
+};
 use super::validation::validate_dependencies;
+use super::watchdog::ResourceWatch;
 use crate::contracts::{SupervisedTask, SupervisorEventListener};
 use crate::enums::{ControlMessage, HealthStatus, SupervisorEvent, TaskConfig};
 use crate::error::SupervisorError;
@@ -14,7 +18,9 @@ use crate::persistence::TaskStateStore;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::Poll;
 use std::time::Duration;
 use tokio::sync::{RwLock, Semaphore, broadcast, watch};
 use tokio::task::JoinHandle;
@@ -52,8 +58,14 @@ pub struct TaskRuntime {
     pub(super) global_concurrency_limit: Option<Arc<Semaphore>>,
     /// Per-task concurrency limits
     pub(crate) task_concurrency_limits: HashMap<&'static str, Arc<Semaphore>>,
+    /// Concurrency limits shared by the tasks of a named group
+    pub(super) concurrency_groups: HashMap<&'static str, Arc<Semaphore>>,
     /// Hot-reloadable task configurations
     pub(super) task_configs: HashMap<&'static str, Arc<RwLock<TaskConfig>>>,
+    /// Resource usage of tasks declaring limits
+    pub(super) task_resources: HashMap<&'static str, Arc<ResourceWatch>>,
+    /// Scheduled starts of tasks not starting right away
+    pub(super) task_starts: HashMap<&'static str, StartSchedule>,
     #[cfg(feature = "cron")]
     #[allow(dead_code)]
     pub(super) cron: Option<Arc<tokio::sync::Mutex<Cron>>>,
@@ -84,7 +96,10 @@ impl TaskRuntime {
             state_store: None,
             global_concurrency_limit: None,
             task_concurrency_limits: HashMap::new(),
+            concurrency_groups: HashMap::new(),
             task_configs: HashMap::new(),
+            task_resources: HashMap::new(),
+            task_starts: HashMap::new(),
             #[cfg(feature = "cron")]
             cron: None,
         };
@@ -102,6 +117,14 @@ impl TaskRuntime {
         self
     }
 
+    /// Declares a named concurrency group, allowing at most `limit` of the tasks
+    /// whose `concurrency_group()` returns `name` to run at the same time.
+    pub fn with_concurrency_group(&mut self, name: &'static str, limit: usize) -> &mut Self {
+        self.concurrency_groups
+            .insert(name, Arc::new(Semaphore::new(limit)));
+        self
+    }
+
     // TASK REGISTRATION
 
     /// Registers a task for supervision.
@@ -119,6 +142,15 @@ impl TaskRuntime {
                 .insert(id, Arc::new(Semaphore::new(limit)));
         }
 
+        if let Some(limits) = task.resource_limits() {
+            self.task_resources
+                .insert(id, Arc::new(ResourceWatch::new(limits)));
+        }
+
+        if let Some(start) = StartSchedule::of(&task) {
+            self.task_starts.insert(id, start);
+        }
+
         // Initialize hot-reloadable config from task's current configuration
         let config = TaskConfig::from_task(&task);
         self.task_configs.insert(id, Arc::new(RwLock::new(config)));
@@ -157,6 +189,15 @@ impl TaskRuntime {
                 .insert(id, Arc::new(Semaphore::new(limit)));
         }
 
+        if let Some(limits) = task.resource_limits() {
+            self.task_resources
+                .insert(id, Arc::new(ResourceWatch::new(limits)));
+        }
+
+        if let Some(start) = StartSchedule::of(task.as_ref()) {
+            self.task_starts.insert(id, start);
+        }
+
         // Initialize hot-reloadable config from task's current configuration
         let config = TaskConfig::from_task(task.as_ref());
         self.task_configs.insert(id, Arc::new(RwLock::new(config)));
@@ -187,6 +228,15 @@ impl TaskRuntime {
                 .insert(id, Arc::new(Semaphore::new(limit)));
         }
 
+        if let Some(limits) = task.resource_limits() {
+            self.task_resources
+                .insert(id, Arc::new(ResourceWatch::new(limits)));
+        }
+
+        if let Some(start) = StartSchedule::of(task.as_ref()) {
+            self.task_starts.insert(id, start);
+        }
+
         // Initialize hot-reloadable config from task's current configuration
         let config = TaskConfig::from_task(task.as_ref());
         self.task_configs.insert(id, Arc::new(RwLock::new(config)));
@@ -204,6 +254,35 @@ impl TaskRuntime {
         self
     }
 
+    /// Registers a task starting `delay` after the supervisor starts it, setup included.
+    ///
+    /// Overrides the task's own `start_delay()` and `start_at()`.
+    pub fn register_delayed<T: SupervisedTask + 'static>(
+        &mut self,
+        task: T,
+        delay: Duration,
+    ) -> &mut Self {
+        let id = task.id();
+        self.register(task);
+        self.task_starts.insert(id, StartSchedule::After(delay));
+        self
+    }
+
+    /// Registers a task starting at `at`, setup included.
+    ///
+    /// Overrides the task's own `start_delay()` and `start_at()`.
+    #[cfg(feature = "chrono")]
+    pub fn register_at<T: SupervisedTask + 'static>(
+        &mut self,
+        task: T,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> &mut Self {
+        let id = task.id();
+        self.register(task);
+        self.task_starts.insert(id, StartSchedule::At(at));
+        self
+    }
+
     // DYNAMIC TASK MANAGEMENT
 
     /// Registers and starts a new task at runtime.
@@ -230,6 +309,7 @@ impl TaskRuntime {
         // Validate dependency graph including cycle detection for the newly added task
         let tasks_vec: Vec<&TaskEntry> = self.tasks.values().collect();
         validate_dependencies(&tasks_vec)?;
+        self.validate_concurrency_groups()?;
 
         let entry = self.tasks.get(id).unwrap();
 
@@ -249,7 +329,10 @@ impl TaskRuntime {
         }
 
         let task_limit = self.task_concurrency_limits.get(id).cloned();
+        let group_limit = self.group_semaphore(entry.task.as_ref());
         let task_config = self.task_configs.get(id).cloned();
+        let resource_watch = self.task_resources.get(id).cloned();
+        let start = self.task_starts.get(id).copied();
 
         let params = SupervisionParams {
             task: entry.task.clone(),
@@ -259,8 +342,11 @@ impl TaskRuntime {
             dep_receivers,
             state_store: self.state_store.clone(),
             global_semaphore: self.global_concurrency_limit.clone(),
+            group_semaphore: group_limit,
             task_semaphore: task_limit,
             task_config,
+            resource_watch,
+            start,
         };
 
         let handle = supervise(params);
@@ -631,7 +717,7 @@ impl TaskRuntime {
         for entry in self.tasks.values() {
             if entry.task.group_id() == Some(group_id) {
                 task_count += 1;
-                let health = entry.task.health_check().await;
+                let health = self.task_health(entry).await;
                 match health {
                     HealthStatus::Healthy => has_healthy = true,
                     HealthStatus::Degraded { .. } => has_degraded = true,
@@ -676,7 +762,7 @@ impl TaskRuntime {
                 summaries.push(TaskSummary {
                     id: task_id.to_string(),
                     name: entry.task.name(),
-                    health: entry.task.health_check().await,
+                    health: self.task_health(entry).await,
                 });
             }
         }
@@ -704,7 +790,10 @@ impl TaskRuntime {
         }
 
         let task_limit = self.task_concurrency_limits.get(task_id).cloned();
+        let group_limit = self.group_semaphore(entry.task.as_ref());
         let task_config = self.task_configs.get(task_id).cloned();
+        let resource_watch = self.task_resources.get(task_id).cloned();
+        let start = self.task_starts.get(task_id).copied();
 
         let params = SupervisionParams {
             task: entry.task.clone(),
@@ -714,8 +803,11 @@ impl TaskRuntime {
             dep_receivers,
             state_store: self.state_store.clone(),
             global_semaphore: self.global_concurrency_limit.clone(),
+            group_semaphore: group_limit,
             task_semaphore: task_limit,
             task_config,
+            resource_watch,
+            start,
         };
 
         let handle = supervise(params);
@@ -746,6 +838,8 @@ impl TaskRuntime {
             let _ = entry.control_tx.send(ControlMessage::Stop);
             let name = entry.task.name();
             self.setup_signals.remove(id);
+            self.task_resources.remove(id);
+            self.task_starts.remove(id);
             let _ = self.event_tx.send(SupervisorEvent::TaskRemoved {
                 id: id.to_string(),
                 name,
@@ -774,7 +868,7 @@ impl TaskRuntime {
     /// Returns `SupervisorError::UnknownTask` if no task with the given ID is found.
     pub async fn get_task_info(&self, id: &str) -> Result<TaskSummary, SupervisorError> {
         if let Some(entry) = self.tasks.get(id) {
-            let health = entry.task.health_check().await;
+            let health = self.task_health(entry).await;
             Ok(TaskSummary {
                 id: id.to_string(),
                 name: entry.task.name(),
@@ -792,7 +886,7 @@ impl TaskRuntime {
             summaries.push(TaskSummary {
                 id: id.to_string(),
                 name: entry.task.name(),
-                health: entry.task.health_check().await,
+                health: self.task_health(entry).await,
             });
         }
         summaries
@@ -860,7 +954,7 @@ impl TaskRuntime {
         // Start event listener distribution
         let mut event_rx = self.event_tx.subscribe();
         let listeners = self.listeners.clone();
-        tokio::spawn(async move {
+        spawn_named("supervisor:events", async move {
             loop {
                 match event_rx.recv().await {
                     Ok(event) => {
@@ -915,6 +1009,7 @@ impl TaskRuntime {
         // --- Phase 2: validate dependency graph ---
         let tasks_vec: Vec<&TaskEntry> = self.tasks.values().collect();
         validate_dependencies(&tasks_vec)?;
+        self.validate_concurrency_groups()?;
 
         info!(
             "[Supervisor] Starting {} supervised tasks...",
@@ -950,7 +1045,10 @@ impl TaskRuntime {
                 .collect();
 
             let task_limit = self.task_concurrency_limits.get(id).cloned();
+            let group_limit = self.group_semaphore(entry.task.as_ref());
             let task_config = self.task_configs.get(id).cloned();
+            let resource_watch = self.task_resources.get(id).cloned();
+            let start = self.task_starts.get(id).copied();
 
             let params = SupervisionParams {
                 task: entry.task.clone(),
@@ -960,8 +1058,11 @@ impl TaskRuntime {
                 dep_receivers,
                 state_store: self.state_store.clone(),
                 global_semaphore: self.global_concurrency_limit.clone(),
+                group_semaphore: group_limit,
                 task_semaphore: task_limit,
                 task_config,
+                resource_watch,
+                start,
             };
 
             let handle = supervise(params);
@@ -980,6 +1081,10 @@ impl TaskRuntime {
         let (setup_tx, _) = watch::channel(None);
         let (control_tx, _) = broadcast::channel(10);
         let (event_tx, _) = broadcast::channel(1);
+        let resource_watch = task
+            .resource_limits()
+            .map(|limits| Arc::new(ResourceWatch::new(limits)));
+        let start = StartSchedule::of(&task);
         let params = SupervisionParams {
             task: Arc::new(task),
             setup_tx,
@@ -988,8 +1093,11 @@ impl TaskRuntime {
             dep_receivers: vec![],
             state_store: None,
             global_semaphore: None,
+            group_semaphore: None,
             task_semaphore: None,
             task_config: None,
+            resource_watch,
+            start,
         };
         supervise(params)
     }
@@ -999,6 +1107,8 @@ impl TaskRuntime {
     /// Waits for any one supervised task to terminate.
     ///
     /// Returns the `SupervisionResult` of the first task that finishes.
+    ///
+    /// Cancel safe: if the returned future is dropped before completing, no task is lost.
     pub async fn wait_any(&mut self) -> SupervisionResult {
         if self.handles.is_empty() {
             warn!("[Supervisor] No tasks to wait for");
@@ -1007,33 +1117,22 @@ impl TaskRuntime {
                 task_id: "none".to_string(),
                 total_attempts: 0,
                 final_status: crate::enums::SupervisionStatus::ManuallyStopped,
+                join_error: None,
             };
         }
 
-        // Extract all handles from the HashMap to avoid borrow issues
-        let mut handle_vec: Vec<(&'static str, JoinHandle<SupervisionResult>)> =
-            self.handles.drain().collect();
-
-        // Use select_all to wait for the first one to complete
-        use futures_util::future::select_all;
-
-        let mut futures = Vec::new();
-        for (id, handle) in handle_vec.iter_mut() {
-            let id_clone = *id;
-            futures.push(Box::pin(async move {
-                let result = handle.await;
-                (id_clone, result)
-            }));
-        }
-
-        let ((finished_id, result), _idx, _remaining) = select_all(futures).await;
-
-        // Put remaining handles back into the HashMap
-        for (i, (id, handle)) in handle_vec.into_iter().enumerate() {
-            if i != _idx {
-                self.handles.insert(id, handle);
+        // Poll the handles in place rather than draining them, so dropping this future
+        // (e.g. in a `select!`) keeps every task tracked for `shutdown()`
+        let (finished_id, result) = std::future::poll_fn(|cx| {
+            for (id, handle) in self.handles.iter_mut() {
+                if let Poll::Ready(result) = Pin::new(handle).poll(cx) {
+                    return Poll::Ready((*id, result));
+                }
             }
-        }
+            Poll::Pending
+        })
+        .await;
+        self.handles.remove(finished_id);
 
         match result {
             Ok(supervision_result) => {
@@ -1046,12 +1145,26 @@ impl TaskRuntime {
                 supervision_result
             }
             Err(join_err) => {
-                error!("[Supervisor] Task {} panicked: {:?}", finished_id, join_err);
+                let final_status = if join_err.is_panic() {
+                    crate::enums::SupervisionStatus::SupervisorPanicked
+                } else {
+                    crate::enums::SupervisionStatus::Aborted
+                };
+                error!(
+                    "[Supervisor] Supervision of task {} ended with {:?}: {}",
+                    finished_id, final_status, join_err
+                );
+
                 SupervisionResult {
-                    task_name: "unknown".to_string(),
+                    task_name: self
+                        .tasks
+                        .get(finished_id)
+                        .map(|entry| entry.task.name())
+                        .unwrap_or_else(|| finished_id.to_string()),
                     task_id: finished_id.to_string(),
                     total_attempts: 0,
-                    final_status: crate::enums::SupervisionStatus::ManuallyStopped,
+                    final_status,
+                    join_error: Some(join_err.to_string()),
                 }
             }
         }
@@ -1181,6 +1294,65 @@ impl TaskRuntime {
         order
     }
 
+    /// Semaphore of the concurrency group the task belongs to, if any
+    fn group_semaphore(&self, task: &dyn SupervisedTask) -> Option<Arc<Semaphore>> {
+        task.concurrency_group()
+            .and_then(|group| self.concurrency_groups.get(group).cloned())
+    }
+
+    /// Ensures every concurrency group used by a task was declared
+    fn validate_concurrency_groups(&self) -> Result<(), SupervisorError> {
+        for (id, entry) in &self.tasks {
+            if let Some(group) = entry.task.concurrency_group()
+                && !self.concurrency_groups.contains_key(group)
+            {
+                return Err(SupervisorError::config(
+                    *id,
+                    format!("Unknown concurrency group '{group}'"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The task's health check, degraded while it exceeds its resource limits
+    async fn task_health(&self, entry: &TaskEntry) -> HealthStatus {
+        let health = entry.task.health_check().await;
+        match self.task_resources.get(entry.task.id()) {
+            Some(watch) => watch.apply(health),
+            None => health,
+        }
+    }
+
+    /// Creates an unstarted runtime with the same tasks and settings, so a
+    /// supervision branch can be started again from scratch.
+    ///
+    /// Prerequisites are moved into it, as they can only run once.
+    pub(crate) fn instantiate(&mut self) -> Self {
+        let mut runtime = Self::new();
+        runtime.prerequisites = std::mem::take(&mut self.prerequisites);
+        runtime.listeners = self.listeners.clone();
+        runtime.state_store = self.state_store.clone();
+        runtime.global_concurrency_limit = self.global_concurrency_limit.clone();
+        runtime.concurrency_groups = self.concurrency_groups.clone();
+
+        for entry in self.tasks.values() {
+            runtime.register_arc(entry.task.clone());
+        }
+        runtime.task_starts = self.task_starts.clone();
+
+        runtime
+    }
+
+    /// Control channels of all registered tasks
+    pub(crate) fn control_senders(&self) -> Vec<broadcast::Sender<ControlMessage>> {
+        self.tasks
+            .values()
+            .map(|entry| entry.control_tx.clone())
+            .collect()
+    }
+
     /// Returns the number of tasks currently being supervised.
     pub fn task_count(&self) -> usize {
         self.handles.len()
@@ -13,9 +13,11 @@ pub use types::{PrerequisiteFuture, SupervisionResult, TaskEntry};
 pub(crate) mod circuit_breaker;
 mod core;
 mod helpers;
+mod spawn;
 mod supervision;
 mod types;
 mod validation;
+mod watchdog;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,29 @@
+//! Spawning of the supervisor's own tasks, named for tokio-console when possible
+
+use std::future::Future;
+use tokio::task::JoinHandle;
+
+/// Spawns `future` as a task named `name`.
+///
+/// Names are only attached with the `task-names` feature and `--cfg tokio_unstable`,
+/// which tokio requires for its task builder, plain `tokio::spawn` is used otherwise.
+#[track_caller]
+pub(crate) fn spawn_named<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(feature = "task-names", tokio_unstable))]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn(future)
+            .expect("spawning a task on the current runtime")
+    }
+
+    #[cfg(not(all(feature = "task-names", tokio_unstable)))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
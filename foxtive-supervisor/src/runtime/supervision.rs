@@ -1,14 +1,17 @@
 //! Core supervision logic and task lifecycle management
 
-use super::types::{DepSetupReceivers, SupervisionResult};
+use super::spawn::spawn_named;
+use super::types::{DepSetupReceivers, StartSchedule, SupervisionResult};
 use crate::contracts::SupervisedTask;
 use crate::enums::{
     ControlMessage, RestartPolicy, SupervisionStatus, SupervisorEvent, TaskConfig, TaskState,
 };
+use crate::heartbeat::Heartbeat;
 use crate::persistence::{PersistedTaskState, TaskStateStore};
 use crate::runtime::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::runtime::watchdog::{ResourceWatch, TrackedRun, spawn_watchdog};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::{RwLock, Semaphore, broadcast, watch};
 use tokio::task::JoinHandle;
 use tracing::{Instrument, error, info, info_span, warn};
@@ -22,9 +25,15 @@ pub struct SupervisionParams {
     pub dep_receivers: DepSetupReceivers,
     pub state_store: Option<Arc<dyn TaskStateStore>>,
     pub global_semaphore: Option<Arc<Semaphore>>,
+    /// Limit shared with the other tasks of the task's concurrency group
+    pub group_semaphore: Option<Arc<Semaphore>>,
     pub task_semaphore: Option<Arc<Semaphore>>,
     /// Hot-reloadable task configuration
     pub task_config: Option<Arc<RwLock<TaskConfig>>>,
+    /// Resource usage sampled by the watchdog, if the task declares limits
+    pub resource_watch: Option<Arc<ResourceWatch>>,
+    /// When the task starts, if not right away
+    pub start: Option<StartSchedule>,
 }
 
 /// Core supervision loop. Waits for dependency setup signals before running.
@@ -37,8 +46,11 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
         dep_receivers,
         state_store,
         global_semaphore,
+        group_semaphore,
         task_semaphore,
         task_config,
+        resource_watch,
+        start,
     } = params;
 
     let name = task.name();
@@ -50,7 +62,12 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
         task_name = %name
     );
 
-    tokio::spawn(async move {
+    spawn_named(&format!("supervise:{id}"), async move {
+        // Sampled for as long as the task is supervised, aborted on return
+        let _watchdog = resource_watch.clone().map(|watch| {
+            spawn_watchdog(watch, event_tx.clone(), id.to_string(), name.clone())
+        });
+
         // --- Restore state if store exists ---
         let mut attempt = 0usize;
         let mut failure_count = 0usize;
@@ -77,6 +94,39 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
             CircuitBreaker::new(config, event_tx.clone(), id.to_string(), name.clone())
         });
 
+        // Wait for the scheduled start, setup included
+        if let Some(start) = start {
+            let delay = start.remaining();
+            info!(delay_ms = delay.as_millis(), "Delaying task start");
+
+            let sleep = tokio::time::sleep(delay);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    msg = control_rx.recv() => match msg {
+                        Ok(ControlMessage::Stop) => {
+                            info!("Received Stop command before scheduled start");
+                            let _ = setup_tx.send(Some(Err("Task stopped before its scheduled start".to_string())));
+                            let _ = event_tx.send(SupervisorEvent::TaskStopped { id: id.to_string(), name: name.clone() });
+                            return SupervisionResult {
+                                task_name: name,
+                                task_id: id.to_string(),
+                                total_attempts: attempt,
+                                final_status: SupervisionStatus::ManuallyStopped,
+                                join_error: None,
+                            };
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {
+                            (&mut sleep).await;
+                            break;
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+
         // Wait for all dependencies to complete their setup
         for (dep_id, mut rx) in dep_receivers {
             match wait_for_dependency(dep_id, &mut rx, id).await {
@@ -89,27 +139,88 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                         task_id: id.to_string(),
                         total_attempts: attempt,
                         final_status: SupervisionStatus::DependencyFailed,
+                        join_error: None,
                     };
                 }
             }
         }
 
         // --- Setup phase ---
-        let setup_result = {
-            let setup_span = info_span!("task_setup");
-            let event_tx_clone = event_tx.clone();
-            let task_clone = task.clone();
-            let name_clone = name.clone();
-            async move {
-                info!("Running setup phase");
-                let _ = event_tx_clone.send(SupervisorEvent::TaskSetupStarted { id: id.to_string(), name: name_clone });
-                task_clone.setup().await
-            }.instrument(setup_span).await
-        };
-
-        if let Err(e) = setup_result {
+        let setup_retry_policy = task.setup_retry_policy();
+        let setup_started = Instant::now();
+        let mut setup_attempt = 0usize;
+
+        loop {
+            setup_attempt += 1;
+
+            let setup_result = {
+                let setup_span = info_span!("task_setup", attempt = setup_attempt);
+                let event_tx_clone = event_tx.clone();
+                let task_clone = task.clone();
+                let name_clone = name.clone();
+                async move {
+                    info!("Running setup phase");
+                    let _ = event_tx_clone.send(SupervisorEvent::TaskSetupStarted { id: id.to_string(), name: name_clone });
+                    task_clone.setup().await
+                }.instrument(setup_span).await
+            };
+
+            let Err(e) = setup_result else {
+                break;
+            };
+
             let msg = format!("{e:?}");
-            error!(error = %msg, "Setup failed");
+
+            // Retry while the next attempt still starts within the policy's duration
+            let retry_delay = setup_retry_policy.as_ref().and_then(|policy| {
+                let delay = policy.backoff.calculate_delay(setup_attempt);
+                (setup_started.elapsed() + delay < policy.max_duration).then_some(delay)
+            });
+
+            if let Some(delay) = retry_delay {
+                warn!(error = %msg, attempt = setup_attempt, delay_ms = delay.as_millis(), "Setup failed, retrying");
+                let _ = event_tx.send(SupervisorEvent::TaskSetupRetrying {
+                    id: id.to_string(),
+                    name: name.clone(),
+                    attempt: setup_attempt,
+                    error: msg,
+                    delay,
+                });
+
+                // Other control messages don't cut the backoff short
+                let sleep = tokio::time::sleep(delay);
+                tokio::pin!(sleep);
+                let stopped = loop {
+                    tokio::select! {
+                        _ = &mut sleep => break false,
+                        msg = control_rx.recv() => match msg {
+                            Ok(ControlMessage::Stop) => break true,
+                            Err(broadcast::error::RecvError::Closed) => {
+                                (&mut sleep).await;
+                                break false;
+                            }
+                            _ => {}
+                        },
+                    }
+                };
+
+                if stopped {
+                    info!("Received Stop command during setup retry");
+                    let _ = setup_tx.send(Some(Err("Task stopped during setup".to_string())));
+                    let _ = event_tx.send(SupervisorEvent::TaskStopped { id: id.to_string(), name: name.clone() });
+                    task.cleanup().await;
+                    return SupervisionResult {
+                        task_name: name,
+                        task_id: id.to_string(),
+                        total_attempts: attempt,
+                        final_status: SupervisionStatus::ManuallyStopped,
+                        join_error: None,
+                    };
+                }
+                continue;
+            }
+
+            error!(error = %msg, attempts = setup_attempt, "Setup failed");
             let _ = event_tx.send(SupervisorEvent::TaskSetupFailed { id: id.to_string(), name: name.clone(), error: msg.clone() });
             let _ = setup_tx.send(Some(Err(msg)));
             // cleanup() is called after every task termination (success, failure, or panic)
@@ -119,6 +230,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                 task_id: id.to_string(),
                 total_attempts: attempt,
                 final_status: SupervisionStatus::SetupFailed,
+                join_error: None,
             };
         }
 
@@ -163,6 +275,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                             task_id: id.to_string(),
                             total_attempts: attempt,
                             final_status: SupervisionStatus::ManuallyStopped,
+                            join_error: None,
                         };
                     }
                 }
@@ -240,6 +353,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                                     task_id: id.to_string(),
                                     total_attempts: attempt,
                                     final_status: SupervisionStatus::ManuallyStopped,
+                                    join_error: None,
                                 };
                             }
                             Ok(ControlMessage::ResetCircuitBreaker) => {
@@ -283,6 +397,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                         task_id: id.to_string(),
                         total_attempts: attempt,
                         final_status: SupervisionStatus::MaxAttemptsReached,
+                        join_error: None,
                     };
                 }
                 _ => {}
@@ -292,6 +407,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
 
             let run_span = info_span!(
                 "task_run",
+                task_id = id,
                 attempt = attempt,
                 correlation_id = %uuid::Uuid::new_v4()
             );
@@ -304,6 +420,14 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
             }
 
             // Concurrency Control: Acquire permits
+            // The group permit comes first, so tasks waiting on their group don't hold global slots
+            let _group_permit = if let Some(sem) = &group_semaphore {
+                info!("Acquiring concurrency group permit");
+                Some(sem.acquire().await.unwrap())
+            } else {
+                None
+            };
+
             let _global_permit = if let Some(sem) = &global_semaphore {
                 info!("Acquiring global concurrency permit");
                 Some(sem.acquire().await.unwrap())
@@ -320,15 +444,28 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
 
             // Spawn in a child task to catch panics
             let task_clone = task.clone();
-            let mut run_handle = tokio::spawn(
-                async move { task_clone.run().await }
-                    .instrument(run_span.clone())
+            let heartbeat = Heartbeat::new();
+            let heartbeat_timeout = task.heartbeat_timeout();
+            let mut run_handle = spawn_named(
+                &format!("run:{id}#{attempt}"),
+                TrackedRun::new(
+                    heartbeat.clone().scope(async move { task_clone.run().await }),
+                    resource_watch.clone(),
+                )
+                .instrument(run_span.clone())
             );
 
-            // Wait for task completion or control messages
+            // Wait for task completion, a stall or control messages
             let result = loop {
                 tokio::select! {
                     res = &mut run_handle => break Some(res),
+                    _ = wait_for_stall(&heartbeat, heartbeat_timeout) => {
+                        let timeout = heartbeat_timeout.unwrap_or_default();
+                        warn!(timeout_ms = timeout.as_millis(), "Task stalled, no heartbeat within timeout - aborting attempt");
+                        run_handle.abort();
+                        let _ = event_tx.send(SupervisorEvent::TaskStalled { id: id.to_string(), name: name.clone(), attempt, timeout });
+                        break Some(Ok(Err(anyhow::anyhow!("Task stalled: no heartbeat within {timeout:?}"))));
+                    }
                     msg = control_rx.recv() => {
                         match handle_control_message_during_execution(
                             msg,
@@ -383,6 +520,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                             task_id: id.to_string(),
                             total_attempts: attempt,
                             final_status: SupervisionStatus::RestartPrevented,
+                            join_error: None,
                         };
                     }
                 }
@@ -432,6 +570,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                                                     task_id: id.to_string(),
                                                     total_attempts: attempt,
                                                     final_status: SupervisionStatus::ManuallyStopped,
+                                                    join_error: None,
                                                 };
                                             }
                                             Ok(ControlMessage::Pause) => {
@@ -480,6 +619,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                             task_id: id.to_string(),
                             total_attempts: attempt,
                             final_status: SupervisionStatus::ManuallyStopped,
+                            join_error: None,
                         };
                     }
                     ControlMessage::Pause => {
@@ -541,6 +681,7 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
                             task_id: id.to_string(),
                             total_attempts: attempt,
                             final_status: SupervisionStatus::ManuallyStopped,
+                            join_error: None,
                         };
                     }
                     ControlMessage::Restart => {
@@ -559,10 +700,19 @@ pub fn supervise(params: SupervisionParams) -> JoinHandle<SupervisionResult> {
             task_id: id.to_string(),
             total_attempts: attempt,
             final_status: SupervisionStatus::ManuallyStopped,
+            join_error: None,
         }
     }.instrument(supervision_span))
 }
 
+/// Resolves once the heartbeat has been silent for the timeout, never if there's none
+async fn wait_for_stall(heartbeat: &Heartbeat, timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => heartbeat.stalled(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Waits for a dependency to signal completion or failure
 async fn wait_for_dependency(
     dep_id: &'static str,
@@ -616,6 +766,7 @@ async fn process_control_messages(
                     task_id: task_id.to_string(),
                     total_attempts: attempt,
                     final_status: SupervisionStatus::ManuallyStopped,
+                    join_error: None,
                 });
             }
             ControlMessage::Pause => {
@@ -699,6 +850,7 @@ async fn handle_task_result(
                 task_id: task_id.to_string(),
                 total_attempts: attempt,
                 final_status: SupervisionStatus::CompletedNormally,
+                join_error: None,
             })
         }
 
@@ -806,6 +958,7 @@ async fn handle_control_message_during_execution(
                 task_id: task_id.to_string(),
                 total_attempts: attempt,
                 final_status: SupervisionStatus::ManuallyStopped,
+                join_error: None,
             })
         }
         Ok(ControlMessage::Restart) => {
@@ -5,6 +5,7 @@ use crate::enums::{ControlMessage, SupervisionStatus};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{broadcast, watch};
 
 /// A boxed future that resolves to `anyhow::Result<()>`, used as a prerequisite
@@ -22,6 +23,9 @@ pub struct SupervisionResult {
     pub task_id: String,
     pub total_attempts: usize,
     pub final_status: SupervisionStatus,
+    /// Why the supervision loop itself ended abnormally, with
+    /// [`SupervisionStatus::Aborted`] or [`SupervisionStatus::SupervisorPanicked`]
+    pub join_error: Option<String>,
 }
 
 /// Internal handle combining a task with its communication channels
@@ -34,3 +38,32 @@ pub struct TaskEntry {
     /// Channel for sending control messages to the supervisor loop for this task
     pub control_tx: broadcast::Sender<ControlMessage>,
 }
+
+/// When a task starts, relative to its supervision starting
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum StartSchedule {
+    After(Duration),
+    #[cfg(feature = "chrono")]
+    At(chrono::DateTime<chrono::Utc>),
+}
+
+impl StartSchedule {
+    /// The schedule declared by the task itself, `start_at()` taking precedence
+    pub(crate) fn of(task: &dyn SupervisedTask) -> Option<Self> {
+        #[cfg(feature = "chrono")]
+        if let Some(at) = task.start_at() {
+            return Some(Self::At(at));
+        }
+
+        task.start_delay().map(Self::After)
+    }
+
+    /// Time left to wait before starting, zero if the start time has passed
+    pub(crate) fn remaining(&self) -> Duration {
+        match self {
+            Self::After(delay) => *delay,
+            #[cfg(feature = "chrono")]
+            Self::At(at) => (*at - chrono::Utc::now()).to_std().unwrap_or_default(),
+        }
+    }
+}
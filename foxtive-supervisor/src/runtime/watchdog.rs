@@ -0,0 +1,224 @@
+//! Samples the resource usage of supervised tasks against their [`ResourceLimits`].
+
+use super::spawn::spawn_named;
+use crate::enums::{HealthStatus, ResourceLimits, SupervisorEvent};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Usage and state shared between a task's run, its watchdog and the runtime.
+#[derive(Debug)]
+pub struct ResourceWatch {
+    limits: ResourceLimits,
+    /// Total time spent polling the task's `run()` future
+    busy_nanos: AtomicU64,
+    /// Reason the task is degraded, if a limit is exceeded
+    exceeded: Mutex<Option<String>>,
+}
+
+impl ResourceWatch {
+    pub fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            busy_nanos: AtomicU64::new(0),
+            exceeded: Mutex::new(None),
+        }
+    }
+
+    /// The task's own health, downgraded to `Degraded` while a limit is exceeded
+    pub fn apply(&self, health: HealthStatus) -> HealthStatus {
+        match (health, self.exceeded.lock().unwrap().as_ref()) {
+            (HealthStatus::Healthy | HealthStatus::Unknown, Some(reason)) => {
+                HealthStatus::degraded(reason.clone())
+            }
+            (health, _) => health,
+        }
+    }
+
+    fn add_busy(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.busy_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// First limit exceeded by the sample, as (resource, usage, limit)
+    fn check(&self, cpu: f64, memory: Option<u64>) -> Option<(&'static str, f64, f64)> {
+        if let Some(max_cpu) = self.limits.max_cpu
+            && cpu > max_cpu
+        {
+            return Some(("cpu", cpu, max_cpu));
+        }
+
+        match (self.limits.max_process_memory_bytes, memory) {
+            (Some(max), Some(rss)) if rss > max => Some(("process_memory", rss as f64, max as f64)),
+            _ => None,
+        }
+    }
+}
+
+/// Adds the time spent polling the inner future to the watch, if any
+pub struct TrackedRun<F> {
+    inner: Pin<Box<F>>,
+    watch: Option<Arc<ResourceWatch>>,
+}
+
+impl<F: Future> TrackedRun<F> {
+    pub fn new(inner: F, watch: Option<Arc<ResourceWatch>>) -> Self {
+        Self {
+            inner: Box::pin(inner),
+            watch,
+        }
+    }
+}
+
+impl<F: Future> Future for TrackedRun<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(watch) = self.watch.clone() else {
+            return self.inner.as_mut().poll(cx);
+        };
+
+        let started = Instant::now();
+        let poll = self.inner.as_mut().poll(cx);
+        watch.add_busy(started.elapsed());
+        poll
+    }
+}
+
+/// Aborts the watchdog when the supervision loop ends
+pub struct WatchdogGuard(JoinHandle<()>);
+
+impl Drop for WatchdogGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Samples usage every `sample_interval`, emitting an event on each transition
+/// between exceeded and within limits.
+pub fn spawn_watchdog(
+    watch: Arc<ResourceWatch>,
+    event_tx: broadcast::Sender<SupervisorEvent>,
+    id: String,
+    name: String,
+) -> WatchdogGuard {
+    let task_name = format!("watchdog:{id}");
+    let handle = spawn_named(&task_name, async move {
+        let mut interval = tokio::time::interval(watch.limits.sample_interval);
+        interval.tick().await;
+
+        let mut last_sample = Instant::now();
+        let mut last_busy = watch.busy_nanos.load(Ordering::Relaxed);
+
+        loop {
+            interval.tick().await;
+
+            let busy = watch.busy_nanos.load(Ordering::Relaxed);
+            let wall = last_sample.elapsed().as_nanos().max(1) as f64;
+            let cpu = busy.saturating_sub(last_busy) as f64 / wall;
+            last_sample = Instant::now();
+            last_busy = busy;
+
+            let memory = match watch.limits.max_process_memory_bytes {
+                Some(_) => process_memory_bytes().await,
+                None => None,
+            };
+
+            let exceeded = watch.check(cpu, memory);
+            let was_exceeded = watch.exceeded.lock().unwrap().is_some();
+
+            match exceeded {
+                Some((resource, usage, limit)) => {
+                    let reason = format!("{resource} usage {usage:.2} above limit {limit:.2}");
+                    *watch.exceeded.lock().unwrap() = Some(reason);
+
+                    if !was_exceeded {
+                        warn!(task_id = %id, resource, usage, limit, "Task exceeded its resource limit");
+                        let _ = event_tx.send(SupervisorEvent::ResourceThresholdExceeded {
+                            id: id.clone(),
+                            name: name.clone(),
+                            resource: resource.to_string(),
+                            usage,
+                            limit,
+                        });
+                    }
+                }
+                None if was_exceeded => {
+                    *watch.exceeded.lock().unwrap() = None;
+                    info!(task_id = %id, "Task resource usage back within limits");
+                    let _ = event_tx.send(SupervisorEvent::ResourceUsageRecovered {
+                        id: id.clone(),
+                        name: name.clone(),
+                    });
+                }
+                None => {}
+            }
+        }
+    });
+
+    WatchdogGuard(handle)
+}
+
+/// Resident memory of the process, `None` where it can't be read
+async fn process_memory_bytes() -> Option<u64> {
+    let status = tokio::fs::read_to_string("/proc/self/status").await.ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_reports_first_exceeded_limit() {
+        let watch = ResourceWatch::new(ResourceLimits {
+            max_cpu: Some(0.5),
+            max_process_memory_bytes: Some(1024),
+            ..ResourceLimits::default()
+        });
+
+        assert_eq!(watch.check(0.2, Some(512)), None);
+        assert_eq!(watch.check(0.8, Some(512)), Some(("cpu", 0.8, 0.5)));
+        assert_eq!(
+            watch.check(0.2, Some(2048)),
+            Some(("process_memory", 2048.0, 1024.0))
+        );
+        assert_eq!(watch.check(0.2, None), None);
+    }
+
+    #[test]
+    fn test_apply_degrades_only_healthy_tasks() {
+        let watch = ResourceWatch::new(ResourceLimits::default());
+        assert_eq!(watch.apply(HealthStatus::Healthy), HealthStatus::Healthy);
+
+        *watch.exceeded.lock().unwrap() = Some("cpu".into());
+        assert_eq!(
+            watch.apply(HealthStatus::Healthy),
+            HealthStatus::degraded("cpu")
+        );
+        assert_eq!(
+            watch.apply(HealthStatus::unhealthy("down")),
+            HealthStatus::unhealthy("down")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tracked_run_counts_poll_time() {
+        let watch = Arc::new(ResourceWatch::new(ResourceLimits::default()));
+        TrackedRun::new(
+            async { std::thread::sleep(Duration::from_millis(20)) },
+            Some(watch.clone()),
+        )
+        .await;
+
+        assert!(watch.busy_nanos.load(Ordering::Relaxed) >= 20_000_000);
+    }
+}
@@ -140,3 +140,79 @@ async fn test_priority_scheduling() {
     assert_eq!(order[1], "medium");
     assert_eq!(order[2], "low");
 }
+
+/// Tracks how many tasks sharing `current` run at once
+struct GroupedTask {
+    id: String,
+    group: Option<&'static str>,
+    current: Arc<AtomicUsize>,
+    max: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl foxtive_supervisor::contracts::SupervisedTask for GroupedTask {
+    fn id(&self) -> &'static str {
+        Box::leak(self.id.clone().into_boxed_str())
+    }
+    fn concurrency_group(&self) -> Option<&'static str> {
+        self.group
+    }
+    async fn run(&self) -> anyhow::Result<()> {
+        let running = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max.fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.current.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_concurrency_group_limit() {
+    let group_current = Arc::new(AtomicUsize::new(0));
+    let group_max = Arc::new(AtomicUsize::new(0));
+    let other_current = Arc::new(AtomicUsize::new(0));
+    let other_max = Arc::new(AtomicUsize::new(0));
+
+    let mut supervisor = Supervisor::new().with_concurrency_group("db-heavy", 2);
+    for i in 0..6 {
+        supervisor = supervisor
+            .add(GroupedTask {
+                id: format!("db_task_{i}"),
+                group: Some("db-heavy"),
+                current: group_current.clone(),
+                max: group_max.clone(),
+            })
+            .add(GroupedTask {
+                id: format!("other_task_{i}"),
+                group: None,
+                current: other_current.clone(),
+                max: other_max.clone(),
+            });
+    }
+
+    supervisor.start_and_wait_all().await.unwrap();
+
+    assert_eq!(group_max.load(Ordering::SeqCst), 2);
+    // tasks outside the group aren't limited by it
+    assert!(other_max.load(Ordering::SeqCst) > 2);
+}
+
+#[tokio::test]
+async fn test_unknown_concurrency_group_fails_start() {
+    let result = Supervisor::new()
+        .add(GroupedTask {
+            id: "orphan".to_string(),
+            group: Some("missing"),
+            current: Arc::new(AtomicUsize::new(0)),
+            max: Arc::new(AtomicUsize::new(0)),
+        })
+        .start()
+        .await;
+
+    let error = result.unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("Unknown concurrency group 'missing'")
+    );
+}
@@ -104,3 +104,137 @@ async fn test_dependency_failure_prevents_startup() {
             || result.final_status == SupervisionStatus::DependencyFailed
     );
 }
+
+struct FlakySetupTask {
+    id: &'static str,
+    setup_failures: usize,
+    setup_calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    max_duration: std::time::Duration,
+    backoff: std::time::Duration,
+}
+
+#[async_trait::async_trait]
+impl foxtive_supervisor::contracts::SupervisedTask for FlakySetupTask {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+    fn restart_policy(&self) -> foxtive_supervisor::enums::RestartPolicy {
+        foxtive_supervisor::enums::RestartPolicy::Never
+    }
+    fn setup_retry_policy(&self) -> Option<foxtive_supervisor::enums::SetupRetryPolicy> {
+        Some(foxtive_supervisor::enums::SetupRetryPolicy::new(
+            foxtive_supervisor::enums::BackoffStrategy::fixed(self.backoff),
+            self.max_duration,
+        ))
+    }
+    async fn setup(&self) -> anyhow::Result<()> {
+        let calls = self
+            .setup_calls
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if calls < self.setup_failures {
+            anyhow::bail!("Broker not ready")
+        }
+        Ok(())
+    }
+    async fn run(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_setup_retries_until_success() {
+    let setup_calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let result = Supervisor::new()
+        .add(FlakySetupTask {
+            id: "flaky_setup",
+            setup_failures: 3,
+            setup_calls: setup_calls.clone(),
+            max_duration: std::time::Duration::from_secs(5),
+            backoff: std::time::Duration::from_millis(20),
+        })
+        .start_and_wait_any()
+        .await
+        .unwrap();
+
+    assert_eq!(result.final_status, SupervisionStatus::CompletedNormally);
+    assert_eq!(setup_calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_setup_retry_backoff_ignores_other_control_messages() {
+    let setup_calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let supervisor = Supervisor::new().add(FlakySetupTask {
+        id: "slow_retry",
+        setup_failures: 1,
+        setup_calls: setup_calls.clone(),
+        max_duration: std::time::Duration::from_secs(5),
+        backoff: std::time::Duration::from_millis(400),
+    });
+
+    let mut runtime = supervisor.start().await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    runtime.pause_task("slow_retry").unwrap();
+    runtime.restart_task("slow_retry").unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    // Still waiting out the backoff, the messages didn't trigger an early retry
+    assert_eq!(setup_calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let result = runtime.wait_any().await;
+    assert_eq!(result.final_status, SupervisionStatus::CompletedNormally);
+    assert_eq!(setup_calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_setup_retries_give_up_after_max_duration() {
+    let setup_calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let result = Supervisor::new()
+        .add(FlakySetupTask {
+            id: "never_ready",
+            setup_failures: usize::MAX,
+            setup_calls: setup_calls.clone(),
+            max_duration: std::time::Duration::from_millis(100),
+            backoff: std::time::Duration::from_millis(20),
+        })
+        .start_and_wait_any()
+        .await
+        .unwrap();
+
+    assert_eq!(result.final_status, SupervisionStatus::SetupFailed);
+    let calls = setup_calls.load(std::sync::atomic::Ordering::SeqCst);
+    assert!((2..=6).contains(&calls), "unexpected setup calls: {calls}");
+}
+
+#[tokio::test]
+async fn test_panicking_supervision_loop_keeps_task_name() {
+    struct PanickingSetupTask;
+
+    #[async_trait::async_trait]
+    impl foxtive_supervisor::contracts::SupervisedTask for PanickingSetupTask {
+        fn id(&self) -> &'static str {
+            "panicking_setup"
+        }
+        fn name(&self) -> String {
+            "Panicking Setup".to_string()
+        }
+        async fn setup(&self) -> anyhow::Result<()> {
+            panic!("setup exploded")
+        }
+        async fn run(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    let result = Supervisor::new()
+        .add(PanickingSetupTask)
+        .start_and_wait_any()
+        .await
+        .unwrap();
+
+    assert_eq!(result.final_status, SupervisionStatus::SupervisorPanicked);
+    assert_eq!(result.task_name, "Panicking Setup");
+    assert!(result.join_error.unwrap().contains("panicked"));
+}
@@ -0,0 +1,96 @@
+use foxtive_supervisor::contracts::{SupervisedTask, SupervisorEventListener};
+use foxtive_supervisor::enums::{
+    BackoffStrategy, RestartPolicy, SupervisionStatus, SupervisorEvent,
+};
+use foxtive_supervisor::{Heartbeat, Supervisor};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+struct EventCollector {
+    events: Arc<Mutex<Vec<SupervisorEvent>>>,
+}
+
+#[async_trait::async_trait]
+impl SupervisorEventListener for EventCollector {
+    async fn on_event(&self, event: SupervisorEvent) {
+        self.events.lock().await.push(event);
+    }
+}
+
+/// Stops ticking on its first attempt, then ticks steadily until done
+struct ConsumerTask {
+    attempts: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl SupervisedTask for ConsumerTask {
+    fn id(&self) -> &'static str {
+        "consumer"
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::MaxAttempts(3)
+    }
+
+    fn backoff_strategy(&self) -> BackoffStrategy {
+        BackoffStrategy::fixed(Duration::from_millis(10))
+    }
+
+    fn heartbeat_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_millis(80))
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let heartbeat = Heartbeat::current().expect("heartbeat of the running attempt");
+
+        if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+            heartbeat.tick();
+            // silently stops receiving
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            return Ok(());
+        }
+
+        // outlives the timeout, but ticks within it
+        for _ in 0..10 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            heartbeat.tick();
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_stalled_task_is_restarted() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let result = Supervisor::new()
+        .add(ConsumerTask {
+            attempts: attempts.clone(),
+        })
+        .add_listener(Arc::new(EventCollector {
+            events: events.clone(),
+        }))
+        .start_and_wait_any()
+        .await
+        .unwrap();
+
+    assert_eq!(result.final_status, SupervisionStatus::CompletedNormally);
+    assert_eq!(result.total_attempts, 2);
+
+    // listeners receive events asynchronously
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let events = events.lock().await;
+    let stalls = events
+        .iter()
+        .filter(|event| matches!(event, SupervisorEvent::TaskStalled { attempt: 1, .. }))
+        .count();
+    assert_eq!(stalls, 1);
+}
+
+#[tokio::test]
+async fn test_current_is_none_outside_supervision() {
+    assert!(Heartbeat::current().is_none());
+}
@@ -301,3 +301,92 @@ async fn test_hierarchy_with_failing_task() {
 
     runtime.shutdown_all().await;
 }
+
+/// Fails its first `failures` runs, counted across branch restarts
+struct CountingTask {
+    id: &'static str,
+    runs: Arc<std::sync::atomic::AtomicUsize>,
+    failures: usize,
+}
+
+#[async_trait::async_trait]
+impl SupervisedTask for CountingTask {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn restart_policy(&self) -> foxtive_supervisor::enums::RestartPolicy {
+        foxtive_supervisor::enums::RestartPolicy::MaxAttempts(1)
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let runs = self.runs.fetch_add(1, Ordering::SeqCst);
+        if runs < self.failures {
+            anyhow::bail!("Intentional failure");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_child_supervisor_completes_with_its_children() {
+    let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let child = Supervisor::new()
+        .add(CountingTask {
+            id: "child-1",
+            runs: runs.clone(),
+            failures: 0,
+        })
+        .add(CountingTask {
+            id: "child-2",
+            runs: runs.clone(),
+            failures: 0,
+        });
+
+    let result = Supervisor::new()
+        .add_supervisor("branch", child)
+        .start_and_wait_any()
+        .await
+        .unwrap();
+
+    assert_eq!(result.task_id, "branch");
+    assert_eq!(
+        result.final_status,
+        foxtive_supervisor::enums::SupervisionStatus::CompletedNormally
+    );
+    assert_eq!(result.total_attempts, 1);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_failing_child_restarts_whole_branch() {
+    let flaky_runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let steady_runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let child = Supervisor::new()
+        .add(CountingTask {
+            id: "flaky",
+            runs: flaky_runs.clone(),
+            failures: 1,
+        })
+        .add(CountingTask {
+            id: "steady",
+            runs: steady_runs.clone(),
+            failures: 0,
+        });
+
+    let result = Supervisor::new()
+        .add_supervisor("branch", child)
+        .start_and_wait_any()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result.final_status,
+        foxtive_supervisor::enums::SupervisionStatus::CompletedNormally
+    );
+    assert_eq!(result.total_attempts, 2);
+    assert_eq!(flaky_runs.load(Ordering::SeqCst), 2);
+    assert_eq!(steady_runs.load(Ordering::SeqCst), 2);
+}
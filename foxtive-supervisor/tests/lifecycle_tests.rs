@@ -49,3 +49,56 @@ async fn test_task_panic_recovery() {
     assert_eq!(result.final_status, SupervisionStatus::CompletedNormally);
     assert_eq!(result.total_attempts, 3);
 }
+
+#[tokio::test]
+async fn test_delayed_task_start_includes_setup() {
+    let task = HookTrackingTask::new("delayed_start");
+    let setup = task.setup_called.clone();
+
+    let runtime = Supervisor::new()
+        .add_delayed(task, Duration::from_millis(200))
+        .start()
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!setup.load(Ordering::SeqCst));
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(setup.load(Ordering::SeqCst));
+    runtime.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_stop_before_scheduled_start() {
+    let task = HookTrackingTask::new("stopped_before_start");
+    let setup = task.setup_called.clone();
+
+    let runtime = Supervisor::new()
+        .add_delayed(task, Duration::from_secs(60))
+        .start()
+        .await
+        .unwrap();
+
+    tokio::time::timeout(Duration::from_secs(1), runtime.shutdown())
+        .await
+        .expect("shutdown doesn't wait for the scheduled start");
+    assert!(!setup.load(Ordering::SeqCst));
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn test_task_starts_at_scheduled_time() {
+    let task = MockTask::new("scheduled_start");
+    let at = chrono::Utc::now() + chrono::Duration::milliseconds(200);
+
+    let started = std::time::Instant::now();
+    let result = Supervisor::new()
+        .add_at(task, at)
+        .start_and_wait_any()
+        .await
+        .unwrap();
+
+    assert_eq!(result.final_status, SupervisionStatus::CompletedNormally);
+    assert!(started.elapsed() >= Duration::from_millis(150));
+}
@@ -125,3 +125,39 @@ async fn test_shutdown_with_mock_task() {
     runtime.shutdown().await;
     assert!(mock.shutdown_called());
 }
+
+#[tokio::test]
+async fn test_cancelled_wait_any_keeps_tasks_for_shutdown() {
+    struct LongTask {
+        shut_down: Arc<AtomicBool>,
+    }
+
+    #[async_trait::async_trait]
+    impl foxtive_supervisor::contracts::SupervisedTask for LongTask {
+        fn id(&self) -> &'static str {
+            "long"
+        }
+        async fn run(&self) -> anyhow::Result<()> {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            Ok(())
+        }
+        async fn on_shutdown(&self) {
+            self.shut_down.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let shut_down = Arc::new(AtomicBool::new(false));
+    let mut runtime = Supervisor::new()
+        .add(LongTask {
+            shut_down: shut_down.clone(),
+        })
+        .start()
+        .await
+        .unwrap();
+
+    let waited = tokio::time::timeout(Duration::from_millis(100), runtime.wait_any()).await;
+    assert!(waited.is_err());
+
+    runtime.shutdown().await;
+    assert!(shut_down.load(Ordering::SeqCst));
+}
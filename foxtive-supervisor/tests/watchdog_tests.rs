@@ -0,0 +1,86 @@
+use foxtive_supervisor::Supervisor;
+use foxtive_supervisor::contracts::{SupervisedTask, SupervisorEventListener};
+use foxtive_supervisor::enums::{ResourceLimits, RestartPolicy, SupervisorEvent};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+struct EventCollector {
+    events: Arc<Mutex<Vec<SupervisorEvent>>>,
+}
+
+#[async_trait::async_trait]
+impl SupervisorEventListener for EventCollector {
+    async fn on_event(&self, event: SupervisorEvent) {
+        self.events.lock().await.push(event);
+    }
+}
+
+/// Keeps its worker thread busy for `busy_for`, then idles
+struct BusyTask {
+    busy_for: Duration,
+}
+
+#[async_trait::async_trait]
+impl SupervisedTask for BusyTask {
+    fn id(&self) -> &'static str {
+        "busy_task"
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let started = std::time::Instant::now();
+        while started.elapsed() < self.busy_for {
+            std::thread::sleep(Duration::from_millis(10));
+            tokio::task::yield_now().await;
+        }
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Ok(())
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::Never
+    }
+
+    fn resource_limits(&self) -> Option<ResourceLimits> {
+        Some(ResourceLimits {
+            max_cpu: Some(0.5),
+            sample_interval: Duration::from_millis(100),
+            ..ResourceLimits::default()
+        })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_watchdog_degrades_task_above_cpu_limit_and_recovers() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let runtime = Supervisor::new()
+        .add(BusyTask {
+            busy_for: Duration::from_millis(400),
+        })
+        .add_listener(Arc::new(EventCollector {
+            events: events.clone(),
+        }))
+        .start()
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let info = runtime.get_task_info("busy_task").await.unwrap();
+    assert!(info.health.is_degraded(), "health: {:?}", info.health);
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let info = runtime.get_task_info("busy_task").await.unwrap();
+    assert!(info.health.is_healthy(), "health: {:?}", info.health);
+
+    let captured = events.lock().await;
+    assert!(captured.iter().any(|event| matches!(
+        event,
+        SupervisorEvent::ResourceThresholdExceeded { id, resource, .. } if id == "busy_task" && resource == "cpu"
+    )));
+    assert!(captured.iter().any(|event| matches!(
+        event,
+        SupervisorEvent::ResourceUsageRecovered { id, .. } if id == "busy_task"
+    )));
+}
@@ -0,0 +1,340 @@
+//! # App Module
+//!
+//! Bootstraps a service: initializes tracing, creates the global state with
+//! [`make_state`], starts the supervised tasks and keeps them running until the
+//! process is asked to stop.
+//!
+//! - `SIGTERM` and `SIGINT` (Ctrl+C) shut the tasks down gracefully, within the
+//!   configured timeout. A second signal stops waiting for them.
+//! - `SIGHUP` runs the reload hook, e.g. to re-read configuration, without restarting.
+//! - The first task to terminate shuts the others down, an error is returned if it failed.
+//!
+//! ## Features
+//!
+//! This module requires the `app` feature to be enabled.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use foxtive::app::{self, AppSetup};
+//! use foxtive::setup::FoxtiveSetup;
+//! use foxtive::Environment;
+//! # struct HttpServer;
+//! # #[async_trait::async_trait]
+//! # impl foxtive::supervisor::SupervisedTask for HttpServer {
+//! #     fn id(&self) -> &'static str { "http" }
+//! #     async fn run(&self) -> anyhow::Result<()> { Ok(()) }
+//! # }
+//!
+//! #[tokio::main]
+//! async fn main() -> foxtive::prelude::AppResult<()> {
+//!     let setup = FoxtiveSetup::builder()
+//!         .env(Environment::Production)
+//!         .app("billing", "Billing")
+//!         .build()?;
+//!
+//!     let setup = AppSetup::new(setup).on_reload(|| async {
+//!         tracing::info!("reloading configuration");
+//!         Ok(())
+//!     });
+//!
+//!     app::run(setup, |supervisor| supervisor.add(HttpServer)).await
+//! }
+//! ```
+
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use crate::setup::trace::{Tracing, init_tracing_with_guard};
+use crate::setup::{FoxtiveSetup, make_state};
+use foxtive_supervisor::Supervisor;
+use foxtive_supervisor::TaskRuntime;
+use futures_util::future::BoxFuture;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+type ReloadHook = Arc<dyn Fn() -> BoxFuture<'static, AppResult<()>> + Send + Sync>;
+
+/// Process signals handled by [`run`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    Terminate,
+    Reload,
+}
+
+/// Configuration of the process started by [`run`]
+pub struct AppSetup {
+    foxtive: FoxtiveSetup,
+    tracing: Option<Tracing>,
+    shutdown_timeout: Duration,
+    on_reload: Option<ReloadHook>,
+}
+
+impl AppSetup {
+    /// Initializes tracing with `Tracing::default()` and gives tasks 30 seconds to shut down
+    pub fn new(setup: FoxtiveSetup) -> Self {
+        Self {
+            foxtive: setup,
+            tracing: Some(Tracing::default()),
+            shutdown_timeout: Duration::from_secs(30),
+            on_reload: None,
+        }
+    }
+
+    pub fn tracing(mut self, tracing: Tracing) -> Self {
+        self.tracing = Some(tracing);
+        self
+    }
+
+    /// Leaves tracing to the caller, e.g. when it's initialized before the setup is built
+    pub fn without_tracing(mut self) -> Self {
+        self.tracing = None;
+        self
+    }
+
+    /// Longest the whole shutdown may take, the remaining tasks are then left behind
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Runs the hook on `SIGHUP`, a failing hook is logged and the process keeps running
+    pub fn on_reload<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = AppResult<()>> + Send + 'static,
+    {
+        self.on_reload = Some(Arc::new(move || Box::pin(hook())));
+        self
+    }
+}
+
+/// Bootstraps the process and supervises the tasks added by `tasks` until a
+/// termination signal, or until one of them terminates.
+///
+/// # Errors
+///
+/// Returns an error if tracing or the state can't be initialized, if the tasks
+/// can't be started, and if a task terminated with a failure
+pub async fn run<F>(setup: AppSetup, tasks: F) -> AppResult<()>
+where
+    F: FnOnce(Supervisor) -> Supervisor,
+{
+    let _tracing = setup.tracing.map(init_tracing_with_guard).transpose()?;
+
+    make_state(setup.foxtive).await?;
+
+    let signals = listen_for_signals()?;
+    let runtime = tasks(Supervisor::new()).start().await?;
+    info!("[app] started, waiting for tasks or signals");
+
+    supervise(
+        runtime,
+        signals,
+        setup.on_reload.as_ref(),
+        setup.shutdown_timeout,
+    )
+    .await
+}
+
+async fn supervise(
+    mut runtime: TaskRuntime,
+    mut signals: mpsc::Receiver<Signal>,
+    on_reload: Option<&ReloadHook>,
+    shutdown_timeout: Duration,
+) -> AppResult<()> {
+    let outcome = loop {
+        tokio::select! {
+            result = runtime.wait_any() => {
+                break match result.final_status.is_failure() {
+                    true => Err(internal_server_error!(
+                        "Task '{}' terminated: {:?}",
+                        result.task_name,
+                        result.final_status
+                    )),
+                    false => {
+                        info!("[app] task '{}' completed, shutting down", result.task_name);
+                        Ok(())
+                    }
+                };
+            }
+            signal = signals.recv() => match signal {
+                Some(Signal::Reload) => match on_reload {
+                    Some(hook) => {
+                        info!("[app] reloading");
+                        if let Err(err) = hook().await {
+                            error!("[app] reload failed: {err}");
+                        }
+                    }
+                    None => info!("[app] no reload hook, ignoring SIGHUP"),
+                },
+                Some(Signal::Terminate) | None => {
+                    info!("[app] termination requested, shutting down");
+                    break Ok(());
+                }
+            },
+        }
+    };
+
+    tokio::select! {
+        finished = tokio::time::timeout(shutdown_timeout, runtime.shutdown()) => {
+            if finished.is_err() {
+                warn!("[app] tasks didn't shut down within {shutdown_timeout:?}, exiting anyway");
+            }
+        }
+        _ = wait_for_terminate(&mut signals) => {
+            warn!("[app] termination requested again, exiting without waiting for tasks");
+        }
+    }
+
+    outcome
+}
+
+async fn wait_for_terminate(signals: &mut mpsc::Receiver<Signal>) {
+    while let Some(signal) = signals.recv().await {
+        if signal == Signal::Terminate {
+            return;
+        }
+    }
+
+    // no more signals can arrive, let the shutdown finish
+    std::future::pending::<()>().await
+}
+
+/// Forwards the process signals to the returned channel
+fn listen_for_signals() -> AppResult<mpsc::Receiver<Signal>> {
+    let (tx, rx) = mpsc::channel(4);
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut hangup = signal(SignalKind::hangup())?;
+
+        tokio::spawn(async move {
+            loop {
+                let signal = tokio::select! {
+                    _ = terminate.recv() => Signal::Terminate,
+                    _ = interrupt.recv() => Signal::Terminate,
+                    _ = hangup.recv() => Signal::Reload,
+                };
+
+                if tx.send(signal).await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    #[cfg(not(unix))]
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if tx.send(Signal::Terminate).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foxtive_supervisor::SupervisedTask;
+    use foxtive_supervisor::enums::RestartPolicy;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    struct Server {
+        fail: bool,
+        stopped: Arc<AtomicBool>,
+    }
+
+    #[async_trait::async_trait]
+    impl SupervisedTask for Server {
+        fn id(&self) -> &'static str {
+            "server"
+        }
+
+        fn restart_policy(&self) -> RestartPolicy {
+            RestartPolicy::MaxAttempts(1)
+        }
+
+        async fn run(&self) -> anyhow::Result<()> {
+            match self.fail {
+                true => Err(anyhow::anyhow!("port in use")),
+                false => {
+                    tokio::time::sleep(Duration::from_secs(3600)).await;
+                    Ok(())
+                }
+            }
+        }
+
+        async fn on_shutdown(&self) {
+            self.stopped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    async fn runtime(fail: bool) -> (TaskRuntime, Arc<AtomicBool>) {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = Server {
+            fail,
+            stopped: stopped.clone(),
+        };
+
+        (
+            Supervisor::new().add(server).start().await.unwrap(),
+            stopped,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_terminate_shuts_tasks_down() {
+        let (runtime, stopped) = runtime(false).await;
+        let (tx, rx) = mpsc::channel(4);
+
+        tx.send(Signal::Terminate).await.unwrap();
+        supervise(runtime, rx, None, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(stopped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_reload_runs_the_hook_and_keeps_running() {
+        let (runtime, stopped) = runtime(false).await;
+        let (tx, rx) = mpsc::channel(4);
+        let reloads = Arc::new(AtomicUsize::new(0));
+
+        let counter = reloads.clone();
+        let hook: ReloadHook = Arc::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(()) })
+        });
+
+        tx.send(Signal::Reload).await.unwrap();
+        tx.send(Signal::Reload).await.unwrap();
+        tx.send(Signal::Terminate).await.unwrap();
+        supervise(runtime, rx, Some(&hook), Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(reloads.load(Ordering::SeqCst), 2);
+        assert!(stopped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_failed_task_is_returned_as_error() {
+        let (runtime, _) = runtime(true).await;
+        let (_tx, rx) = mpsc::channel(4);
+
+        let err = supervise(runtime, rx, None, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("server"));
+    }
+}
@@ -0,0 +1,17 @@
+use crate::audit::AuditEvent;
+use crate::prelude::AppResult;
+
+/// Contract for implementing audit sinks
+///
+/// A sink stores or forwards audit events, e.g. to the logs, a database table or a queue.
+#[async_trait::async_trait]
+pub trait AuditSinkContract: Send + Sync {
+    /// Name of the sink, used in logs and errors
+    fn name(&self) -> &str;
+
+    /// Writes the event
+    ///
+    /// # Parameters
+    /// - `event`: The event to write
+    async fn write(&self, event: &AuditEvent) -> AppResult<()>;
+}
@@ -0,0 +1,282 @@
+//! # Audit Module
+//!
+//! Structured audit logging: who did what to which resource, with the state of the
+//! resource before and after the change, recorded to one or more sinks.
+//!
+//! Events pick up the request id, correlation id and user of the current
+//! [`RequestContext`], so handlers only describe the change. Built-in sinks write
+//! events to the logs, a database table, a Redis list or a RabbitMQ exchange.
+//!
+//! ## Features
+//!
+//! This module requires the `audit` feature to be enabled, the database, Redis and
+//! RabbitMQ sinks additionally require the `database`, `redis` and `rabbitmq` features.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::audit::{self, AuditEvent, Auditor};
+//! use foxtive::audit::sinks::TracingAuditSink;
+//! use serde_json::json;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! Auditor::new().sink(TracingAuditSink).install().unwrap();
+//!
+//! let event = AuditEvent::new("user.email_changed", "user")
+//!     .actor("admin-1")
+//!     .resource_id("42")
+//!     .before(&json!({ "email": "old@example.com" }))
+//!     .after(&json!({ "email": "new@example.com" }));
+//!
+//! audit::record(event).await.unwrap();
+//! # });
+//! ```
+
+pub mod contract;
+pub mod sinks;
+
+use crate::audit::contract::AuditSinkContract;
+use crate::audit::sinks::TracingAuditSink;
+use crate::context::RequestContext;
+use crate::helpers::id;
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::{Arc, OnceLock};
+use tracing::error;
+
+static AUDITOR: OnceLock<Auditor> = OnceLock::new();
+
+/// A change made by an actor to a resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub id: String,
+    pub occurred_at: DateTime<Utc>,
+    /// Who made the change, defaults to the user of the current [`RequestContext`]
+    pub actor: Option<String>,
+    /// What was done, e.g. `user.email_changed`
+    pub action: String,
+    /// Kind of resource changed, e.g. `user`
+    pub resource: String,
+    pub resource_id: Option<String>,
+    /// State of the resource before the change
+    pub before: Option<Value>,
+    /// State of the resource after the change
+    pub after: Option<Value>,
+    pub request_id: Option<String>,
+    pub correlation_id: Option<String>,
+    /// Anything else worth keeping, e.g. the client IP or a reason
+    pub metadata: Map<String, Value>,
+}
+
+impl AuditEvent {
+    /// Creates an event happening now, within the current [`RequestContext`] if any
+    pub fn new(action: &str, resource: &str) -> Self {
+        let context = RequestContext::current();
+
+        Self {
+            id: id::ulid(),
+            occurred_at: Utc::now(),
+            actor: context.as_ref().and_then(|c| c.user_id.clone()),
+            action: action.to_string(),
+            resource: resource.to_string(),
+            resource_id: None,
+            before: None,
+            after: None,
+            request_id: context.as_ref().map(|c| c.request_id.clone()),
+            correlation_id: context.map(|c| c.correlation_id),
+            metadata: Map::new(),
+        }
+    }
+
+    pub fn actor<A: ToString>(mut self, actor: A) -> Self {
+        self.actor = Some(actor.to_string());
+        self
+    }
+
+    pub fn resource_id<I: ToString>(mut self, resource_id: I) -> Self {
+        self.resource_id = Some(resource_id.to_string());
+        self
+    }
+
+    /// Sets the state before the change, `null` if it can't be serialized
+    pub fn before<T: Serialize>(mut self, before: &T) -> Self {
+        self.before = Some(serde_json::to_value(before).unwrap_or_default());
+        self
+    }
+
+    /// Sets the state after the change, `null` if it can't be serialized
+    pub fn after<T: Serialize>(mut self, after: &T) -> Self {
+        self.after = Some(serde_json::to_value(after).unwrap_or_default());
+        self
+    }
+
+    /// Adds a metadata entry, `null` if it can't be serialized
+    pub fn metadata<T: Serialize>(mut self, key: &str, value: &T) -> Self {
+        self.metadata.insert(
+            key.to_string(),
+            serde_json::to_value(value).unwrap_or_default(),
+        );
+        self
+    }
+}
+
+/// Records audit events to every registered sink.
+#[derive(Clone, Default)]
+pub struct Auditor {
+    sinks: Vec<Arc<dyn AuditSinkContract>>,
+}
+
+impl Auditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sink<S: AuditSinkContract + 'static>(mut self, sink: S) -> Self {
+        self.sinks.push(Arc::new(sink));
+        self
+    }
+
+    /// Names of the registered sinks, in registration order
+    pub fn sinks(&self) -> Vec<&str> {
+        self.sinks.iter().map(|sink| sink.name()).collect()
+    }
+
+    /// Sets the auditor used by [`record`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an auditor is already installed
+    pub fn install(self) -> AppResult<()> {
+        AUDITOR
+            .set(self)
+            .map_err(|_| internal_server_error!("An auditor is already installed"))
+    }
+
+    /// The auditor set with [`Auditor::install`]
+    pub fn global() -> Option<&'static Auditor> {
+        AUDITOR.get()
+    }
+
+    /// Writes the event to all sinks concurrently, a failing sink doesn't prevent
+    /// the others from writing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing the sinks that failed
+    pub async fn record(&self, event: &AuditEvent) -> AppResult<()> {
+        let writes = self.sinks.iter().map(|sink| async move {
+            let result = sink.write(event).await;
+            (sink.name(), result)
+        });
+
+        let mut failures = vec![];
+        for (name, result) in join_all(writes).await {
+            if let Err(err) = result {
+                error!(
+                    "[audit] failed to write '{}' to {name}: {err}",
+                    event.action
+                );
+                failures.push(format!("{name}: {err}"));
+            }
+        }
+
+        match failures.is_empty() {
+            true => Ok(()),
+            false => Err(anyhow::anyhow!(
+                "audit event failed on {}",
+                failures.join(", ")
+            )),
+        }
+    }
+}
+
+/// Records the event with the installed [`Auditor`], or to the logs if none is installed
+pub async fn record(event: AuditEvent) -> AppResult<()> {
+    match Auditor::global() {
+        Some(auditor) => auditor.record(&event).await,
+        None => TracingAuditSink.write(&event).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct Memory(Arc<Mutex<Vec<AuditEvent>>>);
+
+    #[async_trait::async_trait]
+    impl AuditSinkContract for Memory {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn write(&self, event: &AuditEvent) -> AppResult<()> {
+            self.0.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    struct Broken;
+
+    #[async_trait::async_trait]
+    impl AuditSinkContract for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        async fn write(&self, _event: &AuditEvent) -> AppResult<()> {
+            Err(anyhow::anyhow!("unreachable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_picks_up_request_context() {
+        let context = RequestContext::new().with_user_id("user-7");
+        let event = context
+            .clone()
+            .scope(async { AuditEvent::new("order.cancelled", "order") })
+            .await;
+
+        assert_eq!(event.actor.as_deref(), Some("user-7"));
+        assert_eq!(event.request_id, Some(context.request_id));
+        assert_eq!(event.correlation_id, Some(context.correlation_id));
+    }
+
+    #[test]
+    fn test_event_builders() {
+        let event = AuditEvent::new("user.updated", "user")
+            .actor("admin")
+            .resource_id(42)
+            .before(&serde_json::json!({ "name": "Jane" }))
+            .after(&serde_json::json!({ "name": "Janet" }))
+            .metadata("ip", &"10.0.0.1");
+
+        assert_eq!(event.actor.as_deref(), Some("admin"));
+        assert_eq!(event.resource_id.as_deref(), Some("42"));
+        assert_eq!(event.before.unwrap()["name"], "Jane");
+        assert_eq!(event.after.unwrap()["name"], "Janet");
+        assert_eq!(event.metadata["ip"], "10.0.0.1");
+        assert!(event.request_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failing_sink_does_not_stop_the_others() {
+        let memory = Memory::default();
+        let auditor = Auditor::new().sink(Broken).sink(memory.clone());
+
+        let err = auditor
+            .record(&AuditEvent::new("user.deleted", "user"))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("broken"));
+        assert_eq!(memory.0.lock().unwrap().len(), 1);
+        assert_eq!(auditor.sinks(), vec!["broken", "memory"]);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::audit::AuditEvent;
+use crate::audit::contract::AuditSinkContract;
+use crate::database::DBPool;
+use crate::helpers::blk;
+use crate::prelude::AppResult;
+use diesel::sql_types::{Nullable, Text};
+use diesel::{RunQueryDsl, sql_query};
+
+/// Inserts events into a PostgreSQL table, created with:
+///
+/// ```sql
+/// CREATE TABLE audit_events (
+///     id             VARCHAR(26) PRIMARY KEY,
+///     occurred_at    TIMESTAMPTZ NOT NULL,
+///     actor          VARCHAR,
+///     action         VARCHAR NOT NULL,
+///     resource       VARCHAR NOT NULL,
+///     resource_id    VARCHAR,
+///     before         JSONB,
+///     after          JSONB,
+///     request_id     VARCHAR,
+///     correlation_id VARCHAR,
+///     metadata       JSONB NOT NULL
+/// );
+/// ```
+pub struct DatabaseAuditSink {
+    pool: DBPool,
+    table: String,
+}
+
+impl DatabaseAuditSink {
+    /// Inserts into the `audit_events` table
+    pub fn new(pool: DBPool) -> Self {
+        Self {
+            pool,
+            table: "audit_events".to_string(),
+        }
+    }
+
+    /// Inserts into `table` instead, optionally schema-qualified, e.g. `compliance.audit_events`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table` isn't made of letters, digits, underscores and a schema dot
+    pub fn table(mut self, table: &str) -> Self {
+        assert!(
+            !table.is_empty()
+                && table
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'),
+            "invalid audit table name: {table}"
+        );
+
+        self.table = table.to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSinkContract for DatabaseAuditSink {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    async fn write(&self, event: &AuditEvent) -> AppResult<()> {
+        let query = format!(
+            "INSERT INTO {} (id, occurred_at, actor, action, resource, resource_id, before, after, \
+             request_id, correlation_id, metadata) \
+             VALUES ($1, $2::timestamptz, $3, $4, $5, $6, $7::jsonb, $8::jsonb, $9, $10, $11::jsonb)",
+            self.table
+        );
+
+        let json = |value: &Option<serde_json::Value>| value.as_ref().map(|v| v.to_string());
+        let (before, after) = (json(&event.before), json(&event.after));
+        let metadata = serde_json::to_string(&event.metadata)?;
+        let event = event.clone();
+        let pool = self.pool.clone();
+
+        blk(move || -> AppResult<()> {
+            let mut conn = pool.get()?;
+            sql_query(query)
+                .bind::<Text, _>(event.id)
+                .bind::<Text, _>(event.occurred_at.to_rfc3339())
+                .bind::<Nullable<Text>, _>(event.actor)
+                .bind::<Text, _>(event.action)
+                .bind::<Text, _>(event.resource)
+                .bind::<Nullable<Text>, _>(event.resource_id)
+                .bind::<Nullable<Text>, _>(before)
+                .bind::<Nullable<Text>, _>(after)
+                .bind::<Nullable<Text>, _>(event.request_id)
+                .bind::<Nullable<Text>, _>(event.correlation_id)
+                .bind::<Text, _>(metadata)
+                .execute(&mut conn)?;
+            Ok(())
+        })
+        .await?
+    }
+}
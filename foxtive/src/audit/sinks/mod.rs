@@ -0,0 +1,15 @@
+#[cfg(feature = "database")]
+mod database_sink;
+#[cfg(feature = "rabbitmq")]
+mod rabbitmq_sink;
+#[cfg(feature = "redis")]
+mod redis_sink;
+mod tracing_sink;
+
+#[cfg(feature = "database")]
+pub use database_sink::DatabaseAuditSink;
+#[cfg(feature = "rabbitmq")]
+pub use rabbitmq_sink::RabbitMqAuditSink;
+#[cfg(feature = "redis")]
+pub use redis_sink::RedisAuditSink;
+pub use tracing_sink::TracingAuditSink;
@@ -0,0 +1,40 @@
+use crate::audit::AuditEvent;
+use crate::audit::contract::AuditSinkContract;
+use crate::prelude::{AppResult, RabbitMQ};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Publishes events as JSON to a RabbitMQ exchange, using the action as routing key
+pub struct RabbitMqAuditSink {
+    rabbitmq: Arc<Mutex<RabbitMQ>>,
+    exchange: String,
+}
+
+impl RabbitMqAuditSink {
+    /// # Arguments
+    ///
+    /// * `rabbitmq`: RabbitMQ client to publish with
+    /// * `exchange`: exchange receiving the events, typically a topic exchange
+    pub fn new(rabbitmq: Arc<Mutex<RabbitMQ>>, exchange: &str) -> Self {
+        Self {
+            rabbitmq,
+            exchange: exchange.to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSinkContract for RabbitMqAuditSink {
+    fn name(&self) -> &str {
+        "rabbitmq"
+    }
+
+    async fn write(&self, event: &AuditEvent) -> AppResult<()> {
+        let content = serde_json::to_vec(event)?;
+        self.rabbitmq
+            .lock()
+            .await
+            .publish(&self.exchange, &event.action, &content)
+            .await
+    }
+}
@@ -0,0 +1,36 @@
+use crate::audit::AuditEvent;
+use crate::audit::contract::AuditSinkContract;
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use std::sync::Arc;
+
+/// Appends events as JSON to a Redis list, for another process to ship them
+pub struct RedisAuditSink {
+    redis: Arc<Redis>,
+    key: String,
+}
+
+impl RedisAuditSink {
+    /// # Arguments
+    ///
+    /// * `redis`: Redis client to push with
+    /// * `key`: list receiving the events, e.g. `audit:events`
+    pub fn new(redis: Arc<Redis>, key: &str) -> Self {
+        Self {
+            redis,
+            key: key.to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSinkContract for RedisAuditSink {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    async fn write(&self, event: &AuditEvent) -> AppResult<()> {
+        self.redis.rpush(&self.key, event).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use crate::audit::AuditEvent;
+use crate::audit::contract::AuditSinkContract;
+use crate::prelude::AppResult;
+use tracing::info;
+
+/// Writes events to the logs, under the `audit` target, with the event as JSON
+pub struct TracingAuditSink;
+
+#[async_trait::async_trait]
+impl AuditSinkContract for TracingAuditSink {
+    fn name(&self) -> &str {
+        "tracing"
+    }
+
+    async fn write(&self, event: &AuditEvent) -> AppResult<()> {
+        info!(
+            target: "audit",
+            action = %event.action,
+            resource = %event.resource,
+            actor = event.actor.as_deref().unwrap_or("-"),
+            event = %serde_json::to_string(event)?,
+            "[audit] {} on {}",
+            event.action,
+            event.resource
+        );
+        Ok(())
+    }
+}
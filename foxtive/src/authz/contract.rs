@@ -0,0 +1,26 @@
+use crate::authz::{Authorizable, Resource, Role};
+use crate::prelude::AppResult;
+
+/// Contract for implementing authorization policies
+#[async_trait::async_trait]
+pub trait PolicyContract: Send + Sync {
+    /// Whether the user may perform `action` on the resource
+    ///
+    /// # Parameters
+    /// - `user`: The user acting
+    /// - `action`: What the user wants to do, e.g. `edit`
+    /// - `resource`: What the user acts on, a resource type such as `"post"` or a record
+    async fn can(
+        &self,
+        user: &dyn Authorizable,
+        action: &str,
+        resource: &dyn Resource,
+    ) -> AppResult<bool>;
+}
+
+/// Contract for implementing role storage
+#[async_trait::async_trait]
+pub trait RoleStoreContract: Send + Sync {
+    /// Loads the roles of the given names, unknown names are skipped
+    async fn roles(&self, names: &[String]) -> AppResult<Vec<Role>>;
+}
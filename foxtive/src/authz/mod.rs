@@ -0,0 +1,281 @@
+//! # Authz Module
+//!
+//! Role-based authorization: users hold roles, roles grant permissions, and policies
+//! decide whether a user may perform an action on a resource, so handlers ask one
+//! question instead of re-implementing the rules.
+//!
+//! Permissions are named `{resource}:{action}`, e.g. `post:edit`, and either part may be
+//! a `*` wildcard. Roles are kept in a [`RoleStoreContract`], in memory or in a database
+//! table. The [`Authorizer`] grants an action when any of its policies does: the
+//! [`RolePolicy`](policies::RolePolicy) checks permissions, the
+//! [`OwnerPolicy`](policies::OwnerPolicy) lets users act on what they own, and custom
+//! [`PolicyContract`]s can express anything else.
+//!
+//! ## Features
+//!
+//! This module requires the `authz` feature to be enabled, the database store
+//! additionally requires the `database` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::authz::policies::{OwnerPolicy, RolePolicy};
+//! use foxtive::authz::stores::InMemoryRoleStore;
+//! use foxtive::authz::{Authorizable, Authorizer, Resource, Role};
+//!
+//! struct User {
+//!     id: u64,
+//!     roles: Vec<String>,
+//! }
+//!
+//! impl Authorizable for User {
+//!     fn auth_id(&self) -> String {
+//!         self.id.to_string()
+//!     }
+//!
+//!     fn roles(&self) -> Vec<String> {
+//!         self.roles.clone()
+//!     }
+//! }
+//!
+//! struct Post {
+//!     author_id: u64,
+//! }
+//!
+//! impl Resource for Post {
+//!     fn resource_type(&self) -> &str {
+//!         "post"
+//!     }
+//!
+//!     fn owner_id(&self) -> Option<String> {
+//!         Some(self.author_id.to_string())
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let roles = InMemoryRoleStore::new()
+//!     .role(Role::new("editor").permission("post:*"))
+//!     .role(Role::new("reader").permission("post:read"));
+//!
+//! let authz = Authorizer::new()
+//!     .policy(RolePolicy::new(roles))
+//!     .policy(OwnerPolicy::new(&["edit", "delete"]));
+//!
+//! let reader = User { id: 1, roles: vec!["reader".to_string()] };
+//! assert!(authz.can(&reader, "read", "post").await.unwrap());
+//! assert!(!authz.can(&reader, "edit", &Post { author_id: 2 }).await.unwrap());
+//! assert!(authz.can(&reader, "edit", &Post { author_id: 1 }).await.unwrap());
+//!
+//! authz.authorize(&reader, "delete", &Post { author_id: 2 }).await.unwrap_err();
+//! # });
+//! ```
+
+pub mod contract;
+pub mod policies;
+pub mod stores;
+
+pub use contract::{PolicyContract, RoleStoreContract};
+
+use crate::forbidden;
+use crate::prelude::AppResult;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+const WILDCARD: &str = "*";
+
+/// A permission, `{resource}:{action}`, either part may be a `*` wildcard and `*`
+/// alone grants everything
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Permission(String);
+
+impl Permission {
+    pub fn new(permission: &str) -> Self {
+        Self(permission.to_string())
+    }
+
+    /// The permission to perform `action` on resources of type `resource`
+    pub fn of(resource: &str, action: &str) -> Self {
+        Self(format!("{resource}:{action}"))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this permission grants `required`, which has no wildcards
+    pub fn grants(&self, required: &Permission) -> bool {
+        if self.0 == WILDCARD {
+            return true;
+        }
+
+        match (self.0.split_once(':'), required.0.split_once(':')) {
+            (Some((resource, action)), Some((required_resource, required_action))) => {
+                (resource == WILDCARD || resource == required_resource)
+                    && (action == WILDCARD || action == required_action)
+            }
+            _ => self.0 == required.0,
+        }
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Permission {
+    fn from(permission: &str) -> Self {
+        Self::new(permission)
+    }
+}
+
+/// A named set of permissions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Role {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+impl Role {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            permissions: vec![],
+        }
+    }
+
+    pub fn permission(mut self, permission: &str) -> Self {
+        self.permissions.push(Permission::new(permission));
+        self
+    }
+
+    pub fn grants(&self, required: &Permission) -> bool {
+        self.permissions.iter().any(|p| p.grants(required))
+    }
+}
+
+/// A user, or any other principal, whose actions are authorized.
+///
+/// Only made of plain accessors, so it is easily implemented, or derived, for
+/// the user models of the app.
+pub trait Authorizable: Send + Sync {
+    /// Identifier of the user, compared with [`Resource::owner_id`]
+    fn auth_id(&self) -> String;
+
+    /// Names of the roles held by the user
+    fn roles(&self) -> Vec<String>;
+
+    /// Permissions granted to the user directly, on top of those of its roles
+    fn permissions(&self) -> Vec<Permission> {
+        vec![]
+    }
+}
+
+/// Something actions are performed on, a resource type such as `"post"` for actions
+/// like `create`, or a record for actions like `edit`
+pub trait Resource: Send + Sync {
+    /// Type of the resource, the first part of its permissions, e.g. `post`
+    fn resource_type(&self) -> &str;
+
+    /// Identifier of the user owning the resource, if any
+    fn owner_id(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Resource for str {
+    fn resource_type(&self) -> &str {
+        self
+    }
+}
+
+impl<R: Resource + ?Sized> Resource for &R {
+    fn resource_type(&self) -> &str {
+        (**self).resource_type()
+    }
+
+    fn owner_id(&self) -> Option<String> {
+        (**self).owner_id()
+    }
+}
+
+/// Authorizes actions with its policies, an action is allowed when any policy allows it.
+#[derive(Clone, Default)]
+pub struct Authorizer {
+    policies: Vec<Arc<dyn PolicyContract>>,
+}
+
+impl Authorizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn policy<P: PolicyContract + 'static>(mut self, policy: P) -> Self {
+        self.policies.push(Arc::new(policy));
+        self
+    }
+
+    /// Whether the user may perform `action` on the resource, policies are asked
+    /// in registration order until one allows it
+    pub async fn can<R: Resource + ?Sized>(
+        &self,
+        user: &dyn Authorizable,
+        action: &str,
+        resource: &R,
+    ) -> AppResult<bool> {
+        for policy in &self.policies {
+            if policy.can(user, action, &resource).await? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Like [`Authorizer::can`], failing with a forbidden error when not allowed
+    pub async fn authorize<R: Resource + ?Sized>(
+        &self,
+        user: &dyn Authorizable,
+        action: &str,
+        resource: &R,
+    ) -> AppResult<()> {
+        match self.can(user, action, resource).await? {
+            true => Ok(()),
+            false => Err(forbidden!(
+                "You are not allowed to {action} this {}",
+                resource.resource_type()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_wildcards() {
+        let required = Permission::of("post", "edit");
+
+        assert!(Permission::new("post:edit").grants(&required));
+        assert!(Permission::new("post:*").grants(&required));
+        assert!(Permission::new("*:edit").grants(&required));
+        assert!(Permission::new("*").grants(&required));
+        assert!(!Permission::new("post:read").grants(&required));
+        assert!(!Permission::new("comment:*").grants(&required));
+        assert!(!Permission::new("post").grants(&required));
+    }
+
+    #[test]
+    fn test_role_grants_any_of_its_permissions() {
+        let role = Role::new("moderator")
+            .permission("comment:*")
+            .permission("post:read");
+
+        assert!(role.grants(&Permission::of("comment", "delete")));
+        assert!(role.grants(&Permission::of("post", "read")));
+        assert!(!role.grants(&Permission::of("post", "delete")));
+    }
+}
@@ -0,0 +1,144 @@
+//! Built-in authorization policies.
+
+use crate::authz::contract::{PolicyContract, RoleStoreContract};
+use crate::authz::{Authorizable, Permission, Resource};
+use crate::prelude::AppResult;
+use std::sync::Arc;
+
+/// Allows actions granted by the user's roles or direct permissions, `edit` on a
+/// `post` requiring the `post:edit` permission
+pub struct RolePolicy {
+    store: Arc<dyn RoleStoreContract>,
+}
+
+impl RolePolicy {
+    pub fn new<S: RoleStoreContract + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PolicyContract for RolePolicy {
+    async fn can(
+        &self,
+        user: &dyn Authorizable,
+        action: &str,
+        resource: &dyn Resource,
+    ) -> AppResult<bool> {
+        let required = Permission::of(resource.resource_type(), action);
+
+        if user.permissions().iter().any(|p| p.grants(&required)) {
+            return Ok(true);
+        }
+
+        let roles = self.store.roles(&user.roles()).await?;
+        Ok(roles.iter().any(|role| role.grants(&required)))
+    }
+}
+
+/// Allows the given actions on resources owned by the user
+pub struct OwnerPolicy {
+    actions: Vec<String>,
+}
+
+impl OwnerPolicy {
+    pub fn new(actions: &[&str]) -> Self {
+        Self {
+            actions: actions.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PolicyContract for OwnerPolicy {
+    async fn can(
+        &self,
+        user: &dyn Authorizable,
+        action: &str,
+        resource: &dyn Resource,
+    ) -> AppResult<bool> {
+        if !self.actions.iter().any(|a| a == action) {
+            return Ok(false);
+        }
+
+        Ok(resource.owner_id() == Some(user.auth_id()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authz::Role;
+    use crate::authz::stores::InMemoryRoleStore;
+
+    struct User {
+        roles: Vec<String>,
+        permissions: Vec<Permission>,
+    }
+
+    impl Authorizable for User {
+        fn auth_id(&self) -> String {
+            "7".to_string()
+        }
+
+        fn roles(&self) -> Vec<String> {
+            self.roles.clone()
+        }
+
+        fn permissions(&self) -> Vec<Permission> {
+            self.permissions.clone()
+        }
+    }
+
+    struct Invoice(&'static str);
+
+    impl Resource for Invoice {
+        fn resource_type(&self) -> &str {
+            "invoice"
+        }
+
+        fn owner_id(&self) -> Option<String> {
+            Some(self.0.to_string())
+        }
+    }
+
+    fn user(roles: &[&str], permissions: &[&str]) -> User {
+        User {
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            permissions: permissions.iter().map(|p| Permission::new(p)).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_role_policy() {
+        let policy = RolePolicy::new(
+            InMemoryRoleStore::new()
+                .role(Role::new("accountant").permission("invoice:*"))
+                .role(Role::new("auditor").permission("*:read")),
+        );
+
+        let accountant = user(&["accountant"], &[]);
+        assert!(policy.can(&accountant, "void", &"invoice").await.unwrap());
+        assert!(!policy.can(&accountant, "read", &"payroll").await.unwrap());
+
+        let auditor = user(&["auditor", "unknown-role"], &[]);
+        assert!(policy.can(&auditor, "read", &"payroll").await.unwrap());
+        assert!(!policy.can(&auditor, "void", &"invoice").await.unwrap());
+
+        let direct = user(&[], &["payroll:export"]);
+        assert!(policy.can(&direct, "export", &"payroll").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_owner_policy() {
+        let policy = OwnerPolicy::new(&["view", "pay"]);
+        let owner = user(&[], &[]);
+
+        assert!(policy.can(&owner, "pay", &Invoice("7")).await.unwrap());
+        assert!(!policy.can(&owner, "pay", &Invoice("8")).await.unwrap());
+        assert!(!policy.can(&owner, "void", &Invoice("7")).await.unwrap());
+        assert!(!policy.can(&owner, "view", &"invoice").await.unwrap());
+    }
+}
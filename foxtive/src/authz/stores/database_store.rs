@@ -0,0 +1,89 @@
+use crate::authz::contract::RoleStoreContract;
+use crate::authz::{Permission, Role};
+use crate::database::DBPool;
+use crate::helpers::blk;
+use crate::prelude::AppResult;
+use diesel::sql_types::{Array, Text};
+use diesel::{QueryableByName, RunQueryDsl, sql_query};
+use std::collections::BTreeMap;
+
+/// Loads roles from a PostgreSQL table of role permissions, created with:
+///
+/// ```sql
+/// CREATE TABLE role_permissions (
+///     role       VARCHAR NOT NULL,
+///     permission VARCHAR NOT NULL,
+///     PRIMARY KEY (role, permission)
+/// );
+/// ```
+pub struct DatabaseRoleStore {
+    pool: DBPool,
+    table: String,
+}
+
+#[derive(QueryableByName)]
+struct RolePermissionRow {
+    #[diesel(sql_type = Text)]
+    role: String,
+    #[diesel(sql_type = Text)]
+    permission: String,
+}
+
+impl DatabaseRoleStore {
+    /// Reads the `role_permissions` table
+    pub fn new(pool: DBPool) -> Self {
+        Self {
+            pool,
+            table: "role_permissions".to_string(),
+        }
+    }
+
+    /// Reads `table` instead, optionally schema-qualified, e.g. `auth.role_permissions`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table` isn't made of letters, digits, underscores and a schema dot
+    pub fn table(mut self, table: &str) -> Self {
+        assert!(
+            !table.is_empty()
+                && table
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'),
+            "invalid role permissions table name: {table}"
+        );
+
+        self.table = table.to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleStoreContract for DatabaseRoleStore {
+    async fn roles(&self, names: &[String]) -> AppResult<Vec<Role>> {
+        let query = format!(
+            "SELECT role, permission FROM {} WHERE role = ANY($1)",
+            self.table
+        );
+        let names = names.to_vec();
+        let pool = self.pool.clone();
+
+        let rows = blk(move || -> AppResult<Vec<RolePermissionRow>> {
+            let mut conn = pool.get()?;
+            Ok(sql_query(query)
+                .bind::<Array<Text>, _>(names)
+                .load(&mut conn)?)
+        })
+        .await??;
+
+        let mut roles = BTreeMap::<String, Role>::new();
+        for row in rows {
+            roles
+                .entry(row.role.clone())
+                .or_insert_with(|| Role::new(&row.role))
+                .permissions
+                .push(Permission::new(&row.permission));
+        }
+
+        Ok(roles.into_values().collect())
+    }
+}
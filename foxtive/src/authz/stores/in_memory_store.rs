@@ -0,0 +1,42 @@
+use crate::authz::Role;
+use crate::authz::contract::RoleStoreContract;
+use crate::prelude::AppResult;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Keeps roles in memory, typically defined in code at startup
+#[derive(Default)]
+pub struct InMemoryRoleStore {
+    roles: RwLock<HashMap<String, Role>>,
+}
+
+impl InMemoryRoleStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn role(self, role: Role) -> Self {
+        self.insert(role);
+        self
+    }
+
+    /// Adds a role, replacing the one of the same name
+    pub fn insert(&self, role: Role) {
+        self.roles.write().unwrap().insert(role.name.clone(), role);
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Role> {
+        self.roles.write().unwrap().remove(name)
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleStoreContract for InMemoryRoleStore {
+    async fn roles(&self, names: &[String]) -> AppResult<Vec<Role>> {
+        let roles = self.roles.read().unwrap();
+        Ok(names
+            .iter()
+            .filter_map(|name| roles.get(name).cloned())
+            .collect())
+    }
+}
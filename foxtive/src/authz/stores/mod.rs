@@ -0,0 +1,7 @@
+#[cfg(feature = "database")]
+mod database_store;
+mod in_memory_store;
+
+#[cfg(feature = "database")]
+pub use database_store::DatabaseRoleStore;
+pub use in_memory_store::InMemoryRoleStore;
@@ -0,0 +1,348 @@
+use crate::internal_server_error;
+use crate::results::AppResult;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Marks entries that aren't plain JSON, followed by the format and compression tags,
+/// e.g. `~mg:<base64>` for gzipped MessagePack. JSON text never starts with `~`.
+const ENVELOPE_MARKER: char = '~';
+
+/// Serialization format of cached values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheFormat {
+    #[default]
+    Json,
+    /// Requires the `cache-msgpack` feature
+    MessagePack,
+    /// Requires the `cache-bincode` feature, types relying on `deserialize_any`,
+    /// e.g. untagged enums or `serde_json::Value`, aren't supported
+    Bincode,
+}
+
+/// Compression of cached values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    /// Requires the `cache-gzip` feature, level 0-9
+    Gzip(u32),
+    /// Requires the `cache-zstd` feature, level 1-22
+    Zstd(i32),
+}
+
+impl Compression {
+    pub fn gzip() -> Self {
+        Self::Gzip(6)
+    }
+
+    pub fn zstd() -> Self {
+        Self::Zstd(3)
+    }
+}
+
+/// Encodes the values stored by [`Cache`](crate::cache::Cache).
+///
+/// JSON is the default and is stored as is. Other formats and compressed values are
+/// base64 encoded and tagged, so values are decoded by the codec they were written with
+/// and switching codecs doesn't invalidate existing entries.
+///
+/// # Example
+///
+/// ```
+/// use foxtive::cache::CacheCodec;
+///
+/// let codec = CacheCodec::json();
+///
+/// let encoded = codec.encode(&vec![1, 2, 3]).unwrap();
+/// assert_eq!(encoded, "[1,2,3]");
+/// assert_eq!(codec.decode::<Vec<u8>>(&encoded).unwrap(), [1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheCodec {
+    format: CacheFormat,
+    compression: Compression,
+    min_compress_size: usize,
+}
+
+impl CacheCodec {
+    pub fn new(format: CacheFormat) -> Self {
+        Self {
+            format,
+            ..Self::default()
+        }
+    }
+
+    pub fn json() -> Self {
+        Self::new(CacheFormat::Json)
+    }
+
+    #[cfg(feature = "cache-msgpack")]
+    pub fn msgpack() -> Self {
+        Self::new(CacheFormat::MessagePack)
+    }
+
+    #[cfg(feature = "cache-bincode")]
+    pub fn bincode() -> Self {
+        Self::new(CacheFormat::Bincode)
+    }
+
+    /// Compresses serialized values of at least `min_size` bytes
+    pub fn compress(mut self, compression: Compression, min_size: usize) -> Self {
+        self.compression = compression;
+        self.min_compress_size = min_size;
+        self
+    }
+
+    pub fn format(&self) -> CacheFormat {
+        self.format
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    pub fn encode<T: Serialize + ?Sized>(&self, value: &T) -> AppResult<String> {
+        let bytes = serialize(self.format, value)?;
+
+        let compression = match self.compression {
+            Compression::None => Compression::None,
+            _ if bytes.len() < self.min_compress_size => Compression::None,
+            compression => compression,
+        };
+
+        if self.format == CacheFormat::Json && compression == Compression::None {
+            // serde_json only produces valid UTF-8
+            return Ok(String::from_utf8(bytes)?);
+        }
+
+        let bytes = compress(compression, &bytes)?;
+        Ok(format!(
+            "{ENVELOPE_MARKER}{}{}:{}",
+            format_tag(self.format),
+            compression_tag(compression),
+            STANDARD.encode(bytes)
+        ))
+    }
+
+    /// Decodes values written by any codec, as long as its features are enabled
+    pub fn decode<T: DeserializeOwned>(&self, raw: &str) -> AppResult<T> {
+        let Some(envelope) = raw.strip_prefix(ENVELOPE_MARKER) else {
+            return Ok(serde_json::from_str(raw)?);
+        };
+
+        let mut tags = envelope.chars();
+        let (Some(format), Some(compression), Some(':')) = (tags.next(), tags.next(), tags.next())
+        else {
+            return Err(internal_server_error!("Malformed cache entry"));
+        };
+
+        let bytes = STANDARD.decode(tags.as_str())?;
+        let bytes = decompress(compression, &bytes)?;
+
+        match format {
+            'j' => Ok(serde_json::from_slice(&bytes)?),
+            'm' => deserialize_msgpack(&bytes),
+            'b' => deserialize_bincode(&bytes),
+            tag => Err(internal_server_error!("Unknown cache entry format '{tag}'")),
+        }
+    }
+}
+
+fn format_tag(format: CacheFormat) -> char {
+    match format {
+        CacheFormat::Json => 'j',
+        CacheFormat::MessagePack => 'm',
+        CacheFormat::Bincode => 'b',
+    }
+}
+
+fn compression_tag(compression: Compression) -> char {
+    match compression {
+        Compression::None => 'n',
+        Compression::Gzip(_) => 'g',
+        Compression::Zstd(_) => 'z',
+    }
+}
+
+fn serialize<T: Serialize + ?Sized>(format: CacheFormat, value: &T) -> AppResult<Vec<u8>> {
+    match format {
+        CacheFormat::Json => Ok(serde_json::to_vec(value)?),
+        #[cfg(feature = "cache-msgpack")]
+        CacheFormat::MessagePack => Ok(rmp_serde::to_vec_named(value)?),
+        #[cfg(feature = "cache-bincode")]
+        CacheFormat::Bincode => Ok(bincode::serde::encode_to_vec(
+            value,
+            bincode::config::standard(),
+        )?),
+        #[allow(unreachable_patterns)]
+        format => Err(missing_feature(format_tag(format))),
+    }
+}
+
+#[cfg(feature = "cache-msgpack")]
+fn deserialize_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> AppResult<T> {
+    Ok(rmp_serde::from_slice(bytes)?)
+}
+
+#[cfg(not(feature = "cache-msgpack"))]
+fn deserialize_msgpack<T: DeserializeOwned>(_bytes: &[u8]) -> AppResult<T> {
+    Err(missing_feature('m'))
+}
+
+#[cfg(feature = "cache-bincode")]
+fn deserialize_bincode<T: DeserializeOwned>(bytes: &[u8]) -> AppResult<T> {
+    let (value, _) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+    Ok(value)
+}
+
+#[cfg(not(feature = "cache-bincode"))]
+fn deserialize_bincode<T: DeserializeOwned>(_bytes: &[u8]) -> AppResult<T> {
+    Err(missing_feature('b'))
+}
+
+fn compress(compression: Compression, bytes: &[u8]) -> AppResult<Vec<u8>> {
+    match compression {
+        Compression::None => Ok(bytes.to_vec()),
+        #[cfg(feature = "cache-gzip")]
+        Compression::Gzip(level) => {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+            encoder.write_all(bytes)?;
+            Ok(encoder.finish()?)
+        }
+        #[cfg(feature = "cache-zstd")]
+        Compression::Zstd(level) => Ok(zstd::encode_all(bytes, level)?),
+        #[allow(unreachable_patterns)]
+        compression => Err(missing_feature(compression_tag(compression))),
+    }
+}
+
+fn decompress(tag: char, bytes: &[u8]) -> AppResult<Vec<u8>> {
+    match tag {
+        'n' => Ok(bytes.to_vec()),
+        #[cfg(feature = "cache-gzip")]
+        'g' => {
+            use std::io::Read;
+
+            let mut decompressed = Vec::new();
+            flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+            Ok(decompressed)
+        }
+        #[cfg(feature = "cache-zstd")]
+        'z' => Ok(zstd::decode_all(bytes)?),
+        tag => Err(missing_feature(tag)),
+    }
+}
+
+fn missing_feature(tag: char) -> anyhow::Error {
+    let feature = match tag {
+        'm' => "cache-msgpack",
+        'b' => "cache-bincode",
+        'g' => "cache-gzip",
+        'z' => "cache-zstd",
+        _ => return internal_server_error!("Unknown cache entry tag '{tag}'"),
+    };
+
+    internal_server_error!("Cache entry requires the '{feature}' feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Order {
+        id: u64,
+        items: Vec<String>,
+    }
+
+    fn order() -> Order {
+        Order {
+            id: 7,
+            items: vec!["book".repeat(100), "pen".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_json_is_stored_as_is() {
+        let codec = CacheCodec::default();
+        let encoded = codec.encode(&order()).unwrap();
+
+        assert!(encoded.starts_with('{'));
+        assert_eq!(codec.decode::<Order>(&encoded).unwrap(), order());
+    }
+
+    #[test]
+    fn test_decodes_plain_json_regardless_of_codec() {
+        let codec = CacheCodec::json().compress(Compression::Zstd(3), 0);
+        assert_eq!(codec.decode::<Vec<u8>>("[1,2]").unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn test_malformed_envelope() {
+        let codec = CacheCodec::default();
+        assert!(codec.decode::<Vec<u8>>("~j").is_err());
+        assert!(codec.decode::<Vec<u8>>("~xn:AAAA").is_err());
+    }
+
+    #[cfg(feature = "cache-msgpack")]
+    #[test]
+    fn test_msgpack_round_trip() {
+        let codec = CacheCodec::msgpack();
+        let encoded = codec.encode(&order()).unwrap();
+
+        assert!(encoded.starts_with("~mn:"));
+        assert_eq!(codec.decode::<Order>(&encoded).unwrap(), order());
+        // other codecs read it too
+        assert_eq!(
+            CacheCodec::json().decode::<Order>(&encoded).unwrap(),
+            order()
+        );
+    }
+
+    #[cfg(feature = "cache-bincode")]
+    #[test]
+    fn test_bincode_round_trip() {
+        let codec = CacheCodec::bincode();
+        let encoded = codec.encode(&order()).unwrap();
+
+        assert!(encoded.starts_with("~bn:"));
+        assert_eq!(codec.decode::<Order>(&encoded).unwrap(), order());
+    }
+
+    #[cfg(feature = "cache-gzip")]
+    #[test]
+    fn test_gzip_above_threshold() {
+        let codec = CacheCodec::json().compress(Compression::gzip(), 100);
+
+        let large = codec.encode(&order()).unwrap();
+        assert!(large.starts_with("~jg:"));
+        assert!(large.len() < serde_json::to_string(&order()).unwrap().len());
+        assert_eq!(codec.decode::<Order>(&large).unwrap(), order());
+
+        assert_eq!(codec.encode(&[1, 2]).unwrap(), "[1,2]");
+    }
+
+    #[cfg(feature = "cache-zstd")]
+    #[test]
+    fn test_zstd_round_trip() {
+        let codec = CacheCodec::json().compress(Compression::zstd(), 0);
+        let encoded = codec.encode(&order()).unwrap();
+
+        assert!(encoded.starts_with("~jz:"));
+        assert_eq!(codec.decode::<Order>(&encoded).unwrap(), order());
+    }
+
+    #[cfg(not(feature = "cache-zstd"))]
+    #[test]
+    fn test_missing_feature_is_an_error() {
+        let codec = CacheCodec::json().compress(Compression::zstd(), 0);
+        let error = codec.encode(&order()).unwrap_err();
+        assert!(error.to_string().contains("cache-zstd"));
+    }
+}
@@ -1,11 +1,19 @@
+use crate::cache::KeyPattern;
 use crate::prelude::AppResult;
 use async_trait::async_trait;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::future::Future;
+use std::time::Duration;
 use tracing::{debug, error};
 
 /// Contract for implementing cache storage drivers
+///
+/// Values are kept until forgotten, except those written with
+/// [`put_raw_for`](Self::put_raw_for) on drivers supporting expiry. Stores that need
+/// their values to expire on every driver, like the session and idempotency ones, thus
+/// keep the expiry along with the value, ignore expired values when read, and provide a
+/// sweep for the drivers that never expire them.
 #[async_trait::async_trait]
 pub trait CacheDriverContract: Send + Sync {
     /// Retrieves all keys present in the cache
@@ -17,11 +25,11 @@ pub trait CacheDriverContract: Send + Sync {
     /// Retrieves all keys matching the specified pattern
     ///
     /// # Parameters
-    /// - `pattern`: Pattern string to match keys against
+    /// - `pattern`: Pattern to match keys against
     ///
     /// # Returns
     /// - `AppResult<Vec<String>>`: A vector of matching cache keys
-    async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>>;
+    async fn keys_by_pattern(&self, pattern: &KeyPattern) -> AppResult<Vec<String>>;
 
     /// Stores a raw string value in the cache
     ///
@@ -33,6 +41,23 @@ pub trait CacheDriverContract: Send + Sync {
     /// - `AppResult<String>`: The stored string value
     async fn put_raw(&self, key: &str, value: String) -> AppResult<String>;
 
+    /// Stores a raw string value that expires after `ttl`
+    ///
+    /// The default implementation stores it with [`put_raw`](Self::put_raw), without
+    /// expiry, as the in-memory and filesystem drivers do.
+    ///
+    /// # Parameters
+    /// - `key`: Cache key to store the value under
+    /// - `value`: String value to store
+    /// - `ttl`: How long the value is kept
+    ///
+    /// # Returns
+    /// - `AppResult<String>`: The stored string value
+    async fn put_raw_for(&self, key: &str, value: String, ttl: Duration) -> AppResult<String> {
+        let _ = ttl;
+        self.put_raw(key, value).await
+    }
+
     /// Retrieves a raw string value from the cache
     ///
     /// # Parameters
@@ -42,6 +67,77 @@ pub trait CacheDriverContract: Send + Sync {
     /// - `AppResult<Option<String>>`: The stored string value if it exists
     async fn get_raw(&self, key: &str) -> AppResult<Option<String>>;
 
+    /// Checks whether a key is present in the cache
+    ///
+    /// # Parameters
+    /// - `key`: Cache key to check
+    ///
+    /// # Returns
+    /// - `AppResult<bool>`: Whether the key exists
+    async fn has(&self, key: &str) -> AppResult<bool> {
+        Ok(self.get_raw(key).await?.is_some())
+    }
+
+    /// Stores a raw string value only if the key is absent
+    ///
+    /// The default implementation checks then stores, drivers able to do both
+    /// atomically should override it.
+    ///
+    /// # Parameters
+    /// - `key`: Cache key to store the value under
+    /// - `value`: String value to store
+    ///
+    /// # Returns
+    /// - `AppResult<bool>`: Whether the value was stored
+    async fn add_raw(&self, key: &str, value: String) -> AppResult<bool> {
+        if self.has(key).await? {
+            return Ok(false);
+        }
+
+        self.put_raw(key, value).await?;
+        Ok(true)
+    }
+
+    /// Replaces the value of a key only if it still holds `current`
+    ///
+    /// The default implementation gets, compares then stores, drivers able to do it
+    /// atomically should override it.
+    ///
+    /// # Parameters
+    /// - `key`: Cache key to store the value under
+    /// - `current`: Value the key must hold
+    /// - `value`: String value to store
+    ///
+    /// # Returns
+    /// - `AppResult<bool>`: Whether the value was replaced
+    async fn replace_raw(&self, key: &str, current: &str, value: String) -> AppResult<bool> {
+        if self.get_raw(key).await?.as_deref() != Some(current) {
+            return Ok(false);
+        }
+
+        self.put_raw(key, value).await?;
+        Ok(true)
+    }
+
+    /// Retrieves a raw string value and removes it from the cache
+    ///
+    /// The default implementation gets then forgets, drivers able to do both
+    /// atomically should override it.
+    ///
+    /// # Parameters
+    /// - `key`: Cache key to retrieve and remove
+    ///
+    /// # Returns
+    /// - `AppResult<Option<String>>`: The stored string value if it existed
+    async fn pull_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let value = self.get_raw(key).await?;
+        if value.is_some() {
+            self.forget(key).await?;
+        }
+
+        Ok(value)
+    }
+
     /// Removes a single key from the cache
     ///
     /// # Parameters
@@ -54,11 +150,11 @@ pub trait CacheDriverContract: Send + Sync {
     /// Removes all keys matching the specified pattern
     ///
     /// # Parameters
-    /// - `pattern`: Pattern string to match keys for removal
+    /// - `pattern`: Pattern to match keys for removal
     ///
     /// # Returns
     /// - `AppResult<i32>`: Number of keys removed
-    async fn forget_by_pattern(&self, pattern: &str) -> AppResult<i32>;
+    async fn forget_by_pattern(&self, pattern: &KeyPattern) -> AppResult<i32>;
 }
 
 /// Extension trait providing serialization-aware caching operations
@@ -0,0 +1,335 @@
+use crate::cache::KeyPattern;
+use crate::cache::contract::CacheDriverContract;
+use crate::database::DBPool;
+use crate::helpers::blk;
+use crate::prelude::AppResult;
+use diesel::sql_types::{Array, BigInt, Nullable, Text};
+use diesel::{PgConnection, QueryableByName, RunQueryDsl, sql_query};
+use std::time::Duration;
+
+/// Entries that haven't expired
+const LIVE: &str = "(expires_at IS NULL OR expires_at > now())";
+
+/// Longest TTL stored as an expiry, 100 years. Longer ones would overflow the Postgres
+/// interval and timestamp, the entries are kept without expiry instead
+const MAX_TTL: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+/// Stores entries in a PostgreSQL table, created with:
+///
+/// ```sql
+/// CREATE TABLE cache_entries (
+///     key        VARCHAR PRIMARY KEY,
+///     value      TEXT NOT NULL,
+///     expires_at TIMESTAMPTZ
+/// );
+/// CREATE INDEX cache_entries_expires_at ON cache_entries (expires_at);
+/// ```
+///
+/// Entries written with a [`ttl`](Self::ttl) expire after it, as do the ones written
+/// with [`put_raw_for`](CacheDriverContract::put_raw_for), expired entries are ignored
+/// and deleted by [`prune`](Self::prune). TTLs over 100 years are stored without expiry.
+#[derive(Clone)]
+pub struct DatabaseCacheDriver {
+    pool: DBPool,
+    table: String,
+    ttl: Option<Duration>,
+}
+
+#[derive(QueryableByName)]
+struct KeyRow {
+    #[diesel(sql_type = Text)]
+    key: String,
+}
+
+#[derive(QueryableByName)]
+struct ValueRow {
+    #[diesel(sql_type = Text)]
+    value: String,
+}
+
+impl DatabaseCacheDriver {
+    /// Stores entries in the `cache_entries` table, without expiry
+    pub fn new(pool: DBPool) -> Self {
+        Self {
+            pool,
+            table: "cache_entries".to_string(),
+            ttl: None,
+        }
+    }
+
+    /// Stores entries in `table` instead, optionally schema-qualified, e.g. `cache.entries`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table` isn't made of letters, digits, underscores and a schema dot
+    pub fn table(mut self, table: &str) -> Self {
+        assert!(
+            !table.is_empty()
+                && table
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'),
+            "invalid cache table name: {table}"
+        );
+
+        self.table = table.to_string();
+        self
+    }
+
+    /// Expires entries `ttl` after they are written
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Deletes the expired entries, returning how many were deleted
+    pub async fn prune(&self) -> AppResult<usize> {
+        let query = format!(
+            "DELETE FROM {} WHERE expires_at IS NOT NULL AND expires_at <= now()",
+            self.table
+        );
+
+        self.run(move |conn| Ok(sql_query(query).execute(conn)?))
+            .await
+    }
+
+    fn ttl_millis(&self) -> Option<i64> {
+        self.ttl.and_then(expiry_millis)
+    }
+
+    async fn run<R, F>(&self, query: F) -> AppResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> AppResult<R> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        blk(move || query(&mut *pool.get()?)).await?
+    }
+
+    async fn upsert(&self, key: &str, value: String, ttl: Option<i64>) -> AppResult<String> {
+        let query = format!(
+            "INSERT INTO {} (key, value, expires_at) \
+             VALUES ($1, $2, now() + $3 * interval '1 millisecond') \
+             ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, expires_at = EXCLUDED.expires_at",
+            self.table
+        );
+        let key = key.to_string();
+
+        self.run(move |conn| {
+            sql_query(query)
+                .bind::<Text, _>(&key)
+                .bind::<Text, _>(value)
+                .bind::<Nullable<BigInt>, _>(ttl)
+                .execute(conn)?;
+            Ok(key)
+        })
+        .await
+    }
+
+    async fn select_keys(&self, filter: Option<String>) -> AppResult<Vec<String>> {
+        let query = match filter {
+            Some(_) => format!(
+                "SELECT key FROM {} WHERE {LIVE} AND key LIKE $1 ESCAPE '\\'",
+                self.table
+            ),
+            None => format!("SELECT key FROM {} WHERE {LIVE}", self.table),
+        };
+
+        let rows = self
+            .run(move |conn| {
+                Ok(match filter {
+                    Some(like) => sql_query(query)
+                        .bind::<Text, _>(like)
+                        .load::<KeyRow>(conn)?,
+                    None => sql_query(query).load::<KeyRow>(conn)?,
+                })
+            })
+            .await?;
+
+        Ok(rows.into_iter().map(|row| row.key).collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheDriverContract for DatabaseCacheDriver {
+    async fn keys(&self) -> AppResult<Vec<String>> {
+        self.select_keys(None).await
+    }
+
+    async fn keys_by_pattern(&self, pattern: &KeyPattern) -> AppResult<Vec<String>> {
+        if let Some(like) = to_like(pattern) {
+            return self.select_keys(Some(like)).await;
+        }
+
+        let matcher = pattern.matcher()?;
+        let all_keys = self.keys().await?;
+
+        Ok(all_keys
+            .into_iter()
+            .filter(|key| matcher.is_match(key))
+            .collect())
+    }
+
+    async fn put_raw(&self, key: &str, value: String) -> AppResult<String> {
+        self.upsert(key, value, self.ttl_millis()).await
+    }
+
+    async fn put_raw_for(&self, key: &str, value: String, ttl: Duration) -> AppResult<String> {
+        self.upsert(key, value, expiry_millis(ttl)).await
+    }
+
+    async fn get_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let query = format!("SELECT value FROM {} WHERE key = $1 AND {LIVE}", self.table);
+        let key = key.to_string();
+
+        let rows = self
+            .run(move |conn| {
+                Ok(sql_query(query)
+                    .bind::<Text, _>(key)
+                    .load::<ValueRow>(conn)?)
+            })
+            .await?;
+
+        Ok(rows.into_iter().next().map(|row| row.value))
+    }
+
+    async fn add_raw(&self, key: &str, value: String) -> AppResult<bool> {
+        // An expired entry is replaced, a live one is kept
+        let query = format!(
+            "INSERT INTO {table} (key, value, expires_at) \
+             VALUES ($1, $2, now() + $3 * interval '1 millisecond') \
+             ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, expires_at = EXCLUDED.expires_at \
+             WHERE {table}.expires_at IS NOT NULL AND {table}.expires_at <= now()",
+            table = self.table
+        );
+        let (key, ttl) = (key.to_string(), self.ttl_millis());
+
+        let stored = self
+            .run(move |conn| {
+                Ok(sql_query(query)
+                    .bind::<Text, _>(key)
+                    .bind::<Text, _>(value)
+                    .bind::<Nullable<BigInt>, _>(ttl)
+                    .execute(conn)?)
+            })
+            .await?;
+
+        Ok(stored == 1)
+    }
+
+    async fn replace_raw(&self, key: &str, current: &str, value: String) -> AppResult<bool> {
+        let query = format!(
+            "UPDATE {} SET value = $3, expires_at = now() + $4 * interval '1 millisecond' \
+             WHERE key = $1 AND value = $2 AND {LIVE}",
+            self.table
+        );
+        let (key, current, ttl) = (key.to_string(), current.to_string(), self.ttl_millis());
+
+        let replaced = self
+            .run(move |conn| {
+                Ok(sql_query(query)
+                    .bind::<Text, _>(key)
+                    .bind::<Text, _>(current)
+                    .bind::<Text, _>(value)
+                    .bind::<Nullable<BigInt>, _>(ttl)
+                    .execute(conn)?)
+            })
+            .await?;
+
+        Ok(replaced == 1)
+    }
+
+    async fn pull_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let query = format!(
+            "DELETE FROM {} WHERE key = $1 AND {LIVE} RETURNING value",
+            self.table
+        );
+        let key = key.to_string();
+
+        let rows = self
+            .run(move |conn| {
+                Ok(sql_query(query)
+                    .bind::<Text, _>(key)
+                    .load::<ValueRow>(conn)?)
+            })
+            .await?;
+
+        Ok(rows.into_iter().next().map(|row| row.value))
+    }
+
+    async fn forget(&self, key: &str) -> AppResult<i32> {
+        let query = format!("DELETE FROM {} WHERE key = $1 AND {LIVE}", self.table);
+        let key = key.to_string();
+
+        let removed = self
+            .run(move |conn| Ok(sql_query(query).bind::<Text, _>(key).execute(conn)?))
+            .await?;
+
+        Ok(removed as i32)
+    }
+
+    async fn forget_by_pattern(&self, pattern: &KeyPattern) -> AppResult<i32> {
+        let keys = self.keys_by_pattern(pattern).await?;
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let query = format!("DELETE FROM {} WHERE key = ANY($1)", self.table);
+        let removed = self
+            .run(move |conn| {
+                Ok(sql_query(query)
+                    .bind::<Array<Text>, _>(keys)
+                    .execute(conn)?)
+            })
+            .await?;
+
+        Ok(removed as i32)
+    }
+}
+
+/// The TTL in milliseconds, `None` (no expiry) above [`MAX_TTL`]
+fn expiry_millis(ttl: Duration) -> Option<i64> {
+    (ttl <= MAX_TTL).then_some(ttl.as_millis() as i64)
+}
+
+/// `LIKE` pattern of the literal patterns, globs and regular expressions are matched
+/// on the client
+fn to_like(pattern: &KeyPattern) -> Option<String> {
+    let escape = |text: &str| {
+        text.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    };
+
+    match pattern {
+        KeyPattern::Prefix(text) => Some(format!("{}%", escape(text))),
+        KeyPattern::Suffix(text) => Some(format!("%{}", escape(text))),
+        KeyPattern::Contains(text) => Some(format!("%{}%", escape(text))),
+        KeyPattern::Glob(_) | KeyPattern::Regex(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_patterns_are_escaped_for_like() {
+        assert_eq!(
+            to_like(&KeyPattern::prefix("user_1:")).as_deref(),
+            Some("user\\_1:%")
+        );
+        assert_eq!(
+            to_like(&KeyPattern::contains("50%")).as_deref(),
+            Some("%50\\%%")
+        );
+        assert_eq!(to_like(&KeyPattern::glob("user:*")), None);
+    }
+
+    #[test]
+    fn test_very_long_ttls_do_not_expire() {
+        assert_eq!(expiry_millis(Duration::from_secs(60)), Some(60_000));
+        assert_eq!(expiry_millis(MAX_TTL), Some(MAX_TTL.as_millis() as i64));
+        assert_eq!(expiry_millis(MAX_TTL + Duration::from_secs(1)), None);
+        assert_eq!(expiry_millis(Duration::MAX), None);
+    }
+}
@@ -1,160 +1,280 @@
+use crate::cache::KeyPattern;
 use crate::cache::contract::CacheDriverContract;
 use crate::results::AppResult;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
-use tokio::sync::RwLock;
-
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+/// Stores each entry in its own file, named after the SHA-256 of the key and sharded
+/// in two levels of subdirectories by the first two bytes of the hash, e.g.
+/// `3f/a2/3fa2….cache`.
+///
+/// The first line of a file holds the key as a JSON string, so keys can be listed, and
+/// files are written to a temporary file first and renamed, so readers never see a
+/// partial write.
 #[derive(Clone)]
 pub struct FilesystemCacheDriver {
     base_path: Arc<PathBuf>,
-    // Cache for computed paths
-    path_cache: Arc<RwLock<HashMap<String, PathBuf>>>,
 }
 
 impl FilesystemCacheDriver {
     pub fn new(base_path: impl AsRef<Path>) -> Self {
         Self {
             base_path: Arc::new(PathBuf::from(base_path.as_ref())),
-            path_cache: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
-    async fn key_to_path(&self, key: &str) -> PathBuf {
-        // Check path cache first
-        if let Some(path) = self.path_cache.read().await.get(key) {
-            return path.clone();
+    fn key_to_path(&self, key: &str) -> PathBuf {
+        let hash = hex::encode(Sha256::digest(key.as_bytes()));
+        self.base_path
+            .join(&hash[0..2])
+            .join(&hash[2..4])
+            .join(format!("{hash}.cache"))
+    }
+
+    /// Writes the entry to a temporary file next to `path`, ready to be moved in place
+    async fn write_temp(&self, path: &Path, key: &str, value: &str) -> AppResult<PathBuf> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let temp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        let mut file = fs::File::create(&temp).await?;
+        let written = async {
+            file.write_all(serde_json::to_string(key)?.as_bytes())
+                .await?;
+            file.write_all(b"\n").await?;
+            file.write_all(value.as_bytes()).await?;
+            file.flush().await?;
+            AppResult::Ok(())
         }
+        .await;
 
-        // Handle empty key specially to avoid empty filename
-        let safe_key = if key.is_empty() {
-            "empty_key".to_string()
-        } else {
-            key.replace([':', '/', '\\', '<', '>', '"', '|', '?', '*'], "_")
-        };
+        if let Err(err) = written {
+            let _ = fs::remove_file(&temp).await;
+            return Err(err);
+        }
 
-        let path = self.base_path.join(format!("{safe_key}.cache"));
-        self.path_cache
-            .write()
-            .await
-            .insert(key.to_string(), path.clone());
-        path
+        Ok(temp)
     }
-}
 
-#[async_trait]
-impl CacheDriverContract for FilesystemCacheDriver {
-    async fn keys(&self) -> AppResult<Vec<String>> {
-        // Read from path cache first
-        let path_cache = self.path_cache.read().await;
-        let mut keys: Vec<String> = path_cache.keys().cloned().collect();
+    /// The value of an entry file, `None` if it holds another key
+    async fn read_entry(path: &Path, key: &str) -> std::io::Result<Option<String>> {
+        let mut reader = BufReader::new(fs::File::open(path).await?);
+
+        let mut stored_key = String::new();
+        reader.read_line(&mut stored_key).await?;
+        if serde_json::from_str::<String>(stored_key.trim_end())
+            .ok()
+            .as_deref()
+            != Some(key)
+        {
+            return Ok(None);
+        }
 
-        // Also scan the directory for any files not yet in cache
-        let mut dir = fs::read_dir(&*self.base_path).await?;
-        while let Some(entry) = dir.next_entry().await? {
-            if entry.file_type().await?.is_file()
-                && let Some(file_name) = entry.file_name().to_str()
-            {
-                // Only process .cache files
-                if let Some(stripped) = file_name.strip_suffix(".cache") {
-                    // Convert filename back to key
-                    let original_key = if stripped == "empty_key" {
-                        "".to_string()
-                    } else {
-                        stripped.to_string()
+        let mut contents = String::with_capacity(1024); // Pre-allocate with reasonable size
+        reader.read_to_string(&mut contents).await?;
+        Ok(Some(contents))
+    }
+
+    /// Key and path of every entry
+    async fn entries(&self) -> AppResult<Vec<(String, PathBuf)>> {
+        let mut entries = Vec::new();
+        for first in Self::dirs(&self.base_path).await? {
+            for second in Self::dirs(&first).await? {
+                let mut dir = fs::read_dir(&second).await?;
+                while let Some(entry) = dir.next_entry().await? {
+                    let path = entry.path();
+                    if path
+                        .extension()
+                        .is_none_or(|extension| extension != "cache")
+                    {
+                        continue;
+                    }
+
+                    // removed since the directory was read
+                    let file = match fs::File::open(&path).await {
+                        Ok(file) => file,
+                        Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                        Err(e) => return Err(e.into()),
                     };
 
-                    // Add to result if not already included from path cache
-                    if !keys.contains(&original_key) {
-                        keys.push(original_key);
+                    let mut key = String::new();
+                    BufReader::new(file).read_line(&mut key).await?;
+                    if let Ok(key) = serde_json::from_str::<String>(key.trim_end()) {
+                        entries.push((key, path));
                     }
                 }
             }
         }
 
-        Ok(keys)
+        Ok(entries)
     }
 
-    async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>> {
-        let regex = fancy_regex::Regex::new(pattern)?;
+    /// Shard subdirectories of `path`
+    async fn dirs(path: &Path) -> AppResult<Vec<PathBuf>> {
+        let mut dir = match fs::read_dir(path).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut dirs = Vec::new();
+        while let Some(entry) = dir.next_entry().await? {
+            let is_shard = entry.file_name().len() == 2 && entry.file_type().await?.is_dir();
+            if is_shard {
+                dirs.push(entry.path());
+            }
+        }
+        Ok(dirs)
+    }
+}
+
+#[async_trait]
+impl CacheDriverContract for FilesystemCacheDriver {
+    async fn keys(&self) -> AppResult<Vec<String>> {
+        Ok(self
+            .entries()
+            .await?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect())
+    }
+
+    async fn keys_by_pattern(&self, pattern: &KeyPattern) -> AppResult<Vec<String>> {
+        let matcher = pattern.matcher()?;
         let all_keys = self.keys().await?;
 
         Ok(all_keys
             .into_iter()
-            .filter(|key| matches!(regex.is_match(key), Ok(true)))
+            .filter(|key| matcher.is_match(key))
             .collect())
     }
 
     async fn put_raw(&self, key: &str, value: String) -> AppResult<String> {
-        let path = self.key_to_path(key).await;
+        let path = self.key_to_path(key);
+        let temp = self.write_temp(&path, key, &value).await?;
 
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).await?;
+        // Renaming replaces the file atomically
+        if let Err(e) = fs::rename(&temp, &path).await {
+            let _ = fs::remove_file(&temp).await;
+            return Err(e.into());
         }
 
-        let file = fs::File::create(&path).await?;
-        let mut writer = BufWriter::new(file);
-        writer.write_all(value.as_bytes()).await?;
-        writer.flush().await?;
-
         Ok(key.to_string())
     }
 
     async fn get_raw(&self, key: &str) -> AppResult<Option<String>> {
-        let path = self.key_to_path(key).await;
-
-        match fs::File::open(&path).await {
-            Ok(file) => {
-                let mut reader = BufReader::new(file);
-                let mut contents = String::with_capacity(1024); // Pre-allocate with reasonable size
-                reader.read_to_string(&mut contents).await?;
-                Ok(Some(contents))
-            }
+        match Self::read_entry(&self.key_to_path(key), key).await {
+            Ok(contents) => Ok(contents),
             Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
 
-    async fn forget(&self, key: &str) -> AppResult<i32> {
-        let path = self.key_to_path(key).await;
+    async fn has(&self, key: &str) -> AppResult<bool> {
+        let path = self.key_to_path(key);
+        Ok(fs::try_exists(&path).await?)
+    }
+
+    async fn add_raw(&self, key: &str, value: String) -> AppResult<bool> {
+        let path = self.key_to_path(key);
+        let temp = self.write_temp(&path, key, &value).await?;
+
+        // Linking fails if the file exists, so only one writer wins, and the file
+        // appears complete
+        let linked = fs::hard_link(&temp, &path).await;
+        fs::remove_file(&temp).await?;
+
+        match linked {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn replace_raw(&self, key: &str, current: &str, value: String) -> AppResult<bool> {
+        let path = self.key_to_path(key);
+
+        // Renaming claims the file, concurrent replaces of the same value get nothing
+        let claimed = path.with_extension(format!("replace-{}", uuid::Uuid::new_v4()));
+        match fs::rename(&path, &claimed).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+
+        let replacement = match Self::read_entry(&claimed, key).await {
+            Ok(stored) if stored.as_deref() == Some(current) => {
+                self.write_temp(&path, key, &value).await
+            }
+            Ok(_) => Ok(claimed.clone()),
+            Err(e) => Err(e.into()),
+        };
 
-        // Remove from path cache
-        self.path_cache.write().await.remove(key);
+        // The claimed file is put back unless replaced, linking fails if the key was
+        // added since the claim, which then wins
+        let (source, matches) = match replacement {
+            Ok(source) => {
+                let matches = source != claimed;
+                (source, matches)
+            }
+            Err(err) => {
+                let _ = fs::hard_link(&claimed, &path).await;
+                let _ = fs::remove_file(&claimed).await;
+                return Err(err);
+            }
+        };
+        let linked = fs::hard_link(&source, &path).await;
+        fs::remove_file(&claimed).await?;
+        if matches {
+            fs::remove_file(&source).await?;
+        }
 
-        match fs::remove_file(&path).await {
+        match linked {
+            Ok(_) => Ok(matches),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn pull_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let path = self.key_to_path(key);
+
+        // Renaming claims the file, concurrent pulls of the same key get nothing
+        let claimed = path.with_extension(format!("pull-{}", uuid::Uuid::new_v4()));
+        match fs::rename(&path, &claimed).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let contents = Self::read_entry(&claimed, key).await;
+        fs::remove_file(&claimed).await?;
+        Ok(contents?)
+    }
+
+    async fn forget(&self, key: &str) -> AppResult<i32> {
+        match fs::remove_file(self.key_to_path(key)).await {
             Ok(_) => Ok(1),
             Err(e) if e.kind() == ErrorKind::NotFound => Ok(0),
             Err(e) => Err(e.into()),
         }
     }
 
-    async fn forget_by_pattern(&self, pattern: &str) -> AppResult<i32> {
-        let regex = fancy_regex::Regex::new(pattern)?;
+    async fn forget_by_pattern(&self, pattern: &KeyPattern) -> AppResult<i32> {
+        let matcher = pattern.matcher()?;
         let mut removed_count = 0;
 
-        // First, collect matching keys from the path cache
-        let path_cache = self.path_cache.read().await;
-        let keys_to_remove: Vec<String> = path_cache
-            .keys()
-            .filter_map(|key| match regex.is_match(key) {
-                Ok(true) => Some(key.clone()),
-                _ => None,
-            })
-            .collect();
-        drop(path_cache); // Release the read lock
-
-        // Remove matching files and their cache entries
-        for key in keys_to_remove {
-            let path = self.key_to_path(&key).await;
-
-            // Remove from path cache
-            self.path_cache.write().await.remove(&key);
+        for (key, path) in self.entries().await? {
+            if !matcher.is_match(&key) {
+                continue;
+            }
 
-            // Remove the file
             match fs::remove_file(&path).await {
                 Ok(_) => removed_count += 1,
                 Err(e) if e.kind() == ErrorKind::NotFound => {}
@@ -177,6 +297,123 @@ mod tests {
         (driver, temp_dir)
     }
 
+    #[tokio::test]
+    async fn test_has_add_and_pull() {
+        let (driver, _temp) = setup_test_cache().await;
+
+        assert!(!driver.has("token:1").await.unwrap());
+        assert!(driver.add_raw("token:1", "a".to_string()).await.unwrap());
+        assert!(!driver.add_raw("token:1", "b".to_string()).await.unwrap());
+        assert!(driver.has("token:1").await.unwrap());
+
+        assert!(
+            !driver
+                .replace_raw("token:1", "b", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(
+            driver
+                .replace_raw("token:1", "a", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !driver
+                .replace_raw("token:2", "a", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(!driver.has("token:2").await.unwrap());
+
+        assert_eq!(driver.pull_raw("token:1").await.unwrap(), Some("c".into()));
+        assert_eq!(driver.pull_raw("token:1").await.unwrap(), None);
+        assert!(!driver.has("token:1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_hashed_into_shards() {
+        let (driver, temp) = setup_test_cache().await;
+
+        driver.put_raw("a:b", "colon".to_string()).await.unwrap();
+        driver
+            .put_raw("a_b", "underscore".to_string())
+            .await
+            .unwrap();
+        driver
+            .put_raw("line\nbreak", "newline".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(driver.get_raw("a:b").await.unwrap(), Some("colon".into()));
+        assert_eq!(
+            driver.get_raw("a_b").await.unwrap(),
+            Some("underscore".into())
+        );
+        assert_eq!(
+            driver.get_raw("line\nbreak").await.unwrap(),
+            Some("newline".into())
+        );
+
+        let path = driver.key_to_path("a:b");
+        let relative = path.strip_prefix(temp.path()).unwrap();
+        assert_eq!(relative.components().count(), 3);
+
+        // nothing left behind by the temporary files
+        let dir = path.parent().unwrap();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+
+        let mut keys = driver.keys().await.unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["a:b", "a_b", "line\nbreak"]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_pull_returns_value_once() {
+        let (driver, _temp) = setup_test_cache().await;
+        driver.put_raw("once", "value".to_string()).await.unwrap();
+
+        let pulls = (0..10).map(|_| {
+            let driver = driver.clone();
+            tokio::spawn(async move { driver.pull_raw("once").await.unwrap() })
+        });
+
+        let pulled = futures::future::join_all(pulls)
+            .await
+            .into_iter()
+            .filter(|result| matches!(result, Ok(Some(_))))
+            .count();
+        assert_eq!(pulled, 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_replace_succeeds_once() {
+        let (driver, _temp) = setup_test_cache().await;
+        driver.put_raw("once", "old".to_string()).await.unwrap();
+
+        let replaces = (0..10).map(|i| {
+            let driver = driver.clone();
+            tokio::spawn(async move {
+                driver
+                    .replace_raw("once", "old", format!("new-{i}"))
+                    .await
+                    .unwrap()
+            })
+        });
+
+        let replaced = futures::future::join_all(replaces)
+            .await
+            .into_iter()
+            .filter(|result| matches!(result, Ok(true)))
+            .count();
+        assert_eq!(replaced, 1);
+
+        let value = driver.get_raw("once").await.unwrap().unwrap();
+        assert!(value.starts_with("new-"));
+        let dir = driver.key_to_path("once").parent().unwrap().to_path_buf();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+    }
+
     #[tokio::test]
     async fn test_forget_by_pattern_basic() {
         let (driver, _temp_dir) = setup_test_cache().await;
@@ -193,7 +430,10 @@ mod tests {
         }
 
         // Test exact prefix match
-        let removed = driver.forget_by_pattern("^user:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^user:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("user:123").await.unwrap(), None);
         assert_eq!(driver.get_raw("user:456").await.unwrap(), None);
@@ -223,32 +463,47 @@ mod tests {
         }
 
         // Test case 1: Exact prefix match
-        let removed = driver.forget_by_pattern("^user:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^user:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("user:123").await.unwrap(), None);
         assert_eq!(driver.get_raw("user:456").await.unwrap(), None);
         assert!(driver.get_raw("cache:temp:1").await.unwrap().is_some());
 
         // Test case 2: Match with multiple segments
-        let removed = driver.forget_by_pattern("^cache:temp:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^cache:temp:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("cache:temp:1").await.unwrap(), None);
         assert_eq!(driver.get_raw("cache:temp:2").await.unwrap(), None);
 
         // Test case 3: Case-insensitive match
-        let removed = driver.forget_by_pattern("(?i)^session:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("(?i)^session:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("session:abc").await.unwrap(), None);
         assert_eq!(driver.get_raw("SESSION:xyz").await.unwrap(), None);
 
         // Test case 4: Pattern with special characters
-        let removed = driver.forget_by_pattern("test[.-]key").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("test[.-]key"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("test.key").await.unwrap(), None);
         assert_eq!(driver.get_raw("test-key").await.unwrap(), None);
 
         // Test case 5: Empty pattern (matches empty strings)
-        let removed = driver.forget_by_pattern("^$").await.unwrap(); // Using ^$ to match only empty strings
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^$"))
+            .await
+            .unwrap(); // Using ^$ to match only empty strings
         assert_eq!(removed, 1); // Should match only the empty key
         assert_eq!(driver.get_raw("").await.unwrap(), None);
 
@@ -262,12 +517,18 @@ mod tests {
         );
 
         // Now test the pattern match
-        let removed = driver.forget_by_pattern("^$").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^$"))
+            .await
+            .unwrap();
         assert_eq!(removed, 1);
         assert_eq!(driver.get_raw(empty_key).await.unwrap(), None);
 
         // Test case 7: Pattern with escaped special characters
-        let removed = driver.forget_by_pattern(r"special\*char").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex(r"special\*char"))
+            .await
+            .unwrap();
         assert_eq!(removed, 1);
         assert_eq!(driver.get_raw("special*char").await.unwrap(), None);
     }
@@ -290,7 +551,7 @@ mod tests {
         let handle1 = tokio::spawn(async move {
             // Pattern for 0-49
             driver_clone_1
-                .forget_by_pattern("^test:([0-4]\\d|[0-9])$")
+                .forget_by_pattern(&KeyPattern::regex("^test:([0-4]\\d|[0-9])$"))
                 .await
                 .unwrap()
         });
@@ -299,7 +560,7 @@ mod tests {
         let handle2 = tokio::spawn(async move {
             // Pattern for 50-99
             driver_clone_2
-                .forget_by_pattern("^test:[5-9]\\d$")
+                .forget_by_pattern(&KeyPattern::regex("^test:[5-9]\\d$"))
                 .await
                 .unwrap()
         });
@@ -327,7 +588,7 @@ mod tests {
         let (driver, _temp_dir) = setup_test_cache().await;
 
         // Test with invalid regex pattern
-        let result = driver.forget_by_pattern("[").await;
+        let result = driver.forget_by_pattern(&KeyPattern::regex("[")).await;
         assert!(result.is_err());
     }
 
@@ -346,7 +607,10 @@ mod tests {
             .unwrap();
 
         // Test pattern that doesn't match any keys
-        let removed = driver.forget_by_pattern("^nonexistent:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^nonexistent:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 0);
 
         // Verify original data still exists
@@ -417,7 +681,10 @@ mod tests {
         }
 
         // Test exact prefix match
-        let mut keys = driver.keys_by_pattern("^user:.*").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^user:.*"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -426,7 +693,10 @@ mod tests {
         );
 
         // Test cache prefix match
-        let mut keys = driver.keys_by_pattern("^cache:temp:.*").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^cache:temp:.*"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -453,7 +723,10 @@ mod tests {
         }
 
         // Test case-insensitive pattern
-        let mut keys = driver.keys_by_pattern("(?i)^abc").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("(?i)^abc"))
+            .await
+            .unwrap();
         // Sort case-insensitively
         keys.sort_by_key(|k| k.to_lowercase());
 
@@ -462,14 +735,20 @@ mod tests {
         assert_eq!(keys, expected, "Should match case-insensitive");
 
         // Test pattern with underscore
-        let mut keys = driver.keys_by_pattern("test_key.*").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("test_key.*"))
+            .await
+            .unwrap();
         keys.sort();
         let mut expected = vec!["test_key".to_string(), "test_key2".to_string()];
         expected.sort();
         assert_eq!(keys, expected, "Should match keys with underscore");
 
         // Test numeric prefix
-        let keys = driver.keys_by_pattern("^\\d+").await.unwrap();
+        let keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^\\d+"))
+            .await
+            .unwrap();
         assert_eq!(
             keys,
             vec!["123test".to_string()],
@@ -491,7 +770,10 @@ mod tests {
             .await
             .unwrap();
 
-        let keys = driver.keys_by_pattern("^nonexistent:.*").await.unwrap();
+        let keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^nonexistent:.*"))
+            .await
+            .unwrap();
         assert!(keys.is_empty(), "Should return empty vec for no matches");
     }
 
@@ -500,7 +782,7 @@ mod tests {
         let (driver, _temp_dir) = setup_test_cache().await;
 
         // Test with invalid regex pattern
-        let result = driver.keys_by_pattern("[").await;
+        let result = driver.keys_by_pattern(&KeyPattern::regex("[")).await;
         assert!(result.is_err(), "Should return error for invalid regex");
     }
 }
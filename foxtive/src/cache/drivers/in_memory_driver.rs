@@ -1,7 +1,22 @@
+use crate::cache::KeyPattern;
 use crate::cache::contract::CacheDriverContract;
+use crate::internal_server_error;
 use crate::results::AppResult;
 use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+
+/// First line of a snapshot file, followed by the format version
+const SNAPSHOT_MAGIC: &str = "FOXTIVE-CACHE-SNAPSHOT";
+
+const SNAPSHOT_VERSION: u32 = 1;
 
 #[derive(Clone, Default)]
 pub struct InMemoryDriver {
@@ -12,6 +27,129 @@ impl InMemoryDriver {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates a driver holding the entries of the snapshot at `path`, empty if there is
+    /// no snapshot yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot can't be read, is of an unsupported version, or
+    /// is corrupted
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use foxtive::cache::drivers::InMemoryDriver;
+    /// use std::time::Duration;
+    ///
+    /// # async fn run() -> foxtive::results::AppResult<()> {
+    /// let driver = InMemoryDriver::from_snapshot("./cache.snapshot")?;
+    /// driver.snapshot_every("./cache.snapshot", Duration::from_secs(60));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_snapshot(path: impl AsRef<Path>) -> AppResult<Self> {
+        let driver = Self::new();
+        driver.restore(path)?;
+        Ok(driver)
+    }
+
+    /// Writes every entry to `path`, returning how many were written
+    ///
+    /// The snapshot is written to a temporary file and renamed, an interrupted
+    /// snapshot leaves the previous one in place.
+    pub fn snapshot(&self, path: impl AsRef<Path>) -> AppResult<usize> {
+        let path = path.as_ref();
+        let entries: BTreeMap<String, String> = self
+            .storage
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+
+        let body = serde_json::to_string(&entries)?;
+        let checksum = hex::encode(Sha256::digest(body.as_bytes()));
+        let contents = format!("{SNAPSHOT_MAGIC} {SNAPSHOT_VERSION}\n{checksum}\n{body}");
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let temp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        if let Err(e) = std::fs::write(&temp, contents).and_then(|_| std::fs::rename(&temp, path)) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e.into());
+        }
+
+        Ok(entries.len())
+    }
+
+    /// Loads the entries of the snapshot at `path` over the current ones, returning
+    /// how many were loaded, `0` if there is no snapshot
+    pub fn restore(&self, path: impl AsRef<Path>) -> AppResult<usize> {
+        let path = path.as_ref();
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let entries = parse_snapshot(&contents).map_err(|reason| {
+            internal_server_error!("Invalid cache snapshot {path:?}: {reason}")
+        })?;
+
+        let count = entries.len();
+        for (key, value) in entries {
+            self.storage.insert(key, value);
+        }
+
+        debug!("[cache] restored {count} entries from {path:?}");
+        Ok(count)
+    }
+
+    /// Snapshots the entries to `path` every `interval`, abort the returned handle
+    /// to stop
+    pub fn snapshot_every(&self, path: impl Into<PathBuf>, interval: Duration) -> JoinHandle<()> {
+        let driver = self.clone();
+        let path = path.into();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                let (driver, path) = (driver.clone(), path.clone());
+                let result = tokio::task::spawn_blocking(move || driver.snapshot(&path)).await;
+                match result {
+                    Ok(Ok(count)) => debug!("[cache] snapshot of {count} entries written"),
+                    Ok(Err(err)) => error!("[cache] failed to write snapshot: {err}"),
+                    Err(err) => error!("[cache] snapshot task failed: {err}"),
+                }
+            }
+        })
+    }
+}
+
+/// Entries of a snapshot, or why it can't be trusted
+fn parse_snapshot(contents: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut lines = contents.splitn(3, '\n');
+    let (Some(header), Some(checksum), Some(body)) = (lines.next(), lines.next(), lines.next())
+    else {
+        return Err("truncated".to_string());
+    };
+
+    let version = header
+        .strip_prefix(SNAPSHOT_MAGIC)
+        .and_then(|version| version.trim().parse::<u32>().ok())
+        .ok_or("not a cache snapshot")?;
+
+    if version != SNAPSHOT_VERSION {
+        return Err(format!("unsupported version {version}"));
+    }
+
+    if hex::encode(Sha256::digest(body.as_bytes())) != checksum {
+        return Err("checksum mismatch".to_string());
+    }
+
+    serde_json::from_str(body).map_err(|err| err.to_string())
 }
 
 #[async_trait::async_trait]
@@ -24,13 +162,13 @@ impl CacheDriverContract for InMemoryDriver {
             .collect())
     }
 
-    async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>> {
-        let regex = fancy_regex::Regex::new(pattern)?;
+    async fn keys_by_pattern(&self, pattern: &KeyPattern) -> AppResult<Vec<String>> {
+        let matcher = pattern.matcher()?;
         let all_keys = self.keys().await?;
 
         Ok(all_keys
             .into_iter()
-            .filter(|key| matches!(regex.is_match(key), Ok(true)))
+            .filter(|key| matcher.is_match(key))
             .collect())
     }
 
@@ -43,6 +181,34 @@ impl CacheDriverContract for InMemoryDriver {
         Ok(self.storage.get(key).map(|value| value.value().clone()))
     }
 
+    async fn has(&self, key: &str) -> AppResult<bool> {
+        Ok(self.storage.contains_key(key))
+    }
+
+    async fn add_raw(&self, key: &str, value: String) -> AppResult<bool> {
+        match self.storage.entry(key.to_string()) {
+            Entry::Occupied(_) => Ok(false),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                Ok(true)
+            }
+        }
+    }
+
+    async fn replace_raw(&self, key: &str, current: &str, value: String) -> AppResult<bool> {
+        match self.storage.get_mut(key) {
+            Some(mut entry) if entry.as_str() == current => {
+                *entry = value;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn pull_raw(&self, key: &str) -> AppResult<Option<String>> {
+        Ok(self.storage.remove(key).map(|(_, value)| value))
+    }
+
     async fn forget(&self, key: &str) -> AppResult<i32> {
         Ok(if self.storage.remove(key).is_some() {
             1
@@ -51,8 +217,8 @@ impl CacheDriverContract for InMemoryDriver {
         })
     }
 
-    async fn forget_by_pattern(&self, pattern: &str) -> AppResult<i32> {
-        let regex = fancy_regex::Regex::new(pattern)?;
+    async fn forget_by_pattern(&self, pattern: &KeyPattern) -> AppResult<i32> {
+        let matcher = pattern.matcher()?;
         let mut removed_count = 0;
 
         // Collect keys to remove to avoid mutation during iteration
@@ -61,10 +227,7 @@ impl CacheDriverContract for InMemoryDriver {
             .iter()
             .filter_map(|entry| {
                 let key = entry.key();
-                match regex.is_match(key) {
-                    Ok(true) => Some(key.clone()),
-                    _ => None,
-                }
+                matcher.is_match(key).then(|| key.clone())
             })
             .collect();
 
@@ -160,7 +323,10 @@ mod tests {
         }
 
         // Test exact prefix match
-        let mut keys = driver.keys_by_pattern("^prefix:.*").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^prefix:.*"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -169,7 +335,10 @@ mod tests {
         );
 
         // Test single key match
-        let keys = driver.keys_by_pattern("^different$").await.unwrap();
+        let keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^different$"))
+            .await
+            .unwrap();
         assert_eq!(
             keys,
             vec!["different".to_string()],
@@ -196,7 +365,10 @@ mod tests {
         }
 
         // Test case-insensitive match
-        let mut keys = driver.keys_by_pattern("(?i)^abc\\d+").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("(?i)^abc\\d+"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -205,7 +377,10 @@ mod tests {
         );
 
         // Test pattern with special characters
-        let mut keys = driver.keys_by_pattern("test[.-]key").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("test[.-]key"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -222,15 +397,80 @@ mod tests {
         driver.put_raw("test1", "value1".to_string()).await.unwrap();
         driver.put_raw("test2", "value2".to_string()).await.unwrap();
 
-        let keys = driver.keys_by_pattern("^nonexistent:.*").await.unwrap();
+        let keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^nonexistent:.*"))
+            .await
+            .unwrap();
         assert!(keys.is_empty(), "Should return empty vec for no matches");
     }
 
+    #[tokio::test]
+    async fn test_keys_by_literal_and_glob_patterns() {
+        let driver = InMemoryDriver::new();
+
+        for key in ["user.1", "userx1", "tmp:user.1", "user.12"] {
+            driver.put_raw(key, "value".to_string()).await.unwrap();
+        }
+
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::prefix("user."))
+            .await
+            .unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["user.1".to_string(), "user.12".to_string()]);
+
+        let keys = driver
+            .keys_by_pattern(&KeyPattern::glob("user?1"))
+            .await
+            .unwrap();
+        assert_eq!(keys.len(), 2, "Glob should match user.1 and userx1");
+
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::contains(":user."))
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_has_add_and_pull() {
+        let driver = InMemoryDriver::new();
+
+        assert!(!driver.has("token:1").await.unwrap());
+        assert!(driver.add_raw("token:1", "a".to_string()).await.unwrap());
+        assert!(!driver.add_raw("token:1", "b".to_string()).await.unwrap());
+        assert!(driver.has("token:1").await.unwrap());
+
+        assert!(
+            !driver
+                .replace_raw("token:1", "b", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(
+            driver
+                .replace_raw("token:1", "a", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !driver
+                .replace_raw("token:2", "a", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(!driver.has("token:2").await.unwrap());
+
+        assert_eq!(driver.pull_raw("token:1").await.unwrap(), Some("c".into()));
+        assert_eq!(driver.pull_raw("token:1").await.unwrap(), None);
+        assert!(!driver.has("token:1").await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_keys_by_pattern_invalid_regex() {
         let driver = InMemoryDriver::new();
 
-        let result = driver.keys_by_pattern("[").await;
+        let result = driver.keys_by_pattern(&KeyPattern::regex("[")).await;
         assert!(result.is_err(), "Should return error for invalid regex");
     }
 
@@ -242,7 +482,10 @@ mod tests {
         driver.put_raw("test1", "value1".to_string()).await.unwrap();
         driver.put_raw("test2", "value2".to_string()).await.unwrap();
 
-        let mut keys = driver.keys_by_pattern("").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex(""))
+            .await
+            .unwrap();
         keys.sort();
 
         // Empty pattern in regex matches everything
@@ -292,26 +535,38 @@ mod tests {
         }
 
         // Test case 1: Exact prefix match
-        let removed = driver.forget_by_pattern("^user:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^user:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("user:123").await.unwrap(), None);
         assert_eq!(driver.get_raw("user:456").await.unwrap(), None);
         assert!(driver.get_raw("cache:temp:1").await.unwrap().is_some());
 
         // Test case 2: Match with multiple segments
-        let removed = driver.forget_by_pattern("^cache:temp:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^cache:temp:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("cache:temp:1").await.unwrap(), None);
         assert_eq!(driver.get_raw("cache:temp:2").await.unwrap(), None);
 
         // Test case 3: Case-insensitive match
-        let removed = driver.forget_by_pattern("(?i)^session:.*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("(?i)^session:.*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("session:abc").await.unwrap(), None);
         assert_eq!(driver.get_raw("SESSION:xyz").await.unwrap(), None);
 
         // Test case 4: Pattern with special characters
-        let removed = driver.forget_by_pattern("test[.-]key").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("test[.-]key"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("test.key").await.unwrap(), None);
         assert_eq!(driver.get_raw("test-key").await.unwrap(), None);
@@ -326,16 +581,25 @@ mod tests {
             .put_raw("key2", "value2".to_string())
             .await
             .unwrap();
-        let removed = driver_empty.forget_by_pattern(".*").await.unwrap();
+        let removed = driver_empty
+            .forget_by_pattern(&KeyPattern::regex(".*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
 
         // Test case 6: Pattern matching empty key
-        let removed = driver.forget_by_pattern("^$").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex("^$"))
+            .await
+            .unwrap();
         assert_eq!(removed, 1);
         assert_eq!(driver.get_raw("").await.unwrap(), None);
 
         // Test case 7: Pattern with escaped special characters
-        let removed = driver.forget_by_pattern(r"special\*char").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::regex(r"special\*char"))
+            .await
+            .unwrap();
         assert_eq!(removed, 1);
         assert_eq!(driver.get_raw("special*char").await.unwrap(), None);
     }
@@ -359,7 +623,7 @@ mod tests {
         let handle1 = tokio::spawn(async move {
             // Pattern for 0-49: matches both single and double digits
             driver
-                .forget_by_pattern("^test:([0-4]\\d|[0-9])$")
+                .forget_by_pattern(&KeyPattern::regex("^test:([0-4]\\d|[0-9])$"))
                 .await
                 .unwrap()
         });
@@ -368,7 +632,7 @@ mod tests {
         let handle2 = tokio::spawn(async move {
             // Pattern for 50-99: matches both single and double digits
             driver_clone_2
-                .forget_by_pattern("^test:[5-9]\\d$")
+                .forget_by_pattern(&KeyPattern::regex("^test:[5-9]\\d$"))
                 .await
                 .unwrap()
         });
@@ -386,4 +650,53 @@ mod tests {
         let remaining = driver_clone.storage.iter().count();
         assert_eq!(remaining, 0, "Some keys remained in storage: {remaining}");
     }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cache.snapshot");
+
+        // no snapshot yet
+        assert_eq!(InMemoryDriver::new().restore(&path).unwrap(), 0);
+
+        let driver = InMemoryDriver::new();
+        driver.put_raw("user:1", "ada".to_string()).await.unwrap();
+        driver
+            .put_raw("multi\nline", "a\nb".to_string())
+            .await
+            .unwrap();
+        assert_eq!(driver.snapshot(&path).unwrap(), 2);
+
+        let restored = InMemoryDriver::from_snapshot(&path).unwrap();
+        assert_eq!(
+            restored.get_raw("user:1").await.unwrap(),
+            Some("ada".into())
+        );
+        assert_eq!(
+            restored.get_raw("multi\nline").await.unwrap(),
+            Some("a\nb".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_corruption_is_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cache.snapshot");
+
+        let driver = InMemoryDriver::new();
+        driver.put_raw("key", "value".to_string()).await.unwrap();
+        driver.snapshot(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replace("value", "v4lue")).unwrap();
+        let err = InMemoryDriver::from_snapshot(&path).err().unwrap();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        std::fs::write(&path, contents.replacen(" 1\n", " 9\n", 1)).unwrap();
+        let err = InMemoryDriver::from_snapshot(&path).err().unwrap();
+        assert!(err.to_string().contains("unsupported version 9"));
+
+        std::fs::write(&path, &contents[..contents.len() / 2]).unwrap();
+        assert!(InMemoryDriver::from_snapshot(&path).is_err());
+    }
 }
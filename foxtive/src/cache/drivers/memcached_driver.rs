@@ -0,0 +1,563 @@
+use crate::cache::KeyPattern;
+use crate::cache::contract::CacheDriverContract;
+use crate::internal_server_error;
+use crate::results::AppResult;
+use deadpool::managed::{self, Metrics, RecycleError, RecycleResult};
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Longest key memcached accepts
+const MAX_KEY_LENGTH: usize = 250;
+
+/// Cache driver storing entries in memcached, over its text protocol
+///
+/// Keys are limited to 250 bytes without whitespace or control characters. Listing
+/// keys uses `lru_crawler metadump`, available since memcached 1.4.31, and
+/// [`pull_raw`](CacheDriverContract::pull_raw) uses the meta commands of memcached 1.6.
+///
+/// # Example
+///
+/// ```no_run
+/// use foxtive::cache::{Cache, drivers::MemcachedCacheDriver};
+/// use std::sync::Arc;
+///
+/// # fn run() -> foxtive::results::AppResult<()> {
+/// let driver = MemcachedCacheDriver::new("127.0.0.1:11211", 16)?;
+/// let cache = Cache::new(Arc::new(driver));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct MemcachedCacheDriver {
+    pool: managed::Pool<Manager>,
+}
+
+impl MemcachedCacheDriver {
+    /// Creates a driver for the server at `address` (`host:port`), connections are
+    /// opened on first use, up to `max_connections` at a time
+    pub fn new(address: impl Into<String>, max_connections: usize) -> AppResult<Self> {
+        let manager = Manager {
+            address: address.into(),
+        };
+
+        let pool = managed::Pool::builder(manager)
+            .max_size(max_connections)
+            .build()
+            .map_err(|err| internal_server_error!("Failed to create memcached pool: {err}"))?;
+
+        Ok(Self { pool })
+    }
+
+    async fn connection(&self) -> AppResult<managed::Object<Manager>> {
+        self.pool
+            .get()
+            .await
+            .map_err(|err| internal_server_error!("Failed to connect to memcached: {err}"))
+    }
+
+    /// Stores with the `set` or `add` command, `exptime` as memcached understands it
+    async fn store(&self, command: &str, key: &str, value: &str, exptime: u64) -> AppResult<bool> {
+        let key = validate_key(key)?;
+        let mut conn = self.connection().await?;
+
+        let request = format!("{command} {key} 0 {exptime} {}\r\n{value}\r\n", value.len());
+        match conn.request(&request).await?.as_str() {
+            "STORED" => Ok(true),
+            "NOT_STORED" => Ok(false),
+            reply => Err(conn.unexpected(command, reply)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheDriverContract for MemcachedCacheDriver {
+    async fn keys(&self) -> AppResult<Vec<String>> {
+        let mut conn = self.connection().await?;
+        conn.send("lru_crawler metadump all\r\n").await?;
+
+        let mut keys = Vec::new();
+        loop {
+            let line = conn.read_line().await?;
+            if line == "END" {
+                break;
+            }
+
+            let key = line
+                .split(' ')
+                .find_map(|field| field.strip_prefix("key="))
+                .ok_or_else(|| conn.unexpected("lru_crawler metadump", &line))?;
+            keys.push(percent_decode(key));
+        }
+
+        Ok(keys)
+    }
+
+    async fn keys_by_pattern(&self, pattern: &KeyPattern) -> AppResult<Vec<String>> {
+        let matcher = pattern.matcher()?;
+        let all_keys = self.keys().await?;
+
+        Ok(all_keys
+            .into_iter()
+            .filter(|key| matcher.is_match(key))
+            .collect())
+    }
+
+    async fn put_raw(&self, key: &str, value: String) -> AppResult<String> {
+        self.store("set", key, &value, 0).await?;
+        Ok(key.to_string())
+    }
+
+    async fn put_raw_for(&self, key: &str, value: String, ttl: Duration) -> AppResult<String> {
+        self.store("set", key, &value, exptime(ttl)).await?;
+        Ok(key.to_string())
+    }
+
+    async fn get_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let key = validate_key(key)?;
+        let mut conn = self.connection().await?;
+
+        let reply = conn.request(&format!("get {key}\r\n")).await?;
+        if reply == "END" {
+            return Ok(None);
+        }
+
+        // VALUE <key> <flags> <bytes>
+        let length = reply
+            .strip_prefix("VALUE ")
+            .and_then(|rest| rest.rsplit(' ').next())
+            .and_then(|length| length.parse::<usize>().ok())
+            .ok_or_else(|| conn.unexpected("get", &reply))?;
+
+        let value = conn.read_data(length).await?;
+        match conn.read_line().await?.as_str() {
+            "END" => Ok(Some(value)),
+            reply => Err(conn.unexpected("get", reply)),
+        }
+    }
+
+    async fn add_raw(&self, key: &str, value: String) -> AppResult<bool> {
+        self.store("add", key, &value, 0).await
+    }
+
+    async fn replace_raw(&self, key: &str, current: &str, value: String) -> AppResult<bool> {
+        let key = validate_key(key)?;
+        let mut conn = self.connection().await?;
+
+        let Some((stored, cas)) = conn.get_with_cas(key).await? else {
+            return Ok(false);
+        };
+        if stored != current {
+            return Ok(false);
+        }
+
+        // Storing with the CAS of the read value fails if it changed since
+        let request = format!("ms {key} {} C{cas}\r\n{value}\r\n", value.len());
+        match conn.request(&request).await?.as_str() {
+            "HD" => Ok(true),
+            "EX" | "NF" | "NS" => Ok(false),
+            reply => Err(conn.unexpected("ms", reply)),
+        }
+    }
+
+    async fn pull_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let key = validate_key(key)?;
+        let mut conn = self.connection().await?;
+
+        loop {
+            let Some((value, cas)) = conn.get_with_cas(key).await? else {
+                return Ok(None);
+            };
+
+            // Deleting with the CAS of the read value, only one caller deletes it
+            match conn
+                .request(&format!("md {key} C{cas}\r\n"))
+                .await?
+                .as_str()
+            {
+                "HD" => return Ok(Some(value)),
+                "NF" => return Ok(None),
+                // changed since read, try again
+                "EX" => continue,
+                reply => return Err(conn.unexpected("md", reply)),
+            }
+        }
+    }
+
+    async fn forget(&self, key: &str) -> AppResult<i32> {
+        let key = validate_key(key)?;
+        let mut conn = self.connection().await?;
+
+        match conn.request(&format!("delete {key}\r\n")).await?.as_str() {
+            "DELETED" => Ok(1),
+            "NOT_FOUND" => Ok(0),
+            reply => Err(conn.unexpected("delete", reply)),
+        }
+    }
+
+    async fn forget_by_pattern(&self, pattern: &KeyPattern) -> AppResult<i32> {
+        let mut removed = 0;
+        for key in self.keys_by_pattern(pattern).await? {
+            removed += self.forget(&key).await?;
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Memcached reads an exptime over 30 days as a Unix timestamp, and 0 as no expiry
+fn exptime(ttl: Duration) -> u64 {
+    const MAX_RELATIVE: u64 = 30 * 24 * 60 * 60;
+
+    let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+    match seconds {
+        0 => 1,
+        seconds if seconds > MAX_RELATIVE => chrono::Utc::now().timestamp() as u64 + seconds,
+        seconds => seconds,
+    }
+}
+
+fn validate_key(key: &str) -> AppResult<&str> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LENGTH
+        && !key
+            .chars()
+            .any(|char| char.is_whitespace() || char.is_control());
+
+    match valid {
+        true => Ok(key),
+        false => Err(internal_server_error!("Invalid memcached key '{key}'")),
+    }
+}
+
+/// Decodes the URL-encoded keys of `lru_crawler metadump`
+fn percent_decode(key: &str) -> String {
+    let bytes = key.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = (bytes[index] == b'%')
+            .then(|| key.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+struct Manager {
+    address: String,
+}
+
+impl managed::Manager for Manager {
+    type Type = Connection;
+    type Error = io::Error;
+
+    async fn create(&self) -> Result<Connection, io::Error> {
+        Ok(Connection {
+            stream: BufReader::new(TcpStream::connect(&self.address).await?),
+            broken: false,
+        })
+    }
+
+    async fn recycle(&self, conn: &mut Connection, _: &Metrics) -> RecycleResult<io::Error> {
+        match conn.broken {
+            true => Err(RecycleError::message("connection left in an unknown state")),
+            false => Ok(()),
+        }
+    }
+}
+
+struct Connection {
+    stream: BufReader<TcpStream>,
+    /// Set when a reply wasn't read completely, the connection is then discarded
+    broken: bool,
+}
+
+impl Connection {
+    async fn send(&mut self, request: &str) -> AppResult<()> {
+        self.broken = true;
+        self.stream.get_mut().write_all(request.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Sends the request and reads the first line of the reply
+    async fn request(&mut self, request: &str) -> AppResult<String> {
+        self.send(request).await?;
+        self.read_line().await
+    }
+
+    async fn read_line(&mut self) -> AppResult<String> {
+        let mut line = String::new();
+        if self.stream.read_line(&mut line).await? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let line = line.trim_end_matches(['\r', '\n']).to_string();
+        if line == "ERROR" || line.starts_with("CLIENT_ERROR") || line.starts_with("SERVER_ERROR") {
+            return Err(internal_server_error!("Memcached error: {line}"));
+        }
+
+        // Every reply this driver reads ends with one of these lines
+        self.broken = !matches!(
+            line.as_str(),
+            "STORED"
+                | "NOT_STORED"
+                | "END"
+                | "DELETED"
+                | "NOT_FOUND"
+                | "EN"
+                | "HD"
+                | "NF"
+                | "EX"
+                | "NS"
+        );
+        Ok(line)
+    }
+
+    /// The value of `key` and its CAS token, `None` if it is absent
+    async fn get_with_cas(&mut self, key: &str) -> AppResult<Option<(String, String)>> {
+        let reply = self.request(&format!("mg {key} v c\r\n")).await?;
+        if reply == "EN" {
+            return Ok(None);
+        }
+
+        // VA <bytes> c<cas>
+        let mut fields = reply.split(' ');
+        let (Some("VA"), Some(length), Some(cas)) = (
+            fields.next(),
+            fields
+                .next()
+                .and_then(|length| length.parse::<usize>().ok()),
+            fields.find_map(|flag| flag.strip_prefix('c')),
+        ) else {
+            return Err(self.unexpected("mg", &reply));
+        };
+        let cas = cas.to_string();
+
+        Ok(Some((self.read_data(length).await?, cas)))
+    }
+
+    /// Reads a data block of `length` bytes and its line ending
+    async fn read_data(&mut self, length: usize) -> AppResult<String> {
+        let mut data = vec![0; length + 2];
+        self.stream.read_exact(&mut data).await?;
+        data.truncate(length);
+        Ok(String::from_utf8(data)?)
+    }
+
+    fn unexpected(&mut self, command: &str, reply: &str) -> crate::Error {
+        self.broken = true;
+        internal_server_error!("Unexpected memcached reply to {command}: {reply}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    /// Serves the subset of the memcached protocol the driver uses
+    async fn fake_memcached() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let store: Arc<Mutex<HashMap<String, (String, u64)>>> = Arc::default();
+
+        tokio::spawn(async move {
+            let mut cas = 0;
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                let store = store.clone();
+                cas += 1_000;
+                let mut next_cas = cas;
+
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(socket);
+                    let mut line = String::new();
+                    while stream.read_line(&mut line).await.unwrap() > 0 {
+                        let parts: Vec<String> =
+                            line.split_whitespace().map(str::to_string).collect();
+                        line.clear();
+
+                        let reply = match parts[0].as_str() {
+                            "set" | "add" => {
+                                let mut data = vec![0; parts[4].parse::<usize>().unwrap() + 2];
+                                stream.read_exact(&mut data).await.unwrap();
+                                data.truncate(data.len() - 2);
+
+                                let mut store = store.lock().unwrap();
+                                next_cas += 1;
+                                if parts[0] == "add" && store.contains_key(&parts[1]) {
+                                    "NOT_STORED\r\n".to_string()
+                                } else {
+                                    let value = String::from_utf8(data).unwrap();
+                                    store.insert(parts[1].clone(), (value, next_cas));
+                                    "STORED\r\n".to_string()
+                                }
+                            }
+                            "get" => match store.lock().unwrap().get(&parts[1]) {
+                                Some((value, _)) => format!(
+                                    "VALUE {} 0 {}\r\n{value}\r\nEND\r\n",
+                                    parts[1],
+                                    value.len()
+                                ),
+                                None => "END\r\n".to_string(),
+                            },
+                            "delete" => match store.lock().unwrap().remove(&parts[1]) {
+                                Some(_) => "DELETED\r\n".to_string(),
+                                None => "NOT_FOUND\r\n".to_string(),
+                            },
+                            "mg" => match store.lock().unwrap().get(&parts[1]) {
+                                Some((value, cas)) => {
+                                    format!("VA {} c{cas}\r\n{value}\r\n", value.len())
+                                }
+                                None => "EN\r\n".to_string(),
+                            },
+                            "ms" => {
+                                let mut data = vec![0; parts[2].parse::<usize>().unwrap() + 2];
+                                stream.read_exact(&mut data).await.unwrap();
+                                data.truncate(data.len() - 2);
+
+                                let mut store = store.lock().unwrap();
+                                let cas = parts[3][1..].parse::<u64>().unwrap();
+                                next_cas += 1;
+                                match store.get(&parts[1]) {
+                                    Some((_, stored)) if *stored == cas => {
+                                        let value = String::from_utf8(data).unwrap();
+                                        store.insert(parts[1].clone(), (value, next_cas));
+                                        "HD\r\n".to_string()
+                                    }
+                                    Some(_) => "EX\r\n".to_string(),
+                                    None => "NF\r\n".to_string(),
+                                }
+                            }
+                            "md" => {
+                                let mut store = store.lock().unwrap();
+                                let cas = parts[2][1..].parse::<u64>().unwrap();
+                                match store.get(&parts[1]) {
+                                    Some((_, stored)) if *stored == cas => {
+                                        store.remove(&parts[1]);
+                                        "HD\r\n".to_string()
+                                    }
+                                    Some(_) => "EX\r\n".to_string(),
+                                    None => "NF\r\n".to_string(),
+                                }
+                            }
+                            "lru_crawler" => {
+                                let mut reply: String = store
+                                    .lock()
+                                    .unwrap()
+                                    .keys()
+                                    .map(|key| {
+                                        format!(
+                                            "key={} exp=-1 la=0 cas=1\r\n",
+                                            key.replace(':', "%3A")
+                                        )
+                                    })
+                                    .collect();
+                                reply.push_str("END\r\n");
+                                reply
+                            }
+                            _ => "ERROR\r\n".to_string(),
+                        };
+
+                        stream.get_mut().write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        address
+    }
+
+    #[tokio::test]
+    async fn test_driver_round_trip() {
+        let driver = MemcachedCacheDriver::new(fake_memcached().await, 4).unwrap();
+
+        driver
+            .put_raw("user:1", "ada\r\nlovelace".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            driver.get_raw("user:1").await.unwrap(),
+            Some("ada\r\nlovelace".into())
+        );
+        assert!(driver.has("user:1").await.unwrap());
+        assert_eq!(driver.get_raw("user:2").await.unwrap(), None);
+
+        assert!(driver.add_raw("user:2", "grace".into()).await.unwrap());
+        assert!(!driver.add_raw("user:2", "other".into()).await.unwrap());
+
+        assert!(
+            !driver
+                .replace_raw("user:2", "other", "ada".into())
+                .await
+                .unwrap()
+        );
+        assert!(
+            driver
+                .replace_raw("user:2", "grace", "hopper".into())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !driver
+                .replace_raw("user:3", "grace", "hopper".into())
+                .await
+                .unwrap()
+        );
+
+        let mut keys = driver.keys().await.unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["user:1", "user:2"]);
+
+        assert_eq!(
+            driver.pull_raw("user:2").await.unwrap(),
+            Some("hopper".into())
+        );
+        assert_eq!(driver.pull_raw("user:2").await.unwrap(), None);
+
+        assert_eq!(
+            driver
+                .forget_by_pattern(&KeyPattern::prefix("user:"))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(driver.forget("user:1").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_keys_are_rejected() {
+        let driver = MemcachedCacheDriver::new(fake_memcached().await, 1).unwrap();
+
+        assert!(driver.get_raw("with space").await.is_err());
+        assert!(driver.put_raw(&"k".repeat(251), "v".into()).await.is_err());
+        assert_eq!(percent_decode("a%3Ab%2"), "a:b%2");
+    }
+
+    #[test]
+    fn test_exptime() {
+        assert_eq!(exptime(Duration::from_millis(1)), 1);
+        assert_eq!(exptime(Duration::from_millis(1500)), 2);
+        assert_eq!(exptime(Duration::from_secs(60)), 60);
+
+        let absolute = exptime(Duration::from_secs(31 * 24 * 60 * 60));
+        assert!(absolute > chrono::Utc::now().timestamp() as u64);
+    }
+}
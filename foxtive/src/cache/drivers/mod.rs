@@ -1,10 +1,17 @@
+#[cfg(feature = "cache-database")]
+mod database_driver;
 #[cfg(feature = "cache-filesystem")]
 mod filesystem_driver;
 #[cfg(feature = "cache-in-memory")]
 mod in_memory_driver;
+#[cfg(feature = "cache-memcached")]
+mod memcached_driver;
 #[cfg(feature = "cache-redis")]
 mod redis_driver;
 
+#[cfg(feature = "cache-database")]
+pub use database_driver::DatabaseCacheDriver;
+
 #[cfg(feature = "cache-filesystem")]
 pub use filesystem_driver::FilesystemCacheDriver;
 
@@ -13,3 +20,6 @@ pub use redis_driver::RedisCacheDriver;
 
 #[cfg(feature = "cache-in-memory")]
 pub use in_memory_driver::InMemoryDriver;
+
+#[cfg(feature = "cache-memcached")]
+pub use memcached_driver::MemcachedCacheDriver;
@@ -1,7 +1,10 @@
+use crate::cache::KeyPattern;
 use crate::cache::contract::CacheDriverContract;
 use crate::prelude::Redis;
+use crate::redis::RedisContract;
 use crate::results::AppResult;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct RedisCacheDriver {
@@ -21,57 +24,70 @@ impl CacheDriverContract for RedisCacheDriver {
         self.redis.keys().await
     }
 
-    async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>> {
-        // Use Redis KEYS command with the provided pattern directly
-        // Redis patterns use glob-style patterns, which is different from regex
-        // but the contract expects regex patterns, so we need to convert
-        let redis_pattern = regex_to_redis_pattern(pattern);
-        self.redis.keys_by_pattern(&redis_pattern).await
+    async fn keys_by_pattern(&self, pattern: &KeyPattern) -> AppResult<Vec<String>> {
+        match pattern.to_glob() {
+            Some(glob) => self.redis.keys_by_pattern(&glob).await,
+            // KEYS only understands globs, regular expressions are matched here
+            None => {
+                let matcher = pattern.matcher()?;
+                let keys = self.redis.keys().await?;
+                Ok(keys
+                    .into_iter()
+                    .filter(|key| matcher.is_match(key))
+                    .collect())
+            }
+        }
     }
 
     async fn put_raw(&self, key: &str, value: String) -> AppResult<String> {
         self.redis.set(key, &value).await
     }
 
+    async fn put_raw_for(&self, key: &str, value: String, ttl: Duration) -> AppResult<String> {
+        RedisContract::set_raw(self.redis.as_ref(), key, &value, Some(ttl)).await?;
+        Ok(key.to_string())
+    }
+
     async fn get_raw(&self, key: &str) -> AppResult<Option<String>> {
         self.redis.get::<Option<String>>(key).await
     }
 
-    async fn forget(&self, key: &str) -> AppResult<i32> {
-        self.redis.delete(key).await
+    async fn has(&self, key: &str) -> AppResult<bool> {
+        self.redis.exists(key).await
     }
 
-    async fn forget_by_pattern(&self, key: &str) -> AppResult<i32> {
-        self.redis
-            .delete_by_pattern(key)
-            .await
-            .map(|count| count as i32)
+    async fn add_raw(&self, key: &str, value: String) -> AppResult<bool> {
+        self.redis.set_nx(key, &value).await
     }
-}
 
-// Helper function to convert regex patterns to Redis glob patterns
-fn regex_to_redis_pattern(pattern: &str) -> String {
-    // Handle some common regex patterns and convert them to Redis patterns
-    let mut redis_pattern = pattern.to_string();
-
-    // Replace regex start/end markers
-    redis_pattern = redis_pattern.replace("^", "");
-    redis_pattern = redis_pattern.replace("$", "");
+    async fn replace_raw(&self, key: &str, current: &str, value: String) -> AppResult<bool> {
+        self.redis.compare_and_set(key, current, &value).await
+    }
 
-    // Replace regex .* with Redis *
-    redis_pattern = redis_pattern.replace(".*", "*");
+    async fn pull_raw(&self, key: &str) -> AppResult<Option<String>> {
+        self.redis.get_del::<Option<String>>(key).await
+    }
 
-    // Replace regex dot with Redis ?
-    redis_pattern = redis_pattern.replace(".", "?");
+    async fn forget(&self, key: &str) -> AppResult<i32> {
+        self.redis.delete(key).await
+    }
 
-    // Handle case-insensitive flag by removing it (Redis KEYS is case-sensitive)
-    redis_pattern = redis_pattern.replace("(?i)", "");
+    async fn forget_by_pattern(&self, pattern: &KeyPattern) -> AppResult<i32> {
+        if let Some(glob) = pattern.to_glob() {
+            return self
+                .redis
+                .delete_by_pattern(&glob)
+                .await
+                .map(|count| count as i32);
+        }
 
-    // Escape special Redis pattern characters that might be in the regex
-    redis_pattern = redis_pattern.replace("[", "\\[");
-    redis_pattern = redis_pattern.replace("]", "\\]");
+        let mut removed = 0;
+        for key in self.keys_by_pattern(pattern).await? {
+            removed += self.redis.delete(&key).await?;
+        }
 
-    redis_pattern
+        Ok(removed)
+    }
 }
 
 #[cfg(test)]
@@ -116,6 +132,46 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_has_add_and_pull() {
+        let driver = match setup_test_driver().await {
+            Some(driver) => driver,
+            None => {
+                eprintln!("Skipping Redis tests - no connection available");
+                return;
+            }
+        };
+
+        assert!(!driver.has("token:1").await.unwrap());
+        assert!(driver.add_raw("token:1", "a".to_string()).await.unwrap());
+        assert!(!driver.add_raw("token:1", "b".to_string()).await.unwrap());
+        assert!(driver.has("token:1").await.unwrap());
+
+        assert!(
+            !driver
+                .replace_raw("token:1", "b", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(
+            driver
+                .replace_raw("token:1", "a", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !driver
+                .replace_raw("token:2", "a", "c".to_string())
+                .await
+                .unwrap()
+        );
+        assert!(!driver.has("token:2").await.unwrap());
+
+        assert_eq!(driver.pull_raw("token:1").await.unwrap(), Some("c".into()));
+        assert_eq!(driver.pull_raw("token:1").await.unwrap(), None);
+        assert!(!driver.has("token:1").await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_basic_operations() {
         let driver = match setup_test_driver().await {
@@ -188,26 +244,38 @@ mod tests {
         }
 
         // Test case 1: Exact prefix match
-        let removed = driver.forget_by_pattern("user:*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::glob("user:*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("user:123").await.unwrap(), None);
         assert_eq!(driver.get_raw("user:456").await.unwrap(), None);
         assert!(driver.get_raw("cache:temp:1").await.unwrap().is_some());
 
         // Test case 2: Match with multiple segments
-        let removed = driver.forget_by_pattern("cache:temp:*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::prefix("cache:temp:"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("cache:temp:1").await.unwrap(), None);
         assert_eq!(driver.get_raw("cache:temp:2").await.unwrap(), None);
 
         // Test case 3: Pattern with special characters
-        let removed = driver.forget_by_pattern("test?key").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::glob("test?key"))
+            .await
+            .unwrap();
         assert_eq!(removed, 2);
         assert_eq!(driver.get_raw("test.key").await.unwrap(), None);
         assert_eq!(driver.get_raw("test-key").await.unwrap(), None);
 
         // Test case 4: Pattern with escaped special characters
-        let removed = driver.forget_by_pattern("special\\*char").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::glob("special\\*char"))
+            .await
+            .unwrap();
         assert_eq!(removed, 1);
         assert_eq!(driver.get_raw("special*char").await.unwrap(), None);
     }
@@ -342,7 +410,10 @@ mod tests {
         let _ = tokio::join!(write_handle, read_handle);
 
         // Clean up
-        let removed = driver.forget_by_pattern("test:*").await.unwrap();
+        let removed = driver
+            .forget_by_pattern(&KeyPattern::glob("test:*"))
+            .await
+            .unwrap();
         assert_eq!(removed, 200);
     }
 
@@ -509,8 +580,11 @@ mod tests {
             driver.put_raw(key, value.to_string()).await.unwrap();
         }
 
-        // Test prefix match (regex pattern will be converted to Redis pattern)
-        let mut keys = driver.keys_by_pattern("^user:.*").await.unwrap();
+        // Test prefix match, regular expressions are matched client side
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^user:.*"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -519,7 +593,10 @@ mod tests {
         );
 
         // Test exact match
-        let keys = driver.keys_by_pattern("^other$").await.unwrap();
+        let keys = driver
+            .keys_by_pattern(&KeyPattern::regex("^other$"))
+            .await
+            .unwrap();
         assert_eq!(
             keys,
             vec!["other".to_string()],
@@ -551,8 +628,11 @@ mod tests {
             driver.put_raw(key, value.to_string()).await.unwrap();
         }
 
-        // Test single character wildcard (. in regex becomes ? in Redis)
-        let mut keys = driver.keys_by_pattern("test.").await.unwrap();
+        // Test single character wildcard
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::glob("test?"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -560,8 +640,11 @@ mod tests {
             "Should match single character wildcard"
         );
 
-        // Test multi-character wildcard (.* in regex becomes * in Redis)
-        let mut keys = driver.keys_by_pattern("test.*").await.unwrap();
+        // Test multi-character wildcard
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::glob("test*"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
@@ -572,6 +655,22 @@ mod tests {
             ],
             "Should match multi-character wildcard"
         );
+
+        // Test regex, which has no glob equivalent
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::regex(r"^a?test\d$"))
+            .await
+            .unwrap();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "atest1".to_string(),
+                "test1".to_string(),
+                "test2".to_string()
+            ],
+            "Should match regex"
+        );
     }
 
     #[tokio::test]
@@ -598,13 +697,23 @@ mod tests {
         }
 
         // Test pattern with escaped special characters
-        let mut keys = driver.keys_by_pattern("test\\[.*\\]").await.unwrap();
+        let mut keys = driver
+            .keys_by_pattern(&KeyPattern::glob("test\\[*\\]"))
+            .await
+            .unwrap();
         keys.sort();
         assert_eq!(
             keys,
             vec!["test[1]".to_string(), "test[2]".to_string()],
             "Should match escaped special characters"
         );
+
+        // Test literal prefix, special characters aren't wildcards
+        let keys = driver
+            .keys_by_pattern(&KeyPattern::prefix("test{"))
+            .await
+            .unwrap();
+        assert_eq!(keys, vec!["test{3}".to_string()]);
     }
 
     #[tokio::test]
@@ -623,7 +732,7 @@ mod tests {
         driver.put_raw("test1", "value1".to_string()).await.unwrap();
         driver.put_raw("test2", "value2".to_string()).await.unwrap();
 
-        let keys = driver.keys_by_pattern("").await.unwrap();
+        let keys = driver.keys_by_pattern(&KeyPattern::glob("")).await.unwrap();
         assert!(keys.is_empty(), "Empty pattern should return no matches");
     }
 }
@@ -0,0 +1,90 @@
+/// Builds the cache key of a value, e.g. the arguments of a cached query
+///
+/// With the `macros` feature, it can be derived to get stable, versioned keys instead of
+/// `format!("user:{}:{}", ...)` strings repeated across the code:
+///
+/// ```ignore
+/// use foxtive::cache::CacheKey;
+///
+/// #[derive(CacheKey)]
+/// #[cache_key(prefix = "user", version = 2)]
+/// struct UserKey {
+///     id: u64,
+///     locale: String,
+/// }
+///
+/// let key = UserKey { id: 42, locale: "en".into() };
+/// let user = cache.get_or_put(&key.cache_key(), || fetch_user(42)).await?; // "user:v2:42:en"
+/// ```
+///
+/// Bumping the version makes the entries cached with the previous shape unreachable.
+/// Derived keys escape `:` and `\` in field values, so `("a:b", "c")` and `("a", "b:c")`
+/// don't share a key.
+pub trait CacheKey {
+    fn cache_key(&self) -> String;
+}
+
+/// Escapes a field of a derived key, prefixing `\` and the `:` separator with `\`
+#[doc(hidden)]
+pub fn escape_key_part(part: impl std::fmt::Display) -> String {
+    let part = part.to_string();
+    if !part.contains([':', '\\']) {
+        return part;
+    }
+
+    let mut escaped = String::with_capacity(part.len() + 2);
+    for c in part.chars() {
+        if c == ':' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(all(test, feature = "macros"))]
+mod tests {
+    use crate::cache::CacheKey;
+
+    #[derive(CacheKey)]
+    #[cache_key(prefix = "user", version = 2)]
+    struct UserKey {
+        #[cache_key]
+        id: u64,
+        #[cache_key]
+        locale: &'static str,
+        #[allow(dead_code)]
+        requested_by: &'static str,
+    }
+
+    #[derive(CacheKey)]
+    struct Report(u32, &'static str);
+
+    #[derive(CacheKey)]
+    #[cache_key(prefix = "settings")]
+    struct Settings;
+
+    #[test]
+    fn test_derived_keys() {
+        let key = UserKey {
+            id: 42,
+            locale: "en",
+            requested_by: "admin",
+        };
+        assert_eq!(key.cache_key(), "user:v2:42:en");
+
+        assert_eq!(Report(2026, "q3").cache_key(), "report:v1:2026:q3");
+        assert_eq!(Settings.cache_key(), "settings:v1");
+    }
+
+    #[test]
+    fn test_separators_in_fields_are_escaped() {
+        #[derive(CacheKey)]
+        struct Pair(&'static str, &'static str);
+
+        assert_eq!(Pair("a:b", "c").cache_key(), r"pair:v1:a\:b:c");
+        assert_eq!(Pair("a", "b:c").cache_key(), r"pair:v1:a:b\:c");
+        assert_eq!(Pair(r"a\", ":c").cache_key(), r"pair:v1:a\\:\:c");
+        assert_ne!(Pair(r"a\", "c").cache_key(), Pair("a:", "c").cache_key());
+    }
+}
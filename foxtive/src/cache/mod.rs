@@ -31,14 +31,28 @@
 //! }
 //! ```
 
+mod codec;
 pub mod contract;
 pub mod drivers;
+mod key;
+mod pattern;
 
-use crate::cache::contract::{CacheDriverContract, CacheDriverExt};
+pub use codec::{CacheCodec, CacheFormat, Compression};
+#[cfg(feature = "macros")]
+pub use foxtive_macros::CacheKey;
+pub use key::{CacheKey, escape_key_part};
+pub use pattern::{KeyMatcher, KeyPattern};
+
+use crate::Environment;
+use crate::cache::contract::CacheDriverContract;
+use crate::instrument::observe;
 use crate::prelude::AppResult;
 use serde::{Serialize, de::DeserializeOwned};
+use std::borrow::Cow;
 use std::future::Future;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::debug;
 
 /// A generic caching interface that provides methods for storing and retrieving serialized data.
 ///
@@ -47,6 +61,36 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct Cache {
     driver: Arc<dyn CacheDriverContract>,
+    codec: CacheCodec,
+    stats: Arc<CacheStats>,
+    /// Prepended to every key, see [`Cache::namespaced`]
+    namespace: Option<Arc<str>>,
+}
+
+/// Lookup counters of a [`Cache`], shared by its clones
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheStats {
+    /// Lookups that found a value
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Lookups that found nothing
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    fn record<T>(&self, found: &Option<T>) {
+        match found {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+    }
 }
 
 impl Cache {
@@ -69,7 +113,123 @@ impl Cache {
     /// }
     /// ```
     pub fn new(driver: Arc<dyn CacheDriverContract>) -> Self {
-        Self { driver }
+        Self {
+            driver,
+            codec: CacheCodec::default(),
+            stats: Arc::default(),
+            namespace: None,
+        }
+    }
+
+    /// Prefixes every key with `{app_code}:{env}:`, so environments and apps sharing
+    /// a backend don't read or overwrite each other's entries
+    ///
+    /// Keys are given and listed without the prefix, and [`keys`](Self::keys) and the
+    /// pattern methods only see the keys of the namespace.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use foxtive::Environment;
+    /// use foxtive::cache::{Cache, drivers::FilesystemCacheDriver};
+    ///
+    /// let driver = Arc::new(FilesystemCacheDriver::new("./"));
+    /// // "user:1" is stored as "shop:staging:user:1"
+    /// let cache = Cache::new(driver).namespaced("shop", Environment::Staging);
+    /// ```
+    pub fn namespaced(self, app_code: &str, env: Environment) -> Self {
+        self.with_namespace(format!("{app_code}:{}:", env.as_str()))
+    }
+
+    /// Prefixes every key with `namespace` as is
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        let namespace = namespace.into();
+        self.namespace = (!namespace.is_empty()).then(|| Arc::from(namespace));
+        self
+    }
+
+    /// The same cache without the key prefix, for entries shared by every app and
+    /// environment using the backend
+    pub fn without_namespace(&self) -> Self {
+        Self {
+            namespace: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// The key as stored by the driver
+    fn key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match &self.namespace {
+            Some(namespace) => Cow::Owned(format!("{namespace}{key}")),
+            None => Cow::Borrowed(key),
+        }
+    }
+
+    /// Keys of the namespace without its prefix
+    fn strip(&self, keys: Vec<String>) -> Vec<String> {
+        match &self.namespace {
+            Some(namespace) => keys
+                .into_iter()
+                .filter_map(|key| key.strip_prefix(&**namespace).map(str::to_string))
+                .collect(),
+            None => keys,
+        }
+    }
+
+    /// Keys of the namespace matching the pattern, with the prefix
+    async fn namespaced_keys(
+        &self,
+        namespace: &str,
+        pattern: &KeyPattern,
+    ) -> AppResult<Vec<String>> {
+        match pattern.within(namespace) {
+            Some(pattern) => self.driver.keys_by_pattern(&pattern).await,
+            None => {
+                let matcher = pattern.matcher()?;
+                let keys = self
+                    .driver
+                    .keys_by_pattern(&KeyPattern::prefix(namespace))
+                    .await?;
+
+                Ok(keys
+                    .into_iter()
+                    .filter(|key| matcher.is_match(&key[namespace.len()..]))
+                    .collect())
+            }
+        }
+    }
+
+    /// Encodes values with `codec` instead of plain JSON, entries written by
+    /// other codecs remain readable
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use foxtive::cache::{Cache, CacheCodec, Compression, drivers::FilesystemCacheDriver};
+    ///
+    /// let driver = Arc::new(FilesystemCacheDriver::new("./"));
+    /// // gzip values of 1 KiB and more
+    /// let cache = Cache::new(driver).with_codec(CacheCodec::json().compress(Compression::gzip(), 1024));
+    /// ```
+    pub fn with_codec(mut self, codec: CacheCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    pub fn codec(&self) -> CacheCodec {
+        self.codec
+    }
+
+    /// Hits and misses of [`get`](Self::get), [`pull`](Self::pull) and
+    /// [`get_or_put`](Self::get_or_put) since the cache was created
+    pub fn stats(&self) -> &CacheStats {
+        &self.stats
     }
 
     /// Returns a clone of the underlying driver.
@@ -116,9 +276,12 @@ impl Cache {
     where
         T: Serialize + Sync,
     {
-        self.driver
-            .put_raw(key, serde_json::to_string(value)?)
-            .await
+        observe("cache", "put", async {
+            self.driver
+                .put_raw(&self.key(key), self.codec.encode(value)?)
+                .await
+        })
+        .await
     }
 
     /// Retrieves a value from the cache and deserializes it into the specified type.
@@ -152,13 +315,11 @@ impl Cache {
     where
         T: DeserializeOwned + Sync,
     {
-        let raw = self.driver.get_raw(key).await?;
+        let raw = observe("cache", "get", self.driver.get_raw(&self.key(key))).await?;
+        self.stats.record(&raw);
 
         match raw {
-            Some(json) => {
-                let deserialized = serde_json::from_str::<T>(&json).map_err(crate::Error::msg)?;
-                Ok(Some(deserialized))
-            }
+            Some(raw) => Ok(Some(self.codec.decode::<T>(&raw)?)),
             None => Ok(None),
         }
     }
@@ -189,7 +350,98 @@ impl Cache {
     /// }
     /// ```
     pub async fn forget(&self, key: &str) -> AppResult<i32> {
-        self.driver.forget(key).await
+        observe("cache", "forget", self.driver.forget(&self.key(key))).await
+    }
+
+    /// Checks whether a key is present in the cache.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use foxtive::cache::{Cache, drivers::FilesystemCacheDriver};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let driver = Arc::new(FilesystemCacheDriver::new("./"));
+    ///     let cache = Cache::new(driver);
+    ///
+    ///     if !cache.has("report:daily").await.unwrap() {
+    ///         // build the report
+    ///     }
+    /// }
+    /// ```
+    pub async fn has(&self, key: &str) -> AppResult<bool> {
+        observe("cache", "has", self.driver.has(&self.key(key))).await
+    }
+
+    /// Retrieves a value and removes it from the cache, atomically on the Redis,
+    /// filesystem and in-memory drivers, so only one caller gets it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use foxtive::cache::{Cache, drivers::FilesystemCacheDriver};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let driver = Arc::new(FilesystemCacheDriver::new("./"));
+    ///     let cache = Cache::new(driver);
+    ///
+    ///     // one-time login token
+    ///     let user_id: Option<u64> = cache.pull("login-token:abc").await.unwrap();
+    /// }
+    /// ```
+    pub async fn pull<T>(&self, key: &str) -> AppResult<Option<T>>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        let raw = observe("cache", "pull", self.driver.pull_raw(&self.key(key))).await?;
+        self.stats.record(&raw);
+
+        match raw {
+            Some(raw) => Ok(Some(self.codec.decode::<T>(&raw)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Stores a value only if the key is absent, returning whether it was stored.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use foxtive::cache::{Cache, drivers::FilesystemCacheDriver};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let driver = Arc::new(FilesystemCacheDriver::new("./"));
+    ///     let cache = Cache::new(driver);
+    ///
+    ///     if cache.add("welcome-email:1", &true).await.unwrap() {
+    ///         // first time, send the email
+    ///     }
+    /// }
+    /// ```
+    pub async fn add<T>(&self, key: &str, value: &T) -> AppResult<bool>
+    where
+        T: Serialize + Sync,
+    {
+        observe("cache", "add", async {
+            self.driver
+                .add_raw(&self.key(key), self.codec.encode(value)?)
+                .await
+        })
+        .await
+    }
+
+    /// Stores a value without expiry, the same as [`Cache::put`] as cached values don't expire.
+    pub async fn forever<T>(&self, key: &str, value: &T) -> AppResult<String>
+    where
+        T: Serialize + Sync,
+    {
+        self.put(key, value).await
     }
 
     /// Retrieves a value from the cache or computes and stores it if not present.
@@ -226,7 +478,16 @@ impl Cache {
         Fun: FnOnce() -> Fut + Send,
         Fut: Future<Output = AppResult<Val>> + Send,
     {
-        self.driver.get_or_put(key, setter).await
+        if let Some(value) = self.get::<Val>(key).await? {
+            debug!("'{key}' collected from cache :)");
+            return Ok(value);
+        }
+
+        debug!("'{key}' is missing in cache, executing setter()...");
+
+        let value = setter().await?;
+        self.put(key, &value).await?;
+        Ok(value)
     }
 
     /// Retrieves all keys present in the cache.
@@ -250,14 +511,24 @@ impl Cache {
     /// }
     /// ```
     pub async fn keys(&self) -> AppResult<Vec<String>> {
-        self.driver.keys().await
+        observe("cache", "keys", async {
+            match &self.namespace {
+                Some(namespace) => Ok(self.strip(
+                    self.driver
+                        .keys_by_pattern(&KeyPattern::prefix(&**namespace))
+                        .await?,
+                )),
+                None => self.driver.keys().await,
+            }
+        })
+        .await
     }
 
     /// Retrieves all keys matching the specified pattern.
     ///
     /// # Arguments
     ///
-    /// * `pattern` - Pattern string to match keys against
+    /// * `pattern` - Pattern to match keys against
     ///
     /// # Returns
     ///
@@ -267,7 +538,7 @@ impl Cache {
     ///
     /// ```no_run
     /// use std::sync::Arc;
-    /// use foxtive::cache::{Cache, drivers::FilesystemCacheDriver};
+    /// use foxtive::cache::{Cache, KeyPattern, drivers::FilesystemCacheDriver};
     ///
     /// #[tokio::main]
     /// async fn main() {
@@ -275,18 +546,24 @@ impl Cache {
     ///     let cache = Cache::new(driver);
     ///
     ///     // Get all keys starting with "user:"
-    ///     let user_keys = cache.keys_by_pattern("user:*").await.unwrap();
+    ///     let user_keys = cache.keys_by_pattern(&KeyPattern::prefix("user:")).await.unwrap();
     /// }
     /// ```
-    pub async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>> {
-        self.driver.keys_by_pattern(pattern).await
+    pub async fn keys_by_pattern(&self, pattern: &KeyPattern) -> AppResult<Vec<String>> {
+        observe("cache", "keys_by_pattern", async {
+            match &self.namespace {
+                Some(namespace) => Ok(self.strip(self.namespaced_keys(namespace, pattern).await?)),
+                None => self.driver.keys_by_pattern(pattern).await,
+            }
+        })
+        .await
     }
 
     /// Removes all keys matching the specified pattern from the cache.
     ///
     /// # Arguments
     ///
-    /// * `pattern` - Pattern string to match keys for removal
+    /// * `pattern` - Pattern to match keys for removal
     ///
     /// # Returns
     ///
@@ -296,7 +573,7 @@ impl Cache {
     ///
     /// ```no_run
     /// use std::sync::Arc;
-    /// use foxtive::cache::{Cache, drivers::FilesystemCacheDriver};
+    /// use foxtive::cache::{Cache, KeyPattern, drivers::FilesystemCacheDriver};
     ///
     /// #[tokio::main]
     /// async fn main() {
@@ -304,10 +581,80 @@ impl Cache {
     ///     let cache = Cache::new(driver);
     ///
     ///     // Remove all keys starting with "user:"
-    ///     let removed_count = cache.forget_by_pattern("user:*").await.unwrap();
+    ///     let removed_count = cache.forget_by_pattern(&KeyPattern::prefix("user:")).await.unwrap();
     /// }
     /// ```
-    pub async fn forget_by_pattern(&self, pattern: &str) -> AppResult<i32> {
-        self.driver.forget_by_pattern(pattern).await
+    pub async fn forget_by_pattern(&self, pattern: &KeyPattern) -> AppResult<i32> {
+        observe("cache", "forget_by_pattern", async {
+            let Some(namespace) = &self.namespace else {
+                return self.driver.forget_by_pattern(pattern).await;
+            };
+
+            match pattern.within(namespace) {
+                Some(pattern) => self.driver.forget_by_pattern(&pattern).await,
+                None => {
+                    let mut removed = 0;
+                    for key in self.namespaced_keys(namespace, pattern).await? {
+                        removed += self.driver.forget(&key).await?;
+                    }
+                    Ok(removed)
+                }
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(all(test, feature = "cache-in-memory"))]
+mod tests {
+    use super::*;
+    use crate::cache::drivers::InMemoryDriver;
+
+    #[tokio::test]
+    async fn test_namespaces_are_isolated() {
+        let driver: Arc<dyn CacheDriverContract> = Arc::new(InMemoryDriver::new());
+        let staging = Cache::new(driver.clone()).namespaced("shop", Environment::Staging);
+        let production = Cache::new(driver.clone()).namespaced("shop", Environment::Production);
+
+        staging.put("user:1", &"staging").await.unwrap();
+        production.put("user:1", &"production").await.unwrap();
+
+        assert_eq!(
+            staging.get::<String>("user:1").await.unwrap().as_deref(),
+            Some("staging")
+        );
+        assert_eq!(staging.keys().await.unwrap(), vec!["user:1".to_string()]);
+        assert!(driver.has("shop:production:user:1").await.unwrap());
+
+        let shared = staging.without_namespace();
+        assert_eq!(shared.namespace(), None);
+        assert_eq!(shared.keys().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_patterns_stay_within_namespace() {
+        let driver: Arc<dyn CacheDriverContract> = Arc::new(InMemoryDriver::new());
+        let cache = Cache::new(driver.clone()).with_namespace("app:");
+        let other = Cache::new(driver).with_namespace("other:");
+
+        for key in ["user:1", "user:2", "post:1"] {
+            cache.put(key, &1).await.unwrap();
+            other.put(key, &1).await.unwrap();
+        }
+
+        let mut keys = cache
+            .keys_by_pattern(&KeyPattern::suffix(":1"))
+            .await
+            .unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["post:1".to_string(), "user:1".to_string()]);
+
+        let removed = cache
+            .forget_by_pattern(&KeyPattern::regex("^user:"))
+            .await
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(cache.keys().await.unwrap(), vec!["post:1".to_string()]);
+        assert_eq!(other.keys().await.unwrap().len(), 3);
     }
 }
@@ -0,0 +1,254 @@
+use crate::results::AppResult;
+use fancy_regex::Regex;
+
+/// Selects keys for [`Cache::keys_by_pattern`](crate::cache::Cache::keys_by_pattern)
+/// and [`Cache::forget_by_pattern`](crate::cache::Cache::forget_by_pattern).
+///
+/// Each driver matches every variant with the same semantics: Redis matches prefix, suffix,
+/// contains and glob patterns on the server and regular expressions on the client, the
+/// other drivers match everything on the client.
+///
+/// # Example
+///
+/// ```
+/// use foxtive::cache::KeyPattern;
+///
+/// let pattern = KeyPattern::glob("user:[0-9]*");
+/// assert!(pattern.matcher().unwrap().is_match("user:42"));
+///
+/// // literal text, `*` isn't a wildcard
+/// let pattern = KeyPattern::prefix("tmp*:");
+/// assert!(!pattern.matcher().unwrap().is_match("tmp1:a"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyPattern {
+    /// Keys starting with the text
+    Prefix(String),
+    /// Keys ending with the text
+    Suffix(String),
+    /// Keys containing the text
+    Contains(String),
+    /// Redis glob matching the whole key: `*`, `?`, `[abc]`, `[^abc]`, `[a-z]` and `\` escapes
+    Glob(String),
+    /// Regular expression, matching anywhere in the key unless anchored
+    Regex(String),
+}
+
+impl KeyPattern {
+    pub fn prefix(text: impl Into<String>) -> Self {
+        Self::Prefix(text.into())
+    }
+
+    pub fn suffix(text: impl Into<String>) -> Self {
+        Self::Suffix(text.into())
+    }
+
+    pub fn contains(text: impl Into<String>) -> Self {
+        Self::Contains(text.into())
+    }
+
+    pub fn glob(pattern: impl Into<String>) -> Self {
+        Self::Glob(pattern.into())
+    }
+
+    pub fn regex(pattern: impl Into<String>) -> Self {
+        Self::Regex(pattern.into())
+    }
+
+    /// Equivalent Redis glob, `None` for regular expressions which globs can't express
+    pub fn to_glob(&self) -> Option<String> {
+        match self {
+            KeyPattern::Prefix(text) => Some(format!("{}*", escape_glob(text))),
+            KeyPattern::Suffix(text) => Some(format!("*{}", escape_glob(text))),
+            KeyPattern::Contains(text) => Some(format!("*{}*", escape_glob(text))),
+            KeyPattern::Glob(pattern) => Some(pattern.clone()),
+            KeyPattern::Regex(_) => None,
+        }
+    }
+
+    /// Compiles the pattern for matching keys on the client
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a regular expression is invalid
+    pub fn matcher(&self) -> AppResult<KeyMatcher> {
+        Ok(match self {
+            KeyPattern::Prefix(text) => KeyMatcher::Prefix(text.clone()),
+            KeyPattern::Suffix(text) => KeyMatcher::Suffix(text.clone()),
+            KeyPattern::Contains(text) => KeyMatcher::Contains(text.clone()),
+            KeyPattern::Glob(pattern) => KeyMatcher::Regex(Regex::new(&glob_to_regex(pattern))?),
+            KeyPattern::Regex(pattern) => KeyMatcher::Regex(Regex::new(pattern)?),
+        })
+    }
+
+    /// The same pattern for keys stored under `namespace`, `None` for regular expressions
+    /// which are matched on the client instead
+    pub(crate) fn within(&self, namespace: &str) -> Option<KeyPattern> {
+        match self {
+            KeyPattern::Prefix(text) => Some(KeyPattern::Prefix(format!("{namespace}{text}"))),
+            KeyPattern::Regex(_) => None,
+            pattern => pattern
+                .to_glob()
+                .map(|glob| KeyPattern::Glob(format!("{}{glob}", escape_glob(namespace)))),
+        }
+    }
+}
+
+/// Compiled [`KeyPattern`]
+#[derive(Debug, Clone)]
+pub enum KeyMatcher {
+    Prefix(String),
+    Suffix(String),
+    Contains(String),
+    Regex(Regex),
+}
+
+impl KeyMatcher {
+    pub fn is_match(&self, key: &str) -> bool {
+        match self {
+            KeyMatcher::Prefix(text) => key.starts_with(text.as_str()),
+            KeyMatcher::Suffix(text) => key.ends_with(text.as_str()),
+            KeyMatcher::Contains(text) => key.contains(text.as_str()),
+            KeyMatcher::Regex(regex) => matches!(regex.is_match(key), Ok(true)),
+        }
+    }
+}
+
+/// Escapes the characters Redis treats specially in globs
+fn escape_glob(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        if matches!(char, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// Anchored regex matching what Redis matches for the glob,
+/// an unclosed `[` is taken literally as Redis does
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::from("^");
+    let mut index = 0;
+
+    while index < chars.len() {
+        match chars[index] {
+            '*' => regex.push_str("(?s:.*)"),
+            '?' => regex.push_str("(?s:.)"),
+            '\\' if index + 1 < chars.len() => {
+                index += 1;
+                regex.push_str(&fancy_regex::escape(&chars[index].to_string()));
+            }
+            '[' if chars[index + 1..].contains(&']') => {
+                index += 1;
+                let negated = chars[index] == '^' && chars[index + 1..].contains(&']');
+                if negated {
+                    index += 1;
+                }
+
+                let mut class = String::new();
+                while index < chars.len() && chars[index] != ']' {
+                    match chars[index] {
+                        '\\' if index + 1 < chars.len() => {
+                            index += 1;
+                            class.push_str(&escape_class_char(chars[index]));
+                        }
+                        '-' => class.push('-'),
+                        char => class.push_str(&escape_class_char(char)),
+                    }
+                    index += 1;
+                }
+
+                // `[]` matches nothing in Redis, `[^]` any single char
+                match (class.is_empty(), negated) {
+                    (true, false) => regex.push_str("(?!)"),
+                    (true, true) => regex.push_str("(?s:.)"),
+                    (false, negated) => {
+                        regex.push('[');
+                        if negated {
+                            regex.push('^');
+                        }
+                        regex.push_str(&class);
+                        regex.push(']');
+                    }
+                }
+            }
+            char => regex.push_str(&fancy_regex::escape(&char.to_string())),
+        }
+        index += 1;
+    }
+
+    regex.push('$');
+    regex
+}
+
+fn escape_class_char(char: char) -> String {
+    match char {
+        '\\' | ']' | '[' | '^' | '-' | '&' | '~' => format!("\\{char}"),
+        char => char.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: KeyPattern, key: &str) -> bool {
+        pattern.matcher().unwrap().is_match(key)
+    }
+
+    #[test]
+    fn test_literal_patterns_match_text_as_is() {
+        assert!(is_match(KeyPattern::prefix("user.1"), "user.1:name"));
+        assert!(!is_match(KeyPattern::prefix("user.1"), "userx1:name"));
+        assert!(is_match(KeyPattern::suffix(":*"), "tags:*"));
+        assert!(!is_match(KeyPattern::suffix(":*"), "tags:a"));
+        assert!(is_match(KeyPattern::contains("[x]"), "a[x]b"));
+        assert!(!is_match(KeyPattern::contains("[x]"), "axb"));
+    }
+
+    #[test]
+    fn test_to_glob_escapes_literals() {
+        assert_eq!(
+            KeyPattern::prefix(r"a*b?[c]\").to_glob().unwrap(),
+            r"a\*b\?\[c\]\\*"
+        );
+        assert_eq!(KeyPattern::suffix("x").to_glob().unwrap(), "*x");
+        assert_eq!(KeyPattern::contains("x").to_glob().unwrap(), "*x*");
+        assert_eq!(KeyPattern::glob("a*").to_glob().unwrap(), "a*");
+        assert_eq!(KeyPattern::regex("^a").to_glob(), None);
+    }
+
+    #[test]
+    fn test_glob_matches_like_redis() {
+        assert!(is_match(KeyPattern::glob("h?llo"), "hello"));
+        assert!(!is_match(KeyPattern::glob("h?llo"), "heello"));
+        assert!(is_match(KeyPattern::glob("h*llo"), "heeeello"));
+        assert!(is_match(KeyPattern::glob("h[ae]llo"), "hallo"));
+        assert!(!is_match(KeyPattern::glob("h[ae]llo"), "hillo"));
+        assert!(is_match(KeyPattern::glob("h[^e]llo"), "hallo"));
+        assert!(!is_match(KeyPattern::glob("h[^e]llo"), "hello"));
+        assert!(is_match(KeyPattern::glob("h[a-b]llo"), "hbllo"));
+        assert!(!is_match(KeyPattern::glob("h[a-b]llo"), "hcllo"));
+        assert!(is_match(KeyPattern::glob(r"special\*char"), "special*char"));
+        assert!(!is_match(
+            KeyPattern::glob(r"special\*char"),
+            "specialXchar"
+        ));
+        assert!(is_match(KeyPattern::glob("a.b[c"), "a.b[c"));
+        assert!(!is_match(KeyPattern::glob("a.b"), "axb"));
+        assert!(!is_match(KeyPattern::glob("user:*"), "xuser:1"));
+        assert!(is_match(KeyPattern::glob("line*"), "line1\nline2"));
+        assert!(is_match(KeyPattern::glob(r"a[\]x]"), "a]"));
+        assert!(!is_match(KeyPattern::glob("a[]"), "a"));
+    }
+
+    #[test]
+    fn test_regex_matches_unanchored() {
+        assert!(is_match(KeyPattern::regex(r"\d+$"), "order:12"));
+        assert!(is_match(KeyPattern::regex("(?i)^session:"), "SESSION:1"));
+        assert!(KeyPattern::regex("[").matcher().is_err());
+    }
+}
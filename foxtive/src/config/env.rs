@@ -0,0 +1,186 @@
+//! Overlaying environment variables on the merged configuration tree.
+
+use serde_json::{Map, Value};
+
+/// Separator between nesting levels in variable names, `APP_DATABASE__POOL_SIZE`
+/// maps to `database.pool_size`
+pub(crate) const NESTING_SEPARATOR: &str = "__";
+
+/// Applies every variable starting with `{prefix}_` to the tree.
+///
+/// Values replacing an existing key keep that key's type where possible, so that
+/// `APP_NAME=2024` stays a string when `name` is a string in the defaults or files.
+/// New keys are kept as strings, and parsed into the type the target struct expects
+/// when the tree is deserialized.
+pub(crate) fn overlay<I>(tree: &mut Value, prefix: &str, vars: I)
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let prefix = format!("{prefix}_");
+    for (name, raw) in vars {
+        let Some(key) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+
+        let path = key
+            .split(NESTING_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        if path.iter().any(String::is_empty) {
+            continue;
+        }
+
+        set_path(tree, &path, &raw);
+    }
+}
+
+fn set_path(tree: &mut Value, path: &[String], raw: &str) {
+    if !tree.is_object() {
+        *tree = Value::Object(Map::new());
+    }
+
+    let Value::Object(map) = tree else {
+        return;
+    };
+
+    match path {
+        [] => {}
+        [last] => {
+            let value = coerce(raw, map.get(last));
+            map.insert(last.clone(), value);
+        }
+        [first, rest @ ..] => {
+            let child = map
+                .entry(first.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            set_path(child, rest, raw);
+        }
+    }
+}
+
+fn coerce(raw: &str, existing: Option<&Value>) -> Value {
+    let as_string = || Value::String(raw.to_string());
+
+    match existing {
+        Some(Value::String(_)) => as_string(),
+        Some(Value::Bool(_)) => raw.parse().map(Value::Bool).unwrap_or_else(|_| as_string()),
+        Some(Value::Number(_)) => parse_number(raw).unwrap_or_else(as_string),
+        Some(Value::Array(_)) => match serde_json::from_str::<Value>(raw) {
+            Ok(value @ Value::Array(_)) => value,
+            _ => Value::Array(
+                raw.split(',')
+                    .map(|item| Value::String(item.trim().to_string()))
+                    .collect(),
+            ),
+        },
+        _ => as_string(),
+    }
+}
+
+pub(super) fn parse_number(raw: &str) -> Option<Value> {
+    if let Ok(value) = raw.parse::<i64>() {
+        return Some(Value::from(value));
+    }
+
+    raw.parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .map(Value::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_overlay_nested_keys() {
+        let mut tree = json!({ "database": { "url": "postgres://localhost", "pool_size": 5 } });
+        overlay(
+            &mut tree,
+            "APP",
+            vars(&[
+                ("APP_DATABASE__POOL_SIZE", "20"),
+                ("APP_SERVER__PORT", "8080"),
+                ("OTHER_DEBUG", "true"),
+            ]),
+        );
+
+        assert_eq!(
+            tree,
+            json!({
+                "database": { "url": "postgres://localhost", "pool_size": 20 },
+                "server": { "port": "8080" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_overlay_keeps_existing_types() {
+        let mut tree = json!({ "name": "app", "debug": false, "hosts": [] });
+        overlay(
+            &mut tree,
+            "APP",
+            vars(&[
+                ("APP_NAME", "2024"),
+                ("APP_DEBUG", "true"),
+                ("APP_HOSTS", "a.example.com, b.example.com"),
+            ]),
+        );
+
+        assert_eq!(
+            tree,
+            json!({
+                "name": "2024",
+                "debug": true,
+                "hosts": ["a.example.com", "b.example.com"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_new_values_are_kept_as_strings() {
+        let mut tree = json!({});
+        overlay(
+            &mut tree,
+            "APP",
+            vars(&[
+                ("APP_NAME", "2024"),
+                ("APP_DEBUG", "true"),
+                ("APP_HOSTS", "[1, 2]"),
+            ]),
+        );
+
+        assert_eq!(
+            tree,
+            json!({ "name": "2024", "debug": "true", "hosts": "[1, 2]" })
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("1.5"), Some(json!(1.5)));
+        assert_eq!(parse_number("-3"), Some(json!(-3)));
+        assert_eq!(parse_number("inf"), None);
+        assert_eq!(parse_number("hello"), None);
+    }
+
+    #[test]
+    fn test_ignores_malformed_names() {
+        let mut tree = json!({});
+        overlay(
+            &mut tree,
+            "APP",
+            vars(&[("APP_A____B", "1"), ("APP_", "1")]),
+        );
+        assert_eq!(tree, json!({}));
+    }
+}
@@ -0,0 +1,233 @@
+//! Deserializing the merged tree with strings accepted where other types are expected.
+//!
+//! Environment variables are strings, and the type of a key only set by the environment
+//! is unknown until the target struct asks for it. Guessing it up front turns
+//! `APP_NAME=2024` into a number that a `String` field rejects, so the tree keeps those
+//! values as strings and they are parsed here, into whatever the struct expects.
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Error, Map, Value};
+
+/// A tree node whose strings are parsed into the booleans, numbers, sequences and maps
+/// the target type expects
+pub(crate) struct Lenient(pub(crate) Value);
+
+impl Lenient {
+    /// The node with a string parsed as a boolean or number, when it is one
+    fn scalar(self) -> Value {
+        match self.0 {
+            Value::String(raw) => {
+                let trimmed = raw.trim();
+                if let Ok(value) = trimmed.parse::<bool>() {
+                    return Value::Bool(value);
+                }
+
+                super::env::parse_number(trimmed).unwrap_or(Value::String(raw))
+            }
+            value => value,
+        }
+    }
+
+    /// The node with a string parsed as a JSON array, or split on commas
+    fn seq(self) -> Value {
+        match self.0 {
+            Value::String(raw) => match serde_json::from_str::<Value>(&raw) {
+                Ok(value @ Value::Array(_)) => value,
+                _ => Value::Array(
+                    raw.split(',')
+                        .map(|item| Value::String(item.trim().to_string()))
+                        .collect(),
+                ),
+            },
+            value => value,
+        }
+    }
+
+    /// The node with a string parsed as a JSON object, when it is one
+    fn map(self) -> Value {
+        match self.0 {
+            Value::String(raw) => match serde_json::from_str::<Value>(&raw) {
+                Ok(value @ Value::Object(_)) => value,
+                _ => Value::String(raw),
+            },
+            value => value,
+        }
+    }
+}
+
+fn visit_array<'de, V: Visitor<'de>>(items: Vec<Value>, visitor: V) -> Result<V::Value, Error> {
+    let mut seq = SeqDeserializer::new(items.into_iter().map(Lenient));
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+fn visit_object<'de, V: Visitor<'de>>(
+    entries: Map<String, Value>,
+    visitor: V,
+) -> Result<V::Value, Error> {
+    let mut map = MapDeserializer::new(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key, Lenient(value))),
+    );
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+macro_rules! deserialize_scalar {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.scalar().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Array(items) => visit_array(items, visitor),
+            Value::Object(entries) => visit_object(entries, visitor),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_scalar! {
+        deserialize_bool
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Lenient(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        Lenient(self.seq()).deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        Lenient(self.map()).deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple_struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Lenient {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Settings {
+        name: String,
+        port: u16,
+        ratio: f64,
+        debug: bool,
+        timeout: Option<u64>,
+        hosts: Vec<String>,
+        weights: Vec<u8>,
+        labels: HashMap<String, String>,
+        nested: Nested,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Nested {
+        retries: u32,
+    }
+
+    #[test]
+    fn test_strings_are_parsed_into_the_expected_types() {
+        let tree = json!({
+            "name": "2024",
+            "port": "8080",
+            "ratio": "0.5",
+            "debug": "true",
+            "timeout": "30",
+            "hosts": "a.example.com, b.example.com",
+            "weights": "[1, 2]",
+            "labels": r#"{ "team": "core" }"#,
+            "nested": { "retries": "3" }
+        });
+
+        assert_eq!(
+            Settings::deserialize(Lenient(tree)).unwrap(),
+            Settings {
+                name: "2024".to_string(),
+                port: 8080,
+                ratio: 0.5,
+                debug: true,
+                timeout: Some(30),
+                hosts: vec!["a.example.com".to_string(), "b.example.com".to_string()],
+                weights: vec![1, 2],
+                labels: HashMap::from([("team".to_string(), "core".to_string())]),
+                nested: Nested { retries: 3 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_unparsable_strings_are_rejected() {
+        assert!(u16::deserialize(Lenient(json!("port"))).is_err());
+        assert!(u8::deserialize(Lenient(json!("300"))).is_err());
+        assert!(bool::deserialize(Lenient(json!("yes"))).is_err());
+        assert!(Nested::deserialize(Lenient(json!("retries"))).is_err());
+    }
+}
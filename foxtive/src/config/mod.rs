@@ -0,0 +1,501 @@
+//! # Config Module
+//!
+//! Layered application configuration, deserialized into typed structs.
+//!
+//! Sources are merged in the order they are added, later sources overriding earlier
+//! ones key by key:
+//!
+//! 1. defaults, from any serializable value
+//! 2. files: JSON, TOML (`config-toml` feature) or YAML (`config-yaml` feature)
+//! 3. environment variables starting with the app's env prefix, `APP_DATABASE__POOL_SIZE`
+//!    overriding `database.pool_size`
+//!
+//! Missing and malformed values are reported at startup with the exact key at fault,
+//! and [`ConfigLoader::watch`] notifies the application when config files change.
+//!
+//! ## Features
+//!
+//! This module requires the `config` feature to be enabled.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::config::ConfigLoader;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Settings {
+//!     database: Database,
+//!     debug: bool,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Database {
+//!     url: String,
+//!     pool_size: u32,
+//! }
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! let file = dir.path().join("app.json");
+//! std::fs::write(&file, r#"{ "database": { "url": "postgres://db/app" } }"#).unwrap();
+//!
+//! let settings: Settings = ConfigLoader::new()
+//!     .defaults(serde_json::json!({ "debug": false, "database": { "pool_size": 10 } }))
+//!     .unwrap()
+//!     .file(&file)
+//!     .optional_file(dir.path().join("app.local.json"))
+//!     .env_prefix("MY_APP")
+//!     .require("database.url")
+//!     .load()
+//!     .unwrap();
+//!
+//! assert_eq!(settings.database.url, "postgres://db/app");
+//! assert_eq!(settings.database.pool_size, 10);
+//! ```
+
+mod env;
+mod lenient;
+
+use crate::helpers::try_blk;
+use crate::prelude::AppResult;
+use lenient::Lenient;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+/// Errors raised while loading configuration
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("failed to parse config file {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error("unsupported config file format: {0}")]
+    UnsupportedFormat(PathBuf),
+
+    #[error("missing required config keys: {}", .0.join(", "))]
+    MissingKeys(Vec<String>),
+
+    #[error("invalid config at `{path}`: {message}")]
+    Invalid { path: String, message: String },
+}
+
+#[derive(Debug, Clone)]
+struct FileSource {
+    path: PathBuf,
+    required: bool,
+}
+
+/// Builds a configuration from layered sources.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    defaults: Value,
+    files: Vec<FileSource>,
+    env_prefix: Option<String>,
+    required: Vec<String>,
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lowest priority layer, typically `Settings::default()` or a `json!` literal
+    pub fn defaults<T: Serialize>(mut self, defaults: T) -> AppResult<Self> {
+        merge(&mut self.defaults, serde_json::to_value(defaults)?);
+        Ok(self)
+    }
+
+    /// Adds a file that must exist, its format is detected from the extension
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        self.files.push(FileSource {
+            path: path.as_ref().to_path_buf(),
+            required: true,
+        });
+        self
+    }
+
+    /// Adds a file that is skipped when it does not exist, e.g. local overrides
+    pub fn optional_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.files.push(FileSource {
+            path: path.as_ref().to_path_buf(),
+            required: false,
+        });
+        self
+    }
+
+    /// Overlays environment variables named `{prefix}_{KEY}`, with `__` separating
+    /// nesting levels, usually the same prefix as `FoxtiveSetup::env_prefix`
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Fails loading when the dotted key is missing or null in every source
+    pub fn require(mut self, key: &str) -> Self {
+        self.required.push(key.to_string());
+        self
+    }
+
+    /// Merges all sources into a single tree, without deserializing it
+    pub fn load_value(&self) -> AppResult<Value> {
+        let mut tree = self.defaults.clone();
+        if !tree.is_object() {
+            tree = Value::Object(Map::new());
+        }
+
+        for source in &self.files {
+            if !source.required && !source.path.exists() {
+                debug!("[config] skipping missing file {}", source.path.display());
+                continue;
+            }
+
+            merge(&mut tree, read_file(&source.path)?);
+        }
+
+        if let Some(prefix) = &self.env_prefix {
+            env::overlay(&mut tree, prefix, std::env::vars());
+        }
+
+        let missing = self
+            .required
+            .iter()
+            .filter(|key| lookup(&tree, key).is_none_or(Value::is_null))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            return Err(ConfigError::MissingKeys(missing).into());
+        }
+
+        Ok(tree)
+    }
+
+    /// Merges all sources and deserializes the result
+    pub fn load<T: DeserializeOwned>(&self) -> AppResult<T> {
+        deserialize(self.load_value()?)
+    }
+
+    /// Polls the config files every `interval`, reloading the configuration and passing
+    /// the result to `on_change` whenever one of them is modified, created or removed.
+    ///
+    /// Environment variables are read again on each reload, and files are checked and read
+    /// on the blocking pool. Abort the returned handle to stop watching.
+    pub fn watch<T, F>(self, interval: Duration, on_change: F) -> JoinHandle<()>
+    where
+        T: DeserializeOwned,
+        F: Fn(AppResult<T>) + Send + 'static,
+    {
+        tokio::spawn(async move {
+            let loader = Arc::new(self);
+            let mut fingerprint = loader.blocking(Self::fingerprint).await.ok();
+            loop {
+                tokio::time::sleep(interval).await;
+
+                let current = loader.blocking(Self::fingerprint).await.ok();
+                if current != fingerprint {
+                    info!("[config] change detected, reloading");
+                    fingerprint = current;
+                    let tree = loader.blocking(Self::load_value).await;
+                    on_change(tree.and_then(|tree| tree).and_then(deserialize));
+                }
+            }
+        })
+    }
+
+    /// Runs `f` on the blocking pool, as it reads the file system
+    async fn blocking<R, F>(self: &Arc<Self>, f: F) -> AppResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&Self) -> R + Send + 'static,
+    {
+        let loader = self.clone();
+        try_blk(move || f(&loader)).await
+    }
+
+    fn fingerprint(&self) -> Vec<Option<SystemTime>> {
+        self.files
+            .iter()
+            .map(|source| std::fs::metadata(&source.path).and_then(|meta| meta.modified()))
+            .map(Result::ok)
+            .collect()
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(tree: Value) -> AppResult<T> {
+    serde_path_to_error::deserialize(Lenient(tree)).map_err(|err| {
+        ConfigError::Invalid {
+            path: err.path().to_string(),
+            message: err.inner().to_string(),
+        }
+        .into()
+    })
+}
+
+fn read_file(path: &Path) -> AppResult<Value> {
+    let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let parsed: Result<Value, String> = match extension.as_str() {
+        "json" => serde_json::from_str(&content).map_err(|err| err.to_string()),
+        #[cfg(feature = "config-toml")]
+        "toml" => toml::from_str(&content).map_err(|err| err.to_string()),
+        #[cfg(feature = "config-yaml")]
+        "yaml" | "yml" => serde_yaml_ng::from_str(&content).map_err(|err| err.to_string()),
+        _ => return Err(ConfigError::UnsupportedFormat(path.to_path_buf()).into()),
+    };
+
+    parsed.map_err(|message| {
+        ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+        }
+        .into()
+    })
+}
+
+/// Deep-merges `overlay` into `base`, objects are merged key by key and any
+/// other value is replaced
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn lookup<'a>(tree: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(tree, |node, part| node.get(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Settings {
+        name: String,
+        server: Server,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_files_override_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = write(dir.path(), "base.json", r#"{ "server": { "port": 80 } }"#);
+        let local = write(
+            dir.path(),
+            "local.json",
+            r#"{ "server": { "port": 8080 } }"#,
+        );
+
+        let settings: Settings = ConfigLoader::new()
+            .defaults(json!({ "name": "app", "server": { "host": "0.0.0.0", "port": 1 } }))
+            .unwrap()
+            .file(base)
+            .optional_file(local)
+            .optional_file(dir.path().join("missing.json"))
+            .load()
+            .unwrap();
+
+        assert_eq!(
+            settings,
+            Settings {
+                name: "app".to_string(),
+                server: Server {
+                    host: "0.0.0.0".to_string(),
+                    port: 8080
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_reports_missing_and_invalid_keys() {
+        let err = ConfigLoader::new()
+            .defaults(json!({ "server": { "host": null } }))
+            .unwrap()
+            .require("server.host")
+            .require("name")
+            .load_value()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "missing required config keys: server.host, name"
+        );
+
+        let err = ConfigLoader::new()
+            .defaults(json!({ "name": "app", "server": { "host": "h", "port": 70000 } }))
+            .unwrap()
+            .load::<Settings>()
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("invalid config at `server.port`:")
+        );
+
+        let err = ConfigLoader::new()
+            .defaults(json!({ "name": "app", "server": { "port": 80 } }))
+            .unwrap()
+            .load::<Settings>()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid config at `server`: missing field `host`"
+        );
+    }
+
+    #[test]
+    fn test_env_values_take_the_type_of_the_field() {
+        let mut tree = json!({ "server": { "host": "localhost" } });
+        env::overlay(
+            &mut tree,
+            "APP",
+            [
+                ("APP_NAME".to_string(), "2024".to_string()),
+                ("APP_SERVER__PORT".to_string(), "8080".to_string()),
+            ],
+        );
+
+        let settings: Settings = deserialize(tree).unwrap();
+        assert_eq!(settings.name, "2024");
+        assert_eq!(settings.server.port, 8080);
+
+        let mut tree = json!({ "name": "app", "server": { "host": "localhost" } });
+        env::overlay(
+            &mut tree,
+            "APP",
+            [("APP_SERVER__PORT".to_string(), "http".to_string())],
+        );
+        let err = deserialize::<Settings>(tree).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("invalid config at `server.port`:")
+        );
+    }
+
+    #[test]
+    fn test_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = ConfigLoader::new()
+            .file(dir.path().join("missing.json"))
+            .load_value();
+        assert!(matches!(
+            missing.unwrap_err().downcast_ref::<ConfigError>(),
+            Some(ConfigError::Read { .. })
+        ));
+
+        let broken = write(dir.path(), "broken.json", "{");
+        let parse = ConfigLoader::new().file(broken).load_value();
+        assert!(matches!(
+            parse.unwrap_err().downcast_ref::<ConfigError>(),
+            Some(ConfigError::Parse { .. })
+        ));
+
+        let ini = write(dir.path(), "app.ini", "a=1");
+        let unsupported = ConfigLoader::new().file(ini).load_value();
+        assert!(matches!(
+            unsupported.unwrap_err().downcast_ref::<ConfigError>(),
+            Some(ConfigError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[cfg(feature = "config-toml")]
+    #[test]
+    fn test_toml_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write(
+            dir.path(),
+            "app.toml",
+            "name = \"app\"\n[server]\nhost = \"localhost\"\nport = 3000\n",
+        );
+
+        let settings: Settings = ConfigLoader::new().file(file).load().unwrap();
+        assert_eq!(settings.server.port, 3000);
+    }
+
+    #[cfg(feature = "config-yaml")]
+    #[test]
+    fn test_yaml_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write(
+            dir.path(),
+            "app.yml",
+            "name: app\nserver:\n  host: localhost\n  port: 4000\n",
+        );
+
+        let settings: Settings = ConfigLoader::new().file(file).load().unwrap();
+        assert_eq!(settings.server.port, 4000);
+    }
+
+    #[tokio::test]
+    async fn test_watch_reloads_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write(dir.path(), "app.json", r#"{ "name": "first" }"#);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = ConfigLoader::new().file(&file).watch(
+            Duration::from_millis(10),
+            move |result: AppResult<Value>| {
+                let _ = tx.send(result.unwrap()["name"].clone());
+            },
+        );
+
+        // make sure the modification time differs on coarse-grained filesystems
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let modified = SystemTime::now() + Duration::from_secs(5);
+        write(dir.path(), "app.json", r#"{ "name": "second" }"#);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let name = tokio::task::spawn_blocking(move || rx.recv_timeout(Duration::from_secs(5)))
+            .await
+            .unwrap()
+            .unwrap();
+        handle.abort();
+
+        assert_eq!(name, json!("second"));
+    }
+}
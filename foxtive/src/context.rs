@@ -0,0 +1,280 @@
+//! # Context Module
+//!
+//! A [`RequestContext`] identifies the request a piece of work is done for. It is stored
+//! task-locally, so it doesn't have to be passed down every call, and travels with the
+//! outgoing HTTP requests and RabbitMQ messages, so logs written by other services and
+//! queue consumers can be correlated with the request that originated them.
+//!
+//! ## Features
+//!
+//! - Request id, correlation id and user id, available anywhere in the task
+//! - Injection into and extraction from HTTP headers, `x-request-id`, `x-correlation-id` and `x-user-id`
+//! - Injection into and extraction from RabbitMQ message headers, with the `rabbitmq` feature
+//!   messages published within a context carry it, and consumers handle them within it
+//! - A tracing span holding the ids, for the logs written while handling the request
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::context::RequestContext;
+//! use http::HeaderMap;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let mut incoming = HeaderMap::new();
+//! incoming.insert("x-correlation-id", "checkout-42".parse().unwrap());
+//!
+//! let context = RequestContext::from_headers(&incoming).with_user_id("user-7");
+//!
+//! context
+//!     .scope(async {
+//!         let current = RequestContext::current().unwrap();
+//!         assert_eq!(current.correlation_id, "checkout-42");
+//!
+//!         // forwarded to the next service
+//!         let mut outgoing = HeaderMap::new();
+//!         current.inject_headers(&mut outgoing);
+//!         assert_eq!(outgoing["x-user-id"], "user-7");
+//!     })
+//!     .await;
+//! # });
+//! ```
+
+use crate::helpers::id::ulid;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use tracing::{Instrument, Span, info_span};
+
+tokio::task_local! {
+    static CONTEXT: RequestContext;
+}
+
+/// Identifies the request a piece of work is done for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestContext {
+    /// Unique to each request, or consumed message
+    pub request_id: String,
+    /// Shared by the requests and messages originating from the same request
+    pub correlation_id: String,
+    pub user_id: Option<String>,
+}
+
+impl Default for RequestContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestContext {
+    pub const REQUEST_ID_HEADER: &'static str = "x-request-id";
+    pub const CORRELATION_ID_HEADER: &'static str = "x-correlation-id";
+    pub const USER_ID_HEADER: &'static str = "x-user-id";
+
+    /// Context of a request originating here, its correlation id is its request id
+    pub fn new() -> Self {
+        let request_id = ulid();
+        Self {
+            correlation_id: request_id.clone(),
+            request_id,
+            user_id: None,
+        }
+    }
+
+    pub fn with_user_id(mut self, user_id: impl ToString) -> Self {
+        self.user_id = Some(user_id.to_string());
+        self
+    }
+
+    /// Context of work started on behalf of this one, e.g. a published message,
+    /// with a new request id and the same correlation id and user
+    pub fn child(&self) -> Self {
+        Self {
+            request_id: ulid(),
+            correlation_id: self.correlation_id.clone(),
+            user_id: self.user_id.clone(),
+        }
+    }
+
+    /// Context of the current task, if it runs within [`RequestContext::scope`]
+    pub fn current() -> Option<Self> {
+        CONTEXT.try_with(Clone::clone).ok()
+    }
+
+    /// Runs `future` with this context as the current one, within its [`RequestContext::span`]
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        let span = self.span();
+        CONTEXT.scope(self, future.instrument(span)).await
+    }
+
+    /// Runs `func` with this context as the current one
+    pub fn sync_scope<R>(self, func: impl FnOnce() -> R) -> R {
+        let span = self.span();
+        CONTEXT.sync_scope(self, || span.in_scope(func))
+    }
+
+    /// Span recording the ids on the events logged within it
+    pub fn span(&self) -> Span {
+        info_span!(
+            "request",
+            request_id = %self.request_id,
+            correlation_id = %self.correlation_id,
+            user_id = self.user_id.as_deref(),
+        )
+    }
+
+    /// Context sent by the caller, a missing request id is generated
+    /// and a missing correlation id is the request id
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        Self::from_parts(
+            header(Self::REQUEST_ID_HEADER),
+            header(Self::CORRELATION_ID_HEADER),
+            header(Self::USER_ID_HEADER),
+        )
+    }
+
+    /// Adds the context headers, e.g. to a request made to another service
+    pub fn inject_headers(&self, headers: &mut HeaderMap) {
+        for (name, value) in self.pairs() {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                headers.insert(HeaderName::from_static(name), value);
+            }
+        }
+    }
+
+    /// Context of a consumed message, see [`RequestContext::from_headers`]
+    #[cfg(feature = "rabbitmq")]
+    pub fn from_amqp_headers(headers: &lapin::types::FieldTable) -> Self {
+        use lapin::types::AMQPValue;
+
+        let header = |name: &str| {
+            match headers.inner().get(name) {
+                Some(AMQPValue::LongString(value)) => {
+                    Some(String::from_utf8_lossy(value.as_bytes()).to_string())
+                }
+                Some(AMQPValue::ShortString(value)) => Some(value.to_string()),
+                _ => None,
+            }
+            .filter(|value| !value.is_empty())
+        };
+
+        Self::from_parts(
+            header(Self::REQUEST_ID_HEADER),
+            header(Self::CORRELATION_ID_HEADER),
+            header(Self::USER_ID_HEADER),
+        )
+    }
+
+    /// Adds the context headers to a message
+    #[cfg(feature = "rabbitmq")]
+    pub fn inject_amqp_headers(&self, headers: &mut lapin::types::FieldTable) {
+        use lapin::types::{AMQPValue, LongString};
+
+        for (name, value) in self.pairs() {
+            headers.insert(name.into(), AMQPValue::LongString(LongString::from(value)));
+        }
+    }
+
+    fn from_parts(
+        request_id: Option<String>,
+        correlation_id: Option<String>,
+        user_id: Option<String>,
+    ) -> Self {
+        let request_id = request_id.unwrap_or_else(ulid);
+        Self {
+            correlation_id: correlation_id.unwrap_or_else(|| request_id.clone()),
+            request_id,
+            user_id,
+        }
+    }
+
+    fn pairs(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            (Self::REQUEST_ID_HEADER, Some(self.request_id.as_str())),
+            (
+                Self::CORRELATION_ID_HEADER,
+                Some(self.correlation_id.as_str()),
+            ),
+            (Self::USER_ID_HEADER, self.user_id.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_round_trip() {
+        let context = RequestContext::new().with_user_id(7);
+
+        let mut headers = HeaderMap::new();
+        context.inject_headers(&mut headers);
+
+        assert_eq!(headers.len(), 3);
+        assert_eq!(RequestContext::from_headers(&headers), context);
+    }
+
+    #[test]
+    fn test_missing_headers_are_generated() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("req-1"));
+
+        let context = RequestContext::from_headers(&headers);
+        assert_eq!(context.request_id, "req-1");
+        assert_eq!(context.correlation_id, "req-1");
+        assert_eq!(context.user_id, None);
+
+        let context = RequestContext::from_headers(&HeaderMap::new());
+        assert!(!context.request_id.is_empty());
+        assert_eq!(context.correlation_id, context.request_id);
+    }
+
+    #[test]
+    fn test_child_keeps_correlation() {
+        let parent = RequestContext::new().with_user_id("user-1");
+        let child = parent.child();
+
+        assert_ne!(child.request_id, parent.request_id);
+        assert_eq!(child.correlation_id, parent.request_id);
+        assert_eq!(child.user_id, parent.user_id);
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_current_context() {
+        assert_eq!(RequestContext::current(), None);
+
+        let context = RequestContext::new();
+        let current = context.clone().scope(async {
+            tokio::task::yield_now().await;
+            RequestContext::current()
+        });
+
+        assert_eq!(current.await, Some(context.clone()));
+        assert_eq!(
+            context.clone().sync_scope(RequestContext::current),
+            Some(context)
+        );
+        assert_eq!(RequestContext::current(), None);
+    }
+
+    #[cfg(feature = "rabbitmq")]
+    #[test]
+    fn test_amqp_headers_round_trip() {
+        let context = RequestContext::new().with_user_id("user-1");
+
+        let mut headers = lapin::types::FieldTable::default();
+        context.inject_amqp_headers(&mut headers);
+
+        assert_eq!(RequestContext::from_amqp_headers(&headers), context);
+    }
+}
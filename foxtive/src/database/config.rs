@@ -1,3 +1,5 @@
+use crate::setup::ConnectPolicy;
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[derive(Clone)]
@@ -9,6 +11,34 @@ pub struct DbConfig {
     pub(crate) max_lifetime: Option<Duration>,
     pub(crate) idle_timeout: Option<Duration>,
     pub(crate) connection_timeout: Duration,
+    pub(crate) connect_policy: ConnectPolicy,
+    pub(crate) ssl_mode: Option<SslMode>,
+    pub(crate) ssl_root_cert: Option<PathBuf>,
+    pub(crate) statement_timeout: Option<Duration>,
+}
+
+/// How the connection is secured, the `sslmode` of libpq
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslMode {
+    Disable,
+    Prefer,
+    Require,
+    /// TLS, with a server certificate signed by a trusted authority
+    VerifyCa,
+    /// TLS, with a server certificate signed by a trusted authority and matching the host
+    VerifyFull,
+}
+
+impl SslMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+        }
+    }
 }
 
 impl DbConfig {
@@ -21,6 +51,10 @@ impl DbConfig {
             idle_timeout: Some(Duration::from_secs(10 * 60)),
             max_lifetime: Some(Duration::from_secs(30 * 60)),
             connection_timeout: Duration::from_secs(30),
+            connect_policy: ConnectPolicy::Required,
+            ssl_mode: None,
+            ssl_root_cert: None,
+            statement_timeout: None,
         }
     }
 
@@ -118,4 +152,119 @@ impl DbConfig {
         self.connection_timeout = connection_timeout;
         self
     }
+
+    /// Sets how the pool connects while the state is created.
+    ///
+    /// Defaults to [`ConnectPolicy::Required`].
+    pub fn connect_policy(mut self, connect_policy: ConnectPolicy) -> Self {
+        self.connect_policy = connect_policy;
+        self
+    }
+
+    /// Sets how the connection is secured, overriding the `sslmode` of the DSN.
+    ///
+    /// Defaults to the DSN's, `prefer` when it has none.
+    pub fn ssl_mode(mut self, ssl_mode: SslMode) -> Self {
+        self.ssl_mode = Some(ssl_mode);
+        self
+    }
+
+    /// Sets the certificate authorities the server certificate is verified against,
+    /// used by [`SslMode::VerifyCa`] and [`SslMode::VerifyFull`].
+    ///
+    /// Defaults to `~/.postgresql/root.crt`.
+    pub fn ssl_root_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ssl_root_cert = Some(path.into());
+        self
+    }
+
+    /// Sets the `statement_timeout` of every connection, statements running longer
+    /// are cancelled by the server.
+    ///
+    /// Defaults to the server's.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `statement_timeout` is under a millisecond, the server's unit,
+    /// as a zero timeout disables it
+    pub fn statement_timeout(mut self, statement_timeout: Duration) -> Self {
+        assert!(
+            statement_timeout.as_millis() > 0,
+            "statement_timeout must be at least one millisecond"
+        );
+        self.statement_timeout = Some(statement_timeout);
+        self
+    }
+
+    /// The DSN with the TLS parameters applied, in URL or `key=value` form like the DSN
+    pub(crate) fn connection_string(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(ssl_mode) = self.ssl_mode {
+            params.push(("sslmode", ssl_mode.as_str().to_string()));
+        }
+        if let Some(path) = &self.ssl_root_cert {
+            params.push(("sslrootcert", path.to_string_lossy().into_owned()));
+        }
+
+        let is_url = self.dsn.starts_with("postgres://") || self.dsn.starts_with("postgresql://");
+        let mut dsn = self.dsn.clone();
+        for (key, value) in params {
+            match is_url {
+                true => {
+                    dsn.push(if dsn.contains('?') { '&' } else { '?' });
+                    dsn.push_str(&format!("{key}={}", url_encode(&value)));
+                }
+                false => {
+                    let value = value.replace('\\', "\\\\").replace('\'', "\\'");
+                    dsn.push_str(&format!(" {key}='{value}'"));
+                }
+            }
+        }
+
+        dsn
+    }
+}
+
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_string_applies_tls() {
+        let config = DbConfig::create("postgres://app@db/shop")
+            .ssl_mode(SslMode::VerifyFull)
+            .ssl_root_cert("/etc/certs/root ca.crt");
+        assert_eq!(
+            config.connection_string(),
+            "postgres://app@db/shop?sslmode=verify-full&sslrootcert=/etc/certs/root%20ca.crt"
+        );
+
+        let config = DbConfig::create("host=db dbname=shop").ssl_mode(SslMode::Require);
+        assert_eq!(
+            config.connection_string(),
+            "host=db dbname=shop sslmode='require'"
+        );
+
+        let dsn = "postgres://db/shop?application_name=api";
+        assert_eq!(DbConfig::create(dsn).connection_string(), dsn);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one millisecond")]
+    fn test_statement_timeout_rejects_sub_millisecond() {
+        let _ =
+            DbConfig::create("postgres://db/shop").statement_timeout(Duration::from_micros(500));
+    }
 }
@@ -1,17 +1,41 @@
 use crate::database::config::DbConfig;
 use crate::results::AppResult;
 use anyhow::Error;
-use diesel::r2d2::ConnectionManager;
-use diesel::{PgConnection, r2d2};
+use diesel::r2d2::{ConnectionManager, CustomizeConnection};
+use diesel::{PgConnection, RunQueryDsl, r2d2, sql_query};
+use std::time::Duration;
 
+/// Creates the pool, connecting right away unless the config's connect policy isn't
+/// [`ConnectPolicy::Required`](crate::setup::ConnectPolicy::Required)
 pub fn create_db_pool(config: DbConfig) -> AppResult<crate::database::DBPool> {
-    let manager = ConnectionManager::<PgConnection>::new(&config.dsn);
-    r2d2::Pool::builder()
+    let manager = ConnectionManager::<PgConnection>::new(config.connection_string());
+    let mut builder = r2d2::Pool::builder()
         .max_size(config.max_size)
         .max_lifetime(config.max_lifetime)
         .min_idle(config.min_idle)
         .idle_timeout(config.idle_timeout)
-        .connection_timeout(config.connection_timeout)
-        .build(manager)
-        .map_err(Error::msg)
+        .test_on_check_out(config.test_on_check_out)
+        .connection_timeout(config.connection_timeout);
+
+    if let Some(timeout) = config.statement_timeout {
+        builder = builder.connection_customizer(Box::new(StatementTimeout(timeout)));
+    }
+
+    match config.connect_policy.is_required() {
+        true => builder.build(manager).map_err(Error::msg),
+        false => Ok(builder.build_unchecked(manager)),
+    }
+}
+
+/// Sets the `statement_timeout` of the connections as they are opened
+#[derive(Debug)]
+struct StatementTimeout(Duration);
+
+impl CustomizeConnection<PgConnection, r2d2::Error> for StatementTimeout {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), r2d2::Error> {
+        sql_query(format!("SET statement_timeout = {}", self.0.as_millis()))
+            .execute(conn)
+            .map(drop)
+            .map_err(r2d2::Error::QueryError)
+    }
 }
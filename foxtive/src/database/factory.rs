@@ -0,0 +1,376 @@
+//! # Factories and Seeders
+//!
+//! Standard building blocks for test and seed data:
+//!
+//! - [`Factory`] builds values of a type, usually a diesel `Insertable`, from a [`Faker`].
+//! - [`Faker`] hands out sequences and fake data from a seeded generator, so the same
+//!   seed always produces the same data and failing tests can be reproduced.
+//! - [`Seeder`] inserts data, [`Seeders`] runs them in order, each in its own transaction,
+//!   from a binary, a CLI command or a test.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use diesel::PgConnection;
+//! use foxtive::database::factory::{Factory, Faker, Seeder, Seeders};
+//! use foxtive::prelude::AppResult;
+//!
+//! struct NewUser {
+//!     name: String,
+//!     email: String,
+//!     age: u64,
+//! }
+//!
+//! struct UserFactory;
+//!
+//! impl Factory<NewUser> for UserFactory {
+//!     fn make(&self, fake: &mut Faker) -> NewUser {
+//!         NewUser {
+//!             name: fake.name(),
+//!             email: fake.unique_email(),
+//!             age: fake.number(18..=90),
+//!         }
+//!     }
+//! }
+//!
+//! struct UserSeeder;
+//!
+//! impl Seeder for UserSeeder {
+//!     fn name(&self) -> &'static str {
+//!         "users"
+//!     }
+//!
+//!     fn run(&self, conn: &mut PgConnection, fake: &mut Faker) -> AppResult<()> {
+//!         let users = UserFactory.make_many(fake, 50);
+//!         // diesel::insert_into(users::table).values(&users).execute(conn)?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn main() -> AppResult<()> {
+//! # let mut conn: PgConnection = unimplemented!();
+//! let seeders = Seeders::new().add(UserSeeder);
+//!
+//! // e.g. `cargo run --bin seed -- users`
+//! let names: Vec<String> = std::env::args().skip(1).collect();
+//! match names.is_empty() {
+//!     true => seeders.run(&mut conn)?,
+//!     false => seeders.run_only(&mut conn, &names)?,
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::database::DBPool;
+use crate::database::ext::DatabaseConnectionExt;
+use crate::helpers::blk;
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use diesel::{Connection, PgConnection};
+use std::ops::RangeInclusive;
+use tracing::info;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Chidi", "Daniel", "Emeka", "Fatima", "Grace", "Hannah", "Ibrahim",
+    "James", "Kemi", "Linus", "Maria", "Ngozi", "Olivia", "Peter", "Sofia", "Tunde", "Zainab",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Adeyemi", "Brown", "Garcia", "Hopper", "Ibe", "Johnson", "Kim", "Lovelace", "Martin", "Nwosu",
+    "Okafor", "Rossi", "Smith", "Turing", "Williams",
+];
+
+const WORDS: &[&str] = &[
+    "alpha", "amber", "beacon", "cedar", "delta", "ember", "falcon", "harbor", "island", "jasper",
+    "kernel", "lumen", "meadow", "nimbus", "orbit", "pioneer", "quartz", "river", "summit",
+    "timber", "vector", "willow",
+];
+
+const DEFAULT_SEED: u64 = 0x5EED;
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+/// Seeded generator of sequences and fake data
+#[derive(Debug, Clone)]
+pub struct Faker {
+    state: u64,
+    sequence: u64,
+}
+
+impl Default for Faker {
+    fn default() -> Self {
+        Self::seeded(DEFAULT_SEED)
+    }
+}
+
+impl Faker {
+    /// Creates a faker producing the same data for the same seed
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            state: seed,
+            sequence: 0,
+        }
+    }
+
+    /// Next number of the sequence, starting at 1, e.g. for unique columns
+    pub fn sequence(&mut self) -> u64 {
+        self.sequence += 1;
+        self.sequence
+    }
+
+    /// Random number within the range
+    pub fn number(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        match end.checked_sub(start).and_then(|span| span.checked_add(1)) {
+            Some(span) => start + self.next() % span,
+            None if start > end => start,
+            // the range covers every u64
+            None => self.next(),
+        }
+    }
+
+    pub fn boolean(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    /// Random element of a non-empty slice
+    ///
+    /// # Panics
+    ///
+    /// Panics if `items` is empty
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        assert!(!items.is_empty(), "cannot pick from an empty slice");
+        &items[(self.next() % items.len() as u64) as usize]
+    }
+
+    pub fn first_name(&mut self) -> String {
+        self.pick(FIRST_NAMES).to_string()
+    }
+
+    pub fn last_name(&mut self) -> String {
+        self.pick(LAST_NAMES).to_string()
+    }
+
+    pub fn name(&mut self) -> String {
+        format!("{} {}", self.first_name(), self.last_name())
+    }
+
+    pub fn word(&mut self) -> String {
+        self.pick(WORDS).to_string()
+    }
+
+    /// Sentence of `words` words, capitalized and ending with a period
+    pub fn sentence(&mut self, words: usize) -> String {
+        let sentence = (0..words.max(1))
+            .map(|_| *self.pick(WORDS))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut chars = sentence.chars();
+        match chars.next() {
+            Some(first) => format!("{}{}.", first.to_uppercase(), chars.as_str()),
+            None => sentence,
+        }
+    }
+
+    /// Email address made unique by the sequence
+    pub fn unique_email(&mut self) -> String {
+        let user = self.first_name().to_lowercase();
+        let sequence = self.sequence();
+        format!("{user}{sequence}@{}", self.pick(DOMAINS))
+    }
+
+    /// Username made unique by the sequence
+    pub fn unique_username(&mut self) -> String {
+        let word = self.word();
+        let sequence = self.sequence();
+        format!("{word}_{sequence}")
+    }
+
+    /// SplitMix64
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Builds values of `T` for tests and seeders
+pub trait Factory<T> {
+    /// Builds a value with default, usually fake, data
+    fn make(&self, fake: &mut Faker) -> T;
+
+    fn make_many(&self, fake: &mut Faker, count: usize) -> Vec<T> {
+        (0..count).map(|_| self.make(fake)).collect()
+    }
+
+    /// Builds a value and overrides some of its data, e.g. for a specific state
+    fn make_with<F>(&self, fake: &mut Faker, tweak: F) -> T
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut value = self.make(fake);
+        tweak(&mut value);
+        value
+    }
+}
+
+/// Inserts a set of data, run by [`Seeders`]
+pub trait Seeder: Send + Sync {
+    /// Name used to run the seeder alone, e.g. from the command line
+    fn name(&self) -> &'static str;
+
+    fn run(&self, conn: &mut PgConnection, fake: &mut Faker) -> AppResult<()>;
+}
+
+/// Runs seeders in the order they were added
+pub struct Seeders {
+    seeders: Vec<Box<dyn Seeder>>,
+    seed: u64,
+}
+
+impl Default for Seeders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Seeders {
+    /// Creates an empty runner using the default seed of [`Faker`]
+    pub fn new() -> Self {
+        Self {
+            seeders: vec![],
+            seed: DEFAULT_SEED,
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<S: Seeder + 'static>(mut self, seeder: S) -> Self {
+        self.seeders.push(Box::new(seeder));
+        self
+    }
+
+    /// Seed of the faker handed to the seeders
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.seeders.iter().map(|seeder| seeder.name()).collect()
+    }
+
+    /// Runs every seeder, each in its own transaction, stopping at the first failure
+    pub fn run(&self, conn: &mut PgConnection) -> AppResult<()> {
+        let mut fake = Faker::seeded(self.seed);
+        for seeder in &self.seeders {
+            Self::run_one(seeder.as_ref(), conn, &mut fake)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs the named seeders, in the order they were added
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without running anything, if a name matches no seeder
+    pub fn run_only<N: AsRef<str>>(&self, conn: &mut PgConnection, names: &[N]) -> AppResult<()> {
+        if let Some(unknown) = names
+            .iter()
+            .find(|name| !self.names().contains(&name.as_ref()))
+        {
+            return Err(internal_server_error!(
+                "Unknown seeder '{}', available seeders: {}",
+                unknown.as_ref(),
+                self.names().join(", ")
+            ));
+        }
+
+        let mut fake = Faker::seeded(self.seed);
+        for seeder in &self.seeders {
+            if names.iter().any(|name| name.as_ref() == seeder.name()) {
+                Self::run_one(seeder.as_ref(), conn, &mut fake)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs every seeder on a blocking thread, with a connection from the pool
+    pub async fn run_on(self, pool: DBPool) -> AppResult<()> {
+        blk(move || self.run(&mut *pool.connection()?)).await?
+    }
+
+    fn run_one(seeder: &dyn Seeder, conn: &mut PgConnection, fake: &mut Faker) -> AppResult<()> {
+        info!("[seeder] running {}", seeder.name());
+        conn.transaction(|conn| seeder.run(conn, fake))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct User {
+        name: String,
+        email: String,
+        admin: bool,
+    }
+
+    struct UserFactory;
+
+    impl Factory<User> for UserFactory {
+        fn make(&self, fake: &mut Faker) -> User {
+            User {
+                name: fake.name(),
+                email: fake.unique_email(),
+                admin: false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_seed_produces_same_data() {
+        let first = UserFactory.make_many(&mut Faker::seeded(7), 5);
+        let second = UserFactory.make_many(&mut Faker::seeded(7), 5);
+
+        let names = |users: &[User]| users.iter().map(|u| u.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&first), names(&second));
+    }
+
+    #[test]
+    fn test_sequences_keep_values_unique() {
+        let mut fake = Faker::default();
+        let users = UserFactory.make_many(&mut fake, 100);
+
+        let mut emails = users.iter().map(|u| u.email.as_str()).collect::<Vec<_>>();
+        emails.sort();
+        emails.dedup();
+        assert_eq!(emails.len(), 100);
+        assert_eq!(fake.sequence(), 101);
+    }
+
+    #[test]
+    fn test_make_with_overrides_data() {
+        let admin = UserFactory.make_with(&mut Faker::default(), |user| user.admin = true);
+        assert!(admin.admin);
+    }
+
+    #[test]
+    fn test_numbers_and_sentences() {
+        let mut fake = Faker::default();
+        for _ in 0..100 {
+            assert!((5..=10).contains(&fake.number(5..=10)));
+        }
+        assert_eq!(fake.number(3..=3), 3);
+        fake.number(0..=u64::MAX);
+
+        let sentence = fake.sentence(4);
+        assert_eq!(sentence.split(' ').count(), 4);
+        assert!(sentence.ends_with('.'));
+        assert!(sentence.chars().next().unwrap().is_uppercase());
+    }
+}
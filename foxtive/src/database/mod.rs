@@ -6,10 +6,13 @@ mod config;
 mod conn;
 pub mod ext;
 mod ext_impl;
+pub mod factory;
 pub mod pagination;
+mod tx;
 
-pub use config::DbConfig;
+pub use config::{DbConfig, SslMode};
 pub use conn::create_db_pool;
+pub use tx::with_tx;
 
 pub type DBPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
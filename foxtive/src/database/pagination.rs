@@ -5,7 +5,8 @@ use diesel::prelude::*;
 use diesel::query_builder::*;
 use diesel::query_dsl::methods::LoadQuery;
 use diesel::sql_types::BigInt;
-use serde::Serialize;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::results::AppPaginationResult;
 
@@ -13,22 +14,116 @@ pub trait Paginate: Sized {
     fn paginate(self, page: i64) -> Paginated<Self>;
 }
 
-#[derive(Serialize)]
+/// A page of records and where it stands in the whole result set.
+///
+/// Serialized as `total_pages`, `total_records`, `records`, `page`, `per_page`, `has_next`,
+/// `has_prev` and, when set with [`PageData::with_links`], `links`, so every API returns
+/// pagination in the same shape within the `data` of the JSON envelope.
 pub struct PageData<U> {
     pub total_pages: i64,
     pub total_records: i64,
     pub records: Vec<U>,
+    /// Number of the page, starting at 1
+    pub page: i64,
+    pub per_page: i64,
+    pub links: Option<PageLinks>,
+}
+
+/// Links to the pages around a [`PageData`], e.g. for a `Link` header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageLinks {
+    pub first: String,
+    pub prev: Option<String>,
+    pub next: Option<String>,
+    pub last: String,
+}
+
+impl PageLinks {
+    /// Value of an RFC 5988 `Link` header, e.g. `<https://..?page=3>; rel="next"`
+    pub fn header_value(&self) -> String {
+        [
+            Some((&self.first, "first")),
+            self.prev.as_ref().map(|url| (url, "prev")),
+            self.next.as_ref().map(|url| (url, "next")),
+            Some((&self.last, "last")),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|(url, rel)| format!("<{url}>; rel=\"{rel}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
 }
 
 impl<M> PageData<M> {
-    pub fn new(records: Vec<M>, total_pages: i64, total_records: i64) -> PageData<M> {
+    /// Creates the `page` of a result set of `total_records` split in pages of `per_page`
+    pub fn new(records: Vec<M>, page: i64, per_page: i64, total_records: i64) -> PageData<M> {
         PageData {
             records,
-            total_pages,
+            total_pages: total_pages(total_records, per_page),
             total_records,
+            page,
+            per_page,
+            links: None,
         }
     }
 
+    pub fn has_next(&self) -> bool {
+        self.page < self.total_pages
+    }
+
+    pub fn has_prev(&self) -> bool {
+        self.page > 1
+    }
+
+    /// Links to the first, previous, next and last pages, built from the URL of the
+    /// endpoint and the query of the current request, with its `page` replaced
+    #[cfg(feature = "http")]
+    pub fn links(
+        &self,
+        base_url: &str,
+        params: &crate::http::QueryParams,
+    ) -> crate::results::AppResult<PageLinks> {
+        let mut query: Vec<(String, String)> =
+            serde_urlencoded::from_str(&serde_urlencoded::to_string(params)?)?;
+        query.retain(|(key, _)| key != "page" && key != "per_page");
+        // the extra parameters come from a map, sorting keeps the links stable
+        query.sort();
+
+        let separator = match base_url.contains('?') {
+            true => '&',
+            false => '?',
+        };
+
+        let link = |page: i64| -> crate::results::AppResult<String> {
+            let mut query = query.clone();
+            query.push(("page".to_string(), page.to_string()));
+            query.push(("per_page".to_string(), self.per_page.to_string()));
+            Ok(format!(
+                "{base_url}{separator}{}",
+                serde_urlencoded::to_string(query)?
+            ))
+        };
+
+        Ok(PageLinks {
+            first: link(1)?,
+            prev: self.has_prev().then(|| link(self.page - 1)).transpose()?,
+            next: self.has_next().then(|| link(self.page + 1)).transpose()?,
+            last: link(self.total_pages.max(1))?,
+        })
+    }
+
+    /// Adds the [`PageData::links`] to the serialized page
+    #[cfg(feature = "http")]
+    pub fn with_links(
+        mut self,
+        base_url: &str,
+        params: &crate::http::QueryParams,
+    ) -> crate::results::AppResult<PageData<M>> {
+        self.links = Some(self.links(base_url, params)?);
+        Ok(self)
+    }
+
     pub fn format_result<T, F>(result: PageData<M>, func: F) -> PageData<T>
     where
         F: Fn(M) -> T,
@@ -38,7 +133,14 @@ impl<M> PageData<M> {
             records.push(func(model));
         }
 
-        PageData::new(records, result.total_pages, result.total_records)
+        PageData {
+            records,
+            total_pages: result.total_pages,
+            total_records: result.total_records,
+            page: result.page,
+            per_page: result.per_page,
+            links: result.links,
+        }
     }
 
     pub fn format<T, F>(self, func: F) -> PageData<T>
@@ -49,6 +151,31 @@ impl<M> PageData<M> {
     }
 }
 
+impl<U: Serialize> Serialize for PageData<U> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = 7 + usize::from(self.links.is_some());
+        let mut page = serializer.serialize_struct("PageData", fields)?;
+        page.serialize_field("total_pages", &self.total_pages)?;
+        page.serialize_field("total_records", &self.total_records)?;
+        page.serialize_field("records", &self.records)?;
+        page.serialize_field("page", &self.page)?;
+        page.serialize_field("per_page", &self.per_page)?;
+        page.serialize_field("has_next", &self.has_next())?;
+        page.serialize_field("has_prev", &self.has_prev())?;
+        if let Some(links) = &self.links {
+            page.serialize_field("links", links)?;
+        }
+        page.end()
+    }
+}
+
+fn total_pages(total_records: i64, per_page: i64) -> i64 {
+    match per_page > 0 {
+        true => (total_records + per_page - 1) / per_page,
+        false => 0,
+    }
+}
+
 impl<T> Paginate for T {
     fn paginate(self, page: i64) -> Paginated<Self> {
         Paginated {
@@ -83,17 +210,12 @@ impl<T> Paginated<T> {
     where
         Self: LoadQuery<'a, PgConnection, (U, i64)>,
     {
-        let per_page = self.per_page;
+        let (page, per_page) = (self.page, self.per_page);
         let results = self.load::<(U, i64)>(conn)?;
         let total = results.first().map(|x| x.1).unwrap_or(0);
         let records = results.into_iter().map(|x| x.0).collect();
-        let total_pages = (total as f64 / per_page as f64).ceil() as i64;
 
-        Ok(PageData {
-            records,
-            total_pages,
-            total_records: total,
-        })
+        Ok(PageData::new(records, page, per_page, total))
     }
 }
 
@@ -117,3 +239,72 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_position() {
+        let page = PageData::new(vec![1, 2], 2, 2, 5);
+        assert_eq!(page.total_pages, 3);
+        assert!(page.has_next());
+        assert!(page.has_prev());
+
+        let last = PageData::new(vec![5], 3, 2, 5);
+        assert!(!last.has_next());
+
+        let empty = PageData::<i32>::new(vec![], 1, 10, 0);
+        assert_eq!(empty.total_pages, 0);
+        assert!(!empty.has_next());
+        assert!(!empty.has_prev());
+    }
+
+    #[test]
+    fn test_serialized_shape() {
+        let page = PageData::new(vec!["a"], 1, 1, 2);
+        let json = serde_json::to_value(&page).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "total_pages": 2,
+                "total_records": 2,
+                "records": ["a"],
+                "page": 1,
+                "per_page": 1,
+                "has_next": true,
+                "has_prev": false,
+            })
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_links_keep_the_query() {
+        let params: crate::http::QueryParams =
+            serde_urlencoded::from_str("search=fox&page=2&per_page=2&order=name:asc").unwrap();
+        let page = PageData::new(vec![3, 4], 2, 2, 6)
+            .with_links("https://api.example.com/users", &params)
+            .unwrap();
+
+        let links = page.links.clone().unwrap();
+        assert_eq!(
+            links.next.as_deref(),
+            Some("https://api.example.com/users?order=name%3Aasc&search=fox&page=3&per_page=2")
+        );
+        assert_eq!(
+            links.prev.as_deref(),
+            Some("https://api.example.com/users?order=name%3Aasc&search=fox&page=1&per_page=2")
+        );
+        assert!(links.last.ends_with("page=3&per_page=2"));
+        assert!(
+            links
+                .header_value()
+                .starts_with(r#"<https://api.example.com/users?order=name%3Aasc&search=fox&page=1&per_page=2>; rel="first", "#)
+        );
+
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(json["links"]["next"], links.next.unwrap());
+    }
+}
@@ -0,0 +1,48 @@
+use crate::database::DBPool;
+use crate::database::ext::DatabaseConnectionExt;
+use crate::helpers::blk;
+use crate::prelude::AppResult;
+use diesel::{Connection, PgConnection};
+
+/// Runs `func` in a transaction on a connection of `pool`, on the blocking pool
+///
+/// The transaction is committed when `func` returns `Ok` and rolled back when it
+/// returns `Err`, so a write endpoint needs no connection or transaction handling:
+///
+/// ```no_run
+/// use diesel::prelude::*;
+/// use foxtive::database::{DBPool, with_tx};
+/// use foxtive::prelude::AppResult;
+///
+/// diesel::table! {
+///     accounts (id) {
+///         id -> Int4,
+///         balance -> Int8,
+///     }
+/// }
+///
+/// async fn transfer(pool: &DBPool, from: i32, to: i32, amount: i64) -> AppResult<()> {
+///     with_tx(pool, move |conn| {
+///         diesel::update(accounts::table.find(from))
+///             .set(accounts::balance.eq(accounts::balance - amount))
+///             .execute(conn)?;
+///         diesel::update(accounts::table.find(to))
+///             .set(accounts::balance.eq(accounts::balance + amount))
+///             .execute(conn)?;
+///         Ok(())
+///     })
+///     .await
+/// }
+/// ```
+pub async fn with_tx<R, F>(pool: &DBPool, func: F) -> AppResult<R>
+where
+    R: Send + 'static,
+    F: FnOnce(&mut PgConnection) -> AppResult<R> + Send + 'static,
+{
+    let pool = pool.clone();
+    blk(move || {
+        let mut conn = pool.connection()?;
+        (*conn).transaction(func)
+    })
+    .await?
+}
@@ -1,4 +1,6 @@
 use crate::ValidationErrors;
+use crate::enums::{CodedMessage, ErrorMapping, MessageCatalog};
+use crate::helpers::json::JsonResponse;
 #[cfg(feature = "reqwest")]
 use crate::helpers::reqwest::ReqwestResponseError;
 use crate::results::AppResult;
@@ -23,6 +25,8 @@ pub enum AppMessage {
     InternalServerError(String),
     ErrorMessage(String, StatusCode),
     MissingEnvironmentVariable(String, VarError),
+    /// Error identified by a code, see [`MessageCatalog`]
+    Coded(CodedMessage),
     #[cfg(feature = "reqwest")]
     ReqwestResponseError(ReqwestResponseError),
 }
@@ -106,6 +110,30 @@ impl AppMessage {
         AppMessage::MissingEnvironmentVariable(name.into(), error)
     }
 
+    /// Creates the message of `code` from the installed [`MessageCatalog`], `{name}`
+    /// placeholders of its template are replaced by `params`.
+    ///
+    /// Without a catalog, or for an unknown code, it is an internal server error (500)
+    /// whose message is the code itself.
+    ///
+    /// # Example
+    /// ```
+    /// use foxtive::enums::AppMessage;
+    ///
+    /// let msg = AppMessage::coded("order.not_found", [("id", 42)]);
+    /// assert_eq!(msg.code(), "order.not_found");
+    /// ```
+    pub fn coded<K, V>(code: &str, params: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        match MessageCatalog::global() {
+            Some(catalog) => catalog.message(code, params),
+            None => MessageCatalog::new("en").message(code, params),
+        }
+    }
+
     #[cfg(feature = "reqwest")]
     /// Creates a Reqwest response error message.
     pub fn reqwest_response_error(err: ReqwestResponseError) -> Self {
@@ -130,6 +158,7 @@ impl AppMessage {
             AppMessage::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppMessage::MissingEnvironmentVariable(_, _) => StatusCode::INTERNAL_SERVER_ERROR,
             AppMessage::ErrorMessage(_, status) => *status,
+            AppMessage::Coded(coded) => coded.status,
             #[cfg(feature = "reqwest")]
             AppMessage::ReqwestResponseError(err) => *err.code(),
         }
@@ -152,15 +181,18 @@ impl AppMessage {
             AppMessage::MissingEnvironmentVariable(name, e) => {
                 Cow::from(format!("Missing environment variable '{name}': {e}"))
             }
+            AppMessage::Coded(coded) => Cow::from(coded.render()),
             #[cfg(feature = "reqwest")]
             AppMessage::ReqwestResponseError(err) => Cow::from(err.body().to_string()),
         }
     }
 
-    /// Returns field-level validation errors, if this is a `ValidationError`.
+    /// Returns field-level validation errors, if this is a `ValidationError`
+    /// or a coded message made from one.
     pub fn validation_errors(&self) -> Option<&ValidationErrors> {
         match self {
             AppMessage::ValidationError(_, errors) => Some(errors),
+            AppMessage::Coded(coded) => coded.errors.as_ref(),
             _ => None,
         }
     }
@@ -180,11 +212,21 @@ impl AppMessage {
             AppMessage::InternalServerError(_) => "internal_server_error",
             AppMessage::MissingEnvironmentVariable(_, _) => "missing_environment_variable",
             AppMessage::ErrorMessage(_, _) => "error_message",
+            AppMessage::Coded(_) => "coded",
             #[cfg(feature = "reqwest")]
             AppMessage::ReqwestResponseError(_) => "reqwest_response_error",
         }
     }
 
+    /// Returns the machine-readable code, the catalog code of a coded message
+    /// and the [`kind_name`](Self::kind_name) otherwise.
+    pub fn code(&self) -> &str {
+        match self {
+            AppMessage::Coded(coded) => &coded.code,
+            _ => self.kind_name(),
+        }
+    }
+
     // Status category helpers
 
     /// Returns `true` if the status code is 2xx.
@@ -236,6 +278,27 @@ impl AppMessage {
         anyhow::Error::from(self)
     }
 
+    /// Converts into the standard response envelope, `data` holds the field errors of a
+    /// validation error, the params of a coded message and is `null` otherwise.
+    pub fn to_json_response(&self) -> JsonResponse<serde_json::Value> {
+        let data = match self {
+            AppMessage::ValidationError(_, errors) => serde_json::json!(errors),
+            AppMessage::Coded(coded) => match &coded.errors {
+                Some(errors) => serde_json::json!(errors),
+                None => serde_json::json!(coded.params),
+            },
+            _ => serde_json::Value::Null,
+        };
+
+        JsonResponse {
+            code: self.code().to_string(),
+            success: self.is_success(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            message: Some(self.message().into_owned()),
+            data,
+        }
+    }
+
     /// Converts into an `AppResult<T>` (always `Err`).
     pub fn into_result<T>(self) -> AppResult<T> {
         Err(self.into_anyhow())
@@ -245,8 +308,10 @@ impl AppMessage {
 impl From<crate::Error> for AppMessage {
     fn from(value: anyhow::Error) -> Self {
         value.downcast::<AppMessage>().unwrap_or_else(|e| {
-            error!("AppMessage downcast failed, wrapping as InternalServerError: {e}");
-            AppMessage::InternalServerError(e.to_string())
+            ErrorMapping::map(&e).unwrap_or_else(|| {
+                error!("AppMessage downcast failed, wrapping as InternalServerError: {e}");
+                AppMessage::InternalServerError(e.to_string())
+            })
         })
     }
 }
@@ -414,6 +479,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_code() {
+        assert_eq!(AppMessage::not_found("x").code(), "not_found");
+
+        MessageCatalog::new("en")
+            .entry(
+                "app_message.test.locked",
+                StatusCode::LOCKED,
+                [("en", "Account {name} is locked")],
+            )
+            .install()
+            .unwrap();
+
+        let msg = AppMessage::coded("app_message.test.locked", [("name", "ada")]);
+        assert_eq!(msg.code(), "app_message.test.locked");
+        assert_eq!(msg.status_code(), StatusCode::LOCKED);
+        assert_eq!(msg.to_string(), "Account ada is locked");
+    }
+
+    #[test]
+    fn test_to_json_response() {
+        let mut errors = ValidationErrors::new();
+        errors.insert("email".into(), vec!["is required".into()]);
+
+        let response = AppMessage::validation_error("Validation failed", errors).to_json_response();
+        assert_eq!(response.code, "validation_error");
+        assert!(!response.success);
+        assert_eq!(response.message.as_deref(), Some("Validation failed"));
+        assert_eq!(response.data["email"][0], "is required");
+
+        let response = AppMessage::success("Saved").to_json_response();
+        assert!(response.success);
+        assert!(response.data.is_null());
+    }
+
     #[cfg(feature = "reqwest")]
     #[test]
     fn test_reqwest_response_error() {
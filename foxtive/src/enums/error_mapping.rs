@@ -0,0 +1,164 @@
+use crate::enums::AppMessage;
+use std::any::TypeId;
+use std::error::Error as StdError;
+use std::sync::{Arc, RwLock};
+
+type Mapper = Arc<dyn Fn(&crate::Error) -> Option<AppMessage> + Send + Sync>;
+
+static MAPPINGS: RwLock<Vec<(TypeId, Mapper)>> = RwLock::new(Vec::new());
+
+/// Domain error that knows its [`AppMessage`], and so its status code
+///
+/// Once the type is registered with [`ErrorMapping::register`], errors of that type
+/// propagated with `?` through an [`AppResult`](crate::results::AppResult) become the
+/// right message when converted to an [`AppMessage`], instead of a 500.
+///
+/// # Example
+///
+/// ```
+/// use foxtive::enums::{AppMessage, ErrorMapping, ToAppMessage};
+/// use foxtive::results::AppResult;
+/// use http::StatusCode;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// enum WalletError {
+///     #[error("wallet {0} not found")]
+///     NotFound(u64),
+///     #[error("insufficient funds")]
+///     InsufficientFunds,
+/// }
+///
+/// impl ToAppMessage for WalletError {
+///     fn to_app_message(&self) -> AppMessage {
+///         match self {
+///             WalletError::NotFound(_) => AppMessage::not_found(self.to_string()),
+///             WalletError::InsufficientFunds => AppMessage::conflict(self.to_string()),
+///         }
+///     }
+/// }
+///
+/// ErrorMapping::register::<WalletError>();
+///
+/// fn debit() -> AppResult<()> {
+///     Err(WalletError::InsufficientFunds)?
+/// }
+///
+/// let msg = AppMessage::from(debit().unwrap_err());
+/// assert_eq!(msg.status_code(), StatusCode::CONFLICT);
+/// ```
+pub trait ToAppMessage {
+    fn to_app_message(&self) -> AppMessage;
+}
+
+impl ToAppMessage for AppMessage {
+    fn to_app_message(&self) -> AppMessage {
+        self.clone()
+    }
+}
+
+/// Process-wide table of error types mapped to [`AppMessage`]s, consulted when an
+/// [`Error`](crate::Error) that isn't an [`AppMessage`] is converted to one
+pub struct ErrorMapping;
+
+impl ErrorMapping {
+    /// Maps errors of type `E` with their [`ToAppMessage`] implementation
+    pub fn register<E>()
+    where
+        E: ToAppMessage + StdError + Send + Sync + 'static,
+    {
+        Self::register_with::<E>(E::to_app_message);
+    }
+
+    /// Maps errors of type `E` with `map`, for error types of other crates
+    ///
+    /// Registering a type again replaces its previous mapping.
+    pub fn register_with<E>(map: impl Fn(&E) -> AppMessage + Send + Sync + 'static)
+    where
+        E: StdError + Send + Sync + 'static,
+    {
+        let mapper: Mapper = Arc::new(move |err: &crate::Error| {
+            err.downcast_ref::<E>()
+                .or_else(|| err.chain().find_map(|cause| cause.downcast_ref::<E>()))
+                .map(&map)
+        });
+
+        let mut mappings = MAPPINGS.write().unwrap_or_else(|err| err.into_inner());
+        mappings.retain(|(type_id, _)| *type_id != TypeId::of::<E>());
+        mappings.push((TypeId::of::<E>(), mapper));
+    }
+
+    /// Removes the mapping of errors of type `E`
+    pub fn unregister<E: 'static>() {
+        MAPPINGS
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|(type_id, _)| *type_id != TypeId::of::<E>());
+    }
+
+    /// The message of the first registered type found in the error or its causes
+    pub fn map(err: &crate::Error) -> Option<AppMessage> {
+        let mappers: Vec<Mapper> = MAPPINGS
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .map(|(_, mapper)| mapper.clone())
+            .collect();
+
+        mappers.iter().find_map(|mapper| mapper(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+    use http::StatusCode;
+
+    #[derive(Debug, thiserror::Error)]
+    enum AccountError {
+        #[error("account is locked")]
+        Locked,
+        #[error("account not found")]
+        Missing,
+    }
+
+    impl ToAppMessage for AccountError {
+        fn to_app_message(&self) -> AppMessage {
+            match self {
+                AccountError::Locked => AppMessage::error_message("locked", StatusCode::LOCKED),
+                AccountError::Missing => AppMessage::not_found("account not found"),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("upstream failed")]
+    struct UpstreamError;
+
+    #[test]
+    fn test_registered_errors_are_mapped() {
+        ErrorMapping::register::<AccountError>();
+
+        let msg = AppMessage::from(crate::Error::from(AccountError::Missing));
+        assert_eq!(msg.status_code(), StatusCode::NOT_FOUND);
+
+        // found behind context too
+        let err = Err::<(), _>(AccountError::Locked)
+            .context("loading account")
+            .unwrap_err();
+        assert_eq!(AppMessage::from(err).status_code(), StatusCode::LOCKED);
+    }
+
+    #[test]
+    fn test_register_with_and_unregister() {
+        ErrorMapping::register_with::<UpstreamError>(|_| {
+            AppMessage::error_message("upstream failed", StatusCode::BAD_GATEWAY)
+        });
+        let msg = AppMessage::from(crate::Error::from(UpstreamError));
+        assert_eq!(msg.status_code(), StatusCode::BAD_GATEWAY);
+
+        ErrorMapping::unregister::<UpstreamError>();
+        let msg = AppMessage::from(crate::Error::from(UpstreamError));
+        assert_eq!(msg.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
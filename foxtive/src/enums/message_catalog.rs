@@ -0,0 +1,309 @@
+use crate::ValidationErrors;
+use crate::enums::AppMessage;
+use crate::internal_server_error;
+use crate::results::AppResult;
+use http::StatusCode;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+
+static CATALOG: OnceLock<MessageCatalog> = OnceLock::new();
+
+/// Error identified by a machine-readable code, created with [`AppMessage::coded`]
+/// or [`MessageCatalog::message`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodedMessage {
+    pub code: String,
+    pub status: StatusCode,
+    /// Message in the catalog's default locale, `{name}` placeholders are replaced by the params
+    pub template: String,
+    pub params: BTreeMap<String, String>,
+    /// Field errors of the validation error the code was attached to, see
+    /// [`AppResultExt::with_code`](crate::ext::AppResultExt::with_code)
+    pub errors: Option<ValidationErrors>,
+}
+
+impl CodedMessage {
+    /// The template with its placeholders replaced
+    pub fn render(&self) -> String {
+        render(&self.template, &self.params)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CatalogEntry {
+    status: StatusCode,
+    /// locale → message template
+    templates: HashMap<String, String>,
+}
+
+/// Error codes of the application, with their status and localized message templates,
+/// so every API returns the same code, status and wording for the same error.
+///
+/// # Example
+///
+/// ```
+/// use foxtive::enums::MessageCatalog;
+/// use foxtive::StatusCode;
+///
+/// let catalog = MessageCatalog::new("en")
+///     .entry("wallet.insufficient_funds", StatusCode::UNPROCESSABLE_ENTITY, [
+///         ("en", "Your balance of {balance} is too low"),
+///         ("fr", "Votre solde de {balance} est insuffisant"),
+///     ]);
+///
+/// let message = catalog.message("wallet.insufficient_funds", [("balance", "12.50")]);
+/// assert_eq!(message.code(), "wallet.insufficient_funds");
+/// assert_eq!(message.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+/// assert_eq!(message.message(), "Your balance of 12.50 is too low");
+/// assert_eq!(catalog.localize(&message, "fr-CA"), "Votre solde de 12.50 est insuffisant");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageCatalog {
+    default_locale: String,
+    entries: HashMap<String, CatalogEntry>,
+}
+
+impl MessageCatalog {
+    pub fn new(default_locale: &str) -> Self {
+        Self {
+            default_locale: default_locale.to_string(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Adds an error code with its status and `(locale, template)` pairs
+    pub fn entry<'a>(
+        mut self,
+        code: &str,
+        status: StatusCode,
+        templates: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let templates = templates
+            .into_iter()
+            .map(|(locale, template)| (locale.to_string(), template.to_string()))
+            .collect();
+
+        self.entries
+            .insert(code.to_string(), CatalogEntry { status, templates });
+        self
+    }
+
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
+    pub fn contains(&self, code: &str) -> bool {
+        self.entries.contains_key(code)
+    }
+
+    /// Sets the catalog used by [`AppMessage::coded`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a catalog is already installed
+    pub fn install(self) -> AppResult<()> {
+        CATALOG
+            .set(self)
+            .map_err(|_| internal_server_error!("A message catalog is already installed"))
+    }
+
+    /// The catalog set with [`MessageCatalog::install`]
+    pub fn global() -> Option<&'static MessageCatalog> {
+        CATALOG.get()
+    }
+
+    /// Creates the message of `code`, an unknown code is an internal server error
+    /// whose message is the code itself
+    pub fn message<K, V>(&self, code: &str, params: impl IntoIterator<Item = (K, V)>) -> AppMessage
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        AppMessage::Coded(self.coded(code, params))
+    }
+
+    pub(crate) fn coded<K, V>(
+        &self,
+        code: &str,
+        params: impl IntoIterator<Item = (K, V)>,
+    ) -> CodedMessage
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        let (status, template) = match self.entries.get(code) {
+            Some(entry) => (
+                entry.status,
+                self.template(entry, &self.default_locale)
+                    .unwrap_or(code)
+                    .to_string(),
+            ),
+            None => (StatusCode::INTERNAL_SERVER_ERROR, code.to_string()),
+        };
+
+        CodedMessage {
+            code: code.to_string(),
+            status,
+            template,
+            params: params
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.to_string()))
+                .collect(),
+            errors: None,
+        }
+    }
+
+    /// Text of `message` in `locale`, e.g. `fr-CA`, falling back to its language, `fr`,
+    /// and then to the default locale. Messages without a code keep their text.
+    pub fn localize<'a>(&self, message: &'a AppMessage, locale: &str) -> Cow<'a, str> {
+        let AppMessage::Coded(coded) = message else {
+            return message.message();
+        };
+
+        match self
+            .entries
+            .get(&coded.code)
+            .and_then(|entry| self.template(entry, locale))
+        {
+            Some(template) => Cow::Owned(render(template, &coded.params)),
+            None => Cow::Owned(coded.render()),
+        }
+    }
+
+    /// [`AppMessage::to_json_response`] with the message localized
+    pub fn to_json_response(
+        &self,
+        message: &AppMessage,
+        locale: &str,
+    ) -> crate::helpers::json::JsonResponse<serde_json::Value> {
+        let mut response = message.to_json_response();
+        response.message = Some(self.localize(message, locale).into_owned());
+        response
+    }
+
+    fn template<'a>(&'a self, entry: &'a CatalogEntry, locale: &str) -> Option<&'a str> {
+        let language = locale.split(['-', '_']).next().unwrap_or(locale);
+
+        [locale, language, self.default_locale.as_str()]
+            .into_iter()
+            .find_map(|locale| entry.templates.get(locale))
+            .map(String::as_str)
+    }
+}
+
+/// Replaces the `{name}` placeholders, unknown ones are kept
+fn render(template: &str, params: &BTreeMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest
+            .find('}')
+            .and_then(|end| Some((end, params.get(&rest[1..end])?)));
+
+        match value {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> MessageCatalog {
+        MessageCatalog::new("en")
+            .entry(
+                "user.not_found",
+                StatusCode::NOT_FOUND,
+                [
+                    ("en", "User {id} was not found"),
+                    ("fr", "Utilisateur {id} introuvable"),
+                ],
+            )
+            .entry(
+                "user.banned",
+                StatusCode::FORBIDDEN,
+                [("fr", "Compte suspendu")],
+            )
+    }
+
+    #[test]
+    fn test_render() {
+        let params = BTreeMap::from([("id".to_string(), "7".to_string())]);
+        assert_eq!(render("user {id}, {id}", &params), "user 7, 7");
+        assert_eq!(render("{unknown} {id}", &params), "{unknown} 7");
+        assert_eq!(render("unclosed {id", &params), "unclosed {id");
+    }
+
+    #[test]
+    fn test_message_uses_catalog_status_and_default_locale() {
+        let message = catalog().message("user.not_found", [("id", 7)]);
+
+        assert_eq!(message.code(), "user.not_found");
+        assert_eq!(message.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(message.message(), "User 7 was not found");
+        assert_eq!(message.kind_name(), "coded");
+    }
+
+    #[test]
+    fn test_unknown_code() {
+        let message = catalog().message("order.missing", [("id", 7)]);
+
+        assert_eq!(message.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(message.message(), "order.missing");
+    }
+
+    #[test]
+    fn test_localize_falls_back() {
+        let catalog = catalog();
+        let message = catalog.message("user.not_found", [("id", 7)]);
+
+        assert_eq!(
+            catalog.localize(&message, "fr"),
+            "Utilisateur 7 introuvable"
+        );
+        assert_eq!(
+            catalog.localize(&message, "fr_BE"),
+            "Utilisateur 7 introuvable"
+        );
+        assert_eq!(catalog.localize(&message, "de"), "User 7 was not found");
+
+        // no template in the default locale
+        let banned = catalog.message("user.banned", None::<(&str, &str)>);
+        assert_eq!(banned.message(), "user.banned");
+        assert_eq!(catalog.localize(&banned, "fr"), "Compte suspendu");
+
+        let plain = AppMessage::not_found("Missing");
+        assert_eq!(catalog.localize(&plain, "fr"), "Missing");
+    }
+
+    #[test]
+    fn test_json_response() {
+        let catalog = catalog();
+        let message = catalog.message("user.not_found", [("id", 7)]);
+
+        let response = catalog.to_json_response(&message, "fr");
+        assert_eq!(response.code, "user.not_found");
+        assert!(!response.success);
+        assert_eq!(
+            response.message.as_deref(),
+            Some("Utilisateur 7 introuvable")
+        );
+        assert_eq!(response.data, serde_json::json!({"id": "7"}));
+    }
+}
@@ -1,3 +1,7 @@
 mod app_message;
+mod error_mapping;
+mod message_catalog;
 
 pub use app_message::AppMessage;
+pub use error_mapping::{ErrorMapping, ToAppMessage};
+pub use message_catalog::{CodedMessage, MessageCatalog};
@@ -4,33 +4,77 @@ use crate::prelude::AppResult;
 use std::fmt;
 use std::str::FromStr;
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub enum Environment {
     #[default]
     Local,
     Development,
+    Testing,
     Staging,
     Production,
+    /// Any other environment, e.g. `sandbox`, named in lowercase
+    ///
+    /// Parsed from the explicit `custom:<name>` form only, so a misspelt
+    /// known name is rejected rather than running with the wrong defaults.
+    Custom(String),
 }
 
 impl Environment {
+    /// Creates an environment from its name, known names give their variant
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty or not made of letters, digits, `-` and `_`
+    pub fn custom(name: &str) -> AppResult<Environment> {
+        let name = name.trim().to_lowercase();
+        if let Some(env) = Self::known(&name) {
+            return Ok(env);
+        }
+
+        match !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            true => Ok(Environment::Custom(name)),
+            false => Err(internal_server_error!(
+                "Invalid custom environment name: '{name}'. Environment names are made of letters, digits, '-' and '_'"
+            )),
+        }
+    }
+
+    fn known(name: &str) -> Option<Environment> {
+        match name {
+            "local" | "localhost" => Some(Environment::Local),
+            "development" | "develop" | "dev" => Some(Environment::Development),
+            "testing" | "test" => Some(Environment::Testing),
+            "staging" | "stage" | "stg" => Some(Environment::Staging),
+            "production" | "prod" | "prd" => Some(Environment::Production),
+            _ => None,
+        }
+    }
+
     /// Returns the string representation of the environment
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Environment::Local => "local",
             Environment::Development => "development",
+            Environment::Testing => "testing",
             Environment::Staging => "staging",
             Environment::Production => "production",
+            Environment::Custom(name) => name,
         }
     }
 
     /// Returns the abbreviated form of the environment
-    pub fn as_short_str(&self) -> &'static str {
+    pub fn as_short_str(&self) -> &str {
         match self {
             Environment::Local => "local",
             Environment::Development => "dev",
+            Environment::Testing => "test",
             Environment::Staging => "staging",
             Environment::Production => "prod",
+            Environment::Custom(name) => name,
         }
     }
 
@@ -39,11 +83,21 @@ impl Environment {
         matches!(self, Environment::Production)
     }
 
+    /// Checks if the environment is the one tests run in
+    pub fn is_testing(&self) -> bool {
+        matches!(self, Environment::Testing)
+    }
+
     /// Checks if the environment is local development
     pub fn is_local(&self) -> bool {
         matches!(self, Environment::Local)
     }
 
+    /// Checks if the environment is a custom one
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Environment::Custom(_))
+    }
+
     /// Checks if the environment is a development-like environment (local or dev)
     pub fn is_dev_like(&self) -> bool {
         matches!(self, Environment::Local | Environment::Development)
@@ -71,11 +125,12 @@ impl Environment {
             .unwrap_or(default)
     }
 
-    /// Gets all possible environment variants
+    /// Gets all the known environment variants, custom environments excluded
     pub fn all() -> &'static [Environment] {
         &[
             Environment::Local,
             Environment::Development,
+            Environment::Testing,
             Environment::Staging,
             Environment::Production,
         ]
@@ -91,16 +146,19 @@ impl fmt::Display for Environment {
 impl FromStr for Environment {
     type Err = crate::Error;
 
+    /// Parses the name case-insensitively, accepting the common abbreviations,
+    /// custom environments must be spelt `custom:<name>`
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "local" => Ok(Environment::Local),
-            "development" | "dev" => Ok(Environment::Development),
-            "staging" | "stage" => Ok(Environment::Staging),
-            "production" | "prod" => Ok(Environment::Production),
-            _ => Err(internal_server_error!(
-                "Invalid environment value: '{s}'. Valid values are: local, development (dev), staging (stage), production (prod)"
-            )),
+        let name = s.trim().to_lowercase();
+        if let Some(custom) = name.strip_prefix("custom:") {
+            return Environment::custom(custom);
         }
+
+        Self::known(&name).ok_or_else(|| {
+            internal_server_error!(
+                "Invalid environment value: '{s}'. Valid values are: local, development (dev), testing (test), staging (stage), production (prod) or custom:<name>"
+            )
+        })
     }
 }
 
@@ -114,7 +172,10 @@ mod serde_impl {
         where
             S: Serializer,
         {
-            serializer.serialize_str(self.as_str())
+            match self {
+                Environment::Custom(name) => serializer.serialize_str(&format!("custom:{name}")),
+                env => serializer.serialize_str(env.as_str()),
+            }
         }
     }
 
@@ -202,8 +263,40 @@ mod tests {
             Environment::Development
         );
 
+        // Custom environments are explicit
+        assert_eq!(
+            " custom:Sandbox ".parse::<Environment>().unwrap(),
+            Environment::Custom("sandbox".to_string())
+        );
+        assert!("custom:".parse::<Environment>().is_err());
+        assert!("custom:sand box".parse::<Environment>().is_err());
+
         // Invalid value
         assert!("invalid".parse::<Environment>().is_err());
+        assert!("prodction".parse::<Environment>().is_err());
+    }
+
+    #[test]
+    fn test_custom() {
+        let sandbox = Environment::custom("sandbox").unwrap();
+        assert_eq!(sandbox.as_str(), "sandbox");
+        assert_eq!(sandbox.as_short_str(), "sandbox");
+        assert!(sandbox.is_custom());
+        assert!(!sandbox.is_production());
+        assert!(sandbox.allows_debug());
+
+        assert_eq!(Environment::custom("PRD").unwrap(), Environment::Production);
+        assert!(Environment::Testing.is_testing());
+    }
+
+    #[test]
+    fn test_serde() {
+        let json = serde_json::to_string(&Environment::custom("sandbox").unwrap()).unwrap();
+        assert_eq!(json, "\"custom:sandbox\"");
+        let env: Environment = serde_json::from_str(&json).unwrap();
+        assert_eq!(env, Environment::Custom("sandbox".to_string()));
+        let env: Environment = serde_json::from_str("\"prod\"").unwrap();
+        assert_eq!(env, Environment::Production);
     }
 
     #[test]
@@ -220,9 +313,10 @@ mod tests {
     #[test]
     fn test_all() {
         let all = Environment::all();
-        assert_eq!(all.len(), 4);
+        assert_eq!(all.len(), 5);
         assert!(all.contains(&Environment::Local));
         assert!(all.contains(&Environment::Development));
+        assert!(all.contains(&Environment::Testing));
         assert!(all.contains(&Environment::Staging));
         assert!(all.contains(&Environment::Production));
     }
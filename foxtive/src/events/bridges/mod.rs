@@ -0,0 +1,10 @@
+#[cfg(feature = "rabbitmq")]
+mod rabbitmq_bridge;
+#[cfg(feature = "redis")]
+mod redis_bridge;
+
+#[cfg(feature = "rabbitmq")]
+pub use rabbitmq_bridge::RabbitMqEventBridge;
+
+#[cfg(feature = "redis")]
+pub use redis_bridge::RedisEventBridge;
@@ -0,0 +1,36 @@
+use crate::events::contract::EventBridgeContract;
+use crate::prelude::{AppResult, RabbitMQ};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Publishes events to a RabbitMQ exchange, using the event name as routing key
+pub struct RabbitMqEventBridge {
+    rabbitmq: Arc<Mutex<RabbitMQ>>,
+    exchange: String,
+}
+
+impl RabbitMqEventBridge {
+    /// # Arguments
+    ///
+    /// * `rabbitmq`: RabbitMQ client to publish with
+    /// * `exchange`: exchange receiving the events, typically a topic exchange
+    pub fn new(rabbitmq: Arc<Mutex<RabbitMQ>>, exchange: &str) -> Self {
+        Self {
+            rabbitmq,
+            exchange: exchange.to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventBridgeContract for RabbitMqEventBridge {
+    async fn publish(&self, event: &str, payload: &Value) -> AppResult<()> {
+        let content = serde_json::to_vec(payload)?;
+        self.rabbitmq
+            .lock()
+            .await
+            .publish(&self.exchange, event, &content)
+            .await
+    }
+}
@@ -0,0 +1,33 @@
+use crate::events::contract::EventBridgeContract;
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Publishes events to Redis pub/sub, on the channel `{prefix}{event}`
+pub struct RedisEventBridge {
+    redis: Arc<Redis>,
+    prefix: String,
+}
+
+impl RedisEventBridge {
+    /// # Arguments
+    ///
+    /// * `redis`: Redis client to publish with
+    /// * `prefix`: prepended to event names to build channel names, e.g. `events:`
+    pub fn new(redis: Arc<Redis>, prefix: &str) -> Self {
+        Self {
+            redis,
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventBridgeContract for RedisEventBridge {
+    async fn publish(&self, event: &str, payload: &Value) -> AppResult<()> {
+        let channel = format!("{}{event}", self.prefix);
+        self.redis.publish(&channel, payload).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+use crate::prelude::AppResult;
+use serde_json::Value;
+
+/// Contract for forwarding dispatched events to an external broker, so that
+/// other services can react to them too
+#[async_trait::async_trait]
+pub trait EventBridgeContract: Send + Sync {
+    /// Publishes an event
+    ///
+    /// # Parameters
+    /// - `event`: Name of the event, see [`Event::NAME`](crate::events::Event::NAME)
+    /// - `payload`: The serialized event
+    async fn publish(&self, event: &str, payload: &Value) -> AppResult<()>;
+}
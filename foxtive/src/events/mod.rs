@@ -0,0 +1,349 @@
+//! # Events Module
+//!
+//! An in-process event bus: modules dispatch domain events (`UserRegistered`,
+//! `OrderPaid`, ...) without knowing who reacts to them, and listeners subscribe
+//! to the events they care about.
+//!
+//! Listeners are either synchronous, running inline while the event is dispatched,
+//! or asynchronous, running concurrently with each other. An optional bridge also
+//! publishes every event to Redis pub/sub or RabbitMQ, for other services.
+//!
+//! ## Features
+//!
+//! This module requires the `events` feature to be enabled, the dispatcher is then
+//! available from the global state through [`AppStateExt::events`](crate::prelude::AppStateExt).
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::events::{Event, EventDispatcher};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct UserRegistered {
+//!     email: String,
+//! }
+//!
+//! impl Event for UserRegistered {
+//!     const NAME: &'static str = "user.registered";
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let events = EventDispatcher::new();
+//!
+//! events.listen(|event: &UserRegistered| {
+//!     println!("audit: {} registered", event.email);
+//!     Ok(())
+//! });
+//!
+//! events.listen_async(|event: std::sync::Arc<UserRegistered>| async move {
+//!     println!("sending welcome email to {}", event.email);
+//!     Ok(())
+//! });
+//!
+//! let event = UserRegistered { email: "jane@example.com".to_string() };
+//! events.dispatch(event).await.unwrap();
+//! # });
+//! ```
+
+pub mod bridges;
+pub mod contract;
+
+use crate::events::contract::EventBridgeContract;
+use crate::prelude::AppResult;
+use futures_util::future::{BoxFuture, join_all, ready};
+use serde::Serialize;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, error};
+
+/// A domain event, identified by its type when dispatched in-process and by its
+/// name when bridged to other services.
+pub trait Event: Serialize + Send + Sync + 'static {
+    /// Name of the event, e.g. `user.registered`
+    const NAME: &'static str;
+}
+
+type SharedEvent = Arc<dyn Any + Send + Sync>;
+type Listener = Arc<dyn Fn(SharedEvent) -> BoxFuture<'static, AppResult<()>> + Send + Sync>;
+
+#[derive(Default)]
+struct Inner {
+    listeners: RwLock<HashMap<TypeId, Vec<Listener>>>,
+    bridge: RwLock<Option<Arc<dyn EventBridgeContract>>>,
+}
+
+/// Routes dispatched events to their listeners.
+///
+/// Cloning is cheap and clones share listeners, so a dispatcher can be handed to
+/// every module needing it.
+#[derive(Clone, Default)]
+pub struct EventDispatcher {
+    inner: Arc<Inner>,
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a listener running inline when an event of type `E` is dispatched
+    pub fn listen<E, F>(&self, listener: F) -> &Self
+    where
+        E: Event,
+        F: Fn(&E) -> AppResult<()> + Send + Sync + 'static,
+    {
+        self.add_listener::<E>(Arc::new(move |event: SharedEvent| {
+            let result = match event.downcast_ref::<E>() {
+                Some(event) => listener(event),
+                None => Ok(()),
+            };
+            Box::pin(ready(result))
+        }))
+    }
+
+    /// Registers a listener running concurrently with the other asynchronous
+    /// listeners when an event of type `E` is dispatched
+    pub fn listen_async<E, F, Fut>(&self, listener: F) -> &Self
+    where
+        E: Event,
+        F: Fn(Arc<E>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = AppResult<()>> + Send + 'static,
+    {
+        self.add_listener::<E>(Arc::new(move |event: SharedEvent| {
+            match event.downcast::<E>() {
+                Ok(event) => Box::pin(listener(event)),
+                Err(_) => Box::pin(ready(Ok(()))),
+            }
+        }))
+    }
+
+    /// Publishes every dispatched event through the given bridge, replacing any previous one
+    pub fn set_bridge(&self, bridge: Arc<dyn EventBridgeContract>) -> &Self {
+        *self.inner.bridge.write().unwrap() = Some(bridge);
+        self
+    }
+
+    /// Number of listeners registered for events of type `E`
+    pub fn listener_count<E: Event>(&self) -> usize {
+        self.listeners_of::<E>().len()
+    }
+
+    /// Dispatches the event to all its listeners and to the bridge, if any.
+    ///
+    /// Every listener runs even if some of them fail, the returned error then
+    /// reports the failures.
+    pub async fn dispatch<E: Event>(&self, event: E) -> AppResult<()> {
+        let listeners = self.listeners_of::<E>();
+        debug!(
+            "[events] dispatching '{}' to {} listeners",
+            E::NAME,
+            listeners.len()
+        );
+
+        let event = Arc::new(event);
+        let bridge = self.inner.bridge.read().unwrap().clone();
+        let payload = match bridge {
+            Some(_) => Some(serde_json::to_value(&*event)?),
+            None => None,
+        };
+
+        let shared: SharedEvent = event;
+        let deliveries = listeners
+            .iter()
+            .map(|listener| listener(shared.clone()))
+            .collect::<Vec<_>>();
+
+        let mut errors = join_all(deliveries)
+            .await
+            .into_iter()
+            .filter_map(Result::err)
+            .collect::<Vec<_>>();
+
+        if let (Some(bridge), Some(payload)) = (bridge, payload)
+            && let Err(err) = bridge.publish(E::NAME, &payload).await
+        {
+            errors.push(err);
+        }
+
+        for err in &errors {
+            error!("[events] listener of '{}' failed: {err}", E::NAME);
+        }
+
+        match errors.len() {
+            0 => Ok(()),
+            count => Err(anyhow::anyhow!(
+                "{count} listener(s) of '{}' failed: {}",
+                E::NAME,
+                errors[0]
+            )),
+        }
+    }
+
+    /// Dispatches the event in the background, failures are only logged
+    pub fn dispatch_detached<E: Event>(&self, event: E) {
+        let dispatcher = self.clone();
+        tokio::spawn(async move {
+            let _ = dispatcher.dispatch(event).await;
+        });
+    }
+
+    fn add_listener<E: Event>(&self, listener: Listener) -> &Self {
+        self.inner
+            .listeners
+            .write()
+            .unwrap()
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(listener);
+        self
+    }
+
+    fn listeners_of<E: Event>(&self) -> Vec<Listener> {
+        self.inner
+            .listeners
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<E>())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Serialize)]
+    struct OrderPaid {
+        order_id: u64,
+    }
+
+    impl Event for OrderPaid {
+        const NAME: &'static str = "order.paid";
+    }
+
+    #[derive(Serialize)]
+    struct OrderCancelled;
+
+    impl Event for OrderCancelled {
+        const NAME: &'static str = "order.cancelled";
+    }
+
+    #[derive(Default)]
+    struct RecordingBridge {
+        published: Mutex<Vec<(String, Value)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventBridgeContract for RecordingBridge {
+        async fn publish(&self, event: &str, payload: &Value) -> AppResult<()> {
+            self.published
+                .lock()
+                .unwrap()
+                .push((event.to_string(), payload.clone()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_reaches_listeners_of_the_event_type() {
+        let events = EventDispatcher::new();
+        let total = Arc::new(AtomicUsize::new(0));
+
+        let sync_total = total.clone();
+        events.listen(move |event: &OrderPaid| {
+            sync_total.fetch_add(event.order_id as usize, Ordering::SeqCst);
+            Ok(())
+        });
+
+        let async_total = total.clone();
+        events.listen_async(move |event: Arc<OrderPaid>| {
+            let total = async_total.clone();
+            async move {
+                total.fetch_add(event.order_id as usize * 10, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        events.listen(|_: &OrderCancelled| panic!("must not be called"));
+
+        assert_eq!(events.listener_count::<OrderPaid>(), 2);
+        events.dispatch(OrderPaid { order_id: 3 }).await.unwrap();
+        assert_eq!(total.load(Ordering::SeqCst), 33);
+    }
+
+    #[tokio::test]
+    async fn test_failing_listener_does_not_stop_others() {
+        let events = EventDispatcher::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        events.listen(|_: &OrderPaid| Err(anyhow::anyhow!("audit unavailable")));
+        let counter = calls.clone();
+        events.listen(move |_: &OrderPaid| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+
+        let err = events
+            .dispatch(OrderPaid { order_id: 1 })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1 listener(s) of 'order.paid' failed: audit unavailable"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_without_listeners() {
+        let events = EventDispatcher::new();
+        assert!(events.dispatch(OrderCancelled).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bridge_receives_serialized_events() {
+        let events = EventDispatcher::new();
+        let bridge = Arc::new(RecordingBridge::default());
+        events.set_bridge(bridge.clone());
+
+        events.dispatch(OrderPaid { order_id: 9 }).await.unwrap();
+
+        let published = bridge.published.lock().unwrap();
+        assert_eq!(
+            *published,
+            vec![(
+                "order.paid".to_string(),
+                serde_json::json!({ "order_id": 9 })
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_detached() {
+        let events = EventDispatcher::new();
+        let received = Arc::new(AtomicUsize::new(0));
+
+        let counter = received.clone();
+        events.listen(move |event: &OrderPaid| {
+            counter.store(event.order_id as usize, Ordering::SeqCst);
+            Ok(())
+        });
+
+        events.dispatch_detached(OrderPaid { order_id: 5 });
+        for _ in 0..100 {
+            if received.load(Ordering::SeqCst) != 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(received.load(Ordering::SeqCst), 5);
+    }
+}
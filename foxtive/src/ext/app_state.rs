@@ -2,8 +2,14 @@
 use crate::cache::Cache;
 #[cfg(feature = "database")]
 use crate::database::ext::DatabaseConnectionExt;
+#[cfg(feature = "events")]
+use crate::events::EventDispatcher;
 #[cfg(feature = "database")]
 use crate::prelude::AppResult;
+#[cfg(feature = "secrets")]
+use crate::secrets::Secrets;
+#[cfg(feature = "storage")]
+use crate::storage::Storage;
 use crate::{Environment, FOXTIVE};
 use crate::{FoxtiveHelpers, FoxtiveState};
 #[cfg(feature = "database")]
@@ -30,7 +36,15 @@ pub trait AppStateExt {
     /// initialized. This usually indicates a setup or startup error in the
     /// application's lifecycle, as the state is expected to be ready before this
     /// method is called.
+    ///
+    /// With the `testing` feature, a state installed with
+    /// [`FoxtiveState::install`] takes precedence on the runtime it was installed on.
     fn app(&self) -> &FoxtiveState {
+        #[cfg(feature = "testing")]
+        if let Some(state) = crate::setup::testing::installed() {
+            return state;
+        }
+
         FOXTIVE.get().expect("Foxtive isn't initialized yet ")
     }
 
@@ -46,6 +60,11 @@ pub trait AppStateExt {
     /// Returns `true` if the global `FOXTIVE` state has been set, and `false`
     /// otherwise.
     fn is_initialized(&self) -> bool {
+        #[cfg(feature = "testing")]
+        if crate::setup::testing::installed().is_some() {
+            return true;
+        }
+
         FOXTIVE.get().is_some()
     }
 
@@ -53,7 +72,7 @@ pub trait AppStateExt {
     ///
     /// This value is retrieved from the global `FoxtiveState`.
     fn env(&self) -> Environment {
-        self.app().env
+        self.app().env.clone()
     }
 
     /// Returns the unique application code.
@@ -70,7 +89,7 @@ pub trait AppStateExt {
     /// This function will panic if the global `FOXTIVE` state has not yet been
     /// initialized.
     fn helpers(&self) -> &FoxtiveHelpers {
-        &FOXTIVE.get().unwrap().helpers
+        &self.app().helpers
     }
 
     /// Returns a clone of the Redis connection pool.
@@ -99,6 +118,20 @@ pub trait AppStateExt {
         &self.app().redis
     }
 
+    /// Returns the Redis client as a [`RedisContract`](crate::redis::RedisContract),
+    /// replaceable by a mock in test states.
+    ///
+    /// This method requires the `"redis"` feature to be enabled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the global `FOXTIVE` state has not yet been
+    /// initialized.
+    #[cfg(feature = "redis")]
+    fn redis_contract(&self) -> Arc<dyn crate::redis::RedisContract> {
+        self.app().redis_contract.clone()
+    }
+
     /// Returns a clone of the RabbitMQ connection pool.
     ///
     /// This method requires the `"rabbitmq"` feature to be enabled.
@@ -125,6 +158,20 @@ pub trait AppStateExt {
         self.app().rabbitmq.clone()
     }
 
+    /// Returns the RabbitMQ client as a [`RabbitContract`](crate::rabbitmq::RabbitContract),
+    /// replaceable by a mock in test states.
+    ///
+    /// This method requires the `"rabbitmq"` feature to be enabled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the global `FOXTIVE` state has not yet been
+    /// initialized.
+    #[cfg(feature = "rabbitmq")]
+    fn rabbitmq_contract(&self) -> Arc<dyn crate::rabbitmq::RabbitContract> {
+        self.app().rabbitmq_contract.clone()
+    }
+
     /// Returns a clone of the global `Cache` instance.
     ///
     /// This method requires the `"cache"` feature to be enabled.
@@ -138,6 +185,45 @@ pub trait AppStateExt {
         self.app().cache.clone()
     }
 
+    /// Returns a clone of the global `Storage` instance.
+    ///
+    /// This method requires the `"storage"` feature to be enabled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the global `FOXTIVE` state has not yet been
+    /// initialized.
+    #[cfg(feature = "storage")]
+    fn storage(&self) -> Arc<Storage> {
+        self.app().storage.clone()
+    }
+
+    /// Returns a clone of the global `EventDispatcher` instance.
+    ///
+    /// This method requires the `"events"` feature to be enabled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the global `FOXTIVE` state has not yet been
+    /// initialized.
+    #[cfg(feature = "events")]
+    fn events(&self) -> Arc<EventDispatcher> {
+        self.app().events.clone()
+    }
+
+    /// Returns a clone of the global `Secrets` instance.
+    ///
+    /// This method requires the `"secrets"` feature to be enabled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the global `FOXTIVE` state has not yet been
+    /// initialized.
+    #[cfg(feature = "secrets")]
+    fn secrets(&self) -> Arc<Secrets> {
+        self.app().secrets.clone()
+    }
+
     /// Returns a reference to the database connection pool.
     ///
     /// This method requires the `"database"` feature to be enabled.
@@ -168,6 +254,37 @@ pub trait AppStateExt {
     fn db_conn(&self) -> AppResult<r2d2::PooledConnection<ConnectionManager<PgConnection>>> {
         self.app().database.connection()
     }
+
+    /// Runs `func` in a transaction on a pooled connection, committed when it returns
+    /// `Ok` and rolled back when it returns `Err`, see [`with_tx`](crate::database::with_tx).
+    ///
+    /// This method requires the `"database"` feature to be enabled.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the global `FOXTIVE` state has not yet been
+    /// initialized.
+    #[cfg(feature = "database")]
+    fn with_tx<R, F>(&self, func: F) -> impl Future<Output = AppResult<R>> + Send
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> AppResult<R> + Send + 'static,
+    {
+        let pool = self.app().database.clone();
+        async move { crate::database::with_tx(&pool, func).await }
+    }
 }
 
 impl AppStateExt for OnceLock<FoxtiveState> {}
+
+/// Gives an explicitly passed state, e.g. created with
+/// [`create_state`](crate::setup::create_state), the accessors of the global one.
+impl AppStateExt for FoxtiveState {
+    fn app(&self) -> &FoxtiveState {
+        self
+    }
+
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
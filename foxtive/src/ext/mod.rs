@@ -2,5 +2,5 @@ pub mod app_state;
 mod result;
 mod string;
 
-pub use result::{AppErrorExt, RecoverAppResultExt};
+pub use result::{AppErrorExt, AppResultExt, RecoverAppResultExt};
 pub use string::StringExt;
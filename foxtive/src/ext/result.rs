@@ -1,8 +1,10 @@
 use crate::Error;
-use crate::enums::AppMessage;
+use crate::enums::{AppMessage, CodedMessage, ErrorMapping, MessageCatalog};
 use crate::prelude::AppResult;
+use http::StatusCode;
 use std::borrow::Cow;
 use std::future::Future;
+use tracing::{error, warn};
 
 pub trait RecoverAppResultExt<T> {
     fn recover_from<F>(self, func: F) -> AppResult<T>
@@ -19,6 +21,37 @@ pub trait AppErrorExt {
     fn message(&self) -> Cow<'_, str>;
 }
 
+/// Logging and HTTP mapping of the error path, without a `map_err` at every call site
+///
+/// ```
+/// use foxtive::ext::AppResultExt;
+/// use foxtive::prelude::AppResult;
+/// use foxtive::{StatusCode, internal_server_error};
+///
+/// fn charge() -> AppResult<()> {
+///     Err(internal_server_error!("card declined"))
+/// }
+///
+/// let err = charge()
+///     .log_err("while charging the order")
+///     .to_status(StatusCode::PAYMENT_REQUIRED)
+///     .with_code("payment.declined")
+///     .unwrap_err();
+/// ```
+pub trait AppResultExt<T> {
+    /// Logs the error with `context`, client errors as warnings and the others as errors,
+    /// the result is returned unchanged
+    fn log_err(self, context: &str) -> AppResult<T>;
+
+    /// Attaches a machine-readable code to the error. A code of the installed
+    /// [`MessageCatalog`] takes its status and message, the error keeps them otherwise.
+    /// The field errors of a validation error are kept either way.
+    fn with_code(self, code: &str) -> AppResult<T>;
+
+    /// Replaces the status the error maps to, keeping its message, code and field errors
+    fn to_status(self, status: StatusCode) -> AppResult<T>;
+}
+
 impl<T: Send> RecoverAppResultExt<T> for AppResult<T> {
     fn recover_from<F>(self, func: F) -> AppResult<T>
     where
@@ -71,6 +104,81 @@ impl<T> RecoverAppResultExt<T> for Error {
     }
 }
 
+impl<T> AppResultExt<T> for AppResult<T> {
+    fn log_err(self, context: &str) -> AppResult<T> {
+        if let Err(err) = &self {
+            match err.downcast_ref::<AppMessage>() {
+                Some(msg) if msg.is_client_error() => {
+                    warn!(
+                        code = msg.code(),
+                        status = msg.status_code().as_u16(),
+                        "{context}: {msg}"
+                    )
+                }
+                Some(msg) => {
+                    error!(
+                        code = msg.code(),
+                        status = msg.status_code().as_u16(),
+                        "{context}: {msg}"
+                    )
+                }
+                None => error!("{context}: {err:?}"),
+            }
+        }
+
+        self
+    }
+
+    fn with_code(self, code: &str) -> AppResult<T> {
+        self.map_err(|err| {
+            let msg = into_message(err);
+            let errors = msg.validation_errors().cloned();
+
+            let mut coded = match MessageCatalog::global() {
+                Some(catalog) if catalog.contains(code) => {
+                    catalog.coded(code, None::<(String, String)>)
+                }
+                _ => CodedMessage {
+                    code: code.to_string(),
+                    status: msg.status_code(),
+                    template: msg.message().into_owned(),
+                    params: Default::default(),
+                    errors: None,
+                },
+            };
+
+            coded.errors = errors;
+            AppMessage::Coded(coded).into_anyhow()
+        })
+    }
+
+    fn to_status(self, status: StatusCode) -> AppResult<T> {
+        self.map_err(|err| match into_message(err) {
+            AppMessage::Coded(mut coded) => {
+                coded.status = status;
+                AppMessage::Coded(coded).into_anyhow()
+            }
+            AppMessage::ValidationError(msg, errors) => AppMessage::Coded(CodedMessage {
+                code: "validation_error".to_string(),
+                status,
+                template: msg,
+                params: Default::default(),
+                errors: Some(errors),
+            })
+            .into_anyhow(),
+            msg => AppMessage::error_message(msg.message(), status).into_anyhow(),
+        })
+    }
+}
+
+/// The message of `err` like `AppMessage::from`, without logging the errors that
+/// aren't messages, they are logged where they end up being handled
+fn into_message(err: Error) -> AppMessage {
+    err.downcast::<AppMessage>().unwrap_or_else(|err| {
+        ErrorMapping::map(&err).unwrap_or_else(|| AppMessage::InternalServerError(err.to_string()))
+    })
+}
+
 impl AppErrorExt for Error {
     fn message(&self) -> Cow<'_, str> {
         match self.downcast_ref::<AppMessage>() {
@@ -132,6 +240,66 @@ mod tests {
         assert_eq!(result.unwrap(), "recovered");
     }
 
+    #[test]
+    fn test_log_err_returns_the_error() {
+        let result: AppResult<()> = Err(invalid!("Bad pin"));
+        let err = result.log_err("while verifying the pin").unwrap_err();
+        assert_eq!(err.message(), "Bad pin");
+
+        assert_eq!(Ok::<_, Error>(1).log_err("unused").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_with_code_keeps_status_and_message() {
+        let result: AppResult<()> = Err(invalid!("Bad pin"));
+        let msg = AppMessage::from(result.with_code("pin.invalid").unwrap_err());
+
+        assert_eq!(msg.code(), "pin.invalid");
+        assert_eq!(msg.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(msg.message(), "Bad pin");
+    }
+
+    #[test]
+    fn test_to_status() {
+        let result: AppResult<()> = Err(anyhow::anyhow!("connection refused"));
+        let msg = AppMessage::from(result.to_status(StatusCode::BAD_GATEWAY).unwrap_err());
+        assert_eq!(msg.status_code(), StatusCode::BAD_GATEWAY);
+        assert_eq!(msg.message(), "connection refused");
+
+        let result: AppResult<()> = Err(invalid!("Bad pin"));
+        let msg = AppMessage::from(
+            result
+                .with_code("pin.invalid")
+                .to_status(StatusCode::UNPROCESSABLE_ENTITY)
+                .unwrap_err(),
+        );
+        assert_eq!(msg.code(), "pin.invalid");
+        assert_eq!(msg.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_field_errors_are_kept() {
+        let errors = crate::ValidationErrors::from([(
+            "pin".to_string(),
+            vec!["must have 4 digits".to_string()],
+        )]);
+        let failed = || -> AppResult<()> {
+            AppMessage::ValidationError("Invalid pin".to_string(), errors.clone()).into_result()
+        };
+
+        let msg = AppMessage::from(failed().with_code("pin.invalid").unwrap_err());
+        assert_eq!(msg.code(), "pin.invalid");
+        assert_eq!(msg.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(msg.validation_errors(), Some(&errors));
+        assert_eq!(msg.to_json_response().data, serde_json::json!(errors));
+
+        let msg = AppMessage::from(failed().to_status(StatusCode::BAD_REQUEST).unwrap_err());
+        assert_eq!(msg.code(), "validation_error");
+        assert_eq!(msg.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(msg.message(), "Invalid pin");
+        assert_eq!(msg.validation_errors(), Some(&errors));
+    }
+
     #[test]
     fn test_msg() {
         let err = internal_server_error!("Internal Server Error");
@@ -12,6 +12,14 @@ pub trait StringExt {
     fn is_numeric(&self) -> bool;
     fn is_alphabetic(&self) -> bool;
     fn camel_case(&self) -> String;
+    fn snake_case(&self) -> String;
+    fn kebab_case(&self) -> String;
+    fn pascal_case(&self) -> String;
+    fn title_case(&self) -> String;
+    #[cfg(feature = "unicode")]
+    fn slugify(&self) -> String;
+    #[cfg(feature = "unicode")]
+    fn truncate_graphemes(&self, max_graphemes: usize) -> String;
     fn pad_left(&self, width: usize, pad_char: char) -> String;
 }
 
@@ -58,6 +66,32 @@ impl StringExt for str {
         Str::camel_case(self)
     }
 
+    fn snake_case(&self) -> String {
+        Str::snake_case(self)
+    }
+
+    fn kebab_case(&self) -> String {
+        Str::kebab_case(self)
+    }
+
+    fn pascal_case(&self) -> String {
+        Str::pascal_case(self)
+    }
+
+    fn title_case(&self) -> String {
+        Str::title_case(self)
+    }
+
+    #[cfg(feature = "unicode")]
+    fn slugify(&self) -> String {
+        Str::slugify(self)
+    }
+
+    #[cfg(feature = "unicode")]
+    fn truncate_graphemes(&self, max_graphemes: usize) -> String {
+        Str::truncate_graphemes(self, max_graphemes)
+    }
+
     fn pad_left(&self, width: usize, pad_char: char) -> String {
         Str::pad_left(self, width, pad_char)
     }
@@ -105,6 +139,32 @@ impl StringExt for String {
         self.as_str().camel_case()
     }
 
+    fn snake_case(&self) -> String {
+        self.as_str().snake_case()
+    }
+
+    fn kebab_case(&self) -> String {
+        self.as_str().kebab_case()
+    }
+
+    fn pascal_case(&self) -> String {
+        self.as_str().pascal_case()
+    }
+
+    fn title_case(&self) -> String {
+        self.as_str().title_case()
+    }
+
+    #[cfg(feature = "unicode")]
+    fn slugify(&self) -> String {
+        self.as_str().slugify()
+    }
+
+    #[cfg(feature = "unicode")]
+    fn truncate_graphemes(&self, max_graphemes: usize) -> String {
+        self.as_str().truncate_graphemes(max_graphemes)
+    }
+
     fn pad_left(&self, width: usize, pad_char: char) -> String {
         self.as_str().pad_left(width, pad_char)
     }
@@ -0,0 +1,265 @@
+use crate::health::{Check, HealthIndicator};
+#[cfg(any(feature = "database", feature = "redis", feature = "rabbitmq"))]
+use serde_json::json;
+#[cfg(feature = "cache")]
+use std::sync::Arc;
+
+/// Checks the database by running `SELECT 1` on a pooled connection
+#[cfg(feature = "database")]
+pub struct DatabaseHealthIndicator {
+    pool: crate::database::DBPool,
+    critical: bool,
+}
+
+#[cfg(feature = "database")]
+impl DatabaseHealthIndicator {
+    pub fn new(pool: crate::database::DBPool) -> Self {
+        Self {
+            pool,
+            critical: true,
+        }
+    }
+
+    /// Reports failures as degrading the application instead of making it unhealthy,
+    /// e.g. for a service that isn't required at boot
+    pub fn non_critical(mut self) -> Self {
+        self.critical = false;
+        self
+    }
+}
+
+#[cfg(feature = "database")]
+#[async_trait::async_trait]
+impl HealthIndicator for DatabaseHealthIndicator {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    fn critical(&self) -> bool {
+        self.critical
+    }
+
+    async fn check(&self) -> Check {
+        use diesel::RunQueryDsl;
+
+        let pool = self.pool.clone();
+        let result = crate::helpers::blk(move || {
+            let mut conn = pool.get()?;
+            diesel::sql_query("SELECT 1").execute(&mut conn)?;
+            crate::prelude::AppResult::Ok(())
+        })
+        .await;
+
+        let state = self.pool.state();
+        let details = json!({
+            "connections": state.connections,
+            "idle_connections": state.idle_connections,
+            "max_size": self.pool.max_size(),
+        });
+
+        match result {
+            Ok(Ok(())) => Check::healthy(),
+            Ok(Err(err)) => Check::unhealthy(err.to_string()),
+            Err(err) => Check::unhealthy(format!("check panicked: {err}")),
+        }
+        .with_details(details)
+    }
+}
+
+/// Checks Redis by sending `PING` on a pooled connection
+#[cfg(feature = "redis")]
+pub struct RedisHealthIndicator {
+    pool: deadpool_redis::Pool,
+    critical: bool,
+}
+
+#[cfg(feature = "redis")]
+impl RedisHealthIndicator {
+    pub fn new(pool: deadpool_redis::Pool) -> Self {
+        Self {
+            pool,
+            critical: true,
+        }
+    }
+
+    /// Reports failures as degrading the application instead of making it unhealthy,
+    /// e.g. for a service that isn't required at boot
+    pub fn non_critical(mut self) -> Self {
+        self.critical = false;
+        self
+    }
+
+    async fn ping(&self) -> crate::prelude::AppResult<()> {
+        let mut conn = self.pool.get().await?;
+        let _: String = redis::cmd("PING").query_async(&mut conn).await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait::async_trait]
+impl HealthIndicator for RedisHealthIndicator {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    fn critical(&self) -> bool {
+        self.critical
+    }
+
+    async fn check(&self) -> Check {
+        let result = self.ping().await;
+        let status = self.pool.status();
+        let details = json!({
+            "size": status.size,
+            "available": status.available,
+            "max_size": status.max_size,
+        });
+
+        match result {
+            Ok(()) => Check::healthy(),
+            Err(err) => Check::unhealthy(err.to_string()),
+        }
+        .with_details(details)
+    }
+}
+
+/// Checks RabbitMQ by obtaining a pooled connection and verifying it is open
+#[cfg(feature = "rabbitmq")]
+pub struct RabbitMqHealthIndicator {
+    pool: deadpool_lapin::Pool,
+    critical: bool,
+}
+
+#[cfg(feature = "rabbitmq")]
+impl RabbitMqHealthIndicator {
+    pub fn new(pool: deadpool_lapin::Pool) -> Self {
+        Self {
+            pool,
+            critical: true,
+        }
+    }
+
+    /// Reports failures as degrading the application instead of making it unhealthy,
+    /// e.g. for a service that isn't required at boot
+    pub fn non_critical(mut self) -> Self {
+        self.critical = false;
+        self
+    }
+}
+
+#[cfg(feature = "rabbitmq")]
+#[async_trait::async_trait]
+impl HealthIndicator for RabbitMqHealthIndicator {
+    fn name(&self) -> &str {
+        "rabbitmq"
+    }
+
+    fn critical(&self) -> bool {
+        self.critical
+    }
+
+    async fn check(&self) -> Check {
+        let result = match self.pool.get().await {
+            Ok(conn) if conn.status().connected() => Check::healthy(),
+            Ok(_) => Check::unhealthy("connection is not open"),
+            Err(err) => Check::unhealthy(err.to_string()),
+        };
+
+        let status = self.pool.status();
+        result.with_details(json!({
+            "size": status.size,
+            "available": status.available,
+            "max_size": status.max_size,
+        }))
+    }
+}
+
+/// Checks the cache driver by writing, reading back and removing a probe key
+#[cfg(feature = "cache")]
+pub struct CacheHealthIndicator {
+    cache: Arc<crate::cache::Cache>,
+}
+
+#[cfg(feature = "cache")]
+impl CacheHealthIndicator {
+    pub fn new(cache: Arc<crate::cache::Cache>) -> Self {
+        Self { cache }
+    }
+
+    async fn probe(&self) -> crate::prelude::AppResult<bool> {
+        let driver = self.cache.driver();
+        let key = format!("foxtive:health:{}", crate::helpers::id::ulid());
+
+        driver.put_raw(&key, "ok".to_string()).await?;
+        let value = driver.get_raw(&key).await?;
+        driver.forget(&key).await?;
+
+        Ok(value.as_deref() == Some("ok"))
+    }
+}
+
+#[cfg(feature = "cache")]
+#[async_trait::async_trait]
+impl HealthIndicator for CacheHealthIndicator {
+    fn name(&self) -> &str {
+        "cache"
+    }
+
+    async fn check(&self) -> Check {
+        match self.probe().await {
+            Ok(true) => Check::healthy(),
+            Ok(false) => Check::unhealthy("probe value could not be read back"),
+            Err(err) => Check::unhealthy(err.to_string()),
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "cache-in-memory", feature = "redis")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "cache-in-memory")]
+    #[tokio::test]
+    async fn test_cache_indicator() {
+        use crate::cache::Cache;
+        use crate::cache::drivers::InMemoryDriver;
+        use crate::health::HealthStatus;
+
+        let cache = Arc::new(Cache::new(Arc::new(InMemoryDriver::new())));
+        let check = CacheHealthIndicator::new(cache.clone()).check().await;
+
+        assert_eq!(check.status, HealthStatus::Healthy);
+        assert!(cache.driver().keys().await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "redis")]
+    #[tokio::test]
+    async fn test_redis_indicator_reports_unreachable_server() {
+        use crate::health::HealthStatus;
+
+        let manager = deadpool_redis::Manager::new("redis://127.0.0.1:1").unwrap();
+        let pool = deadpool_redis::Pool::builder(manager).build().unwrap();
+        let check = RedisHealthIndicator::new(pool).check().await;
+
+        assert_eq!(check.status, HealthStatus::Unhealthy);
+        assert_eq!(check.details.unwrap()["available"], 0);
+    }
+
+    #[cfg(feature = "redis")]
+    #[tokio::test]
+    async fn test_non_critical_indicator_degrades_readiness() {
+        use crate::health::{HealthChecker, HealthStatus};
+        use std::sync::Arc;
+
+        let manager = deadpool_redis::Manager::new("redis://127.0.0.1:1").unwrap();
+        let pool = deadpool_redis::Pool::builder(manager).build().unwrap();
+        let report = HealthChecker::new()
+            .indicator(Arc::new(RedisHealthIndicator::new(pool).non_critical()))
+            .readiness()
+            .await;
+
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert!(!report.checks["redis"].critical);
+    }
+}
@@ -0,0 +1,448 @@
+//! # Health Module
+//!
+//! Aggregates the health of the application's dependencies into a serializable report,
+//! ready to be returned by liveness and readiness endpoints or consumed by the
+//! supervisor's health checks.
+//!
+//! Each dependency is checked by a [`HealthIndicator`]. Built-in indicators cover Redis,
+//! RabbitMQ, the database pool and the cache driver, and are registered automatically by
+//! [`HealthChecker::from_state`] for the enabled features.
+//!
+//! The overall status is the worst status among critical indicators, failing non-critical
+//! indicators only degrade it. Services that aren't required at boot (see
+//! [`ConnectPolicy`](crate::setup::ConnectPolicy)) are reported as non-critical.
+//!
+//! ## Features
+//!
+//! This module requires the `health` feature to be enabled.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::health::{Check, HealthChecker, HealthIndicator, HealthStatus};
+//! use std::sync::Arc;
+//!
+//! struct PaymentGateway;
+//!
+//! #[async_trait::async_trait]
+//! impl HealthIndicator for PaymentGateway {
+//!     fn name(&self) -> &str {
+//!         "payment-gateway"
+//!     }
+//!
+//!     fn critical(&self) -> bool {
+//!         false
+//!     }
+//!
+//!     async fn check(&self) -> Check {
+//!         Check::unhealthy("connection refused")
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let checker = HealthChecker::new().indicator(Arc::new(PaymentGateway));
+//!
+//! let report = checker.readiness().await;
+//! assert_eq!(report.status, HealthStatus::Degraded);
+//! assert!(report.is_ready());
+//! assert_eq!(report.http_status(), http::StatusCode::OK);
+//! # });
+//! ```
+
+#[cfg(any(
+    feature = "database",
+    feature = "redis",
+    feature = "rabbitmq",
+    feature = "cache"
+))]
+mod indicators;
+
+#[cfg(any(
+    feature = "database",
+    feature = "redis",
+    feature = "rabbitmq",
+    feature = "cache"
+))]
+pub use indicators::*;
+
+use futures_util::future::join_all;
+use http::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Health of a component, or of the whole application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+/// Result of a single health check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl Check {
+    pub fn healthy() -> Self {
+        Self {
+            status: HealthStatus::Healthy,
+            reason: None,
+            details: None,
+        }
+    }
+
+    pub fn degraded(reason: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Degraded,
+            reason: Some(reason.into()),
+            details: None,
+        }
+    }
+
+    pub fn unhealthy(reason: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Unhealthy,
+            reason: Some(reason.into()),
+            details: None,
+        }
+    }
+
+    /// Attaches extra information, e.g. pool statistics
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+/// Contract for checking the health of a dependency
+#[async_trait::async_trait]
+pub trait HealthIndicator: Send + Sync {
+    /// Name of the component in the report, e.g. `redis`
+    fn name(&self) -> &str;
+
+    /// Whether a failure makes the application unhealthy (`true`, the default)
+    /// or only degraded
+    fn critical(&self) -> bool {
+        true
+    }
+
+    async fn check(&self) -> Check;
+}
+
+/// Health of a component in a [`HealthReport`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentHealth {
+    #[serde(flatten)]
+    pub check: Check,
+    pub critical: bool,
+    pub duration_ms: u64,
+}
+
+/// Aggregated health of the application
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: BTreeMap<String, ComponentHealth>,
+}
+
+impl HealthReport {
+    /// Whether the application can serve traffic, i.e. it is not unhealthy
+    pub fn is_ready(&self) -> bool {
+        self.status != HealthStatus::Unhealthy
+    }
+
+    /// `200 OK` when ready, `503 Service Unavailable` otherwise
+    pub fn http_status(&self) -> StatusCode {
+        match self.is_ready() {
+            true => StatusCode::OK,
+            false => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+#[cfg(feature = "supervisor")]
+impl From<&HealthReport> for foxtive_supervisor::enums::HealthStatus {
+    fn from(report: &HealthReport) -> Self {
+        let failing = || {
+            report
+                .checks
+                .iter()
+                .filter(|(_, component)| component.check.status != HealthStatus::Healthy)
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match report.status {
+            HealthStatus::Healthy => Self::Healthy,
+            HealthStatus::Degraded => Self::degraded(format!("degraded: {}", failing())),
+            HealthStatus::Unhealthy => Self::unhealthy(format!("unhealthy: {}", failing())),
+        }
+    }
+}
+
+/// Runs health indicators and aggregates their results.
+#[derive(Clone)]
+pub struct HealthChecker {
+    indicators: Vec<Arc<dyn HealthIndicator>>,
+    timeout: Duration,
+}
+
+impl Default for HealthChecker {
+    fn default() -> Self {
+        Self {
+            indicators: vec![],
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl HealthChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a checker with the built-in indicators of the dependencies
+    /// enabled in the state
+    #[allow(unused_variables, unused_mut)]
+    pub fn from_state(state: &crate::FoxtiveState) -> Self {
+        let mut checker = Self::new();
+
+        #[cfg(feature = "database")]
+        {
+            let mut indicator = DatabaseHealthIndicator::new(state.database.clone());
+            if !state.database_connect_policy.is_required() {
+                indicator = indicator.non_critical();
+            }
+
+            checker = checker.indicator(Arc::new(indicator));
+        }
+
+        #[cfg(feature = "redis")]
+        {
+            let mut indicator = RedisHealthIndicator::new(state.redis_pool.clone());
+            if !state.redis_connect_policy.is_required() {
+                indicator = indicator.non_critical();
+            }
+
+            checker = checker.indicator(Arc::new(indicator));
+        }
+
+        #[cfg(feature = "rabbitmq")]
+        {
+            let mut indicator = RabbitMqHealthIndicator::new(state.rabbitmq_pool.clone());
+            if !state.rabbitmq_connect_policy.is_required() {
+                indicator = indicator.non_critical();
+            }
+
+            checker = checker.indicator(Arc::new(indicator));
+        }
+
+        #[cfg(feature = "cache")]
+        {
+            checker = checker.indicator(Arc::new(CacheHealthIndicator::new(state.cache.clone())));
+        }
+
+        checker
+    }
+
+    pub fn indicator(mut self, indicator: Arc<dyn HealthIndicator>) -> Self {
+        self.indicators.push(indicator);
+        self
+    }
+
+    /// Maximum duration of a single check before it is reported unhealthy, 5 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Reports the process itself as alive, without checking any dependency
+    pub fn liveness(&self) -> HealthReport {
+        HealthReport {
+            status: HealthStatus::Healthy,
+            checks: BTreeMap::new(),
+        }
+    }
+
+    /// Runs every indicator concurrently and aggregates the results
+    pub async fn readiness(&self) -> HealthReport {
+        let checks = self.indicators.iter().map(|indicator| async move {
+            let started = Instant::now();
+            let check = match tokio::time::timeout(self.timeout, indicator.check()).await {
+                Ok(check) => check,
+                Err(_) => Check::unhealthy(format!("timed out after {:?}", self.timeout)),
+            };
+
+            let component = ComponentHealth {
+                check,
+                critical: indicator.critical(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            };
+
+            (indicator.name().to_string(), component)
+        });
+
+        let checks = join_all(checks)
+            .await
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let status = checks
+            .values()
+            .map(|component| match component.critical {
+                true => component.check.status,
+                false => component.check.status.min(HealthStatus::Degraded),
+            })
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+
+        HealthReport { status, checks }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Fixed {
+        name: &'static str,
+        critical: bool,
+        check: Check,
+        delay: Duration,
+    }
+
+    impl Fixed {
+        fn new(name: &'static str, critical: bool, check: Check) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                critical,
+                check,
+                delay: Duration::ZERO,
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HealthIndicator for Fixed {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn critical(&self) -> bool {
+            self.critical
+        }
+
+        async fn check(&self) -> Check {
+            tokio::time::sleep(self.delay).await;
+            self.check.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_aggregation() {
+        let empty = HealthChecker::new().readiness().await;
+        assert_eq!(empty.status, HealthStatus::Healthy);
+
+        let degraded = HealthChecker::new()
+            .indicator(Fixed::new("db", true, Check::healthy()))
+            .indicator(Fixed::new("mail", false, Check::unhealthy("down")))
+            .readiness()
+            .await;
+        assert_eq!(degraded.status, HealthStatus::Degraded);
+        assert!(degraded.is_ready());
+
+        let unhealthy = HealthChecker::new()
+            .indicator(Fixed::new("db", true, Check::unhealthy("down")))
+            .indicator(Fixed::new("mail", false, Check::healthy()))
+            .readiness()
+            .await;
+        assert_eq!(unhealthy.status, HealthStatus::Unhealthy);
+        assert_eq!(unhealthy.http_status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_slow_indicator_times_out() {
+        let slow = Arc::new(Fixed {
+            name: "slow",
+            critical: true,
+            check: Check::healthy(),
+            delay: Duration::from_secs(5),
+        });
+
+        let report = HealthChecker::new()
+            .indicator(slow)
+            .timeout(Duration::from_millis(20))
+            .readiness()
+            .await;
+
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert_eq!(
+            report.checks["slow"].check.reason.as_deref(),
+            Some("timed out after 20ms")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_serialization() {
+        let check = Check::degraded("pool exhausted").with_details(json!({ "available": 0 }));
+        let mut report = HealthChecker::new()
+            .indicator(Fixed::new("redis", true, check))
+            .readiness()
+            .await;
+        report.checks.get_mut("redis").unwrap().duration_ms = 3;
+
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "status": "degraded",
+                "checks": {
+                    "redis": {
+                        "status": "degraded",
+                        "reason": "pool exhausted",
+                        "details": { "available": 0 },
+                        "critical": true,
+                        "duration_ms": 3
+                    }
+                }
+            })
+        );
+
+        let liveness = HealthChecker::new().liveness();
+        assert_eq!(
+            serde_json::to_value(&liveness).unwrap(),
+            json!({ "status": "healthy", "checks": {} })
+        );
+    }
+
+    #[cfg(feature = "supervisor")]
+    #[tokio::test]
+    async fn test_into_supervisor_health() {
+        use foxtive_supervisor::enums::HealthStatus as TaskHealth;
+
+        let report = HealthChecker::new()
+            .indicator(Fixed::new("db", true, Check::unhealthy("down")))
+            .indicator(Fixed::new("redis", true, Check::healthy()))
+            .readiness()
+            .await;
+
+        assert_eq!(
+            TaskHealth::from(&report),
+            TaskHealth::unhealthy("unhealthy: db")
+        );
+        assert_eq!(
+            TaskHealth::from(&HealthChecker::new().liveness()),
+            TaskHealth::Healthy
+        );
+    }
+}
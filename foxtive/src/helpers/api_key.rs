@@ -0,0 +1,310 @@
+//! API keys authenticating machine clients.
+//!
+//! Keys look like `fx_live_3kq8z1m0_Xb7...`: a prefix telling what the key is for, an
+//! environment, a short public id to list and revoke the key by, and a random secret.
+//! Only a hash of the key is stored, computed with the [`Password`] helper, so a leaked
+//! database doesn't leak usable keys.
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::api_key::ApiKeys;
+//!
+//! let keys = ApiKeys::new("api-key-salt".to_string());
+//!
+//! // shown to the client once, only `id` and `hash` are stored
+//! let generated = keys.generate().unwrap();
+//! assert!(generated.key.starts_with("fx_live_"));
+//!
+//! assert!(keys.verify(&generated.key, &generated.hash).unwrap());
+//! assert!(!keys.verify("fx_live_3kq8z1m0_forged", &generated.hash).unwrap());
+//! ```
+
+use crate::helpers::password::Password;
+use crate::prelude::AppResult;
+use crate::{internal_server_error, invalid, unauthorized};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+const ID_LENGTH: usize = 8;
+const SECRET_LENGTH: usize = 32;
+const ID_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const SECRET_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+type UsageHook = Arc<dyn Fn(&str, DateTime<Utc>) + Send + Sync>;
+
+/// A newly generated key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedApiKey {
+    /// The full key, to hand to the client once and never store
+    pub key: String,
+    /// Public id of the key, to store along with the hash
+    pub id: String,
+    /// Hash of the key, to store
+    pub hash: String,
+}
+
+/// The parts of a key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedApiKey<'a> {
+    pub prefix: &'a str,
+    pub environment: &'a str,
+    pub id: &'a str,
+    pub secret: &'a str,
+}
+
+/// Generates, hashes and verifies API keys.
+pub struct ApiKeys {
+    prefix: String,
+    environment: String,
+    password: Password,
+    on_used: Option<UsageHook>,
+}
+
+impl ApiKeys {
+    /// Creates a generator of `fx_live_` keys, hashed with the given salt
+    pub fn new(salt: String) -> Self {
+        Self {
+            prefix: "fx".to_string(),
+            environment: "live".to_string(),
+            password: Password::new(salt),
+            on_used: None,
+        }
+    }
+
+    /// Sets what the keys are for, e.g. `sk` for secret keys
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the prefix is made of lowercase letters and digits, as `_`
+    /// separates the parts of a key
+    pub fn prefix(mut self, prefix: &str) -> AppResult<Self> {
+        self.prefix = validate_part("prefix", prefix)?;
+        Ok(self)
+    }
+
+    /// Sets the environment, e.g. `test`, so test keys are told apart and rejected in production
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the environment is made of lowercase letters and digits
+    pub fn environment(mut self, environment: &str) -> AppResult<Self> {
+        self.environment = validate_part("environment", environment)?;
+        Ok(self)
+    }
+
+    /// Replaces the password helper hashing the keys, e.g. to change its cost
+    pub fn password(mut self, password: Password) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// Registers a hook called with the key id whenever a key is verified successfully,
+    /// e.g. to record when it was last used. It runs inline, so slow writes should be
+    /// spawned or throttled.
+    pub fn on_used<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str, DateTime<Utc>) + Send + Sync + 'static,
+    {
+        self.on_used = Some(Arc::new(hook));
+        self
+    }
+
+    /// Generates a random key and its hash
+    pub fn generate(&self) -> AppResult<GeneratedApiKey> {
+        let id = random_string(ID_ALPHABET, ID_LENGTH)?;
+        let secret = random_string(SECRET_ALPHABET, SECRET_LENGTH)?;
+        let key = format!("{}_{}_{id}_{secret}", self.prefix, self.environment);
+
+        Ok(GeneratedApiKey {
+            hash: self.hash(&key)?,
+            key,
+            id,
+        })
+    }
+
+    /// Hashes a key for storage
+    pub fn hash(&self, key: &str) -> AppResult<String> {
+        self.password.hash(key)
+    }
+
+    /// Splits a key into its parts
+    ///
+    /// # Errors
+    ///
+    /// Returns an unauthorized error if the key is malformed or has another prefix or environment
+    pub fn parse<'a>(&self, key: &'a str) -> AppResult<ParsedApiKey<'a>> {
+        let mut parts = key.splitn(4, '_');
+        let (Some(prefix), Some(environment), Some(id), Some(secret)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(unauthorized!("malformed api key"));
+        };
+
+        if prefix != self.prefix || environment != self.environment {
+            return Err(unauthorized!("api key is not valid for this environment"));
+        }
+
+        if id.len() != ID_LENGTH || secret.is_empty() {
+            return Err(unauthorized!("malformed api key"));
+        }
+
+        Ok(ParsedApiKey {
+            prefix,
+            environment,
+            id,
+            secret,
+        })
+    }
+
+    /// The public id of a key, to look up its stored hash
+    pub fn id<'a>(&self, key: &'a str) -> AppResult<&'a str> {
+        Ok(self.parse(key)?.id)
+    }
+
+    /// Verifies a key against its stored hash in constant time, calling the
+    /// [`on_used`](Self::on_used) hook when it matches.
+    ///
+    /// Keys that are malformed or of another prefix or environment don't match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored hash is malformed
+    pub fn verify(&self, key: &str, hash: &str) -> AppResult<bool> {
+        let Ok(parsed) = self.parse(key) else {
+            return Ok(false);
+        };
+
+        if !self.password.verify(hash, key)? {
+            return Ok(false);
+        }
+
+        if let Some(hook) = &self.on_used {
+            hook(parsed.id, Utc::now());
+        }
+
+        Ok(true)
+    }
+}
+
+/// Checks a part of the key is made of lowercase letters and digits, so it can't contain
+/// the `_` separator
+fn validate_part(name: &str, value: &str) -> AppResult<String> {
+    let valid = !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit());
+
+    match valid {
+        true => Ok(value.to_string()),
+        false => Err(invalid!(
+            "api key {name} must be made of lowercase letters and digits: {value:?}"
+        )),
+    }
+}
+
+/// Random string of `len` characters of `alphabet`, without modulo bias
+fn random_string(alphabet: &[u8], len: usize) -> AppResult<String> {
+    // largest multiple of the alphabet length fitting in a byte
+    let limit = 256 - (256 % alphabet.len());
+
+    let mut out = String::with_capacity(len);
+    let mut bytes = [0u8; 64];
+    while out.len() < len {
+        getrandom::fill(&mut bytes)
+            .map_err(|e| internal_server_error!("failed to generate random bytes: {e}"))?;
+
+        for byte in bytes {
+            if (byte as usize) < limit && out.len() < len {
+                out.push(alphabet[byte as usize % alphabet.len()] as char);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn keys() -> ApiKeys {
+        // cheap hashing, tests don't need the default cost
+        ApiKeys::new("test-salt".to_string())
+            .password(Password::new("test-salt".to_string()).cost(1024, 1, 1))
+    }
+
+    #[test]
+    fn test_generated_key_format() {
+        let generated = keys()
+            .prefix("sk")
+            .unwrap()
+            .environment("test")
+            .unwrap()
+            .generate()
+            .unwrap();
+        let parts: Vec<&str> = generated.key.splitn(4, '_').collect();
+
+        assert_eq!(parts[..2], ["sk", "test"]);
+        assert_eq!(parts[2], generated.id);
+        assert_eq!(parts[2].len(), ID_LENGTH);
+        assert_eq!(parts[3].len(), SECRET_LENGTH);
+        assert!(parts[3].chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(generated.key, keys().generate().unwrap().key);
+    }
+
+    #[test]
+    fn test_verify() {
+        let keys = keys();
+        let generated = keys.generate().unwrap();
+
+        assert!(keys.verify(&generated.key, &generated.hash).unwrap());
+        assert!(
+            !keys
+                .verify(&format!("{}x", generated.key), &generated.hash)
+                .unwrap()
+        );
+        assert!(!keys.verify("not-a-key", &generated.hash).unwrap());
+    }
+
+    #[test]
+    fn test_keys_of_another_environment_are_rejected() {
+        let test_keys = keys().environment("test").unwrap();
+        let generated = test_keys.generate().unwrap();
+
+        assert!(keys().parse(&generated.key).is_err());
+        assert!(!keys().verify(&generated.key, &generated.hash).unwrap());
+    }
+
+    #[test]
+    fn test_usage_hook_is_called_with_the_key_id() {
+        let used = Arc::new(Mutex::new(vec![]));
+        let recorded = used.clone();
+        let keys = keys().on_used(move |id, _at| recorded.lock().unwrap().push(id.to_string()));
+
+        let generated = keys.generate().unwrap();
+        keys.verify(&generated.key, &generated.hash).unwrap();
+        keys.verify("fx_live_12345678_wrong", &generated.hash)
+            .unwrap();
+
+        assert_eq!(*used.lock().unwrap(), vec![generated.id]);
+    }
+
+    #[test]
+    fn test_parts_with_the_separator_are_rejected() {
+        assert!(keys().prefix("sk_live").is_err());
+        assert!(keys().environment("test_1").is_err());
+        assert!(keys().prefix("").is_err());
+        assert!(keys().environment("Test").is_err());
+        assert!(keys().prefix("sk2").is_ok());
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = keys().parse("fx_live_abcd1234_se_cret").unwrap();
+        assert_eq!(parsed.id, "abcd1234");
+        assert_eq!(parsed.secret, "se_cret");
+        assert!(keys().parse("fx_live_short_secret").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! AWS Signature Version 4 primitives shared by the S3 storage driver and the
+//! AWS Secrets Manager provider.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::{Digest, Sha256};
+
+pub(crate) const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+pub(crate) fn amz_date(now: DateTime<Utc>) -> String {
+    now.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Credential scope of a request, `{date}/{region}/{service}/aws4_request`
+pub(crate) fn scope(now: DateTime<Utc>, region: &str, service: &str) -> String {
+    format!("{}/{region}/{service}/aws4_request", now.format("%Y%m%d"))
+}
+
+/// Signs a canonical request with a key derived from the secret access key
+pub(crate) fn signature(
+    secret_key: &str,
+    region: &str,
+    service: &str,
+    now: DateTime<Utc>,
+    canonical_request: &str,
+) -> String {
+    let string_to_sign = format!(
+        "{ALGORITHM}\n{}\n{}\n{}",
+        amz_date(now),
+        scope(now, region, service),
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let secret = format!("AWS4{secret_key}");
+    let key = hmac_sha256(secret.as_bytes(), &now.format("%Y%m%d").to_string());
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    let key = hmac_sha256(&key, "aws4_request");
+
+    hex::encode(hmac_sha256(&key, &string_to_sign))
+}
+
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac =
+        <Hmac<Sha256> as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+pub(crate) fn signed_headers(headers: &[(&str, &str)]) -> String {
+    headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Builds the canonical request, `headers` must be lowercase and sorted by name
+pub(crate) fn canonical_request(
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(&str, &str)],
+    payload_hash: &str,
+) -> String {
+    let canonical_headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect::<String>();
+
+    format!(
+        "{method}\n{path}\n{query}\n{canonical_headers}\n{}\n{payload_hash}",
+        signed_headers(headers)
+    )
+}
+
+#[cfg(feature = "storage-s3")]
+pub(crate) fn canonical_query(params: &[(&str, String)]) -> String {
+    let mut pairs = params
+        .iter()
+        .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+        .collect::<Vec<_>>();
+    pairs.sort();
+
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encodes everything except the unreserved characters, as SigV4 requires
+#[cfg(feature = "storage-s3")]
+pub(crate) fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(all(test, feature = "storage-s3"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(uri_encode("a b/c~d", false), "a%20b/c~d");
+        assert_eq!(uri_encode("a b/c~d", true), "a%20b%2Fc~d");
+        assert_eq!(uri_encode("é", true), "%C3%A9");
+    }
+
+    #[test]
+    fn test_canonical_query_is_sorted_and_encoded() {
+        let query = canonical_query(&[("prefix", "a b".to_string()), ("list-type", "2".into())]);
+        assert_eq!(query, "list-type=2&prefix=a%20b");
+    }
+}
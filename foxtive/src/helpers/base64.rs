@@ -4,6 +4,9 @@
 //! and decoding Base64 strings back to their original form. It uses the standard
 //! Base64 alphabet as defined in RFC 4648.
 //!
+//! URL-safe, unpadded variants are also available, including a typed pair for
+//! serializing values into opaque tokens (pagination cursors, OAuth state, etc.).
+//!
 //! # Examples
 //!
 //! ```
@@ -26,8 +29,11 @@
 //! - Decoding invalid Base64 strings
 //! - Decoding Base64 strings that result in invalid UTF-8
 
+use crate::bad_request;
 use crate::prelude::AppResult;
 use base64::{Engine, engine};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 
 /// A utility struct providing Base64 encoding and decoding functionality.
 #[derive(Debug)]
@@ -88,6 +94,80 @@ impl Base64 {
             engine::general_purpose::STANDARD.decode(str)?,
         )?)
     }
+
+    /// Encodes a string slice using the URL-safe alphabet without padding,
+    /// making the output safe to use in paths and query strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::base64::Base64;
+    ///
+    /// assert_eq!(Base64::encode_url("subjects?>").unwrap(), "c3ViamVjdHM_Pg");
+    /// ```
+    pub fn encode_url(str: &str) -> AppResult<String> {
+        Ok(Self::encode_url_bytes(str.as_bytes()))
+    }
+
+    /// Decodes a URL-safe, unpadded Base64 string back to its original form.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the input is not valid URL-safe Base64 or not valid UTF-8
+    pub fn decode_url(str: &str) -> AppResult<String> {
+        Ok(String::from_utf8(Self::decode_url_bytes(str)?)?)
+    }
+
+    /// Encodes raw bytes using the URL-safe alphabet without padding
+    pub fn encode_url_bytes(bytes: &[u8]) -> String {
+        engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decodes a URL-safe, unpadded Base64 string into raw bytes
+    pub fn decode_url_bytes(str: &str) -> AppResult<Vec<u8>> {
+        Ok(engine::general_purpose::URL_SAFE_NO_PAD.decode(str)?)
+    }
+
+    /// Serializes a value to JSON and encodes it as URL-safe, unpadded Base64.
+    ///
+    /// The result is opaque but not encrypted nor signed, so it must not carry
+    /// secrets or anything the client should not be able to alter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::base64::Base64;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    /// struct Cursor {
+    ///     id: u64,
+    ///     created_at: i64,
+    /// }
+    ///
+    /// let cursor = Cursor { id: 42, created_at: 1700000000 };
+    /// let token = Base64::encode_struct(&cursor).unwrap();
+    /// assert!(!token.contains('='));
+    ///
+    /// let decoded: Cursor = Base64::decode_struct(&token).unwrap();
+    /// assert_eq!(decoded, cursor);
+    /// ```
+    pub fn encode_struct<T: Serialize>(value: &T) -> AppResult<String> {
+        Ok(Self::encode_url_bytes(&serde_json::to_vec(value)?))
+    }
+
+    /// Decodes a token produced by [`Base64::encode_struct`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a bad request error when the token is malformed, as these tokens
+    /// usually come back from clients
+    pub fn decode_struct<T: DeserializeOwned>(token: &str) -> AppResult<T> {
+        let bytes =
+            Self::decode_url_bytes(token).map_err(|e| bad_request!("malformed token: {e}"))?;
+
+        serde_json::from_slice(&bytes).map_err(|e| bad_request!("malformed token: {e}"))
+    }
 }
 
 #[cfg(test)]
@@ -128,4 +208,30 @@ mod tests {
         let result = Base64::decode(input);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_base64_url_roundtrip() {
+        let input = "??>>~~ hello";
+        let encoded = Base64::encode_url(input).unwrap();
+        assert!(!encoded.contains(['+', '/', '=']));
+        assert_eq!(Base64::decode_url(&encoded).unwrap(), input);
+        assert!(Base64::decode_url("aGVsbG8=").is_err());
+    }
+
+    #[test]
+    fn test_base64_struct_roundtrip() {
+        let value = serde_json::json!({"page": 2, "after": "abc"});
+        let token = Base64::encode_struct(&value).unwrap();
+        let decoded: serde_json::Value = Base64::decode_struct(&token).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn test_base64_decode_struct_invalid_token() {
+        let err = Base64::decode_struct::<serde_json::Value>("not json!").unwrap_err();
+        assert!(err.to_string().contains("malformed token"));
+
+        let not_json = Base64::encode_url("plain text").unwrap();
+        assert!(Base64::decode_struct::<serde_json::Value>(&not_json).is_err());
+    }
 }
@@ -0,0 +1,343 @@
+//! Authenticated symmetric encryption.
+//!
+//! This module encrypts strings and bytes with AES-256-GCM or ChaCha20-Poly1305,
+//! typically keyed from the application's `app_key`, so that sensitive data (PII,
+//! tokens) can be stored in caches or pushed to queues without being readable.
+//!
+//! Every ciphertext is self-describing, which allows the algorithm and key to be
+//! rotated without breaking existing payloads:
+//!
+//! ```text
+//! [format version: 1 byte][algorithm: 1 byte][key id: 1 byte][nonce: 12 bytes][ciphertext + tag]
+//! ```
+//!
+//! The header is authenticated along with the data, so a payload altered to name another
+//! version, algorithm or key fails to decrypt.
+//!
+//! String helpers encode that payload with URL-safe base64 (no padding).
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::cipher::Cipher;
+//!
+//! let cipher = Cipher::new(1, "my-app-key");
+//! let encrypted = cipher.encrypt_string("john@example.com").unwrap();
+//! assert_eq!(cipher.decrypt_string(&encrypted).unwrap(), "john@example.com");
+//! ```
+//!
+//! # Key rotation
+//!
+//! ```
+//! use foxtive::helpers::cipher::Cipher;
+//!
+//! let old = Cipher::new(1, "old-key");
+//! let encrypted = old.encrypt_string("secret").unwrap();
+//!
+//! let new = Cipher::new(2, "new-key").with_previous_key(1, "old-key");
+//! assert_eq!(new.decrypt_string(&encrypted).unwrap(), "secret");
+//! assert!(new.needs_reencrypt_string(&encrypted).unwrap());
+//! ```
+
+use crate::prelude::AppResult;
+use crate::{bad_request, internal_server_error};
+use aes_gcm::Aes256Gcm;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use base64::{Engine, engine};
+use chacha20poly1305::ChaCha20Poly1305;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+/// Current ciphertext format version
+pub const FORMAT_VERSION: u8 = 1;
+
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = 3;
+
+/// HKDF info, so keys derived from the same secret for other purposes differ
+const KEY_INFO: &[u8] = b"foxtive cipher key";
+
+/// Supported AEAD algorithms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CipherAlgorithm {
+    #[default]
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+impl CipherAlgorithm {
+    fn id(&self) -> u8 {
+        match self {
+            CipherAlgorithm::Aes256Gcm => 1,
+            CipherAlgorithm::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> AppResult<Self> {
+        match id {
+            1 => Ok(CipherAlgorithm::Aes256Gcm),
+            2 => Ok(CipherAlgorithm::ChaCha20Poly1305),
+            _ => Err(bad_request!("unknown cipher algorithm: {id}")),
+        }
+    }
+}
+
+/// Encrypts and decrypts data with a current key, while still accepting previous keys.
+#[derive(Clone)]
+pub struct Cipher {
+    algorithm: CipherAlgorithm,
+    key_id: u8,
+    keys: HashMap<u8, [u8; 32]>,
+}
+
+impl Debug for Cipher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cipher")
+            .field("algorithm", &self.algorithm)
+            .field("key_id", &self.key_id)
+            .field("keys", &self.keys.len())
+            .finish()
+    }
+}
+
+impl Cipher {
+    /// Creates a cipher that encrypts with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id`: identifier stored in every ciphertext, used to pick the key when decrypting
+    /// * `secret`: high entropy secret (e.g. the app key), the encryption key is derived from it
+    ///   with HKDF-SHA256
+    pub fn new(key_id: u8, secret: &str) -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(key_id, Self::derive_key(secret));
+
+        Self {
+            algorithm: CipherAlgorithm::default(),
+            key_id,
+            keys,
+        }
+    }
+
+    /// Sets the algorithm used for encryption, decryption always follows the ciphertext header
+    pub fn algorithm(mut self, algorithm: CipherAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Registers a previous key so that data encrypted with it can still be decrypted
+    pub fn with_previous_key(mut self, key_id: u8, secret: &str) -> Self {
+        self.keys
+            .entry(key_id)
+            .or_insert_with(|| Self::derive_key(secret));
+        self
+    }
+
+    /// Returns the id of the key used for encryption
+    pub fn key_id(&self) -> u8 {
+        self.key_id
+    }
+
+    /// Encrypts bytes into the versioned binary format
+    pub fn encrypt(&self, plaintext: &[u8]) -> AppResult<Vec<u8>> {
+        let key = &self.keys[&self.key_id];
+        let header = [FORMAT_VERSION, self.algorithm.id(), self.key_id];
+        let payload = Payload {
+            msg: plaintext,
+            aad: &header,
+        };
+
+        let (nonce, ciphertext) = match self.algorithm {
+            CipherAlgorithm::Aes256Gcm => {
+                let cipher = Aes256Gcm::new(key.into());
+                let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+                (nonce.to_vec(), cipher.encrypt(&nonce, payload))
+            }
+            CipherAlgorithm::ChaCha20Poly1305 => {
+                let cipher = ChaCha20Poly1305::new(key.into());
+                let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+                (nonce.to_vec(), cipher.encrypt(&nonce, payload))
+            }
+        };
+
+        let ciphertext =
+            ciphertext.map_err(|_| internal_server_error!("failed to encrypt data"))?;
+
+        let mut out = Vec::with_capacity(HEADER_LEN + NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Decrypts bytes produced by [`Cipher::encrypt`]
+    pub fn decrypt(&self, payload: &[u8]) -> AppResult<Vec<u8>> {
+        let (algorithm, key_id) = Self::parse_header(payload)?;
+        let key = self
+            .keys
+            .get(&key_id)
+            .ok_or_else(|| bad_request!("unknown cipher key id: {key_id}"))?;
+
+        let nonce = &payload[HEADER_LEN..HEADER_LEN + NONCE_LEN];
+        let ciphertext = Payload {
+            msg: &payload[HEADER_LEN + NONCE_LEN..],
+            aad: &payload[..HEADER_LEN],
+        };
+
+        let plaintext = match algorithm {
+            CipherAlgorithm::Aes256Gcm => {
+                Aes256Gcm::new(key.into()).decrypt(nonce.into(), ciphertext)
+            }
+            CipherAlgorithm::ChaCha20Poly1305 => {
+                ChaCha20Poly1305::new(key.into()).decrypt(nonce.into(), ciphertext)
+            }
+        };
+
+        plaintext.map_err(|_| bad_request!("failed to decrypt data"))
+    }
+
+    /// Encrypts a string, returning URL-safe base64
+    pub fn encrypt_string(&self, plaintext: &str) -> AppResult<String> {
+        let payload = self.encrypt(plaintext.as_bytes())?;
+        Ok(engine::general_purpose::URL_SAFE_NO_PAD.encode(payload))
+    }
+
+    /// Decrypts a string produced by [`Cipher::encrypt_string`]
+    pub fn decrypt_string(&self, encrypted: &str) -> AppResult<String> {
+        let payload = Self::decode(encrypted)?;
+        Ok(String::from_utf8(self.decrypt(&payload)?)?)
+    }
+
+    /// Whether the payload was encrypted with an outdated key or algorithm and should be re-encrypted
+    pub fn needs_reencrypt(&self, payload: &[u8]) -> AppResult<bool> {
+        let (algorithm, key_id) = Self::parse_header(payload)?;
+        Ok(algorithm != self.algorithm || key_id != self.key_id)
+    }
+
+    /// Same as [`Cipher::needs_reencrypt`] for string payloads
+    pub fn needs_reencrypt_string(&self, encrypted: &str) -> AppResult<bool> {
+        self.needs_reencrypt(&Self::decode(encrypted)?)
+    }
+
+    fn decode(encrypted: &str) -> AppResult<Vec<u8>> {
+        Ok(engine::general_purpose::URL_SAFE_NO_PAD.decode(encrypted)?)
+    }
+
+    fn parse_header(payload: &[u8]) -> AppResult<(CipherAlgorithm, u8)> {
+        if payload.len() < HEADER_LEN + NONCE_LEN {
+            return Err(bad_request!("encrypted payload is too short"));
+        }
+
+        if payload[0] != FORMAT_VERSION {
+            return Err(bad_request!(
+                "unsupported cipher format version: {}",
+                payload[0]
+            ));
+        }
+
+        Ok((CipherAlgorithm::from_id(payload[1])?, payload[2]))
+    }
+
+    fn derive_key(secret: &str) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, secret.as_bytes())
+            .expand(KEY_INFO, &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_aes() {
+        let cipher = Cipher::new(1, "key");
+        let encrypted = cipher.encrypt(b"hello").unwrap();
+
+        assert_eq!(encrypted[0], FORMAT_VERSION);
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_roundtrip_chacha() {
+        let cipher = Cipher::new(1, "key").algorithm(CipherAlgorithm::ChaCha20Poly1305);
+        let encrypted = cipher.encrypt_string("hello").unwrap();
+        assert_eq!(cipher.decrypt_string(&encrypted).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_nonce_is_random() {
+        let cipher = Cipher::new(1, "key");
+        assert_ne!(
+            cipher.encrypt_string("hello").unwrap(),
+            cipher.encrypt_string("hello").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let encrypted = Cipher::new(1, "key").encrypt(b"hello").unwrap();
+        assert!(Cipher::new(1, "other").decrypt(&encrypted).is_err());
+        assert!(Cipher::new(2, "key").decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let cipher = Cipher::new(1, "key");
+        let mut encrypted = cipher.encrypt(b"hello").unwrap();
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+
+        assert!(cipher.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_header_is_authenticated() {
+        let cipher = Cipher::new(1, "key").with_previous_key(2, "key");
+        let mut encrypted = cipher.encrypt(b"hello").unwrap();
+
+        // same key under another id
+        encrypted[2] = 2;
+        assert!(cipher.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_key_is_derived_with_hkdf() {
+        // RFC 5869 expansion of the secret, without salt
+        let key = Cipher::derive_key("key");
+        let mut expected = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(&[0u8; 32]), b"key")
+            .expand(KEY_INFO, &mut expected)
+            .unwrap();
+        assert_eq!(key, expected);
+        assert_ne!(key, Cipher::derive_key("other"));
+    }
+
+    #[test]
+    fn test_rotation_and_algorithm_change() {
+        let old = Cipher::new(1, "old");
+        let encrypted = old.encrypt(b"data").unwrap();
+
+        let new = Cipher::new(2, "new")
+            .algorithm(CipherAlgorithm::ChaCha20Poly1305)
+            .with_previous_key(1, "old");
+
+        assert_eq!(new.decrypt(&encrypted).unwrap(), b"data");
+        assert!(new.needs_reencrypt(&encrypted).unwrap());
+
+        let reencrypted = new.encrypt(&new.decrypt(&encrypted).unwrap()).unwrap();
+        assert!(!new.needs_reencrypt(&reencrypted).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_payloads() {
+        let cipher = Cipher::new(1, "key");
+        assert!(cipher.decrypt(&[1, 1]).is_err());
+        assert!(cipher.decrypt(&[9; 40]).is_err());
+        assert!(cipher.decrypt_string("not base64!").is_err());
+    }
+}
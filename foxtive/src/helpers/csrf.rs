@@ -0,0 +1,183 @@
+//! CSRF tokens for form-based apps using sessions.
+//!
+//! Tokens are HMAC-signed and bound to the session id, so a token issued for one
+//! session is rejected for another, and expire after a configurable lifetime. The
+//! session id is part of the signature only, it never appears in the token.
+//!
+//! For the double-submit pattern, the token is also set in a cookie and
+//! [`Csrf::verify_double_submit`] checks the submitted copy matches it.
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::csrf::Csrf;
+//! use std::time::Duration;
+//!
+//! let csrf = Csrf::new("app-key").ttl(Duration::from_secs(3600));
+//!
+//! // rendered in a hidden `_csrf` form field
+//! let token = csrf.issue("session-id").unwrap();
+//!
+//! assert!(csrf.verify(&token, "session-id").is_ok());
+//! assert!(csrf.verify(&token, "another-session").is_err());
+//! ```
+
+use crate::helpers::hmac::{HashFunc, Hmac, constant_time_eq};
+use crate::prelude::AppResult;
+use crate::{forbidden, internal_server_error};
+use chrono::Utc;
+use std::time::Duration;
+
+/// Header carrying the token of AJAX requests
+pub const HEADER_NAME: &str = "x-csrf-token";
+/// Form field carrying the token of form submissions
+pub const FORM_FIELD: &str = "_csrf";
+/// Cookie carrying the token for the double-submit pattern
+pub const COOKIE_NAME: &str = "csrf_token";
+
+const NONCE_BYTES: usize = 16;
+
+/// Issues and verifies CSRF tokens bound to session ids.
+#[derive(Clone)]
+pub struct Csrf {
+    hmac: Hmac,
+    ttl: Duration,
+}
+
+impl Csrf {
+    /// Creates tokens signed with HMAC-SHA256, valid for 2 hours
+    pub fn new(secret: &str) -> Self {
+        Self {
+            hmac: Hmac::new(secret, HashFunc::Sha256),
+            ttl: Duration::from_secs(2 * 60 * 60),
+        }
+    }
+
+    /// How long issued tokens remain valid
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Issues a token for the session, `{nonce}.{expires}.{signature}`
+    pub fn issue(&self, session_id: &str) -> AppResult<String> {
+        let mut nonce = [0u8; NONCE_BYTES];
+        getrandom::fill(&mut nonce)
+            .map_err(|e| internal_server_error!("failed to generate a csrf nonce: {e}"))?;
+
+        let unsigned = format!(
+            "{}.{}",
+            hex::encode(nonce),
+            Utc::now().timestamp() + self.ttl.as_secs() as i64
+        );
+        let signature = self.signature(&unsigned, session_id)?;
+
+        Ok(format!("{unsigned}.{signature}"))
+    }
+
+    /// Verifies a token was issued for the session and hasn't expired
+    ///
+    /// # Errors
+    ///
+    /// Returns a forbidden error if the token is malformed, forged, issued for another
+    /// session or expired
+    pub fn verify(&self, token: &str, session_id: &str) -> AppResult<()> {
+        let (unsigned, signature) = token
+            .rsplit_once('.')
+            .ok_or_else(|| forbidden!("malformed csrf token"))?;
+
+        let expected = self.signature(unsigned, session_id)?;
+        if !constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+            return Err(forbidden!("invalid csrf token"));
+        }
+
+        let expires = unsigned
+            .split_once('.')
+            .and_then(|(_, expires)| expires.parse::<i64>().ok())
+            .ok_or_else(|| forbidden!("malformed csrf token"))?;
+
+        match Utc::now().timestamp() <= expires {
+            true => Ok(()),
+            false => Err(forbidden!("csrf token has expired")),
+        }
+    }
+
+    /// Verifies the double-submit pattern: the submitted token, from the form or
+    /// [`HEADER_NAME`], must equal the [`COOKIE_NAME`] cookie and be valid for the session
+    pub fn verify_double_submit(
+        &self,
+        cookie_token: &str,
+        submitted_token: &str,
+        session_id: &str,
+    ) -> AppResult<()> {
+        if !constant_time_eq(cookie_token.as_bytes(), submitted_token.as_bytes()) {
+            return Err(forbidden!("csrf token doesn't match its cookie"));
+        }
+
+        self.verify(submitted_token, session_id)
+    }
+
+    fn signature(&self, unsigned: &str, session_id: &str) -> AppResult<String> {
+        self.hmac.hash(&format!("{unsigned}.{session_id}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_unique_and_bound_to_the_session() {
+        let csrf = Csrf::new("secret");
+        let token = csrf.issue("session-a").unwrap();
+
+        assert_ne!(token, csrf.issue("session-a").unwrap());
+        assert!(csrf.verify(&token, "session-a").is_ok());
+        assert!(csrf.verify(&token, "session-b").is_err());
+        assert!(
+            Csrf::new("another-secret")
+                .verify(&token, "session-a")
+                .is_err()
+        );
+        assert!(!token.contains("session-a"));
+    }
+
+    #[test]
+    fn test_tampered_and_malformed_tokens_are_rejected() {
+        let csrf = Csrf::new("secret");
+        let token = csrf.issue("session").unwrap();
+
+        let (nonce, rest) = token.split_once('.').unwrap();
+        let tampered = format!("{}{}.{rest}", &nonce[1..], &nonce[..1]);
+
+        assert!(csrf.verify(&tampered, "session").is_err());
+        assert!(csrf.verify("garbage", "session").is_err());
+        assert!(csrf.verify("", "session").is_err());
+    }
+
+    #[test]
+    fn test_expired_tokens_are_rejected() {
+        let csrf = Csrf::new("secret");
+        let unsigned = format!("00.{}", Utc::now().timestamp() - 1);
+        let token = format!(
+            "{unsigned}.{}",
+            csrf.signature(&unsigned, "session").unwrap()
+        );
+
+        let err = csrf.verify(&token, "session").unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+
+    #[test]
+    fn test_double_submit() {
+        let csrf = Csrf::new("secret");
+        let token = csrf.issue("session").unwrap();
+        let other = csrf.issue("session").unwrap();
+
+        assert!(csrf.verify_double_submit(&token, &token, "session").is_ok());
+        assert!(
+            csrf.verify_double_submit(&token, &other, "session")
+                .is_err()
+        );
+    }
+}
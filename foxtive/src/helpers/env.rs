@@ -1,8 +1,357 @@
+//! Typed access to environment variables.
+//!
+//! Like [`var`], the accessors read `{env_prefix}_{key}`, with the prefix of the application.
+//!
+//! ```
+//! use foxtive::helpers::env::{self, Requirement, Secret};
+//!
+//! // reports every missing or malformed variable in a single error
+//! let result = env::validate("DOCS", &[
+//!     Requirement::required::<String>("DATABASE_URL"),
+//!     Requirement::required::<u16>("PORT"),
+//!     Requirement::optional::<bool>("DEBUG"),
+//! ]);
+//! assert!(result.is_err());
+//!
+//! let workers: usize = env::optional_with_default("DOCS", "WORKERS", 4).unwrap();
+//! assert_eq!(workers, 4);
+//!
+//! let secret: Secret<String> = "hunter2".parse().unwrap();
+//! assert_eq!(format!("{secret:?}"), "Secret(***)");
+//! assert_eq!(secret.expose(), "hunter2");
+//! ```
+
 use crate::prelude::AppMessage;
 use crate::results::AppResult;
 use std::env;
+use std::env::VarError;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
 
 pub fn var(env_prefix: &str, key: &str) -> AppResult<String> {
+    var_in(&process_env, env_prefix, key)
+}
+
+/// Reads and parses a variable that must be set
+pub fn required<T>(env_prefix: &str, key: &str) -> AppResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    required_in(&process_env, env_prefix, key)
+}
+
+/// Reads and parses a variable, returning `None` when it is not set
+pub fn optional<T>(env_prefix: &str, key: &str) -> AppResult<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    optional_in(&process_env, env_prefix, key)
+}
+
+/// Reads and parses a variable, returning `default` when it is not set.
+///
+/// A variable that is set but cannot be parsed is still an error, so that a typo
+/// does not silently fall back to the default.
+pub fn optional_with_default<T>(env_prefix: &str, key: &str, default: T) -> AppResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    optional(env_prefix, key).map(|value| value.unwrap_or(default))
+}
+
+/// Checks all the given requirements, reporting every missing or invalid variable at once
+pub fn validate(env_prefix: &str, requirements: &[Requirement]) -> AppResult<()> {
+    validate_in(&process_env, env_prefix, requirements)
+}
+
+/// Where variables are read from, the process environment outside of tests
+pub(crate) type Source<'a> = &'a dyn Fn(&str) -> Result<String, VarError>;
+
+pub(crate) fn process_env(key: &str) -> Result<String, VarError> {
+    env::var(key)
+}
+
+/// [`var`] reading from `source`
+pub(crate) fn var_in(source: Source, env_prefix: &str, key: &str) -> AppResult<String> {
+    let key = format!("{env_prefix}_{key}");
+    source(&key).map_err(|e| AppMessage::MissingEnvironmentVariable(key, e).into_anyhow())
+}
+
+fn required_in<T>(source: Source, env_prefix: &str, key: &str) -> AppResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
     let key = format!("{env_prefix}_{key}");
-    env::var(&key).map_err(|e| AppMessage::MissingEnvironmentVariable(key, e).into_anyhow())
+    let raw = source(&key)
+        .map_err(|e| AppMessage::MissingEnvironmentVariable(key.clone(), e).into_anyhow())?;
+
+    parse(&key, &raw)
+}
+
+fn optional_in<T>(source: Source, env_prefix: &str, key: &str) -> AppResult<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let key = format!("{env_prefix}_{key}");
+    match source(&key) {
+        Ok(raw) => parse(&key, &raw).map(Some),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(e) => Err(AppMessage::MissingEnvironmentVariable(key, e).into_anyhow()),
+    }
+}
+
+fn validate_in(source: Source, env_prefix: &str, requirements: &[Requirement]) -> AppResult<()> {
+    let mut error = EnvValidationError::default();
+
+    for requirement in requirements {
+        let key = format!("{env_prefix}_{}", requirement.key);
+        match source(&key) {
+            Ok(raw) => {
+                if let Err(reason) = (requirement.check)(&raw) {
+                    error.invalid.push((key, reason));
+                }
+            }
+            Err(VarError::NotPresent) if !requirement.required => {}
+            Err(VarError::NotPresent) => error.missing.push(key),
+            Err(e) => error.invalid.push((key, e.to_string())),
+        }
+    }
+
+    match error.missing.is_empty() && error.invalid.is_empty() {
+        true => Ok(()),
+        false => Err(error.into()),
+    }
+}
+
+fn parse<T>(key: &str, raw: &str) -> AppResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    raw.parse().map_err(|e| {
+        AppMessage::InternalServerError(format!(
+            "Environment variable '{key}' has an invalid value: {e}"
+        ))
+        .into_anyhow()
+    })
+}
+
+fn check<T>(raw: &str) -> Result<(), String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    raw.parse::<T>().map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// An environment variable expected at startup, named without the prefix, see [`validate`]
+#[derive(Clone)]
+pub struct Requirement {
+    key: String,
+    required: bool,
+    check: fn(&str) -> Result<(), String>,
+}
+
+impl Requirement {
+    /// The variable must be set and parse as `T`
+    pub fn required<T>(key: impl Into<String>) -> Self
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Self {
+            key: key.into(),
+            required: true,
+            check: check::<T>,
+        }
+    }
+
+    /// The variable may be unset, but must parse as `T` when set
+    pub fn optional<T>(key: impl Into<String>) -> Self
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Self {
+            key: key.into(),
+            required: false,
+            check: check::<T>,
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl Debug for Requirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Requirement")
+            .field("key", &self.key)
+            .field("required", &self.required)
+            .finish()
+    }
+}
+
+/// Every problem found by [`validate`]
+#[derive(Debug, Default, Clone, PartialEq, thiserror::Error)]
+pub struct EnvValidationError {
+    /// Required variables that are not set, prefixed
+    pub missing: Vec<String>,
+    /// Variables that are set but invalid, with the reason
+    pub invalid: Vec<(String, String)>,
+}
+
+impl Display for EnvValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid environment")?;
+
+        if !self.missing.is_empty() {
+            write!(f, "; missing: {}", self.missing.join(", "))?;
+        }
+
+        if !self.invalid.is_empty() {
+            let invalid = self
+                .invalid
+                .iter()
+                .map(|(key, reason)| format!("{key} ({reason})"))
+                .collect::<Vec<_>>();
+            write!(f, "; invalid: {}", invalid.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A value that is never printed by `Debug`, for passwords, tokens and keys
+/// read from the environment.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The wrapped value, to be passed on without logging it
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
+
+impl<T: FromStr> FromStr for Secret<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// An environment holding only `vars`, tests don't touch the process one
+    fn source(vars: &[(&str, &str)]) -> impl Fn(&str) -> Result<String, VarError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned().ok_or(VarError::NotPresent)
+    }
+
+    #[test]
+    fn test_required_parses_value() {
+        let source = source(&[("APP_PORT", "8080"), ("APP_BAD_PORT", "http")]);
+
+        assert_eq!(required_in::<u16>(&source, "APP", "PORT").unwrap(), 8080);
+        assert!(required_in::<u16>(&source, "APP", "MISSING").is_err());
+        assert!(required_in::<u16>(&source, "OTHER", "PORT").is_err());
+
+        let err = required_in::<u16>(&source, "APP", "BAD_PORT").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Environment variable 'APP_BAD_PORT' has an invalid value")
+        );
+    }
+
+    #[test]
+    fn test_optional_values() {
+        let source = source(&[("APP_WORKERS", "8"), ("APP_BAD_WORKERS", "eight")]);
+
+        assert_eq!(
+            optional_in::<usize>(&source, "APP", "WORKERS").unwrap(),
+            Some(8)
+        );
+        assert_eq!(
+            optional_in::<usize>(&source, "APP", "NO_WORKERS").unwrap(),
+            None
+        );
+        assert!(optional_in::<usize>(&source, "APP", "BAD_WORKERS").is_err());
+        assert_eq!(
+            optional_with_default("FOXTIVE_ENV_TEST", "UNSET", 2usize).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_everything_at_once() {
+        let source = source(&[
+            ("APP_VALID_URL", "postgres://localhost"),
+            ("APP_INVALID_DEBUG", "yes"),
+        ]);
+
+        let err = validate_in(
+            &source,
+            "APP",
+            &[
+                Requirement::required::<String>("VALID_URL"),
+                Requirement::required::<String>("MISSING_A"),
+                Requirement::required::<u16>("MISSING_B"),
+                Requirement::optional::<u16>("MISSING_C"),
+                Requirement::optional::<bool>("INVALID_DEBUG"),
+            ],
+        )
+        .unwrap_err();
+
+        let err = err.downcast::<EnvValidationError>().unwrap();
+        assert_eq!(err.missing, vec!["APP_MISSING_A", "APP_MISSING_B"]);
+        assert_eq!(err.invalid.len(), 1);
+        assert_eq!(err.invalid[0].0, "APP_INVALID_DEBUG");
+        assert_eq!(
+            err.to_string(),
+            "invalid environment; missing: APP_MISSING_A, APP_MISSING_B; \
+             invalid: APP_INVALID_DEBUG (provided string was not `true` or `false`)"
+        );
+
+        let requirements = [Requirement::required::<String>("VALID_URL")];
+        assert!(validate_in(&source, "APP", &requirements).is_ok());
+    }
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let source = source(&[("APP_TOKEN", "s3cr3t")]);
+
+        let token = required_in::<Secret<String>>(&source, "APP", "TOKEN").unwrap();
+        assert_eq!(format!("{token:?}"), "Secret(***)");
+        assert_eq!(format!("{:?}", Some(&token)), "Some(Secret(***))");
+        assert_eq!(token.expose(), "s3cr3t");
+        assert_eq!(token.into_inner(), "s3cr3t");
+    }
 }
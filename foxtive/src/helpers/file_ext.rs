@@ -7,6 +7,43 @@ pub const COMPOUND_EXTENSIONS: &[&str] = &[
     "7z.001", "7z.gpg", "gz", "bz2", "xz", "zst", "lz", "lzma", "lzo", "sz", "br",
 ];
 
+/// File type detected from the leading (magic) bytes of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SniffedType {
+    pub mime: &'static str,
+    pub extension: &'static str,
+}
+
+/// Signatures checked by [`FileExtHelper::sniff`]: offset, magic bytes, mime type, extension
+const SIGNATURES: &[(usize, &[u8], &str, &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png", "png"),
+    (0, b"\xff\xd8\xff", "image/jpeg", "jpg"),
+    (0, b"GIF87a", "image/gif", "gif"),
+    (0, b"GIF89a", "image/gif", "gif"),
+    (0, b"BM", "image/bmp", "bmp"),
+    (0, b"II*\x00", "image/tiff", "tiff"),
+    (0, b"MM\x00*", "image/tiff", "tiff"),
+    (0, b"\x00\x00\x01\x00", "image/x-icon", "ico"),
+    (0, b"%PDF-", "application/pdf", "pdf"),
+    (0, b"PK\x03\x04", "application/zip", "zip"),
+    (0, b"\x1f\x8b", "application/gzip", "gz"),
+    (0, b"BZh", "application/x-bzip2", "bz2"),
+    (0, b"\xfd7zXZ\x00", "application/x-xz", "xz"),
+    (
+        0,
+        b"7z\xbc\xaf\x27\x1c",
+        "application/x-7z-compressed",
+        "7z",
+    ),
+    (0, b"Rar!\x1a\x07", "application/vnd.rar", "rar"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd", "zst"),
+    (0, b"ID3", "audio/mpeg", "mp3"),
+    (0, b"OggS", "audio/ogg", "ogg"),
+    (0, b"fLaC", "audio/flac", "flac"),
+    (0, b"\x1a\x45\xdf\xa3", "video/webm", "webm"),
+    (0, b"\x00asm", "application/wasm", "wasm"),
+];
+
 pub struct FileExtHelper {
     known_exts: HashSet<String>,
 }
@@ -119,6 +156,58 @@ impl FileExtHelper {
         let ext = self.get_extension(filename);
         (base, ext)
     }
+
+    /// Detect the file type from its leading bytes, regardless of the claimed extension.
+    ///
+    /// The first 16 bytes are enough for every supported format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::FileExtHelper;
+    ///
+    /// let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+    /// let sniffed = FileExtHelper::sniff(png).unwrap();
+    /// assert_eq!(sniffed.mime, "image/png");
+    /// assert_eq!(sniffed.extension, "png");
+    ///
+    /// assert!(FileExtHelper::sniff(b"plain text").is_none());
+    /// ```
+    pub fn sniff(bytes: &[u8]) -> Option<SniffedType> {
+        let riff = |kind: &[u8]| bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(kind);
+
+        if riff(b"WEBP") {
+            return Some(SniffedType {
+                mime: "image/webp",
+                extension: "webp",
+            });
+        }
+
+        if riff(b"WAVE") {
+            return Some(SniffedType {
+                mime: "audio/wav",
+                extension: "wav",
+            });
+        }
+
+        // ISO base media files (mp4, mov, heic, avif) carry a brand after "ftyp"
+        if bytes.get(4..8) == Some(b"ftyp") {
+            let (mime, extension) = match bytes.get(8..12) {
+                Some(b"avif") => ("image/avif", "avif"),
+                Some(b"heic" | b"heix" | b"mif1") => ("image/heic", "heic"),
+                Some(b"qt  ") => ("video/quicktime", "mov"),
+                _ => ("video/mp4", "mp4"),
+            };
+            return Some(SniffedType { mime, extension });
+        }
+
+        SIGNATURES
+            .iter()
+            .find(|(offset, magic, _, _)| {
+                bytes.get(*offset..).is_some_and(|b| b.starts_with(magic))
+            })
+            .map(|(_, _, mime, extension)| SniffedType { mime, extension })
+    }
 }
 
 impl Default for FileExtHelper {
@@ -199,6 +288,28 @@ mod tests {
         assert_eq!(handler.remove_extension("backup.xz"), "backup");
     }
 
+    #[test]
+    fn test_sniff_magic_bytes() {
+        let cases: &[(&[u8], &str)] = &[
+            (b"\xff\xd8\xff\xe0\x00\x10JFIF", "jpg"),
+            (b"GIF89a\x01\x00", "gif"),
+            (b"%PDF-1.7\n", "pdf"),
+            (b"PK\x03\x04\x14\x00", "zip"),
+            (b"RIFF\x24\x00\x00\x00WEBPVP8 ", "webp"),
+            (b"RIFF\x24\x00\x00\x00WAVEfmt ", "wav"),
+            (b"\x00\x00\x00\x20ftypisom", "mp4"),
+            (b"\x00\x00\x00\x1cftypavif", "avif"),
+        ];
+
+        for (bytes, extension) in cases {
+            assert_eq!(FileExtHelper::sniff(bytes).unwrap().extension, *extension);
+        }
+
+        assert!(FileExtHelper::sniff(b"").is_none());
+        assert!(FileExtHelper::sniff(b"RIFF").is_none());
+        assert!(FileExtHelper::sniff(b"<svg></svg>").is_none());
+    }
+
     #[test]
     fn test_single_level_extensions() {
         let handler = FileExtHelper::new();
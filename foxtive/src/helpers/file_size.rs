@@ -1,3 +1,4 @@
+use serde::{Deserialize, Deserializer, Serializer, de};
 use std::fmt;
 
 /// Represents different size units and their corresponding values
@@ -331,6 +332,210 @@ pub fn format_size_decimal(size_in_bytes: u64) -> String {
     SizeFormatter::with_config(config).format(size_in_bytes)
 }
 
+/// Why a size could not be parsed by [`parse_size`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SizeParseError {
+    #[error("size is empty")]
+    Empty,
+    #[error("invalid size number: {0}")]
+    InvalidNumber(String),
+    #[error("unknown size unit: {0}")]
+    UnknownUnit(String),
+    #[error("size does not fit in 64 bits")]
+    Overflow,
+}
+
+/// Parses a human readable size, e.g. `1.5 GB`, `100MB`, `512` or `2 KiB`, into bytes.
+///
+/// `KB`, `MB`... are binary (1024-based), as [`format_size`] writes them, `KiB`, `MiB`...
+/// are always binary, use [`parse_size_decimal`] for 1000-based `KB`, `MB`...
+///
+/// Units are case-insensitive and may be abbreviated (`k`, `KB`) or written in full (`kilobytes`).
+/// Spaces, underscores and apostrophes group thousands; a dot is a decimal separator, a comma
+/// is one unless it is followed by exactly three digits, e.g. `1,5 GB` and `1,024 KB`.
+/// When both are present the last one is the decimal separator, e.g. `1.024,5 KB`.
+///
+/// # Examples
+///
+/// ```rust
+/// use foxtive::helpers::file_size::parse_size;
+///
+/// assert_eq!(parse_size("1.5 KB").unwrap(), 1536);
+/// assert_eq!(parse_size("100MB").unwrap(), 100 * 1024 * 1024);
+/// assert_eq!(parse_size("1,5 kib").unwrap(), 1536);
+/// assert_eq!(parse_size("2 048").unwrap(), 2048);
+/// ```
+pub fn parse_size(text: &str) -> Result<u64, SizeParseError> {
+    parse_size_with(text, true)
+}
+
+/// [`parse_size`] with `KB`, `MB`... being 1000-based, `KiB`, `MiB`... stay 1024-based
+pub fn parse_size_decimal(text: &str) -> Result<u64, SizeParseError> {
+    parse_size_with(text, false)
+}
+
+/// Parses a size, `use_binary_prefix` tells whether `KB`, `MB`... are 1024 or 1000-based
+pub fn parse_size_with(text: &str, use_binary_prefix: bool) -> Result<u64, SizeParseError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(SizeParseError::Empty);
+    }
+
+    let split = text.find(char::is_alphabetic).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+
+    let multiplier = unit_multiplier(unit.trim(), use_binary_prefix)
+        .ok_or_else(|| SizeParseError::UnknownUnit(unit.trim().to_string()))?;
+
+    let number = normalize_number(number.trim())
+        .ok_or_else(|| SizeParseError::InvalidNumber(number.trim().to_string()))?;
+
+    if let Ok(integer) = number.parse::<u64>() {
+        return integer
+            .checked_mul(multiplier)
+            .ok_or(SizeParseError::Overflow);
+    }
+
+    let value: f64 = number
+        .parse()
+        .map_err(|_| SizeParseError::InvalidNumber(number.clone()))?;
+
+    let bytes = (value * multiplier as f64).round();
+    if bytes >= u64::MAX as f64 {
+        return Err(SizeParseError::Overflow);
+    }
+
+    Ok(bytes as u64)
+}
+
+/// Formats a size with the largest binary unit dividing it exactly, e.g. `100 MB` or `1537 B`,
+/// so [`parse_size`] reads it back to the same number of bytes
+pub fn format_size_exact(size_in_bytes: u64) -> String {
+    let units = [
+        SizeUnit::Exabyte,
+        SizeUnit::Petabyte,
+        SizeUnit::Terabyte,
+        SizeUnit::Gigabyte,
+        SizeUnit::Megabyte,
+        SizeUnit::Kilobyte,
+    ];
+
+    units
+        .into_iter()
+        .find(|unit| size_in_bytes > 0 && size_in_bytes.is_multiple_of(unit.bytes()))
+        .map(|unit| format!("{} {}", size_in_bytes / unit.bytes(), unit.abbrev()))
+        .unwrap_or_else(|| format!("{size_in_bytes} B"))
+}
+
+/// Deserializes a size given in bytes, `1048576`, or as a human readable string, `"1 MB"`.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use foxtive::helpers::file_size::deserialize_size;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     #[serde(deserialize_with = "deserialize_size")]
+///     max_upload_size: u64,
+/// }
+///
+/// let config: Config = serde_json::from_str(r#"{"max_upload_size": "100MB"}"#).unwrap();
+/// assert_eq!(config.max_upload_size, 100 * 1024 * 1024);
+/// ```
+pub fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    match RawSize::deserialize(deserializer)? {
+        RawSize::Bytes(bytes) => Ok(bytes),
+        RawSize::Text(text) => parse_size(&text).map_err(de::Error::custom),
+    }
+}
+
+/// [`deserialize_size`] of an optional field, `null` or an empty string is `None`
+pub fn deserialize_optional_size<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error> {
+    match Option::<RawSize>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(RawSize::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(RawSize::Text(text)) if text.trim().is_empty() => Ok(None),
+        Some(RawSize::Text(text)) => parse_size(&text).map(Some).map_err(de::Error::custom),
+    }
+}
+
+/// Serializes a size with [`format_size_exact`]
+pub fn serialize_size<S: Serializer>(
+    size_in_bytes: &u64,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_size_exact(*size_in_bytes))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSize {
+    Bytes(u64),
+    Text(String),
+}
+
+fn unit_multiplier(unit: &str, use_binary_prefix: bool) -> Option<u64> {
+    let unit = unit.to_lowercase();
+    let base: u64 = if use_binary_prefix { 1024 } else { 1000 };
+
+    let (exponent, binary) = match unit.as_str() {
+        "" | "b" | "byte" | "bytes" => return Some(1),
+        "k" | "kb" | "kilobyte" | "kilobytes" => (1, false),
+        "m" | "mb" | "megabyte" | "megabytes" => (2, false),
+        "g" | "gb" | "gigabyte" | "gigabytes" => (3, false),
+        "t" | "tb" | "terabyte" | "terabytes" => (4, false),
+        "p" | "pb" | "petabyte" | "petabytes" => (5, false),
+        "e" | "eb" | "exabyte" | "exabytes" => (6, false),
+        "ki" | "kib" | "kibibyte" | "kibibytes" => (1, true),
+        "mi" | "mib" | "mebibyte" | "mebibytes" => (2, true),
+        "gi" | "gib" | "gibibyte" | "gibibytes" => (3, true),
+        "ti" | "tib" | "tebibyte" | "tebibytes" => (4, true),
+        "pi" | "pib" | "pebibyte" | "pebibytes" => (5, true),
+        "ei" | "eib" | "exbibyte" | "exbibytes" => (6, true),
+        _ => return None,
+    };
+
+    let base = if binary { 1024 } else { base };
+    Some(base.pow(exponent))
+}
+
+/// Removes the thousands separators and makes the decimal separator a dot
+fn normalize_number(number: &str) -> Option<String> {
+    let number: String = number
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '\'' | '\u{a0}' | '\u{202f}'))
+        .collect();
+
+    let decimal = match (number.rfind('.'), number.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(dot.max(comma)),
+        (Some(dot), None) => (number.matches('.').count() == 1).then_some(dot),
+        (None, Some(comma)) => {
+            let grouping = number.len() - comma - 1 == 3;
+            (number.matches(',').count() == 1 && !grouping).then_some(comma)
+        }
+        (None, None) => None,
+    };
+
+    let normalized: String = number
+        .char_indices()
+        .filter_map(|(index, c)| match c {
+            '.' | ',' if Some(index) == decimal => Some('.'),
+            '.' | ',' => None,
+            _ => Some(c),
+        })
+        .collect();
+
+    let valid = !normalized.is_empty()
+        && normalized != "."
+        && normalized.chars().all(|c| c.is_ascii_digit() || c == '.');
+
+    valid.then_some(normalized)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -410,4 +615,94 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("512 B").unwrap(), 512);
+        assert_eq!(parse_size("1.5 GB").unwrap(), 1_610_612_736);
+        assert_eq!(parse_size("100MB").unwrap(), 104_857_600);
+        assert_eq!(parse_size("2 kilobytes").unwrap(), 2048);
+        assert_eq!(parse_size("1 TiB").unwrap(), 1024_u64.pow(4));
+        assert_eq!(parse_size(" 1.50 KB ").unwrap(), 1536);
+    }
+
+    #[test]
+    fn test_parse_size_decimal() {
+        assert_eq!(parse_size_decimal("1.5 GB").unwrap(), 1_500_000_000);
+        assert_eq!(parse_size_decimal("1 KiB").unwrap(), 1024);
+        assert_eq!(parse_size_with("1 k", false).unwrap(), 1000);
+    }
+
+    #[test]
+    fn test_parse_size_separators() {
+        assert_eq!(parse_size("1,5 KB").unwrap(), 1536);
+        assert_eq!(parse_size("1,024 B").unwrap(), 1024);
+        assert_eq!(parse_size("1.024,5 KB").unwrap(), 1_049_088);
+        assert_eq!(parse_size("1,024.5 KB").unwrap(), 1_049_088);
+        assert_eq!(parse_size("1 000 000").unwrap(), 1_000_000);
+        assert_eq!(parse_size("1_000").unwrap(), 1000);
+        assert_eq!(parse_size("1.000.000").unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn test_parse_size_errors() {
+        assert_eq!(parse_size(""), Err(SizeParseError::Empty));
+        assert_eq!(
+            parse_size("12 parsecs"),
+            Err(SizeParseError::UnknownUnit("parsecs".to_string()))
+        );
+        assert!(matches!(
+            parse_size("-1 KB"),
+            Err(SizeParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_size("MB"),
+            Err(SizeParseError::InvalidNumber(_))
+        ));
+        assert_eq!(parse_size("20 EB"), Err(SizeParseError::Overflow));
+    }
+
+    #[test]
+    fn test_format_size_exact_round_trips() {
+        for bytes in [0, 1, 1023, 1024, 1537, 104_857_600, 3 * 1024_u64.pow(4)] {
+            assert_eq!(parse_size(&format_size_exact(bytes)).unwrap(), bytes);
+        }
+
+        assert_eq!(format_size_exact(104_857_600), "100 MB");
+        assert_eq!(format_size_exact(1537), "1537 B");
+    }
+
+    #[test]
+    fn test_serde() {
+        #[derive(Debug, serde::Serialize, Deserialize)]
+        struct Config {
+            #[serde(
+                deserialize_with = "deserialize_size",
+                serialize_with = "serialize_size"
+            )]
+            max_upload_size: u64,
+            #[serde(default, deserialize_with = "deserialize_optional_size")]
+            max_body_size: Option<u64>,
+        }
+
+        let config: Config =
+            serde_json::from_str(r#"{"max_upload_size": "100MB", "max_body_size": 2048}"#).unwrap();
+        assert_eq!(config.max_upload_size, 104_857_600);
+        assert_eq!(config.max_body_size, Some(2048));
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["max_upload_size"], "100 MB");
+
+        let config: Config = serde_json::from_str(r#"{"max_upload_size": 10}"#).unwrap();
+        assert_eq!(config.max_body_size, None);
+
+        let invalid = serde_json::from_str::<Config>(r#"{"max_upload_size": "10 XB"}"#);
+        assert!(
+            invalid
+                .unwrap_err()
+                .to_string()
+                .contains("unknown size unit")
+        );
+    }
 }
@@ -0,0 +1,130 @@
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{Deserializer, Error as _, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Error, Value};
+
+/// Deserializes a parsed form, reading numbers, booleans and enums from the strings
+/// form values arrive as
+pub(super) struct FormDeserializer(pub(super) Value);
+
+impl IntoDeserializer<'_, Error> for FormDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0 {
+                    Value::String(text) => match text.trim().parse() {
+                        Ok(value) => visitor.$visit(value),
+                        Err(_) => Err(Error::custom(format!("invalid number '{text}'"))),
+                    },
+                    value => value.$method(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for FormDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Array(items) => visitor.visit_seq(SeqDeserializer::new(
+                items.into_iter().map(FormDeserializer),
+            )),
+            Value::Object(map) => visitor.visit_map(MapDeserializer::new(
+                map.into_iter()
+                    .map(|(key, value)| (key, FormDeserializer(value))),
+            )),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::String(text) => match text.trim().to_lowercase().as_str() {
+                "true" | "1" | "on" | "yes" => visitor.visit_bool(true),
+                "false" | "0" | "off" | "no" | "" => visitor.visit_bool(false),
+                _ => Err(Error::custom(format!("invalid boolean '{text}'"))),
+            },
+            value => value.deserialize_bool(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self.0 {
+            Value::Null => visitor.visit_none(),
+            Value::String(text) if text.is_empty() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            // a list given a single value
+            Value::String(text) if text.is_empty() => {
+                visitor.visit_seq(SeqDeserializer::new(std::iter::empty::<FormDeserializer>()))
+            }
+            Value::String(_) => visitor.visit_seq(SeqDeserializer::new(std::iter::once(self))),
+            value => FormDeserializer(value).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::String(text) => {
+                let text: serde::de::value::StringDeserializer<Error> = text.into_deserializer();
+                text.deserialize_enum(name, variants, visitor)
+            }
+            value => value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple_struct map struct
+        identifier ignored_any
+    }
+}
@@ -0,0 +1,28 @@
+//! Form handling utilities
+//!
+//! Besides the common DTOs, this parses form-encoded bodies and multipart fields with
+//! nested `a[b][0][c]` keys into JSON values or typed structs, and encodes values back
+//! to that form for outgoing requests, with the `http` feature.
+
+#[cfg(feature = "http")]
+mod de;
+#[cfg(feature = "http")]
+mod nested;
+
+#[cfg(feature = "http")]
+pub use nested::{
+    from_nested_str, from_nested_value, from_pairs, parse_nested, to_nested_string, to_pairs,
+};
+
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct IdsVecDto {
+    pub ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct IdUuidDto {
+    pub id: Uuid,
+}
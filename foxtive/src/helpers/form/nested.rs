@@ -0,0 +1,418 @@
+use super::de::FormDeserializer;
+use crate::bad_request;
+use crate::prelude::AppResult;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Deepest nesting accepted in a key, `a[b][c]` is nested twice
+const MAX_DEPTH: usize = 32;
+
+/// Parses a form-encoded body or query string with nested keys into a JSON value
+///
+/// Bracketed keys nest, `a[b]=x` gives `{"a": {"b": "x"}}`, numeric and empty
+/// brackets build arrays, `a[1]=y&a[0]=x` and `a[]=x&a[]=y` both give `{"a": ["x", "y"]}`,
+/// as does a repeated plain key, `a=x&a=y`. Values are kept as strings.
+///
+/// # Errors
+///
+/// Fails when a key is both a value and a container, `a=x&a[b]=y`, or nests too deep.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::form::parse_nested;
+/// use serde_json::json;
+///
+/// let value = parse_nested("user[name]=Ada&user[roles][]=admin&user[roles][]=dev").unwrap();
+/// assert_eq!(value, json!({"user": {"name": "Ada", "roles": ["admin", "dev"]}}));
+/// ```
+pub fn parse_nested(input: &str) -> AppResult<Value> {
+    from_pairs(form_urlencoded::parse(
+        input.trim_start_matches('?').as_bytes(),
+    ))
+}
+
+/// Builds a JSON value from already decoded `(key, value)` pairs with nested keys,
+/// the text fields of a multipart form for instance, see [`parse_nested`]
+pub fn from_pairs<I, K, V>(pairs: I) -> AppResult<Value>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: Into<String>,
+{
+    let (mut root, mut indexes) = (Map::new(), Indexes::new());
+    for (key, value) in pairs {
+        let key = key.as_ref();
+        let segments = parse_key(key).ok_or_else(|| bad_request!("malformed form key '{key}'"))?;
+        insert(
+            &mut root,
+            &mut indexes,
+            &mut Vec::new(),
+            &segments,
+            value.into(),
+        )
+        .map_err(|err| bad_request!("form key '{key}' {err}"))?;
+    }
+
+    Ok(compact(Value::Object(root)))
+}
+
+/// Deserializes a form-encoded string with nested keys into `T`
+///
+/// Numbers, booleans (`true`, `false`, `1`, `0`, `on`, `off`) and enums are read from
+/// their text, an empty value is `None` for an `Option` and a single value fills a `Vec`.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::form::from_nested_str;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Filter {
+///     status: Vec<String>,
+///     price: Range,
+///     in_stock: bool,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Range {
+///     min: u32,
+///     max: Option<u32>,
+/// }
+///
+/// let filter: Filter =
+///     from_nested_str("status[]=paid&price[min]=10&price[max]=&in_stock=on").unwrap();
+/// assert_eq!(filter.status, ["paid"]);
+/// assert_eq!((filter.price.min, filter.price.max), (10, None));
+/// assert!(filter.in_stock);
+/// ```
+pub fn from_nested_str<T: DeserializeOwned>(input: &str) -> AppResult<T> {
+    from_nested_value(parse_nested(input)?)
+}
+
+/// Deserializes a value built by [`parse_nested`] or [`from_pairs`] into `T`,
+/// reading scalars from their text as [`from_nested_str`] does
+pub fn from_nested_value<T: DeserializeOwned>(value: Value) -> AppResult<T> {
+    T::deserialize(FormDeserializer(value)).map_err(|err| bad_request!("invalid form: {err}"))
+}
+
+/// Flattens `value` into `(key, value)` pairs with bracketed keys, the inverse of
+/// [`from_pairs`], to build the fields of an outgoing multipart form
+///
+/// Nulls are left out, booleans and numbers are written as text.
+///
+/// # Errors
+///
+/// Fails unless `value` serializes to a map or struct.
+pub fn to_pairs<T: Serialize + ?Sized>(value: &T) -> AppResult<Vec<(String, String)>> {
+    let Value::Object(map) = serde_json::to_value(value)? else {
+        return Err(bad_request!("only maps and structs can be form encoded"));
+    };
+
+    let mut pairs = Vec::new();
+    for (key, value) in map {
+        flatten(key, value, &mut pairs);
+    }
+
+    Ok(pairs)
+}
+
+/// Form-encodes `value` with bracketed keys, for outgoing requests to APIs expecting
+/// nested form bodies
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::form::{parse_nested, to_nested_string};
+/// use serde_json::json;
+///
+/// let value = json!({"user": {"name": "Ada Lovelace", "roles": ["admin"]}});
+/// let encoded = to_nested_string(&value).unwrap();
+/// assert_eq!(encoded, "user%5Bname%5D=Ada+Lovelace&user%5Broles%5D%5B0%5D=admin");
+/// assert_eq!(parse_nested(&encoded).unwrap(), value);
+/// ```
+pub fn to_nested_string<T: Serialize + ?Sized>(value: &T) -> AppResult<String> {
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    serializer.extend_pairs(to_pairs(value)?);
+    Ok(serializer.finish())
+}
+
+/// Splits `a[b][]` into `["a", "b", ""]`
+fn parse_key(key: &str) -> Option<Vec<&str>> {
+    let (name, mut rest) = match key.find('[') {
+        Some(pos) => key.split_at(pos),
+        None => (key, ""),
+    };
+
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut segments = vec![name];
+    while !rest.is_empty() {
+        let (segment, tail) = rest.strip_prefix('[')?.split_once(']')?;
+        segments.push(segment);
+        rest = tail;
+    }
+
+    match segments.len() > MAX_DEPTH + 1 {
+        true => None,
+        false => Some(segments),
+    }
+}
+
+/// Next append index of each container by path, kept alongside the value so an empty
+/// bracket doesn't rescan its siblings, or why the container can't be appended to
+type Indexes = HashMap<Vec<String>, Result<usize, &'static str>>;
+
+fn insert(
+    map: &mut Map<String, Value>,
+    indexes: &mut Indexes,
+    path: &mut Vec<String>,
+    segments: &[&str],
+    value: String,
+) -> Result<(), String> {
+    let (segment, rest) = segments.split_first().expect("keys have a name");
+    let key = match segment.is_empty() {
+        true => next_index(indexes, path)?.to_string(),
+        false => segment.to_string(),
+    };
+    record(indexes, path, &key);
+    path.push(key.clone());
+
+    if rest.is_empty() {
+        match map.get_mut(&key) {
+            None => {
+                map.insert(key, Value::String(value));
+            }
+            // a repeated key collects its values
+            Some(Value::String(previous)) => {
+                let previous = std::mem::take(previous);
+                let values = Map::from_iter([
+                    ("0".to_string(), Value::String(previous)),
+                    ("1".to_string(), Value::String(value)),
+                ]);
+                map.insert(key, Value::Object(values));
+                indexes.insert(path.clone(), Ok(2));
+            }
+            Some(Value::Object(children)) => {
+                let index = next_index(indexes, path)?.to_string();
+                record(indexes, path, &index);
+                children.insert(index, Value::String(value));
+            }
+            Some(_) => unreachable!("only strings and objects are inserted"),
+        }
+
+        return Ok(());
+    }
+
+    match map.entry(key).or_insert_with(|| Value::Object(Map::new())) {
+        Value::Object(children) => insert(children, indexes, path, rest, value),
+        _ => Err("is both a value and a nested field".to_string()),
+    }
+}
+
+/// The index an empty bracket appends at, the container must hold an array
+fn next_index(indexes: &Indexes, path: &[String]) -> Result<usize, String> {
+    match indexes.get(path) {
+        Some(next) => next.map_err(str::to_string),
+        None => Ok(0),
+    }
+}
+
+/// Accounts for `key` being added to the container at `path`
+fn record(indexes: &mut Indexes, path: &[String], key: &str) {
+    let added = match key.parse::<usize>() {
+        Ok(index) => index.checked_add(1).ok_or("has too large an index"),
+        Err(_) => Err("mixes list items and named fields"),
+    };
+
+    let next = match indexes.get(path) {
+        Some(Err(_)) => return,
+        Some(Ok(current)) => added.map(|added| added.max(*current)),
+        None => added,
+    };
+    indexes.insert(path.to_vec(), next);
+}
+
+/// Turns the objects whose keys are all indexes into arrays, in index order
+fn compact(value: Value) -> Value {
+    let Value::Object(map) = value else {
+        return value;
+    };
+
+    let indexes: Option<Vec<usize>> = map.keys().map(|key| key.parse().ok()).collect();
+    match indexes {
+        Some(indexes) if !map.is_empty() => {
+            let mut items: Vec<(usize, Value)> = indexes
+                .into_iter()
+                .zip(map.into_iter().map(|(_, value)| compact(value)))
+                .collect();
+            items.sort_by_key(|(index, _)| *index);
+            Value::Array(items.into_iter().map(|(_, value)| value).collect())
+        }
+        _ => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, compact(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn flatten(key: String, value: Value, pairs: &mut Vec<(String, String)>) {
+    match value {
+        Value::Null => {}
+        Value::String(value) => pairs.push((key, value)),
+        Value::Bool(_) | Value::Number(_) => pairs.push((key, value.to_string())),
+        Value::Array(items) => {
+            for (index, item) in items.into_iter().enumerate() {
+                flatten(format!("{key}[{index}]"), item, pairs);
+            }
+        }
+        Value::Object(map) => {
+            for (child, item) in map {
+                flatten(format!("{key}[{child}]"), item, pairs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_nested_keys() {
+        let value = parse_nested("a[b][0][c]=x&a[b][0][d]=y&a[b][1][c]=z&page=2").unwrap();
+        assert_eq!(
+            value,
+            json!({"a": {"b": [{"c": "x", "d": "y"}, {"c": "z"}]}, "page": "2"})
+        );
+
+        let value =
+            parse_nested("?ids[2]=c&ids[0]=a&ids[10]=d&tag=x&tag=y&q=fox+%26+hound").unwrap();
+        assert_eq!(
+            value,
+            json!({"ids": ["a", "c", "d"], "tag": ["x", "y"], "q": "fox & hound"})
+        );
+
+        assert_eq!(parse_nested("").unwrap(), json!({}));
+    }
+
+    #[test]
+    fn test_parse_nested_rejects_conflicts() {
+        assert!(parse_nested("a=x&a[b]=y").is_err());
+        assert!(parse_nested("a[b]=x&a[]=y").is_err());
+        assert!(parse_nested("a[b=x").is_err());
+        assert!(parse_nested("[a]=x").is_err());
+        assert!(parse_nested(&format!("a[{}]=x&a[]=y", usize::MAX)).is_err());
+        assert!(parse_nested(&format!("a{}=x", "[b]".repeat(MAX_DEPTH + 1))).is_err());
+    }
+
+    #[test]
+    fn test_parse_nested_appends_after_highest_index() {
+        let value = parse_nested("a[]=x&a[5]=y&a[]=z&b=1&b=2&b[]=3&c[0][]=p&c[0][]=q").unwrap();
+        assert_eq!(
+            value,
+            json!({"a": ["x", "y", "z"], "b": ["1", "2", "3"], "c": [["p", "q"]]})
+        );
+
+        let value = parse_nested("a[]=y&a[b]=x").unwrap();
+        assert_eq!(value, json!({"a": {"0": "y", "b": "x"}}));
+
+        let body = "a[]=x&".repeat(50_000);
+        let value = parse_nested(&body).unwrap();
+        assert_eq!(value["a"].as_array().map(Vec::len), Some(50_000));
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Paid,
+        Refunded,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u64,
+        quantity: Option<i32>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Order {
+        status: Status,
+        gift: bool,
+        total: f64,
+        items: Vec<Item>,
+        tags: Vec<String>,
+        note: Option<String>,
+    }
+
+    #[test]
+    fn test_from_nested_str_reads_scalars() {
+        let order: Order = from_nested_str(
+            "status=paid&gift=1&total=9.5&items[0][id]=7&items[0][quantity]=2&items[1][id]=8&items[1][quantity]=&tags=vip",
+        )
+        .unwrap();
+
+        assert_eq!(
+            order,
+            Order {
+                status: Status::Paid,
+                gift: true,
+                total: 9.5,
+                items: vec![
+                    Item {
+                        id: 7,
+                        quantity: Some(2)
+                    },
+                    Item {
+                        id: 8,
+                        quantity: None
+                    },
+                ],
+                tags: vec!["vip".to_string()],
+                note: None,
+            }
+        );
+
+        let order: Order =
+            from_nested_str("status=refunded&gift=off&total=0&items=&tags[]=a&tags[]=b&note=late")
+                .unwrap();
+        assert_eq!(order.status, Status::Refunded);
+        assert!(!order.gift && order.items.is_empty());
+        assert_eq!((order.tags.len(), order.note.as_deref()), (2, Some("late")));
+
+        assert!(from_nested_str::<Order>("status=paid&gift=maybe&total=1&items=&tags=").is_err());
+        assert!(from_nested_str::<Order>("status=lost&gift=1&total=1&items=&tags=").is_err());
+        assert!(from_nested_str::<Order>("status=paid&gift=1&total=1&items=&tags=").is_ok());
+    }
+
+    #[test]
+    fn test_to_nested_string_roundtrip() {
+        let value = json!({
+            "customer": {"name": "Ada", "tags": ["a", "b"], "vip": true, "age": 36, "fax": null},
+        });
+
+        let pairs = to_pairs(&value).unwrap();
+        assert_eq!(
+            pairs,
+            [
+                ("customer[age]".to_string(), "36".to_string()),
+                ("customer[name]".to_string(), "Ada".to_string()),
+                ("customer[tags][0]".to_string(), "a".to_string()),
+                ("customer[tags][1]".to_string(), "b".to_string()),
+                ("customer[vip]".to_string(), "true".to_string()),
+            ]
+        );
+
+        let encoded = to_nested_string(&value).unwrap();
+        assert_eq!(from_pairs(pairs).unwrap(), parse_nested(&encoded).unwrap());
+        assert!(to_nested_string(&json!(["a"])).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::prelude::AppResult;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Hash function used to compute checksums
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    /// Much faster than SHA-256 on large files, preferred for deduplication
+    Blake3,
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            Hasher::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Computes the hex encoded checksum of in-memory data
+pub fn hash_bytes(data: &[u8], algorithm: HashAlgorithm) -> String {
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Computes the hex encoded checksum of everything read from the reader, in chunks
+pub async fn hash_reader<R>(mut reader: R, algorithm: HashAlgorithm) -> AppResult<String>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Computes the hex encoded checksum of a file without loading it in memory
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::fs::{hash_file, verify_file, HashAlgorithm};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let path = std::env::temp_dir().join("foxtive-hash-doc.txt");
+/// tokio::fs::write(&path, "hello world").await.unwrap();
+///
+/// let checksum = hash_file(&path, HashAlgorithm::Sha256).await.unwrap();
+/// assert_eq!(checksum, "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+/// assert!(verify_file(&path, HashAlgorithm::Sha256, &checksum).await.unwrap());
+/// # tokio::fs::remove_file(&path).await.unwrap();
+/// # });
+/// ```
+pub async fn hash_file<P: AsRef<Path>>(path: P, algorithm: HashAlgorithm) -> AppResult<String> {
+    hash_reader(File::open(path).await?, algorithm).await
+}
+
+/// Checks a file against an expected hex checksum (case-insensitive)
+pub async fn verify_file<P: AsRef<Path>>(
+    path: P,
+    algorithm: HashAlgorithm,
+    expected: &str,
+) -> AppResult<bool> {
+    let actual = hash_file(path, algorithm).await?;
+    let expected = expected.trim().to_ascii_lowercase();
+
+    Ok(actual.len() == expected.len()
+        && actual
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0)
+}
+
+/// Computes a single checksum for a directory tree.
+///
+/// Every file contributes its path relative to `dir` and its content checksum, in sorted
+/// order, so the result changes when a file is added, removed, renamed or modified, and
+/// does not depend on where the tree is located. Empty directories are ignored.
+pub async fn hash_dir<P: AsRef<Path>>(dir: P, algorithm: HashAlgorithm) -> AppResult<String> {
+    let root = dir.as_ref();
+    let mut files = list_files(root).await?;
+    files.sort();
+
+    let mut hasher = Hasher::new(algorithm);
+    for file in files {
+        let relative = file.strip_prefix(root).unwrap_or(&file);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+
+        hasher.update(relative.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(hash_file(&file, algorithm).await?.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    Ok(hasher.finalize())
+}
+
+async fn list_files(root: &Path) -> AppResult<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_bytes() {
+        assert_eq!(
+            hash_bytes(b"", HashAlgorithm::Sha256),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hash_bytes(b"", HashAlgorithm::Blake3),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hash_file_matches_in_memory_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("large.bin");
+        let content: Vec<u8> = (0..CHUNK_SIZE * 3 + 17).map(|i| (i % 251) as u8).collect();
+        tokio::fs::write(&path, &content).await.unwrap();
+
+        for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Blake3] {
+            let checksum = hash_file(&path, algorithm).await.unwrap();
+            assert_eq!(checksum, hash_bytes(&content, algorithm));
+            assert!(
+                verify_file(&path, algorithm, &checksum.to_uppercase())
+                    .await
+                    .unwrap()
+            );
+            assert!(!verify_file(&path, algorithm, "abc").await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_file_missing() {
+        assert!(
+            hash_file("/definitely/not/here", HashAlgorithm::Sha256)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hash_dir() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+
+        for dir in [first.path(), second.path()] {
+            tokio::fs::create_dir_all(dir.join("nested")).await.unwrap();
+            tokio::fs::write(dir.join("a.txt"), "a").await.unwrap();
+            tokio::fs::write(dir.join("nested/b.txt"), "b")
+                .await
+                .unwrap();
+        }
+
+        let algorithm = HashAlgorithm::Blake3;
+        let checksum = hash_dir(first.path(), algorithm).await.unwrap();
+        assert_eq!(checksum, hash_dir(second.path(), algorithm).await.unwrap());
+
+        tokio::fs::rename(second.path().join("a.txt"), second.path().join("c.txt"))
+            .await
+            .unwrap();
+        assert_ne!(checksum, hash_dir(second.path(), algorithm).await.unwrap());
+    }
+}
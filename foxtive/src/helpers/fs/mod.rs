@@ -1,6 +1,16 @@
 use std::env;
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "checksum")]
+mod checksum;
+#[cfg(feature = "fs")]
+mod ops;
+
+#[cfg(feature = "checksum")]
+pub use checksum::{HashAlgorithm, hash_bytes, hash_dir, hash_file, hash_reader, verify_file};
+#[cfg(feature = "fs")]
+pub use ops::{CopyProgress, copy_with_progress, dir_size, write_atomic};
+
 pub fn get_cwd() -> String {
     env::current_dir().unwrap().to_str().unwrap().to_string()
 }
@@ -0,0 +1,270 @@
+use crate::prelude::AppResult;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Progress of a [`copy_with_progress`], reported after every chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyProgress {
+    /// Bytes copied so far
+    pub copied: u64,
+    /// Size of the source file when the copy started
+    pub total: u64,
+}
+
+impl CopyProgress {
+    /// Copied share of the file, from 0 to 100, an empty file is complete right away
+    pub fn percent(&self) -> f64 {
+        match self.total {
+            0 => 100.0,
+            total => (self.copied as f64 / total as f64 * 100.0).min(100.0),
+        }
+    }
+}
+
+/// Writes `contents` to `path` so readers see either the old file or the whole new one,
+/// never a partial write
+///
+/// The data is written to a temporary file in the same directory, flushed to disk and
+/// renamed over `path`, which keeps its permissions when it already exists.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::fs::write_atomic;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let path = std::env::temp_dir().join("foxtive-write-atomic-doc.json");
+/// write_atomic(&path, r#"{"version": 2}"#).await.unwrap();
+/// assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), r#"{"version": 2}"#);
+/// # tokio::fs::remove_file(&path).await.unwrap();
+/// # });
+/// ```
+pub async fn write_atomic<P, C>(path: P, contents: C) -> AppResult<()>
+where
+    P: AsRef<Path>,
+    C: AsRef<[u8]>,
+{
+    let path = path.as_ref();
+    let temp = temp_path(path);
+
+    let written = async {
+        let mut file = File::create(&temp).await?;
+        file.write_all(contents.as_ref()).await?;
+        file.sync_all().await?;
+        keep_permissions(path, &temp).await?;
+        fs::rename(&temp, path).await?;
+        AppResult::Ok(())
+    }
+    .await;
+
+    if written.is_err() {
+        let _ = fs::remove_file(&temp).await;
+    }
+
+    written
+}
+
+/// Copies `src` to `dst` in chunks, calling `on_progress` after each of them, and
+/// returns the number of bytes copied
+///
+/// Like [`write_atomic`], the copy only appears at `dst` once complete. The source
+/// permissions are copied along.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::fs::copy_with_progress;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let dir = std::env::temp_dir();
+/// let (src, dst) = (dir.join("foxtive-copy-doc.src"), dir.join("foxtive-copy-doc.dst"));
+/// tokio::fs::write(&src, vec![0u8; 200_000]).await.unwrap();
+///
+/// let copied = copy_with_progress(&src, &dst, |progress| {
+///     println!("{:.0}%", progress.percent());
+/// })
+/// .await
+/// .unwrap();
+/// assert_eq!(copied, 200_000);
+/// # tokio::fs::remove_file(&src).await.unwrap();
+/// # tokio::fs::remove_file(&dst).await.unwrap();
+/// # });
+/// ```
+pub async fn copy_with_progress<S, D, F>(src: S, dst: D, mut on_progress: F) -> AppResult<u64>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+    F: FnMut(CopyProgress),
+{
+    let (src, dst) = (src.as_ref(), dst.as_ref());
+    let mut reader = File::open(src).await?;
+    let metadata = reader.metadata().await?;
+    let temp = temp_path(dst);
+
+    let copied = async {
+        let mut writer = File::create(&temp).await?;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut progress = CopyProgress {
+            copied: 0,
+            total: metadata.len(),
+        };
+
+        loop {
+            let read = reader.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+
+            writer.write_all(&buffer[..read]).await?;
+            progress.copied += read as u64;
+            on_progress(progress);
+        }
+
+        if progress.copied == 0 {
+            on_progress(progress);
+        }
+
+        writer.sync_all().await?;
+        fs::set_permissions(&temp, metadata.permissions()).await?;
+        fs::rename(&temp, dst).await?;
+        AppResult::Ok(progress.copied)
+    }
+    .await;
+
+    if copied.is_err() {
+        let _ = fs::remove_file(&temp).await;
+    }
+
+    copied
+}
+
+/// Total size in bytes of the files under `dir`, recursively
+///
+/// Symbolic links aren't followed, so a link to a large tree doesn't count it twice.
+/// The result pairs with the [`file_size`](crate::helpers::file_size) formatters.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::file_size::format_size;
+/// use foxtive::helpers::fs::dir_size;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let dir = std::env::temp_dir().join("foxtive-dir-size-doc");
+/// tokio::fs::create_dir_all(dir.join("logs")).await.unwrap();
+/// tokio::fs::write(dir.join("logs/app.log"), vec![b'x'; 2048]).await.unwrap();
+///
+/// let size = dir_size(&dir).await.unwrap();
+/// assert_eq!(size, 2048);
+/// assert_eq!(format_size(size), "2.00 KB");
+/// # tokio::fs::remove_dir_all(&dir).await.unwrap();
+/// # });
+/// ```
+pub async fn dir_size<P: AsRef<Path>>(dir: P) -> AppResult<u64> {
+    let mut size = 0;
+    let mut pending = vec![dir.as_ref().to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                size += entry.metadata().await?.len();
+            }
+        }
+    }
+
+    Ok(size)
+}
+
+/// A hidden temporary file next to `path`, on the same filesystem so renaming is atomic
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.tmp-{}", uuid::Uuid::new_v4()))
+}
+
+async fn keep_permissions(path: &Path, temp: &Path) -> AppResult<()> {
+    match fs::metadata(path).await {
+        Ok(metadata) => Ok(fs::set_permissions(temp, metadata.permissions()).await?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_atomic_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        write_atomic(&path, "first").await.unwrap();
+        write_atomic(&path, b"second").await.unwrap();
+
+        assert_eq!(fs::read_to_string(&path).await.unwrap(), "second");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert!(
+            write_atomic(dir.path().join("missing/config.json"), "x")
+                .await
+                .is_err()
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dst) = (dir.path().join("src.bin"), dir.path().join("dst.bin"));
+        let content: Vec<u8> = (0..CHUNK_SIZE * 2 + 5).map(|i| (i % 251) as u8).collect();
+        fs::write(&src, &content).await.unwrap();
+
+        let mut reports = vec![];
+        let copied = copy_with_progress(&src, &dst, |progress| reports.push(progress))
+            .await
+            .unwrap();
+
+        assert_eq!(copied, content.len() as u64);
+        assert_eq!(fs::read(&dst).await.unwrap(), content);
+        assert!(reports.len() >= 3);
+        assert_eq!(reports.last().unwrap().percent(), 100.0);
+
+        let empty = dir.path().join("empty");
+        fs::write(&empty, "").await.unwrap();
+        let mut reports = vec![];
+        copy_with_progress(&empty, dir.path().join("empty.copy"), |p| reports.push(p))
+            .await
+            .unwrap();
+        assert_eq!(
+            reports,
+            [CopyProgress {
+                copied: 0,
+                total: 0
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dir_size() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).await.unwrap();
+        fs::write(dir.path().join("one"), vec![0u8; 10])
+            .await
+            .unwrap();
+        fs::write(dir.path().join("a/b/two"), vec![0u8; 32])
+            .await
+            .unwrap();
+
+        assert_eq!(dir_size(dir.path()).await.unwrap(), 42);
+        assert!(dir_size(dir.path().join("missing")).await.is_err());
+    }
+}
@@ -3,11 +3,30 @@
 //! This module provides a flexible HMAC implementation that supports various SHA-2 hash functions
 //! for generating and verifying message authentication codes. The implementation is thread-safe
 //! and can be used in concurrent contexts.
+//!
+//! Previous keys can be kept alongside the current one, so signatures made before a key
+//! rotation still verify, and timestamped signatures bind a message to the time it was
+//! signed, as webhook senders and receivers do:
+//!
+//! ```
+//! use foxtive::helpers::hmac::{HashFunc, Hmac};
+//! use std::time::Duration;
+//!
+//! let old = Hmac::new("old_secret", HashFunc::Sha256);
+//! let signature = old.sign_timestamped("{\"event\":\"paid\"}").unwrap();
+//! assert!(signature.starts_with("t="));
+//!
+//! let hmac = Hmac::new("new_secret", HashFunc::Sha256).previous_key("old_secret");
+//! hmac.verify_timestamped("{\"event\":\"paid\"}", &signature, Duration::from_secs(300))
+//!     .unwrap();
+//! ```
 
 use crate::results::AppResult;
+use crate::unauthorized;
 use chrono::Utc;
 use hmac::{Hmac as HHmac, KeyInit, Mac};
 use sha2::{Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256};
+use std::time::Duration;
 
 /// Supported hash functions for HMAC generation and verification.
 #[derive(Clone, Copy, PartialEq, Debug, Default)]
@@ -37,6 +56,8 @@ pub struct Hmac {
     secret: String,
     /// Hash function used for HMAC generation and verification
     func: HashFunc,
+    /// Retired keys still accepted during verification
+    previous_keys: Vec<String>,
 }
 
 impl Hmac {
@@ -57,9 +78,29 @@ impl Hmac {
         Hmac {
             func,
             secret: secret.to_string(),
+            previous_keys: Vec::new(),
         }
     }
 
+    /// Also accepts HMACs made with `secret` when verifying, for the time a rotated key
+    /// is phased out; new HMACs are always made with the current key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use foxtive::helpers::hmac::{Hmac, HashFunc};
+    ///
+    /// let value = "message".to_string();
+    /// let hash = Hmac::new("old_key", HashFunc::Sha256).hash(&value).unwrap();
+    ///
+    /// let hmac = Hmac::new("new_key", HashFunc::Sha256).previous_key("old_key");
+    /// assert!(hmac.verify(&value, &hash).unwrap());
+    /// ```
+    pub fn previous_key(mut self, secret: &str) -> Self {
+        self.previous_keys.push(secret.to_string());
+        self
+    }
+
     /// Generates an HMAC for the given value using the specified hash function.
     ///
     /// # Arguments
@@ -81,35 +122,39 @@ impl Hmac {
     /// let value = "message".to_string();
     /// let hash = hmac.hash(&value).unwrap();
     /// ```
-    pub fn hash(&self, value: &String) -> AppResult<String> {
+    pub fn hash(&self, value: &str) -> AppResult<String> {
+        self.hash_with(&self.secret, value)
+    }
+
+    fn hash_with(&self, secret: &str, value: &str) -> AppResult<String> {
         match self.func {
             HashFunc::Sha224 => {
-                let mut mac = HHmac::<Sha224>::new_from_slice(self.secret.as_bytes())?;
+                let mut mac = HHmac::<Sha224>::new_from_slice(secret.as_bytes())?;
                 mac.update(value.as_bytes());
                 Self::convert_to_string(&mac.finalize().into_bytes())
             }
             HashFunc::Sha256 => {
-                let mut mac = HHmac::<Sha256>::new_from_slice(self.secret.as_bytes())?;
+                let mut mac = HHmac::<Sha256>::new_from_slice(secret.as_bytes())?;
                 mac.update(value.as_bytes());
                 Self::convert_to_string(&mac.finalize().into_bytes())
             }
             HashFunc::Sha384 => {
-                let mut mac = HHmac::<Sha384>::new_from_slice(self.secret.as_bytes())?;
+                let mut mac = HHmac::<Sha384>::new_from_slice(secret.as_bytes())?;
                 mac.update(value.as_bytes());
                 Self::convert_to_string(&mac.finalize().into_bytes())
             }
             HashFunc::Sha512 => {
-                let mut mac = HHmac::<Sha512>::new_from_slice(self.secret.as_bytes())?;
+                let mut mac = HHmac::<Sha512>::new_from_slice(secret.as_bytes())?;
                 mac.update(value.as_bytes());
                 Self::convert_to_string(&mac.finalize().into_bytes())
             }
             HashFunc::Sha512224 => {
-                let mut mac = HHmac::<Sha512_224>::new_from_slice(self.secret.as_bytes())?;
+                let mut mac = HHmac::<Sha512_224>::new_from_slice(secret.as_bytes())?;
                 mac.update(value.as_bytes());
                 Self::convert_to_string(&mac.finalize().into_bytes())
             }
             HashFunc::Sha512256 => {
-                let mut mac = HHmac::<Sha512_256>::new_from_slice(self.secret.as_bytes())?;
+                let mut mac = HHmac::<Sha512_256>::new_from_slice(secret.as_bytes())?;
                 mac.update(value.as_bytes());
                 Self::convert_to_string(&mac.finalize().into_bytes())
             }
@@ -153,6 +198,9 @@ impl Hmac {
     /// Returns a Result containing a boolean indicating whether the HMAC is valid
     /// or an error if verification fails.
     ///
+    /// The HMAC is compared in constant time, against the current key and then the
+    /// [previous keys](Hmac::previous_key).
+    ///
     /// # Example
     ///
     /// ```
@@ -164,9 +212,90 @@ impl Hmac {
     ///
     /// assert!(hmac.verify(&value, &hash).unwrap());
     /// ```
-    pub fn verify(&self, value: &String, hash: &String) -> AppResult<bool> {
-        let computed = self.hash(value)?;
-        Ok(hash == &computed)
+    pub fn verify(&self, value: &str, hash: &str) -> AppResult<bool> {
+        self.matches_any_key(value, |computed| constant_time_eq(computed, hash))
+    }
+
+    /// Signs `value` along with the current time, returning `t=<timestamp>,v1=<hmac>`
+    /// where the HMAC covers `<timestamp>.<value>`.
+    ///
+    /// The result is meant to be sent next to the message, in a webhook signature header
+    /// for instance, and checked with [`Hmac::verify_timestamped`].
+    pub fn sign_timestamped(&self, value: &str) -> AppResult<String> {
+        self.sign_timestamped_at(value, Utc::now().timestamp())
+    }
+
+    /// Signs `value` as [`Hmac::sign_timestamped`] does, with the given unix timestamp
+    pub fn sign_timestamped_at(&self, value: &str, timestamp: i64) -> AppResult<String> {
+        let hash = self.hash_with(&self.secret, &format!("{timestamp}.{value}"))?;
+        Ok(format!("t={timestamp},v1={hash}"))
+    }
+
+    /// Verifies a signature made by [`Hmac::sign_timestamped`] with the current key or a
+    /// previous one, failing when its timestamp is further than `max_age` from now,
+    /// which protects against replays.
+    ///
+    /// Several `v1` entries may be given, as a sender rotating its key does, the
+    /// signature is valid when any of them matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use foxtive::helpers::hmac::{Hmac, HashFunc};
+    /// use std::time::Duration;
+    ///
+    /// let hmac = Hmac::new("my_secret_key", HashFunc::Sha256);
+    /// let signature = hmac.sign_timestamped("message").unwrap();
+    ///
+    /// assert!(hmac.verify_timestamped("message", &signature, Duration::from_secs(300)).is_ok());
+    /// assert!(hmac.verify_timestamped("tampered", &signature, Duration::from_secs(300)).is_err());
+    /// ```
+    pub fn verify_timestamped(
+        &self,
+        value: &str,
+        signature: &str,
+        max_age: Duration,
+    ) -> AppResult<()> {
+        let mut timestamp = None;
+        let mut hashes = Vec::new();
+        for part in signature.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", ts)) => timestamp = ts.parse::<i64>().ok(),
+                Some(("v1", hash)) => hashes.push(hash),
+                _ => {}
+            }
+        }
+
+        let timestamp =
+            timestamp.ok_or_else(|| unauthorized!("signature has no valid timestamp"))?;
+        if Utc::now().timestamp().abs_diff(timestamp) > max_age.as_secs() {
+            return Err(unauthorized!(
+                "signature timestamp is outside the tolerance"
+            ));
+        }
+
+        let signed = format!("{timestamp}.{value}");
+        let valid = self.matches_any_key(&signed, |computed| {
+            hashes
+                .iter()
+                .fold(false, |acc, hash| acc | constant_time_eq(computed, hash))
+        })?;
+
+        match valid {
+            true => Ok(()),
+            false => Err(unauthorized!("invalid signature")),
+        }
+    }
+
+    /// Whether `check` accepts the HMAC of `value` made with any of the keys,
+    /// every key is tried so the time taken doesn't tell which one matched
+    fn matches_any_key(&self, value: &str, check: impl Fn(&str) -> bool) -> AppResult<bool> {
+        let mut valid = false;
+        for secret in std::iter::once(&self.secret).chain(&self.previous_keys) {
+            valid |= check(&self.hash_with(secret, value)?);
+        }
+
+        Ok(valid)
     }
 
     /// Converts a byte slice to its hexadecimal string representation.
@@ -184,9 +313,32 @@ impl Hmac {
     }
 }
 
+/// Compares two values in constant time, so the time taken doesn't reveal how much of
+/// a secret value, a signature or a token, was guessed right
+///
+/// Only the lengths, which aren't secret for fixed size HMACs, are compared early.
+///
+/// # Example
+///
+/// ```
+/// use foxtive::helpers::hmac::constant_time_eq;
+///
+/// assert!(constant_time_eq("abc", "abc"));
+/// assert!(!constant_time_eq(b"abc", b"abd"));
+/// ```
+pub fn constant_time_eq(a: impl AsRef<[u8]>, b: impl AsRef<[u8]>) -> bool {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{HashFunc, Hmac};
+    use super::{HashFunc, Hmac, constant_time_eq};
+    use std::time::Duration;
 
     #[test]
     fn test_hash() {
@@ -298,4 +450,62 @@ mod tests {
             "Verification should fail with different hash function"
         );
     }
+
+    #[test]
+    fn test_verify_with_previous_keys() {
+        let value = "my message".to_string();
+        let old_hash = Hmac::new("old", HashFunc::Sha256).hash(&value).unwrap();
+        let other_hash = Hmac::new("other", HashFunc::Sha256).hash(&value).unwrap();
+
+        let hmac = Hmac::new("new", HashFunc::Sha256).previous_key("old");
+        assert!(hmac.verify(&value, &old_hash).unwrap());
+        assert!(hmac.verify(&value, &hmac.hash(&value).unwrap()).unwrap());
+        assert!(!hmac.verify(&value, &other_hash).unwrap());
+        assert_ne!(hmac.hash(&value).unwrap(), old_hash);
+    }
+
+    #[test]
+    fn test_timestamped_signatures() {
+        let hmac = Hmac::new("mysecret", HashFunc::Sha256);
+        let max_age = Duration::from_secs(300);
+
+        let signature = hmac.sign_timestamped_at("body", 1_700_000_000).unwrap();
+        let expected = hmac.hash("1700000000.body").unwrap();
+        assert_eq!(signature, format!("t=1700000000,v1={expected}"));
+        assert!(
+            hmac.verify_timestamped("body", &signature, max_age)
+                .is_err()
+        );
+
+        let signature = hmac.sign_timestamped("body").unwrap();
+        assert!(hmac.verify_timestamped("body", &signature, max_age).is_ok());
+        assert!(
+            hmac.verify_timestamped("other", &signature, max_age)
+                .is_err()
+        );
+        assert!(hmac.verify_timestamped("body", "v1=abc", max_age).is_err());
+
+        let rotated = Hmac::new("next", HashFunc::Sha256)
+            .sign_timestamped("body")
+            .unwrap();
+        let (_, new_hash) = rotated.rsplit_once(',').unwrap();
+        let both = format!("{signature},{new_hash}");
+        let receiver = Hmac::new("next", HashFunc::Sha256);
+        assert!(receiver.verify_timestamped("body", &both, max_age).is_ok());
+
+        for extreme in [i64::MIN, i64::MAX] {
+            let signature = hmac.sign_timestamped_at("body", extreme).unwrap();
+            assert!(
+                hmac.verify_timestamped("body", &signature, max_age)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq("abc", "abd"));
+        assert!(!constant_time_eq("abc", "ab"));
+    }
 }
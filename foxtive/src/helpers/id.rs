@@ -0,0 +1,374 @@
+//! Time-sortable identifier generation.
+//!
+//! This module provides three families of identifiers whose natural ordering follows
+//! their creation time, which keeps database indexes compact and makes ids usable as
+//! cursors:
+//!
+//! - [ULID](https://github.com/ulid/spec): 128-bit, 26 character Crockford base32 strings
+//! - UUIDv7: RFC 9562 UUIDs, for columns typed as `uuid`
+//! - Snowflake: 64-bit integers made of a timestamp, a node id and a sequence
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::id::{self, Snowflake};
+//!
+//! let first = id::ulid_at(1_700_000_000_000);
+//! let second = id::ulid_at(1_700_000_000_001);
+//! assert_eq!(first.len(), 26);
+//! assert!(first < second);
+//!
+//! let uuid = id::uuid_v7();
+//! assert_eq!(uuid.get_version_num(), 7);
+//!
+//! let snowflake = Snowflake::new(1).unwrap();
+//! let (a, b) = (snowflake.next_id(), snowflake.next_id());
+//! assert!(a < b);
+//! assert_eq!(snowflake.decompose(a).node_id, 1);
+//! ```
+
+use crate::helpers::env;
+use crate::invalid;
+use crate::prelude::AppResult;
+use chrono::Utc;
+use std::sync::Mutex;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Crockford's base32 alphabet, used by ULID
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The 80 random bits of a ULID
+const ULID_RANDOM_MASK: u128 = (1u128 << 80) - 1;
+
+/// Base62 alphabet in ASCII order, so encoded strings sort like the numbers they encode
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Width of [`encode_sortable`] output, enough for `u64::MAX`
+const SORTABLE_WIDTH: usize = 11;
+
+/// Default snowflake epoch: 2024-01-01T00:00:00Z in milliseconds
+pub const DEFAULT_SNOWFLAKE_EPOCH: u64 = 1_704_067_200_000;
+
+const NODE_BITS: u64 = 10;
+const SEQUENCE_BITS: u64 = 12;
+const MAX_NODE_ID: u16 = (1 << NODE_BITS) - 1;
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+
+/// Last ULID timestamp and randomness handed out by [`ulid`]
+static ULID_STATE: Mutex<(u64, u128)> = Mutex::new((0, 0));
+
+/// Generates a ULID for the current time
+///
+/// ULIDs generated within the same millisecond in this process are monotonic: the
+/// randomness of the previous one is incremented, so they keep their creation order.
+pub fn ulid() -> String {
+    let mut state = ULID_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let (last_ms, last_random) = *state;
+
+    // never go backwards if the clock does (NTP adjustments)
+    let now = now_millis().max(last_ms);
+    let (now, random) = if now == last_ms {
+        match last_random
+            .checked_add(1)
+            .filter(|r| *r <= ULID_RANDOM_MASK)
+        {
+            Some(random) => (now, random),
+            None => (now + 1, ulid_random()),
+        }
+    } else {
+        (now, ulid_random())
+    };
+
+    *state = (now, random);
+    encode_ulid(now, random)
+}
+
+/// Generates a ULID for the given unix timestamp in milliseconds
+pub fn ulid_at(timestamp_ms: u64) -> String {
+    encode_ulid(timestamp_ms, ulid_random())
+}
+
+fn ulid_random() -> u128 {
+    u128::from_be_bytes(*Uuid::new_v4().as_bytes()) & ULID_RANDOM_MASK
+}
+
+fn encode_ulid(timestamp_ms: u64, random: u128) -> String {
+    let value = ((timestamp_ms as u128 & ((1u128 << 48) - 1)) << 80) | random;
+
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD[((value >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Extracts the unix timestamp in milliseconds from a ULID
+pub fn ulid_timestamp(ulid: &str) -> AppResult<u64> {
+    if ulid.len() != 26 {
+        return Err(invalid!("invalid ulid length: {}", ulid.len()));
+    }
+
+    let mut timestamp = 0u64;
+    // bytes rather than chars, a multi-byte char simply isn't a ULID digit
+    for byte in &ulid.as_bytes()[..10] {
+        let value = CROCKFORD
+            .iter()
+            .position(|a| *a == byte.to_ascii_uppercase())
+            .ok_or_else(|| invalid!("invalid ulid character: {}", byte.escape_ascii()))?;
+        timestamp = (timestamp << 5) | value as u64;
+    }
+
+    Ok(timestamp)
+}
+
+/// Generates a UUIDv7 (unix timestamp based, sortable)
+pub fn uuid_v7() -> Uuid {
+    Uuid::now_v7()
+}
+
+/// Generates a UUIDv7 in its hyphenated string form
+pub fn uuid_v7_string() -> String {
+    uuid_v7().to_string()
+}
+
+/// Encodes a number as a fixed-width base62 string whose lexicographic order
+/// matches numeric order, handy for compact sortable keys (e.g. snowflake ids in URLs).
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::id::{decode_sortable, encode_sortable};
+///
+/// let (a, b) = (encode_sortable(61), encode_sortable(62));
+/// assert_eq!(a, "0000000000z");
+/// assert_eq!(b, "00000000010");
+/// assert!(a < b);
+/// assert_eq!(decode_sortable(&b).unwrap(), 62);
+/// ```
+pub fn encode_sortable(mut value: u64) -> String {
+    let mut out = [b'0'; SORTABLE_WIDTH];
+    for slot in out.iter_mut().rev() {
+        *slot = BASE62[(value % 62) as usize];
+        value /= 62;
+    }
+
+    out.iter().map(|b| *b as char).collect()
+}
+
+/// Decodes a string produced by [`encode_sortable`]
+pub fn decode_sortable(encoded: &str) -> AppResult<u64> {
+    encoded.chars().try_fold(0u64, |acc, c| {
+        let digit = BASE62
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or_else(|| invalid!("invalid sortable id character: {c}"))?;
+
+        acc.checked_mul(62)
+            .and_then(|v| v.checked_add(digit as u64))
+            .ok_or_else(|| invalid!("sortable id overflows u64"))
+    })
+}
+
+/// The components of a snowflake id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnowflakeParts {
+    /// unix timestamp in milliseconds
+    pub timestamp: u64,
+    pub node_id: u16,
+    pub sequence: u16,
+}
+
+/// Generates 64-bit, roughly time-ordered ids: 41 bits of milliseconds since the epoch,
+/// 10 bits of node id and 12 bits of sequence (4096 ids per millisecond per node).
+///
+/// Each process (or pod) must use a distinct node id, typically provided through the environment.
+#[derive(Debug)]
+pub struct Snowflake {
+    epoch: u64,
+    node_id: u16,
+    state: Mutex<(u64, u64)>,
+}
+
+impl Snowflake {
+    /// Creates a generator for the given node id (0 - 1023)
+    pub fn new(node_id: u16) -> AppResult<Self> {
+        if node_id > MAX_NODE_ID {
+            return Err(invalid!(
+                "snowflake node id must be between 0 and {MAX_NODE_ID}, got {node_id}"
+            ));
+        }
+
+        Ok(Self {
+            epoch: DEFAULT_SNOWFLAKE_EPOCH,
+            node_id,
+            state: Mutex::new((0, 0)),
+        })
+    }
+
+    /// Creates a generator reading the node id from the `{env_prefix}_NODE_ID` variable
+    pub fn from_env(env_prefix: &str) -> AppResult<Self> {
+        Self::from_env_in(&env::process_env, env_prefix)
+    }
+
+    fn from_env_in(source: env::Source, env_prefix: &str) -> AppResult<Self> {
+        let node_id = env::var_in(source, env_prefix, "NODE_ID")?;
+        let node_id = node_id
+            .trim()
+            .parse::<u16>()
+            .map_err(|e| invalid!("invalid snowflake node id '{node_id}': {e}"))?;
+
+        Self::new(node_id)
+    }
+
+    /// Uses a custom epoch (unix milliseconds), must not change once ids are issued
+    pub fn with_epoch(mut self, epoch_ms: u64) -> Self {
+        self.epoch = epoch_ms;
+        self
+    }
+
+    pub fn node_id(&self) -> u16 {
+        self.node_id
+    }
+
+    /// Returns the next id, waiting for the next millisecond when the sequence is exhausted
+    ///
+    /// After the clock steps back, ids keep using the last timestamp until it catches up,
+    /// sleeping without holding the generator's lock if the sequence runs out meanwhile.
+    pub fn next_id(&self) -> i64 {
+        loop {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let (last_ms, sequence) = *state;
+
+            // never go backwards if the clock does (NTP adjustments)
+            let clock = now_millis().saturating_sub(self.epoch);
+            let now = clock.max(last_ms);
+
+            let sequence = match now == last_ms {
+                true => (sequence + 1) & MAX_SEQUENCE,
+                false => 0,
+            };
+
+            if now == last_ms && sequence == 0 {
+                drop(state);
+                std::thread::sleep(Duration::from_millis(last_ms + 1 - clock));
+                continue;
+            }
+
+            *state = (now, sequence);
+
+            return ((now << (NODE_BITS + SEQUENCE_BITS))
+                | ((self.node_id as u64) << SEQUENCE_BITS)
+                | sequence) as i64;
+        }
+    }
+
+    /// Splits an id into its timestamp, node id and sequence
+    pub fn decompose(&self, id: i64) -> SnowflakeParts {
+        let id = id as u64;
+        SnowflakeParts {
+            timestamp: (id >> (NODE_BITS + SEQUENCE_BITS)) + self.epoch,
+            node_id: ((id >> SEQUENCE_BITS) & MAX_NODE_ID as u64) as u16,
+            sequence: (id & MAX_SEQUENCE) as u16,
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    Utc::now().timestamp_millis().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_ulid_format_and_timestamp() {
+        let id = ulid_at(1_469_918_176_385);
+        assert_eq!(id.len(), 26);
+        assert!(id.starts_with("01ARYZ6S41"));
+        assert_eq!(ulid_timestamp(&id).unwrap(), 1_469_918_176_385);
+        assert!(ulid_timestamp("short").is_err());
+
+        // 26 bytes, with a two-byte char across byte 10
+        let multibyte = format!("01ARYZ6S4é{}", "0".repeat(15));
+        assert_eq!(multibyte.len(), 26);
+        assert!(ulid_timestamp(&multibyte).is_err());
+    }
+
+    #[test]
+    fn test_ulid_sorts_by_time() {
+        assert!(ulid_at(1_000) < ulid_at(2_000));
+    }
+
+    #[test]
+    fn test_ulid_is_monotonic_within_a_millisecond() {
+        let ids: Vec<String> = (0..10_000).map(|_| ulid()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_uuid_v7() {
+        let (a, b) = (uuid_v7(), uuid_v7());
+        assert_eq!(a.get_version_num(), 7);
+        assert!(a.to_string() <= b.to_string());
+    }
+
+    #[test]
+    fn test_sortable_encoding() {
+        for value in [0, 1, 61, 62, 3843, u32::MAX as u64, u64::MAX] {
+            let encoded = encode_sortable(value);
+            assert_eq!(encoded.len(), SORTABLE_WIDTH);
+            assert_eq!(decode_sortable(&encoded).unwrap(), value);
+        }
+
+        assert!(encode_sortable(999) < encode_sortable(1000));
+        assert!(decode_sortable("!!").is_err());
+    }
+
+    #[test]
+    fn test_snowflake_uniqueness_and_order() {
+        let generator = Snowflake::new(42).unwrap();
+        let ids: Vec<i64> = (0..10_000).map(|_| generator.next_id()).collect();
+
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+
+        let parts = generator.decompose(ids[0]);
+        assert_eq!(parts.node_id, 42);
+        assert!(parts.timestamp <= now_millis());
+    }
+
+    #[test]
+    fn test_snowflake_waits_for_the_clock_after_stepping_back() {
+        let generator = Snowflake::new(1).unwrap();
+        let ahead = now_millis() - DEFAULT_SNOWFLAKE_EPOCH + 20;
+        *generator.state.lock().unwrap() = (ahead, MAX_SEQUENCE);
+
+        let id = generator.next_id();
+        let parts = generator.decompose(id);
+        assert!(parts.timestamp > ahead + DEFAULT_SNOWFLAKE_EPOCH);
+        assert_eq!(parts.sequence, 0);
+    }
+
+    #[test]
+    fn test_snowflake_node_bounds() {
+        assert!(Snowflake::new(1023).is_ok());
+        assert!(Snowflake::new(1024).is_err());
+    }
+
+    #[test]
+    fn test_snowflake_from_env() {
+        // an environment of its own, tests run in parallel with the process one
+        let source = |key: &str| match key {
+            "SNOWFLAKE_TEST_NODE_ID" => Ok(" 7 ".to_string()),
+            "SNOWFLAKE_BAD_NODE_ID" => Ok("seven".to_string()),
+            _ => Err(std::env::VarError::NotPresent),
+        };
+
+        let generator = Snowflake::from_env_in(&source, "SNOWFLAKE_TEST").unwrap();
+        assert_eq!(generator.node_id(), 7);
+        assert!(Snowflake::from_env_in(&source, "SNOWFLAKE_BAD").is_err());
+        assert!(Snowflake::from_env_in(&source, "SNOWFLAKE_MISSING").is_err());
+    }
+}
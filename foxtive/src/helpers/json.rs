@@ -1,4 +1,8 @@
+use crate::invalid;
+use crate::results::AppResult;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,3 +44,340 @@ impl<T: Serialize> Display for JsonResponse<T> {
         f.write_str(serde_json::to_string(self).unwrap().as_str())
     }
 }
+
+/// Value at `path`, either a JSON Pointer (RFC 6901), `/items/0/name`, or a dotted path,
+/// `items.0.name` or `items[0].name`. An empty path is the value itself.
+///
+/// # Examples
+///
+/// ```rust
+/// use foxtive::helpers::json::get_path;
+/// use serde_json::json;
+///
+/// let payload = json!({"data": {"items": [{"id": 7}]}});
+///
+/// assert_eq!(get_path(&payload, "/data/items/0/id"), Some(&json!(7)));
+/// assert_eq!(get_path(&payload, "data.items[0].id"), Some(&json!(7)));
+/// assert_eq!(get_path(&payload, "data.missing"), None);
+/// ```
+pub fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    match path.starts_with('/') || path.is_empty() {
+        true => value.pointer(path),
+        false => value.pointer(&dotted_to_pointer(path)),
+    }
+}
+
+/// Mutable [`get_path`]
+pub fn get_path_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    match path.starts_with('/') || path.is_empty() {
+        true => value.pointer_mut(path),
+        false => value.pointer_mut(&dotted_to_pointer(path)),
+    }
+}
+
+/// Value at `path`, see [`get_path`], deserialized into `T`
+///
+/// # Errors
+///
+/// Returns an error if there is no value at `path` or if it isn't a `T`
+pub fn get_path_as<T: DeserializeOwned>(value: &Value, path: &str) -> AppResult<T> {
+    let found = get_path(value, path).ok_or_else(|| invalid!("No JSON value at '{path}'"))?;
+
+    T::deserialize(found).map_err(|err| invalid!("Invalid JSON value at '{path}': {err}"))
+}
+
+/// Applies a JSON Merge Patch (RFC 7386): objects are merged recursively,
+/// `null` removes a member and any other value replaces the target.
+///
+/// # Examples
+///
+/// ```rust
+/// use foxtive::helpers::json::merge_patch;
+/// use serde_json::json;
+///
+/// let mut user = json!({"name": "Jane", "address": {"city": "Lagos", "zip": "100001"}});
+/// merge_patch(&mut user, &json!({"address": {"zip": null}, "age": 30}));
+///
+/// assert_eq!(user, json!({"name": "Jane", "address": {"city": "Lagos"}, "age": 30}));
+/// ```
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            match value {
+                Value::Null => {
+                    target.remove(key);
+                }
+                value => merge_patch(target.entry(key).or_insert(Value::Null), value),
+            }
+        }
+    }
+}
+
+/// Merge patch turning `from` into `to`, the reverse of [`merge_patch`].
+///
+/// Merge patches can't set a member to `null`, such members are removed once the patch is applied.
+pub fn create_merge_patch(from: &Value, to: &Value) -> Value {
+    let (Value::Object(from), Value::Object(to)) = (from, to) else {
+        return to.clone();
+    };
+
+    let mut patch = Map::new();
+
+    for key in from.keys().filter(|key| !to.contains_key(*key)) {
+        patch.insert(key.clone(), Value::Null);
+    }
+
+    for (key, value) in to {
+        match from.get(key) {
+            Some(previous) if previous == value => {}
+            Some(previous) => {
+                patch.insert(key.clone(), create_merge_patch(previous, value));
+            }
+            None => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    Value::Object(patch)
+}
+
+/// Difference between two JSON values, found by [`diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonChange {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        from: Value,
+        to: Value,
+    },
+}
+
+impl JsonChange {
+    /// JSON Pointer of the changed value
+    pub fn path(&self) -> &str {
+        match self {
+            JsonChange::Added { path, .. }
+            | JsonChange::Removed { path, .. }
+            | JsonChange::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// Changes turning `from` into `to`, objects and arrays are compared member by member,
+/// array elements by index.
+///
+/// # Examples
+///
+/// ```rust
+/// use foxtive::helpers::json::{diff, JsonChange};
+/// use serde_json::json;
+///
+/// let changes = diff(&json!({"status": "pending", "tags": ["a"]}), &json!({"status": "paid", "tags": ["a", "b"]}));
+///
+/// assert_eq!(changes, [
+///     JsonChange::Changed { path: "/status".into(), from: json!("pending"), to: json!("paid") },
+///     JsonChange::Added { path: "/tags/1".into(), value: json!("b") },
+/// ]);
+/// ```
+pub fn diff(from: &Value, to: &Value) -> Vec<JsonChange> {
+    let mut changes = vec![];
+    diff_at(String::new(), from, to, &mut changes);
+    changes
+}
+
+fn diff_at(path: String, from: &Value, to: &Value, changes: &mut Vec<JsonChange>) {
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            for (key, value) in from {
+                let path = format!("{path}/{}", escape_pointer_token(key));
+                match to.get(key) {
+                    Some(other) => diff_at(path, value, other, changes),
+                    None => changes.push(JsonChange::Removed {
+                        path,
+                        value: value.clone(),
+                    }),
+                }
+            }
+
+            for (key, value) in to.iter().filter(|(key, _)| !from.contains_key(*key)) {
+                changes.push(JsonChange::Added {
+                    path: format!("{path}/{}", escape_pointer_token(key)),
+                    value: value.clone(),
+                });
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for index in 0..from.len().max(to.len()) {
+                let path = format!("{path}/{index}");
+                match (from.get(index), to.get(index)) {
+                    (Some(value), Some(other)) => diff_at(path, value, other, changes),
+                    (Some(value), None) => changes.push(JsonChange::Removed {
+                        path,
+                        value: value.clone(),
+                    }),
+                    (None, Some(value)) => changes.push(JsonChange::Added {
+                        path,
+                        value: value.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (from, to) if from != to => changes.push(JsonChange::Changed {
+            path,
+            from: from.clone(),
+            to: to.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// `items[0].name` → `/items/0/name`
+fn dotted_to_pointer(path: &str) -> String {
+    path.replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .fold(String::new(), |pointer, segment| {
+            format!("{pointer}/{}", escape_pointer_token(segment))
+        })
+}
+
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_get_path() {
+        let payload = json!({"a/b": {"c~d": [1, {"e": true}]}, "list": [[0, 5]]});
+
+        assert_eq!(get_path(&payload, ""), Some(&payload));
+        assert_eq!(get_path(&payload, "/a~1b/c~0d/1/e"), Some(&json!(true)));
+        assert_eq!(get_path(&payload, "a/b.c~d[1].e"), Some(&json!(true)));
+        assert_eq!(get_path(&payload, "list[0][1]"), Some(&json!(5)));
+        assert_eq!(get_path(&payload, "list.0.9"), None);
+
+        let mut payload = payload;
+        *get_path_mut(&mut payload, "list[0][1]").unwrap() = json!(6);
+        assert_eq!(get_path_as::<Vec<u8>>(&payload, "list.0").unwrap(), [0, 6]);
+        assert!(get_path_as::<String>(&payload, "list.0").is_err());
+        assert!(get_path_as::<String>(&payload, "missing").is_err());
+    }
+
+    #[test]
+    fn test_merge_patch_rfc_examples() {
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a": "foo"}), json!("bar"), json!("bar")),
+            (
+                json!({"e": null}),
+                json!({"a": 1}),
+                json!({"e": null, "a": 1}),
+            ),
+            (
+                json!([1, 2]),
+                json!({"a": "b", "c": null}),
+                json!({"a": "b"}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ];
+
+        for (target, patch, expected) in cases {
+            let mut result = target.clone();
+            merge_patch(&mut result, &patch);
+            assert_eq!(result, expected, "{target} + {patch}");
+        }
+    }
+
+    #[test]
+    fn test_create_merge_patch_round_trips() {
+        let from = json!({"name": "Jane", "address": {"city": "Lagos", "zip": "1"}, "tags": [1]});
+        let to = json!({"name": "Jane", "address": {"city": "Abuja"}, "tags": [1, 2], "age": 30});
+
+        let patch = create_merge_patch(&from, &to);
+        assert_eq!(
+            patch,
+            json!({"address": {"city": "Abuja", "zip": null}, "tags": [1, 2], "age": 30})
+        );
+
+        let mut result = from.clone();
+        merge_patch(&mut result, &patch);
+        assert_eq!(result, to);
+    }
+
+    #[test]
+    fn test_diff() {
+        let from = json!({"a": 1, "b": {"c": [1, 2]}, "gone": true});
+        let to = json!({"a": 1, "b": {"c": [1]}, "new/key": null});
+
+        let changes = diff(&from, &to);
+        assert_eq!(
+            changes,
+            [
+                JsonChange::Removed {
+                    path: "/b/c/1".into(),
+                    value: json!(2)
+                },
+                JsonChange::Removed {
+                    path: "/gone".into(),
+                    value: json!(true)
+                },
+                JsonChange::Added {
+                    path: "/new~1key".into(),
+                    value: json!(null)
+                },
+            ]
+        );
+        assert_eq!(changes[2].path(), "/new~1key");
+
+        assert!(diff(&from, &from).is_empty());
+        assert_eq!(
+            diff(&json!(1), &json!("1")),
+            [JsonChange::Changed {
+                path: String::new(),
+                from: json!(1),
+                to: json!("1")
+            }]
+        );
+    }
+}
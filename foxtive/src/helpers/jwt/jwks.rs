@@ -0,0 +1,398 @@
+use super::JwtKeyResolver;
+use crate::helpers::reqwest::HttpClient;
+use crate::prelude::AppResult;
+use chrono::Utc;
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::decode_header;
+use jsonwebtoken::jwk::JwkSet;
+use reqwest::Method;
+use reqwest::header::{CACHE_CONTROL, HeaderMap};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::debug;
+
+#[cfg(feature = "cache")]
+use crate::cache::Cache;
+#[cfg(feature = "cache")]
+use std::sync::Arc;
+#[cfg(feature = "cache")]
+use tracing::warn;
+
+/// Fetches and caches the JSON Web Key Set published by an identity provider
+/// (Auth0, Keycloak, Cognito...), so tokens it issues can be verified by kid.
+///
+/// Keys are kept in memory and, when a [`Cache`] is attached, shared through
+/// it so that multiple instances don't all hit the JWKS endpoint. Fetched keys
+/// stay fresh for the endpoint's `Cache-Control: max-age`, or the configured
+/// [`ttl`](Self::ttl) when it doesn't send one.
+///
+/// # Examples
+///
+/// ```no_run
+/// use foxtive::helpers::jwt::{Algorithm, Jwt, JwksProvider, Validation};
+/// use serde_json::Value;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// # async fn example(token: &str) -> foxtive::prelude::AppResult<()> {
+/// let jwks = Arc::new(
+///     JwksProvider::new("https://tenant.auth0.com/.well-known/jwks.json")?
+///         .ttl(Duration::from_secs(3600)),
+/// );
+///
+/// let jwt = Jwt::verifier(Algorithm::RS256, jwks.clone());
+/// let validation = Validation::new(Algorithm::RS256);
+/// let data = jwt.verify_with_jwks::<Value>(token, &validation, &jwks).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct JwksProvider {
+    url: String,
+    ttl: Duration,
+    min_refresh_interval: Duration,
+    client: HttpClient,
+    #[cfg(feature = "cache")]
+    cache: Option<Arc<Cache>>,
+    keys: RwLock<Option<CachedJwks>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CachedJwks {
+    /// unix timestamp of when the keys were fetched
+    fetched_at: i64,
+    /// seconds the keys stay fresh, entries cached without it are stale
+    #[serde(default)]
+    max_age: u64,
+    keys: JwkSet,
+}
+
+impl JwksProvider {
+    /// Creates a provider for the given JWKS url, with a one hour TTL
+    pub fn new(url: impl Into<String>) -> AppResult<Self> {
+        Ok(Self {
+            url: url.into(),
+            ttl: Duration::from_secs(3600),
+            min_refresh_interval: Duration::from_secs(30),
+            client: HttpClient::builder()
+                .timeout(Duration::from_secs(10))
+                .build()?,
+            #[cfg(feature = "cache")]
+            cache: None,
+            keys: RwLock::new(None),
+        })
+    }
+
+    /// How long fetched keys are considered fresh when the endpoint doesn't
+    /// send a `Cache-Control: max-age`
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Minimum time between forced refreshes triggered by unknown key ids
+    pub fn min_refresh_interval(mut self, interval: Duration) -> Self {
+        self.min_refresh_interval = interval;
+        self
+    }
+
+    /// Uses a custom http client (e.g. with retries or a proxy)
+    pub fn client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Shares fetched keys through the given cache
+    #[cfg(feature = "cache")]
+    pub fn cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Returns the JWKS url
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Replaces the in-memory key set, useful for preloading or testing
+    pub fn set_keys(&self, keys: JwkSet) {
+        self.store(CachedJwks {
+            fetched_at: Utc::now().timestamp(),
+            max_age: self.ttl.as_secs(),
+            keys,
+        });
+    }
+
+    /// Returns the in-memory key set, if loaded
+    pub fn keys(&self) -> Option<JwkSet> {
+        self.read().map(|cached| cached.keys)
+    }
+
+    /// Whether the in-memory key set is missing or older than its max age
+    pub fn is_stale(&self) -> bool {
+        self.read().is_none_or(|cached| cached.is_stale())
+    }
+
+    /// Loads the key set from the cache, or from the endpoint when the cache is empty or stale
+    pub async fn load(&self) -> AppResult<()> {
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache {
+            match cache.get::<CachedJwks>(&self.cache_key()).await {
+                Ok(Some(cached)) if !cached.is_stale() => {
+                    self.store(cached);
+                    return Ok(());
+                }
+                Ok(_) => {}
+                Err(err) => warn!("[jwks] failed to read cached keys: {err:?}"),
+            }
+        }
+
+        self.refresh().await
+    }
+
+    /// Fetches the key set from the endpoint regardless of freshness
+    pub async fn refresh(&self) -> AppResult<()> {
+        debug!("[jwks] fetching keys from {}", self.url);
+
+        let response = self.client.send(Method::GET, &self.url, None).await?;
+        let max_age = max_age(response.headers()).unwrap_or(self.ttl);
+        let cached = CachedJwks {
+            fetched_at: Utc::now().timestamp(),
+            max_age: max_age.as_secs(),
+            keys: serde_json::from_slice(&response.bytes().await?)?,
+        };
+
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache
+            && let Err(err) = cache.put(&self.cache_key(), &cached).await
+        {
+            warn!("[jwks] failed to cache keys: {err:?}");
+        }
+
+        self.store(cached);
+        Ok(())
+    }
+
+    /// Makes sure a key for the token's `kid` is available, loading stale keys
+    /// and refreshing once (rate limited) when the kid is unknown
+    pub async fn prepare(&self, token: &str) -> AppResult<()> {
+        if self.is_stale() {
+            self.load().await?;
+        }
+
+        let kid = decode_header(token)?.kid;
+        if let Some(kid) = kid
+            && self.keys().is_some_and(|keys| keys.find(&kid).is_none())
+            && self
+                .read()
+                .is_none_or(|cached| cached.age() >= self.min_refresh_interval.as_secs() as i64)
+        {
+            debug!("[jwks] unknown key id {kid}, refreshing");
+            self.refresh().await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cache")]
+    fn cache_key(&self) -> String {
+        format!("jwks:{}", self.url)
+    }
+
+    fn read(&self) -> Option<CachedJwks> {
+        self.keys.read().ok().and_then(|keys| keys.clone())
+    }
+
+    fn store(&self, cached: CachedJwks) {
+        if let Ok(mut keys) = self.keys.write() {
+            *keys = Some(cached);
+        }
+    }
+}
+
+impl CachedJwks {
+    /// seconds since the keys were fetched
+    fn age(&self) -> i64 {
+        Utc::now().timestamp() - self.fetched_at
+    }
+
+    fn is_stale(&self) -> bool {
+        self.age() >= self.max_age as i64
+    }
+}
+
+/// The `max-age` of a `Cache-Control` header, zero when the response must not be reused
+fn max_age(headers: &HeaderMap) -> Option<Duration> {
+    let cache_control = headers.get(CACHE_CONTROL)?.to_str().ok()?;
+    cache_control
+        .split(',')
+        .map(|directive| directive.trim().to_ascii_lowercase())
+        .find_map(|directive| match directive.as_str() {
+            "no-store" | "no-cache" => Some(Duration::ZERO),
+            _ => directive
+                .strip_prefix("max-age=")
+                .and_then(|secs| secs.trim_matches('"').parse().ok())
+                .map(Duration::from_secs),
+        })
+}
+
+impl JwtKeyResolver for JwksProvider {
+    fn resolve(&self, kid: Option<&str>) -> Option<DecodingKey> {
+        let keys = self.keys()?;
+        let jwk = match kid {
+            Some(kid) => keys.find(kid)?,
+            // without a kid we can only pick a key when there is no ambiguity
+            None if keys.keys.len() == 1 => &keys.keys[0],
+            None => return None,
+        };
+
+        DecodingKey::from_jwk(jwk).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::jwt::{Algorithm, Jwt, JwtTokenClaims, Validation};
+    use crate::helpers::time::current_timestamp;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const MODULUS: &str = "45YvzSUfRDq2wHp7kvgCn1wk6MVGyosLIf9nAutXNVA4DLDISAAO1C2nmZyCMX78xqkbM47WqsDLOD_WpreMz__EZffdQh5iZt0cwnBXLJvON3AOgGtKMsTP0tb211944V2tyBVsY_Dgvs3bJfv-Q3eZXYN5HnBW1m6jmK-9i_J3gdiZUlfiOUxRJRz-UMwCxi--zN2FKrNJYS29SzJ3nWuZqgw4TqDlIohlS4NJj1WsLG8iG7TM5uTxvL8W31qRT_3bWu5NPDP64-7RIx7nh2LUuGyIocvnsRYleX123ziO_PhqiW-ieTkx3HO0Z4DJuTLqgAtKDr_-CWhE-ROQQQ";
+
+    fn key_set(kid: &str) -> JwkSet {
+        serde_json::from_value(serde_json::json!({
+            "keys": [{
+                "kty": "RSA",
+                "kid": kid,
+                "alg": "RS256",
+                "use": "sig",
+                "n": MODULUS,
+                "e": "AQAB"
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn signed_token(kid: &str) -> String {
+        let (public_key, private_key) = Jwt::dummy_keys();
+        let claims = JwtTokenClaims {
+            sub: "user".to_string(),
+            iat: current_timestamp() as usize,
+            exp: (current_timestamp() + 300) as usize,
+            iss: "issuer".to_string(),
+            aud: "api".to_string(),
+            jti: "1".to_string(),
+        };
+
+        Jwt::new(public_key, private_key, 5)
+            .kid(kid)
+            .generate(claims)
+            .unwrap()
+            .access_token
+    }
+
+    fn validation() -> Validation {
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&["api"]);
+        validation
+    }
+
+    async fn serve_once(body: String, cache_control: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncache-control: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                cache_control,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        format!("http://{addr}/jwks.json")
+    }
+
+    #[test]
+    fn test_resolver_verifies_by_kid() {
+        let jwks = Arc::new(JwksProvider::new("http://localhost/jwks.json").unwrap());
+        jwks.set_keys(key_set("key-1"));
+
+        let jwt = Jwt::verifier(Algorithm::RS256, jwks);
+        let data = jwt
+            .verify::<JwtTokenClaims>(&signed_token("key-1"), &validation())
+            .unwrap();
+        assert_eq!(data.claims.sub, "user");
+
+        assert!(
+            jwt.verify::<JwtTokenClaims>(&signed_token("key-2"), &validation())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let jwks = JwksProvider::new("http://localhost/jwks.json")
+            .unwrap()
+            .ttl(Duration::from_secs(60));
+        assert!(jwks.is_stale());
+
+        jwks.set_keys(key_set("key-1"));
+        assert!(!jwks.is_stale());
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_jwks_fetches_keys() {
+        let body = serde_json::to_string(&key_set("remote")).unwrap();
+        let url = serve_once(body, "public").await;
+
+        let jwks = Arc::new(JwksProvider::new(url).unwrap());
+        let jwt = Jwt::verifier(Algorithm::RS256, jwks.clone());
+
+        let data = jwt
+            .verify_with_jwks::<JwtTokenClaims>(&signed_token("remote"), &validation(), &jwks)
+            .await
+            .unwrap();
+
+        assert_eq!(data.claims.aud, "api");
+        assert!(jwks.keys().unwrap().find("remote").is_some());
+    }
+
+    #[test]
+    fn test_max_age() {
+        let headers = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CACHE_CONTROL, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(
+            max_age(&headers("public, max-age=600, must-revalidate")),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(max_age(&headers("no-store")), Some(Duration::ZERO));
+        assert_eq!(max_age(&headers("public")), None);
+        assert_eq!(max_age(&HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_honours_max_age() {
+        let body = serde_json::to_string(&key_set("remote")).unwrap();
+
+        let url = serve_once(body.clone(), "max-age=0").await;
+        let jwks = JwksProvider::new(url).unwrap();
+        jwks.refresh().await.unwrap();
+        assert!(jwks.is_stale());
+
+        let url = serve_once(body, "max-age=600").await;
+        let jwks = JwksProvider::new(url).unwrap().ttl(Duration::from_secs(0));
+        jwks.refresh().await.unwrap();
+        assert!(!jwks.is_stale());
+    }
+}
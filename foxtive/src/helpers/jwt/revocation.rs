@@ -0,0 +1,134 @@
+use crate::prelude::AppResult;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Holds the ids (`jti`) of revoked tokens until the tokens expire by themselves
+///
+/// Set on a [`Jwt`](super::Jwt) with [`revocation_store`](super::Jwt::revocation_store),
+/// tokens are revoked by [`Jwt::revoke`](super::Jwt::revoke) and rejected by every
+/// verification. The sync ones, [`Jwt::verify`](super::Jwt::verify) and the like, rely on
+/// [`is_revoked_now`](RevocationStore::is_revoked_now) and fail with stores that can
+/// only answer asynchronously, [`Jwt::verify_not_revoked`](super::Jwt::verify_not_revoked)
+/// works with any store.
+#[async_trait::async_trait]
+pub trait RevocationStore: Send + Sync {
+    /// Marks `jti` revoked for `ttl`, the remaining lifetime of its token
+    async fn revoke(&self, jti: &str, ttl: Duration) -> AppResult<()>;
+
+    /// Whether `jti` was revoked
+    async fn is_revoked(&self, jti: &str) -> AppResult<bool>;
+
+    /// Whether `jti` was revoked, answered without waiting on any I/O,
+    /// `None` when the store can only be queried asynchronously
+    fn is_revoked_now(&self, _jti: &str) -> Option<AppResult<bool>> {
+        None
+    }
+}
+
+/// Keeps the revoked ids in memory, for a single instance or tests
+#[derive(Default)]
+pub struct InMemoryRevocationStore {
+    revoked: Mutex<HashMap<String, Instant>>,
+}
+
+impl InMemoryRevocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl RevocationStore for InMemoryRevocationStore {
+    async fn revoke(&self, jti: &str, ttl: Duration) -> AppResult<()> {
+        let now = Instant::now();
+        let mut revoked = self.revoked.lock().unwrap_or_else(|err| err.into_inner());
+        revoked.retain(|_, until| *until > now);
+        revoked.insert(jti.to_string(), now + ttl);
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &str) -> AppResult<bool> {
+        self.is_revoked_now(jti).unwrap_or(Ok(false))
+    }
+
+    fn is_revoked_now(&self, jti: &str) -> Option<AppResult<bool>> {
+        let revoked = self.revoked.lock().unwrap_or_else(|err| err.into_inner());
+        Some(Ok(revoked
+            .get(jti)
+            .is_some_and(|until| *until > Instant::now())))
+    }
+}
+
+/// Keeps the revoked ids in a [`Cache`](crate::cache::Cache), under `jwt:revoked:<jti>`
+///
+/// Entries hold their expiry, an expired entry is ignored and removed when read.
+#[cfg(feature = "cache")]
+#[derive(Clone)]
+pub struct CacheRevocationStore {
+    cache: crate::cache::Cache,
+}
+
+#[cfg(feature = "cache")]
+impl CacheRevocationStore {
+    pub fn new(cache: crate::cache::Cache) -> Self {
+        Self { cache }
+    }
+}
+
+#[cfg(feature = "cache")]
+#[async_trait::async_trait]
+impl RevocationStore for CacheRevocationStore {
+    async fn revoke(&self, jti: &str, ttl: Duration) -> AppResult<()> {
+        let until = crate::helpers::time::current_timestamp() + ttl.as_secs();
+        self.cache.put(&revoked_key(jti), &until).await?;
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &str) -> AppResult<bool> {
+        let key = revoked_key(jti);
+        match self.cache.get::<u64>(&key).await? {
+            Some(until) if until > crate::helpers::time::current_timestamp() => Ok(true),
+            Some(_) => {
+                self.cache.forget(&key).await?;
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Keeps the revoked ids in Redis, under `jwt:revoked:<jti>` expiring with their tokens
+#[cfg(feature = "redis")]
+pub struct RedisRevocationStore {
+    redis: crate::redis::Redis,
+}
+
+#[cfg(feature = "redis")]
+impl RedisRevocationStore {
+    pub fn new(redis: crate::redis::Redis) -> Self {
+        Self { redis }
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait::async_trait]
+impl RevocationStore for RedisRevocationStore {
+    async fn revoke(&self, jti: &str, ttl: Duration) -> AppResult<()> {
+        use redis::AsyncCommands;
+
+        let key = self.redis.key(&revoked_key(jti)).into_owned();
+        let mut conn = self.redis.redis().await?;
+        let _: () = conn.set_ex(key, 1, ttl.as_secs().max(1)).await?;
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &str) -> AppResult<bool> {
+        self.redis.exists(&revoked_key(jti)).await
+    }
+}
+
+#[cfg(any(feature = "cache", feature = "redis"))]
+fn revoked_key(jti: &str) -> String {
+    format!("jwt:revoked:{jti}")
+}
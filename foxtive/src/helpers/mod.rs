@@ -6,35 +6,54 @@
 //! ## Features
 //!
 //! The library supports the following optional features that can be enabled in your `Cargo.toml`:
+//! - `api-key`: Enables generating, hashing and verifying API keys
 //! - `base64`: Enables base64 encoding/decoding utilities
+//! - `checksum`: Enables streaming SHA-256/BLAKE3 checksums of files and directories
+//! - `cipher`: Enables authenticated symmetric encryption (AES-GCM / ChaCha20-Poly1305)
+//! - `csrf`: Enables session-bound CSRF tokens
+//! - `fs`: Enables atomic writes, copies with progress and directory sizes
 //! - `hmac`: Provides HMAC cryptographic functionality
 //! - `jwt`: Includes JSON Web Token handling
 //! - `crypto`: Enables password hashing and cryptographic functions
+//! - `money`: Enables currency-aware decimal arithmetic and formatting
 //! - `reqwest`: Provides HTTP client utilities
 //! - `regex`: Enables regular expression functionality and text cleaning utilities
+//! - `retry`: Enables retrying async operations with the supervisor's backoff strategies
+//! - `upload`: Enables validating and storing uploaded files
+//! - `totp`: Enables time-based one-time passwords for two-factor authentication
 //!
 //! ## Modules
 //!
 //! ### Always Available Modules
 //!
-//! * `form` - Form handling utilities
-//! * `fs` - File system operations
+//! * `form` - Form handling utilities, nested `a[b][0]` form keys parsing and encoding with the `http` feature
+//! * `fs` - File system operations, plus file and directory checksums with the `checksum` feature
+//!   and atomic writes, copies with progress and directory sizes with the `fs` feature
+//! * `id` - ULID, UUIDv7 and snowflake id generation
 //! * `json` - JSON processing utilities
-//! * `number` - Numeric type conversions and operations
-//! * `once_lock` - Thread-safe initialization primitives
+//! * `number` - Numeric type conversions and operations, ordinal, compact and locale-aware formatting
+//! * `once_lock` - Async lazy initialization with a retry policy, `AsyncOnce`
 //! * `string` - String manipulation utilities
 //! * `time` - Time and date handling functions
 //! * `blk` - Re-exported tokio blocking operations
 //!
 //! ### Feature-Gated Modules
 //!
+//! * `api_key` (requires `api-key` feature) - Prefixed API keys, hashed for storage
 //! * `base64` (requires `base64` feature) - Base64 encoding and decoding
+//! * `cipher` (requires `cipher` feature) - Reversible encryption with key rotation
+//! * `csrf` (requires `csrf` feature) - Signed CSRF tokens bound to session ids
 //! * `hmac` (requires `hmac` feature) - HMAC message authentication
 //! * `jwt` (requires `jwt` feature) - JSON Web Token operations
+//! * `money` (requires `money` feature) - Money arithmetic, rounding and formatting
 //! * `password` (requires `crypto` feature) - Password hashing and verification
 //! * `reqwest` (requires `reqwest` feature) - HTTP client utilities
+//! * `retry` (requires `retry` feature) - Retrying fallible async operations with backoff
+//! * `signer` (requires `hmac` feature) - Signed, expiring URLs and tokens
 //! * `regex` (requires `regex` feature) - Regular expression operations and validation
 //! * `text_cleaner` (requires `regex` feature) - Text cleaning and sanitization utilities
+//! * `totp` (requires `totp` feature) - TOTP codes, otpauth URIs and recovery codes
+//! * `upload` (requires `upload` feature) - Upload validation, type sniffing and storage
 //!
 //! ## Usage
 //!
@@ -83,23 +102,43 @@
 //!
 //! Many operations in this library support async/await syntax, particularly in the
 //! `reqwest` and file system operations. The library uses tokio as its async runtime.
+#[cfg(feature = "api-key")]
+pub mod api_key;
+#[cfg(any(feature = "storage-s3", feature = "secrets-aws"))]
+pub(crate) mod aws_sigv4;
 #[cfg(feature = "base64")]
 pub mod base64;
+#[cfg(feature = "cipher")]
+pub mod cipher;
+#[cfg(feature = "csrf")]
+pub mod csrf;
 pub mod form;
 pub mod fs;
 #[cfg(feature = "hmac")]
 pub mod hmac;
+pub mod id;
 pub mod json;
 #[cfg(feature = "jwt")]
 pub mod jwt;
+#[cfg(feature = "money")]
+pub mod money;
 pub mod number;
+pub mod once_lock;
 #[cfg(feature = "crypto")]
 pub mod password;
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
+#[cfg(feature = "retry")]
+pub mod retry;
+#[cfg(feature = "hmac")]
+pub mod signer;
 pub mod string;
 pub mod time;
 mod tokio;
+#[cfg(feature = "totp")]
+pub mod totp;
+#[cfg(feature = "upload")]
+pub mod upload;
 
 pub mod env;
 mod file_ext;
@@ -109,8 +148,8 @@ mod input_sanitizer;
 pub mod regex;
 pub mod serde_json;
 
-pub use tokio::{blk, block, run_async};
+pub use tokio::{blk, block, limit_blocking_tasks, run_async, try_blk};
 
-pub use file_ext::{COMPOUND_EXTENSIONS, FileExtHelper};
+pub use file_ext::{COMPOUND_EXTENSIONS, FileExtHelper, SniffedType};
 
 pub use input_sanitizer::*;
@@ -0,0 +1,583 @@
+//! Currency-aware money arithmetic and formatting on top of [`rust_decimal`].
+//!
+//! Floating point numbers can't represent most decimal fractions exactly, so amounts
+//! are held as [`Decimal`] together with their [`Currency`], which knows its symbol and
+//! number of minor units (cents, kobo...).
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::money::{Currency, Decimal, Money};
+//! use std::str::FromStr;
+//!
+//! let price = Money::from_minor(1_250_000, Currency::NGN).unwrap();
+//! assert_eq!(price.format(), "₦12,500.00");
+//!
+//! let vat = price.percentage(Decimal::from_str("7.5").unwrap()).unwrap();
+//! assert_eq!(vat.to_string(), "NGN 937.50");
+//!
+//! let total = price.checked_add(&vat).unwrap();
+//! assert_eq!(total.to_minor().unwrap(), 1_343_750);
+//! ```
+
+use crate::prelude::AppResult;
+use crate::{bad_request, invalid};
+use rust_decimal::prelude::ToPrimitive;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub use rust_decimal::{Decimal, RoundingStrategy};
+
+/// An ISO 4217 currency with its display symbol and number of minor units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency {
+    pub code: &'static str,
+    pub symbol: &'static str,
+    pub minor_units: u32,
+}
+
+impl Currency {
+    pub const USD: Currency = Currency::new("USD", "$", 2);
+    pub const EUR: Currency = Currency::new("EUR", "€", 2);
+    pub const GBP: Currency = Currency::new("GBP", "£", 2);
+    pub const NGN: Currency = Currency::new("NGN", "₦", 2);
+    pub const GHS: Currency = Currency::new("GHS", "GH₵", 2);
+    pub const KES: Currency = Currency::new("KES", "KSh", 2);
+    pub const ZAR: Currency = Currency::new("ZAR", "R", 2);
+    pub const CAD: Currency = Currency::new("CAD", "CA$", 2);
+    pub const JPY: Currency = Currency::new("JPY", "¥", 0);
+    pub const KWD: Currency = Currency::new("KWD", "KD", 3);
+
+    const KNOWN: [Currency; 10] = [
+        Self::USD,
+        Self::EUR,
+        Self::GBP,
+        Self::NGN,
+        Self::GHS,
+        Self::KES,
+        Self::ZAR,
+        Self::CAD,
+        Self::JPY,
+        Self::KWD,
+    ];
+
+    pub const fn new(code: &'static str, symbol: &'static str, minor_units: u32) -> Self {
+        Self {
+            code,
+            symbol,
+            minor_units,
+        }
+    }
+
+    /// Looks up one of the built-in currencies by its code (case-insensitive)
+    pub fn from_code(code: &str) -> Option<Currency> {
+        Self::KNOWN
+            .into_iter()
+            .find(|c| c.code.eq_ignore_ascii_case(code))
+    }
+}
+
+/// An amount of money in a given currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Money {
+    amount: Decimal,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Self { amount, currency }
+    }
+
+    /// Creates money from an amount in minor units, e.g. cents
+    ///
+    /// # Errors
+    ///
+    /// Fails when the currency has more minor units than a [`Decimal`] can hold (28).
+    pub fn from_minor(minor: i64, currency: Currency) -> AppResult<Self> {
+        Decimal::try_new(minor, currency.minor_units)
+            .map(|amount| Self::new(amount, currency))
+            .map_err(|_| invalid!("{} has too many minor units", currency.code))
+    }
+
+    /// Parses an amount such as `"1,234.50"` or `"$1,234.50"`
+    pub fn parse(amount: &str, currency: Currency) -> AppResult<Self> {
+        Ok(Self::new(parse_decimal(amount)?, currency))
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.amount.is_sign_negative() && !self.amount.is_zero()
+    }
+
+    /// Converts to minor units, rounding half away from zero
+    pub fn to_minor(&self) -> AppResult<i64> {
+        10i64
+            .checked_pow(self.currency.minor_units)
+            .and_then(|factor| self.round().amount.checked_mul(Decimal::from(factor)))
+            .and_then(|scaled| scaled.to_i64())
+            .ok_or_else(|| invalid!("amount {} does not fit in minor units", self.amount))
+    }
+
+    /// Rounds to the currency's minor units, half away from zero
+    pub fn round(&self) -> Self {
+        self.round_with(RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    /// Rounds to the currency's minor units with the given strategy
+    pub fn round_with(&self, strategy: RoundingStrategy) -> Self {
+        Self::new(
+            self.amount
+                .round_dp_with_strategy(self.currency.minor_units, strategy),
+            self.currency,
+        )
+    }
+
+    pub fn checked_add(&self, other: &Money) -> AppResult<Money> {
+        self.ensure_same_currency(other)?;
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or_else(|| invalid!("money addition overflowed"))?;
+        Ok(Self::new(amount, self.currency))
+    }
+
+    pub fn checked_sub(&self, other: &Money) -> AppResult<Money> {
+        self.ensure_same_currency(other)?;
+        let amount = self
+            .amount
+            .checked_sub(other.amount)
+            .ok_or_else(|| invalid!("money subtraction overflowed"))?;
+        Ok(Self::new(amount, self.currency))
+    }
+
+    /// Multiplies by a factor (quantity, exchange rate...), without rounding
+    pub fn multiply(&self, factor: Decimal) -> AppResult<Money> {
+        let amount = self
+            .amount
+            .checked_mul(factor)
+            .ok_or_else(|| invalid!("money multiplication overflowed"))?;
+        Ok(Self::new(amount, self.currency))
+    }
+
+    /// Returns `percent`% of this amount, rounded to minor units (e.g. `7.5` for VAT)
+    pub fn percentage(&self, percent: Decimal) -> AppResult<Money> {
+        Ok(self.multiply(percent / Decimal::ONE_HUNDRED)?.round())
+    }
+
+    /// Returns this amount increased by `percent`%
+    pub fn add_percentage(&self, percent: Decimal) -> AppResult<Money> {
+        self.checked_add(&self.percentage(percent)?)
+    }
+
+    /// Returns this amount reduced by `percent`%
+    pub fn discount(&self, percent: Decimal) -> AppResult<Money> {
+        self.checked_sub(&self.percentage(percent)?)
+    }
+
+    /// Splits the amount into `parts` shares that add up exactly to the (rounded) total,
+    /// distributing leftover minor units to the first shares
+    pub fn split(&self, parts: u32) -> AppResult<Vec<Money>> {
+        if parts == 0 {
+            return Err(bad_request!("cannot split money into zero parts"));
+        }
+
+        let total = self.to_minor()?;
+        let (share, remainder) = (total / parts as i64, total % parts as i64);
+
+        (0..parts as i64)
+            .map(|i| {
+                let extra = if i < remainder.abs() {
+                    remainder.signum()
+                } else {
+                    0
+                };
+                Self::from_minor(share + extra, self.currency)
+            })
+            .collect()
+    }
+
+    /// Formats with the currency symbol and thousand separators, e.g. `$1,234.50`
+    pub fn format(&self) -> String {
+        format!(
+            "{}{}{}",
+            if self.is_negative() { "-" } else { "" },
+            self.currency.symbol,
+            self.format_amount()
+        )
+    }
+
+    /// Formats the amount only, with thousand separators and the currency's minor units
+    pub fn format_amount(&self) -> String {
+        format_decimal(
+            self.round().amount.abs(),
+            self.currency.minor_units,
+            ",",
+            ".",
+        )
+    }
+
+    fn ensure_same_currency(&self, other: &Money) -> AppResult<()> {
+        match self.currency == other.currency {
+            true => Ok(()),
+            false => Err(bad_request!(
+                "currency mismatch: {} and {}",
+                self.currency.code,
+                other.currency.code
+            )),
+        }
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let amount = self.round().amount;
+        write!(
+            f,
+            "{} {:.prec$}",
+            self.currency.code,
+            amount,
+            prec = self.currency.minor_units as usize
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MoneyRepr {
+    amount: String,
+    currency: String,
+}
+
+/// Serialized as `{"amount": "12.50", "currency": "USD"}`, amounts as strings to keep precision
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MoneyRepr {
+            amount: self.amount.to_string(),
+            currency: self.currency.code.to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = MoneyRepr::deserialize(deserializer)?;
+        let currency = Currency::from_code(&repr.currency)
+            .ok_or_else(|| D::Error::custom(format!("unknown currency: {}", repr.currency)))?;
+        let amount = parse_decimal(&repr.amount).map_err(D::Error::custom)?;
+        Ok(Money::new(amount, currency))
+    }
+}
+
+/// Parses a decimal from a string, allowing a leading currency symbol and comma
+/// thousand separators. Anything else that isn't part of the number is rejected.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::money::parse_decimal;
+///
+/// assert_eq!(parse_decimal("$1,234.50").unwrap().to_string(), "1234.50");
+/// assert_eq!(parse_decimal("-₦ 12").unwrap().to_string(), "-12");
+/// assert!(parse_decimal("abc").is_err());
+/// assert!(parse_decimal("1a2").is_err());
+/// assert!(parse_decimal("12,34").is_err());
+/// ```
+pub fn parse_decimal(value: &str) -> AppResult<Decimal> {
+    let invalid = || bad_request!("invalid decimal amount: {value}");
+
+    let trimmed = value.trim();
+    let (sign, unsigned) = match trimmed.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+
+    // a currency symbol or code, e.g. `$`, `GH₵` or `KSh `
+    let number = unsigned
+        .trim_start_matches(|c: char| {
+            !c.is_ascii_digit() && !c.is_whitespace() && !matches!(c, '.' | ',' | '-' | '+')
+        })
+        .trim_start();
+
+    let (integer, rest) = number
+        .find(['.', 'e', 'E'])
+        .map_or((number, ""), |at| number.split_at(at));
+
+    if integer.contains(',') {
+        let mut groups = integer.split(',');
+        let first = groups.next().unwrap_or_default();
+        let grouped = (1..=3).contains(&first.len()) && groups.all(|group| group.len() == 3);
+        if !grouped {
+            return Err(invalid());
+        }
+    }
+
+    let cleaned = format!("{sign}{}{rest}", integer.replace(',', ""));
+    Decimal::from_str(&cleaned)
+        .or_else(|_| Decimal::from_scientific(&cleaned))
+        .map_err(|_| invalid())
+}
+
+/// Formats a decimal with the given number of decimal places and separators.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::money::{format_decimal, Decimal};
+///
+/// assert_eq!(format_decimal(Decimal::new(123456789, 2), 2, ",", "."), "1,234,567.89");
+/// assert_eq!(format_decimal(Decimal::new(123456789, 2), 2, ".", ","), "1.234.567,89");
+/// assert_eq!(format_decimal(Decimal::new(-5, 1), 0, ",", "."), "-1");
+/// ```
+pub fn format_decimal(
+    value: Decimal,
+    decimal_places: u32,
+    thousands_separator: &str,
+    decimal_separator: &str,
+) -> String {
+    let rounded =
+        value.round_dp_with_strategy(decimal_places, RoundingStrategy::MidpointAwayFromZero);
+    let formatted = format!("{:.prec$}", rounded.abs(), prec = decimal_places as usize);
+    let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let mut grouped = String::new();
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(thousands_separator);
+        }
+        grouped.push(c);
+    }
+
+    let sign = if rounded.is_sign_negative() && !rounded.is_zero() {
+        "-"
+    } else {
+        ""
+    };
+
+    match fraction.is_empty() {
+        true => format!("{sign}{grouped}"),
+        false => format!("{sign}{grouped}{decimal_separator}{fraction}"),
+    }
+}
+
+/// Deserializes a [`Decimal`] from a number or a string such as `"1,234.56"` or `"$12"`.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::money::{deserialize_decimal_from_any, Decimal};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Payment {
+///     #[serde(deserialize_with = "deserialize_decimal_from_any")]
+///     amount: Decimal,
+/// }
+///
+/// let payment: Payment = serde_json::from_str(r#"{"amount": "1,500.25"}"#).unwrap();
+/// assert_eq!(payment.amount.to_string(), "1500.25");
+/// ```
+pub fn deserialize_decimal_from_any<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Decimal, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Number(n) => parse_decimal(&n.to_string()).map_err(D::Error::custom),
+        Value::String(s) => parse_decimal(&s).map_err(D::Error::custom),
+        _ => Err(D::Error::custom("Expected number or string")),
+    }
+}
+
+/// Serializes a [`Decimal`] as a string, preserving precision for JSON consumers.
+pub fn serialize_decimal_as_string<S: Serializer>(
+    value: &Decimal,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+/// Deserializes a percentage (0-100 or 0.0-1.0, number or string with `%`) into a
+/// normalized [`Decimal`] between 0 and 1, mirroring
+/// [`deserialize_percentage_to_decimal`](crate::helpers::serde_json::deserialize_percentage_to_decimal).
+pub fn deserialize_percentage<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Decimal, D::Error> {
+    let num = match Value::deserialize(deserializer)? {
+        Value::Number(n) => parse_decimal(&n.to_string()).map_err(D::Error::custom)?,
+        Value::String(s) => {
+            parse_decimal(s.trim().trim_end_matches('%')).map_err(D::Error::custom)?
+        }
+        _ => return Err(D::Error::custom("Expected number or string")),
+    };
+
+    if num > Decimal::ONE && num <= Decimal::ONE_HUNDRED {
+        Ok(num / Decimal::ONE_HUNDRED)
+    } else if num >= Decimal::ZERO && num <= Decimal::ONE {
+        Ok(num)
+    } else {
+        Err(D::Error::custom(
+            "Percentage must be between 0-100 or 0.0-1.0",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_minor_units() {
+        let money = Money::from_minor(12345, Currency::USD).unwrap();
+        assert_eq!(money.amount(), dec("123.45"));
+        assert_eq!(money.to_minor().unwrap(), 12345);
+
+        let yen = Money::from_minor(500, Currency::JPY).unwrap();
+        assert_eq!(yen.format(), "¥500");
+
+        let too_precise = Currency::new("XTS", "T", 19);
+        assert!(Money::new(Decimal::ONE, too_precise).to_minor().is_err());
+        assert!(Money::from_minor(1, too_precise).is_ok());
+        assert!(Money::from_minor(1, Currency::new("XTS", "T", 29)).is_err());
+    }
+
+    #[test]
+    fn test_rounding_strategies() {
+        let money = Money::new(dec("2.345"), Currency::USD);
+        assert_eq!(money.round().amount(), dec("2.35"));
+        assert_eq!(
+            money
+                .round_with(RoundingStrategy::MidpointNearestEven)
+                .amount(),
+            dec("2.34")
+        );
+        assert_eq!(
+            money.round_with(RoundingStrategy::ToZero).amount(),
+            dec("2.34")
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_requires_same_currency() {
+        let usd = Money::from_minor(100, Currency::USD).unwrap();
+        let eur = Money::from_minor(100, Currency::EUR).unwrap();
+
+        assert!(usd.checked_add(&eur).is_err());
+        assert_eq!(usd.checked_sub(&usd).unwrap(), Money::zero(Currency::USD));
+    }
+
+    #[test]
+    fn test_percentages() {
+        let money = Money::from_minor(10000, Currency::USD).unwrap();
+        assert_eq!(money.percentage(dec("7.5")).unwrap().amount(), dec("7.50"));
+        assert_eq!(
+            money.add_percentage(dec("10")).unwrap().amount(),
+            dec("110.00")
+        );
+        assert_eq!(money.discount(dec("25")).unwrap().amount(), dec("75.00"));
+    }
+
+    #[test]
+    fn test_multiplication_overflow_is_an_error() {
+        let money = Money::new(Decimal::MAX, Currency::USD);
+        assert!(money.multiply(dec("2")).is_err());
+        assert!(money.percentage(dec("200")).is_err());
+        assert!(money.to_minor().is_err());
+    }
+
+    #[test]
+    fn test_parse_decimal_rejects_invalid_input() {
+        assert_eq!(parse_decimal(" GH₵1,000 ").unwrap(), dec("1000"));
+        assert_eq!(parse_decimal("KSh 12.5").unwrap(), dec("12.5"));
+        assert_eq!(parse_decimal("-$0.99").unwrap(), dec("-0.99"));
+        assert_eq!(parse_decimal("1.5e3").unwrap(), dec("1500"));
+
+        for value in ["1a2", "12$", "1 000", "1,00", ",100", "1-2", "--1", "", "$"] {
+            assert!(parse_decimal(value).is_err(), "{value} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_split_keeps_total() {
+        let shares = Money::from_minor(100, Currency::USD)
+            .unwrap()
+            .split(3)
+            .unwrap();
+        let minors: Vec<i64> = shares.iter().map(|m| m.to_minor().unwrap()).collect();
+        assert_eq!(minors, vec![34, 33, 33]);
+
+        let negative = Money::from_minor(-100, Currency::USD)
+            .unwrap()
+            .split(3)
+            .unwrap();
+        let minors: Vec<i64> = negative.iter().map(|m| m.to_minor().unwrap()).collect();
+        assert_eq!(minors, vec![-34, -33, -33]);
+
+        assert!(Money::zero(Currency::USD).split(0).is_err());
+    }
+
+    #[test]
+    fn test_formatting() {
+        assert_eq!(
+            Money::from_minor(123456789, Currency::USD)
+                .unwrap()
+                .format(),
+            "$1,234,567.89"
+        );
+        assert_eq!(
+            Money::from_minor(-5000, Currency::GBP).unwrap().format(),
+            "-£50.00"
+        );
+        assert_eq!(
+            Money::from_minor(1234, Currency::KWD).unwrap().to_string(),
+            "KWD 1.234"
+        );
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let money = Money::from_minor(1050, Currency::EUR).unwrap();
+        let json = serde_json::to_string(&money).unwrap();
+        assert_eq!(json, r#"{"amount":"10.50","currency":"EUR"}"#);
+
+        let back: Money = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, money);
+
+        assert!(serde_json::from_str::<Money>(r#"{"amount":"1","currency":"XXX"}"#).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_percentage() {
+        #[derive(Deserialize)]
+        struct Rate {
+            #[serde(deserialize_with = "deserialize_percentage")]
+            rate: Decimal,
+        }
+
+        let parse = |json: &str| serde_json::from_str::<Rate>(json).map(|r| r.rate);
+        assert_eq!(parse(r#"{"rate": 50}"#).unwrap(), dec("0.5"));
+        assert_eq!(parse(r#"{"rate": "12.5%"}"#).unwrap(), dec("0.125"));
+        assert_eq!(parse(r#"{"rate": 0.25}"#).unwrap(), dec("0.25"));
+        assert!(parse(r#"{"rate": 150}"#).is_err());
+    }
+}
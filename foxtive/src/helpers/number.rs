@@ -1,3 +1,8 @@
+use crate::bad_request;
+use crate::prelude::AppResult;
+use num_traits::ToPrimitive;
+use std::fmt::Display;
+
 /// Alias for `format_currency` - formats a number with commas and two decimal places.
 pub use format_currency as format_number;
 
@@ -128,6 +133,236 @@ pub fn format_integer<T: std::fmt::Display>(num: T) -> String {
     result
 }
 
+/// Integer types [`convert`] can convert to, naming their range in errors
+pub trait IntRange: Sized {
+    /// Name of the type, e.g. `u8`
+    fn type_name() -> &'static str;
+
+    /// Range of the type, e.g. `0..=255`
+    fn range() -> String;
+}
+
+macro_rules! int_range {
+    ($($ty:ident),*) => {
+        $(
+            impl IntRange for $ty {
+                fn type_name() -> &'static str {
+                    stringify!($ty)
+                }
+
+                fn range() -> String {
+                    format!("{}..={}", $ty::MIN, $ty::MAX)
+                }
+            }
+        )*
+    };
+}
+
+int_range!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+/// Converts between integer types, failing with an error naming the value and the
+/// target range instead of wrapping or truncating as `as` does.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::number::convert;
+///
+/// let quantity: u8 = convert(200_i64).unwrap();
+/// assert_eq!(quantity, 200);
+///
+/// let err = convert::<_, u8>(300_i64).unwrap_err();
+/// assert_eq!(err.to_string(), "300 is out of range for u8 (0..=255)");
+/// assert!(convert::<_, u32>(-1_i32).is_err());
+/// ```
+pub fn convert<T, U>(value: T) -> AppResult<U>
+where
+    T: TryInto<U> + Display + Copy,
+    U: IntRange,
+{
+    value.try_into().map_err(|_| {
+        bad_request!(
+            "{value} is out of range for {} ({})",
+            U::type_name(),
+            U::range()
+        )
+    })
+}
+
+/// Formats an integer as an ordinal, `1st`, `2nd`, `3rd`, `4th`, `11th`, `22nd`...
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::number::ordinal;
+///
+/// assert_eq!(ordinal(3), "3rd");
+/// assert_eq!(ordinal(12_u64), "12th");
+/// assert_eq!(ordinal(101), "101st");
+/// ```
+pub fn ordinal<T: Into<i128>>(num: T) -> String {
+    let num = num.into();
+    let suffix = match (num.unsigned_abs() % 10, num.unsigned_abs() % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{num}{suffix}")
+}
+
+/// Formats a number compactly with at most one decimal, `950`, `1.2k`, `3.4M`, `5B`,
+/// `1.1T`, for dashboards and reports where the exact value doesn't matter.
+///
+/// Accepts any primitive number, including `i64`, `u64` and `u128`, converted to `f64`.
+/// A value that can't be converted formats as `NaN`.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::number::format_compact;
+///
+/// assert_eq!(format_compact(950), "950");
+/// assert_eq!(format_compact(1_234), "1.2k");
+/// assert_eq!(format_compact(-3_400_000), "-3.4M");
+/// assert_eq!(format_compact(999_999), "1M");
+/// assert_eq!(format_compact(8_200_000_000_i64), "8.2B");
+/// ```
+pub fn format_compact<T: ToPrimitive>(num: T) -> String {
+    const UNITS: [(f64, &str); 5] = [(1.0, ""), (1e3, "k"), (1e6, "M"), (1e9, "B"), (1e12, "T")];
+
+    let num = num.to_f64().unwrap_or(f64::NAN);
+    let abs = num.abs();
+    let mut index = UNITS
+        .iter()
+        .rposition(|(size, _)| abs >= *size)
+        .unwrap_or(0);
+
+    let mut scaled = (abs / UNITS[index].0 * 10.0).round() / 10.0;
+    // 999_999 rounds to 1000k, which reads better as 1M
+    if scaled >= 1000.0 && index + 1 < UNITS.len() {
+        index += 1;
+        scaled = (abs / UNITS[index].0 * 10.0).round() / 10.0;
+    }
+
+    let sign = match num < 0.0 && scaled > 0.0 {
+        true => "-",
+        false => "",
+    };
+
+    let digits = format!("{scaled:.1}");
+    let digits = digits.strip_suffix(".0").unwrap_or(&digits);
+    format!("{sign}{digits}{}", UNITS[index].1)
+}
+
+/// Thousand and decimal separators of a locale, for rendering numbers in reports
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::number::NumberFormat;
+///
+/// let german = NumberFormat::for_locale("de-DE");
+/// assert_eq!(german.format(1234567.891, 2), "1.234.567,89");
+/// assert_eq!(german.format_integer(-1234), "-1.234");
+///
+/// let swiss = NumberFormat::for_locale("de_CH");
+/// assert_eq!(swiss.format(1234.5, 2), "1\u{2019}234.50");
+///
+/// assert_eq!(NumberFormat::default().format(1234.5, 1), "1,234.5");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    thousands: String,
+    decimal: String,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new(",", ".")
+    }
+}
+
+impl NumberFormat {
+    pub fn new(thousands: &str, decimal: &str) -> Self {
+        Self {
+            thousands: thousands.to_string(),
+            decimal: decimal.to_string(),
+        }
+    }
+
+    /// The separators of a locale like `fr`, `de-DE` or `pt_BR`, English ones for
+    /// locales that aren't known
+    pub fn for_locale(locale: &str) -> Self {
+        let locale = locale.replace('_', "-").to_lowercase();
+        let (language, region) = locale.split_once('-').unwrap_or((&locale, ""));
+
+        match (language, region) {
+            ("de" | "fr" | "it" | "rm", "ch" | "li") => Self::new("\u{2019}", "."),
+            ("es", "mx" | "us") => Self::default(),
+            ("fr", _) => Self::new("\u{202f}", ","),
+            (
+                "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl"
+                | "sr" | "vi",
+                _,
+            ) => Self::new(".", ","),
+            (
+                "nb" | "nn" | "no" | "sv" | "fi" | "pl" | "cs" | "sk" | "ru" | "uk" | "hu" | "bg"
+                | "lt" | "lv" | "et",
+                _,
+            ) => Self::new("\u{a0}", ","),
+            _ => Self::default(),
+        }
+    }
+
+    /// Formats `num` with exactly `decimals` decimal places
+    pub fn format<T: Into<f64>>(&self, num: T, decimals: usize) -> String {
+        let formatted = format!("{:.decimals$}", num.into());
+        let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+        let (sign, digits) = match integer.strip_prefix('-') {
+            // -0.00 reads as 0.00
+            Some(digits) if !formatted.bytes().any(|b| (b'1'..=b'9').contains(&b)) => ("", digits),
+            Some(digits) => ("-", digits),
+            None => ("", integer),
+        };
+
+        let mut result = format!("{sign}{}", group_digits(digits, &self.thousands));
+        if !fraction.is_empty() {
+            result.push_str(&self.decimal);
+            result.push_str(fraction);
+        }
+
+        result
+    }
+
+    /// Formats an integer with thousand separators
+    pub fn format_integer<T: Into<i128>>(&self, num: T) -> String {
+        let num = num.into();
+        let digits = group_digits(&num.unsigned_abs().to_string(), &self.thousands);
+        match num < 0 {
+            true => format!("-{digits}"),
+            false => digits,
+        }
+    }
+}
+
+/// Inserts `separator` between groups of three digits, from the right
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push_str(separator);
+        }
+        result.push(digit);
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,4 +406,77 @@ mod tests {
         assert_eq!(format_currency(5000_i32), "5,000.00");
         assert_eq!(format_currency(-999), "-999.00");
     }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert::<_, u16>(65_535_u32).unwrap(), 65_535);
+        assert_eq!(convert::<_, i8>(-128_i64).unwrap(), -128);
+        assert_eq!(
+            convert::<_, i8>(-129_i64).unwrap_err().to_string(),
+            "-129 is out of range for i8 (-128..=127)"
+        );
+        assert!(convert::<_, usize>(-1_isize).is_err());
+    }
+
+    #[test]
+    fn test_ordinal() {
+        let expected = [
+            (0, "0th"),
+            (1, "1st"),
+            (2, "2nd"),
+            (3, "3rd"),
+            (4, "4th"),
+            (11, "11th"),
+            (12, "12th"),
+            (13, "13th"),
+            (21, "21st"),
+            (111, "111th"),
+            (1002, "1002nd"),
+            (-1, "-1st"),
+        ];
+        for (num, ordinal_form) in expected {
+            assert_eq!(ordinal(num), ordinal_form);
+        }
+    }
+
+    #[test]
+    fn test_format_compact() {
+        assert_eq!(format_compact(0), "0");
+        assert_eq!(format_compact(12.34), "12.3");
+        assert_eq!(format_compact(999), "999");
+        assert_eq!(format_compact(999.96), "1k");
+        assert_eq!(format_compact(1_000), "1k");
+        assert_eq!(format_compact(1_250), "1.3k");
+        assert_eq!(format_compact(15_000_000), "15M");
+        assert_eq!(format_compact(2_500_000_000_u32), "2.5B");
+        assert_eq!(format_compact(-7_100_000_000_000.0), "-7.1T");
+        assert_eq!(format_compact(-0.01), "0");
+        assert_eq!(format_compact(-4_200_000_000_i64), "-4.2B");
+        assert_eq!(format_compact(u64::MAX), "18446744.1T");
+        assert_eq!(format_compact(3_000_usize), "3k");
+    }
+
+    #[test]
+    fn test_number_format_locales() {
+        assert_eq!(
+            NumberFormat::for_locale("en-US").format(1234567.5, 2),
+            "1,234,567.50"
+        );
+        assert_eq!(NumberFormat::for_locale("pt_BR").format(1234.6, 0), "1.235");
+        assert_eq!(
+            NumberFormat::for_locale("fr").format(1234.5, 1),
+            "1\u{202f}234,5"
+        );
+        assert_eq!(
+            NumberFormat::for_locale("sv-SE").format_integer(1_000_000),
+            "1\u{a0}000\u{a0}000"
+        );
+        assert_eq!(
+            NumberFormat::for_locale("xx").format_integer(i64::MIN),
+            "-9,223,372,036,854,775,808"
+        );
+        assert_eq!(NumberFormat::default().format(-0.001, 2), "0.00");
+        assert_eq!(NumberFormat::default().format(-999.6, 0), "-1,000");
+        assert_eq!(NumberFormat::new(" ", ".").format(123, 0), "123");
+    }
 }
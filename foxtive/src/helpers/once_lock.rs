@@ -0,0 +1,224 @@
+//! Lazy initialization for values that need async work to be created,
+//! e.g. clients connecting to an optional service on first use.
+
+use crate::internal_server_error;
+use crate::results::AppResult;
+use futures_util::future::BoxFuture;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+type Initializer<T> = Box<dyn Fn() -> BoxFuture<'static, AppResult<T>> + Send + Sync>;
+
+/// What [`AsyncOnce::get`] does after the initialization failed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InitRetry {
+    /// The next call tries again
+    #[default]
+    Always,
+    /// Calls fail with the previous error until the delay has passed
+    After(Duration),
+    /// The failure is final, later calls fail with the same error
+    Never,
+}
+
+struct Failure {
+    at: Instant,
+    message: String,
+}
+
+/// Value created by an async closure the first time it is needed, exactly once,
+/// concurrent callers wait for the running initialization instead of starting their own.
+///
+/// # Example
+///
+/// ```
+/// use foxtive::helpers::once_lock::{AsyncOnce, InitRetry};
+/// use std::time::Duration;
+///
+/// struct SearchClient {
+///     url: String,
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let search = AsyncOnce::new(|| async {
+///     // connect, authenticate...
+///     Ok(SearchClient { url: "http://localhost:7700".to_string() })
+/// })
+/// .retry(InitRetry::After(Duration::from_secs(5)));
+///
+/// assert!(search.try_get().is_none());
+/// assert_eq!(search.get().await.unwrap().url, "http://localhost:7700");
+/// assert!(search.is_initialized());
+/// # });
+/// ```
+pub struct AsyncOnce<T> {
+    value: OnceLock<T>,
+    init: Initializer<T>,
+    retry: InitRetry,
+    failure: Mutex<Option<Failure>>,
+}
+
+impl<T: Send + Sync + 'static> AsyncOnce<T> {
+    pub fn new<F, Fut>(init: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = AppResult<T>> + Send + 'static,
+    {
+        Self {
+            value: OnceLock::new(),
+            init: Box::new(move || Box::pin(init())),
+            retry: InitRetry::default(),
+            failure: Mutex::new(None),
+        }
+    }
+
+    pub fn retry(mut self, policy: InitRetry) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The value, initialized by this call if no previous one succeeded
+    ///
+    /// # Errors
+    ///
+    /// Returns the initialization error, or the previous one while the [`InitRetry`] policy
+    /// doesn't allow a new attempt
+    pub async fn get(&self) -> AppResult<&T> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+
+        let mut failure = self.failure.lock().await;
+
+        // initialized by the caller we waited for
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+
+        if let Some(previous) = failure.as_ref() {
+            match self.retry {
+                InitRetry::Always => {}
+                InitRetry::After(delay) if previous.at.elapsed() >= delay => {}
+                InitRetry::After(_) | InitRetry::Never => {
+                    return Err(internal_server_error!(
+                        "initialization failed: {}",
+                        previous.message
+                    ));
+                }
+            }
+        }
+
+        match (self.init)().await {
+            Ok(value) => {
+                *failure = None;
+                Ok(self.value.get_or_init(|| value))
+            }
+            Err(err) => {
+                *failure = Some(Failure {
+                    at: Instant::now(),
+                    message: err.to_string(),
+                });
+                Err(err)
+            }
+        }
+    }
+
+    /// The value if it is already initialized
+    pub fn try_get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.value.get().is_some()
+    }
+}
+
+impl<T: Debug> Debug for AsyncOnce<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncOnce")
+            .field("value", &self.value.get())
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counted(
+        attempts: Arc<AtomicUsize>,
+        failures: usize,
+    ) -> impl Fn() -> BoxFuture<'static, AppResult<usize>> + Send + Sync {
+        move || {
+            let attempts = attempts.clone();
+            Box::pin(async move {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                match attempt > failures {
+                    true => Ok(attempt),
+                    false => Err(internal_server_error!("attempt {attempt} failed")),
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initializes_once_for_concurrent_callers() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let once = Arc::new(AsyncOnce::new(counted(attempts.clone(), 0)));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let once = once.clone();
+                tokio::spawn(async move { *once.get().await.unwrap() })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 1);
+        }
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_always() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let once = AsyncOnce::new(counted(attempts.clone(), 1));
+
+        assert!(once.get().await.is_err());
+        assert!(!once.is_initialized());
+        assert_eq!(*once.get().await.unwrap(), 2);
+        assert_eq!(once.try_get(), Some(&2));
+    }
+
+    #[tokio::test]
+    async fn test_retry_never() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let once = AsyncOnce::new(counted(attempts.clone(), 1)).retry(InitRetry::Never);
+
+        assert!(once.get().await.is_err());
+        let err = once.get().await.unwrap_err();
+        assert!(err.to_string().contains("attempt 1 failed"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_delay() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let once = AsyncOnce::new(counted(attempts.clone(), 1))
+            .retry(InitRetry::After(Duration::from_millis(50)));
+
+        assert!(once.get().await.is_err());
+        assert!(once.get().await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(*once.get().await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,16 @@
+use crate::prelude::AppResult;
+use sha_crypt::{PasswordVerifier, ShaCrypt, password_hash};
+
+/// Verifies a bcrypt (`$2a$`, `$2b$`, `$2y$`) hash
+pub(super) fn verify_bcrypt(hash: &str, password: &str) -> AppResult<bool> {
+    Ok(bcrypt::verify(password, hash)?)
+}
+
+/// Verifies a SHA-256-crypt (`$5$`) or SHA-512-crypt (`$6$`) hash
+pub(super) fn verify_sha_crypt(hash: &str, password: &str) -> AppResult<bool> {
+    match ShaCrypt::default().verify_password(password.as_bytes(), hash) {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::PasswordInvalid) => Ok(false),
+        Err(err) => Err(anyhow::anyhow!("invalid sha-crypt hash: {err}")),
+    }
+}
@@ -0,0 +1,505 @@
+#[cfg(feature = "crypto-legacy")]
+mod legacy;
+mod policy;
+
+use crate::prelude::AppResult;
+use argon2::{Config, Variant};
+
+pub use policy::{PasswordPolicy, PasswordViolation};
+
+/// Format of an encoded password hash, told by its prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashFormat {
+    /// `$argon2i$`, `$argon2d$` or `$argon2id$`, produced by [`Password::hash`]
+    Argon2,
+    /// `$2a$`, `$2b$`, `$2x$` or `$2y$`
+    Bcrypt,
+    /// `$5$` (SHA-256-crypt) or `$6$` (SHA-512-crypt)
+    ShaCrypt,
+}
+
+impl HashFormat {
+    /// Detects the format of `hash`, `None` when it's none of the known ones
+    pub fn detect(hash: &str) -> Option<HashFormat> {
+        let id = hash.strip_prefix('$')?.split('$').next()?;
+        match id {
+            "argon2i" | "argon2d" | "argon2id" => Some(HashFormat::Argon2),
+            "2a" | "2b" | "2x" | "2y" => Some(HashFormat::Bcrypt),
+            "5" | "6" => Some(HashFormat::ShaCrypt),
+            _ => None,
+        }
+    }
+
+    /// Whether hashes of this format are replaced by Argon2 hashes on login
+    pub fn is_legacy(&self) -> bool {
+        !matches!(self, HashFormat::Argon2)
+    }
+}
+
+/// Outcome of [`Password::verify_and_upgrade`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordCheck {
+    /// The password doesn't match
+    Invalid,
+    /// The password matches and the hash is current
+    Valid,
+    /// The password matches, the hash was legacy or outdated and should be replaced
+    /// by the one held
+    Upgraded(String),
+}
+
+impl PasswordCheck {
+    /// Whether the password matched
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, PasswordCheck::Invalid)
+    }
+
+    /// The hash to store in place of the verified one, if any
+    pub fn new_hash(&self) -> Option<&str> {
+        match self {
+            PasswordCheck::Upgraded(hash) => Some(hash),
+            _ => None,
+        }
+    }
+}
+
+/// A struct for handling password hashing and verification using Argon2.
+///
+/// The `Password` struct provides a secure way to hash passwords and verify them using the Argon2
+/// password hashing algorithm. It maintains a salt value that is used in the hashing process.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::password::Password;
+///
+/// // Create a new Password instance with a salt
+/// let password = Password::new("unique_salt".to_string());
+///
+/// // Hash a password
+/// let hash = password.hash("my_secret_password").unwrap();
+///
+/// // Verify a password against a hash
+/// assert!(password.verify(&hash, "my_secret_password").unwrap());
+/// assert!(!password.verify(&hash, "wrong_password").unwrap());
+/// ```
+pub struct Password {
+    salt: String,
+    config: Config<'static>,
+    policy: PasswordPolicy,
+}
+
+impl Password {
+    /// Creates a new `Password` instance with the specified salt.
+    ///
+    /// The salt should be a unique, random string that will be used in the password hashing process.
+    /// It's recommended to use a cryptographically secure random generator to create the salt.
+    ///
+    /// # Arguments
+    ///
+    /// * `salt` - A string that will be used as the salt in the password hashing process
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::password::Password;
+    ///
+    /// let password = Password::new("unique_salt".to_string());
+    /// ```
+    pub fn new(salt: String) -> Password {
+        Password {
+            salt,
+            config: Config::default(),
+            policy: PasswordPolicy::default(),
+        }
+    }
+
+    /// Sets the Argon2 cost parameters used for new hashes.
+    ///
+    /// Hashes created with other parameters still verify, and are reported by [`Password::needs_rehash`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mem_cost` - memory in KiB
+    /// * `time_cost` - number of passes
+    /// * `lanes` - degree of parallelism
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::password::Password;
+    ///
+    /// let old = Password::new("unique_salt".to_string()).cost(4096, 1, 1);
+    /// let hash = old.hash("my_secret_password").unwrap();
+    ///
+    /// let current = Password::new("unique_salt".to_string());
+    /// assert!(current.needs_rehash(&hash).unwrap());
+    /// ```
+    pub fn cost(mut self, mem_cost: u32, time_cost: u32, lanes: u32) -> Self {
+        self.config.mem_cost = mem_cost;
+        self.config.time_cost = time_cost;
+        self.config.lanes = lanes;
+        self
+    }
+
+    /// Sets the Argon2 variant used for new hashes (defaults to Argon2id)
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.config.variant = variant;
+        self
+    }
+
+    /// Sets the policy enforced by [`Password::validate`]
+    pub fn policy(mut self, policy: PasswordPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the policy enforced by [`Password::validate`]
+    pub fn get_policy(&self) -> &PasswordPolicy {
+        &self.policy
+    }
+
+    /// Checks a plain password against the configured policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error listing every violation under the `password` field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::password::{Password, PasswordPolicy};
+    ///
+    /// let password = Password::new("unique_salt".to_string())
+    ///     .policy(PasswordPolicy::new().min_length(10).require_digit());
+    ///
+    /// assert!(password.validate("correct-horse-9").is_ok());
+    /// assert!(password.validate("short").is_err());
+    /// ```
+    pub fn validate(&self, pwd: &str) -> AppResult<()> {
+        self.policy.validate(pwd)
+    }
+
+    /// Whether the hash was produced with different parameters than currently configured,
+    /// meaning it should be replaced by a fresh hash on the next successful login.
+    ///
+    /// Legacy hashes, see [`HashFormat::is_legacy`], always need a rehash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hash isn't a valid encoded Argon2 hash.
+    pub fn needs_rehash(&self, hash: &str) -> AppResult<bool> {
+        if HashFormat::detect(hash).is_some_and(|format| format.is_legacy()) {
+            return Ok(true);
+        }
+
+        let mut parts = hash.split('$').skip(1);
+        let variant = Variant::from_str(parts.next().unwrap_or_default())?;
+
+        let mut params = parts.next().unwrap_or_default();
+        if params.starts_with("v=") {
+            let version = params.trim_start_matches("v=");
+            if version != self.config.version.as_u32().to_string() {
+                return Ok(true);
+            }
+
+            params = parts.next().unwrap_or_default();
+        }
+
+        let (mut mem_cost, mut time_cost, mut lanes) = (None, None, None);
+        for param in params.split(',') {
+            match param.split_once('=') {
+                Some(("m", value)) => mem_cost = value.parse::<u32>().ok(),
+                Some(("t", value)) => time_cost = value.parse::<u32>().ok(),
+                Some(("p", value)) => lanes = value.parse::<u32>().ok(),
+                _ => {}
+            }
+        }
+
+        if mem_cost.is_none() || time_cost.is_none() || lanes.is_none() {
+            return Err(argon2::Error::DecodingFail.into());
+        }
+
+        Ok(variant != self.config.variant
+            || mem_cost != Some(self.config.mem_cost)
+            || time_cost != Some(self.config.time_cost)
+            || lanes != Some(self.config.lanes))
+    }
+
+    /// Hashes a password string using Argon2 with the instance's salt.
+    ///
+    /// This method uses the configured Argon2 parameters (the defaults unless changed via
+    /// [`Password::cost`]) and combines the provided
+    /// password with the instance's salt to create a secure hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `pwd` - The password string to hash
+    ///
+    /// # Returns
+    ///
+    /// * `AppResult<String>` - A Result containing either the encoded hash string or an error
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Argon2 hashing process fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::password::Password;
+    ///
+    /// let password = Password::new("unique_salt".to_string());
+    /// let hash = password.hash("my_secret_password").unwrap();
+    /// ```
+    pub fn hash(&self, pwd: &str) -> AppResult<String> {
+        Ok(argon2::hash_encoded(
+            pwd.as_bytes(),
+            self.salt.as_bytes(),
+            &self.config,
+        )?)
+    }
+
+    /// Verifies a password against a previously generated hash.
+    ///
+    /// This method checks if the provided password matches the provided hash. The hash should
+    /// have been generated using the same salt that the Password instance was created with.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The encoded hash string to verify against
+    /// * `password` - The password to verify
+    ///
+    /// # Returns
+    ///
+    /// * `AppResult<bool>` - A Result containing either:
+    ///   * `true` if the password matches the hash
+    ///   * `false` if the password doesn't match the hash
+    ///   * An error if the verification process fails
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The hash string is invalid or malformed
+    /// * The Argon2 verification process fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::password::Password;
+    ///
+    /// let password = Password::new("unique_salt".to_string());
+    /// let hash = password.hash("my_secret_password").unwrap();
+    ///
+    /// // Verify the correct password
+    /// assert!(password.verify(&hash, "my_secret_password").unwrap());
+    ///
+    /// // Verify incorrect password
+    /// assert!(!password.verify(&hash, "wrong_password").unwrap());
+    /// ```
+    pub fn verify(&self, hash: &str, password: &str) -> AppResult<bool> {
+        Ok(argon2::verify_encoded(hash, password.as_bytes())?)
+    }
+
+    /// Verifies a password against a hash of any supported format and, when it matches
+    /// a legacy or outdated hash, hashes it again with the current configuration, so an
+    /// imported user base migrates one login at a time.
+    ///
+    /// Bcrypt and sha-crypt hashes are verified with the `crypto-legacy` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hash is malformed or of an unsupported format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::password::{Password, PasswordCheck};
+    ///
+    /// let old = Password::new("unique_salt".to_string()).cost(4096, 1, 1);
+    /// let hash = old.hash("my_secret_password").unwrap();
+    ///
+    /// let password = Password::new("unique_salt".to_string());
+    /// let check = password.verify_and_upgrade(&hash, "my_secret_password").unwrap();
+    /// assert!(check.is_valid());
+    ///
+    /// // store the new hash in place of the old one
+    /// let new_hash = check.new_hash().unwrap();
+    /// assert!(!password.needs_rehash(new_hash).unwrap());
+    ///
+    /// let check = password.verify_and_upgrade(&hash, "wrong_password").unwrap();
+    /// assert_eq!(check, PasswordCheck::Invalid);
+    /// ```
+    pub fn verify_and_upgrade(&self, hash: &str, password: &str) -> AppResult<PasswordCheck> {
+        let valid = match HashFormat::detect(hash) {
+            #[cfg(feature = "crypto-legacy")]
+            Some(HashFormat::Bcrypt) => legacy::verify_bcrypt(hash, password)?,
+            #[cfg(feature = "crypto-legacy")]
+            Some(HashFormat::ShaCrypt) => legacy::verify_sha_crypt(hash, password)?,
+            #[cfg(not(feature = "crypto-legacy"))]
+            Some(format @ (HashFormat::Bcrypt | HashFormat::ShaCrypt)) => {
+                return Err(anyhow::anyhow!(
+                    "{format:?} hashes are verified with the `crypto-legacy` feature"
+                ));
+            }
+            _ => self.verify(hash, password)?,
+        };
+
+        if !valid {
+            return Ok(PasswordCheck::Invalid);
+        }
+
+        match self.needs_rehash(hash)? {
+            true => Ok(PasswordCheck::Upgraded(self.hash(password)?)),
+            false => Ok(PasswordCheck::Valid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use argon2::{self, Error};
+
+    use super::*;
+
+    #[test]
+    fn test_password_new() {
+        let salt = "random_salt".to_string();
+        let password = Password::new(salt.clone());
+        assert_eq!(password.salt, salt);
+    }
+
+    #[test]
+    fn test_password_hash() {
+        let salt = "random_salt".to_string();
+        let password = Password::new(salt.clone());
+        let pwd = "my_password";
+
+        let hash = password.hash(pwd).unwrap();
+
+        // Verify the generated hash is not empty
+        assert!(!hash.is_empty());
+
+        // Verify that the hash can be successfully verified
+        assert!(argon2::verify_encoded(&hash, pwd.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_password_verify_correct() {
+        let salt = "random_salt".to_string();
+        let password = Password::new(salt.clone());
+        let pwd = "my_password";
+
+        let hash = password.hash(pwd).unwrap();
+
+        // Verify that the password matches the hash
+        assert!(password.verify(&hash, pwd).unwrap());
+    }
+
+    #[test]
+    fn test_password_verify_incorrect() {
+        let salt = "random_salt".to_string();
+        let password = Password::new(salt.clone());
+        let pwd = "my_password";
+
+        let hash = password.hash(pwd).unwrap();
+
+        // Try to verify with a different password
+        let incorrect_password = "wrong_password";
+        assert!(!password.verify(&hash, incorrect_password).unwrap())
+    }
+
+    #[test]
+    fn test_password_verify_invalid_hash() {
+        let salt = "random_salt".to_string();
+        let password = Password::new(salt.clone());
+        let invalid_hash = "invalid_hash";
+        let pwd = "my_password";
+
+        // Verify that using an invalid hash returns error
+        let err = password
+            .verify(invalid_hash, pwd)
+            .unwrap_err()
+            .downcast::<Error>()
+            .unwrap();
+
+        assert_eq!(err, Error::DecodingFail);
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let password = Password::new("random_salt".to_string());
+        let hash = password.hash("my_password").unwrap();
+        assert!(!password.needs_rehash(&hash).unwrap());
+
+        let stronger = Password::new("random_salt".to_string()).cost(32 * 1024, 3, 1);
+        assert!(stronger.needs_rehash(&hash).unwrap());
+
+        let argon2i = Password::new("random_salt".to_string()).variant(Variant::Argon2i);
+        assert!(argon2i.needs_rehash(&hash).unwrap());
+
+        assert!(password.needs_rehash("invalid_hash").is_err());
+    }
+
+    #[test]
+    fn test_hash_format_detection() {
+        let hash = Password::new("random_salt".to_string())
+            .hash("my_password")
+            .unwrap();
+        assert_eq!(HashFormat::detect(&hash), Some(HashFormat::Argon2));
+        assert_eq!(
+            HashFormat::detect("$2y$04$abcdefghijklmnopqrstuu"),
+            Some(HashFormat::Bcrypt)
+        );
+        assert_eq!(
+            HashFormat::detect("$6$rounds=5000$salt$hash"),
+            Some(HashFormat::ShaCrypt)
+        );
+        assert_eq!(HashFormat::detect("5f4dcc3b5aa765d61d8327deb882cf99"), None);
+    }
+
+    #[test]
+    fn test_verify_and_upgrade_current_hash() {
+        let password = Password::new("random_salt".to_string());
+        let hash = password.hash("my_password").unwrap();
+
+        assert_eq!(
+            password.verify_and_upgrade(&hash, "my_password").unwrap(),
+            PasswordCheck::Valid
+        );
+        assert_eq!(
+            password.verify_and_upgrade(&hash, "wrong").unwrap(),
+            PasswordCheck::Invalid
+        );
+    }
+
+    #[cfg(feature = "crypto-legacy")]
+    #[test]
+    fn test_verify_and_upgrade_legacy_hashes() {
+        let password = Password::new("random_salt".to_string());
+
+        // `openssl passwd -6 -salt saltsalt password`
+        let sha512 = "$6$saltsalt$qFmFH.bQmmtXzyBY0s9v7Oicd2z4XSIecDzlB5KiA2/jctKu9YterLp8wwnSq.qc.eoxqOmSuNp2xS0ktL3nh/";
+        let bcrypt = bcrypt::hash("password", 4).unwrap();
+
+        for hash in [sha512, bcrypt.as_str()] {
+            let check = password.verify_and_upgrade(hash, "password").unwrap();
+            let new_hash = check.new_hash().expect("legacy hashes are upgraded");
+            assert!(password.verify(new_hash, "password").unwrap());
+
+            assert_eq!(
+                password.verify_and_upgrade(hash, "wrong").unwrap(),
+                PasswordCheck::Invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_uses_policy() {
+        let password =
+            Password::new("random_salt".to_string()).policy(PasswordPolicy::new().min_length(12));
+
+        assert!(password.validate("long-enough-pass").is_ok());
+        assert!(password.validate("short").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::prelude::AppResult;
+use crate::validation_error;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+type DenylistHook = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// A single rule a password failed to satisfy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordViolation {
+    TooShort {
+        min: usize,
+    },
+    TooLong {
+        max: usize,
+    },
+    MissingUppercase,
+    MissingLowercase,
+    MissingDigit,
+    MissingSymbol,
+    /// The password appears in a breached/common password list
+    Breached,
+}
+
+impl PasswordViolation {
+    /// Machine-readable code of the violation
+    pub fn code(&self) -> &'static str {
+        match self {
+            PasswordViolation::TooShort { .. } => "too_short",
+            PasswordViolation::TooLong { .. } => "too_long",
+            PasswordViolation::MissingUppercase => "missing_uppercase",
+            PasswordViolation::MissingLowercase => "missing_lowercase",
+            PasswordViolation::MissingDigit => "missing_digit",
+            PasswordViolation::MissingSymbol => "missing_symbol",
+            PasswordViolation::Breached => "breached",
+        }
+    }
+}
+
+impl Display for PasswordViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PasswordViolation::TooShort { min } => {
+                write!(f, "must be at least {min} characters long")
+            }
+            PasswordViolation::TooLong { max } => {
+                write!(f, "must be at most {max} characters long")
+            }
+            PasswordViolation::MissingUppercase => {
+                write!(f, "must contain at least one uppercase letter")
+            }
+            PasswordViolation::MissingLowercase => {
+                write!(f, "must contain at least one lowercase letter")
+            }
+            PasswordViolation::MissingDigit => write!(f, "must contain at least one digit"),
+            PasswordViolation::MissingSymbol => write!(f, "must contain at least one symbol"),
+            PasswordViolation::Breached => {
+                write!(f, "has appeared in a data breach, please choose another")
+            }
+        }
+    }
+}
+
+/// Configurable password requirements.
+///
+/// The default policy only requires 8 characters, stricter rules are opt-in.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::password::{PasswordPolicy, PasswordViolation};
+///
+/// let policy = PasswordPolicy::new()
+///     .min_length(10)
+///     .require_uppercase()
+///     .require_digit()
+///     .denylist(|pwd| ["Password123", "Qwerty12345"].contains(&pwd));
+///
+/// assert_eq!(
+///     policy.check("short"),
+///     vec![
+///         PasswordViolation::TooShort { min: 10 },
+///         PasswordViolation::MissingUppercase,
+///         PasswordViolation::MissingDigit,
+///     ]
+/// );
+///
+/// assert_eq!(policy.check("Password123"), vec![PasswordViolation::Breached]);
+/// assert!(policy.check("Tr0ub4dor&3x").is_empty());
+/// ```
+#[derive(Clone)]
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: Option<usize>,
+    require_uppercase: bool,
+    require_lowercase: bool,
+    require_digit: bool,
+    require_symbol: bool,
+    denylist: Option<DenylistHook>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: None,
+            require_uppercase: false,
+            require_lowercase: false,
+            require_digit: false,
+            require_symbol: false,
+            denylist: None,
+        }
+    }
+}
+
+impl Debug for PasswordPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasswordPolicy")
+            .field("min_length", &self.min_length)
+            .field("max_length", &self.max_length)
+            .field("require_uppercase", &self.require_uppercase)
+            .field("require_lowercase", &self.require_lowercase)
+            .field("require_digit", &self.require_digit)
+            .field("require_symbol", &self.require_symbol)
+            .field("denylist", &self.denylist.is_some())
+            .finish()
+    }
+}
+
+impl PasswordPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Minimum number of characters (default 8)
+    pub fn min_length(mut self, length: usize) -> Self {
+        self.min_length = length;
+        self
+    }
+
+    /// Maximum number of characters
+    pub fn max_length(mut self, length: usize) -> Self {
+        self.max_length = Some(length);
+        self
+    }
+
+    pub fn require_uppercase(mut self) -> Self {
+        self.require_uppercase = true;
+        self
+    }
+
+    pub fn require_lowercase(mut self) -> Self {
+        self.require_lowercase = true;
+        self
+    }
+
+    pub fn require_digit(mut self) -> Self {
+        self.require_digit = true;
+        self
+    }
+
+    pub fn require_symbol(mut self) -> Self {
+        self.require_symbol = true;
+        self
+    }
+
+    /// Requires upper and lower case letters, digits and symbols
+    pub fn require_all_classes(self) -> Self {
+        self.require_uppercase()
+            .require_lowercase()
+            .require_digit()
+            .require_symbol()
+    }
+
+    /// Registers a hook returning `true` for passwords that must be rejected,
+    /// e.g. a lookup into a breached-password list
+    pub fn denylist<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.denylist = Some(Arc::new(hook));
+        self
+    }
+
+    /// Returns every rule the password violates, empty when it is acceptable
+    pub fn check(&self, pwd: &str) -> Vec<PasswordViolation> {
+        let mut violations = vec![];
+        let length = pwd.chars().count();
+
+        if length < self.min_length {
+            violations.push(PasswordViolation::TooShort {
+                min: self.min_length,
+            });
+        }
+
+        if let Some(max) = self.max_length
+            && length > max
+        {
+            violations.push(PasswordViolation::TooLong { max });
+        }
+
+        if self.require_uppercase && !pwd.chars().any(char::is_uppercase) {
+            violations.push(PasswordViolation::MissingUppercase);
+        }
+
+        if self.require_lowercase && !pwd.chars().any(char::is_lowercase) {
+            violations.push(PasswordViolation::MissingLowercase);
+        }
+
+        if self.require_digit && !pwd.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::MissingDigit);
+        }
+
+        if self.require_symbol && !pwd.chars().any(|c| !c.is_alphanumeric()) {
+            violations.push(PasswordViolation::MissingSymbol);
+        }
+
+        if let Some(denylist) = &self.denylist
+            && denylist(pwd)
+        {
+            violations.push(PasswordViolation::Breached);
+        }
+
+        violations
+    }
+
+    /// Same as [`PasswordPolicy::check`], but returns a validation error
+    /// with the violations listed under the `password` field
+    pub fn validate(&self, pwd: &str) -> AppResult<()> {
+        let violations = self.check(pwd);
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        let messages = violations.iter().map(|v| v.to_string()).collect();
+        let errors = HashMap::from([("password".to_string(), messages)]);
+        Err(validation_error!(
+            "Password does not meet requirements",
+            errors
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::AppMessage;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("12345678").is_empty());
+        assert_eq!(
+            policy.check("1234567"),
+            vec![PasswordViolation::TooShort { min: 8 }]
+        );
+    }
+
+    #[test]
+    fn test_character_classes() {
+        let policy = PasswordPolicy::new().min_length(1).require_all_classes();
+
+        assert_eq!(
+            policy.check("abc"),
+            vec![
+                PasswordViolation::MissingUppercase,
+                PasswordViolation::MissingDigit,
+                PasswordViolation::MissingSymbol,
+            ]
+        );
+        assert!(policy.check("aB3$").is_empty());
+    }
+
+    #[test]
+    fn test_length_counts_characters() {
+        let policy = PasswordPolicy::new().min_length(4).max_length(5);
+        assert!(policy.check("ñññÑ").is_empty());
+        assert_eq!(
+            policy.check("abcdef"),
+            vec![PasswordViolation::TooLong { max: 5 }]
+        );
+    }
+
+    #[test]
+    fn test_denylist() {
+        let policy = PasswordPolicy::new().denylist(|pwd| pwd.eq_ignore_ascii_case("password"));
+        assert_eq!(policy.check("PASSWORD"), vec![PasswordViolation::Breached]);
+        assert_eq!(PasswordViolation::Breached.code(), "breached");
+    }
+
+    #[test]
+    fn test_validate_returns_field_errors() {
+        let policy = PasswordPolicy::new().min_length(10).require_digit();
+        let err = policy.validate("short").unwrap_err();
+
+        match err.downcast_ref::<AppMessage>() {
+            Some(AppMessage::ValidationError(_, errors)) => {
+                assert_eq!(errors["password"].len(), 2);
+            }
+            _ => panic!("expected a validation error"),
+        }
+    }
+}
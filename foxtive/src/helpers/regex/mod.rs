@@ -1,8 +1,10 @@
 mod tester;
 mod text_cleaner;
+mod text_cleaner_builder;
 
 pub use tester::*;
 pub use text_cleaner::TextCleaner;
+pub use text_cleaner_builder::{CasePolicy, CharClass, PrefixRule, TextCleanerBuilder};
 
 /// Enum to specify case-sensitivity and character transformation rules.
 #[derive(Clone, Copy)]
@@ -44,9 +46,21 @@ pub enum RegexType {
     /// Keeps digits, removes everything else
     Digits,
 
-    /// Basic email validation/cleaning
+    /// Email address: dot-atom local part of up to 64 chars, hostname labels, 254 chars at most
     Email,
 
+    /// Phone number in E.164 format, e.g. `+2348012345678`
+    Phone,
+
+    /// `http` or `https` URL with a hostname, optional port, path, query and fragment
+    Url,
+
+    /// IBAN structure, uppercase without spaces, see [`Tester::validate_iban`] for the checksum
+    Iban,
+
+    /// BIC / SWIFT code, 8 or 11 chars
+    Bic,
+
     /// Use a custom cleaning pattern (allowed_chars, max_length)
     Custom(&'static str, Option<CaseSensitivity>, usize),
 }
@@ -1,4 +1,11 @@
 use crate::helpers::regex::{CaseSensitivity, RegexType};
+use fancy_regex::Regex;
+use std::collections::HashMap;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+/// Compiled patterns, keyed by pattern and case-insensitivity. Patterns are `'static`,
+/// so the cache is bounded by the patterns present in the binary.
+static COMPILED: OnceLock<RwLock<HashMap<(&'static str, bool), Regex>>> = OnceLock::new();
 
 /// A utility struct for working with regular expressions for username validation.
 pub struct Tester;
@@ -30,20 +37,59 @@ impl Tester {
     /// assert_eq!(result.is_ok() && result.unwrap(), false);
     /// ```
     pub fn validate(val: &str, rt: RegexType) -> Box<Result<bool, fancy_regex::Error>> {
+        let regex = match Tester::compiled(rt) {
+            Ok(regex) => regex,
+            Err(err) => return Box::new(Err(err)),
+        };
+
+        Box::new(regex.is_match(val))
+    }
+
+    /// Validates every value against the same pattern, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use foxtive::helpers::regex::{Tester, RegexType};
+    ///
+    /// let results = Tester::validate_many(["123", "12a"], RegexType::Digits).unwrap();
+    /// assert_eq!(results, vec![true, false]);
+    /// ```
+    pub fn validate_many<'a>(
+        vals: impl IntoIterator<Item = &'a str>,
+        rt: RegexType,
+    ) -> Result<Vec<bool>, fancy_regex::Error> {
+        let regex = Tester::compiled(rt)?;
+        vals.into_iter().map(|val| regex.is_match(val)).collect()
+    }
+
+    /// Returns the compiled regex of `rt`, compiling it on first use only
+    fn compiled(rt: RegexType) -> Result<Regex, fancy_regex::Error> {
         let (regex_pattern, case_sensitivity) = Tester::acquire_regex(rt);
+        let insensitive = matches!(case_sensitivity, CaseSensitivity::CaseInsensitive);
+        let cache = COMPILED.get_or_init(Default::default);
+
+        let cached = cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(regex_pattern, insensitive))
+            .cloned();
+        if let Some(regex) = cached {
+            return Ok(regex);
+        }
 
         // Adjust the regex pattern for case-insensitivity if necessary
-        let regex_pattern = match case_sensitivity {
-            CaseSensitivity::CaseInsensitive => format!("(?i){regex_pattern}"),
-            _ => regex_pattern.to_string(),
+        let regex = match insensitive {
+            true => Regex::new(&format!("(?i){regex_pattern}"))?,
+            false => Regex::new(regex_pattern)?,
         };
 
-        let regex = match fancy_regex::Regex::new(&regex_pattern) {
-            Ok(regex) => regex,
-            Err(err) => return Box::new(Err(err)),
-        };
+        cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((regex_pattern, insensitive), regex.clone());
 
-        Box::new(regex.is_match(val))
+        Ok(regex)
     }
 
     /// Validates a username using a specified regex type. This method accepts a `Cow<str>` so it can handle both
@@ -76,6 +122,36 @@ impl Tester {
         )
     }
 
+    /// Validates an IBAN, its structure and its ISO 7064 mod-97 checksum. Spaces are ignored
+    /// and lowercase letters accepted, see [`TextCleaner::normalize_iban`](crate::helpers::regex::TextCleaner::normalize_iban).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use foxtive::helpers::regex::Tester;
+    ///
+    /// assert!(Tester::validate_iban("GB82 WEST 1234 5698 7654 32"));
+    /// assert!(!Tester::validate_iban("GB82 WEST 1234 5698 7654 33"));
+    /// ```
+    pub fn validate_iban(val: &str) -> bool {
+        let iban = crate::helpers::regex::TextCleaner::normalize_iban(val);
+        if !matches!(*Tester::validate(&iban, RegexType::Iban), Ok(true)) {
+            return false;
+        }
+
+        // the first four chars move to the end, letters count as 10 to 35
+        let (head, tail) = iban.split_at(4);
+        let remainder = tail.chars().chain(head.chars()).fold(0u32, |acc, c| {
+            let value = c.to_digit(36).unwrap_or(0);
+            match value {
+                0..=9 => (acc * 10 + value) % 97,
+                _ => (acc * 100 + value) % 97,
+            }
+        });
+
+        remainder == 1
+    }
+
     /// Retrieves the regex pattern associated with the given `RegexType` variant.
     ///
     /// # Parameters
@@ -103,9 +179,22 @@ impl Tester {
             ), // Letters, digits, dashes, dots, and underscores.
             RegexType::Digits => (r"^[0-9]+$", CaseSensitivity::CaseSensitive),
             RegexType::Email => (
-                r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$",
+                r"^(?=.{1,254}$)(?=[^@]{1,64}@)[a-zA-Z0-9!#$%&'*+/=?^_`{|}~-]+(?:\.[a-zA-Z0-9!#$%&'*+/=?^_`{|}~-]+)*@(?:[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?\.)+[a-zA-Z]{2,63}$",
+                CaseSensitivity::CaseInsensitive,
+            ),
+            RegexType::Phone => (r"^\+[1-9]\d{1,14}$", CaseSensitivity::CaseSensitive),
+            RegexType::Url => (
+                r"^https?://(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)*[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?(?::\d{1,5})?(?:[/?#]\S*)?$",
                 CaseSensitivity::CaseInsensitive,
             ),
+            RegexType::Iban => (
+                r"^[A-Z]{2}\d{2}[A-Z0-9]{11,30}$",
+                CaseSensitivity::CaseSensitive,
+            ),
+            RegexType::Bic => (
+                r"^[A-Z]{4}[A-Z]{2}[A-Z0-9]{2}(?:[A-Z0-9]{3})?$",
+                CaseSensitivity::CaseSensitive,
+            ),
             RegexType::Custom(val, cs, _size) => {
                 (val, cs.unwrap_or(CaseSensitivity::CaseSensitive))
             }
@@ -116,6 +205,113 @@ impl Tester {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_compiled_patterns_are_cached() {
+        let rt = RegexType::Custom(
+            r"^cached-[a-z]+$",
+            Some(CaseSensitivity::CaseInsensitive),
+            0,
+        );
+        assert!(Tester::validate("cached-ABC", rt.clone()).unwrap());
+
+        let key = (r"^cached-[a-z]+$", true);
+        assert!(COMPILED.get().unwrap().read().unwrap().contains_key(&key));
+
+        // the case-sensitive variant is compiled separately
+        let rt = RegexType::Custom(r"^cached-[a-z]+$", None, 0);
+        assert!(!Tester::validate("cached-ABC", rt).unwrap());
+    }
+
+    #[test]
+    fn test_validate_many() {
+        let results = Tester::validate_many(
+            ["user.name", "user..name", "user"],
+            RegexType::AlphaNumericDot(CaseSensitivity::CaseSensitive),
+        )
+        .unwrap();
+        assert_eq!(results, vec![true, false, true]);
+
+        let invalid = RegexType::Custom(r"^(unclosed$", None, 0);
+        assert!(Tester::validate_many(["a"], invalid).is_err());
+    }
+
+    #[test]
+    fn test_email_rejects_malformed_addresses() {
+        for email in [
+            "first.last+tag@sub.example.org",
+            "o'reilly@example.io",
+            "USER@EXAMPLE.COM",
+        ] {
+            assert!(
+                Tester::validate(email, RegexType::Email).unwrap(),
+                "{email}"
+            );
+        }
+
+        let long_local = format!("{}@example.com", "a".repeat(65));
+        for email in [
+            "user..name@example.com",
+            ".user@example.com",
+            "user.@example.com",
+            "user@-example.com",
+            "user@example.c",
+            "user name@example.com",
+            long_local.as_str(),
+        ] {
+            assert!(
+                !Tester::validate(email, RegexType::Email).unwrap(),
+                "{email}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_phone() {
+        assert!(Tester::validate("+2348012345678", RegexType::Phone).unwrap());
+        assert!(Tester::validate("+14155552671", RegexType::Phone).unwrap());
+
+        for phone in ["08012345678", "+0123456", "+1234567890123456", "+1 415 555"] {
+            assert!(
+                !Tester::validate(phone, RegexType::Phone).unwrap(),
+                "{phone}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_url() {
+        for url in [
+            "https://example.com",
+            "http://localhost:8080/health",
+            "https://api.example.co.uk/v1/users?page=2#top",
+        ] {
+            assert!(Tester::validate(url, RegexType::Url).unwrap(), "{url}");
+        }
+
+        for url in [
+            "ftp://example.com",
+            "https://",
+            "https://-example.com",
+            "https://example.com/a b",
+            "example.com",
+        ] {
+            assert!(!Tester::validate(url, RegexType::Url).unwrap(), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_iban_and_bic() {
+        assert!(Tester::validate_iban("DE89370400440532013000"));
+        assert!(Tester::validate_iban("gb82 west 1234 5698 7654 32"));
+        assert!(!Tester::validate_iban("DE89370400440532013001"));
+        assert!(!Tester::validate_iban("DE8937"));
+
+        assert!(Tester::validate("DEUTDEFF", RegexType::Bic).unwrap());
+        assert!(Tester::validate("DEUTDEFF500", RegexType::Bic).unwrap());
+        assert!(!Tester::validate("DEUTDEFF5", RegexType::Bic).unwrap());
+        assert!(!Tester::validate("deutdeff", RegexType::Bic).unwrap());
+    }
+
     // Test for Alphabetic regex type
     #[test]
     fn test_alphabetic_valid() {
@@ -1,9 +1,49 @@
-use crate::helpers::regex::{CaseSensitivity, RegexType};
-
-/// A utility struct for cleaning text using regex patterns for username validation.
-pub struct TextCleaner;
+use crate::helpers::regex::{
+    CasePolicy, CaseSensitivity, CharClass, PrefixRule, RegexType, TextCleanerBuilder,
+};
+use std::sync::Arc;
+
+pub(super) type Rule = Arc<dyn Fn(String) -> String + Send + Sync>;
+
+/// Cleans text with a compiled set of rules, built with [`TextCleaner::builder`]
+/// or from one of the [`RegexType`] presets.
+///
+/// # Examples
+///
+/// ```rust
+/// use foxtive::helpers::regex::{CasePolicy, CharClass, TextCleaner};
+///
+/// let slug = TextCleaner::builder()
+///     .allow(CharClass::Alphanumeric)
+///     .replacement('-')
+///     .collapse('-')
+///     .trim_end('-')
+///     .case(CasePolicy::Lower)
+///     .max_length(20)
+///     .build();
+///
+/// assert_eq!(slug.apply("Hello, World!"), "hello-world");
+/// assert_eq!(slug.apply("Rust & Tokio"), "rust-tokio");
+/// ```
+#[derive(Clone)]
+pub struct TextCleaner {
+    pub(super) allowed: Vec<CharClass>,
+    pub(super) forbidden: Vec<String>,
+    pub(super) collapse: Vec<char>,
+    pub(super) trim_end: Vec<char>,
+    pub(super) prefix: PrefixRule,
+    pub(super) max_length: Option<usize>,
+    pub(super) case: CasePolicy,
+    pub(super) replacement: Option<char>,
+    pub(super) normalize_whitespace: bool,
+    pub(super) rules: Vec<Rule>,
+}
 
 impl TextCleaner {
+    pub fn builder() -> TextCleanerBuilder {
+        TextCleanerBuilder::default()
+    }
+
     /// Cleans a string according to the specified cleaning rules.
     ///
     /// # Parameters
@@ -27,43 +67,7 @@ impl TextCleaner {
     /// assert_eq!(cleaned, "user.name.123");
     /// ```
     pub fn clean(text: &str, cleaning_type: RegexType) -> String {
-        match cleaning_type {
-            RegexType::Alphabetic(case_sensitivity) => {
-                Self::clean_alphabetic(text, case_sensitivity)
-            }
-            RegexType::AlphaNumeric(case_sensitivity) => {
-                Self::clean_alphanumeric(text, case_sensitivity)
-            }
-            RegexType::AlphaNumericLoose(case_sensitivity) => {
-                Self::clean_alphanumeric_loose(text, case_sensitivity)
-            }
-            RegexType::AlphaNumericSpace(case_sensitivity) => {
-                Self::clean_alphanumeric_space(text, case_sensitivity)
-            }
-            RegexType::AlphaNumericDash(case_sensitivity) => {
-                Self::clean_alphanumeric_dash(text, case_sensitivity)
-            }
-            RegexType::AlphaNumericDot(case_sensitivity) => {
-                Self::clean_alphanumeric_dot(text, case_sensitivity)
-            }
-            RegexType::AlphaNumericDashDot(case_sensitivity) => {
-                Self::clean_alphanumeric_dash_dot(text, case_sensitivity)
-            }
-            RegexType::AlphaNumericUnderscore(case_sensitivity) => {
-                Self::clean_alphanumeric_underscore(text, case_sensitivity)
-            }
-            RegexType::AlphaNumericDotUnderscore(case_sensitivity) => {
-                Self::clean_alphanumeric_dot_underscore(text, case_sensitivity)
-            }
-            RegexType::Digits => Self::clean_digits(text),
-            RegexType::Email => Self::clean_email(text),
-            RegexType::Custom(allowed_chars, case_sensitivity, max_length) => Self::clean_custom(
-                text,
-                allowed_chars,
-                case_sensitivity.unwrap_or(CaseSensitivity::CaseSensitive),
-                max_length,
-            ),
-        }
+        Self::from(cleaning_type).apply(text)
     }
 
     /// Cleans text for username format (AlphaNumericDot with case sensitivity).
@@ -84,168 +88,118 @@ impl TextCleaner {
     /// assert_eq!(cleaned, "user.first123");
     /// ```
     pub fn clean_username(text: &str) -> String {
-        Self::clean(
-            text,
-            RegexType::AlphaNumericDot(CaseSensitivity::CaseSensitive),
-        )
+        Self::username().apply(text)
     }
 
-    /// Cleans text to contain only alphabetic characters.
-    fn clean_alphabetic(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text.chars().filter(|c| c.is_alphabetic()).collect();
-
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        Self::truncate_to_length(result, 38)
-    }
-
-    /// Cleans text to contain only alphanumeric characters, ensuring it starts with a letter.
-    fn clean_alphanumeric(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text.chars().filter(|c| c.is_alphanumeric()).collect();
-
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        result = Self::ensure_starts_with_letter(result);
-        Self::truncate_to_length(result, 38)
+    /// The cleaner of [`TextCleaner::clean_username`]
+    pub fn username() -> Self {
+        Self::from(RegexType::AlphaNumericDot(CaseSensitivity::CaseSensitive))
     }
 
-    /// Cleans text to contain only alphanumeric characters, without a starting character restriction.
-    fn clean_alphanumeric_loose(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text.chars().filter(|c| c.is_alphanumeric()).collect();
-
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        Self::truncate_to_length(result, 38)
-    }
-
-    /// Cleans text for alphanumeric + space pattern, normalizing whitespace.
-    fn clean_alphanumeric_space(text: &str, case_sensitivity: CaseSensitivity) -> String {
+    /// Cleans `text` with the rules of this cleaner, in this order: disallowed characters
+    /// are removed or replaced, case policy, prefix rule, forbidden sequences, collapsed
+    /// characters, whitespace normalization, trailing characters, custom rules and max length.
+    pub fn apply(&self, text: &str) -> String {
         let mut result: String = text
             .chars()
-            .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+            .filter_map(|c| match self.is_allowed(c) {
+                true => Some(c),
+                false => self.replacement,
+            })
             .collect();
 
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        result = Self::ensure_starts_with_letter(result);
-        result = Self::normalize_whitespace(result);
-        result = Self::remove_trailing_whitespace(result);
-        Self::truncate_to_length(result, 38)
-    }
-
-    /// Cleans text for alphanumeric + dash pattern.
-    fn clean_alphanumeric_dash(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '-')
-            .collect();
+        result = match self.case {
+            CasePolicy::Preserve => result,
+            CasePolicy::Lower => result.to_lowercase(),
+            CasePolicy::Upper => result.to_uppercase(),
+        };
+
+        result = match self.prefix {
+            PrefixRule::Any => result,
+            PrefixRule::Letter => Self::skip_leading(result, char::is_alphabetic),
+            PrefixRule::Alphanumeric => Self::skip_leading(result, char::is_alphanumeric),
+        };
+
+        for sequence in self.forbidden.iter().filter(|seq| !seq.is_empty()) {
+            // removing a sequence may join the parts of another one
+            while result.contains(sequence.as_str()) {
+                result = result.replace(sequence.as_str(), "");
+            }
+        }
 
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        result = Self::ensure_starts_with_letter(result);
-        result = Self::remove_consecutive_chars(result, '-');
-        result = Self::remove_trailing_char(result, '-');
-        Self::truncate_to_length(result, 38)
-    }
+        for target_char in &self.collapse {
+            result = Self::remove_consecutive_chars(result, *target_char);
+        }
 
-    /// Cleans text for alphanumeric + dot pattern.
-    fn clean_alphanumeric_dot(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '.')
-            .collect();
+        if self.normalize_whitespace {
+            result = Self::normalize_whitespace(result);
+        }
 
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        result = Self::ensure_starts_with_letter(result);
-        result = Self::remove_consecutive_chars(result, '.');
-        result = Self::remove_trailing_char(result, '.');
-        Self::truncate_to_length(result, 38)
-    }
+        for target_char in &self.trim_end {
+            result = Self::remove_trailing_char(result, *target_char);
+        }
 
-    /// Cleans text for alphanumeric + dash + dot pattern.
-    fn clean_alphanumeric_dash_dot(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '.' || *c == '_')
-            .collect();
+        for rule in &self.rules {
+            result = rule(result);
+        }
 
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        result = Self::ensure_starts_with_letter(result);
-        result = Self::remove_consecutive_chars(result, '.');
-        result = Self::remove_consecutive_chars(result, '-');
-        result = Self::remove_trailing_char(result, '.');
-        result = Self::remove_trailing_char(result, '-');
-        Self::truncate_to_length(result, 38)
+        match self.max_length {
+            Some(max_length) => Self::truncate_to_length(result, max_length),
+            None => result,
+        }
     }
 
-    /// Cleans text for alphanumeric + underscore pattern.
-    fn clean_alphanumeric_underscore(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '_')
-            .collect();
-
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        result = Self::ensure_starts_with_letter(result);
-        result = Self::remove_consecutive_chars(result, '_');
-        result = Self::remove_trailing_char(result, '_');
-        Self::truncate_to_length(result, 38)
-    }
+    /// Converts a phone number to E.164, `None` if it can't be.
+    ///
+    /// A national number, starting with a trunk `0`, gets `country_code` in place of the `0`.
+    /// Separators (spaces, dashes, dots, parentheses) are removed and a `00` prefix becomes `+`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use foxtive::helpers::regex::TextCleaner;
+    ///
+    /// assert_eq!(TextCleaner::phone_to_e164("0801 234 5678", Some("234")).as_deref(), Some("+2348012345678"));
+    /// assert_eq!(TextCleaner::phone_to_e164("(415) 555-2671", None), None);
+    /// assert_eq!(TextCleaner::phone_to_e164("00 1 415 555 2671", None).as_deref(), Some("+14155552671"));
+    /// ```
+    pub fn phone_to_e164(text: &str, country_code: Option<&str>) -> Option<String> {
+        let cleaned = Self::clean_phone(text);
 
-    /// Cleans text for alphanumeric + dot + underscore pattern.
-    fn clean_alphanumeric_dot_underscore(text: &str, case_sensitivity: CaseSensitivity) -> String {
-        let mut result: String = text
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '_')
-            .collect();
+        let e164 = match cleaned.strip_prefix('0') {
+            _ if cleaned.starts_with('+') => cleaned,
+            Some(national) => format!("+{}{national}", country_code?.trim_start_matches('+')),
+            None => return None,
+        };
 
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        result = Self::ensure_starts_with_letter(result);
-        result = Self::remove_consecutive_chars(result, '.');
-        result = Self::remove_consecutive_chars(result, '_');
-        result = Self::remove_trailing_char(result, '.');
-        result = Self::remove_trailing_char(result, '_');
-        Self::truncate_to_length(result, 38)
+        let valid = crate::helpers::regex::Tester::validate(&e164, RegexType::Phone);
+        matches!(*valid, Ok(true)).then_some(e164)
     }
 
-    /// Cleans text to contain only ascii digits.
-    fn clean_digits(text: &str) -> String {
-        text.chars().filter(|c| c.is_ascii_digit()).collect()
+    /// Removes the spaces of an IBAN or a BIC and uppercases it
+    pub fn normalize_iban(text: &str) -> String {
+        Self::from(RegexType::Iban).apply(text)
     }
 
-    /// Cleans an email address by removing invalid characters.
-    fn clean_email(text: &str) -> String {
-        // A very basic email cleaner. A proper implementation would be more complex.
-        text.chars()
-            .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '@' || *c == '-' || *c == '_')
-            .collect::<String>()
-            .to_lowercase()
+    fn is_allowed(&self, c: char) -> bool {
+        self.allowed.is_empty() || self.allowed.iter().any(|class| class.contains(c))
     }
 
-    /// Cleans text using custom allowed characters.
-    fn clean_custom(
-        text: &str,
-        allowed_chars: &str,
-        case_sensitivity: CaseSensitivity,
-        max_length: usize,
-    ) -> String {
-        let allowed_set: std::collections::HashSet<char> = allowed_chars.chars().collect();
-
-        let mut result: String = text
-            .chars()
-            .filter(|c| allowed_set.contains(c) || c.is_alphanumeric())
-            .collect();
+    /// Keeps the digits of a phone number, with a leading `+`, a `00` prefix becoming `+`
+    fn clean_phone(text: &str) -> String {
+        let text = text.trim();
+        let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
 
-        result = Self::apply_case_transformation(result, case_sensitivity);
-        Self::truncate_to_length(result, max_length)
-    }
-
-    /// Applies case transformation based on sensitivity setting.
-    fn apply_case_transformation(text: String, case_sensitivity: CaseSensitivity) -> String {
-        match case_sensitivity {
-            CaseSensitivity::CaseSensitive => text.to_lowercase(),
-            CaseSensitivity::CaseInsensitive => text.to_lowercase(),
+        match (text.starts_with('+'), digits.strip_prefix("00")) {
+            (true, _) => format!("+{digits}"),
+            (false, Some(international)) => format!("+{international}"),
+            (false, None) => digits,
         }
     }
 
-    /// Ensures the string starts with a letter, removing leading non-letters.
-    fn ensure_starts_with_letter(text: String) -> String {
-        text.chars().skip_while(|c| !c.is_alphabetic()).collect()
+    /// Removes the leading characters not matching `keep`.
+    fn skip_leading(text: String, keep: fn(char) -> bool) -> String {
+        text.chars().skip_while(|c| !keep(*c)).collect()
     }
 
     /// Removes consecutive occurrences of a specific character.
@@ -269,29 +223,9 @@ impl TextCleaner {
         text.trim_end_matches(target_char).to_string()
     }
 
-    /// Normalizes whitespace by replacing multiple consecutive whitespace characters with single spaces.
+    /// Replaces runs of whitespace with single spaces and trims both ends.
     fn normalize_whitespace(text: String) -> String {
-        let mut result = String::new();
-        let mut prev_was_space = false;
-
-        for ch in text.chars() {
-            if ch.is_whitespace() {
-                if !prev_was_space {
-                    result.push(' '); // Convert all whitespace to regular space
-                    prev_was_space = true;
-                }
-            } else {
-                result.push(ch);
-                prev_was_space = false;
-            }
-        }
-
-        result
-    }
-
-    /// Removes trailing whitespace.
-    fn remove_trailing_whitespace(text: String) -> String {
-        text.trim_end().to_string()
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
     }
 
     /// Truncates string to specified maximum length.
@@ -300,6 +234,82 @@ impl TextCleaner {
     }
 }
 
+/// The presets, usernames and the like are lowercased and limited to 38 chars
+impl From<RegexType> for TextCleaner {
+    fn from(cleaning_type: RegexType) -> Self {
+        let username = |case_sensitivity: CaseSensitivity| {
+            TextCleaner::builder()
+                .allow(CharClass::Alphanumeric)
+                .case(case_sensitivity.into())
+                .prefix(PrefixRule::Letter)
+                .max_length(38)
+        };
+
+        let builder = match cleaning_type {
+            RegexType::Alphabetic(case_sensitivity) => TextCleaner::builder()
+                .allow(CharClass::Alphabetic)
+                .case(case_sensitivity.into())
+                .max_length(38),
+            RegexType::AlphaNumeric(case_sensitivity) => username(case_sensitivity),
+            RegexType::AlphaNumericLoose(case_sensitivity) => {
+                username(case_sensitivity).prefix(PrefixRule::Any)
+            }
+            RegexType::AlphaNumericSpace(case_sensitivity) => username(case_sensitivity)
+                .allow(CharClass::Whitespace)
+                .normalize_whitespace(true),
+            RegexType::AlphaNumericDash(case_sensitivity) => username(case_sensitivity)
+                .allow(CharClass::Char('-'))
+                .collapse('-')
+                .trim_end('-'),
+            RegexType::AlphaNumericDot(case_sensitivity) => username(case_sensitivity)
+                .allow(CharClass::Char('.'))
+                .collapse('.')
+                .trim_end('.'),
+            RegexType::AlphaNumericDashDot(case_sensitivity) => username(case_sensitivity)
+                .allow_chars("-._")
+                .collapse('.')
+                .collapse('-')
+                .trim_end('.')
+                .trim_end('-'),
+            RegexType::AlphaNumericUnderscore(case_sensitivity) => username(case_sensitivity)
+                .allow(CharClass::Char('_'))
+                .collapse('_')
+                .trim_end('_'),
+            RegexType::AlphaNumericDotUnderscore(case_sensitivity) => username(case_sensitivity)
+                .allow_chars("._")
+                .collapse('.')
+                .collapse('_')
+                .trim_end('.')
+                .trim_end('_'),
+            RegexType::Digits => TextCleaner::builder().allow(CharClass::Digit),
+            RegexType::Email => TextCleaner::builder()
+                .allow(CharClass::Alphanumeric)
+                .allow_chars(".@-_")
+                .case(CasePolicy::Lower),
+            RegexType::Phone => TextCleaner::builder().rule(|text| Self::clean_phone(&text)),
+            RegexType::Url => {
+                TextCleaner::builder().allow(CharClass::Predicate(|c| !c.is_whitespace()))
+            }
+            RegexType::Iban | RegexType::Bic => TextCleaner::builder()
+                .allow(CharClass::Predicate(|c| !c.is_whitespace()))
+                .case(CasePolicy::Upper),
+            RegexType::Custom(allowed_chars, case_sensitivity, max_length) => {
+                TextCleaner::builder()
+                    .allow(CharClass::Alphanumeric)
+                    .allow_chars(allowed_chars)
+                    .case(
+                        case_sensitivity
+                            .unwrap_or(CaseSensitivity::CaseSensitive)
+                            .into(),
+                    )
+                    .max_length(max_length)
+            }
+        };
+
+        builder.build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -355,6 +365,77 @@ mod tests {
         assert_eq!(cleaned, "user..email@example.com");
     }
 
+    #[test]
+    fn test_phone_to_e164() {
+        let cases = [
+            ("+234 801-234-5678", None, Some("+2348012345678")),
+            ("0044 (20) 7946.0958", None, Some("+442079460958")),
+            ("0801 234 5678", Some("+234"), Some("+2348012345678")),
+            ("0801 234 5678", None, None),
+            ("415 555 2671", Some("1"), None),
+            ("+0801", None, None),
+        ];
+
+        for (phone, country_code, expected) in cases {
+            assert_eq!(
+                TextCleaner::phone_to_e164(phone, country_code).as_deref(),
+                expected,
+                "{phone}"
+            );
+        }
+
+        assert_eq!(
+            TextCleaner::clean(" 00 1 (415) 555-2671 ", RegexType::Phone),
+            "+14155552671"
+        );
+    }
+
+    #[test]
+    fn test_clean_iban_bic_and_url() {
+        assert_eq!(
+            TextCleaner::clean("gb82 west 1234 5698 7654 32", RegexType::Iban),
+            "GB82WEST12345698765432"
+        );
+        assert_eq!(
+            TextCleaner::clean(" deut de ff ", RegexType::Bic),
+            "DEUTDEFF"
+        );
+        assert_eq!(
+            TextCleaner::clean(" https://example.com/a \n", RegexType::Url),
+            "https://example.com/a"
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let cleaner = TextCleaner::builder()
+            .allow(CharClass::Alphanumeric)
+            .allow_chars("-")
+            .forbid("--")
+            .prefix(PrefixRule::Alphanumeric)
+            .case(CasePolicy::Upper)
+            .max_length(8)
+            .build();
+
+        assert_eq!(cleaner.apply("-ab---c d!"), "AB-CD");
+        assert_eq!(cleaner.apply("abcdefghij"), "ABCDEFGH");
+    }
+
+    #[test]
+    fn test_builder_replacement_and_rules() {
+        let cleaner = TextCleaner::builder()
+            .allow(CharClass::Alphanumeric)
+            .replacement('_')
+            .collapse('_')
+            .trim_end('_')
+            .rule(|text| format!("user_{text}"))
+            .build();
+
+        assert_eq!(cleaner.apply("john  doe!!"), "user_john_doe");
+        // nothing allowed explicitly, everything is kept
+        assert_eq!(TextCleaner::builder().build().apply("a b!"), "a b!");
+    }
+
     #[test]
     fn test_clean_alphanumeric_space() {
         let dirty_text = "User   Name  123!!!";
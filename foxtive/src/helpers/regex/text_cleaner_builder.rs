@@ -0,0 +1,150 @@
+use crate::helpers::regex::CaseSensitivity;
+use crate::helpers::regex::TextCleaner;
+use std::sync::Arc;
+
+/// Characters a [`TextCleaner`] keeps
+#[derive(Debug, Clone)]
+pub enum CharClass {
+    Alphabetic,
+    Alphanumeric,
+    /// ASCII digits
+    Digit,
+    Whitespace,
+    Char(char),
+    /// Any of the given characters
+    Chars(String),
+    Predicate(fn(char) -> bool),
+}
+
+impl CharClass {
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            CharClass::Alphabetic => c.is_alphabetic(),
+            CharClass::Alphanumeric => c.is_alphanumeric(),
+            CharClass::Digit => c.is_ascii_digit(),
+            CharClass::Whitespace => c.is_whitespace(),
+            CharClass::Char(allowed) => c == *allowed,
+            CharClass::Chars(allowed) => allowed.contains(c),
+            CharClass::Predicate(predicate) => predicate(c),
+        }
+    }
+}
+
+/// Case of the cleaned text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CasePolicy {
+    #[default]
+    Preserve,
+    Lower,
+    Upper,
+}
+
+impl From<CaseSensitivity> for CasePolicy {
+    /// Both are lowercased, as the presets always did
+    fn from(_: CaseSensitivity) -> Self {
+        CasePolicy::Lower
+    }
+}
+
+/// Characters the cleaned text may start with, the leading ones that don't are removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrefixRule {
+    #[default]
+    Any,
+    Letter,
+    Alphanumeric,
+}
+
+/// Builds a reusable [`TextCleaner`], see [`TextCleaner::apply`] for the order the rules run in
+#[derive(Default)]
+pub struct TextCleanerBuilder {
+    allowed: Vec<CharClass>,
+    forbidden: Vec<String>,
+    collapse: Vec<char>,
+    trim_end: Vec<char>,
+    prefix: PrefixRule,
+    max_length: Option<usize>,
+    case: CasePolicy,
+    replacement: Option<char>,
+    normalize_whitespace: bool,
+    rules: Vec<crate::helpers::regex::text_cleaner::Rule>,
+}
+
+impl TextCleanerBuilder {
+    /// Keeps the characters of `class`, every character is kept if no class is allowed
+    pub fn allow(mut self, class: CharClass) -> Self {
+        self.allowed.push(class);
+        self
+    }
+
+    pub fn allow_chars(self, chars: &str) -> Self {
+        self.allow(CharClass::Chars(chars.to_string()))
+    }
+
+    /// Removes every occurrence of `sequence`
+    pub fn forbid(mut self, sequence: &str) -> Self {
+        self.forbidden.push(sequence.to_string());
+        self
+    }
+
+    /// Replaces consecutive occurrences of `c` with a single one
+    pub fn collapse(mut self, c: char) -> Self {
+        self.collapse.push(c);
+        self
+    }
+
+    /// Removes the trailing occurrences of `c`
+    pub fn trim_end(mut self, c: char) -> Self {
+        self.trim_end.push(c);
+        self
+    }
+
+    pub fn prefix(mut self, rule: PrefixRule) -> Self {
+        self.prefix = rule;
+        self
+    }
+
+    /// Maximum length, in characters
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn case(mut self, policy: CasePolicy) -> Self {
+        self.case = policy;
+        self
+    }
+
+    /// Replaces the disallowed characters with `c` instead of removing them
+    pub fn replacement(mut self, c: char) -> Self {
+        self.replacement = Some(c);
+        self
+    }
+
+    /// Replaces runs of whitespace with single spaces and trims both ends
+    pub fn normalize_whitespace(mut self, state: bool) -> Self {
+        self.normalize_whitespace = state;
+        self
+    }
+
+    /// Custom rule, run after the built-in ones and before the text is truncated
+    pub fn rule(mut self, rule: impl Fn(String) -> String + Send + Sync + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    pub fn build(self) -> TextCleaner {
+        TextCleaner {
+            allowed: self.allowed,
+            forbidden: self.forbidden,
+            collapse: self.collapse,
+            trim_end: self.trim_end,
+            prefix: self.prefix,
+            max_length: self.max_length,
+            case: self.case,
+            replacement: self.replacement,
+            normalize_whitespace: self.normalize_whitespace,
+            rules: self.rules,
+        }
+    }
+}
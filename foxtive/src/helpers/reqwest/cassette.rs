@@ -0,0 +1,425 @@
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// Replaces the value of redacted headers in fixtures
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers redacted by default, they usually carry credentials
+const DEFAULT_REDACTED_HEADERS: [&str; 5] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+
+/// Whether a [`Cassette`] sends requests or answers them from its fixture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Sends requests and writes the responses to the fixture, replacing its content
+    Record,
+    /// Answers requests from the fixture, without network access
+    Replay,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// A request and the response it got, as stored in fixtures
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+struct Tape {
+    interactions: Vec<Interaction>,
+    /// Whether each interaction was replayed already
+    played: Vec<bool>,
+}
+
+/// Records the responses of an [`HttpClient`](super::HttpClient) to a JSON fixture and
+/// replays them, so tests of code calling third-party APIs run deterministically in CI.
+///
+/// Requests are matched on method, URL and body, in the order they were recorded, so a
+/// request sent twice gets both recorded responses. Headers listed with
+/// [`Cassette::redact_header`], credentials by default, are stored as [`REDACTED`].
+///
+/// Replayed responses don't carry the request URL, `Response::url` isn't the requested one.
+///
+/// # Example
+///
+/// ```no_run
+/// use foxtive::helpers::reqwest::{Cassette, HttpClient};
+///
+/// # async fn run() -> foxtive::prelude::AppResult<()> {
+/// // records on the first run, commit the fixture and it's replayed from then on
+/// let cassette = Cassette::auto("tests/fixtures/github_user.json")?.redact_header("x-github-token");
+///
+/// let client = HttpClient::builder()
+///     .base_url("https://api.github.com")
+///     .cassette(cassette)
+///     .build()?;
+///
+/// let user: serde_json::Value = client.get_json("users/octocat").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    redacted_headers: Vec<String>,
+    tape: Arc<Mutex<Tape>>,
+}
+
+impl Cassette {
+    /// Records to the fixture, replacing its content
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self::with_tape(path, CassetteMode::Record, vec![])
+    }
+
+    /// Replays the fixture
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fixture can't be read or parsed
+    pub fn replay(path: impl AsRef<Path>) -> AppResult<Self> {
+        let content = std::fs::read_to_string(path.as_ref()).map_err(|err| {
+            internal_server_error!(
+                "Failed to read cassette '{}': {err}",
+                path.as_ref().display()
+            )
+        })?;
+
+        let interactions = serde_json::from_str(&content)?;
+        Ok(Self::with_tape(path, CassetteMode::Replay, interactions))
+    }
+
+    /// Replays the fixture if it exists, records it otherwise
+    pub fn auto(path: impl AsRef<Path>) -> AppResult<Self> {
+        match path.as_ref().exists() {
+            true => Self::replay(path),
+            false => Ok(Self::record(path)),
+        }
+    }
+
+    /// Stores the header as [`REDACTED`], in requests and responses
+    pub fn redact_header(mut self, name: &str) -> Self {
+        self.redacted_headers.push(name.to_lowercase());
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.tape.lock().unwrap().interactions.clone()
+    }
+
+    fn with_tape(
+        path: impl AsRef<Path>,
+        mode: CassetteMode,
+        interactions: Vec<Interaction>,
+    ) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode,
+            redacted_headers: DEFAULT_REDACTED_HEADERS.map(String::from).to_vec(),
+            tape: Arc::new(Mutex::new(Tape {
+                played: vec![false; interactions.len()],
+                interactions,
+            })),
+        }
+    }
+
+    /// Sends the request when recording, answers it from the fixture when replaying.
+    ///
+    /// The outer error reports a cassette failure, e.g. an unrecorded request, the inner
+    /// one a transport error, as `RequestBuilder::send` would return it.
+    pub(crate) async fn send(
+        &self,
+        client: &Client,
+        request: RequestBuilder,
+    ) -> AppResult<Result<Response, reqwest::Error>> {
+        let request = match request.build() {
+            Ok(request) => request,
+            Err(err) => return Ok(Err(err)),
+        };
+
+        let recorded = RecordedRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers: self.headers(request.headers()),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| String::from_utf8_lossy(body).into_owned()),
+        };
+
+        match self.mode {
+            CassetteMode::Replay => self.play(&recorded).map(Ok),
+            CassetteMode::Record => {
+                let response = match client.execute(request).await {
+                    Ok(response) => response,
+                    Err(err) => return Ok(Err(err)),
+                };
+
+                let status = response.status().as_u16();
+                let headers = response.headers().clone();
+                let body = match response.bytes().await {
+                    Ok(body) => body,
+                    Err(err) => return Ok(Err(err)),
+                };
+
+                let response = RecordedResponse {
+                    status,
+                    headers: self.headers(&headers),
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                };
+
+                self.store(Interaction {
+                    request: recorded,
+                    response,
+                })?;
+
+                // the live response is returned, redactions only apply to the fixture
+                let mut live = http::Response::builder().status(status);
+                if let Some(live_headers) = live.headers_mut() {
+                    *live_headers = headers;
+                }
+
+                Ok(Ok(Response::from(live.body(body)?)))
+            }
+        }
+    }
+
+    fn play(&self, request: &RecordedRequest) -> AppResult<Response> {
+        let mut tape = self.tape.lock().unwrap();
+        let Tape {
+            interactions,
+            played,
+        } = &mut *tape;
+
+        let index = interactions
+            .iter()
+            .zip(played.iter())
+            .position(|(interaction, played)| {
+                !played
+                    && interaction.request.method == request.method
+                    && interaction.request.url == request.url
+                    && interaction.request.body == request.body
+            })
+            .ok_or_else(|| {
+                internal_server_error!(
+                    "No recorded response for {} {} in cassette '{}'",
+                    request.method,
+                    request.url,
+                    self.path.display()
+                )
+            })?;
+
+        played[index] = true;
+        debug!("[cassette] replaying {} {}", request.method, request.url);
+        to_response(&interactions[index].response)
+    }
+
+    fn store(&self, interaction: Interaction) -> AppResult<()> {
+        let mut tape = self.tape.lock().unwrap();
+        tape.interactions.push(interaction);
+        tape.played.push(true);
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(
+            &self.path,
+            serde_json::to_string_pretty(&tape.interactions)?,
+        )?;
+        Ok(())
+    }
+
+    fn headers(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = match self
+                    .redacted_headers
+                    .iter()
+                    .any(|redacted| redacted == name.as_str())
+                {
+                    true => REDACTED.to_string(),
+                    false => String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                };
+
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+fn to_response(recorded: &RecordedResponse) -> AppResult<Response> {
+    let mut response = http::Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        response = response.header(name, value);
+    }
+
+    Ok(Response::from(response.body(recorded.body.clone())?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::reqwest::HttpClient;
+    use crate::prelude::AppMessage;
+    use reqwest::StatusCode;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answers a single request with the JSON body, returns the server's base URL
+    async fn serve_once(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 4096];
+            let _ = socket.read(&mut buffer).await.unwrap();
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\nset-cookie: session=secret\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        format!("http://{addr}")
+    }
+
+    fn interaction(url: &str, status: u16, body: &str) -> Interaction {
+        Interaction {
+            request: RecordedRequest {
+                method: "GET".to_string(),
+                url: url.to_string(),
+                headers: vec![],
+                body: None,
+            },
+            response: RecordedResponse {
+                status,
+                headers: vec![("content-type".to_string(), "application/json".to_string())],
+                body: body.to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_and_redacts_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixtures/user.json");
+        let base_url = serve_once(r#"{"id":1}"#).await;
+
+        let client = HttpClient::builder()
+            .base_url(&base_url)
+            .bearer_auth("secret-token")
+            .cassette(Cassette::record(&path))
+            .build()
+            .unwrap();
+
+        let user: serde_json::Value = client.get_json("users/1").await.unwrap();
+        assert_eq!(user["id"], 1);
+
+        let fixture = std::fs::read_to_string(&path).unwrap();
+        assert!(!fixture.contains("secret"));
+
+        let interactions: Vec<Interaction> = serde_json::from_str(&fixture).unwrap();
+        assert_eq!(interactions[0].request.url, format!("{base_url}/users/1"));
+        assert!(
+            interactions[0]
+                .request
+                .headers
+                .contains(&("authorization".to_string(), REDACTED.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replays_in_recorded_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+        let url = "https://api.example.com/users/1";
+        let interactions = [
+            interaction(url, 200, r#"{"name":"Ada"}"#),
+            interaction(url, 200, r#"{"name":"Grace"}"#),
+        ];
+        std::fs::write(&path, serde_json::to_string(&interactions).unwrap()).unwrap();
+
+        let client = HttpClient::builder()
+            .base_url("https://api.example.com")
+            .cassette(Cassette::auto(&path).unwrap())
+            .build()
+            .unwrap();
+
+        let first: serde_json::Value = client.get_json("users/1").await.unwrap();
+        let second: serde_json::Value = client.get_json("users/1").await.unwrap();
+        assert_eq!(first["name"], "Ada");
+        assert_eq!(second["name"], "Grace");
+
+        let err = client
+            .get_json::<serde_json::Value>("users/1")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No recorded response"));
+    }
+
+    #[tokio::test]
+    async fn test_replayed_errors_keep_their_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.json");
+        let url = "https://api.example.com/users/2";
+        std::fs::write(
+            &path,
+            serde_json::to_string(&[interaction(url, 404, "gone")]).unwrap(),
+        )
+        .unwrap();
+
+        let client = HttpClient::builder()
+            .cassette(Cassette::replay(&path).unwrap())
+            .build()
+            .unwrap();
+
+        let err = client.get_json::<serde_json::Value>(url).await.unwrap_err();
+        let Some(AppMessage::ReqwestResponseError(err)) = err.downcast_ref::<AppMessage>() else {
+            panic!("expected a response error, got {err:?}");
+        };
+        assert_eq!(*err.code(), StatusCode::NOT_FOUND);
+        assert_eq!(err.body(), "gone");
+    }
+}
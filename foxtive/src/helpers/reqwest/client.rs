@@ -0,0 +1,695 @@
+use super::ReqwestResponseError;
+use crate::context::RequestContext;
+use crate::prelude::AppResult;
+use chrono::{DateTime, Utc};
+use reqwest::header::{
+    AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, RETRY_AFTER,
+};
+use reqwest::{Client, Method, Response, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{Instrument, debug, warn};
+
+/// Default header used to propagate a request id to downstream services.
+pub const DEFAULT_REQUEST_ID_HEADER: &str = "x-request-id";
+
+type TokenProvider = Arc<dyn Fn() -> AppResult<String> + Send + Sync>;
+
+/// Strategy used to attach an `Authorization` header to outgoing requests.
+#[derive(Clone, Default)]
+pub enum HttpAuth {
+    /// No authorization header is sent.
+    #[default]
+    None,
+    /// A static bearer token.
+    Bearer(String),
+    /// A bearer token resolved on every request (e.g. freshly signed JWTs).
+    Provider(TokenProvider),
+}
+
+impl Debug for HttpAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpAuth::None => write!(f, "None"),
+            HttpAuth::Bearer(_) => write!(f, "Bearer(***)"),
+            HttpAuth::Provider(_) => write!(f, "Provider(..)"),
+        }
+    }
+}
+
+/// Retry policy with exponential backoff, applied to 5xx responses, timeouts and connection errors.
+///
+/// Only idempotent methods (`GET`, `HEAD`, `PUT`, `DELETE`, `OPTIONS`) are retried unless
+/// [`RetryPolicy::retry_non_idempotent`] is set, since a `POST` or `PATCH` that timed out may
+/// already have been applied. A `Retry-After` header on 429 and 503 responses takes precedence
+/// over the backoff; when it asks for longer than `max_delay`, the response is returned as is.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Number of retries after the initial attempt (0 disables retries)
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound for any single delay
+    pub max_delay: Duration,
+    /// Also retry non-idempotent methods such as `POST` and `PATCH`
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Computes the delay before the given retry (1-based), doubling each time up to `max_delay`.
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// Returns the policy with retries enabled for non-idempotent methods as well.
+    ///
+    /// Only use it when the server deduplicates requests, e.g. through an idempotency key.
+    pub fn with_non_idempotent_retries(mut self) -> Self {
+        self.retry_non_idempotent = true;
+        self
+    }
+
+    /// Whether requests with the given method may be retried.
+    pub fn should_retry_method(&self, method: &Method) -> bool {
+        self.retry_non_idempotent
+            || matches!(
+                *method,
+                Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+            )
+    }
+
+    /// Whether a response with the given status should be retried.
+    pub fn should_retry_status(&self, status: StatusCode) -> bool {
+        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    /// Whether a transport error should be retried.
+    pub fn should_retry_error(&self, err: &reqwest::Error) -> bool {
+        err.is_timeout() || err.is_connect()
+    }
+}
+
+/// Builder for [`HttpClient`].
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::reqwest::{HttpClient, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let client = HttpClient::builder()
+///     .base_url("https://api.example.com/v1")
+///     .header("x-client", "foxtive")
+///     .bearer_auth("secret-token")
+///     .timeout(Duration::from_secs(10))
+///     .retry_policy(RetryPolicy::default())
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(client.url("users"), "https://api.example.com/v1/users");
+/// ```
+#[derive(Debug, Default)]
+pub struct HttpClientBuilder {
+    base_url: Option<String>,
+    headers: Vec<(String, String)>,
+    auth: HttpAuth,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    request_id_header: Option<String>,
+    #[cfg(feature = "test-utils")]
+    cassette: Option<super::Cassette>,
+}
+
+impl HttpClientBuilder {
+    /// Base URL that relative paths are joined onto
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// Adds a header sent with every request
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sends a static bearer token with every request
+    pub fn bearer_auth(mut self, token: impl Into<String>) -> Self {
+        self.auth = HttpAuth::Bearer(token.into());
+        self
+    }
+
+    /// Resolves a bearer token on every request using the given provider
+    pub fn token_provider<F>(mut self, provider: F) -> Self
+    where
+        F: Fn() -> AppResult<String> + Send + Sync + 'static,
+    {
+        self.auth = HttpAuth::Provider(Arc::new(provider));
+        self
+    }
+
+    /// Signs a fresh token for every request using the `Jwt` helper held in the global state
+    #[cfg(feature = "jwt")]
+    pub fn jwt_auth<C, F>(self, claims: F) -> Self
+    where
+        C: Serialize,
+        F: Fn() -> C + Send + Sync + 'static,
+    {
+        use crate::FOXTIVE;
+        use crate::prelude::AppStateExt;
+
+        self.token_provider(move || {
+            let token = FOXTIVE.helpers().jwt.generate(claims())?;
+            Ok(token.access_token)
+        })
+    }
+
+    /// Signs a fresh token for every request using the given `Jwt` helper, e.g. the one
+    /// of an explicitly passed state
+    #[cfg(feature = "jwt")]
+    pub fn jwt_auth_with<C, F>(self, jwt: Arc<crate::helpers::jwt::Jwt>, claims: F) -> Self
+    where
+        C: Serialize,
+        F: Fn() -> C + Send + Sync + 'static,
+    {
+        self.token_provider(move || {
+            let token = jwt.generate(claims())?;
+            Ok(token.access_token)
+        })
+    }
+
+    /// Sets the retry policy (defaults to [`RetryPolicy::default`])
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Total timeout for a single attempt
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for establishing connections
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// User agent sent with every request
+    pub fn user_agent(mut self, agent: impl Into<String>) -> Self {
+        self.user_agent = Some(agent.into());
+        self
+    }
+
+    /// Header used to propagate the request id of the current
+    /// [`RequestContext`] (defaults to `x-request-id`)
+    pub fn request_id_header(mut self, name: impl Into<String>) -> Self {
+        self.request_id_header = Some(name.into());
+        self
+    }
+
+    /// Records responses to, or replays them from, the cassette's fixture
+    #[cfg(feature = "test-utils")]
+    pub fn cassette(mut self, cassette: super::Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    pub fn build(self) -> AppResult<HttpClient> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+
+        let mut builder = Client::builder().default_headers(headers);
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        if let Some(agent) = &self.user_agent {
+            builder = builder.user_agent(agent);
+        }
+
+        let request_id_header = HeaderName::from_bytes(
+            self.request_id_header
+                .as_deref()
+                .unwrap_or(DEFAULT_REQUEST_ID_HEADER)
+                .as_bytes(),
+        )?;
+
+        Ok(HttpClient {
+            client: builder.build()?,
+            base_url: self
+                .base_url
+                .map(|url| url.trim_end_matches('/').to_string()),
+            auth: self.auth,
+            retry: self.retry,
+            request_id_header,
+            #[cfg(feature = "test-utils")]
+            cassette: self.cassette,
+        })
+    }
+}
+
+/// Configured HTTP client with base URL, default headers, auth injection,
+/// request id propagation and retries with exponential backoff.
+///
+/// Non-2xx responses are turned into [`ReqwestResponseError`].
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    client: Client,
+    base_url: Option<String>,
+    auth: HttpAuth,
+    retry: RetryPolicy,
+    request_id_header: HeaderName,
+    #[cfg(feature = "test-utils")]
+    cassette: Option<super::Cassette>,
+}
+
+impl HttpClient {
+    pub fn builder() -> HttpClientBuilder {
+        HttpClientBuilder::default()
+    }
+
+    /// Returns the underlying reqwest client
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+
+    /// Resolves a path against the base URL; absolute URLs are returned untouched
+    pub fn url(&self, path: &str) -> String {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return path.to_string();
+        }
+
+        match &self.base_url {
+            None => path.to_string(),
+            Some(base) => format!("{}/{}", base, path.trim_start_matches('/')),
+        }
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(&self, path: &str) -> AppResult<T> {
+        let response = self.send(Method::GET, path, None).await?;
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+
+    pub async fn post_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        self.send_json(Method::POST, path, body).await
+    }
+
+    pub async fn put_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        self.send_json(Method::PUT, path, body).await
+    }
+
+    pub async fn patch_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        self.send_json(Method::PATCH, path, body).await
+    }
+
+    pub async fn delete(&self, path: &str) -> AppResult<Response> {
+        self.send(Method::DELETE, path, None).await
+    }
+
+    async fn send_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        let body = serde_json::to_vec(body)?;
+        let response = self.send(method, path, Some(body)).await?;
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+
+    /// Sends a request, retrying according to the configured policy.
+    ///
+    /// Returns the response if it has a success status, otherwise the
+    /// status and body are returned as [`ReqwestResponseError`].
+    pub async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Vec<u8>>,
+    ) -> AppResult<Response> {
+        let url = self.url(path);
+        // Propagate the context of the request being served, if any
+        let context = RequestContext::current().unwrap_or_default();
+        let request_id = &context.request_id;
+        let span = tracing::info_span!("http_request", %method, %url, %request_id);
+
+        async move {
+            let mut retry = 0;
+            loop {
+                let mut request = self
+                    .client
+                    .request(method.clone(), &url)
+                    .headers(self.context_headers(&context));
+
+                if let Some(token) = self.resolve_token()? {
+                    request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+                }
+
+                if let Some(body) = &body {
+                    request = request
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(body.clone());
+                }
+
+                let can_retry =
+                    retry < self.retry.max_retries && self.retry.should_retry_method(&method);
+
+                #[cfg(feature = "test-utils")]
+                let sent = match &self.cassette {
+                    Some(cassette) => cassette.send(&self.client, request).await?,
+                    None => request.send().await,
+                };
+                #[cfg(not(feature = "test-utils"))]
+                let sent = request.send().await;
+
+                match sent {
+                    Ok(response) => {
+                        let status = response.status();
+                        debug!("[http-client] response status: {status}");
+
+                        if status.is_success() {
+                            return Ok(response);
+                        }
+
+                        // Retry-After takes precedence, but waiting past max_delay is not worth it
+                        let delay = match retry_after(&response) {
+                            Some(wait) => (wait <= self.retry.max_delay).then_some(wait),
+                            None => Some(self.retry.delay_for(retry + 1)),
+                        }
+                        .filter(|_| can_retry && self.retry.should_retry_status(status));
+
+                        let Some(delay) = delay else {
+                            let body = response.text().await.unwrap_or_default();
+                            return Err(ReqwestResponseError::make(status, body).into_anyhow());
+                        };
+
+                        warn!("[http-client] request failed with status {status}, retrying");
+                        retry += 1;
+                        tokio::time::sleep(delay).await;
+                    }
+                    Err(err) => {
+                        if !(can_retry && self.retry.should_retry_error(&err)) {
+                            return Err(err.into());
+                        }
+
+                        warn!("[http-client] request error: {err}, retrying");
+                        retry += 1;
+                        tokio::time::sleep(self.retry.delay_for(retry)).await;
+                    }
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// The context headers, with the request id under the configured header
+    fn context_headers(&self, context: &RequestContext) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        context.inject_headers(&mut headers);
+        if let Some(request_id) = headers.remove(RequestContext::REQUEST_ID_HEADER) {
+            headers.insert(self.request_id_header.clone(), request_id);
+        }
+        headers
+    }
+
+    fn resolve_token(&self) -> AppResult<Option<String>> {
+        match &self.auth {
+            HttpAuth::None => Ok(None),
+            HttpAuth::Bearer(token) => Ok(Some(token.clone())),
+            HttpAuth::Provider(provider) => provider().map(Some),
+        }
+    }
+}
+
+/// The delay requested by the `Retry-After` header of a 429 or 503 response, either
+/// in seconds or as an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let status = response.status();
+    if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+        return None;
+    }
+
+    parse_retry_after(
+        response.headers().get(RETRY_AFTER)?.to_str().ok()?,
+        Utc::now(),
+    )
+}
+
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_url_joining() {
+        let client = HttpClient::builder()
+            .base_url("https://api.example.com/v1/")
+            .build()
+            .unwrap();
+
+        assert_eq!(client.url("users"), "https://api.example.com/v1/users");
+        assert_eq!(client.url("/users/1"), "https://api.example.com/v1/users/1");
+        assert_eq!(client.url("https://other.io/x"), "https://other.io/x");
+    }
+
+    #[test]
+    fn test_url_without_base() {
+        let client = HttpClient::builder().build().unwrap();
+        assert_eq!(client.url("https://other.io/x"), "https://other.io/x");
+    }
+
+    #[test]
+    fn test_retry_delay_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            retry_non_idempotent: false,
+        };
+
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(500));
+        assert_eq!(policy.delay_for(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry_status(StatusCode::BAD_GATEWAY));
+        assert!(policy.should_retry_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!policy.should_retry_status(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_only_idempotent_methods_are_retried_by_default() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry_method(&Method::GET));
+        assert!(policy.should_retry_method(&Method::PUT));
+        assert!(policy.should_retry_method(&Method::DELETE));
+        assert!(!policy.should_retry_method(&Method::POST));
+        assert!(!policy.should_retry_method(&Method::PATCH));
+
+        let policy = policy.with_non_idempotent_retries();
+        assert!(policy.should_retry_method(&Method::POST));
+        assert!(policy.should_retry_method(&Method::PATCH));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    /// Answers each connection with the next response and counts the requests received
+    async fn serve(responses: Vec<&'static str>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (format!("http://{addr}"), hits)
+    }
+
+    fn fast_client(base_url: &str) -> HttpClient {
+        HttpClient::builder()
+            .base_url(base_url)
+            .retry_policy(RetryPolicy {
+                max_retries: 2,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_secs(2),
+                retry_non_idempotent: false,
+            })
+            .build()
+            .unwrap()
+    }
+
+    const UNAVAILABLE: &str =
+        "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+    const OK: &str = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{}";
+
+    #[tokio::test]
+    async fn test_post_is_not_retried_by_default() {
+        let (url, hits) = serve(vec![UNAVAILABLE, OK]).await;
+        let client = fast_client(&url);
+
+        let result = client.send(Method::POST, "/orders", None).await;
+        assert!(result.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_honored() {
+        let throttled = "HTTP/1.1 429 Too Many Requests\r\nretry-after: 1\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+        let (url, hits) = serve(vec![throttled, OK]).await;
+        let client = fast_client(&url);
+
+        let started = std::time::Instant::now();
+        client.send(Method::GET, "/orders", None).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_beyond_max_delay_is_not_waited_for() {
+        let throttled = "HTTP/1.1 503 Service Unavailable\r\nretry-after: 3600\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+        let (url, hits) = serve(vec![throttled, OK]).await;
+        let client = fast_client(&url);
+
+        assert!(client.send(Method::GET, "/orders", None).await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_invalid_header_fails_build() {
+        let result = HttpClient::builder().header("bad header", "x").build();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_context_headers_propagate_the_current_context() {
+        let client = HttpClient::builder()
+            .request_id_header("x-trace-id")
+            .build()
+            .unwrap();
+        let context = RequestContext::new().with_user_id("user-7");
+
+        let headers = context
+            .clone()
+            .scope(async { client.context_headers(&RequestContext::current().unwrap_or_default()) })
+            .await;
+
+        assert_eq!(headers["x-trace-id"], context.request_id.as_str());
+        assert_eq!(
+            headers[RequestContext::CORRELATION_ID_HEADER],
+            context.correlation_id.as_str()
+        );
+        assert_eq!(headers[RequestContext::USER_ID_HEADER], "user-7");
+        assert!(!headers.contains_key(RequestContext::REQUEST_ID_HEADER));
+    }
+
+    #[test]
+    fn test_auth_provider_is_resolved() {
+        let client = HttpClient::builder()
+            .token_provider(|| Ok("dynamic".to_string()))
+            .build()
+            .unwrap();
+
+        assert_eq!(client.resolve_token().unwrap(), Some("dynamic".to_string()));
+    }
+}
@@ -1,3 +1,11 @@
+#[cfg(feature = "test-utils")]
+mod cassette;
+mod client;
+
+#[cfg(feature = "test-utils")]
+pub use cassette::*;
+pub use client::*;
+
 use crate::prelude::AppMessage;
 use crate::results::AppResult;
 use reqwest::StatusCode;
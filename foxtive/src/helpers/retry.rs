@@ -0,0 +1,240 @@
+//! Retrying fallible async operations with backoff.
+//!
+//! This module gives one-off operations (an HTTP call in a handler, a flaky
+//! connection attempt) the same retry semantics as supervised tasks, by reusing the
+//! supervisor's [`BackoffStrategy`] to compute delays between attempts.
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::retry::{retry, BackoffStrategy, RetryPolicy};
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::time::Duration;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let policy = RetryPolicy::new(BackoffStrategy::fixed(Duration::from_millis(1)))
+//!     .max_attempts(3)
+//!     .jitter(0.0);
+//!
+//! let calls = AtomicUsize::new(0);
+//! let result = retry(&policy, || async {
+//!     match calls.fetch_add(1, Ordering::SeqCst) {
+//!         0 => Err(anyhow::anyhow!("temporary failure")),
+//!         n => Ok(n),
+//!     }
+//! })
+//! .await;
+//!
+//! assert_eq!(result.unwrap(), 1);
+//! # });
+//! ```
+
+use crate::prelude::AppResult;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+
+pub use foxtive_supervisor::enums::BackoffStrategy;
+
+type RetryPredicate = Arc<dyn Fn(&anyhow::Error) -> bool + Send + Sync>;
+
+/// Describes how many times an operation is attempted and how long to wait in between.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    backoff: BackoffStrategy,
+    max_attempts: usize,
+    jitter: f64,
+    retry_on: Option<RetryPredicate>,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, exponential backoff from 100ms up to 5s, with 20% jitter
+    fn default() -> Self {
+        Self::new(BackoffStrategy::exponential_custom(
+            Duration::from_millis(100),
+            Duration::from_secs(5),
+        ))
+    }
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("backoff", &self.backoff)
+            .field("max_attempts", &self.max_attempts)
+            .field("jitter", &self.jitter)
+            .field("retry_on", &self.retry_on.is_some())
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy with the given backoff, 3 attempts and 20% jitter
+    pub fn new(backoff: BackoffStrategy) -> Self {
+        Self {
+            backoff,
+            max_attempts: 3,
+            jitter: 0.2,
+            retry_on: None,
+        }
+    }
+
+    /// Total number of attempts, including the first one (minimum 1)
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Randomizes each delay by up to the given fraction (0.0 - 1.0) in either direction,
+    /// so that many clients failing at once do not retry in lockstep
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Only retries errors for which the predicate returns `true`, others are returned immediately.
+    ///
+    /// By default every error is retried.
+    pub fn retry_on<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&anyhow::Error) -> bool + Send + Sync + 'static,
+    {
+        self.retry_on = Some(Arc::new(predicate));
+        self
+    }
+
+    pub fn get_max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Delay to wait after the given failed attempt (1-based), jitter included
+    pub fn delay_for(&self, attempt: usize) -> Duration {
+        let delay = self.backoff.calculate_delay(attempt);
+        if self.jitter == 0.0 {
+            return delay;
+        }
+
+        // random factor in [1 - jitter, 1 + jitter]
+        let random = (Uuid::new_v4().as_u128() as u64) as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 + self.jitter * (random * 2.0 - 1.0))
+    }
+
+    /// Whether the given error should be retried
+    pub fn should_retry(&self, err: &anyhow::Error) -> bool {
+        self.retry_on.as_ref().is_none_or(|retry_on| retry_on(err))
+    }
+}
+
+/// Runs the operation until it succeeds, fails with a non-retryable error,
+/// or the policy runs out of attempts, in which case the last error is returned.
+///
+/// # Arguments
+///
+/// * `policy`: attempts, backoff and retry predicate
+/// * `operation`: closure producing a fresh future for each attempt
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, mut operation: F) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < policy.max_attempts && policy.should_retry(&err) => {
+                let delay = policy.delay_for(attempt);
+                warn!(
+                    "[retry] attempt {attempt}/{} failed: {err}, retrying in {delay:?}",
+                    policy.max_attempts
+                );
+
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::AppMessage;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::new(BackoffStrategy::fixed(Duration::from_millis(1))).jitter(0.0)
+    }
+
+    #[tokio::test]
+    async fn test_returns_first_success() {
+        let calls = AtomicUsize::new(0);
+        let result = retry(&fast_policy(), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok("done")
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let calls = AtomicUsize::new(0);
+        let result: AppResult<()> = retry(&fast_policy().max_attempts(4), || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow::anyhow!("failure {n}"))
+        })
+        .await;
+
+        assert_eq!(result.unwrap_err().to_string(), "failure 3");
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_predicate() {
+        let policy = fast_policy().retry_on(|err| {
+            !matches!(
+                err.downcast_ref::<AppMessage>(),
+                Some(AppMessage::Invalid(_))
+            )
+        });
+
+        let calls = AtomicUsize::new(0);
+        let result: AppResult<()> = retry(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(crate::bad_request!("not worth retrying"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_delay_with_jitter_stays_in_bounds() {
+        let policy =
+            RetryPolicy::new(BackoffStrategy::fixed(Duration::from_millis(100))).jitter(0.5);
+        for _ in 0..100 {
+            let delay = policy.delay_for(1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn test_delay_follows_backoff() {
+        let policy = RetryPolicy::new(BackoffStrategy::exponential_custom(
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+        ))
+        .jitter(0.0);
+
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(300));
+    }
+}
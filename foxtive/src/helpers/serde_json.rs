@@ -1,4 +1,6 @@
-use serde::{Deserialize, Deserializer, de};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::de::{DeserializeOwned, IntoDeserializer, Visitor};
+use serde::{Deserialize, Deserializer, Serializer, de};
 use serde_json::Value;
 
 /// Deserializes an optional field that can be either a string or a number into an `Option<String>`.
@@ -157,6 +159,8 @@ where
 /// This is useful for API responses where floating-point values might be represented as:
 /// - A string: `"10.5"` or `"3.14159"`
 /// - A number: `10.5` or `3.14159`
+/// - A formatted string: `"1,234.56"`, `"1 234.56"` or `"12.5%"`, thousands separators
+///   and a trailing percent sign are dropped, `"12.5%"` is `12.5`
 ///
 /// # Errors
 ///
@@ -187,7 +191,7 @@ where
         Value::Number(num) => num
             .as_f64()
             .ok_or_else(|| de::Error::custom("Invalid number")),
-        Value::String(s) => s.parse::<f64>().map_err(de::Error::custom),
+        Value::String(s) => parse_f64(&s).map_err(de::Error::custom),
         _ => Err(de::Error::custom("Expected a number or string")),
     }
 }
@@ -197,6 +201,7 @@ where
 /// This is useful for API responses where floating-point values might be represented as:
 /// - A string: `"10.5"` or `"3.14159"`
 /// - A number: `10.5` or `3.14159`
+/// - A formatted string: `"1,234.56"` or `"12.5%"`, see [`deserialize_f64_from_any`]
 /// - Null or absent: `null` or field not present
 ///
 /// # Errors
@@ -234,7 +239,7 @@ where
             if s.is_empty() {
                 Ok(None)
             } else {
-                s.parse::<f64>().map(Some).map_err(de::Error::custom)
+                parse_f64(&s).map(Some).map_err(de::Error::custom)
             }
         }
         _ => Err(de::Error::custom("Expected a number, string, or null")),
@@ -328,7 +333,7 @@ pub fn deserialize_optional_bool_from_any<'de, D: Deserializer<'de>>(
 /// Useful for API responses where timestamps might be:
 /// - Unix timestamp as number: `1234567890`
 /// - Unix timestamp as string: `"1234567890"`
-/// - ISO 8601 string: `"2023-01-01T00:00:00Z"` (parsed to Unix timestamp)
+/// - ISO 8601 or RFC 2822 string: `"2023-01-01T00:00:00Z"` (parsed to Unix timestamp)
 /// - Null: `null` → `None`
 ///
 /// # Examples
@@ -350,12 +355,9 @@ pub fn deserialize_optional_timestamp<'de, D: Deserializer<'de>>(
     Ok(match value {
         Some(Value::String(s)) => {
             // Try parsing as Unix timestamp first
-            if let Ok(timestamp) = s.parse::<i64>() {
-                Some(timestamp)
-            } else {
-                // Try parsing as ISO 8601 or other date format
-                // You might want to use chrono or time crate for this
-                return Err(de::Error::custom("ISO 8601 parsing not implemented"));
+            match s.parse::<i64>() {
+                Ok(timestamp) => Some(timestamp),
+                Err(_) => Some(parse_datetime(&s).map_err(de::Error::custom)?.timestamp()),
             }
         }
         Some(Value::Number(num)) => Some(
@@ -592,6 +594,406 @@ pub fn deserialize_i64_with_default<'de, D: Deserializer<'de>>(
     }
 }
 
+/// Deserializes a string with its leading and trailing whitespace removed.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use foxtive::helpers::serde_json::deserialize_trimmed_string;
+///
+/// #[derive(Deserialize)]
+/// struct Customer {
+///     #[serde(deserialize_with = "deserialize_trimmed_string")]
+///     name: String,
+/// }
+///
+/// let customer: Customer = serde_json::from_str(r#"{"name": "  Jane Doe\n"}"#).unwrap();
+/// assert_eq!(customer.name, "Jane Doe");
+/// ```
+pub fn deserialize_trimmed_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(value.trim().to_string())
+}
+
+/// Deserializes an optional string with its whitespace trimmed, blank strings are `None`.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use foxtive::helpers::serde_json::deserialize_optional_trimmed_string;
+///
+/// #[derive(Deserialize)]
+/// struct Customer {
+///     #[serde(default, deserialize_with = "deserialize_optional_trimmed_string")]
+///     company: Option<String>, // "   " becomes None
+/// }
+/// ```
+pub fn deserialize_optional_trimmed_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty()))
+}
+
+/// Deserializes a unit enum variant ignoring case and `_`, `-` and space separators,
+/// so `"PENDING"`, `"pending"` and `"Pending"` all match a `Pending` variant.
+///
+/// Variant names are the serialized ones, `#[serde(rename)]` and `rename_all` included.
+///
+/// # Errors
+///
+/// Returns an error if the value is not a string or does not match any variant.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use foxtive::helpers::serde_json::deserialize_enum_case_insensitive;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// #[serde(rename_all = "snake_case")]
+/// enum Status {
+///     InProgress,
+///     Done,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Task {
+///     #[serde(deserialize_with = "deserialize_enum_case_insensitive")]
+///     status: Status,
+/// }
+///
+/// let task: Task = serde_json::from_str(r#"{"status": "IN-PROGRESS"}"#).unwrap();
+/// assert_eq!(task.status, Status::InProgress);
+/// ```
+pub fn deserialize_enum_case_insensitive<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = String::deserialize(deserializer)?;
+    T::deserialize(CaseInsensitiveVariant(value)).map_err(de::Error::custom)
+}
+
+/// Deserializer of a variant name, matched against the variants of the enum deserialized from it
+struct CaseInsensitiveVariant(String);
+
+impl<'de> Deserializer<'de> for CaseInsensitiveVariant {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| !matches!(c, '_' | '-' | ' '))
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        };
+
+        let wanted = normalize(&self.0);
+        match variants.iter().find(|variant| normalize(variant) == wanted) {
+            Some(variant) => visitor.visit_enum(variant.into_deserializer()),
+            // reported as an unknown variant, with the expected ones
+            None => visitor.visit_enum(self.0.into_deserializer()),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Parses a float, dropping thousands separators and a trailing percent sign
+fn parse_f64(text: &str) -> Result<f64, String> {
+    let cleaned: String = text
+        .trim()
+        .trim_end_matches('%')
+        .chars()
+        .filter(|c| !matches!(c, ',' | '_' | ' ' | '\u{a0}'))
+        .collect();
+
+    cleaned
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid number: '{text}'"))
+}
+
+/// Epoch numbers from this value on are milliseconds, below it seconds, it is year 5138 in seconds
+/// and March 1973 in milliseconds.
+const EPOCH_MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Parses a datetime the way external APIs send them:
+/// - Epoch seconds or milliseconds, e.g. `"1700000000"`, `"1700000000123"` or `"1700000000.5"`
+/// - ISO 8601 / RFC 3339, e.g. `"2023-11-14T22:13:20Z"`, `"2023-11-14T23:13:20+01:00"`
+///   or `"2023-11-14T22:13:20.123+0000"`
+/// - ISO 8601 without offset, taken as UTC, e.g. `"2023-11-14 22:13:20"` or `"2023-11-14"`
+/// - RFC 2822, e.g. `"Tue, 14 Nov 2023 22:13:20 +0000"`
+///
+/// # Examples
+///
+/// ```rust
+/// use foxtive::helpers::serde_json::parse_datetime;
+///
+/// let expected = parse_datetime("2023-11-14T22:13:20Z").unwrap();
+/// assert_eq!(parse_datetime("1700000000").unwrap(), expected);
+/// assert_eq!(parse_datetime("Tue, 14 Nov 2023 23:13:20 +0100").unwrap(), expected);
+/// ```
+pub fn parse_datetime(text: &str) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+
+    if let Ok(epoch) = text.parse::<i64>() {
+        return datetime_from_epoch(epoch);
+    }
+
+    if let Ok(seconds) = text.parse::<f64>() {
+        return datetime_from_epoch_f64(seconds);
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Ok(datetime.to_utc());
+    }
+
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f%z",
+        "%Y-%m-%d %H:%M:%S%.f%:z",
+        "%Y-%m-%d %H:%M:%S%.f%z",
+    ] {
+        if let Ok(datetime) = DateTime::parse_from_str(text, format) {
+            return Ok(datetime.to_utc());
+        }
+    }
+
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(datetime.and_utc());
+        }
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(date.and_time(Default::default()).and_utc());
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc2822(text) {
+        return Ok(datetime.to_utc());
+    }
+
+    Err(format!(
+        "Expected epoch seconds or milliseconds, ISO 8601 or RFC 2822 datetime, got '{text}'"
+    ))
+}
+
+/// Deserializes a datetime into a `DateTime<Utc>`, see [`parse_datetime`] for the accepted formats.
+///
+/// Numbers are epoch seconds, or milliseconds when they are at least 100 000 000 000.
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::{DateTime, Utc};
+/// use serde::Deserialize;
+/// use foxtive::helpers::serde_json::deserialize_datetime;
+///
+/// #[derive(Deserialize)]
+/// struct Payment {
+///     #[serde(deserialize_with = "deserialize_datetime")]
+///     paid_at: DateTime<Utc>,
+/// }
+///
+/// let payment: Payment = serde_json::from_str(r#"{"paid_at": 1700000000123}"#).unwrap();
+/// assert_eq!(payment.paid_at.timestamp_millis(), 1700000000123);
+/// ```
+pub fn deserialize_datetime<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DateTime<Utc>, D::Error> {
+    let value: Value = Value::deserialize(deserializer)?;
+    datetime_from_value(value).map_err(de::Error::custom)
+}
+
+/// Deserializes an optional datetime, `null` and empty strings are `None`.
+///
+/// Same as [`deserialize_datetime`] otherwise.
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::{DateTime, Utc};
+/// use serde::Deserialize;
+/// use foxtive::helpers::serde_json::deserialize_optional_datetime;
+///
+/// #[derive(Deserialize)]
+/// struct Payment {
+///     #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+///     refunded_at: Option<DateTime<Utc>>,
+/// }
+/// ```
+pub fn deserialize_optional_datetime<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let value: Option<Value> = Option::deserialize(deserializer)?;
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(value) => datetime_from_value(value)
+            .map(Some)
+            .map_err(de::Error::custom),
+    }
+}
+
+/// Deserializes a datetime into a `NaiveDateTime` in UTC, datetimes with an offset are
+/// converted to UTC. See [`deserialize_datetime`].
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::NaiveDateTime;
+/// use serde::Deserialize;
+/// use foxtive::helpers::serde_json::deserialize_naive_datetime;
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     #[serde(deserialize_with = "deserialize_naive_datetime")]
+///     created_at: NaiveDateTime,
+/// }
+/// ```
+pub fn deserialize_naive_datetime<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<NaiveDateTime, D::Error> {
+    deserialize_datetime(deserializer).map(|datetime| datetime.naive_utc())
+}
+
+/// Optional [`deserialize_naive_datetime`], `null` and empty strings are `None`.
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::NaiveDateTime;
+/// use serde::Deserialize;
+/// use foxtive::helpers::serde_json::deserialize_optional_naive_datetime;
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     #[serde(default, deserialize_with = "deserialize_optional_naive_datetime")]
+///     shipped_at: Option<NaiveDateTime>,
+/// }
+/// ```
+pub fn deserialize_optional_naive_datetime<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDateTime>, D::Error> {
+    deserialize_optional_datetime(deserializer)
+        .map(|datetime| datetime.map(|datetime| datetime.naive_utc()))
+}
+
+/// Serializes a datetime as RFC 3339 in UTC, e.g. `"2023-11-14T22:13:20Z"`,
+/// which every parser reads, whatever its strictness.
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::{DateTime, Utc};
+/// use serde::Serialize;
+/// use foxtive::helpers::serde_json::serialize_datetime;
+///
+/// #[derive(Serialize)]
+/// struct Payment {
+///     #[serde(serialize_with = "serialize_datetime")]
+///     paid_at: DateTime<Utc>,
+/// }
+///
+/// let payment = Payment { paid_at: DateTime::from_timestamp(1700000000, 0).unwrap() };
+/// assert_eq!(serde_json::to_string(&payment).unwrap(), r#"{"paid_at":"2023-11-14T22:13:20Z"}"#);
+/// ```
+pub fn serialize_datetime<S: Serializer>(
+    datetime: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&datetime.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+}
+
+/// Optional [`serialize_datetime`], `None` is `null`.
+pub fn serialize_optional_datetime<S: Serializer>(
+    datetime: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match datetime {
+        Some(datetime) => serialize_datetime(datetime, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Serializes a UTC `NaiveDateTime` as RFC 3339, see [`serialize_datetime`].
+pub fn serialize_naive_datetime<S: Serializer>(
+    datetime: &NaiveDateTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serialize_datetime(&datetime.and_utc(), serializer)
+}
+
+/// Serializes a datetime as epoch seconds.
+pub fn serialize_datetime_as_timestamp<S: Serializer>(
+    datetime: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(datetime.timestamp())
+}
+
+/// Serializes a datetime as epoch milliseconds.
+pub fn serialize_datetime_as_timestamp_millis<S: Serializer>(
+    datetime: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(datetime.timestamp_millis())
+}
+
+fn datetime_from_value(value: Value) -> Result<DateTime<Utc>, String> {
+    match value {
+        Value::String(s) => parse_datetime(&s),
+        Value::Number(num) => match num.as_i64() {
+            Some(epoch) => datetime_from_epoch(epoch),
+            None => datetime_from_epoch_f64(num.as_f64().unwrap_or(f64::NAN)),
+        },
+        _ => Err("Expected string or number".to_string()),
+    }
+}
+
+fn datetime_from_epoch(epoch: i64) -> Result<DateTime<Utc>, String> {
+    let datetime = match epoch.abs() >= EPOCH_MILLIS_THRESHOLD {
+        true => DateTime::from_timestamp_millis(epoch),
+        false => DateTime::from_timestamp(epoch, 0),
+    };
+
+    datetime.ok_or_else(|| format!("Timestamp {epoch} is out of range"))
+}
+
+/// Fractional epoch seconds
+fn datetime_from_epoch_f64(seconds: f64) -> Result<DateTime<Utc>, String> {
+    let millis = (seconds * 1000.0).round();
+    if !millis.is_finite() || millis.abs() >= i64::MAX as f64 {
+        return Err(format!("Timestamp {seconds} is out of range"));
+    }
+
+    DateTime::from_timestamp_millis(millis as i64)
+        .ok_or_else(|| format!("Timestamp {seconds} is out of range"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -790,6 +1192,17 @@ mod tests {
         let result: F64Struct = serde_json::from_value(json).unwrap();
         assert_eq!(result.field, 42.0);
 
+        // Formatted strings
+        for (input, expected) in [
+            ("1,234.56", 1234.56),
+            (" 1 000.5 ", 1000.5),
+            ("12.5%", 12.5),
+        ] {
+            let json = json!({ "field": input });
+            let result: F64Struct = serde_json::from_value(json).unwrap();
+            assert_eq!(result.field, expected, "{input}");
+        }
+
         // Invalid string
         let json = json!({ "field": "not_a_float" });
         assert!(serde_json::from_value::<F64Struct>(json).is_err());
@@ -895,6 +1308,143 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_timestamp_from_iso8601() {
+        let json = r#"{"value": "2023-11-14T22:13:20Z"}"#;
+        let result: TimestampTest = serde_json::from_str(json).unwrap();
+        assert_eq!(result.value, Some(1700000000));
+    }
+
+    #[test]
+    fn test_trimmed_strings() {
+        #[derive(Deserialize)]
+        struct Trimmed {
+            #[serde(deserialize_with = "deserialize_trimmed_string")]
+            name: String,
+            #[serde(default, deserialize_with = "deserialize_optional_trimmed_string")]
+            company: Option<String>,
+        }
+
+        let result: Trimmed =
+            serde_json::from_value(json!({"name": "  Jane ", "company": " Acme\t"})).unwrap();
+        assert_eq!(result.name, "Jane");
+        assert_eq!(result.company.as_deref(), Some("Acme"));
+
+        let result: Trimmed = serde_json::from_value(json!({"name": "", "company": "  "})).unwrap();
+        assert_eq!(result.name, "");
+        assert_eq!(result.company, None);
+    }
+
+    #[test]
+    fn test_enum_case_insensitive() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Status {
+            Pending,
+            #[serde(rename = "in_review")]
+            InReview,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Task {
+            #[serde(deserialize_with = "deserialize_enum_case_insensitive")]
+            status: Status,
+        }
+
+        for (input, expected) in [
+            ("Pending", Status::Pending),
+            ("PENDING", Status::Pending),
+            ("pending", Status::Pending),
+            ("IN_REVIEW", Status::InReview),
+            ("in-review", Status::InReview),
+        ] {
+            let task: Task = serde_json::from_value(json!({ "status": input })).unwrap();
+            assert_eq!(task.status, expected, "{input}");
+        }
+
+        let err = serde_json::from_value::<Task>(json!({"status": "done"})).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `done`"), "{err}");
+        assert!(serde_json::from_value::<Task>(json!({"status": 1})).is_err());
+    }
+
+    // Tests for the datetime deserializers
+    #[derive(Deserialize, serde::Serialize, Debug, PartialEq)]
+    struct DateTimeTest {
+        #[serde(
+            deserialize_with = "deserialize_datetime",
+            serialize_with = "serialize_datetime"
+        )]
+        value: DateTime<Utc>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct OptionalNaiveDateTimeTest {
+        #[serde(default, deserialize_with = "deserialize_optional_naive_datetime")]
+        value: Option<NaiveDateTime>,
+    }
+
+    #[test]
+    fn test_datetime_formats() {
+        let expected = DateTime::from_timestamp(1700000000, 0).unwrap();
+
+        for value in [
+            json!(1700000000),
+            json!(1700000000000_i64),
+            json!(1700000000.0),
+            json!("1700000000"),
+            json!("1700000000000"),
+            json!("2023-11-14T22:13:20Z"),
+            json!("2023-11-14T23:13:20+01:00"),
+            json!("2023-11-14T22:13:20.000+0000"),
+            json!("2023-11-14 22:13:20"),
+            json!("2023-11-14T22:13:20"),
+            json!("Tue, 14 Nov 2023 22:13:20 +0000"),
+            json!("Tue, 14 Nov 2023 22:13:20 GMT"),
+        ] {
+            let result: DateTimeTest = serde_json::from_value(json!({ "value": value })).unwrap();
+            assert_eq!(result.value, expected, "{value}");
+        }
+
+        let result: DateTimeTest = serde_json::from_value(json!({"value": "2023-11-14"})).unwrap();
+        assert_eq!(result.value.timestamp(), 1699920000);
+
+        let result: DateTimeTest = serde_json::from_value(json!({"value": 1700000000.25})).unwrap();
+        assert_eq!(result.value.timestamp_millis(), 1700000000250);
+    }
+
+    #[test]
+    fn test_datetime_invalid() {
+        for value in [json!("yesterday"), json!(true), json!("2023-13-45")] {
+            let result = serde_json::from_value::<DateTimeTest>(json!({ "value": value }));
+            assert!(result.is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_datetime_serialize() {
+        let value = DateTimeTest {
+            value: DateTime::from_timestamp_millis(1700000000123).unwrap(),
+        };
+
+        let json = serde_json::to_value(&value).unwrap();
+        assert_eq!(json, json!({"value": "2023-11-14T22:13:20.123Z"}));
+        assert_eq!(serde_json::from_value::<DateTimeTest>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_optional_naive_datetime() {
+        let result: OptionalNaiveDateTimeTest =
+            serde_json::from_str(r#"{"value": "2023-11-14T23:13:20+01:00"}"#).unwrap();
+        assert_eq!(
+            result.value,
+            Some(DateTime::from_timestamp(1700000000, 0).unwrap().naive_utc())
+        );
+
+        for json in [r#"{"value": null}"#, r#"{"value": ""}"#, "{}"] {
+            let result: OptionalNaiveDateTimeTest = serde_json::from_str(json).unwrap();
+            assert_eq!(result.value, None);
+        }
+    }
+
     // Tests for deserialize_vec_from_string_or_array
     #[test]
     fn test_vec_from_array() {
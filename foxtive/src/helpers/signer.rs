@@ -0,0 +1,176 @@
+//! Signed, expiring URLs and temporary tokens.
+//!
+//! This module builds on the [`Hmac`] helper to produce links that can be handed
+//! out publicly (download links, email verification, password reset) and checked
+//! later without any server-side storage.
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::signer::Signer;
+//! use std::time::Duration;
+//!
+//! let signer = Signer::new("app-key");
+//!
+//! let url = signer.sign("/downloads/report.pdf?user=1", Duration::from_secs(600)).unwrap();
+//! assert!(url.starts_with("/downloads/report.pdf?user=1&expires="));
+//! assert!(signer.verify(&url).is_ok());
+//!
+//! let token = signer.sign_token("user:1", Duration::from_secs(600)).unwrap();
+//! assert_eq!(signer.verify_token(&token).unwrap(), "user:1");
+//! ```
+
+use crate::helpers::hmac::{HashFunc, Hmac, constant_time_eq};
+use crate::prelude::AppResult;
+use crate::{bad_request, unauthorized};
+use chrono::Utc;
+use std::time::Duration;
+
+const EXPIRES_PARAM: &str = "expires";
+const SIGNATURE_PARAM: &str = "signature";
+
+/// Creates and verifies HMAC-signed URLs and tokens with an expiry time.
+#[derive(Clone)]
+pub struct Signer {
+    hmac: Hmac,
+}
+
+impl Signer {
+    /// Creates a signer using HMAC-SHA256 with the given secret
+    pub fn new(secret: &str) -> Self {
+        Self::with_hash(secret, HashFunc::Sha256)
+    }
+
+    /// Creates a signer using the given hash function
+    pub fn with_hash(secret: &str, func: HashFunc) -> Self {
+        Self {
+            hmac: Hmac::new(secret, func),
+        }
+    }
+
+    /// Signs a path or URL, appending `expires` and `signature` query parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: path or full URL, may already contain a query string
+    /// * `ttl`: how long the signed URL remains valid
+    pub fn sign(&self, path: &str, ttl: Duration) -> AppResult<String> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let unsigned = format!("{path}{separator}{EXPIRES_PARAM}={}", Self::expires_at(ttl));
+
+        let signature = self.hmac.hash(&unsigned)?;
+        Ok(format!("{unsigned}&{SIGNATURE_PARAM}={signature}"))
+    }
+
+    /// Verifies a URL produced by [`Signer::sign`], failing when it has been tampered with or expired
+    pub fn verify(&self, signed: &str) -> AppResult<()> {
+        let marker = format!("&{SIGNATURE_PARAM}=");
+        let (unsigned, signature) = signed
+            .rsplit_once(&marker)
+            .ok_or_else(|| bad_request!("url is not signed"))?;
+
+        self.check_signature(unsigned, signature)?;
+
+        let expires = unsigned
+            .rsplit_once(&format!("{EXPIRES_PARAM}="))
+            .and_then(|(_, ts)| ts.parse::<i64>().ok())
+            .ok_or_else(|| bad_request!("signed url has no valid expiry"))?;
+
+        Self::check_expiry(expires)
+    }
+
+    /// Creates a token carrying the given payload, valid for `ttl`.
+    ///
+    /// The payload is hex encoded, not encrypted, so it must not contain secrets.
+    pub fn sign_token(&self, payload: &str, ttl: Duration) -> AppResult<String> {
+        let unsigned = format!("{}.{}", hex::encode(payload), Self::expires_at(ttl));
+        let signature = self.hmac.hash(&unsigned)?;
+        Ok(format!("{unsigned}.{signature}"))
+    }
+
+    /// Verifies a token produced by [`Signer::sign_token`], returning its payload
+    pub fn verify_token(&self, token: &str) -> AppResult<String> {
+        let (unsigned, signature) = token
+            .rsplit_once('.')
+            .ok_or_else(|| bad_request!("malformed token"))?;
+
+        self.check_signature(unsigned, signature)?;
+
+        let (payload, expires) = unsigned
+            .split_once('.')
+            .ok_or_else(|| bad_request!("malformed token"))?;
+
+        Self::check_expiry(expires.parse()?)?;
+
+        Ok(String::from_utf8(hex::decode(payload)?)?)
+    }
+
+    fn check_signature(&self, unsigned: &str, signature: &str) -> AppResult<()> {
+        let expected = self.hmac.hash(unsigned)?;
+        match constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+            true => Ok(()),
+            false => Err(unauthorized!("invalid signature")),
+        }
+    }
+
+    fn check_expiry(expires: i64) -> AppResult<()> {
+        match Utc::now().timestamp() <= expires {
+            true => Ok(()),
+            false => Err(unauthorized!("signature has expired")),
+        }
+    }
+
+    fn expires_at(ttl: Duration) -> i64 {
+        Utc::now().timestamp() + ttl.as_secs() as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify_url() {
+        let signer = Signer::new("secret");
+        let url = signer.sign("/files/1", Duration::from_secs(60)).unwrap();
+
+        assert!(url.starts_with("/files/1?expires="));
+        assert!(signer.verify(&url).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_url_is_rejected() {
+        let signer = Signer::new("secret");
+        let url = signer.sign("/files/1", Duration::from_secs(60)).unwrap();
+
+        assert!(signer.verify(&url.replace("/files/1", "/files/2")).is_err());
+        assert!(Signer::new("other").verify(&url).is_err());
+        assert!(signer.verify("/files/1").is_err());
+    }
+
+    #[test]
+    fn test_expired_url_is_rejected() {
+        let signer = Signer::new("secret");
+        let unsigned = format!("/files/1?expires={}", Utc::now().timestamp() - 10);
+        let signature = signer.hmac.hash(&unsigned).unwrap();
+
+        let err = signer
+            .verify(&format!("{unsigned}&signature={signature}"))
+            .unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+
+    #[test]
+    fn test_token_roundtrip() {
+        let signer = Signer::new("secret");
+        let token = signer
+            .sign_token("verify:john@example.com", Duration::from_secs(60))
+            .unwrap();
+
+        assert_eq!(
+            signer.verify_token(&token).unwrap(),
+            "verify:john@example.com"
+        );
+        assert!(signer.verify_token(&format!("00{token}")).is_err());
+    }
+}
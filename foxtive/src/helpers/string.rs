@@ -1,3 +1,7 @@
+#[cfg(feature = "unicode")]
+use deunicode::deunicode;
+#[cfg(feature = "unicode")]
+use unicode_segmentation::UnicodeSegmentation;
 use uuid::Uuid;
 
 pub struct Str;
@@ -85,6 +89,124 @@ impl Str {
         result
     }
 
+    /// Converts any casing (camelCase, PascalCase, kebab-case, spaced words) to snake_case
+    pub fn snake_case(s: &str) -> String {
+        Self::words(s)
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+
+    /// Converts any casing to kebab-case
+    pub fn kebab_case(s: &str) -> String {
+        Self::words(s)
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Converts any casing to PascalCase
+    pub fn pascal_case(s: &str) -> String {
+        Self::words(s)
+            .iter()
+            .map(|w| Self::uc_first(&w.to_lowercase()))
+            .collect()
+    }
+
+    /// Converts any casing to space separated Title Case
+    pub fn title_case(s: &str) -> String {
+        Self::words(s)
+            .iter()
+            .map(|w| Self::uc_first(&w.to_lowercase()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Generates a URL-friendly slug, transliterating non-ASCII characters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::string::Str;
+    ///
+    /// assert_eq!(Str::slugify("Héllo, Wörld!"), "hello-world");
+    /// assert_eq!(Str::slugify("  Crème brûlée & Co.  "), "creme-brulee-co");
+    /// assert_eq!(Str::slugify("北京"), "bei-jing");
+    /// ```
+    #[cfg(feature = "unicode")]
+    pub fn slugify(s: &str) -> String {
+        let mut slug = String::with_capacity(s.len());
+        for c in deunicode(s).chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+
+        slug.trim_end_matches('-').to_string()
+    }
+
+    /// Truncates a string to at most `max_graphemes` user-perceived characters,
+    /// adding ellipsis if truncated, without splitting emojis or combining marks
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use foxtive::helpers::string::Str;
+    ///
+    /// assert_eq!(Str::truncate_graphemes("héllo wörld", 5), "héllo...");
+    /// assert_eq!(Str::truncate_graphemes("👨‍👩‍👧 family", 1), "👨‍👩‍👧...");
+    /// assert_eq!(Str::truncate_graphemes("short", 10), "short");
+    /// ```
+    #[cfg(feature = "unicode")]
+    pub fn truncate_graphemes(s: &str, max_graphemes: usize) -> String {
+        match s.grapheme_indices(true).nth(max_graphemes) {
+            Some((index, _)) => format!("{}...", &s[..index]),
+            None => s.to_string(),
+        }
+    }
+
+    /// Splits a string into words on separators and case boundaries,
+    /// keeping acronyms together (`"HTTPServer2Go"` => `["HTTP", "Server2", "Go"]`)
+    fn words(s: &str) -> Vec<String> {
+        let mut words = vec![];
+        let mut current = String::new();
+        let chars: Vec<char> = s.chars().collect();
+
+        for (i, &c) in chars.iter().enumerate() {
+            if !c.is_alphanumeric() {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+
+            if c.is_uppercase()
+                && let Some(&prev) = i.checked_sub(1).and_then(|p| chars.get(p))
+            {
+                let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+                let boundary = prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_is_lower);
+
+                if boundary && !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+
+            current.push(c);
+        }
+
+        if !current.is_empty() {
+            words.push(current);
+        }
+
+        words
+    }
+
     /// Pads the string to the left with a specified character until it reaches the given length
     pub fn pad_left(s: &str, width: usize, pad_char: char) -> String {
         if s.len() >= width {
@@ -212,6 +334,42 @@ mod tests {
         assert_eq!(Str::camel_case(""), "");
     }
 
+    #[test]
+    fn test_case_conversions() {
+        assert_eq!(Str::snake_case("helloWorld"), "hello_world");
+        assert_eq!(Str::snake_case("HTTPServerError"), "http_server_error");
+        assert_eq!(Str::snake_case("user-id 2"), "user_id_2");
+        assert_eq!(
+            Str::kebab_case("UserProfile_settings"),
+            "user-profile-settings"
+        );
+        assert_eq!(Str::pascal_case("hello_world"), "HelloWorld");
+        assert_eq!(Str::pascal_case("api-key"), "ApiKey");
+        assert_eq!(Str::title_case("created_at"), "Created At");
+        assert_eq!(Str::title_case("firstName"), "First Name");
+        assert_eq!(Str::snake_case(""), "");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_slugify() {
+        assert_eq!(Str::slugify("Hello World"), "hello-world");
+        assert_eq!(Str::slugify("--Ünïcödé   Title--"), "unicode-title");
+        assert_eq!(Str::slugify("Ærøskøbing"), "aeroskobing");
+        assert_eq!(Str::slugify("!!!"), "");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_truncate_graphemes() {
+        assert_eq!(Str::truncate_graphemes("hello", 3), "hel...");
+        assert_eq!(
+            Str::truncate_graphemes("e\u{301}e\u{301}e\u{301}", 2),
+            "e\u{301}e\u{301}..."
+        );
+        assert_eq!(Str::truncate_graphemes("", 3), "");
+    }
+
     #[test]
     fn test_pad_left() {
         assert_eq!(Str::pad_left("123", 5, '0'), "00123");
@@ -316,6 +474,21 @@ mod ext_tests {
         assert_eq!(String::from("foo_bar_baz").camel_case(), "fooBarBaz");
     }
 
+    #[test]
+    fn test_case_conversion_ext() {
+        assert_eq!("userId".snake_case(), "user_id");
+        assert_eq!(String::from("user_id").kebab_case(), "user-id");
+        assert_eq!("user id".pascal_case(), "UserId");
+        assert_eq!("user_id".title_case(), "User Id");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_unicode_ext() {
+        assert_eq!(String::from("Çà va?").slugify(), "ca-va");
+        assert_eq!("añb".truncate_graphemes(2), "añ...");
+    }
+
     #[test]
     fn test_pad_left_ext() {
         assert_eq!("123".pad_left(5, '0'), "00123");
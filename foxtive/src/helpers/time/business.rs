@@ -0,0 +1,222 @@
+use crate::invalid;
+use crate::prelude::AppResult;
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::collections::HashSet;
+
+/// Longest run of non-business days searched through before giving up,
+/// so a calendar without business days can't loop forever
+const MAX_NON_BUSINESS_DAYS: u32 = 366;
+
+/// Decides which days are worked, used by business-day arithmetic.
+///
+/// Only [`BusinessCalendar::is_holiday`] needs to be implemented, weekends default to
+/// Saturday and Sunday.
+pub trait BusinessCalendar {
+    fn is_holiday(&self, date: NaiveDate) -> bool;
+
+    fn is_weekend(&self, date: NaiveDate) -> bool {
+        matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+    }
+
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.is_weekend(date) && !self.is_holiday(date)
+    }
+}
+
+/// Calendar without holidays, only weekends are skipped
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeekendCalendar;
+
+impl BusinessCalendar for WeekendCalendar {
+    fn is_holiday(&self, _date: NaiveDate) -> bool {
+        false
+    }
+}
+
+/// Calendar skipping weekends and a fixed list of holidays
+#[derive(Debug, Clone, Default)]
+pub struct HolidayCalendar {
+    holidays: HashSet<NaiveDate>,
+}
+
+impl HolidayCalendar {
+    pub fn new(holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        Self {
+            holidays: holidays.into_iter().collect(),
+        }
+    }
+
+    pub fn holiday(mut self, date: NaiveDate) -> Self {
+        self.holidays.insert(date);
+        self
+    }
+}
+
+impl BusinessCalendar for HolidayCalendar {
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+}
+
+/// Moves `days` business days forward (or backward when negative) from `date`
+///
+/// # Errors
+///
+/// Returns an error when the date goes out of range, or when no business day is
+/// found within a year of non-business days
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use foxtive::helpers::time::{add_business_days, HolidayCalendar, WeekendCalendar};
+///
+/// let friday = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+/// let monday = NaiveDate::from_ymd_opt(2024, 12, 23).unwrap();
+/// assert_eq!(add_business_days(friday, 1, &WeekendCalendar).unwrap(), monday);
+///
+/// let christmas = HolidayCalendar::new([
+///     NaiveDate::from_ymd_opt(2024, 12, 25).unwrap(),
+///     NaiveDate::from_ymd_opt(2024, 12, 26).unwrap(),
+/// ]);
+/// let expected = NaiveDate::from_ymd_opt(2024, 12, 27).unwrap();
+/// assert_eq!(add_business_days(monday, 2, &christmas).unwrap(), expected);
+/// ```
+pub fn add_business_days(
+    date: NaiveDate,
+    days: i64,
+    calendar: &impl BusinessCalendar,
+) -> AppResult<NaiveDate> {
+    let mut current = date;
+    let mut remaining = days.unsigned_abs();
+    let mut skipped = 0;
+
+    while remaining > 0 {
+        current = match days > 0 {
+            true => current.succ_opt(),
+            false => current.pred_opt(),
+        }
+        .ok_or_else(|| invalid!("date out of range adding {days} business days to {date}"))?;
+
+        match calendar.is_business_day(current) {
+            true => {
+                remaining -= 1;
+                skipped = 0;
+            }
+            false => {
+                skipped += 1;
+                if skipped >= MAX_NON_BUSINESS_DAYS {
+                    return Err(invalid!(
+                        "no business day found within {MAX_NON_BUSINESS_DAYS} days of {current}"
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(current)
+}
+
+/// Counts business days from `start` (inclusive) to `end` (exclusive),
+/// negative when `end` is before `start`
+pub fn business_days_between(
+    start: NaiveDate,
+    end: NaiveDate,
+    calendar: &impl BusinessCalendar,
+) -> i64 {
+    if end < start {
+        return -business_days_between(end, start, calendar);
+    }
+
+    start
+        .iter_days()
+        .take_while(|day| *day < end)
+        .filter(|day| calendar.is_business_day(*day))
+        .count() as i64
+}
+
+/// Returns `date` if it is a business day, otherwise the next one
+pub fn next_business_day(
+    date: NaiveDate,
+    calendar: &impl BusinessCalendar,
+) -> AppResult<NaiveDate> {
+    match calendar.is_business_day(date) {
+        true => Ok(date),
+        false => add_business_days(date, 1, calendar),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_add_business_days_skips_weekends() {
+        // 2024-06-07 is a Friday
+        assert_eq!(
+            add_business_days(date(2024, 6, 7), 1, &WeekendCalendar).unwrap(),
+            date(2024, 6, 10)
+        );
+        assert_eq!(
+            add_business_days(date(2024, 6, 10), -1, &WeekendCalendar).unwrap(),
+            date(2024, 6, 7)
+        );
+        assert_eq!(
+            add_business_days(date(2024, 6, 8), 0, &WeekendCalendar).unwrap(),
+            date(2024, 6, 8)
+        );
+        assert_eq!(
+            add_business_days(date(2024, 6, 3), 10, &WeekendCalendar).unwrap(),
+            date(2024, 6, 17)
+        );
+    }
+
+    #[test]
+    fn test_holidays_are_skipped() {
+        let calendar = HolidayCalendar::default().holiday(date(2024, 6, 10));
+        assert_eq!(
+            add_business_days(date(2024, 6, 7), 1, &calendar).unwrap(),
+            date(2024, 6, 11)
+        );
+        assert_eq!(
+            next_business_day(date(2024, 6, 8), &calendar).unwrap(),
+            date(2024, 6, 11)
+        );
+    }
+
+    #[test]
+    fn test_business_days_between() {
+        let calendar = HolidayCalendar::new([date(2024, 6, 12)]);
+        assert_eq!(
+            business_days_between(date(2024, 6, 3), date(2024, 6, 17), &WeekendCalendar),
+            10
+        );
+        assert_eq!(
+            business_days_between(date(2024, 6, 10), date(2024, 6, 15), &calendar),
+            4
+        );
+        assert_eq!(
+            business_days_between(date(2024, 6, 15), date(2024, 6, 10), &calendar),
+            -4
+        );
+    }
+
+    #[test]
+    fn test_calendar_without_business_days_is_an_error() {
+        struct Closed;
+
+        impl BusinessCalendar for Closed {
+            fn is_holiday(&self, _date: NaiveDate) -> bool {
+                true
+            }
+        }
+
+        assert!(add_business_days(date(2024, 6, 7), 1, &Closed).is_err());
+        assert!(next_business_day(date(2024, 6, 7), &Closed).is_err());
+        assert!(add_business_days(NaiveDate::MAX, 1, &WeekendCalendar).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::invalid;
+use crate::prelude::AppResult;
+use std::time::Duration;
+
+const UNITS: [(&str, u64); 5] = [
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("m", 60_000),
+    ("s", 1_000),
+    ("ms", 1),
+];
+
+/// Parses durations written as a sequence of `<number><unit>` pairs, e.g. `"1h30m"`,
+/// `"2d 4h"`, `"90s"` or `"250ms"`. Supported units are `d`, `h`, `m`, `s` and `ms`.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::time::parse_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+/// assert_eq!(parse_duration("1m 500ms").unwrap(), Duration::from_millis(60_500));
+/// assert!(parse_duration("10 minutes").is_err());
+/// ```
+pub fn parse_duration(input: &str) -> AppResult<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(invalid!("empty duration"));
+    }
+
+    let mut total = 0u64;
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(invalid!("invalid duration '{input}': expected a number"));
+        }
+
+        let value: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| invalid!("duration '{input}' is too large"))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        rest = rest[unit_len..].trim_start();
+
+        let millis = UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, millis)| *millis)
+            .ok_or_else(|| invalid!("invalid duration '{input}': unknown unit '{unit}'"))?;
+
+        total = value
+            .checked_mul(millis)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(|| invalid!("duration '{input}' is too large"))?;
+    }
+
+    Ok(Duration::from_millis(total))
+}
+
+/// Formats a duration in the compact form accepted by [`parse_duration`], e.g. `"1h30m"`
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::time::format_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+/// assert_eq!(format_duration(Duration::ZERO), "0s");
+/// ```
+pub fn format_duration(duration: Duration) -> String {
+    let mut remaining = duration.as_millis() as u64;
+    if remaining == 0 {
+        return "0s".to_string();
+    }
+
+    let mut out = String::new();
+    for (unit, millis) in UNITS {
+        let value = remaining / millis;
+        if value > 0 {
+            out.push_str(&format!("{value}{unit}"));
+            remaining %= millis;
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(
+            parse_duration("2d4h").unwrap(),
+            Duration::from_secs(2 * 86400 + 4 * 3600)
+        );
+        assert_eq!(
+            parse_duration(" 1h 1m 1s ").unwrap(),
+            Duration::from_secs(3661)
+        );
+        assert_eq!(parse_duration("0m").unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("10y").is_err());
+        assert!(parse_duration("99999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_format_duration_roundtrip() {
+        for input in ["1d2h3m4s5ms", "90m", "250ms"] {
+            let duration = parse_duration(input).unwrap();
+            assert_eq!(
+                parse_duration(&format_duration(duration)).unwrap(),
+                duration
+            );
+        }
+
+        assert_eq!(format_duration(Duration::from_secs(90 * 60)), "1h30m");
+    }
+}
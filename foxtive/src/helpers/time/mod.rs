@@ -0,0 +1,148 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{Datelike, Days, Local, NaiveDate, NaiveDateTime, TimeDelta, Utc, Weekday};
+use serde::{Deserialize, Deserializer, Serializer};
+
+mod business;
+mod duration;
+
+pub use business::{
+    BusinessCalendar, HolidayCalendar, WeekendCalendar, add_business_days, business_days_between,
+    next_business_day,
+};
+pub use duration::{format_duration, parse_duration};
+
+pub fn now_plus_seconds(sec: i64) -> NaiveDateTime {
+    (Local::now() + TimeDelta::try_seconds(sec).unwrap()).naive_local()
+}
+
+pub fn now_plus_minutes(min: i64) -> NaiveDateTime {
+    now_plus_seconds(min * 60)
+}
+
+pub fn current_datetime() -> NaiveDateTime {
+    Local::now().naive_local()
+}
+
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before Unix epoch")
+        .as_secs()
+}
+
+pub fn serde_de_datetime<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S").map_err(serde::de::Error::custom)
+}
+
+pub fn serde_se_datetime<S>(date: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let formatted_date = date.format("%Y-%m-%d %H:%M:%S").to_string();
+    serializer.serialize_str(&formatted_date)
+}
+
+/// Describes a time difference in words, e.g. `"2 hours ago"` or `"in 3 days"`.
+///
+/// Positive deltas are in the past, differences under a minute are `"just now"`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::TimeDelta;
+/// use foxtive::helpers::time::humanize;
+///
+/// assert_eq!(humanize(TimeDelta::hours(2)), "2 hours ago");
+/// assert_eq!(humanize(TimeDelta::days(-1)), "in 1 day");
+/// assert_eq!(humanize(TimeDelta::seconds(20)), "just now");
+/// ```
+pub fn humanize(delta: TimeDelta) -> String {
+    let seconds = delta.num_seconds().unsigned_abs();
+    if seconds < 60 {
+        return "just now".to_string();
+    }
+
+    let (value, unit) = match seconds {
+        s if s < 3_600 => (s / 60, "minute"),
+        s if s < 86_400 => (s / 3_600, "hour"),
+        s if s < 604_800 => (s / 86_400, "day"),
+        s if s < 2_592_000 => (s / 604_800, "week"),
+        s if s < 31_536_000 => (s / 2_592_000, "month"),
+        s => (s / 31_536_000, "year"),
+    };
+
+    let plural = if value == 1 { "" } else { "s" };
+    match delta > TimeDelta::zero() {
+        true => format!("{value} {unit}{plural} ago"),
+        false => format!("in {value} {unit}{plural}"),
+    }
+}
+
+/// Describes how long ago (or ahead) a UTC datetime is, relative to now
+pub fn humanize_since(datetime: NaiveDateTime) -> String {
+    humanize(Utc::now().naive_utc() - datetime)
+}
+
+/// Returns the ISO 8601 week-numbering year and week number of a date
+pub fn iso_week(date: NaiveDate) -> (i32, u32) {
+    let week = date.iso_week();
+    (week.year(), week.week())
+}
+
+/// Returns the Monday of the ISO week containing the date
+pub fn start_of_iso_week(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+/// Returns the Sunday of the ISO week containing the date
+pub fn end_of_iso_week(date: NaiveDate) -> NaiveDate {
+    start_of_iso_week(date) + Days::new(6)
+}
+
+/// Returns the Monday of the given ISO week, if it exists
+pub fn from_iso_week(year: i32, week: u32) -> Option<NaiveDate> {
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_humanize() {
+        assert_eq!(humanize(TimeDelta::minutes(1)), "1 minute ago");
+        assert_eq!(humanize(TimeDelta::minutes(-45)), "in 45 minutes");
+        assert_eq!(humanize(TimeDelta::days(14)), "2 weeks ago");
+        assert_eq!(humanize(TimeDelta::days(65)), "2 months ago");
+        assert_eq!(humanize(TimeDelta::days(800)), "2 years ago");
+        assert_eq!(humanize(TimeDelta::seconds(-5)), "just now");
+    }
+
+    #[test]
+    fn test_humanize_since() {
+        let three_hours_ago = Utc::now().naive_utc() - TimeDelta::hours(3);
+        assert_eq!(humanize_since(three_hours_ago), "3 hours ago");
+    }
+
+    #[test]
+    fn test_iso_week_helpers() {
+        // 2021-01-03 (Sunday) still belongs to week 53 of 2020
+        let date = NaiveDate::from_ymd_opt(2021, 1, 3).unwrap();
+        assert_eq!(iso_week(date), (2020, 53));
+        assert_eq!(
+            start_of_iso_week(date),
+            NaiveDate::from_ymd_opt(2020, 12, 28).unwrap()
+        );
+        assert_eq!(end_of_iso_week(date), date);
+        assert_eq!(
+            from_iso_week(2020, 53),
+            NaiveDate::from_ymd_opt(2020, 12, 28)
+        );
+        assert_eq!(from_iso_week(2021, 53), None);
+    }
+}
@@ -1,12 +1,17 @@
+use crate::internal_server_error;
 use crate::prelude::AppResult;
 use anyhow::Context;
 use std::future::Future;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 use tokio::runtime::Runtime;
-use tokio::task::{JoinHandle, spawn_blocking};
+use tokio::sync::Semaphore;
+use tokio::task::{JoinError, JoinHandle, spawn_blocking};
 
 static RUNTIME: OnceLock<Runtime> = OnceLock::new();
 
+static BLOCKING_LIMIT: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
 fn runtime() -> &'static Runtime {
     RUNTIME.get_or_init(|| Runtime::new().expect("Failed to create Tokio runtime"))
 }
@@ -65,13 +70,109 @@ fn runtime() -> &'static Runtime {
 ///
 /// - Use this for synchronous blocking operations (file I/O, CPU work, sync APIs)
 /// - Don't use this for async operations - use regular `spawn` instead
-/// - The blocking pool has a large but finite number of threads
+/// - The blocking pool has a large but finite number of threads, cap the tasks
+///   running at once with [`limit_blocking_tasks`]
+/// - The function runs in a `blk` span, child of the current one, recording in
+///   `queue_wait_ms` how long it waited for a permit and a thread
 pub fn blk<F, R>(f: F) -> JoinHandle<R>
 where
     F: FnOnce() -> R + Send + 'static,
     R: Send + 'static,
 {
-    spawn_blocking(f)
+    spawn_limited(BLOCKING_LIMIT.get().cloned(), f)
+}
+
+/// Like [`blk`], with a panic of the function turned into an error instead of
+/// being carried by the `JoinError`
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::{run_async, try_blk};
+///
+/// run_async(async {
+///     let result = try_blk(|| -> i32 { panic!("corrupted input") }).await;
+///     assert!(result.unwrap_err().to_string().contains("corrupted input"));
+/// });
+/// ```
+pub async fn try_blk<F, R>(f: F) -> AppResult<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    blk(f).await.map_err(panic_to_error)
+}
+
+/// Caps the tasks spawned by [`blk`] and [`try_blk`] running at once to `max`, the
+/// others wait for one to finish, so a burst of blocking work can't take every thread
+/// of the blocking pool, unlimited by default
+///
+/// Only the first call takes effect, returns whether this one did.
+///
+/// # Panics
+///
+/// Panics if `max` is 0
+pub fn limit_blocking_tasks(max: usize) -> bool {
+    assert!(max > 0, "max must be positive");
+    BLOCKING_LIMIT.set(Arc::new(Semaphore::new(max))).is_ok()
+}
+
+fn spawn_limited<F, R>(limit: Option<Arc<Semaphore>>, f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let queued_at = Instant::now();
+    let parent = tracing::Span::current();
+    let run = move || {
+        let _span = tracing::debug_span!(
+            parent: &parent,
+            "blk",
+            queue_wait_ms = queued_at.elapsed().as_millis() as u64
+        )
+        .entered();
+        f()
+    };
+
+    let Some(limit) = limit else {
+        return spawn_blocking(run);
+    };
+
+    tokio::spawn(async move {
+        let permit = limit
+            .acquire_owned()
+            .await
+            .expect("blocking task limiter is never closed");
+
+        // The permit is held by the function, so it is released once it returns,
+        // even if this task is aborted meanwhile
+        let task = spawn_blocking(move || {
+            let _permit = permit;
+            run()
+        });
+
+        match task.await {
+            Ok(value) => value,
+            Err(err) => match err.try_into_panic() {
+                Ok(panic) => std::panic::resume_unwind(panic),
+                Err(_) => panic!("blocking task was cancelled"),
+            },
+        }
+    })
+}
+
+fn panic_to_error(err: JoinError) -> crate::Error {
+    match err.try_into_panic() {
+        Ok(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            internal_server_error!("Blocking task panicked: {message}")
+        }
+        Err(err) => internal_server_error!("Blocking task failed: {err}"),
+    }
 }
 
 /// Spawns a blocking function, intelligently handling tokio runtime contexts.
@@ -423,6 +524,47 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_try_blk_turns_panics_into_errors() {
+        assert_eq!(try_blk(|| 42).await.unwrap(), 42);
+
+        let err = try_blk(|| -> i32 { panic!("boom {}", 7) })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("boom 7"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_spawn_limited_caps_concurrent_tasks() {
+        let limit = Arc::new(Semaphore::new(2));
+        let running = Arc::new(Mutex::new((0, 0)));
+
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let running = running.clone();
+                spawn_limited(Some(limit.clone()), move || {
+                    {
+                        let mut running = running.lock().unwrap();
+                        running.0 += 1;
+                        running.1 = running.1.max(running.0);
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.lock().unwrap().0 -= 1;
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(running.lock().unwrap().1, 2);
+
+        // panics still reach the handle
+        let handle = spawn_limited(Some(limit.clone()), || panic!("limited panic"));
+        assert!(handle.await.unwrap_err().is_panic());
+        assert_eq!(limit.available_permits(), 2);
+    }
+
     #[tokio::test]
     async fn test_block_with_runtime() {
         let result = block(|| Ok(42)).await.unwrap();
@@ -0,0 +1,397 @@
+//! Time-based one-time passwords (RFC 6238) for two-factor authentication.
+//!
+//! This module covers the full enrolment flow: generating a shared secret, building
+//! the `otpauth://` URI rendered as a QR code by authenticator apps, verifying codes
+//! with tolerance for clock drift, and generating single-use recovery codes.
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::totp::Totp;
+//!
+//! let secret = Totp::generate_secret().unwrap();
+//! let totp = Totp::new(&secret).unwrap().issuer("Foxtive");
+//!
+//! let uri = totp.otpauth_uri("john@example.com");
+//! assert!(uri.starts_with("otpauth://totp/Foxtive:john%40example.com?secret="));
+//!
+//! let code = totp.generate_now();
+//! assert!(totp.verify(&code));
+//! ```
+
+use crate::helpers::time::current_timestamp;
+use crate::prelude::AppResult;
+use crate::{bad_request, internal_server_error};
+use hmac::{Hmac, KeyInit, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+// lowercase letters and digits without easily confused characters (0, 1, i, l, o)
+const RECOVERY_ALPHABET: &[u8; 31] = b"abcdefghjkmnpqrstuvwxyz23456789";
+// largest multiple of the alphabet length within a byte, higher bytes would bias the codes
+const RECOVERY_BYTE_LIMIT: u8 = 31 * 8;
+const MAX_SKEW: u64 = 10;
+
+/// Hash function used to compute codes, most authenticator apps only support SHA1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TotpAlgorithm::Sha1 => "SHA1",
+            TotpAlgorithm::Sha256 => "SHA256",
+            TotpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// TOTP generator and verifier.
+#[derive(Clone)]
+pub struct Totp {
+    secret: Vec<u8>,
+    algorithm: TotpAlgorithm,
+    digits: u32,
+    period: u64,
+    skew: u64,
+    issuer: Option<String>,
+}
+
+impl Totp {
+    /// Creates a verifier from a base32 encoded secret, with 6 digits, 30 seconds
+    /// period and one step of drift tolerance in each direction
+    pub fn new(secret: &str) -> AppResult<Self> {
+        Ok(Self::from_bytes(base32_decode(secret)?))
+    }
+
+    /// Creates a verifier from the raw secret bytes
+    pub fn from_bytes(secret: Vec<u8>) -> Self {
+        Self {
+            secret,
+            algorithm: TotpAlgorithm::default(),
+            digits: 6,
+            period: 30,
+            skew: 1,
+            issuer: None,
+        }
+    }
+
+    /// Generates a random 160-bit secret, base32 encoded, to be stored for the user
+    pub fn generate_secret() -> AppResult<String> {
+        Ok(base32_encode(&random_bytes(20)?))
+    }
+
+    pub fn algorithm(mut self, algorithm: TotpAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Number of digits in a code (6 to 8)
+    pub fn digits(mut self, digits: u32) -> Self {
+        self.digits = digits.clamp(6, 8);
+        self
+    }
+
+    /// Seconds a code remains valid
+    pub fn period(mut self, period: u64) -> Self {
+        self.period = period.max(1);
+        self
+    }
+
+    /// Number of periods before and after the current one that are also accepted (at most 10)
+    pub fn skew(mut self, skew: u64) -> Self {
+        self.skew = skew.min(MAX_SKEW);
+        self
+    }
+
+    /// Issuer shown by authenticator apps
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Returns the base32 encoded secret
+    pub fn secret(&self) -> String {
+        base32_encode(&self.secret)
+    }
+
+    /// Generates the code for the given unix timestamp
+    pub fn generate(&self, timestamp: u64) -> String {
+        self.generate_for_counter(timestamp / self.period)
+    }
+
+    /// Generates the code for the current time
+    pub fn generate_now(&self) -> String {
+        self.generate(current_timestamp())
+    }
+
+    /// Verifies a code against the current time, accepting the configured drift window.
+    ///
+    /// Use [`Totp::verify_step`] to also guard against replayed codes.
+    pub fn verify(&self, code: &str) -> bool {
+        self.verify_at(code, current_timestamp())
+    }
+
+    /// Verifies a code against the given unix timestamp
+    pub fn verify_at(&self, code: &str, timestamp: u64) -> bool {
+        self.verify_step(code, timestamp).is_some()
+    }
+
+    /// Verifies a code against the given unix timestamp and returns the time step it matched.
+    ///
+    /// A code stays valid for the whole drift window, so on its own a verification does
+    /// not stop a code from being replayed. Persist the returned step per user and reject
+    /// any code whose step is less than or equal to the last accepted one.
+    pub fn verify_step(&self, code: &str, timestamp: u64) -> Option<u64> {
+        let code = code.trim().replace(' ', "");
+        if code.len() != self.digits as usize {
+            return None;
+        }
+
+        let counter = timestamp / self.period;
+        let start = counter.saturating_sub(self.skew);
+
+        (start..=counter.saturating_add(self.skew)).find(|counter| {
+            let expected = self.generate_for_counter(*counter);
+            expected
+                .bytes()
+                .zip(code.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0
+        })
+    }
+
+    /// Builds the `otpauth://` URI to be rendered as a QR code
+    pub fn otpauth_uri(&self, account: &str) -> String {
+        let label = match &self.issuer {
+            Some(issuer) => format!("{}:{}", url_encode(issuer), url_encode(account)),
+            None => url_encode(account),
+        };
+
+        let mut uri = format!(
+            "otpauth://totp/{label}?secret={}&algorithm={}&digits={}&period={}",
+            self.secret(),
+            self.algorithm.as_str(),
+            self.digits,
+            self.period
+        );
+
+        if let Some(issuer) = &self.issuer {
+            uri.push_str(&format!("&issuer={}", url_encode(issuer)));
+        }
+
+        uri
+    }
+
+    /// Generates single-use recovery codes formatted as `xxxxx-xxxxx`.
+    ///
+    /// Store them hashed (e.g. with the password helper), like passwords.
+    pub fn recovery_codes(count: usize) -> AppResult<Vec<String>> {
+        (0..count)
+            .map(|_| {
+                let mut code = String::with_capacity(10);
+                while code.len() < 10 {
+                    let chars = random_bytes(10 - code.len())?
+                        .into_iter()
+                        .filter(|b| *b < RECOVERY_BYTE_LIMIT)
+                        .map(|b| RECOVERY_ALPHABET[(b % 31) as usize] as char);
+                    code.extend(chars);
+                }
+                Ok(format!("{}-{}", &code[..5], &code[5..]))
+            })
+            .collect()
+    }
+
+    fn generate_for_counter(&self, counter: u64) -> String {
+        let message = counter.to_be_bytes();
+        let digest = match self.algorithm {
+            TotpAlgorithm::Sha1 => hmac_digest::<Hmac<Sha1>>(&self.secret, &message),
+            TotpAlgorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(&self.secret, &message),
+            TotpAlgorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(&self.secret, &message),
+        };
+
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+
+        let code = binary % 10u32.pow(self.digits);
+        format!("{:0width$}", code, width = self.digits as usize)
+    }
+}
+
+fn hmac_digest<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn random_bytes(len: usize) -> AppResult<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| internal_server_error!("failed to generate random bytes: {e}"))?;
+    Ok(bytes)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    out
+}
+
+fn base32_decode(encoded: &str) -> AppResult<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+
+    for c in encoded.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a as char == c.to_ascii_uppercase())
+            .ok_or_else(|| bad_request!("invalid base32 character: {c}"))?;
+
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(out)
+}
+
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // test vectors from RFC 6238, appendix B
+    #[test]
+    fn test_rfc6238_vectors() {
+        let sha1 = Totp::from_bytes(b"12345678901234567890".to_vec()).digits(8);
+        let sha256 = Totp::from_bytes(b"12345678901234567890123456789012".to_vec())
+            .digits(8)
+            .algorithm(TotpAlgorithm::Sha256);
+        let sha512 = Totp::from_bytes(
+            b"1234567890123456789012345678901234567890123456789012345678901234".to_vec(),
+        )
+        .digits(8)
+        .algorithm(TotpAlgorithm::Sha512);
+
+        assert_eq!(sha1.generate(59), "94287082");
+        assert_eq!(sha1.generate(1111111109), "07081804");
+        assert_eq!(sha1.generate(20000000000), "65353130");
+        assert_eq!(sha256.generate(59), "46119246");
+        assert_eq!(sha512.generate(59), "90693936");
+    }
+
+    #[test]
+    fn test_verify_with_drift() {
+        let totp = Totp::from_bytes(b"12345678901234567890".to_vec());
+        let code = totp.generate(1_000_000);
+
+        assert!(totp.verify_at(&code, 1_000_000));
+        assert!(totp.verify_at(&code, 1_000_030));
+        assert!(!totp.verify_at(&code, 1_000_090));
+        assert!(!totp.clone().skew(0).verify_at(&code, 1_000_030));
+
+        // the skew is capped at 10 periods and never overflows the counter
+        let wide = totp.skew(u64::MAX);
+        assert!(wide.verify_at(&code, 1_000_000 + 30 * 10));
+        assert!(!wide.verify_at(&code, 1_000_000 + 30 * 11));
+        assert!(!wide.verify_at(&code, u64::MAX));
+    }
+
+    #[test]
+    fn test_verify_step() {
+        let totp = Totp::from_bytes(b"12345678901234567890".to_vec());
+        let code = totp.generate(1_000_000);
+        let step = 1_000_000 / 30;
+
+        assert_eq!(totp.verify_step(&code, 1_000_000), Some(step));
+        assert_eq!(totp.verify_step(&code, 1_000_030), Some(step));
+        assert_eq!(totp.verify_step(&code, 1_000_090), None);
+        assert_eq!(totp.verify_step("12345", 1_000_000), None);
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_codes() {
+        let totp = Totp::from_bytes(b"12345678901234567890".to_vec());
+        assert!(!totp.verify_at("12345", 59));
+        assert!(!totp.verify_at("abcdef", 59));
+    }
+
+    #[test]
+    fn test_base32_roundtrip() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("MZXW6YTBOI").unwrap(), b"foobar");
+        assert_eq!(base32_decode("mzxw 6ytb oi======").unwrap(), b"foobar");
+        assert!(base32_decode("MZXW1").is_err());
+    }
+
+    #[test]
+    fn test_generated_secret() {
+        let secret = Totp::generate_secret().unwrap();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(Totp::new(&secret).unwrap().secret(), secret);
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        let totp = Totp::new("MZXW6YTBOI").unwrap().issuer("My App");
+        assert_eq!(
+            totp.otpauth_uri("john@example.com"),
+            "otpauth://totp/My%20App:john%40example.com?secret=MZXW6YTBOI&algorithm=SHA1&digits=6&period=30&issuer=My%20App"
+        );
+    }
+
+    #[test]
+    fn test_recovery_codes() {
+        let codes = Totp::recovery_codes(8).unwrap();
+        assert_eq!(codes.len(), 8);
+        assert!(
+            codes
+                .iter()
+                .all(|c| c.len() == 11 && c.as_bytes()[5] == b'-')
+        );
+        assert!(codes.iter().all(|c| {
+            c.bytes()
+                .filter(|b| *b != b'-')
+                .all(|b| RECOVERY_ALPHABET.contains(&b))
+        }));
+    }
+}
@@ -0,0 +1,396 @@
+//! Storing uploaded files safely.
+//!
+//! This module validates and persists files received from clients (multipart bodies,
+//! raw request streams):
+//!
+//! - the real content type is sniffed from magic bytes rather than trusted from the client
+//! - the size limit is enforced while streaming, without buffering the whole file
+//! - client-provided filenames are sanitized and made unique before touching the disk
+//! - files are written under a temporary name and renamed once complete
+//!
+//! # Examples
+//!
+//! ```
+//! use foxtive::helpers::upload::Uploader;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let dir = std::env::temp_dir().join("foxtive-upload-doc");
+//! let uploader = Uploader::new(&dir)
+//!     .max_size(5 * 1024 * 1024)
+//!     .allow_mime("image/png")
+//!     .allow_mime("image/jpeg");
+//!
+//! let png: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+//! let stored = uploader.store("My Holiday Photo.PNG", png).await.unwrap();
+//! assert!(stored.file_name.starts_with("my-holiday-photo-"));
+//! assert!(stored.file_name.ends_with(".png"));
+//! assert_eq!(stored.mime.as_deref(), Some("image/png"));
+//!
+//! // content is checked, not the extension
+//! assert!(uploader.store("photo.png", &b"#!/bin/sh"[..]).await.is_err());
+//! # tokio::fs::remove_dir_all(&dir).await.unwrap();
+//! # });
+//! ```
+
+use crate::helpers::id;
+use crate::helpers::regex::{CaseSensitivity, RegexType, TextCleaner};
+use crate::helpers::{FileExtHelper, SniffedType};
+use crate::prelude::{AppMessage, AppResult};
+use http::StatusCode;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+/// Number of leading bytes read before deciding on the file type
+const SNIFF_LEN: usize = 64;
+const CHUNK_SIZE: usize = 64 * 1024;
+const MAX_BASE_NAME_LENGTH: usize = 100;
+
+/// Information about a file persisted by [`Uploader::store`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredFile {
+    /// Full path of the stored file
+    pub path: PathBuf,
+    /// Unique, sanitized name the file was stored under
+    pub file_name: String,
+    /// Filename as sent by the client
+    pub original_name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Content type detected from the file content, if recognized
+    pub mime: Option<String>,
+}
+
+/// Validates uploads and stores them in a target directory.
+#[derive(Debug, Clone)]
+pub struct Uploader {
+    dir: PathBuf,
+    max_size: Option<u64>,
+    allowed_mimes: HashSet<String>,
+    allow_any: bool,
+}
+
+impl Uploader {
+    /// Creates an uploader storing files in `dir`, created on first upload if missing
+    ///
+    /// Nothing is accepted until content types are allowed with [`allow_mime`](Self::allow_mime)
+    /// or [`allow_any_type`](Self::allow_any_type).
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            max_size: None,
+            allowed_mimes: HashSet::new(),
+            allow_any: false,
+        }
+    }
+
+    /// Maximum file size in bytes
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Allows uploads of the given content type, may be called multiple times.
+    ///
+    /// Files whose type cannot be detected are rejected.
+    pub fn allow_mime(mut self, mime: impl Into<String>) -> Self {
+        self.allowed_mimes.insert(mime.into());
+        self
+    }
+
+    /// Allows uploads of the given content types
+    pub fn allow_mimes<I, S>(self, mimes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        mimes
+            .into_iter()
+            .fold(self, |uploader, mime| uploader.allow_mime(mime))
+    }
+
+    /// Accepts any content, including files whose type cannot be detected.
+    ///
+    /// Those keep the extension sent by the client, so they must not be served from
+    /// a location where the extension decides how a file is handled (`.php`, `.html`...).
+    pub fn allow_any_type(mut self) -> Self {
+        self.allow_any = true;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Validates and streams the content to a uniquely named file in the target directory.
+    ///
+    /// # Errors
+    ///
+    /// * `413 Payload Too Large` when the content exceeds the maximum size
+    /// * `415 Unsupported Media Type` when the detected type is not allowed
+    /// * any I/O error, in which case nothing is left on disk
+    pub async fn store<R>(&self, original_name: &str, mut reader: R) -> AppResult<StoredFile>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let head = read_head(&mut reader).await?;
+        let sniffed = FileExtHelper::sniff(&head);
+
+        self.check_type(sniffed)?;
+        self.check_size(head.len() as u64)?;
+
+        let (base, extension) = split_sanitized(original_name);
+        let extension = sniffed.map(|s| s.extension.to_string()).or(extension);
+        let unique = format!("{base}-{}", id::ulid().to_lowercase());
+        let file_name = match extension {
+            Some(ext) => format!("{unique}.{ext}"),
+            None => unique,
+        };
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join(&file_name);
+        let partial = self.dir.join(format!(".{file_name}.part"));
+
+        let size = match self.write(&partial, &head, &mut reader).await {
+            Ok(size) => size,
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                return Err(err);
+            }
+        };
+
+        tokio::fs::rename(&partial, &path).await?;
+
+        Ok(StoredFile {
+            path,
+            file_name,
+            original_name: original_name.to_string(),
+            size,
+            mime: sniffed.map(|s| s.mime.to_string()),
+        })
+    }
+
+    async fn write<R>(&self, path: &Path, head: &[u8], reader: &mut R) -> AppResult<u64>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut file = File::create(path).await?;
+        file.write_all(head).await?;
+
+        let mut size = head.len() as u64;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            let read = reader.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+
+            size += read as u64;
+            self.check_size(size)?;
+            file.write_all(&buffer[..read]).await?;
+        }
+
+        file.flush().await?;
+        Ok(size)
+    }
+
+    fn check_size(&self, size: u64) -> AppResult<()> {
+        match self.max_size {
+            Some(max) if size > max => Err(AppMessage::error_message(
+                format!("File exceeds the maximum size of {max} bytes"),
+                StatusCode::PAYLOAD_TOO_LARGE,
+            )
+            .into_anyhow()),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_type(&self, sniffed: Option<SniffedType>) -> AppResult<()> {
+        if self.allow_any || sniffed.is_some_and(|s| self.allowed_mimes.contains(s.mime)) {
+            return Ok(());
+        }
+
+        Err(AppMessage::error_message(
+            "File type is not allowed",
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        )
+        .into_anyhow())
+    }
+}
+
+/// Turns a client-provided filename into a safe one: directory components are dropped,
+/// the name is lowercased and restricted to letters, digits, `-` and `_`.
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::helpers::upload::sanitize_filename;
+///
+/// assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+/// assert_eq!(sanitize_filename("Q3 Report (final).tar.gz"), "q3-report-final.tar.gz");
+/// assert_eq!(sanitize_filename("???.exe"), "file.exe");
+/// ```
+pub fn sanitize_filename(name: &str) -> String {
+    match split_sanitized(name) {
+        (base, Some(ext)) => format!("{base}.{ext}"),
+        (base, None) => base,
+    }
+}
+
+fn split_sanitized(name: &str) -> (String, Option<String>) {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let (base, extension) = FileExtHelper::new().split_filename(name);
+
+    let base = base
+        .split(|c: char| c.is_whitespace() || c == '.')
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = TextCleaner::clean(
+        &base,
+        RegexType::Custom(
+            "-_",
+            Some(CaseSensitivity::CaseInsensitive),
+            MAX_BASE_NAME_LENGTH,
+        ),
+    );
+    let base = collapse_dashes(&base);
+
+    let extension = extension
+        .map(|ext| {
+            TextCleaner::clean(
+                &ext,
+                RegexType::Custom(".", Some(CaseSensitivity::CaseInsensitive), 16),
+            )
+        })
+        .map(|ext| ext.trim_matches('.').to_string())
+        .filter(|ext| !ext.is_empty());
+
+    match base.is_empty() {
+        true => ("file".to_string(), extension),
+        false => (base, extension),
+    }
+}
+
+fn collapse_dashes(value: &str) -> String {
+    value
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .trim_matches('_')
+        .to_string()
+}
+
+async fn read_head<R: AsyncRead + Unpin>(reader: &mut R) -> AppResult<Vec<u8>> {
+    let mut head = vec![0u8; SNIFF_LEN];
+    let mut filled = 0;
+
+    while filled < SNIFF_LEN {
+        let read = reader.read(&mut head[filled..]).await?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+
+    head.truncate(filled);
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+
+    fn status_of(err: &anyhow::Error) -> StatusCode {
+        err.downcast_ref::<AppMessage>().unwrap().status_code()
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("photo.JPG"), "photo.jpg");
+        assert_eq!(sanitize_filename("C:\\Users\\me\\cv v2.docx"), "cv-v2.docx");
+        assert_eq!(
+            sanitize_filename("my..weird   name.txt"),
+            "my-weird-name.txt"
+        );
+        assert_eq!(sanitize_filename(".htaccess"), "htaccess");
+        assert_eq!(sanitize_filename(""), "file");
+    }
+
+    #[tokio::test]
+    async fn test_store_streams_to_unique_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploader = Uploader::new(dir.path()).allow_any_type();
+
+        let content = vec![7u8; CHUNK_SIZE * 2 + 5];
+        let first = uploader.store("data.bin", &content[..]).await.unwrap();
+        let second = uploader.store("data.bin", &content[..]).await.unwrap();
+
+        assert_ne!(first.file_name, second.file_name);
+        assert_eq!(first.size, content.len() as u64);
+        assert_eq!(first.mime, None);
+        assert_eq!(tokio::fs::read(&first.path).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_store_uses_sniffed_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let stored = Uploader::new(dir.path())
+            .allow_any_type()
+            .store("avatar.txt", PNG)
+            .await
+            .unwrap();
+
+        assert!(stored.file_name.starts_with("avatar-"));
+        assert!(stored.file_name.ends_with(".png"));
+        assert_eq!(stored.original_name, "avatar.txt");
+    }
+
+    #[tokio::test]
+    async fn test_store_rejects_oversized_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploader = Uploader::new(dir.path()).allow_any_type().max_size(100);
+
+        let content = vec![0u8; CHUNK_SIZE];
+        let err = uploader.store("big.bin", &content[..]).await.unwrap_err();
+        assert_eq!(status_of(&err), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut entries = tokio::fs::read_dir(dir.path()).await.unwrap();
+        assert!(entries.next_entry().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_store_rejects_disallowed_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploader = Uploader::new(dir.path()).allow_mimes(["image/png", "image/gif"]);
+
+        assert!(uploader.store("ok.png", PNG).await.is_ok());
+
+        let err = uploader
+            .store("doc.png", &b"%PDF-1.4"[..])
+            .await
+            .unwrap_err();
+        assert_eq!(status_of(&err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let err = uploader.store("note.png", &b"hi"[..]).await.unwrap_err();
+        assert_eq!(status_of(&err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_store_rejects_everything_without_allowed_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploader = Uploader::new(dir.path());
+
+        let err = uploader
+            .store("shell.php", &b"<?php"[..])
+            .await
+            .unwrap_err();
+        assert_eq!(status_of(&err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let err = uploader.store("ok.png", PNG).await.unwrap_err();
+        assert_eq!(status_of(&err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
@@ -1,8 +1,8 @@
-use super::ordering::OrderBy;
+use super::ordering::{Direction, OrderBy};
 use crate::http::QueryParams;
 
 pub trait CompactOrdering {
-    /// Parse compact colon-separated order format
+    /// Parse compact colon-separated order format, `column:direction[:nulls_first|nulls_last|ci]`
     fn parse_compact_orders(&self) -> Vec<OrderBy>;
 }
 
@@ -11,16 +11,21 @@ impl CompactOrdering for QueryParams {
         if let Some(order_str) = &self.order {
             let mut orders = Vec::new();
             for order_part in order_str.split(',') {
-                let parts: Vec<&str> = order_part.trim().split(':').collect();
-                if parts.len() == 2 {
-                    let direction = parts[1].trim().to_lowercase();
-                    // Validate direction
-                    if direction == "asc" || direction == "desc" {
-                        orders.push(OrderBy {
-                            column: parts[0].trim().to_string(),
-                            direction,
-                        });
-                    }
+                let mut parts = order_part.trim().split(':');
+                let (Some(column), Some(direction)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+
+                // Skip invalid directions and modifiers
+                let Ok(direction) = direction.parse::<Direction>() else {
+                    continue;
+                };
+
+                let order =
+                    parts.try_fold(OrderBy::new(column.trim(), direction), OrderBy::modifier);
+
+                if let Some(order) = order {
+                    orders.push(order);
                 }
             }
             return orders;
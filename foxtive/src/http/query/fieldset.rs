@@ -0,0 +1,151 @@
+#[cfg(feature = "database")]
+use crate::database::pagination::PageData;
+use crate::http::QueryParams;
+use crate::prelude::AppResult;
+use crate::validation_error;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeSet, HashMap};
+
+/// Fields requested per resource type with sparse fieldsets, e.g.
+/// `?fields[user]=id,name&fields[post]=title`.
+///
+/// A plain `?fields=id,name` applies to every type without a fieldset of its own.
+/// Types without any fieldset are returned whole.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldSet {
+    types: HashMap<String, BTreeSet<String>>,
+    default: Option<BTreeSet<String>>,
+}
+
+impl FieldSet {
+    /// Parses `fields[type]` and `fields` query parameters
+    pub fn parse<'a, I>(params: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a String, &'a String)>,
+    {
+        let mut fieldset = FieldSet::default();
+        for (key, value) in params {
+            let fields: BTreeSet<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+                .map(str::to_string)
+                .collect();
+
+            if key == "fields" {
+                fieldset.default = Some(fields);
+            } else if let Some(kind) = key
+                .strip_prefix("fields[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                fieldset.types.insert(kind.to_string(), fields);
+            }
+        }
+
+        fieldset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.default.is_none()
+    }
+
+    /// Fields requested for the type, `None` if the whole resource is
+    pub fn fields(&self, kind: &str) -> Option<&BTreeSet<String>> {
+        self.types.get(kind).or(self.default.as_ref())
+    }
+
+    /// Whether the field of the type is to be returned
+    pub fn includes(&self, kind: &str, field: &str) -> bool {
+        self.fields(kind)
+            .is_none_or(|fields| fields.contains(field))
+    }
+
+    /// Fails with a validation error listing the requested fields of the type
+    /// that aren't in `allowed`
+    pub fn validate(&self, kind: &str, allowed: &[&str]) -> AppResult<()> {
+        let Some(fields) = self.fields(kind) else {
+            return Ok(());
+        };
+
+        let unknown: Vec<String> = fields
+            .iter()
+            .filter(|field| !allowed.contains(&field.as_str()))
+            .map(|field| format!("'{field}' is not a field of {kind}"))
+            .collect();
+
+        match unknown.is_empty() {
+            true => Ok(()),
+            false => {
+                let errors = HashMap::from([(format!("fields[{kind}]"), unknown)]);
+                Err(validation_error!("Unknown fields requested", errors))
+            }
+        }
+    }
+
+    /// Serializes `value` with only the requested fields of the type, objects in
+    /// arrays included
+    pub fn apply<'a, T: Serialize>(&'a self, kind: &str, value: &'a T) -> Sparse<'a, T> {
+        Sparse {
+            value,
+            fields: self.fields(kind),
+        }
+    }
+
+    /// The page with only the requested fields of the type in its records, its
+    /// pagination metadata kept whole
+    #[cfg(feature = "database")]
+    pub fn apply_page<'a, T: Serialize>(
+        &'a self,
+        kind: &str,
+        page: &'a PageData<T>,
+    ) -> PageData<Sparse<'a, T>> {
+        PageData {
+            records: page
+                .records
+                .iter()
+                .map(|record| self.apply(kind, record))
+                .collect(),
+            total_pages: page.total_pages,
+            total_records: page.total_records,
+            page: page.page,
+            per_page: page.per_page,
+            links: page.links.clone(),
+        }
+    }
+}
+
+/// Serializes a value with only the fields of a [`FieldSet`], see [`FieldSet::apply`]
+pub struct Sparse<'a, T> {
+    value: &'a T,
+    fields: Option<&'a BTreeSet<String>>,
+}
+
+impl<T: Serialize> Serialize for Sparse<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(fields) = self.fields else {
+            return self.value.serialize(serializer);
+        };
+
+        let mut value = serde_json::to_value(self.value).map_err(serde::ser::Error::custom)?;
+        retain(&mut value, fields);
+        value.serialize(serializer)
+    }
+}
+
+/// Keeps the requested fields of objects, and of objects within arrays
+fn retain(value: &mut serde_json::Value, fields: &BTreeSet<String>) {
+    match value {
+        serde_json::Value::Array(items) => {
+            items.iter_mut().for_each(|item| retain(item, fields));
+        }
+        serde_json::Value::Object(object) => object.retain(|key, _| fields.contains(key)),
+        _ => {}
+    }
+}
+
+impl QueryParams {
+    /// The sparse fieldsets of the query, `fields[type]=a,b` and `fields=a,b`
+    pub fn fieldset(&self) -> FieldSet {
+        FieldSet::parse(&self.extra)
+    }
+}
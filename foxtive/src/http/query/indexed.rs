@@ -1,4 +1,4 @@
-use super::ordering::OrderBy;
+use super::ordering::{Direction, Nulls, OrderBy};
 use crate::http::query::{QueryParams, ordering};
 use std::collections::HashMap;
 
@@ -9,18 +9,16 @@ pub trait IndexedOrdering {
 
 impl IndexedOrdering for QueryParams {
     fn parse_indexed_orders(&self) -> Vec<OrderBy> {
-        let mut order_map: HashMap<usize, (Option<String>, Option<String>)> = HashMap::new();
+        let mut order_map: HashMap<usize, HashMap<String, String>> = HashMap::new();
 
-        // Parse flattened parameters like "order[0][column]" and "order[0][direction]"
+        // Parse flattened parameters like "order[0][column]", "order[0][direction]",
+        // "order[0][nulls]" and "order[0][case_insensitive]"
         for (key, value) in &self.extra {
             if let Some((index, field)) = ordering::parse_indexed_key(key) {
-                let entry = order_map.entry(index).or_insert((None, None));
-
-                match field.as_str() {
-                    "column" => entry.0 = Some(value.clone()),
-                    "direction" => entry.1 = Some(value.clone()),
-                    _ => {} // Ignore unknown fields
-                }
+                order_map
+                    .entry(index)
+                    .or_default()
+                    .insert(field, value.clone());
             }
         }
 
@@ -30,15 +28,32 @@ impl IndexedOrdering for QueryParams {
 
         let mut orders = Vec::new();
         for index in indices {
-            if let Some((Some(column), Some(direction))) = order_map.get(&index) {
-                let direction_lower = direction.to_lowercase();
-                if direction_lower == "asc" || direction_lower == "desc" {
-                    orders.push(OrderBy {
-                        column: column.clone(),
-                        direction: direction_lower,
-                    });
-                }
+            let fields = &order_map[&index];
+            let (Some(column), Some(direction)) = (fields.get("column"), fields.get("direction"))
+            else {
+                continue;
+            };
+
+            // Skip invalid directions and null orderings
+            let Ok(mut order) = direction
+                .parse::<Direction>()
+                .map(|direction| OrderBy::new(column.clone(), direction))
+            else {
+                continue;
+            };
+
+            if let Some(nulls) = fields.get("nulls") {
+                let Ok(nulls) = nulls.parse::<Nulls>() else {
+                    continue;
+                };
+                order.nulls = Some(nulls);
             }
+
+            order.case_insensitive = fields
+                .get("case_insensitive")
+                .is_some_and(|value| matches!(value.to_lowercase().as_str(), "1" | "true"));
+
+            orders.push(order);
         }
 
         orders
@@ -1,4 +1,5 @@
 mod compact;
+mod fieldset;
 mod indexed;
 mod ordering;
 #[cfg(test)]
@@ -10,7 +11,8 @@ use indexed::IndexedOrdering;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-pub use ordering::OrderBy;
+pub use fieldset::{FieldSet, Sparse};
+pub use ordering::{Direction, Nulls, OrderBy};
 
 /// Enum representing the type of ordering format detected
 #[derive(Debug, Clone, PartialEq)]
@@ -57,10 +59,12 @@ pub struct QueryParams {
     /// Example: `?stage=pending`
     pub stage: Option<String>,
 
-    /// Compact multi-column ordering specification. Format: "column:direction,column:direction"
+    /// Compact multi-column ordering specification. Format: "column:direction,column:direction",
+    /// each optionally followed by `:nulls_first`, `:nulls_last` or `:ci` (case-insensitive)
     ///
     /// Examples:
     /// - `?order=name:asc,created_at:desc`
+    /// - `?order=name:asc:ci,deleted_at:desc:nulls_last`
     /// - `?order=fms_id:desc,updated_at:asc,status:asc`
     pub order: Option<String>,
 
@@ -180,7 +184,12 @@ impl QueryParams {
         } else {
             orders
                 .iter()
-                .map(|o| format!("{} {}", o.column, o.direction.to_uppercase()))
+                .map(|o| match o.nulls {
+                    Some(nulls) => {
+                        format!("{} {} {}", o.column, o.direction.as_sql(), nulls.as_sql())
+                    }
+                    None => format!("{} {}", o.column, o.direction.as_sql()),
+                })
                 .collect::<Vec<_>>()
                 .join(", ")
         }
@@ -1,7 +1,193 @@
-#[derive(Debug, Clone)]
+use crate::prelude::AppResult;
+use crate::{bad_request, validation_error};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Sort direction, parsed case-insensitively from `asc`/`ascending` and `desc`/`descending`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Asc => "asc",
+            Direction::Desc => "desc",
+        }
+    }
+
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Direction::Asc => "ASC",
+            Direction::Desc => "DESC",
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = crate::Error;
+
+    fn from_str(direction: &str) -> AppResult<Self> {
+        match direction.trim().to_lowercase().as_str() {
+            "asc" | "ascending" => Ok(Direction::Asc),
+            "desc" | "descending" => Ok(Direction::Desc),
+            _ => Err(bad_request!("Invalid sort direction '{direction}'")),
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<&str> for Direction {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Where rows with a null value go, the database default when unset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+impl Nulls {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Nulls::First => "NULLS FIRST",
+            Nulls::Last => "NULLS LAST",
+        }
+    }
+}
+
+impl FromStr for Nulls {
+    type Err = crate::Error;
+
+    fn from_str(nulls: &str) -> AppResult<Self> {
+        match nulls.trim().to_lowercase().as_str() {
+            "first" | "nulls_first" => Ok(Nulls::First),
+            "last" | "nulls_last" => Ok(Nulls::Last),
+            _ => Err(bad_request!("Invalid null ordering '{nulls}'")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OrderBy {
     pub column: String,
-    pub direction: String,
+    pub direction: Direction,
+    pub nulls: Option<Nulls>,
+    /// Compares the lowercased values
+    pub case_insensitive: bool,
+}
+
+impl OrderBy {
+    pub fn new(column: impl Into<String>, direction: Direction) -> Self {
+        Self {
+            column: column.into(),
+            direction,
+            nulls: None,
+            case_insensitive: false,
+        }
+    }
+
+    pub fn nulls(mut self, nulls: Nulls) -> Self {
+        self.nulls = Some(nulls);
+        self
+    }
+
+    pub fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+
+    /// Applies a modifier of the compact format: `nulls_first`, `nulls_last` or `ci`
+    pub(crate) fn modifier(mut self, modifier: &str) -> Option<Self> {
+        match modifier.trim().to_lowercase().as_str() {
+            "ci" => self.case_insensitive = true,
+            modifier => self.nulls = Some(modifier.parse().ok()?),
+        }
+        Some(self)
+    }
+
+    /// SQL fragment of the ordering, e.g. `LOWER("users"."name") DESC NULLS LAST`
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the column isn't in `allowed`, columns come from
+    /// the query string and must never reach SQL unchecked
+    pub fn to_sql(&self, allowed: &[&str]) -> AppResult<String> {
+        if !allowed.contains(&self.column.as_str()) {
+            return Err(validation_error!("Invalid ordering", {
+                "order" => [format!("cannot order by '{}'", self.column)],
+            }));
+        }
+
+        let column = self
+            .column
+            .split('.')
+            .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        let mut sql = match self.case_insensitive {
+            true => format!("LOWER({column}) {}", self.direction.as_sql()),
+            false => format!("{column} {}", self.direction.as_sql()),
+        };
+
+        if let Some(nulls) = self.nulls {
+            sql.push(' ');
+            sql.push_str(nulls.as_sql());
+        }
+
+        Ok(sql)
+    }
+
+    /// SQL fragment of several orderings, e.g. for an `ORDER BY` clause
+    pub fn to_sql_list(orders: &[OrderBy], allowed: &[&str]) -> AppResult<String> {
+        Ok(orders
+            .iter()
+            .map(|order| order.to_sql(allowed))
+            .collect::<AppResult<Vec<_>>>()?
+            .join(", "))
+    }
+
+    /// Diesel expression of the ordering, for `order_by` and `then_order_by`
+    ///
+    /// ```
+    /// use diesel::prelude::*;
+    /// use foxtive::http::query::{Direction, Nulls, OrderBy};
+    ///
+    /// diesel::table! {
+    ///     users (id) {
+    ///         id -> Int4,
+    ///         name -> Nullable<Text>,
+    ///     }
+    /// }
+    ///
+    /// let order = OrderBy::new("name", Direction::Desc).nulls(Nulls::Last);
+    /// let query = users::table
+    ///     .order_by(order.to_diesel(&["id", "name"]).unwrap())
+    ///     .select(users::id);
+    ///
+    /// let sql = diesel::debug_query::<diesel::pg::Pg, _>(&query).to_string();
+    /// assert!(sql.contains(r#"ORDER BY "name" DESC NULLS LAST"#));
+    /// ```
+    #[cfg(feature = "database")]
+    pub fn to_diesel(
+        &self,
+        allowed: &[&str],
+    ) -> AppResult<diesel::expression::SqlLiteral<diesel::sql_types::Untyped>> {
+        Ok(diesel::dsl::sql::<diesel::sql_types::Untyped>(
+            &self.to_sql(allowed)?,
+        ))
+    }
 }
 
 /// Parse indexed parameter keys like "order[0][column]" into (index, field)
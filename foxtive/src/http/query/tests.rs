@@ -1,5 +1,5 @@
 // Example usage in handlers:
-use super::{OrderingFormat, QueryParams};
+use super::{Direction, Nulls, OrderBy, OrderingFormat, QueryParams};
 
 #[test]
 fn test_indexed_order_parsing() {
@@ -159,3 +159,130 @@ fn test_format_detection_methods() {
     assert!(both_params.has_ordering());
     assert_eq!(both_params.ordering_format(), OrderingFormat::Indexed); // Indexed takes priority
 }
+
+#[test]
+fn test_sparse_fieldsets_parsing() {
+    let query_str = "fields[user]=id,name,email&fields[post]=title&fields=id";
+    let params: QueryParams = serde_urlencoded::from_str(query_str).unwrap();
+    let fieldset = params.fieldset();
+
+    assert!(fieldset.includes("user", "email"));
+    assert!(!fieldset.includes("user", "password"));
+    assert!(fieldset.includes("post", "title"));
+    // types without a fieldset of their own use the plain one
+    assert!(fieldset.includes("comment", "id"));
+    assert!(!fieldset.includes("comment", "body"));
+
+    let whole: QueryParams = serde_urlencoded::from_str("page=2").unwrap();
+    assert!(whole.fieldset().is_empty());
+    assert!(whole.fieldset().includes("user", "password"));
+}
+
+#[test]
+fn test_sparse_fieldsets_validation() {
+    let params: QueryParams = serde_urlencoded::from_str("fields[user]=id,password").unwrap();
+    let fieldset = params.fieldset();
+
+    assert!(fieldset.validate("post", &["title"]).is_ok());
+    let err = fieldset.validate("user", &["id", "name"]).unwrap_err();
+    let message = err.downcast_ref::<crate::prelude::AppMessage>().unwrap();
+    assert_eq!(
+        message.status_code(),
+        crate::StatusCode::UNPROCESSABLE_ENTITY
+    );
+}
+
+#[test]
+fn test_sparse_fieldsets_serialization() {
+    #[derive(serde::Serialize)]
+    struct User {
+        id: u32,
+        name: &'static str,
+        email: &'static str,
+    }
+
+    let params: QueryParams = serde_urlencoded::from_str("fields[user]=id,name").unwrap();
+    let fieldset = params.fieldset();
+    let users = [User {
+        id: 1,
+        name: "Ada",
+        email: "ada@example.com",
+    }];
+
+    let json = serde_json::to_value(fieldset.apply("user", &users)).unwrap();
+    assert_eq!(json, serde_json::json!([{ "id": 1, "name": "Ada" }]));
+
+    let json = serde_json::to_value(fieldset.apply("post", &users[0])).unwrap();
+    assert_eq!(json["email"], "ada@example.com");
+}
+
+#[cfg(feature = "database")]
+#[test]
+fn test_sparse_fieldsets_keep_the_page_metadata() {
+    use crate::database::pagination::PageData;
+    use std::collections::BTreeMap;
+
+    let params: QueryParams = serde_urlencoded::from_str("fields=id").unwrap();
+    let fieldset = params.fieldset();
+    // a record with a field named like the page metadata
+    let record = BTreeMap::from([("id", 1), ("total_records", 40)]);
+    let page = PageData::new(vec![record], 1, 10, 1);
+
+    let json = serde_json::to_value(fieldset.apply_page("user", &page)).unwrap();
+    assert_eq!(json["records"], serde_json::json!([{ "id": 1 }]));
+    assert_eq!(json["total_records"], 1);
+    assert_eq!(json["has_next"], false);
+}
+
+#[test]
+fn test_compact_order_modifiers() {
+    let query_str = "order=name:ASCENDING:ci,deleted_at:desc:nulls_last,bad:asc:sideways";
+    let params: QueryParams = serde_urlencoded::from_str(query_str).unwrap();
+
+    let orders = params.parse_ordering();
+    assert_eq!(orders.len(), 2);
+    assert_eq!(
+        orders[0],
+        OrderBy::new("name", Direction::Asc).case_insensitive()
+    );
+    assert_eq!(
+        orders[1],
+        OrderBy::new("deleted_at", Direction::Desc).nulls(Nulls::Last)
+    );
+    assert_eq!(
+        params.ordering_description(),
+        "name ASC, deleted_at DESC NULLS LAST"
+    );
+}
+
+#[test]
+fn test_indexed_order_modifiers() {
+    let query_str = "order[0][column]=name&order[0][direction]=desc&order[0][nulls]=first&order[0][case_insensitive]=true";
+    let params: QueryParams = serde_urlencoded::from_str(query_str).unwrap();
+
+    let orders = params.parse_ordering();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].direction, Direction::Desc);
+    assert_eq!(orders[0].nulls, Some(Nulls::First));
+    assert!(orders[0].case_insensitive);
+}
+
+#[test]
+fn test_order_to_sql() {
+    let order = OrderBy::new("users.name", Direction::Desc)
+        .nulls(Nulls::Last)
+        .case_insensitive();
+    assert_eq!(
+        order.to_sql(&["users.name"]).unwrap(),
+        r#"LOWER("users"."name") DESC NULLS LAST"#
+    );
+
+    let orders = [order, OrderBy::new("id", Direction::Asc)];
+    assert_eq!(
+        OrderBy::to_sql_list(&orders, &["users.name", "id"]).unwrap(),
+        r#"LOWER("users"."name") DESC NULLS LAST, "id" ASC"#
+    );
+
+    let injected = OrderBy::new("id; DROP TABLE users", Direction::Asc);
+    assert!(injected.to_sql(&["id"]).is_err());
+}
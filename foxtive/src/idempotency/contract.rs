@@ -0,0 +1,18 @@
+use crate::idempotency::IdempotencyRecord;
+use crate::prelude::AppResult;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Contract for implementing idempotency key storage
+#[async_trait::async_trait]
+pub trait IdempotencyStoreContract: Send + Sync {
+    /// Atomically claims a key for `ttl`, returns `None` if it was claimed now and the
+    /// existing record otherwise
+    async fn claim(&self, key: &str, ttl: Duration) -> AppResult<Option<IdempotencyRecord>>;
+
+    /// Stores the response of a claimed key, kept for `ttl`
+    async fn complete(&self, key: &str, response: &Value, ttl: Duration) -> AppResult<()>;
+
+    /// Releases a claimed key, so the operation can be retried
+    async fn release(&self, key: &str) -> AppResult<()>;
+}
@@ -0,0 +1,286 @@
+//! # Idempotency Module
+//!
+//! Runs an operation at most once per idempotency key, e.g. the `Idempotency-Key` header
+//! of a request or the id of a payment webhook, so retried requests and redelivered
+//! messages don't charge a customer twice.
+//!
+//! The first call claims the key and runs the operation, its response is then stored
+//! for a configurable lifetime and returned to duplicates without running it again.
+//! A duplicate arriving while the operation still runs gets a conflict error, and a
+//! failed operation releases the key so it can be retried.
+//!
+//! Keys are kept in an [`IdempotencyStoreContract`], Redis or any cache driver.
+//!
+//! ## Features
+//!
+//! This module requires the `idempotency` feature to be enabled, the stores
+//! additionally require the `redis` or `cache` features.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::cache::drivers::InMemoryDriver;
+//! use foxtive::idempotency::{self, Idempotency};
+//! use foxtive::idempotency::stores::CacheIdempotencyStore;
+//! use std::sync::Arc;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let store = CacheIdempotencyStore::new(Arc::new(InMemoryDriver::new()));
+//! Idempotency::new(store).install().unwrap();
+//!
+//! // the webhook is delivered twice, the payment is only captured once
+//! for _ in 0..2 {
+//!     let receipt: String = idempotency::with_idempotency("evt_1042", || async {
+//!         Ok("captured pay_77".to_string())
+//!     })
+//!     .await
+//!     .unwrap();
+//!
+//!     assert_eq!(receipt, "captured pay_77");
+//! }
+//! # });
+//! ```
+
+pub mod contract;
+pub mod stores;
+
+pub use contract::IdempotencyStoreContract;
+
+use crate::prelude::AppResult;
+use crate::{conflict, internal_server_error};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, error};
+
+static IDEMPOTENCY: OnceLock<Idempotency> = OnceLock::new();
+
+/// State of an idempotency key, as kept by stores
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "response", rename_all = "snake_case")]
+pub enum IdempotencyRecord {
+    /// The operation is running
+    InProgress,
+    /// The operation succeeded with this response
+    Completed(Value),
+}
+
+/// Runs operations at most once per idempotency key.
+#[derive(Clone)]
+pub struct Idempotency {
+    store: Arc<dyn IdempotencyStoreContract>,
+    ttl: Duration,
+    lock_ttl: Duration,
+}
+
+impl Idempotency {
+    /// Creates a guard keeping responses for 24 hours, claims for 1 minute
+    pub fn new<S: IdempotencyStoreContract + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            lock_ttl: Duration::from_secs(60),
+        }
+    }
+
+    /// How long responses are kept and returned to duplicates
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// How long a claim lasts, so a key claimed by a crashed process is eventually
+    /// released. Should exceed the duration of the slowest operation.
+    pub fn lock_ttl(mut self, lock_ttl: Duration) -> Self {
+        self.lock_ttl = lock_ttl;
+        self
+    }
+
+    /// Installs the guard used by [`with_idempotency`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a guard is already installed
+    pub fn install(self) -> AppResult<()> {
+        IDEMPOTENCY
+            .set(self)
+            .map_err(|_| internal_server_error!("An idempotency guard is already installed"))
+    }
+
+    /// The guard set with [`Idempotency::install`]
+    pub fn global() -> Option<&'static Idempotency> {
+        IDEMPOTENCY.get()
+    }
+
+    /// Runs the operation unless the key was already used, in which case the stored
+    /// response is returned without running it.
+    ///
+    /// If the response can't be stored, it is still returned, and the key stays
+    /// claimed until the claim expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns a conflict error if the operation is already running for the key,
+    /// and the error of the operation if it fails, releasing the key
+    pub async fn run<T, F, Fut>(&self, key: &str, operation: F) -> AppResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<T>>,
+    {
+        match self.store.claim(key, self.lock_ttl).await? {
+            Some(IdempotencyRecord::Completed(response)) => {
+                debug!("[idempotency] replaying the response of '{key}'");
+                return Ok(serde_json::from_value(response)?);
+            }
+            Some(IdempotencyRecord::InProgress) => {
+                return Err(conflict!(
+                    "A request with this idempotency key is in progress"
+                ));
+            }
+            None => {}
+        }
+
+        let result = match operation().await {
+            Ok(result) => result,
+            Err(err) => {
+                if let Err(release_err) = self.store.release(key).await {
+                    error!("[idempotency] failed to release '{key}': {release_err}");
+                }
+                return Err(err);
+            }
+        };
+
+        let stored = match serde_json::to_value(&result) {
+            Ok(response) => self.store.complete(key, &response, self.ttl).await,
+            Err(err) => Err(err.into()),
+        };
+
+        if let Err(err) = stored {
+            error!("[idempotency] failed to store the response of '{key}': {err}");
+        }
+
+        Ok(result)
+    }
+}
+
+/// Runs the operation with the installed guard, see [`Idempotency::run`]
+///
+/// # Errors
+///
+/// Returns an error if no guard is installed
+pub async fn with_idempotency<T, F, Fut>(key: &str, operation: F) -> AppResult<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    match Idempotency::global() {
+        Some(idempotency) => idempotency.run(key, operation).await,
+        None => Err(internal_server_error!("No idempotency guard is installed")),
+    }
+}
+
+#[cfg(all(test, feature = "cache-in-memory"))]
+mod tests {
+    use super::*;
+    use crate::cache::drivers::InMemoryDriver;
+    use crate::idempotency::stores::CacheIdempotencyStore;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn guard() -> Idempotency {
+        Idempotency::new(CacheIdempotencyStore::new(Arc::new(InMemoryDriver::new())))
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_get_the_stored_response() {
+        let guard = guard();
+        let runs = AtomicU32::new(0);
+
+        for _ in 0..3 {
+            let response: u32 = guard
+                .run("payment-1", || async {
+                    Ok(runs.fetch_add(1, Ordering::SeqCst) + 100)
+                })
+                .await
+                .unwrap();
+            assert_eq!(response, 100);
+        }
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failures_release_the_key() {
+        let guard = guard();
+
+        let failed = guard
+            .run::<u32, _, _>("payment-2", || async { Err(anyhow::anyhow!("declined")) })
+            .await;
+        assert!(failed.is_err());
+
+        let retried = guard.run("payment-2", || async { Ok(7) }).await.unwrap();
+        assert_eq!(retried, 7);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_in_progress_conflict() {
+        let guard = guard();
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let first = guard.run("payment-3", || async {
+            started_tx.send(()).unwrap();
+            finish_rx.await.unwrap();
+            Ok(1)
+        });
+        let duplicate = async {
+            started_rx.await.unwrap();
+            let result = guard
+                .run::<u32, _, _>("payment-3", || async { Ok(2) })
+                .await;
+            finish_tx.send(()).unwrap();
+            result
+        };
+
+        let (first, duplicate) = tokio::join!(first, duplicate);
+        assert_eq!(first.unwrap(), 1);
+        assert!(duplicate.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keys_expire() {
+        let guard = guard().ttl(Duration::from_millis(50));
+
+        assert_eq!(guard.run("payment-4", || async { Ok(1) }).await.unwrap(), 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(guard.run("payment-4", || async { Ok(2) }).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_expired_records_are_pruned() {
+        let store = CacheIdempotencyStore::new(Arc::new(InMemoryDriver::new()));
+        let response = serde_json::json!(1);
+        store
+            .complete("payment-5", &response, Duration::from_millis(50))
+            .await
+            .unwrap();
+        store
+            .complete("payment-6", &response, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(store.prune().await.unwrap(), 1);
+        assert!(
+            store
+                .claim("payment-6", Duration::from_secs(60))
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
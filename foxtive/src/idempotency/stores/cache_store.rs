@@ -0,0 +1,125 @@
+use crate::cache::KeyPattern;
+use crate::cache::contract::CacheDriverContract;
+use crate::idempotency::IdempotencyRecord;
+use crate::idempotency::contract::IdempotencyStoreContract;
+use crate::prelude::AppResult;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stores idempotency keys in a cache driver under `{prefix}{key}`, claimed with
+/// [`CacheDriverContract::add_raw`], or [`CacheDriverContract::replace_raw`] over an
+/// expired record, which are atomic on the built-in drivers.
+///
+/// As explained for [`CacheDriverContract`], the expiry is stored along with the record.
+/// Expired records are replaced when the key is claimed again, completed ones expire on
+/// drivers supporting it, and [`prune`](Self::prune) removes them from the others.
+pub struct CacheIdempotencyStore {
+    driver: Arc<dyn CacheDriverContract>,
+    prefix: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRecord {
+    record: IdempotencyRecord,
+    /// Unix timestamp, in milliseconds
+    expires_at: i64,
+}
+
+impl CacheIdempotencyStore {
+    /// Stores keys under the `idempotency:` prefix
+    pub fn new(driver: Arc<dyn CacheDriverContract>) -> Self {
+        Self {
+            driver,
+            prefix: "idempotency:".to_string(),
+        }
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Removes the expired records, returning how many were removed
+    ///
+    /// Keys never used again are otherwise kept forever on drivers that don't expire
+    /// values, run it periodically with them.
+    pub async fn prune(&self) -> AppResult<usize> {
+        let now = Utc::now().timestamp_millis();
+        let keys = self
+            .driver
+            .keys_by_pattern(&KeyPattern::prefix(&self.prefix))
+            .await?;
+
+        let mut pruned = 0;
+        for key in keys {
+            let Some(raw) = self.driver.get_raw(&key).await? else {
+                continue;
+            };
+
+            let expired = serde_json::from_str::<StoredRecord>(&raw)
+                .is_ok_and(|stored| stored.expires_at <= now);
+            if expired {
+                pruned += self.driver.forget(&key).await? as usize;
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+
+    fn encode(record: IdempotencyRecord, ttl: Duration) -> AppResult<String> {
+        let ttl = i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX);
+        Ok(serde_json::to_string(&StoredRecord {
+            record,
+            expires_at: Utc::now().timestamp_millis().saturating_add(ttl),
+        })?)
+    }
+}
+
+#[async_trait::async_trait]
+impl IdempotencyStoreContract for CacheIdempotencyStore {
+    async fn claim(&self, key: &str, ttl: Duration) -> AppResult<Option<IdempotencyRecord>> {
+        let key = self.key(key);
+        let claim = Self::encode(IdempotencyRecord::InProgress, ttl)?;
+
+        if self.driver.add_raw(&key, claim.clone()).await? {
+            return Ok(None);
+        }
+
+        let Some(raw) = self.driver.get_raw(&key).await? else {
+            // released in between
+            return match self.driver.add_raw(&key, claim).await? {
+                true => Ok(None),
+                false => Ok(Some(IdempotencyRecord::InProgress)),
+            };
+        };
+
+        let stored = serde_json::from_str::<StoredRecord>(&raw)?;
+        if stored.expires_at > Utc::now().timestamp_millis() {
+            return Ok(Some(stored.record));
+        }
+
+        // only replaced if no one claimed it since it was read
+        match self.driver.replace_raw(&key, &raw, claim).await? {
+            true => Ok(None),
+            false => Ok(Some(IdempotencyRecord::InProgress)),
+        }
+    }
+
+    async fn complete(&self, key: &str, response: &Value, ttl: Duration) -> AppResult<()> {
+        let record = Self::encode(IdempotencyRecord::Completed(response.clone()), ttl)?;
+        self.driver.put_raw_for(&self.key(key), record, ttl).await?;
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> AppResult<()> {
+        self.driver.forget(&self.key(key)).await?;
+        Ok(())
+    }
+}
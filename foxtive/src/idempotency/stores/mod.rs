@@ -0,0 +1,9 @@
+#[cfg(feature = "cache")]
+mod cache_store;
+#[cfg(feature = "redis")]
+mod redis_store;
+
+#[cfg(feature = "cache")]
+pub use cache_store::CacheIdempotencyStore;
+#[cfg(feature = "redis")]
+pub use redis_store::RedisIdempotencyStore;
@@ -0,0 +1,76 @@
+use crate::idempotency::IdempotencyRecord;
+use crate::idempotency::contract::IdempotencyStoreContract;
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use crate::results::redis_result::RedisResultToAppResult;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stores idempotency keys as JSON under `{prefix}{key}`, expired by Redis itself
+pub struct RedisIdempotencyStore {
+    redis: Arc<Redis>,
+    prefix: String,
+}
+
+impl RedisIdempotencyStore {
+    /// Stores keys under the `idempotency:` prefix
+    pub fn new(redis: Arc<Redis>) -> Self {
+        Self {
+            redis,
+            prefix: "idempotency:".to_string(),
+        }
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+}
+
+#[async_trait::async_trait]
+impl IdempotencyStoreContract for RedisIdempotencyStore {
+    async fn claim(&self, key: &str, ttl: Duration) -> AppResult<Option<IdempotencyRecord>> {
+        let key = self.key(key);
+        let content = serde_json::to_string(&IdempotencyRecord::InProgress)?;
+        let millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+
+        // SET NX with an expiry, so a crashed claimant doesn't hold the key forever
+        let mut conn = self.redis.redis().await?;
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(self.redis.key(&key).as_ref())
+            .arg(content)
+            .arg("NX")
+            .arg("PX")
+            .arg(millis)
+            .query_async(&mut *conn)
+            .await
+            .into_app_result()?;
+
+        match claimed {
+            Some(_) => Ok(None),
+            None => Ok(Some(
+                self.redis
+                    .get_json(&key)
+                    .await?
+                    .unwrap_or(IdempotencyRecord::InProgress),
+            )),
+        }
+    }
+
+    async fn complete(&self, key: &str, response: &Value, ttl: Duration) -> AppResult<()> {
+        let record = IdempotencyRecord::Completed(response.clone());
+        self.redis
+            .set_json(&self.key(key), &record, Some(ttl))
+            .await
+    }
+
+    async fn release(&self, key: &str) -> AppResult<()> {
+        self.redis.delete(&self.key(key)).await?;
+        Ok(())
+    }
+}
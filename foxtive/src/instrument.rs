@@ -0,0 +1,24 @@
+//! Times calls made on dependencies, recorded with
+//! [`record_operation`](crate::metrics::record_operation), a pass-through without the
+//! `metrics` feature.
+
+use std::future::Future;
+
+#[cfg(feature = "metrics")]
+pub(crate) async fn observe<T, E, F>(component: &str, operation: &str, future: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    let started_at = std::time::Instant::now();
+    let result = future.await;
+    crate::metrics::record_operation(component, operation, result.is_ok(), started_at.elapsed());
+    result
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) async fn observe<T, E, F>(_component: &str, _operation: &str, future: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    future.await
+}
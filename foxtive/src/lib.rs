@@ -1,26 +1,66 @@
 use std::collections::HashMap;
 use std::sync::OnceLock;
 
+// lets the derive macros refer to `::foxtive` from within the crate
+extern crate self as foxtive;
+
 pub mod enums;
 pub mod results;
 
 #[cfg(feature = "redis")]
 pub mod redis;
 
+#[cfg(feature = "app")]
+pub mod app;
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "authz")]
+pub mod authz;
 #[cfg(feature = "cache")]
 pub mod cache;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod context;
 #[cfg(feature = "database")]
 pub mod database;
 mod env;
+#[cfg(feature = "events")]
+pub mod events;
 pub mod ext;
 mod ext_impl;
+#[cfg(feature = "health")]
+pub mod health;
 pub mod helpers;
 #[cfg(feature = "http")]
 pub mod http;
+#[cfg(feature = "idempotency")]
+pub mod idempotency;
+#[cfg(any(feature = "cache", feature = "redis", feature = "rabbitmq"))]
+mod instrument;
 pub mod macros;
+#[cfg(feature = "maintenance")]
+pub mod maintenance;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "notify")]
+pub mod notify;
+#[cfg(feature = "queue")]
+pub mod queue;
 #[cfg(feature = "rabbitmq")]
 pub mod rabbitmq;
+#[cfg(feature = "saga")]
+pub mod saga;
+#[cfg(feature = "secrets")]
+pub mod secrets;
+#[cfg(feature = "session")]
+pub mod session;
 pub mod setup;
+#[cfg(feature = "storage")]
+pub mod storage;
+#[cfg(feature = "templating")]
+pub mod templating;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 pub mod tokio;
 
 pub static FOXTIVE: OnceLock<FoxtiveState> = OnceLock::new();
@@ -33,6 +73,10 @@ pub use ::http::StatusCode;
 pub use anyhow::Error;
 pub use async_trait::async_trait;
 pub use env::Environment;
+#[cfg(feature = "cron")]
+pub use foxtive_cron as cron;
+#[cfg(feature = "supervisor")]
+pub use foxtive_supervisor as supervisor;
 #[cfg(feature = "templating")]
 pub use tera::Context as TemplateContext;
 
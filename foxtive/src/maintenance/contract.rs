@@ -0,0 +1,12 @@
+use crate::maintenance::MaintenanceState;
+use crate::prelude::AppResult;
+
+/// Contract for implementing storage of the maintenance flag, shared by every instance
+#[async_trait::async_trait]
+pub trait MaintenanceStoreContract: Send + Sync {
+    async fn get(&self) -> AppResult<Option<MaintenanceState>>;
+
+    async fn put(&self, state: &MaintenanceState) -> AppResult<()>;
+
+    async fn clear(&self) -> AppResult<()>;
+}
@@ -0,0 +1,285 @@
+//! # Maintenance Module
+//!
+//! A maintenance flag shared by every instance of the application through Redis or a
+//! cache driver. While it's on, HTTP handlers answer with `503 Service Unavailable`,
+//! cron skips its jobs and queue workers stop consuming, e.g. during a database
+//! migration.
+//!
+//! Reads are cached locally for a configurable interval, so checking the flag on every
+//! request doesn't hit the store. A maintenance window can be given a duration, after
+//! which it ends by itself.
+//!
+//! ## Features
+//!
+//! This module requires the `maintenance` feature to be enabled, the stores
+//! additionally require the `redis` or `cache` features. Skipping cron jobs requires
+//! the `cron` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::cache::drivers::InMemoryDriver;
+//! use foxtive::maintenance::Maintenance;
+//! use foxtive::maintenance::stores::CacheMaintenanceStore;
+//! use foxtive::StatusCode;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let store = CacheMaintenanceStore::new(Arc::new(InMemoryDriver::new()));
+//! Maintenance::new(store).install().unwrap();
+//!
+//! // admin endpoint
+//! let maintenance = Maintenance::global().unwrap();
+//! maintenance
+//!     .enable("Upgrading the database", Some(Duration::from_secs(600)))
+//!     .await
+//!     .unwrap();
+//!
+//! // HTTP middleware
+//! let err = maintenance.check().await.unwrap_err();
+//! assert_eq!(err.to_string(), "Upgrading the database");
+//!
+//! maintenance.disable().await.unwrap();
+//! assert!(maintenance.check().await.is_ok());
+//! # });
+//! ```
+
+pub mod contract;
+pub mod stores;
+
+pub use contract::MaintenanceStoreContract;
+
+use crate::enums::AppMessage;
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use chrono::Utc;
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+static MAINTENANCE: OnceLock<Maintenance> = OnceLock::new();
+
+/// A maintenance window, as kept by stores
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceState {
+    /// Shown to clients, e.g. in the 503 response
+    pub message: String,
+    /// Unix timestamp, in milliseconds
+    pub since: i64,
+    /// When the maintenance ends by itself, Unix timestamp in milliseconds
+    pub until: Option<i64>,
+}
+
+impl MaintenanceState {
+    /// Whether the maintenance reached its planned end
+    pub fn is_over(&self) -> bool {
+        self.until
+            .is_some_and(|until| until <= Utc::now().timestamp_millis())
+    }
+
+    /// Time left until the planned end, e.g. for a `Retry-After` header
+    pub fn retry_after(&self) -> Option<Duration> {
+        let left = self.until? - Utc::now().timestamp_millis();
+        Some(Duration::from_millis(u64::try_from(left).unwrap_or(0)))
+    }
+}
+
+struct Cached {
+    state: Option<MaintenanceState>,
+    fetched_at: Instant,
+}
+
+/// Reads and toggles the maintenance flag.
+#[derive(Clone)]
+pub struct Maintenance {
+    store: Arc<dyn MaintenanceStoreContract>,
+    refresh_interval: Duration,
+    cached: Arc<RwLock<Option<Cached>>>,
+}
+
+impl Maintenance {
+    /// Creates a flag read from the store at most once per second
+    pub fn new<S: MaintenanceStoreContract + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            refresh_interval: Duration::from_secs(1),
+            cached: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// How long a read is reused, and thus how late other instances notice a toggle
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Installs the flag returned by [`Maintenance::global`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a flag is already installed
+    pub fn install(self) -> AppResult<()> {
+        MAINTENANCE
+            .set(self)
+            .map_err(|_| internal_server_error!("A maintenance flag is already installed"))
+    }
+
+    /// The flag set with [`Maintenance::install`]
+    pub fn global() -> Option<&'static Maintenance> {
+        MAINTENANCE.get()
+    }
+
+    /// Puts the application in maintenance, for the given duration or until disabled
+    pub async fn enable(&self, message: &str, duration: Option<Duration>) -> AppResult<()> {
+        let now = Utc::now().timestamp_millis();
+        let state = MaintenanceState {
+            message: message.to_string(),
+            since: now,
+            until: duration.map(|duration| {
+                now.saturating_add(i64::try_from(duration.as_millis()).unwrap_or(i64::MAX))
+            }),
+        };
+
+        self.store.put(&state).await?;
+        info!("[maintenance] enabled: {message}");
+        self.cache(Some(state));
+        Ok(())
+    }
+
+    pub async fn disable(&self) -> AppResult<()> {
+        self.store.clear().await?;
+        info!("[maintenance] disabled");
+        self.cache(None);
+        Ok(())
+    }
+
+    /// The current maintenance window, `None` if the application isn't in maintenance
+    pub async fn state(&self) -> AppResult<Option<MaintenanceState>> {
+        let cached = match &*self.cached.read().unwrap() {
+            Some(cached) if cached.fetched_at.elapsed() < self.refresh_interval => {
+                Some(cached.state.clone())
+            }
+            _ => None,
+        };
+
+        let state = match cached {
+            Some(state) => state,
+            None => {
+                let state = self.store.get().await?;
+                self.cache(state.clone());
+                state
+            }
+        };
+
+        Ok(state.filter(|state| !state.is_over()))
+    }
+
+    /// Whether the application is in maintenance.
+    ///
+    /// A store that can't be read is logged and reported as not in maintenance,
+    /// so an unreachable store doesn't take the application down.
+    pub async fn is_active(&self) -> bool {
+        match self.state().await {
+            Ok(state) => state.is_some(),
+            Err(err) => {
+                error!("[maintenance] failed to read the flag: {err}");
+                false
+            }
+        }
+    }
+
+    /// For HTTP middleware, fails with a `503 Service Unavailable` carrying the
+    /// maintenance message while the application is in maintenance
+    pub async fn check(&self) -> AppResult<()> {
+        match self.state().await? {
+            Some(state) => Err(crate::Error::from(AppMessage::ErrorMessage(
+                state.message,
+                StatusCode::SERVICE_UNAVAILABLE,
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Waits for the maintenance to end, checking every `poll`, e.g. before a
+    /// consumer takes its next message
+    pub async fn wait_until_over(&self, poll: Duration) {
+        while self.is_active().await {
+            tokio::time::sleep(poll).await;
+        }
+    }
+
+    fn cache(&self, state: Option<MaintenanceState>) {
+        *self.cached.write().unwrap() = Some(Cached {
+            state,
+            fetched_at: Instant::now(),
+        });
+    }
+}
+
+#[cfg(feature = "cron")]
+#[async_trait::async_trait]
+impl foxtive_cron::contracts::RunCondition for Maintenance {
+    async fn allows(&self, _id: &str, _name: &str) -> bool {
+        !self.is_active().await
+    }
+}
+
+#[cfg(all(test, feature = "cache-in-memory"))]
+mod tests {
+    use super::*;
+    use crate::cache::drivers::InMemoryDriver;
+    use crate::maintenance::stores::CacheMaintenanceStore;
+
+    #[tokio::test]
+    async fn test_enable_and_disable() {
+        let maintenance =
+            Maintenance::new(CacheMaintenanceStore::new(Arc::new(InMemoryDriver::new())));
+        assert!(!maintenance.is_active().await);
+
+        maintenance.enable("Migrating", None).await.unwrap();
+        assert!(maintenance.is_active().await);
+
+        let err = maintenance.check().await.unwrap_err();
+        let message = err.downcast_ref::<AppMessage>().unwrap();
+        assert_eq!(message.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(message.message(), "Migrating");
+
+        maintenance.disable().await.unwrap();
+        assert!(!maintenance.is_active().await);
+        assert!(maintenance.check().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_ends_after_its_duration() {
+        let maintenance =
+            Maintenance::new(CacheMaintenanceStore::new(Arc::new(InMemoryDriver::new())));
+
+        maintenance
+            .enable("Migrating", Some(Duration::from_millis(50)))
+            .await
+            .unwrap();
+        let state = maintenance.state().await.unwrap().unwrap();
+        assert!(state.retry_after().unwrap() <= Duration::from_millis(50));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!maintenance.is_active().await);
+    }
+
+    #[tokio::test]
+    async fn test_toggles_reach_other_instances_after_refresh() {
+        let driver = Arc::new(InMemoryDriver::new());
+        let admin = Maintenance::new(CacheMaintenanceStore::new(driver.clone()));
+        let worker = Maintenance::new(CacheMaintenanceStore::new(driver))
+            .refresh_interval(Duration::from_millis(50));
+
+        assert!(!worker.is_active().await);
+        admin.enable("Migrating", None).await.unwrap();
+        assert!(!worker.is_active().await);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(worker.is_active().await);
+    }
+}
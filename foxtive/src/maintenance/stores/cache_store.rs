@@ -0,0 +1,47 @@
+use crate::cache::contract::CacheDriverContract;
+use crate::maintenance::MaintenanceState;
+use crate::maintenance::contract::MaintenanceStoreContract;
+use crate::prelude::AppResult;
+use std::sync::Arc;
+
+/// Stores the maintenance flag in a cache driver, under the `maintenance` key by default
+pub struct CacheMaintenanceStore {
+    driver: Arc<dyn CacheDriverContract>,
+    key: String,
+}
+
+impl CacheMaintenanceStore {
+    pub fn new(driver: Arc<dyn CacheDriverContract>) -> Self {
+        Self {
+            driver,
+            key: "maintenance".to_string(),
+        }
+    }
+
+    pub fn key(mut self, key: &str) -> Self {
+        self.key = key.to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl MaintenanceStoreContract for CacheMaintenanceStore {
+    async fn get(&self) -> AppResult<Option<MaintenanceState>> {
+        match self.driver.get_raw(&self.key).await? {
+            Some(raw) => Ok(Some(serde_json::from_str(&raw)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, state: &MaintenanceState) -> AppResult<()> {
+        self.driver
+            .put_raw(&self.key, serde_json::to_string(state)?)
+            .await?;
+        Ok(())
+    }
+
+    async fn clear(&self) -> AppResult<()> {
+        self.driver.forget(&self.key).await?;
+        Ok(())
+    }
+}
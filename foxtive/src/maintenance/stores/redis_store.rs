@@ -0,0 +1,41 @@
+use crate::maintenance::MaintenanceState;
+use crate::maintenance::contract::MaintenanceStoreContract;
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use std::sync::Arc;
+
+/// Stores the maintenance flag as JSON, under the `maintenance` key by default
+pub struct RedisMaintenanceStore {
+    redis: Arc<Redis>,
+    key: String,
+}
+
+impl RedisMaintenanceStore {
+    pub fn new(redis: Arc<Redis>) -> Self {
+        Self {
+            redis,
+            key: "maintenance".to_string(),
+        }
+    }
+
+    pub fn key(mut self, key: &str) -> Self {
+        self.key = key.to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl MaintenanceStoreContract for RedisMaintenanceStore {
+    async fn get(&self) -> AppResult<Option<MaintenanceState>> {
+        self.redis.get_json(&self.key).await
+    }
+
+    async fn put(&self, state: &MaintenanceState) -> AppResult<()> {
+        self.redis.set_json(&self.key, state, None).await
+    }
+
+    async fn clear(&self) -> AppResult<()> {
+        self.redis.delete(&self.key).await?;
+        Ok(())
+    }
+}
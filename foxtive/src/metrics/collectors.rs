@@ -0,0 +1,446 @@
+#[allow(unused_imports)]
+use crate::metrics::{MetricFamily, MetricsCollector};
+#[allow(unused_imports)]
+use std::sync::Arc;
+
+/// Reports CPU time, memory, threads and open file descriptors of the process, on Linux
+pub struct ProcessCollector;
+
+#[async_trait::async_trait]
+impl MetricsCollector for ProcessCollector {
+    async fn collect(&self) -> Vec<MetricFamily> {
+        #[cfg(target_os = "linux")]
+        {
+            linux_process_families()
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            vec![]
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn linux_process_families() -> Vec<MetricFamily> {
+    /// `sysconf(_SC_CLK_TCK)`, 100 on every mainstream Linux platform
+    const CLOCK_TICKS_PER_SECOND: f64 = 100.0;
+
+    let mut families = vec![];
+
+    // fields after the parenthesised command name, which may itself contain spaces
+    if let Ok(stat) = std::fs::read_to_string("/proc/self/stat")
+        && let Some((_, fields)) = stat.rsplit_once(')')
+    {
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+        let field = |index: usize| fields.get(index).and_then(|v| v.parse::<f64>().ok());
+
+        if let (Some(utime), Some(stime)) = (field(11), field(12)) {
+            families.push(
+                MetricFamily::counter(
+                    "process_cpu_seconds_total",
+                    "Total user and system CPU time spent in seconds.",
+                )
+                .sample(&[], (utime + stime) / CLOCK_TICKS_PER_SECOND),
+            );
+        }
+
+        if let Some(threads) = field(17) {
+            families.push(
+                MetricFamily::gauge("process_threads", "Number of OS threads in the process.")
+                    .sample(&[], threads),
+            );
+        }
+    }
+
+    if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+        let kilobytes = |key: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(key))
+                .and_then(|rest| rest.split_whitespace().next())
+                .and_then(|value| value.parse::<f64>().ok())
+        };
+
+        if let Some(rss) = kilobytes("VmRSS:") {
+            families.push(
+                MetricFamily::gauge(
+                    "process_resident_memory_bytes",
+                    "Resident memory size in bytes.",
+                )
+                .sample(&[], rss * 1024.0),
+            );
+        }
+
+        if let Some(virtual_memory) = kilobytes("VmSize:") {
+            families.push(
+                MetricFamily::gauge(
+                    "process_virtual_memory_bytes",
+                    "Virtual memory size in bytes.",
+                )
+                .sample(&[], virtual_memory * 1024.0),
+            );
+        }
+    }
+
+    if let Ok(fds) = std::fs::read_dir("/proc/self/fd") {
+        families.push(
+            MetricFamily::gauge("process_open_fds", "Number of open file descriptors.")
+                .sample(&[], fds.count() as f64),
+        );
+    }
+
+    families
+}
+
+/// Families describing a connection pool, labelled with its name
+#[allow(dead_code)]
+fn pool_families(
+    pool: &str,
+    connections: usize,
+    idle: usize,
+    max: usize,
+    waiting: Option<usize>,
+) -> Vec<MetricFamily> {
+    let labels = [("pool", pool)];
+    let mut families = vec![
+        MetricFamily::gauge(
+            "foxtive_pool_connections",
+            "Connections currently open by the pool.",
+        )
+        .sample(&labels, connections as f64),
+        MetricFamily::gauge(
+            "foxtive_pool_idle_connections",
+            "Open connections not in use.",
+        )
+        .sample(&labels, idle as f64),
+        MetricFamily::gauge(
+            "foxtive_pool_max_connections",
+            "Maximum number of connections of the pool.",
+        )
+        .sample(&labels, max as f64),
+    ];
+
+    if let Some(waiting) = waiting {
+        families.push(
+            MetricFamily::gauge("foxtive_pool_waiting", "Callers waiting for a connection.")
+                .sample(&labels, waiting as f64),
+        );
+    }
+
+    families
+}
+
+/// Reports the usage of the database connection pool
+#[cfg(feature = "database")]
+pub struct DatabasePoolCollector {
+    pool: crate::database::DBPool,
+}
+
+#[cfg(feature = "database")]
+impl DatabasePoolCollector {
+    pub fn new(pool: crate::database::DBPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "database")]
+#[async_trait::async_trait]
+impl MetricsCollector for DatabasePoolCollector {
+    async fn collect(&self) -> Vec<MetricFamily> {
+        let state = self.pool.state();
+        pool_families(
+            "database",
+            state.connections as usize,
+            state.idle_connections as usize,
+            self.pool.max_size() as usize,
+            None,
+        )
+    }
+}
+
+/// Reports the usage of the Redis connection pool
+#[cfg(feature = "redis")]
+pub struct RedisPoolCollector {
+    pool: deadpool_redis::Pool,
+}
+
+#[cfg(feature = "redis")]
+impl RedisPoolCollector {
+    pub fn new(pool: deadpool_redis::Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait::async_trait]
+impl MetricsCollector for RedisPoolCollector {
+    async fn collect(&self) -> Vec<MetricFamily> {
+        let status = self.pool.status();
+        pool_families(
+            "redis",
+            status.size,
+            status.available,
+            status.max_size,
+            Some(status.waiting),
+        )
+    }
+}
+
+/// Reports the usage of the RabbitMQ connection pool
+#[cfg(feature = "rabbitmq")]
+pub struct RabbitMqPoolCollector {
+    pool: deadpool_lapin::Pool,
+}
+
+#[cfg(feature = "rabbitmq")]
+impl RabbitMqPoolCollector {
+    pub fn new(pool: deadpool_lapin::Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "rabbitmq")]
+#[async_trait::async_trait]
+impl MetricsCollector for RabbitMqPoolCollector {
+    async fn collect(&self) -> Vec<MetricFamily> {
+        let status = self.pool.status();
+        pool_families(
+            "rabbitmq",
+            status.size,
+            status.available,
+            status.max_size,
+            Some(status.waiting),
+        )
+    }
+}
+
+/// Reports the cache's hits and misses
+#[cfg(feature = "cache")]
+pub struct CacheCollector {
+    cache: Arc<crate::cache::Cache>,
+}
+
+#[cfg(feature = "cache")]
+impl CacheCollector {
+    pub fn new(cache: Arc<crate::cache::Cache>) -> Self {
+        Self { cache }
+    }
+}
+
+#[cfg(feature = "cache")]
+#[async_trait::async_trait]
+impl MetricsCollector for CacheCollector {
+    async fn collect(&self) -> Vec<MetricFamily> {
+        let stats = self.cache.stats();
+        vec![
+            MetricFamily::counter(
+                "foxtive_cache_hits_total",
+                "Cache lookups that found a value.",
+            )
+            .sample(&[], stats.hits() as f64),
+            MetricFamily::counter(
+                "foxtive_cache_misses_total",
+                "Cache lookups that found nothing.",
+            )
+            .sample(&[], stats.misses() as f64),
+        ]
+    }
+}
+
+/// Counts supervisor task lifecycle events, register it both as a supervisor
+/// listener and as a collector
+///
+/// # Example
+///
+/// ```no_run
+/// use foxtive::metrics::{self, SupervisorMetrics};
+/// use foxtive::supervisor::Supervisor;
+/// use std::sync::Arc;
+///
+/// let task_metrics = Arc::new(SupervisorMetrics::new());
+/// metrics::register(task_metrics.clone());
+///
+/// let supervisor = Supervisor::new().add_listener(task_metrics);
+/// ```
+#[cfg(feature = "supervisor")]
+#[derive(Default)]
+pub struct SupervisorMetrics {
+    tasks: std::sync::Mutex<std::collections::BTreeMap<String, TaskCounters>>,
+}
+
+#[cfg(feature = "supervisor")]
+#[derive(Default)]
+struct TaskCounters {
+    starts: u64,
+    failures: u64,
+    panics: u64,
+    running: bool,
+}
+
+#[cfg(feature = "supervisor")]
+impl SupervisorMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "supervisor")]
+#[async_trait::async_trait]
+impl foxtive_supervisor::contracts::SupervisorEventListener for SupervisorMetrics {
+    async fn on_event(&self, event: foxtive_supervisor::enums::SupervisorEvent) {
+        use foxtive_supervisor::enums::SupervisorEvent;
+
+        let mut tasks = self.tasks.lock().unwrap();
+        match event {
+            SupervisorEvent::TaskStarted { id, .. } => {
+                let task = tasks.entry(id).or_default();
+                task.starts += 1;
+                task.running = true;
+            }
+            SupervisorEvent::TaskFailed { id, .. } => {
+                let task = tasks.entry(id).or_default();
+                task.failures += 1;
+                task.running = false;
+            }
+            SupervisorEvent::TaskPanicked { id, .. } => {
+                let task = tasks.entry(id).or_default();
+                task.panics += 1;
+                task.running = false;
+            }
+            SupervisorEvent::TaskFinished { id, .. } | SupervisorEvent::TaskStopped { id, .. } => {
+                tasks.entry(id).or_default().running = false;
+            }
+            SupervisorEvent::TaskRemoved { id, .. } => {
+                tasks.remove(&id);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "supervisor")]
+#[async_trait::async_trait]
+impl MetricsCollector for SupervisorMetrics {
+    async fn collect(&self) -> Vec<MetricFamily> {
+        let tasks = self.tasks.lock().unwrap();
+
+        let mut starts = MetricFamily::counter(
+            "foxtive_supervisor_task_starts_total",
+            "Attempts started by supervised tasks.",
+        );
+        let mut failures = MetricFamily::counter(
+            "foxtive_supervisor_task_failures_total",
+            "Attempts of supervised tasks that returned an error.",
+        );
+        let mut panics = MetricFamily::counter(
+            "foxtive_supervisor_task_panics_total",
+            "Attempts of supervised tasks that panicked.",
+        );
+        let mut running = MetricFamily::gauge(
+            "foxtive_supervisor_task_running",
+            "Whether a supervised task is currently running.",
+        );
+
+        for (id, task) in tasks.iter() {
+            let labels = [("task", id.as_str())];
+            starts = starts.sample(&labels, task.starts as f64);
+            failures = failures.sample(&labels, task.failures as f64);
+            panics = panics.sample(&labels, task.panics as f64);
+            running = running.sample(&labels, f64::from(u8::from(task.running)));
+        }
+
+        vec![starts, failures, panics, running]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_collector_reports_memory() {
+        let families = ProcessCollector.collect().await;
+        let rss = families
+            .iter()
+            .find(|family| family.name == "process_resident_memory_bytes")
+            .expect("resident memory is reported");
+
+        assert!(rss.samples[0].value > 0.0);
+        assert!(
+            families
+                .iter()
+                .any(|family| family.name == "process_cpu_seconds_total")
+        );
+    }
+
+    #[test]
+    fn test_pool_families_are_labelled() {
+        let families = pool_families("redis", 4, 1, 8, Some(0));
+        assert_eq!(families.len(), 4);
+        assert!(families.iter().all(|family| {
+            family.samples[0].labels == vec![("pool".to_string(), "redis".to_string())]
+        }));
+    }
+
+    #[cfg(feature = "cache-in-memory")]
+    #[tokio::test]
+    async fn test_cache_collector_reports_hits_and_misses() {
+        use crate::cache::Cache;
+        use crate::cache::drivers::InMemoryDriver;
+
+        let cache = Arc::new(Cache::new(Arc::new(InMemoryDriver::new())));
+        cache.put("key", &1).await.unwrap();
+        let _: Option<i32> = cache.get("key").await.unwrap();
+        let _: Option<i32> = cache.get("missing").await.unwrap();
+        let _: Option<i32> = cache.pull("key").await.unwrap();
+
+        let families = CacheCollector::new(cache).collect().await;
+        assert_eq!(families[0].samples[0].value, 2.0);
+        assert_eq!(families[1].samples[0].value, 1.0);
+    }
+
+    #[cfg(feature = "supervisor")]
+    #[tokio::test]
+    async fn test_supervisor_metrics_count_task_events() {
+        use foxtive_supervisor::contracts::SupervisorEventListener;
+        use foxtive_supervisor::enums::SupervisorEvent;
+
+        let metrics = SupervisorMetrics::new();
+        metrics
+            .on_event(SupervisorEvent::TaskStarted {
+                id: "worker".to_string(),
+                name: "Worker".to_string(),
+                attempt: 1,
+            })
+            .await;
+        metrics
+            .on_event(SupervisorEvent::TaskFailed {
+                id: "worker".to_string(),
+                name: "Worker".to_string(),
+                attempt: 1,
+                error: "boom".to_string(),
+            })
+            .await;
+        metrics
+            .on_event(SupervisorEvent::TaskStarted {
+                id: "worker".to_string(),
+                name: "Worker".to_string(),
+                attempt: 2,
+            })
+            .await;
+
+        let families = metrics.collect().await;
+        let value = |name: &str| {
+            families
+                .iter()
+                .find(|family| family.name == name)
+                .map(|family| family.samples[0].value)
+        };
+
+        assert_eq!(value("foxtive_supervisor_task_starts_total"), Some(2.0));
+        assert_eq!(value("foxtive_supervisor_task_failures_total"), Some(1.0));
+        assert_eq!(value("foxtive_supervisor_task_running"), Some(1.0));
+    }
+}
@@ -0,0 +1,390 @@
+//! # Metrics Module
+//!
+//! Collects metrics about the process and the application's dependencies and renders them
+//! in the [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/),
+//! ready to be returned by a `/metrics` endpoint.
+//!
+//! Metrics are gathered on demand by [`MetricsCollector`]s. Built-in collectors cover the
+//! process, the database, Redis and RabbitMQ pools, the cache and supervisor tasks; the ones
+//! for the dependencies enabled in the state are registered automatically by
+//! [`MetricsRegistry::from_state`].
+//!
+//! Calls made through `Cache`, `Redis` and `RabbitMQ` are timed and counted by operation and outcome
+//! through the [`metrics`](https://docs.rs/metrics) facade, as `foxtive_operations_total` and
+//! `foxtive_operation_duration_seconds`, see [`record_operation`]. They are exported by the
+//! recorder the application installs, e.g. `metrics-exporter-prometheus`.
+//!
+//! ## Features
+//!
+//! This module requires the `metrics` feature to be enabled.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::metrics::{MetricFamily, MetricsCollector, MetricsRegistry};
+//! use std::sync::Arc;
+//!
+//! struct QueueDepth;
+//!
+//! #[async_trait::async_trait]
+//! impl MetricsCollector for QueueDepth {
+//!     async fn collect(&self) -> Vec<MetricFamily> {
+//!         vec![MetricFamily::gauge("app_queue_depth", "Jobs waiting to be processed").sample(&[("queue", "emails")], 3.0)]
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let registry = MetricsRegistry::new().collector(Arc::new(QueueDepth));
+//!
+//! let text = registry.render().await;
+//! assert!(text.contains("app_queue_depth{queue=\"emails\"} 3"));
+//! # });
+//! ```
+
+mod collectors;
+mod operations;
+
+pub use collectors::*;
+pub use operations::record_operation;
+
+use futures_util::future::join_all;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Content type of [`render`]'s output, for the HTTP response
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Collectors registered with [`register`], rendered along with the state's
+static EXTRA_COLLECTORS: LazyLock<RwLock<Vec<Arc<dyn MetricsCollector>>>> =
+    LazyLock::new(Default::default);
+
+/// Kind of a metric, as announced in its `# TYPE` line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// A single value of a metric, with its labels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Appended to the family name, e.g. `_bucket` for histograms
+    pub suffix: String,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+}
+
+/// A metric and its samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricFamily {
+    pub name: String,
+    pub help: String,
+    pub kind: MetricKind,
+    pub samples: Vec<Sample>,
+}
+
+impl MetricFamily {
+    pub fn new(name: &str, help: &str, kind: MetricKind) -> Self {
+        Self {
+            name: name.to_string(),
+            help: help.to_string(),
+            kind,
+            samples: vec![],
+        }
+    }
+
+    pub fn counter(name: &str, help: &str) -> Self {
+        Self::new(name, help, MetricKind::Counter)
+    }
+
+    pub fn gauge(name: &str, help: &str) -> Self {
+        Self::new(name, help, MetricKind::Gauge)
+    }
+
+    pub fn histogram(name: &str, help: &str) -> Self {
+        Self::new(name, help, MetricKind::Histogram)
+    }
+
+    pub fn sample(self, labels: &[(&str, &str)], value: f64) -> Self {
+        self.suffixed_sample("", labels, value)
+    }
+
+    /// Adds the samples of a histogram: the cumulative count of each `(upper bound, count)`
+    /// bucket, the `+Inf` one, the sum and the count of the observations
+    pub fn histogram_sample(
+        self,
+        labels: &[(&str, &str)],
+        buckets: &[(f64, u64)],
+        sum: f64,
+        count: u64,
+    ) -> Self {
+        let mut family = self;
+        for (bound, bucket_count) in buckets {
+            let bound = format_value(*bound);
+            let labels = [labels, &[("le", bound.as_str())]].concat();
+            family = family.suffixed_sample("_bucket", &labels, *bucket_count as f64);
+        }
+
+        let labels_inf = [labels, &[("le", "+Inf")]].concat();
+        family
+            .suffixed_sample("_bucket", &labels_inf, count as f64)
+            .suffixed_sample("_sum", labels, sum)
+            .suffixed_sample("_count", labels, count as f64)
+    }
+
+    fn suffixed_sample(mut self, suffix: &str, labels: &[(&str, &str)], value: f64) -> Self {
+        self.samples.push(Sample {
+            suffix: suffix.to_string(),
+            labels: labels
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            value,
+        });
+        self
+    }
+}
+
+/// Contract for gathering metrics when they are rendered
+#[async_trait::async_trait]
+pub trait MetricsCollector: Send + Sync {
+    async fn collect(&self) -> Vec<MetricFamily>;
+}
+
+/// Runs metrics collectors and renders their output.
+#[derive(Clone, Default)]
+pub struct MetricsRegistry {
+    collectors: Vec<Arc<dyn MetricsCollector>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the process collector and the collectors
+    /// of the dependencies enabled in the state
+    #[allow(unused_variables, unused_mut)]
+    pub fn from_state(state: &crate::FoxtiveState) -> Self {
+        let mut registry = Self::new().collector(Arc::new(ProcessCollector));
+
+        #[cfg(feature = "database")]
+        {
+            registry =
+                registry.collector(Arc::new(DatabasePoolCollector::new(state.database.clone())));
+        }
+
+        #[cfg(feature = "redis")]
+        {
+            registry =
+                registry.collector(Arc::new(RedisPoolCollector::new(state.redis_pool.clone())));
+        }
+
+        #[cfg(feature = "rabbitmq")]
+        {
+            registry = registry.collector(Arc::new(RabbitMqPoolCollector::new(
+                state.rabbitmq_pool.clone(),
+            )));
+        }
+
+        #[cfg(feature = "cache")]
+        {
+            registry = registry.collector(Arc::new(CacheCollector::new(state.cache.clone())));
+        }
+
+        registry
+    }
+
+    pub fn collector(mut self, collector: Arc<dyn MetricsCollector>) -> Self {
+        self.collectors.push(collector);
+        self
+    }
+
+    /// Runs every collector concurrently and gathers their metrics, samples of
+    /// families sharing a name being merged
+    pub async fn gather(&self) -> Vec<MetricFamily> {
+        let collected = join_all(self.collectors.iter().map(|c| c.collect())).await;
+
+        let mut families = BTreeMap::<String, MetricFamily>::new();
+        for family in collected.into_iter().flatten() {
+            match families.get_mut(&family.name) {
+                Some(existing) => existing.samples.extend(family.samples),
+                None => {
+                    families.insert(family.name.clone(), family);
+                }
+            }
+        }
+
+        families.into_values().collect()
+    }
+
+    /// Renders the gathered metrics in the Prometheus text format
+    pub async fn render(&self) -> String {
+        encode(&self.gather().await)
+    }
+}
+
+/// Registers a collector rendered by [`render`] along with the built-in ones
+pub fn register(collector: Arc<dyn MetricsCollector>) {
+    EXTRA_COLLECTORS.write().unwrap().push(collector);
+}
+
+/// Renders the metrics of the global state's dependencies and of the collectors
+/// registered with [`register`], serve it with [`CONTENT_TYPE`]
+pub async fn render() -> String {
+    use crate::prelude::AppStateExt;
+
+    let mut registry = match crate::FOXTIVE.is_initialized() {
+        true => MetricsRegistry::from_state(crate::FOXTIVE.app()),
+        false => MetricsRegistry::new().collector(Arc::new(ProcessCollector)),
+    };
+
+    for collector in EXTRA_COLLECTORS.read().unwrap().iter() {
+        registry = registry.collector(collector.clone());
+    }
+
+    registry.render().await
+}
+
+/// Encodes metric families in the Prometheus text format
+pub fn encode(families: &[MetricFamily]) -> String {
+    let mut out = String::new();
+    for family in families {
+        let _ = writeln!(out, "# HELP {} {}", family.name, escape_help(&family.help));
+        let _ = writeln!(out, "# TYPE {} {}", family.name, family.kind.as_str());
+
+        for sample in &family.samples {
+            out.push_str(&family.name);
+            out.push_str(&sample.suffix);
+            if !sample.labels.is_empty() {
+                let labels = sample
+                    .labels
+                    .iter()
+                    .map(|(name, value)| format!("{name}=\"{}\"", escape_label(value)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let _ = write!(out, "{{{labels}}}");
+            }
+            let _ = writeln!(out, " {}", format_value(sample.value));
+        }
+    }
+
+    out
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        match value.is_sign_positive() {
+            true => "+Inf".to_string(),
+            false => "-Inf".to_string(),
+        }
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(Vec<MetricFamily>);
+
+    #[async_trait::async_trait]
+    impl MetricsCollector for Fixed {
+        async fn collect(&self) -> Vec<MetricFamily> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let families = vec![
+            MetricFamily::counter("requests_total", "Handled requests")
+                .sample(&[("method", "GET"), ("path", "/a\"b")], 3.0)
+                .sample(&[], 1.5),
+        ];
+
+        assert_eq!(
+            encode(&families),
+            "# HELP requests_total Handled requests\n\
+             # TYPE requests_total counter\n\
+             requests_total{method=\"GET\",path=\"/a\\\"b\"} 3\n\
+             requests_total 1.5\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_histogram() {
+        let families = vec![
+            MetricFamily::histogram("latency_seconds", "Latency").histogram_sample(
+                &[("op", "get")],
+                &[(0.1, 2), (1.0, 3)],
+                1.25,
+                4,
+            ),
+        ];
+
+        assert_eq!(
+            encode(&families),
+            "# HELP latency_seconds Latency\n\
+             # TYPE latency_seconds histogram\n\
+             latency_seconds_bucket{op=\"get\",le=\"0.1\"} 2\n\
+             latency_seconds_bucket{op=\"get\",le=\"1\"} 3\n\
+             latency_seconds_bucket{op=\"get\",le=\"+Inf\"} 4\n\
+             latency_seconds_sum{op=\"get\"} 1.25\n\
+             latency_seconds_count{op=\"get\"} 4\n"
+        );
+    }
+
+    #[test]
+    fn test_format_special_values() {
+        assert_eq!(format_value(f64::NAN), "NaN");
+        assert_eq!(format_value(f64::INFINITY), "+Inf");
+        assert_eq!(format_value(f64::NEG_INFINITY), "-Inf");
+    }
+
+    #[tokio::test]
+    async fn test_gather_merges_families_by_name() {
+        let registry = MetricsRegistry::new()
+            .collector(Arc::new(Fixed(vec![
+                MetricFamily::gauge("pool_size", "Size").sample(&[("pool", "redis")], 4.0),
+            ])))
+            .collector(Arc::new(Fixed(vec![
+                MetricFamily::gauge("pool_size", "Size").sample(&[("pool", "rabbitmq")], 2.0),
+                MetricFamily::gauge("another", "Another").sample(&[], 1.0),
+            ])));
+
+        let families = registry.gather().await;
+        assert_eq!(families.len(), 2);
+        assert_eq!(families[1].name, "pool_size");
+        assert_eq!(families[1].samples.len(), 2);
+
+        let text = registry.render().await;
+        assert_eq!(text.matches("# TYPE pool_size gauge").count(), 1);
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+/// Records an operation made on a dependency, e.g. `("redis", "get", true, elapsed)`,
+/// through the [`metrics`](https://docs.rs/metrics) facade
+///
+/// It increments the `foxtive_operations_total` counter and records the duration in
+/// seconds in the `foxtive_operation_duration_seconds` histogram, both labelled by
+/// component, operation and outcome. Nothing is recorded until the application
+/// installs a recorder, e.g. a Prometheus exporter.
+pub fn record_operation(component: &str, operation: &str, succeeded: bool, duration: Duration) {
+    let outcome = match succeeded {
+        true => "success",
+        false => "error",
+    };
+
+    let labels = [
+        ("component", component.to_string()),
+        ("operation", operation.to_string()),
+        ("outcome", outcome.to_string()),
+    ];
+
+    ::metrics::counter!("foxtive_operations_total", &labels).increment(1);
+    ::metrics::histogram!("foxtive_operation_duration_seconds", &labels)
+        .record(duration.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn test_record_operation_emits_counter_and_histogram() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        ::metrics::with_local_recorder(&recorder, || {
+            record_operation("redis", "get", true, Duration::from_millis(20));
+            record_operation("redis", "get", true, Duration::from_millis(40));
+            record_operation("redis", "get", false, Duration::from_millis(5));
+        });
+
+        let metrics = snapshotter.snapshot().into_vec();
+        let find = |name: &str, outcome: &str| {
+            metrics
+                .iter()
+                .find(|(key, ..)| {
+                    key.key().name() == name
+                        && key
+                            .key()
+                            .labels()
+                            .any(|l| l.key() == "outcome" && l.value() == outcome)
+                })
+                .map(|(.., value)| value)
+        };
+
+        assert_eq!(
+            find("foxtive_operations_total", "success"),
+            Some(&DebugValue::Counter(2))
+        );
+        assert_eq!(
+            find("foxtive_operations_total", "error"),
+            Some(&DebugValue::Counter(1))
+        );
+        match find("foxtive_operation_duration_seconds", "success") {
+            Some(DebugValue::Histogram(values)) => assert_eq!(values.len(), 2),
+            other => panic!("unexpected histogram: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::notify::contract::NotificationChannelContract;
+use crate::notify::{EmailMessage, Notifiable};
+use crate::prelude::AppResult;
+use std::sync::Arc;
+
+/// Contract for the mail transport used by [`EmailChannel`] (SMTP client, transactional
+/// email API...), implemented by the application.
+#[async_trait::async_trait]
+pub trait Mailer: Send + Sync {
+    /// Sends the message to every recipient
+    async fn send(&self, to: &[String], message: &EmailMessage) -> AppResult<()>;
+}
+
+/// Emails notifications to a fixed list of recipients, using [`Notifiable::to_email`]
+/// as the message.
+#[derive(Clone)]
+pub struct EmailChannel {
+    name: String,
+    mailer: Arc<dyn Mailer>,
+    recipients: Vec<String>,
+}
+
+impl EmailChannel {
+    pub fn new<I, S>(mailer: Arc<dyn Mailer>, recipients: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            name: "email".to_string(),
+            mailer,
+            recipients: recipients.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Overrides the channel name, useful to tell several mailing lists apart in reports
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationChannelContract for EmailChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &dyn Notifiable) -> AppResult<()> {
+        if self.recipients.is_empty() {
+            return Ok(());
+        }
+
+        self.mailer
+            .send(&self.recipients, &notification.to_email())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingMailer {
+        sent: Mutex<Vec<(Vec<String>, EmailMessage)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Mailer for RecordingMailer {
+        async fn send(&self, to: &[String], message: &EmailMessage) -> AppResult<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((to.to_vec(), message.clone()));
+            Ok(())
+        }
+    }
+
+    struct Invoice;
+
+    impl Notifiable for Invoice {
+        fn event(&self) -> &str {
+            "invoice.paid"
+        }
+
+        fn to_payload(&self) -> Value {
+            Value::Null
+        }
+
+        fn to_text(&self) -> String {
+            "Invoice #12 was paid".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sends_to_recipients() {
+        let mailer = Arc::new(RecordingMailer::default());
+        let channel = EmailChannel::new(mailer.clone(), ["ops@example.com"]);
+        channel.send(&Invoice).await.unwrap();
+
+        let sent = mailer.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, vec!["ops@example.com"]);
+        assert_eq!(sent[0].1.subject, "invoice.paid");
+        assert_eq!(sent[0].1.text, "Invoice #12 was paid");
+    }
+
+    #[tokio::test]
+    async fn test_skips_without_recipients() {
+        let mailer = Arc::new(RecordingMailer::default());
+        EmailChannel::new(mailer.clone(), Vec::<String>::new())
+            .send(&Invoice)
+            .await
+            .unwrap();
+
+        assert!(mailer.sent.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,28 @@
+mod email;
+mod slack;
+mod webhook;
+
+pub use email::{EmailChannel, Mailer};
+pub use slack::SlackChannel;
+pub use webhook::WebhookChannel;
+
+use crate::helpers::reqwest::ReqwestResponseError;
+use crate::prelude::AppResult;
+use reqwest::RequestBuilder;
+
+/// Sends a JSON request, turning non-success statuses into [`ReqwestResponseError`]
+async fn send_json(request: RequestBuilder, body: Vec<u8>) -> AppResult<()> {
+    let response = request
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    Err(ReqwestResponseError::make(status, body).into_anyhow())
+}
@@ -0,0 +1,48 @@
+use crate::notify::Notifiable;
+use crate::notify::channels::send_json;
+use crate::notify::contract::NotificationChannelContract;
+use crate::prelude::AppResult;
+use reqwest::Client;
+
+/// Posts notifications to a Slack incoming webhook, using [`Notifiable::to_slack`]
+/// as the message body.
+#[derive(Clone)]
+pub struct SlackChannel {
+    name: String,
+    webhook_url: String,
+    client: Client,
+}
+
+impl SlackChannel {
+    pub fn new(webhook_url: &str) -> Self {
+        Self {
+            name: "slack".to_string(),
+            webhook_url: webhook_url.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// Overrides the channel name, useful to tell several workspaces apart in reports
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Uses a preconfigured HTTP client (timeouts, proxy...)
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationChannelContract for SlackChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &dyn Notifiable) -> AppResult<()> {
+        let body = serde_json::to_vec(&notification.to_slack())?;
+        send_json(self.client.post(&self.webhook_url), body).await
+    }
+}
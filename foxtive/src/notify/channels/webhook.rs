@@ -0,0 +1,248 @@
+use crate::helpers::hmac::{HashFunc, Hmac, constant_time_eq};
+use crate::notify::Notifiable;
+use crate::notify::channels::send_json;
+use crate::notify::contract::NotificationChannelContract;
+use crate::prelude::AppResult;
+use crate::unauthorized;
+use chrono::Utc;
+use reqwest::Client;
+use std::time::Duration;
+
+/// Posts the notification payload as JSON to a URL, signed with a shared secret.
+///
+/// Each request carries the following headers:
+///
+/// * `X-Event`: name of the event
+/// * `X-Timestamp`: unix timestamp of the delivery
+/// * `X-Signature`: `sha256=` followed by the hex HMAC-SHA256 of `{timestamp}.{body}`
+///
+/// Receivers check them with [`WebhookChannel::verify`].
+#[derive(Clone)]
+pub struct WebhookChannel {
+    name: String,
+    url: String,
+    secret: String,
+    client: Client,
+}
+
+impl WebhookChannel {
+    pub const EVENT_HEADER: &'static str = "X-Event";
+    pub const TIMESTAMP_HEADER: &'static str = "X-Timestamp";
+    pub const SIGNATURE_HEADER: &'static str = "X-Signature";
+
+    pub fn new(url: &str, secret: &str) -> Self {
+        Self {
+            name: "webhook".to_string(),
+            url: url.to_string(),
+            secret: secret.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// Overrides the channel name, useful to tell several webhooks apart in reports
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Uses a preconfigured HTTP client (timeouts, proxy...)
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Computes the value of the signature header for a delivery
+    pub fn sign(secret: &str, timestamp: i64, body: &str) -> AppResult<String> {
+        let hash = Hmac::new(secret, HashFunc::Sha256).hash(&format!("{timestamp}.{body}"))?;
+        Ok(format!("sha256={hash}"))
+    }
+
+    /// Verifies a delivery received from a webhook channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret`: secret shared with the sender
+    /// * `timestamp`: value of the `X-Timestamp` header
+    /// * `body`: raw request body
+    /// * `signature`: value of the `X-Signature` header
+    /// * `tolerance`: maximum age of the delivery, protecting against replays
+    pub fn verify(
+        secret: &str,
+        timestamp: &str,
+        body: &str,
+        signature: &str,
+        tolerance: Duration,
+    ) -> AppResult<()> {
+        let timestamp = timestamp
+            .parse::<i64>()
+            .map_err(|_| unauthorized!("invalid webhook timestamp"))?;
+
+        if Utc::now().timestamp().abs_diff(timestamp) > tolerance.as_secs() {
+            return Err(unauthorized!("webhook timestamp is outside the tolerance"));
+        }
+
+        let expected = Self::sign(secret, timestamp, body)?;
+        match constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+            true => Ok(()),
+            false => Err(unauthorized!("invalid webhook signature")),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationChannelContract for WebhookChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &dyn Notifiable) -> AppResult<()> {
+        let body = serde_json::to_string(&notification.to_payload())?;
+        let timestamp = Utc::now().timestamp();
+        let signature = Self::sign(&self.secret, timestamp, &body)?;
+
+        let request = self
+            .client
+            .post(&self.url)
+            .header(Self::EVENT_HEADER, notification.event())
+            .header(Self::TIMESTAMP_HEADER, timestamp.to_string())
+            .header(Self::SIGNATURE_HEADER, signature);
+
+        send_json(request, body.into_bytes()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+
+    struct UserCreated;
+
+    impl Notifiable for UserCreated {
+        fn event(&self) -> &str {
+            "user.created"
+        }
+
+        fn to_payload(&self) -> Value {
+            json!({ "id": 7 })
+        }
+    }
+
+    /// Accepts one request, answers with `status` and hands back the raw request
+    async fn serve_once(status: &'static str) -> (String, oneshot::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = String::new();
+            let mut buf = [0u8; 4096];
+
+            // read until the headers and the whole body have arrived
+            while !is_complete(&request) {
+                let read = socket.read(&mut buf).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.push_str(&String::from_utf8_lossy(&buf[..read]));
+            }
+
+            let response =
+                format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let _ = tx.send(request);
+        });
+
+        (format!("http://{addr}/hooks"), rx)
+    }
+
+    fn find_header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+        request.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+
+    fn header<'a>(request: &'a str, name: &str) -> &'a str {
+        find_header(request, name).unwrap()
+    }
+
+    fn is_complete(request: &str) -> bool {
+        let Some((head, body)) = request.split_once("\r\n\r\n") else {
+            return false;
+        };
+
+        let length = find_header(head, "content-length")
+            .and_then(|length| length.parse::<usize>().ok())
+            .unwrap_or(0);
+        body.len() >= length
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let now = Utc::now().timestamp();
+        let signature = WebhookChannel::sign("secret", now, "{}").unwrap();
+        let tolerance = Duration::from_secs(300);
+
+        assert!(signature.starts_with("sha256="));
+        assert!(
+            WebhookChannel::verify("secret", &now.to_string(), "{}", &signature, tolerance).is_ok()
+        );
+        assert!(
+            WebhookChannel::verify("other", &now.to_string(), "{}", &signature, tolerance).is_err()
+        );
+        assert!(
+            WebhookChannel::verify("secret", &now.to_string(), "[]", &signature, tolerance)
+                .is_err()
+        );
+
+        let old = now - 600;
+        let signature = WebhookChannel::sign("secret", old, "{}").unwrap();
+        assert!(
+            WebhookChannel::verify("secret", &old.to_string(), "{}", &signature, tolerance)
+                .is_err()
+        );
+
+        for extreme in [i64::MIN, i64::MAX] {
+            let signature = WebhookChannel::sign("secret", extreme, "{}").unwrap();
+            assert!(
+                WebhookChannel::verify("secret", &extreme.to_string(), "{}", &signature, tolerance)
+                    .is_err()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_signed_payload() {
+        let (url, request) = serve_once("200 OK").await;
+        WebhookChannel::new(&url, "secret")
+            .send(&UserCreated)
+            .await
+            .unwrap();
+
+        let request = request.await.unwrap();
+        let body = request.split("\r\n\r\n").nth(1).unwrap();
+        assert_eq!(body, r#"{"id":7}"#);
+        assert_eq!(header(&request, "x-event"), "user.created");
+
+        WebhookChannel::verify(
+            "secret",
+            header(&request, "x-timestamp"),
+            body,
+            header(&request, "x-signature"),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_on_error_status() {
+        let (url, _request) = serve_once("500 Internal Server Error").await;
+        let result = WebhookChannel::new(&url, "secret").send(&UserCreated).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,18 @@
+use crate::notify::Notifiable;
+use crate::prelude::AppResult;
+
+/// Contract for implementing notification channels
+///
+/// A channel delivers a single notification once, retries are handled by the
+/// [`Notifier`](crate::notify::Notifier) dispatching to it.
+#[async_trait::async_trait]
+pub trait NotificationChannelContract: Send + Sync {
+    /// Name of the channel, used in logs and dispatch reports
+    fn name(&self) -> &str;
+
+    /// Delivers the notification
+    ///
+    /// # Parameters
+    /// - `notification`: The notification to deliver
+    async fn send(&self, notification: &dyn Notifiable) -> AppResult<()>;
+}
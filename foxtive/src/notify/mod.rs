@@ -0,0 +1,299 @@
+//! # Notify Module
+//!
+//! Dispatches application events to external channels (webhooks, Slack, email), so that
+//! services describe *what* happened once and let the notifier deliver it everywhere.
+//!
+//! Each channel is attempted independently and retried with backoff according to its
+//! own [`RetryPolicy`], a failing channel never prevents the others from being notified.
+//!
+//! ## Features
+//!
+//! This module requires the `notify` feature to be enabled.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use foxtive::notify::channels::{SlackChannel, WebhookChannel};
+//! use foxtive::notify::{Notifiable, Notifier};
+//! use serde_json::{Value, json};
+//!
+//! struct OrderShipped {
+//!     order_id: u64,
+//! }
+//!
+//! impl Notifiable for OrderShipped {
+//!     fn event(&self) -> &str {
+//!         "order.shipped"
+//!     }
+//!
+//!     fn to_payload(&self) -> Value {
+//!         json!({ "order_id": self.order_id })
+//!     }
+//!
+//!     fn to_text(&self) -> String {
+//!         format!("Order #{} has been shipped", self.order_id)
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let notifier = Notifier::new()
+//!         .channel(WebhookChannel::new("https://partner.example.com/hooks", "shared-secret"))
+//!         .channel(SlackChannel::new("https://hooks.slack.com/services/T000/B000/XXXX"));
+//!
+//!     let report = notifier.notify(&OrderShipped { order_id: 42 }).await;
+//!     report.into_result().unwrap();
+//! }
+//! ```
+
+pub mod channels;
+pub mod contract;
+
+use crate::helpers::retry::{RetryPolicy, retry};
+use crate::notify::contract::NotificationChannelContract;
+use crate::prelude::AppResult;
+use futures_util::future::join_all;
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+/// An event that can be delivered through notification channels.
+///
+/// Only [`event`](Notifiable::event) and [`to_payload`](Notifiable::to_payload) are
+/// required, the other representations are derived from them unless overridden.
+pub trait Notifiable: Send + Sync {
+    /// Name of the event, e.g. `order.shipped`
+    fn event(&self) -> &str;
+
+    /// Structured representation, sent as the body of webhooks
+    fn to_payload(&self) -> Value;
+
+    /// Human-readable representation, used by chat and email channels
+    fn to_text(&self) -> String {
+        self.event().to_string()
+    }
+
+    /// Slack message body, defaults to a plain text message
+    fn to_slack(&self) -> Value {
+        serde_json::json!({ "text": self.to_text() })
+    }
+
+    /// Email representation, defaults to the event name as subject and the text as body
+    fn to_email(&self) -> EmailMessage {
+        EmailMessage {
+            subject: self.event().to_string(),
+            text: self.to_text(),
+            html: None,
+        }
+    }
+}
+
+/// Content of a notification email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+#[derive(Clone)]
+struct Route {
+    channel: Arc<dyn NotificationChannelContract>,
+    policy: RetryPolicy,
+}
+
+/// Dispatches notifications to every registered channel.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    routes: Vec<Route>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a channel, retried with the default [`RetryPolicy`]
+    pub fn channel<C: NotificationChannelContract + 'static>(self, channel: C) -> Self {
+        self.channel_with_retry(channel, RetryPolicy::default())
+    }
+
+    /// Registers a channel with its own retry policy
+    pub fn channel_with_retry<C: NotificationChannelContract + 'static>(
+        mut self,
+        channel: C,
+        policy: RetryPolicy,
+    ) -> Self {
+        self.routes.push(Route {
+            channel: Arc::new(channel),
+            policy,
+        });
+        self
+    }
+
+    /// Names of the registered channels, in registration order
+    pub fn channels(&self) -> Vec<&str> {
+        self.routes
+            .iter()
+            .map(|route| route.channel.name())
+            .collect()
+    }
+
+    /// Delivers the notification to all channels concurrently, retrying each one
+    /// according to its policy, and reports which channels succeeded.
+    pub async fn notify(&self, notification: &dyn Notifiable) -> DispatchReport {
+        let deliveries = self.routes.iter().map(|route| async move {
+            let name = route.channel.name().to_string();
+            debug!("[notify] sending '{}' via {name}", notification.event());
+
+            let result = retry(&route.policy, || route.channel.send(notification)).await;
+            (name, result)
+        });
+
+        let mut report = DispatchReport::default();
+        for (name, result) in join_all(deliveries).await {
+            match result {
+                Ok(()) => report.delivered.push(name),
+                Err(err) => {
+                    error!(
+                        "[notify] failed to send '{}' via {name}: {err}",
+                        notification.event()
+                    );
+                    report.failed.push((name, err));
+                }
+            }
+        }
+
+        report
+    }
+}
+
+/// Outcome of dispatching a notification
+#[derive(Debug, Default)]
+pub struct DispatchReport {
+    /// Channels the notification was delivered to
+    pub delivered: Vec<String>,
+    /// Channels that still failed after retrying, with their last error
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+impl DispatchReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Converts the report into an error listing every failed channel
+    pub fn into_result(self) -> AppResult<()> {
+        if self.failed.is_empty() {
+            return Ok(());
+        }
+
+        let failures = self
+            .failed
+            .iter()
+            .map(|(name, err)| format!("{name}: {err}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Err(anyhow::anyhow!("notification failed on {failures}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::retry::BackoffStrategy;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    struct Ping;
+
+    impl Notifiable for Ping {
+        fn event(&self) -> &str {
+            "ping"
+        }
+
+        fn to_payload(&self) -> Value {
+            serde_json::json!({})
+        }
+    }
+
+    /// Fails the first `failures` attempts
+    struct Flaky {
+        name: &'static str,
+        failures: usize,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl NotificationChannelContract for Flaky {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _notification: &dyn Notifiable) -> AppResult<()> {
+            match self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                true => Err(anyhow::anyhow!("unavailable")),
+                false => Ok(()),
+            }
+        }
+    }
+
+    fn flaky(name: &'static str, failures: usize) -> (Flaky, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let channel = Flaky {
+            name,
+            failures,
+            calls: calls.clone(),
+        };
+        (channel, calls)
+    }
+
+    fn fast_policy(attempts: usize) -> RetryPolicy {
+        RetryPolicy::new(BackoffStrategy::fixed(Duration::from_millis(1)))
+            .max_attempts(attempts)
+            .jitter(0.0)
+    }
+
+    #[test]
+    fn test_default_representations() {
+        assert_eq!(Ping.to_text(), "ping");
+        assert_eq!(Ping.to_slack(), serde_json::json!({ "text": "ping" }));
+        assert_eq!(Ping.to_email().subject, "ping");
+    }
+
+    #[tokio::test]
+    async fn test_retries_each_channel_independently() {
+        let (recovering, recovering_calls) = flaky("recovering", 2);
+        let (broken, broken_calls) = flaky("broken", usize::MAX);
+        let (healthy, healthy_calls) = flaky("healthy", 0);
+
+        let notifier = Notifier::new()
+            .channel_with_retry(recovering, fast_policy(3))
+            .channel_with_retry(broken, fast_policy(2))
+            .channel_with_retry(healthy, fast_policy(3));
+        assert_eq!(notifier.channels(), vec!["recovering", "broken", "healthy"]);
+
+        let report = notifier.notify(&Ping).await;
+        assert_eq!(report.delivered, vec!["recovering", "healthy"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "broken");
+
+        assert_eq!(recovering_calls.load(Ordering::SeqCst), 3);
+        assert_eq!(broken_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(healthy_calls.load(Ordering::SeqCst), 1);
+
+        let err = report.into_result().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "notification failed on broken: unavailable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_notifier_succeeds() {
+        let report = Notifier::new().notify(&Ping).await;
+        assert!(report.is_success());
+        assert!(report.into_result().is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::prelude::AppResult;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// A unit of background work, serialized into the queue and executed by a
+/// [`QueueWorker`](crate::queue::QueueWorker).
+///
+/// # Examples
+///
+/// ```
+/// use foxtive::prelude::AppResult;
+/// use foxtive::queue::Job;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct SendWelcomeEmail {
+///     user_id: u64,
+/// }
+///
+/// #[async_trait::async_trait]
+/// impl Job for SendWelcomeEmail {
+///     const NAME: &'static str = "send-welcome-email";
+///
+///     async fn handle(&self) -> AppResult<()> {
+///         // deliver the email
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait Job: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Unique name routing stored jobs to this type, keep it stable across deployments
+    const NAME: &'static str;
+
+    /// Executes the job, an error schedules a retry until the worker's attempts run out
+    async fn handle(&self) -> AppResult<()>;
+}
+
+/// Ordering of ready jobs, higher priorities are always picked first
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl Priority {
+    /// All priorities, from the highest to the lowest
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Normal => "normal",
+            Priority::Low => "low",
+        }
+    }
+}
+
+/// Options applied when pushing a job
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JobOptions {
+    pub(crate) delay: Option<Duration>,
+    pub(crate) priority: Priority,
+}
+
+impl JobOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the job available only after the given delay
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// A job as stored in Redis, along with its delivery state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobEnvelope {
+    /// Unique, time-sortable id of the job
+    pub id: String,
+    /// [`Job::NAME`] of the job type
+    pub job: String,
+    /// The serialized job
+    pub payload: Value,
+    pub priority: Priority,
+    /// Number of attempts made so far
+    pub attempts: usize,
+    /// Unix timestamp in milliseconds at which the job was pushed
+    pub enqueued_at: i64,
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
+}
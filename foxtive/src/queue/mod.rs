@@ -0,0 +1,456 @@
+//! # Queue Module
+//!
+//! A Redis-backed background job queue. Jobs are plain serializable structs implementing
+//! [`Job`], pushed through a [`Queue`] and executed by a [`QueueWorker`] running under the
+//! supervisor.
+//!
+//! Each queue is stored under the following Redis keys, within the
+//! [key prefix](crate::redis) of the client:
+//!
+//! - `queue:{name}:high`, `queue:{name}:normal`, `queue:{name}:low`: lists of ready jobs
+//! - `queue:{name}:delayed`: sorted set of delayed and retried jobs, scored by due time
+//! - `queue:{name}:dead`: list of jobs that exhausted their attempts
+//! - `queue:{name}:processing:{consumer}`: list of jobs a worker is executing
+//! - `queue:{name}:consumers`: sorted set of workers, scored by their last heartbeat
+//!
+//! A popped job stays in the processing list of its worker until it is settled, so it
+//! survives a crash of the worker. Once a worker stops sending heartbeats, any other
+//! worker of the queue moves its unsettled jobs back to the ready lists.
+//!
+//! ## Features
+//!
+//! This module requires the `queue` feature to be enabled.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use foxtive::FOXTIVE;
+//! use foxtive::prelude::{AppResult, AppStateExt};
+//! use foxtive::queue::{Job, JobOptions, Priority, Queue, QueueWorker};
+//! use foxtive::supervisor::TaskRuntime;
+//! use serde::{Deserialize, Serialize};
+//! use std::time::Duration;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct ResizeImage {
+//!     path: String,
+//! }
+//!
+//! #[async_trait::async_trait]
+//! impl Job for ResizeImage {
+//!     const NAME: &'static str = "resize-image";
+//!
+//!     async fn handle(&self) -> AppResult<()> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let queue = Queue::new(FOXTIVE.app().redis(), "images");
+//!
+//!     let job = ResizeImage { path: "avatars/1.png".to_string() };
+//!     queue.push(&job).await.unwrap();
+//!
+//!     let options = JobOptions::new()
+//!         .delay(Duration::from_secs(60))
+//!         .priority(Priority::High);
+//!     queue.push_with(&job, options).await.unwrap();
+//!
+//!     let worker = QueueWorker::new("image-worker", queue)
+//!         .register::<ResizeImage>()
+//!         .concurrency(4);
+//!
+//!     let mut runtime = TaskRuntime::new();
+//!     runtime.register(worker);
+//!     runtime.start_all().await.unwrap();
+//!     runtime.wait_any().await;
+//! }
+//! ```
+
+mod job;
+mod worker;
+
+pub use job::{Job, JobEnvelope, JobOptions, Priority};
+pub use worker::QueueWorker;
+
+use crate::helpers::id;
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use crate::results::redis_result::RedisResultToAppResult;
+use chrono::Utc;
+use redis::{AsyncCommands, Direction};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Maximum number of due jobs moved out of the delayed set per poll
+const PROMOTE_BATCH: isize = 100;
+
+/// Pushes jobs to, and pops jobs from, a named Redis queue.
+#[derive(Clone)]
+pub struct Queue {
+    redis: Arc<Redis>,
+    name: String,
+}
+
+impl Queue {
+    pub fn new(redis: Arc<Redis>, name: &str) -> Self {
+        Self {
+            redis,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Pushes a job with normal priority, available immediately, returning its id
+    pub async fn push<J: Job>(&self, job: &J) -> AppResult<String> {
+        self.push_with(job, JobOptions::default()).await
+    }
+
+    /// Pushes a job with the given delay and priority, returning its id
+    pub async fn push_with<J: Job>(&self, job: &J, options: JobOptions) -> AppResult<String> {
+        let envelope = JobEnvelope {
+            id: id::ulid(),
+            job: J::NAME.to_string(),
+            payload: serde_json::to_value(job)?,
+            priority: options.priority,
+            attempts: 0,
+            enqueued_at: Utc::now().timestamp_millis(),
+            last_error: None,
+        };
+
+        match options.delay {
+            Some(delay) => self.schedule(&envelope, delay).await?,
+            None => self.enqueue(&envelope).await?,
+        }
+
+        Ok(envelope.id)
+    }
+
+    /// Number of jobs ready to be processed, across all priorities
+    pub async fn ready_count(&self) -> AppResult<usize> {
+        let mut conn = self.redis.redis().await?;
+        let mut count = 0;
+        for priority in Priority::ALL {
+            let len: usize = conn.llen(self.ready_key(priority)).await?;
+            count += len;
+        }
+
+        Ok(count)
+    }
+
+    /// Number of jobs waiting for their delay to elapse, retries included
+    pub async fn delayed_count(&self) -> AppResult<usize> {
+        let mut conn = self.redis.redis().await?;
+        conn.zcard(self.delayed_key()).await.into_app_result()
+    }
+
+    /// Jobs that exhausted their attempts, most recent first
+    pub async fn dead_letters(&self, limit: usize) -> AppResult<Vec<JobEnvelope>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.redis.redis().await?;
+        let items: Vec<String> = conn.lrange(self.dead_key(), 0, limit as isize - 1).await?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| serde_json::from_str(item).ok())
+            .collect())
+    }
+
+    /// Moves every dead letter back to the ready lists with a fresh attempt count,
+    /// oldest first, returning how many were requeued
+    ///
+    /// Each job leaves the dead letters in the same step it enters its ready list, so a
+    /// failure midway loses nothing. Malformed dead letters are left in place for inspection.
+    pub async fn requeue_dead_letters(&self) -> AppResult<usize> {
+        let script = redis::Script::new(
+            r#"
+            if redis.call("LREM", KEYS[1], -1, ARGV[1]) == 1 then
+                redis.call("LPUSH", KEYS[2], ARGV[2])
+                return 1
+            end
+            return 0
+        "#,
+        );
+
+        let mut conn = self.redis.redis().await?;
+        let items: Vec<String> = conn.lrange(self.dead_key(), 0, -1).await?;
+
+        let mut requeued = 0;
+        for item in items.into_iter().rev() {
+            let mut envelope = match serde_json::from_str::<JobEnvelope>(&item) {
+                Ok(envelope) => envelope,
+                Err(err) => {
+                    warn!(
+                        "[queue][{}] leaving malformed dead letter in place: {err}",
+                        self.name
+                    );
+                    continue;
+                }
+            };
+
+            envelope.attempts = 0;
+            let moved: usize = script
+                .key(self.dead_key())
+                .key(self.ready_key(envelope.priority))
+                .arg(&item)
+                .arg(serde_json::to_string(&envelope)?)
+                .invoke_async(&mut conn)
+                .await?;
+            requeued += moved;
+        }
+
+        Ok(requeued)
+    }
+
+    pub(crate) async fn enqueue(&self, envelope: &JobEnvelope) -> AppResult<()> {
+        let content = serde_json::to_string(envelope)?;
+        let mut conn = self.redis.redis().await?;
+        let _: usize = conn
+            .lpush(self.ready_key(envelope.priority), content)
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn schedule(&self, envelope: &JobEnvelope, delay: Duration) -> AppResult<()> {
+        let due = Utc::now().timestamp_millis() + delay.as_millis() as i64;
+        let content = serde_json::to_string(envelope)?;
+        let mut conn = self.redis.redis().await?;
+        let _: usize = conn.zadd(self.delayed_key(), content, due).await?;
+        Ok(())
+    }
+
+    /// Moves due jobs from the delayed set to their ready list.
+    ///
+    /// A job leaves the set in the same step it enters its list, and only for the worker
+    /// that manages to remove it, so concurrent workers never duplicate or lose it.
+    pub(crate) async fn promote_due(&self) -> AppResult<usize> {
+        let script = redis::Script::new(
+            r#"
+            if redis.call("ZREM", KEYS[1], ARGV[1]) == 1 then
+                redis.call("LPUSH", KEYS[2], ARGV[1])
+                return 1
+            end
+            return 0
+        "#,
+        );
+
+        let mut conn = self.redis.redis().await?;
+        let now = Utc::now().timestamp_millis();
+        let due: Vec<String> = conn
+            .zrangebyscore_limit(self.delayed_key(), "-inf", now, 0, PROMOTE_BATCH)
+            .await?;
+
+        let mut promoted = 0;
+        for item in due {
+            let (target, ready) = match serde_json::from_str::<JobEnvelope>(&item) {
+                Ok(envelope) => (self.ready_key(envelope.priority), true),
+                Err(_) => (self.dead_key(), false),
+            };
+
+            let moved: usize = script
+                .key(self.delayed_key())
+                .key(target)
+                .arg(&item)
+                .invoke_async(&mut conn)
+                .await?;
+            if ready {
+                promoted += moved;
+            }
+        }
+
+        Ok(promoted)
+    }
+
+    /// Waits up to `timeout` for the next ready job, highest priority first, moving it
+    /// to the processing list of `consumer` until it is settled
+    ///
+    /// When every list is empty, only the normal priority list is waited on, so a job of
+    /// another priority pushed in the meantime is picked up on the next call.
+    pub(crate) async fn pop(&self, consumer: &str, timeout: Duration) -> AppResult<Option<String>> {
+        let processing = self.processing_key(consumer);
+        let mut conn = self.redis.redis().await?;
+
+        for priority in Priority::ALL {
+            let item: Option<String> = conn
+                .lmove(
+                    self.ready_key(priority),
+                    &processing,
+                    Direction::Right,
+                    Direction::Left,
+                )
+                .await?;
+
+            if item.is_some() {
+                return Ok(item);
+            }
+        }
+
+        crate::redis::blocking(&conn, timeout.as_secs_f64())
+            .blmove(
+                self.ready_key(Priority::Normal),
+                &processing,
+                Direction::Right,
+                Direction::Left,
+                timeout.as_secs_f64(),
+            )
+            .await
+            .into_app_result()
+    }
+
+    /// Removes a completed job from the processing list of `consumer`
+    pub(crate) async fn ack(&self, consumer: &str, item: &str) -> AppResult<()> {
+        let mut conn = self.redis.redis().await?;
+        let _: usize = conn.lrem(self.processing_key(consumer), 1, item).await?;
+        Ok(())
+    }
+
+    /// Moves a job from the processing list of `consumer` back to its ready list, in one
+    /// transaction
+    pub(crate) async fn release(&self, consumer: &str, item: &str) -> AppResult<()> {
+        let ready = match serde_json::from_str::<JobEnvelope>(item) {
+            Ok(envelope) => self.ready_key(envelope.priority),
+            Err(_) => self.dead_key(),
+        };
+
+        let mut conn = self.redis.redis().await?;
+
+        redis::pipe()
+            .atomic()
+            .lpush(ready, item)
+            .ignore()
+            .lrem(self.processing_key(consumer), 1, item)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .into_app_result()
+    }
+
+    /// Schedules a failed job for another attempt and removes it from the processing list
+    /// of `consumer`, in one transaction
+    pub(crate) async fn retry(
+        &self,
+        consumer: &str,
+        item: &str,
+        envelope: &JobEnvelope,
+        delay: Duration,
+    ) -> AppResult<()> {
+        let due = Utc::now().timestamp_millis() + delay.as_millis() as i64;
+        let content = serde_json::to_string(envelope)?;
+        let mut conn = self.redis.redis().await?;
+
+        redis::pipe()
+            .atomic()
+            .zadd(self.delayed_key(), content, due)
+            .ignore()
+            .lrem(self.processing_key(consumer), 1, item)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .into_app_result()
+    }
+
+    /// Moves a job to the dead letters and removes it from the processing list of
+    /// `consumer`, in one transaction
+    pub(crate) async fn bury(&self, consumer: &str, item: &str, content: String) -> AppResult<()> {
+        let mut conn = self.redis.redis().await?;
+
+        redis::pipe()
+            .atomic()
+            .lpush(self.dead_key(), content)
+            .ignore()
+            .lrem(self.processing_key(consumer), 1, item)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .into_app_result()
+    }
+
+    /// Records that `consumer` is alive, keeping its jobs from being reaped
+    pub(crate) async fn heartbeat(&self, consumer: &str) -> AppResult<()> {
+        let mut conn = self.redis.redis().await?;
+        let _: usize = conn
+            .zadd(
+                self.consumers_key(),
+                consumer,
+                Utc::now().timestamp_millis(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Moves the unsettled jobs of consumers silent for longer than `stale_after` back to
+    /// their ready list, returning how many were requeued.
+    ///
+    /// A consumer is only reaped by the worker that manages to remove it from the set,
+    /// so its jobs are never requeued twice.
+    pub(crate) async fn reap_stale(&self, stale_after: Duration) -> AppResult<usize> {
+        let mut conn = self.redis.redis().await?;
+        let deadline = Utc::now().timestamp_millis() - stale_after.as_millis() as i64;
+        let stale: Vec<String> = conn
+            .zrangebyscore(self.consumers_key(), "-inf", deadline)
+            .await?;
+
+        let mut requeued = 0;
+        for consumer in stale {
+            let removed: usize = conn.zrem(self.consumers_key(), &consumer).await?;
+            if removed == 0 {
+                continue;
+            }
+
+            let processing = self.processing_key(&consumer);
+            loop {
+                let item: Option<String> = conn.lindex(&processing, -1).await?;
+                let Some(item) = item else {
+                    break;
+                };
+
+                let ready = match serde_json::from_str::<JobEnvelope>(&item) {
+                    Ok(envelope) => self.ready_key(envelope.priority),
+                    Err(_) => self.dead_key(),
+                };
+
+                let _: Option<String> = conn
+                    .lmove(&processing, ready, Direction::Right, Direction::Left)
+                    .await?;
+                requeued += 1;
+            }
+        }
+
+        Ok(requeued)
+    }
+
+    fn ready_key(&self, priority: Priority) -> String {
+        self.key(priority.as_str())
+    }
+
+    fn delayed_key(&self) -> String {
+        self.key("delayed")
+    }
+
+    fn dead_key(&self) -> String {
+        self.key("dead")
+    }
+
+    fn processing_key(&self, consumer: &str) -> String {
+        self.key(&format!("processing:{consumer}"))
+    }
+
+    fn consumers_key(&self) -> String {
+        self.key("consumers")
+    }
+
+    /// Prefixed key, the connections of the pool use keys as given
+    fn key(&self, suffix: &str) -> String {
+        self.redis
+            .key(&format!("queue:{}:{suffix}", self.name))
+            .into_owned()
+    }
+}
@@ -0,0 +1,679 @@
+use crate::helpers::id;
+use crate::helpers::retry::RetryPolicy;
+use crate::prelude::AppResult;
+use crate::queue::{Job, JobEnvelope, Queue};
+use foxtive_supervisor::SupervisedTask;
+use foxtive_supervisor::contracts::TaskMetrics;
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, warn};
+
+type JobHandler = Arc<dyn Fn(Value) -> BoxFuture<'static, AppResult<()>> + Send + Sync>;
+
+/// What happens to a job after an attempt
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Outcome {
+    Completed,
+    Retry(Duration),
+    Dead,
+}
+
+/// Stops the heartbeats of a worker when its run ends or is aborted
+struct HeartbeatGuard(JoinHandle<()>);
+
+impl Drop for HeartbeatGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    processed: AtomicU64,
+    failed: AtomicU64,
+    dead: AtomicU64,
+}
+
+/// A supervised task executing jobs from a [`Queue`].
+///
+/// Only job types registered with [`QueueWorker::register`] are executed, jobs of any other
+/// type are moved to the dead-letter list. Failed jobs are retried with the backoff of the
+/// worker's [`RetryPolicy`] and moved to the dead-letter list once it runs out of attempts.
+///
+/// Delivery is at least once: jobs left unsettled by a worker that stopped sending
+/// heartbeats are handed to the other workers after [`QueueWorker::stale_after`]. Each run
+/// of the worker consumes under a fresh consumer id, so the jobs of a run that ended are
+/// reaped once its heartbeats stop, even when the supervisor restarts the worker.
+#[derive(Clone)]
+pub struct QueueWorker {
+    id: &'static str,
+    queue: Queue,
+    handlers: HashMap<&'static str, JobHandler>,
+    retry_policy: RetryPolicy,
+    concurrency: usize,
+    poll_timeout: Duration,
+    stale_after: Duration,
+    semaphore: Arc<Semaphore>,
+    counters: Arc<Counters>,
+    #[cfg(feature = "maintenance")]
+    maintenance: Option<crate::maintenance::Maintenance>,
+}
+
+impl QueueWorker {
+    /// Creates a worker processing one job at a time, with the default [`RetryPolicy`]
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: unique id of the task within the supervisor
+    /// * `queue`: the queue to consume
+    pub fn new(id: &'static str, queue: Queue) -> Self {
+        Self {
+            id,
+            queue,
+            handlers: HashMap::new(),
+            retry_policy: RetryPolicy::default(),
+            concurrency: 1,
+            poll_timeout: Duration::from_secs(1),
+            stale_after: Duration::from_secs(60),
+            semaphore: Arc::new(Semaphore::new(1)),
+            counters: Arc::new(Counters::default()),
+            #[cfg(feature = "maintenance")]
+            maintenance: None,
+        }
+    }
+
+    /// Executes jobs of type `J` pushed to the queue
+    pub fn register<J: Job>(mut self) -> Self {
+        let handler: JobHandler = Arc::new(|payload| {
+            Box::pin(async move {
+                let job: J = serde_json::from_value(payload)?;
+                job.handle().await
+            })
+        });
+
+        self.handlers.insert(J::NAME, handler);
+        self
+    }
+
+    /// Maximum number of jobs executed at the same time (minimum 1)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self.semaphore = Arc::new(Semaphore::new(self.concurrency));
+        self
+    }
+
+    /// Attempts and backoff applied to failed jobs
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// How long to block waiting for a job before checking delayed jobs again, it may
+    /// exceed the response timeout of the Redis config
+    pub fn poll_timeout(mut self, timeout: Duration) -> Self {
+        self.poll_timeout = timeout;
+        self
+    }
+
+    /// How long a worker may go without a heartbeat before its unsettled jobs are
+    /// requeued (default 60 seconds, minimum 1 second)
+    ///
+    /// Heartbeats are sent in the background while jobs run, so this doesn't bound the
+    /// duration of a job.
+    pub fn stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Stops taking jobs while the application is in maintenance, running jobs finish
+    #[cfg(feature = "maintenance")]
+    pub fn pause_during_maintenance(
+        mut self,
+        maintenance: crate::maintenance::Maintenance,
+    ) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    pub fn queue(&self) -> &Queue {
+        &self.queue
+    }
+
+    async fn work(&self) -> AppResult<()> {
+        let consumer = self.consumer();
+        self.queue.heartbeat(&consumer).await?;
+        let _heartbeat = self.spawn_heartbeat(&consumer);
+        self.poll(&consumer).await
+    }
+
+    /// Fresh consumer id for a run of the worker
+    fn consumer(&self) -> String {
+        format!("{}:{}", self.id, id::ulid())
+    }
+
+    fn spawn_heartbeat(&self, consumer: &str) -> HeartbeatGuard {
+        let (queue, consumer) = (self.queue.clone(), consumer.to_string());
+        let interval = self.stale_after / 4;
+
+        HeartbeatGuard(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(err) = queue.heartbeat(&consumer).await {
+                    warn!("[queue][{}] failed to send heartbeat: {err}", queue.name());
+                }
+            }
+        }))
+    }
+
+    /// Takes jobs until one can be neither settled nor released, ending the run so its
+    /// consumer goes stale and the job is reaped
+    async fn poll(&self, consumer: &str) -> AppResult<()> {
+        let stranded = Arc::new(AtomicBool::new(false));
+        loop {
+            if stranded.load(Ordering::Acquire) {
+                return Err(anyhow::anyhow!(
+                    "a job of consumer '{consumer}' could not be settled"
+                ));
+            }
+
+            #[cfg(feature = "maintenance")]
+            if let Some(maintenance) = &self.maintenance
+                && maintenance.is_active().await
+            {
+                debug!("[queue][{}] paused for maintenance", self.queue.name());
+                tokio::time::sleep(self.poll_timeout).await;
+                continue;
+            }
+
+            let promoted = self.queue.promote_due().await?;
+            if promoted > 0 {
+                debug!(
+                    "[queue][{}] promoted {promoted} due jobs",
+                    self.queue.name()
+                );
+            }
+
+            let reaped = self.queue.reap_stale(self.stale_after).await?;
+            if reaped > 0 {
+                warn!(
+                    "[queue][{}] requeued {reaped} jobs of stale workers",
+                    self.queue.name()
+                );
+            }
+
+            let permit = self.semaphore.clone().acquire_owned().await?;
+            let Some(item) = self.queue.pop(consumer, self.poll_timeout).await? else {
+                continue;
+            };
+
+            let (worker, consumer, stranded) =
+                (self.clone(), consumer.to_string(), stranded.clone());
+            tokio::spawn(async move {
+                if let Err(err) = worker.process(&consumer, &item).await {
+                    error!(
+                        "[queue][{}] failed to settle job: {err}",
+                        worker.queue.name()
+                    );
+
+                    if let Err(err) = worker.queue.release(&consumer, &item).await {
+                        error!(
+                            "[queue][{}] failed to release job: {err}",
+                            worker.queue.name()
+                        );
+                        stranded.store(true, Ordering::Release);
+                    }
+                }
+                drop(permit);
+            });
+        }
+    }
+
+    /// Executes a popped job and settles it according to the outcome
+    async fn process(&self, consumer: &str, item: &str) -> AppResult<()> {
+        let mut envelope = match serde_json::from_str::<JobEnvelope>(item) {
+            Ok(envelope) => envelope,
+            Err(err) => {
+                error!("[queue][{}] malformed job: {err}", self.queue.name());
+                self.counters.dead.fetch_add(1, Ordering::Relaxed);
+                return self.queue.bury(consumer, item, item.to_string()).await;
+            }
+        };
+
+        envelope.attempts += 1;
+        let result = self.execute(&envelope).await;
+
+        match self.outcome(&envelope, &result) {
+            Outcome::Completed => {
+                self.counters.processed.fetch_add(1, Ordering::Relaxed);
+                self.queue.ack(consumer, item).await
+            }
+            Outcome::Retry(delay) => {
+                self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                envelope.last_error = result.err().map(|err| err.to_string());
+                warn!(
+                    "[queue][{}] job {} ({}) failed on attempt {}: {}, retrying in {delay:?}",
+                    self.queue.name(),
+                    envelope.id,
+                    envelope.job,
+                    envelope.attempts,
+                    envelope.last_error.as_deref().unwrap_or_default()
+                );
+                self.queue.retry(consumer, item, &envelope, delay).await
+            }
+            Outcome::Dead => {
+                self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                self.counters.dead.fetch_add(1, Ordering::Relaxed);
+                envelope.last_error = result.err().map(|err| err.to_string());
+                error!(
+                    "[queue][{}] job {} ({}) moved to dead letters after {} attempts: {}",
+                    self.queue.name(),
+                    envelope.id,
+                    envelope.job,
+                    envelope.attempts,
+                    envelope.last_error.as_deref().unwrap_or_default()
+                );
+                let content = serde_json::to_string(&envelope)?;
+                self.queue.bury(consumer, item, content).await
+            }
+        }
+    }
+
+    async fn execute(&self, envelope: &JobEnvelope) -> AppResult<()> {
+        match self.handlers.get(envelope.job.as_str()) {
+            // a panicking job fails like any other, rather than staying unsettled
+            Some(handler) => AssertUnwindSafe(handler(envelope.payload.clone()))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|panic| {
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .map(|message| message.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_string());
+                    Err(anyhow::anyhow!("job panicked: {message}"))
+                }),
+            None => Err(anyhow::anyhow!(
+                "no handler registered for job '{}'",
+                envelope.job
+            )),
+        }
+    }
+
+    fn outcome(&self, envelope: &JobEnvelope, result: &AppResult<()>) -> Outcome {
+        let Err(err) = result else {
+            return Outcome::Completed;
+        };
+
+        let retryable = self.handlers.contains_key(envelope.job.as_str())
+            && envelope.attempts < self.retry_policy.get_max_attempts()
+            && self.retry_policy.should_retry(err);
+
+        match retryable {
+            true => Outcome::Retry(self.retry_policy.delay_for(envelope.attempts)),
+            false => Outcome::Dead,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SupervisedTask for QueueWorker {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> String {
+        format!("queue worker ({})", self.queue.name())
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        self.work().await
+    }
+
+    async fn metrics(&self) -> Option<TaskMetrics> {
+        let processed = self.counters.processed.load(Ordering::Relaxed);
+        let failed = self.counters.failed.load(Ordering::Relaxed);
+
+        let mut metrics = TaskMetrics {
+            success_count: processed,
+            error_count: failed,
+            processed_count: processed + failed,
+            ..TaskMetrics::default()
+        };
+        metrics.custom.insert(
+            "dead_letters".to_string(),
+            self.counters.dead.load(Ordering::Relaxed) as f64,
+        );
+
+        Some(metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::retry::BackoffStrategy;
+    use crate::queue::{JobOptions, Priority};
+    use crate::redis::Redis;
+    use redis::AsyncCommands;
+    use serde::{Deserialize, Serialize};
+    use std::sync::atomic::AtomicUsize;
+
+    static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Serialize, Deserialize)]
+    struct Count {
+        by: usize,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Job for Count {
+        const NAME: &'static str = "count";
+
+        async fn handle(&self) -> AppResult<()> {
+            match self.fail {
+                true => Err(anyhow::anyhow!("boom")),
+                false => {
+                    HANDLED.fetch_add(self.by, Ordering::SeqCst);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Explode;
+
+    #[async_trait::async_trait]
+    impl Job for Explode {
+        const NAME: &'static str = "explode";
+
+        async fn handle(&self) -> AppResult<()> {
+            panic!("handler exploded")
+        }
+    }
+
+    fn worker() -> QueueWorker {
+        // the pool connects lazily, these tests never touch Redis
+        let manager = deadpool_redis::Manager::new("redis://127.0.0.1:1").unwrap();
+        let pool = deadpool_redis::Pool::builder(manager).build().unwrap();
+        let queue = Queue::new(Arc::new(Redis::new(pool)), "test");
+
+        let policy = RetryPolicy::new(BackoffStrategy::fixed(Duration::from_secs(2)))
+            .max_attempts(3)
+            .jitter(0.0);
+
+        QueueWorker::new("test-worker", queue)
+            .register::<Count>()
+            .register::<Explode>()
+            .retry_policy(policy)
+    }
+
+    fn envelope(job: &str, payload: Value, attempts: usize) -> JobEnvelope {
+        JobEnvelope {
+            id: "01".to_string(),
+            job: job.to_string(),
+            payload,
+            priority: Priority::Normal,
+            attempts,
+            enqueued_at: 0,
+            last_error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_dispatches_to_registered_job() {
+        let worker = worker();
+        let before = HANDLED.load(Ordering::SeqCst);
+
+        let job = envelope("count", serde_json::json!({ "by": 5, "fail": false }), 1);
+        worker.execute(&job).await.unwrap();
+        assert_eq!(HANDLED.load(Ordering::SeqCst) - before, 5);
+
+        let unknown = envelope("unknown", Value::Null, 1);
+        assert!(worker.execute(&unknown).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_outcome_retries_until_attempts_run_out() {
+        let worker = worker();
+        let payload = serde_json::json!({ "by": 1, "fail": true });
+
+        let first = envelope("count", payload.clone(), 1);
+        let result = worker.execute(&first).await;
+        assert_eq!(
+            worker.outcome(&first, &result),
+            Outcome::Retry(Duration::from_secs(2))
+        );
+
+        let last = envelope("count", payload, 3);
+        assert_eq!(worker.outcome(&last, &result), Outcome::Dead);
+        assert_eq!(worker.outcome(&last, &Ok(())), Outcome::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_jobs_fail_and_are_retried() {
+        let worker = worker();
+        let job = envelope("explode", Value::Null, 1);
+
+        let result = worker.execute(&job).await;
+        let err = result.as_ref().unwrap_err().to_string();
+        assert_eq!(err, "job panicked: handler exploded");
+        assert_eq!(
+            worker.outcome(&job, &result),
+            Outcome::Retry(Duration::from_secs(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_jobs_are_not_retried() {
+        let worker = worker();
+        let unknown = envelope("unknown", Value::Null, 1);
+        let result = worker.execute(&unknown).await;
+        assert_eq!(worker.outcome(&unknown, &result), Outcome::Dead);
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let job = envelope("count", serde_json::json!({ "by": 1, "fail": false }), 2);
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains(r#""priority":"normal""#));
+        assert_eq!(serde_json::from_str::<JobEnvelope>(&json).unwrap(), job);
+    }
+
+    #[test]
+    fn test_keys_and_priority_order() {
+        let queue = worker().queue;
+        let keys = Priority::ALL.map(|priority| queue.ready_key(priority));
+        assert_eq!(
+            keys,
+            ["queue:test:high", "queue:test:normal", "queue:test:low"]
+        );
+        assert_eq!(queue.delayed_key(), "queue:test:delayed");
+        assert_eq!(queue.dead_key(), "queue:test:dead");
+        assert_eq!(
+            queue.processing_key("test-worker:01"),
+            "queue:test:processing:test-worker:01"
+        );
+        assert_eq!(queue.consumers_key(), "queue:test:consumers");
+
+        let manager = deadpool_redis::Manager::new("redis://127.0.0.1:1").unwrap();
+        let pool = deadpool_redis::Pool::builder(manager).build().unwrap();
+        let queue = Queue::new(Arc::new(Redis::new(pool).with_prefix("shop")), "test");
+        assert_eq!(queue.ready_key(Priority::High), "shop:queue:test:high");
+        assert_eq!(queue.dead_key(), "shop:queue:test:dead");
+    }
+
+    #[test]
+    fn test_runs_have_distinct_consumers() {
+        let worker = worker();
+        let (first, second) = (worker.consumer(), worker.consumer());
+        assert!(first.starts_with("test-worker:"));
+        assert_ne!(first, second);
+    }
+
+    /// Queue with a unique name on a local Redis, for the tests marked `#[ignore]`
+    fn live_queue() -> Queue {
+        let pool = deadpool_redis::Config::from_url("redis://127.0.0.1:6379")
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        Queue::new(Arc::new(Redis::new(pool)), &format!("test-{}", id::ulid()))
+    }
+
+    async fn processing_len(queue: &Queue, consumer: &str) -> usize {
+        let mut conn = queue.redis.redis().await.unwrap();
+        conn.llen(queue.processing_key(consumer)).await.unwrap()
+    }
+
+    async fn pop(queue: &Queue, consumer: &str) -> String {
+        let item = queue.pop(consumer, Duration::from_secs(1)).await.unwrap();
+        item.expect("a ready job")
+    }
+
+    fn count(fail: bool) -> Count {
+        Count { by: 1, fail }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_pop_takes_highest_priority_and_ack_settles() {
+        let queue = live_queue();
+        let low = JobOptions::new().priority(Priority::Low);
+        let high = JobOptions::new().priority(Priority::High);
+        queue.push_with(&count(false), low).await.unwrap();
+        let id = queue.push_with(&count(false), high).await.unwrap();
+
+        let item = pop(&queue, "c1").await;
+        let envelope: JobEnvelope = serde_json::from_str(&item).unwrap();
+        assert_eq!(envelope.id, id);
+        assert_eq!(queue.ready_count().await.unwrap(), 1);
+        assert_eq!(processing_len(&queue, "c1").await, 1);
+
+        queue.ack("c1", &item).await.unwrap();
+        assert_eq!(processing_len(&queue, "c1").await, 0);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_retry_delays_job_until_promoted() {
+        let queue = live_queue();
+        queue.push(&count(true)).await.unwrap();
+
+        let item = pop(&queue, "c1").await;
+        let envelope: JobEnvelope = serde_json::from_str(&item).unwrap();
+        queue
+            .retry("c1", &item, &envelope, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(processing_len(&queue, "c1").await, 0);
+        assert_eq!(queue.delayed_count().await.unwrap(), 1);
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(queue.promote_due().await.unwrap(), 1);
+        assert_eq!(queue.delayed_count().await.unwrap(), 0);
+        assert_eq!(queue.ready_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_delayed_jobs_are_not_promoted_early() {
+        let queue = live_queue();
+        let options = JobOptions::new().delay(Duration::from_secs(60));
+        queue.push_with(&count(false), options).await.unwrap();
+
+        assert_eq!(queue.promote_due().await.unwrap(), 0);
+        assert_eq!(queue.delayed_count().await.unwrap(), 1);
+        assert_eq!(queue.ready_count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_bury_and_requeue_dead_letters() {
+        let queue = live_queue();
+        queue.push(&count(true)).await.unwrap();
+
+        let item = pop(&queue, "c1").await;
+        let mut envelope: JobEnvelope = serde_json::from_str(&item).unwrap();
+        envelope.attempts = 3;
+        let content = serde_json::to_string(&envelope).unwrap();
+        queue.bury("c1", &item, content).await.unwrap();
+        let mut conn = queue.redis.redis().await.unwrap();
+        let _: usize = conn.lpush(queue.dead_key(), "not json").await.unwrap();
+
+        assert_eq!(processing_len(&queue, "c1").await, 0);
+        assert!(queue.dead_letters(0).await.unwrap().is_empty());
+        assert_eq!(queue.dead_letters(10).await.unwrap(), vec![envelope]);
+
+        assert_eq!(queue.requeue_dead_letters().await.unwrap(), 1);
+        assert_eq!(queue.ready_count().await.unwrap(), 1);
+
+        let dead: Vec<String> = conn.lrange(queue.dead_key(), 0, -1).await.unwrap();
+        assert_eq!(dead, vec!["not json".to_string()]);
+
+        let item = pop(&queue, "c1").await;
+        let requeued: JobEnvelope = serde_json::from_str(&item).unwrap();
+        assert_eq!(requeued.attempts, 0);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_reap_stale_requeues_jobs_of_silent_consumers() {
+        let queue = live_queue();
+        queue.push(&count(false)).await.unwrap();
+
+        queue.heartbeat("c1").await.unwrap();
+        pop(&queue, "c1").await;
+        assert_eq!(queue.reap_stale(Duration::from_secs(60)).await.unwrap(), 0);
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(queue.reap_stale(Duration::ZERO).await.unwrap(), 1);
+        assert_eq!(processing_len(&queue, "c1").await, 0);
+        assert_eq!(queue.ready_count().await.unwrap(), 1);
+        assert_eq!(queue.reap_stale(Duration::ZERO).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_release_returns_job_to_ready_list() {
+        let queue = live_queue();
+        queue.push(&count(false)).await.unwrap();
+
+        let item = pop(&queue, "c1").await;
+        queue.release("c1", &item).await.unwrap();
+        assert_eq!(processing_len(&queue, "c1").await, 0);
+        assert_eq!(queue.ready_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_process_settles_by_outcome() {
+        let mut worker = worker();
+        worker.queue = live_queue();
+        let consumer = worker.consumer();
+
+        worker.queue.push(&count(false)).await.unwrap();
+        let item = pop(&worker.queue, &consumer).await;
+        worker.process(&consumer, &item).await.unwrap();
+        assert_eq!(processing_len(&worker.queue, &consumer).await, 0);
+
+        worker.queue.push(&count(true)).await.unwrap();
+        let item = pop(&worker.queue, &consumer).await;
+        worker.process(&consumer, &item).await.unwrap();
+        assert_eq!(processing_len(&worker.queue, &consumer).await, 0);
+        assert_eq!(worker.queue.delayed_count().await.unwrap(), 1);
+
+        worker.queue.push(&Explode).await.unwrap();
+        let item = pop(&worker.queue, &consumer).await;
+        worker.process(&consumer, &item).await.unwrap();
+        assert_eq!(processing_len(&worker.queue, &consumer).await, 0);
+        assert_eq!(worker.queue.delayed_count().await.unwrap(), 2);
+    }
+}
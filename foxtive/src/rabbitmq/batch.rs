@@ -0,0 +1,50 @@
+//! Messages published together by [`RabbitMQ::publish_batch`](super::RabbitMQ::publish_batch).
+
+use crate::prelude::AppResult;
+use lapin::publisher_confirm::Confirmation;
+
+/// A message of a batch, published with the instance's default properties
+#[derive(Debug, Clone)]
+pub struct BatchMessage {
+    pub exchange: String,
+    pub routing_key: String,
+    pub payload: Vec<u8>,
+}
+
+impl BatchMessage {
+    pub fn new<E: ToString, R: ToString>(exchange: E, routing_key: R, payload: Vec<u8>) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            payload,
+        }
+    }
+}
+
+/// Outcome of a publish once the broker confirmed it
+pub(crate) fn confirmation_result(confirmation: Confirmation) -> AppResult<()> {
+    match confirmation {
+        Confirmation::Ack(_) | Confirmation::NotRequested => Ok(()),
+        Confirmation::Nack(_) => Err(anyhow::anyhow!("Message was rejected by the broker")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_result() {
+        assert!(confirmation_result(Confirmation::Ack(None)).is_ok());
+        assert!(confirmation_result(Confirmation::NotRequested).is_ok());
+        assert!(confirmation_result(Confirmation::Nack(None)).is_err());
+    }
+
+    #[test]
+    fn test_batch_message_new() {
+        let message = BatchMessage::new("events", "user.created", b"{}".to_vec());
+        assert_eq!(message.exchange, "events");
+        assert_eq!(message.routing_key, "user.created");
+        assert_eq!(message.payload, b"{}");
+    }
+}
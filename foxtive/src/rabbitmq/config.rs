@@ -1,4 +1,6 @@
+use crate::setup::ConnectPolicy;
 use lapin::ConnectionProperties;
+use std::time::Duration;
 
 pub use deadpool::managed::QueueMode;
 pub use deadpool_lapin::{PoolConfig, Timeouts};
@@ -7,6 +9,10 @@ pub struct RabbitmqConfig {
     pub(crate) dsn: String,
     pub(crate) pool_config: PoolConfig,
     pub(crate) conn_props: ConnectionProperties,
+    pub(crate) connect_policy: ConnectPolicy,
+    pub(crate) tls: bool,
+    pub(crate) heartbeat: Option<Duration>,
+    pub(crate) prefetch: Option<u16>,
 }
 
 impl RabbitmqConfig {
@@ -15,6 +21,10 @@ impl RabbitmqConfig {
             dsn: dsn.to_string(),
             pool_config: PoolConfig::default(),
             conn_props: ConnectionProperties::default(),
+            connect_policy: ConnectPolicy::Required,
+            tls: false,
+            heartbeat: None,
+            prefetch: None,
         }
     }
 
@@ -27,4 +37,114 @@ impl RabbitmqConfig {
         self.pool_config = pool_config;
         self
     }
+
+    /// Sets how RabbitMQ is connected to while the state is created,
+    /// defaults to [`ConnectPolicy::Required`]
+    pub fn connect_policy(mut self, connect_policy: ConnectPolicy) -> Self {
+        self.connect_policy = connect_policy;
+        self
+    }
+
+    /// Connects over TLS, as an `amqps://` DSN does, the server certificate is verified
+    /// against the system roots, `SSL_CERT_FILE` included
+    pub fn tls(mut self) -> Self {
+        self.tls = true;
+        self
+    }
+
+    /// Negotiates heartbeats every `interval`, overriding the `heartbeat` of the DSN,
+    /// a zero interval disables them
+    pub fn heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    /// Limits the unacknowledged messages delivered to each consumer,
+    /// unlimited by default
+    pub fn prefetch(mut self, count: u16) -> Self {
+        self.prefetch = Some(count);
+        self
+    }
+
+    /// Names the connections, as shown by the management UI
+    pub fn connection_name(mut self, name: &str) -> Self {
+        self.conn_props = self.conn_props.with_connection_name(name.into());
+        self
+    }
+
+    /// Keeps at most `size` connections open
+    pub fn max_pool_size(mut self, size: usize) -> Self {
+        self.pool_config.max_size = size;
+        self
+    }
+
+    /// Fails getting a connection from a full pool after `timeout`, instead of waiting forever
+    pub fn pool_wait_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_config.timeouts.wait = Some(timeout);
+        self
+    }
+
+    /// The DSN with the TLS scheme and heartbeat applied
+    pub(crate) fn uri(&self) -> String {
+        let mut uri = match self.tls {
+            true => match self.dsn.strip_prefix("amqp://") {
+                Some(rest) => format!("amqps://{rest}"),
+                None => self.dsn.clone(),
+            },
+            false => self.dsn.clone(),
+        };
+
+        if let Some(interval) = self.heartbeat {
+            let (base, query) = uri.split_once('?').unwrap_or((&uri, ""));
+            let mut params: Vec<&str> = query
+                .split('&')
+                .filter(|param| !param.is_empty() && !param.starts_with("heartbeat="))
+                .collect();
+
+            let heartbeat = format!("heartbeat={}", interval.as_secs());
+            params.push(&heartbeat);
+            uri = format!("{base}?{}", params.join("&"));
+        }
+
+        uri
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_applies_tls_and_heartbeat() {
+        let config = RabbitmqConfig::create("amqp://guest:guest@mq:5671/%2f")
+            .tls()
+            .heartbeat(Duration::from_secs(30));
+        assert_eq!(config.uri(), "amqps://guest:guest@mq:5671/%2f?heartbeat=30");
+
+        let config = RabbitmqConfig::create("amqp://mq?heartbeat=60&frame_max=8192")
+            .heartbeat(Duration::from_secs(10));
+        assert_eq!(config.uri(), "amqp://mq?frame_max=8192&heartbeat=10");
+
+        assert_eq!(RabbitmqConfig::create("amqp://mq").uri(), "amqp://mq");
+    }
+
+    #[test]
+    fn test_connection_name_and_pool_size() {
+        let config = RabbitmqConfig::create("amqp://mq")
+            .connection_name("billing-worker")
+            .max_pool_size(4)
+            .pool_wait_timeout(Duration::from_secs(3));
+
+        assert!(
+            config
+                .conn_props
+                .client_properties
+                .contains_key("connection_name")
+        );
+        assert_eq!(config.pool_config.max_size, 4);
+        assert_eq!(
+            config.pool_config.timeouts.wait,
+            Some(Duration::from_secs(3))
+        );
+    }
 }
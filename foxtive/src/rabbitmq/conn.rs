@@ -1,13 +1,14 @@
 use crate::prelude::AppResult;
 use crate::rabbitmq::config::RabbitmqConfig;
 use anyhow::Error;
-use deadpool_lapin::{Manager, Pool};
+use deadpool_lapin::{Manager, Pool, Runtime};
 
 pub async fn create_rmq_conn_pool(config: RabbitmqConfig) -> AppResult<Pool> {
-    let manager = Manager::new(config.dsn, config.conn_props);
+    let manager = Manager::new(config.uri(), config.conn_props);
 
     Pool::builder(manager)
         .config(config.pool_config)
+        .runtime(Runtime::Tokio1)
         .build()
         .map_err(Error::msg)
 }
@@ -0,0 +1,84 @@
+use crate::prelude::AppResult;
+use crate::rabbitmq::RabbitMQ;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// The publishing side of RabbitMQ, implemented by the shared client of the global
+/// state, `Mutex<RabbitMQ>`, and, with the `test-utils` feature, by
+/// [`MockRabbit`](crate::test_utils::MockRabbit) which records what is published.
+///
+/// Services depending on `Arc<dyn RabbitContract>` instead of the client can be
+/// unit tested without a broker.
+///
+/// # Example
+///
+/// ```no_run
+/// use foxtive::rabbitmq::RabbitContract;
+/// use foxtive::prelude::AppResult;
+/// use std::sync::Arc;
+///
+/// struct Orders {
+///     rabbit: Arc<dyn RabbitContract>,
+/// }
+///
+/// impl Orders {
+///     async fn place(&self, id: u64) -> AppResult<()> {
+///         self.rabbit.publish_json("orders", "order.placed", &id).await
+///     }
+/// }
+///
+/// # fn orders(rabbit: Arc<tokio::sync::Mutex<foxtive::rabbitmq::RabbitMQ>>) -> Orders {
+/// // `FOXTIVE.rabbitmq()` in production
+/// Orders { rabbit }
+/// # }
+/// ```
+#[async_trait::async_trait]
+pub trait RabbitContract: Send + Sync {
+    async fn publish(&self, exchange: &str, routing_key: &str, payload: &[u8]) -> AppResult<()>;
+
+    /// Publishes `payload` to be delivered once `delay` has passed
+    async fn publish_delayed(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        payload: &[u8],
+        delay: Duration,
+    ) -> AppResult<()>;
+}
+
+impl dyn RabbitContract + '_ {
+    /// Publishes a value serialized to JSON, readable with `Message::deserialize`
+    pub async fn publish_json<T: Serialize + ?Sized>(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        data: &T,
+    ) -> AppResult<()> {
+        self.publish(exchange, routing_key, &serde_json::to_vec(data)?)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl RabbitContract for Mutex<RabbitMQ> {
+    async fn publish(&self, exchange: &str, routing_key: &str, payload: &[u8]) -> AppResult<()> {
+        self.lock()
+            .await
+            .publish(exchange, routing_key, payload)
+            .await
+    }
+
+    async fn publish_delayed(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        payload: &[u8],
+        delay: Duration,
+    ) -> AppResult<()> {
+        self.lock()
+            .await
+            .publish_delayed(exchange, routing_key, payload, delay)
+            .await
+    }
+}
@@ -0,0 +1,69 @@
+//! How [`RabbitMQ::publish_delayed`](super::RabbitMQ::publish_delayed) holds messages back.
+
+use crate::rabbitmq::topology::Queue;
+use std::time::Duration;
+
+/// How long an unused delay queue outlives the longest message it may hold
+const DELAY_QUEUE_GRACE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DelayStrategy {
+    /// Park messages in a queue per exchange, routing key and delay, whose TTL
+    /// dead-letters them to the target exchange once expired.
+    ///
+    /// Works on any broker; the queues are declared on demand and deleted
+    /// by the broker once unused.
+    #[default]
+    TtlQueues,
+    /// Publish with an `x-delay` header, the target exchange must be declared
+    /// with type `x-delayed-message` from the
+    /// [delayed message plugin](https://github.com/rabbitmq/rabbitmq-delayed-message-exchange)
+    Plugin,
+}
+
+/// Queue parking messages for `delay` before dead-lettering them to `exchange` with `routing_key`
+pub(crate) fn delay_queue(exchange: &str, routing_key: &str, delay: Duration) -> Queue {
+    let name = format!("delayed.{exchange}.{routing_key}.{}ms", delay.as_millis());
+
+    Queue::new(&name)
+        .message_ttl(delay)
+        .dead_letter_exchange(exchange)
+        .dead_letter_routing_key(routing_key)
+        .expires(delay + DELAY_QUEUE_GRACE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lapin::types::AMQPValue;
+
+    #[test]
+    fn test_delay_queue_is_unique_per_target_and_delay() {
+        let queue = delay_queue("emails", "send", Duration::from_secs(30));
+        assert_eq!(queue.name(), "delayed.emails.send.30000ms");
+        assert_ne!(
+            delay_queue("emails", "send", Duration::from_secs(31)).name(),
+            queue.name()
+        );
+    }
+
+    #[test]
+    fn test_delay_queue_dead_letters_to_target_and_outlives_its_messages() {
+        let queue = delay_queue("emails", "send", Duration::from_secs(30));
+        let args = queue.arguments().inner();
+
+        assert_eq!(
+            args.get("x-message-ttl"),
+            Some(&AMQPValue::LongLongInt(30_000))
+        );
+        assert_eq!(
+            args.get("x-dead-letter-exchange"),
+            Some(&AMQPValue::LongString("emails".into()))
+        );
+        assert_eq!(
+            args.get("x-dead-letter-routing-key"),
+            Some(&AMQPValue::LongString("send".into()))
+        );
+        assert_eq!(args.get("x-expires"), Some(&AMQPValue::LongLongInt(90_000)));
+    }
+}
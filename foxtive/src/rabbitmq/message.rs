@@ -1,3 +1,4 @@
+use crate::context::RequestContext;
 use crate::prelude::AppResult;
 use lapin::message::Delivery;
 use lapin::options::{BasicAckOptions, BasicNackOptions};
@@ -20,6 +21,11 @@ impl Message {
         &self.delivery.data
     }
 
+    /// Mutable access to the delivery, for middlewares rewriting payloads or headers
+    pub fn delivery_mut(&mut self) -> &mut Delivery {
+        &mut self.delivery
+    }
+
     pub fn str(&self) -> AppResult<&str> {
         Ok(std::str::from_utf8(&self.delivery.data)?)
     }
@@ -28,6 +34,18 @@ impl Message {
         &self.delivery.routing_key
     }
 
+    /// Context of the request the message was published for, a new one if it carries none.
+    ///
+    /// Consumers handle the message within it, see [`RequestContext::current`]
+    pub fn context(&self) -> RequestContext {
+        self.delivery
+            .properties
+            .headers()
+            .as_ref()
+            .map(RequestContext::from_amqp_headers)
+            .unwrap_or_default()
+    }
+
     pub fn deserialize<T>(&self) -> AppResult<T>
     where
         T: serde::de::DeserializeOwned,
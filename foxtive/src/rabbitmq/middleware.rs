@@ -0,0 +1,129 @@
+//! Hooks applied to every message published or consumed by a [`RabbitMQ`](super::RabbitMQ) instance.
+//!
+//! Middlewares run in registration order on publish and in reverse order on consume,
+//! so a middleware compressing payloads on the way out decompresses them on the way in
+//! after the ones registered later have run.
+
+use crate::prelude::AppResult;
+use crate::rabbitmq::Message;
+use lapin::BasicProperties;
+use std::sync::Arc;
+
+/// A message about to be published
+#[derive(Debug, Clone)]
+pub struct OutgoingMessage {
+    pub exchange: String,
+    pub routing_key: String,
+    pub payload: Vec<u8>,
+    pub properties: BasicProperties,
+}
+
+/// Cross-cutting concern applied to messages, e.g. tracing, header propagation,
+/// payload compression or schema validation
+///
+/// # Example
+///
+/// ```rust
+/// use foxtive::prelude::AppResult;
+/// use foxtive::rabbitmq::middleware::{MessageMiddleware, OutgoingMessage};
+///
+/// struct RejectEmpty;
+///
+/// #[async_trait::async_trait]
+/// impl MessageMiddleware for RejectEmpty {
+///     async fn on_publish(&self, message: &mut OutgoingMessage) -> AppResult<()> {
+///         if message.payload.is_empty() {
+///             return Err(anyhow::anyhow!("refusing to publish an empty message"));
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait MessageMiddleware: Send + Sync {
+    /// Called before a message is published, an error aborts the publish
+    async fn on_publish(&self, _message: &mut OutgoingMessage) -> AppResult<()> {
+        Ok(())
+    }
+
+    /// Called before the handler receives a message, an error is handled
+    /// like one returned by the handler
+    async fn on_consume(&self, _message: &mut Message) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+pub(crate) async fn apply_publish(
+    middlewares: &[Arc<dyn MessageMiddleware>],
+    message: &mut OutgoingMessage,
+) -> AppResult<()> {
+    for middleware in middlewares {
+        middleware.on_publish(message).await?;
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn apply_consume(
+    middlewares: &[Arc<dyn MessageMiddleware>],
+    message: &mut Message,
+) -> AppResult<()> {
+    for middleware in middlewares.iter().rev() {
+        middleware.on_consume(message).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Append(&'static [u8]);
+
+    #[async_trait::async_trait]
+    impl MessageMiddleware for Append {
+        async fn on_publish(&self, message: &mut OutgoingMessage) -> AppResult<()> {
+            message.payload.extend_from_slice(self.0);
+            Ok(())
+        }
+    }
+
+    struct Reject;
+
+    #[async_trait::async_trait]
+    impl MessageMiddleware for Reject {
+        async fn on_publish(&self, _message: &mut OutgoingMessage) -> AppResult<()> {
+            Err(anyhow::anyhow!("rejected"))
+        }
+    }
+
+    fn outgoing() -> OutgoingMessage {
+        OutgoingMessage {
+            exchange: "events".to_string(),
+            routing_key: "user.created".to_string(),
+            payload: b"payload".to_vec(),
+            properties: BasicProperties::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_middlewares_run_in_registration_order() {
+        let middlewares: Vec<Arc<dyn MessageMiddleware>> =
+            vec![Arc::new(Append(b"-a")), Arc::new(Append(b"-b"))];
+
+        let mut message = outgoing();
+        apply_publish(&middlewares, &mut message).await.unwrap();
+        assert_eq!(message.payload, b"payload-a-b");
+    }
+
+    #[tokio::test]
+    async fn test_publish_middleware_error_stops_the_chain() {
+        let middlewares: Vec<Arc<dyn MessageMiddleware>> =
+            vec![Arc::new(Reject), Arc::new(Append(b"-a"))];
+
+        let mut message = outgoing();
+        assert!(apply_publish(&middlewares, &mut message).await.is_err());
+        assert_eq!(message.payload, b"payload");
+    }
+}
@@ -1,7 +1,9 @@
 use futures_util::StreamExt;
 use futures_util::future::BoxFuture;
-use lapin::types::FieldTable;
+use lapin::publisher_confirm::PublisherConfirm;
+use lapin::types::{AMQPValue, FieldTable};
 use lapin::{BasicProperties, ConnectionState};
+use std::collections::VecDeque;
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
@@ -17,20 +19,33 @@ pub use {
 };
 
 use crate::FOXTIVE;
+use crate::context::RequestContext;
+use crate::instrument::observe;
 use crate::prelude::{AppResult, AppStateExt};
+use crate::rabbitmq::batch::BatchMessage;
+pub use crate::rabbitmq::contract::RabbitContract;
+use crate::rabbitmq::delay::DelayStrategy;
 pub use crate::rabbitmq::message::Message;
+use crate::rabbitmq::middleware::{MessageMiddleware, OutgoingMessage};
+use crate::rabbitmq::topology::Topology;
 
+pub mod batch;
 pub mod config;
 pub mod conn;
+mod contract;
+pub mod delay;
 mod message;
+pub mod middleware;
+pub mod topology;
 
 pub type RabbitMQSetupFn = Arc<dyn Fn(RabbitMQ) -> BoxFuture<'static, AppResult<()>> + Send + Sync>;
 
 #[derive(Clone)]
 pub struct RabbitMQ {
     conn_pool: deadpool_lapin::Pool,
-    publish_channel: Channel,
-    consume_channel: Channel,
+    /// opened on first use when the instance is created lazily
+    publish_channel: Option<Channel>,
+    consume_channel: Option<Channel>,
     /// helps determine if the connection can be reconnected
     can_reconnect: bool,
     /// automatically nack a message if the handler returns an error.
@@ -39,6 +54,8 @@ pub struct RabbitMQ {
     requeue_on_failure: bool,
     /// whether the handler should be executed in the background (asynchronously) or not.
     execute_handler_asynchronously: bool,
+    /// max unacknowledged messages delivered to each consumer
+    prefetch: Option<u16>,
     /// max reconnection attempts, defaults to 1,000,000
     max_reconnection_attempts: usize,
     /// max reconnection delay, defaults to 1 second
@@ -51,6 +68,14 @@ pub struct RabbitMQ {
     default_consume_options: BasicConsumeOptions,
     /// setup function to run after the connection is established.
     setup_fn: Option<RabbitMQSetupFn>,
+    /// topology declared again whenever a channel is recreated
+    topology: Option<Arc<Topology>>,
+    /// applied to every published and consumed message
+    middlewares: Vec<Arc<dyn MessageMiddleware>>,
+    /// how delayed messages are held back, defaults to TTL queues
+    delay_strategy: DelayStrategy,
+    /// max publishes awaiting a broker confirmation in a batch, defaults to 256
+    confirm_window: usize,
 }
 
 #[derive(Default)]
@@ -61,43 +86,75 @@ pub struct RabbitMQOptions {
     pub requeue_on_failure: bool,
     /// whether the handler should be executed in the background (asynchronously) or not.
     pub execute_handler_asynchronously: bool,
+    /// max unacknowledged messages delivered to each consumer, unlimited when unset.
+    pub prefetch: Option<u16>,
 }
 impl RabbitMQ {
     const RETRY_DELAY: Duration = Duration::from_secs(2);
 
     /// Create a new instance and connect to the RabbitMQ server
     pub async fn new(pool: deadpool_lapin::Pool) -> AppResult<Self> {
-        Self::new_opt(
-            pool,
-            RabbitMQOptions {
-                nack_on_failure: true,
-                requeue_on_failure: true,
-                execute_handler_asynchronously: true,
-            },
-        )
-        .await
+        Self::new_opt(pool, Self::default_options()).await
     }
 
     /// Create a new instance with connection from foxtive static context
     pub async fn new_from_foxtive() -> AppResult<Self> {
-        Self::new_opt(
-            FOXTIVE.rabbitmq_pool(),
-            RabbitMQOptions {
-                nack_on_failure: true,
-                requeue_on_failure: true,
-                execute_handler_asynchronously: true,
-            },
-        )
-        .await
+        Self::new_opt(FOXTIVE.rabbitmq_pool(), Self::default_options()).await
     }
 
     pub async fn new_opt(pool: deadpool_lapin::Pool, opt: RabbitMQOptions) -> AppResult<Self> {
         let connection = pool.get().await?;
         let publish_channel = connection.create_channel().await?;
         let consume_channel = connection.create_channel().await?;
+        Self::apply_prefetch(&consume_channel, opt.prefetch).await?;
+
+        Ok(Self::with_channels(
+            pool,
+            opt,
+            Some(publish_channel),
+            Some(consume_channel),
+        ))
+    }
+
+    /// Create a new instance without connecting to the RabbitMQ server,
+    /// the connection and channels are opened on first use
+    pub fn new_lazy(pool: deadpool_lapin::Pool) -> Self {
+        Self::new_lazy_opt(pool, Self::default_options())
+    }
+
+    pub fn new_lazy_opt(pool: deadpool_lapin::Pool, opt: RabbitMQOptions) -> Self {
+        Self::with_channels(pool, opt, None, None)
+    }
+
+    pub(crate) fn default_options() -> RabbitMQOptions {
+        RabbitMQOptions {
+            nack_on_failure: true,
+            requeue_on_failure: true,
+            execute_handler_asynchronously: true,
+            prefetch: None,
+        }
+    }
+
+    async fn apply_prefetch(channel: &Channel, prefetch: Option<u16>) -> AppResult<()> {
+        if let Some(count) = prefetch {
+            channel.basic_qos(count, BasicQosOptions::default()).await?;
+        }
 
-        Ok(Self {
+        Ok(())
+    }
+
+    fn with_channels(
+        pool: deadpool_lapin::Pool,
+        opt: RabbitMQOptions,
+        publish_channel: Option<Channel>,
+        consume_channel: Option<Channel>,
+    ) -> Self {
+        Self {
             setup_fn: None,
+            topology: None,
+            middlewares: Vec::new(),
+            delay_strategy: DelayStrategy::default(),
+            confirm_window: 256,
             conn_pool: pool,
             publish_channel,
             consume_channel,
@@ -107,10 +164,11 @@ impl RabbitMQ {
             nack_on_failure: opt.nack_on_failure,
             requeue_on_failure: opt.requeue_on_failure,
             execute_handler_asynchronously: opt.execute_handler_asynchronously,
+            prefetch: opt.prefetch,
             default_publish_options: BasicPublishOptions::default(),
             default_publish_props: BasicProperties::default(),
             default_consume_options: BasicConsumeOptions::default(),
-        })
+        }
     }
 
     /// Set whether to nack a message if the handler returns an error.
@@ -150,10 +208,43 @@ impl RabbitMQ {
         self
     }
 
-    pub async fn declare_exchange(&mut self, exchange: &str, kind: ExchangeKind) -> AppResult<()> {
-        self.ensure_channel_is_usable(true).await?;
+    /// Set how [`publish_delayed`](Self::publish_delayed) holds messages back.
+    /// Default value is [`DelayStrategy::TtlQueues`]
+    pub fn delay_strategy(&mut self, strategy: DelayStrategy) -> &mut Self {
+        self.delay_strategy = strategy;
+        self
+    }
+
+    /// Set how many publishes of [`publish_batch`](Self::publish_batch) may await
+    /// a broker confirmation at once.
+    /// Default value is `256`
+    pub fn confirm_window(&mut self, window: usize) -> &mut Self {
+        self.confirm_window = window.max(1);
+        self
+    }
+
+    /// Registers a middleware applied to every published and consumed message,
+    /// see [`middleware`] for the order they run in
+    pub fn middleware<M: MessageMiddleware + 'static>(&mut self, middleware: M) -> &mut Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Declares `topology` now and again after every reconnection,
+    /// before the setup function runs.
+    pub async fn declare_topology(&mut self, topology: Topology) -> AppResult<&mut Self> {
+        info!("Declaring topology...");
+        let channel = self.usable_channel(true).await?;
+        topology.declare(&channel).await?;
+        info!("Topology declared successfully.");
 
-        self.publish_channel
+        self.topology = Some(Arc::new(topology));
+        Ok(self)
+    }
+
+    pub async fn declare_exchange(&mut self, exchange: &str, kind: ExchangeKind) -> AppResult<()> {
+        self.usable_channel(true)
+            .await?
             .exchange_declare(
                 exchange,
                 kind.clone(),
@@ -171,9 +262,8 @@ impl RabbitMQ {
         options: QueueDeclareOptions,
         args: FieldTable,
     ) -> AppResult<()> {
-        self.ensure_channel_is_usable(true).await?;
-
-        self.publish_channel
+        self.usable_channel(true)
+            .await?
             .queue_declare(queue, options, args)
             .await?;
 
@@ -188,15 +278,15 @@ impl RabbitMQ {
         options: QueueBindOptions,
         args: FieldTable,
     ) -> AppResult<()> {
-        self.ensure_channel_is_usable(true).await?;
-
-        self.publish_channel
+        self.usable_channel(true)
+            .await?
             .queue_bind(queue, exchange, &routing_key.to_string(), options, args)
             .await?;
 
         Ok(())
     }
 
+    /// Publishes `payload`, carrying the current [`RequestContext`] in the message headers
     pub async fn publish<E, R>(
         &mut self,
         exchange: E,
@@ -207,22 +297,174 @@ impl RabbitMQ {
         E: ToString,
         R: ToString,
     {
-        let exchange = exchange.to_string();
+        self.publish_with_headers(exchange, routing_key, payload, FieldTable::default())
+            .await
+    }
 
-        self.ensure_channel_is_usable(true).await?;
+    /// Publishes `payload` to be delivered to `exchange` once `delay` has passed,
+    /// as configured by [`delay_strategy`](Self::delay_strategy)
+    pub async fn publish_delayed<E, R>(
+        &mut self,
+        exchange: E,
+        routing_key: R,
+        payload: &[u8],
+        delay: Duration,
+    ) -> AppResult<()>
+    where
+        E: ToString,
+        R: ToString,
+    {
+        let (exchange, routing_key) = (exchange.to_string(), routing_key.to_string());
 
-        self.publish_channel
-            .basic_publish(
-                &exchange,
-                &routing_key.to_string(),
-                self.default_publish_options,
-                payload,
-                self.default_publish_props.clone(),
-            )
-            .await
-            .inspect_err(|e| error!("Failed to publish message: {e:?}"))?;
+        match self.delay_strategy {
+            DelayStrategy::TtlQueues => {
+                let queue = delay::delay_queue(&exchange, &routing_key, delay);
+                let queue_name = queue.name().to_string();
 
-        Ok(())
+                // Re-declared on every publish, so it exists even after the broker expired it
+                let channel = self.usable_channel(true).await?;
+                Topology::new().queue(queue).declare(&channel).await?;
+
+                self.publish_with_headers("", queue_name, payload, FieldTable::default())
+                    .await
+            }
+            DelayStrategy::Plugin => {
+                let millis = i64::try_from(delay.as_millis()).unwrap_or(i64::MAX);
+                let mut headers = FieldTable::default();
+                headers.insert("x-delay".into(), AMQPValue::LongLongInt(millis));
+
+                self.publish_with_headers(exchange, routing_key, payload, headers)
+                    .await
+            }
+        }
+    }
+
+    /// Publishes `messages` on a dedicated channel in confirm mode, pipelining them
+    /// with up to [`confirm_window`](Self::confirm_window) unconfirmed at once.
+    ///
+    /// Returns the outcome of each message, in order, once all are confirmed or failed.
+    /// Errors only if the channel can't be opened.
+    pub async fn publish_batch<I>(&mut self, messages: I) -> AppResult<Vec<AppResult<()>>>
+    where
+        I: IntoIterator<Item = BatchMessage>,
+    {
+        observe("rabbitmq", "publish_batch", async {
+            let connection = self.conn_pool.get().await?;
+            let channel = connection.create_channel().await?;
+            channel
+                .confirm_select(ConfirmSelectOptions::default())
+                .await?;
+
+            let props = self.publish_props(FieldTable::default());
+
+            let mut results = Vec::new();
+            let mut pending = VecDeque::new();
+            for (index, message) in messages.into_iter().enumerate() {
+                if pending.len() >= self.confirm_window
+                    && let Some((pending_index, confirm)) = pending.pop_front()
+                {
+                    results[pending_index] = confirm_outcome(confirm).await;
+                }
+
+                let mut message = OutgoingMessage {
+                    exchange: message.exchange,
+                    routing_key: message.routing_key,
+                    payload: message.payload,
+                    properties: props.clone(),
+                };
+
+                let published =
+                    match middleware::apply_publish(&self.middlewares, &mut message).await {
+                        Ok(()) => channel
+                            .basic_publish(
+                                &message.exchange,
+                                &message.routing_key,
+                                self.default_publish_options,
+                                &message.payload,
+                                message.properties,
+                            )
+                            .await
+                            .map_err(Into::into),
+                        Err(err) => Err(err),
+                    };
+
+                match published {
+                    Ok(confirm) => {
+                        results.push(Ok(()));
+                        pending.push_back((index, confirm));
+                    }
+                    Err(err) => {
+                        error!("Failed to publish message {index} of batch: {err:?}");
+                        results.push(Err(err));
+                    }
+                }
+            }
+
+            while let Some((index, confirm)) = pending.pop_front() {
+                results[index] = confirm_outcome(confirm).await;
+            }
+
+            if let Err(err) = channel.close(200, "Batch published").await {
+                warn!("Failed to close batch channel: {err}");
+            }
+
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Default publish properties, carrying the current [`RequestContext`] and `extra_headers`
+    fn publish_props(&self, extra_headers: FieldTable) -> BasicProperties {
+        let props = self.default_publish_props.clone();
+        let mut headers = props.headers().clone().unwrap_or_default();
+        if let Some(context) = RequestContext::current() {
+            context.child().inject_amqp_headers(&mut headers);
+        }
+        for (key, value) in extra_headers.inner() {
+            headers.insert(key.clone(), value.clone());
+        }
+
+        match headers.inner().is_empty() {
+            true => props,
+            false => props.with_headers(headers),
+        }
+    }
+
+    async fn publish_with_headers<E, R>(
+        &mut self,
+        exchange: E,
+        routing_key: R,
+        payload: &[u8],
+        extra_headers: FieldTable,
+    ) -> AppResult<()>
+    where
+        E: ToString,
+        R: ToString,
+    {
+        let mut message = OutgoingMessage {
+            exchange: exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            payload: payload.to_vec(),
+            properties: self.publish_props(extra_headers),
+        };
+        observe("rabbitmq", "publish", async {
+            middleware::apply_publish(&self.middlewares, &mut message).await?;
+
+            self.usable_channel(true)
+                .await?
+                .basic_publish(
+                    &message.exchange,
+                    &message.routing_key,
+                    self.default_publish_options,
+                    &message.payload,
+                    message.properties,
+                )
+                .await
+                .inspect_err(|e| error!("Failed to publish message: {e:?}"))?;
+
+            Ok(())
+        })
+        .await
     }
 
     pub async fn consume<F, Fut>(&mut self, queue: &str, tag: &str, func: F) -> AppResult<()>
@@ -272,10 +514,9 @@ impl RabbitMQ {
         F: Fn(Message) -> Fut + Send + Copy + 'static,
         Fut: Future<Output = AppResult<()>> + Send + 'static,
     {
-        self.ensure_channel_is_usable(false).await?;
-
         let mut consumer = self
-            .consume_channel
+            .usable_channel(false)
+            .await?
             .basic_consume(
                 queue,
                 tag,
@@ -290,9 +531,19 @@ impl RabbitMQ {
                 let mut instance = instance.clone();
                 let consumer_tag = tag.to_owned();
 
-                let handler = async move {
-                    let delivery_tag = delivery.delivery_tag;
-                    match func(Message::new(delivery)).await {
+                let mut message = Message::new(delivery);
+                let context = message.context();
+
+                let handler = context.scope(async move {
+                    let delivery_tag = message.delivery().delivery_tag;
+                    let middlewares = &instance.middlewares;
+                    let result = observe("rabbitmq", "consume", async move {
+                        middleware::apply_consume(middlewares, &mut message).await?;
+                        func(message).await
+                    })
+                    .await;
+
+                    match result {
                         Ok(_) => {}
                         Err(err) => {
                             if instance.nack_on_failure {
@@ -303,7 +554,7 @@ impl RabbitMQ {
                             error!("[consume-executor][{consumer_tag}] Returned error: {err:?}");
                         }
                     }
-                };
+                });
 
                 if self.execute_handler_asynchronously {
                     Handle::current().spawn(handler);
@@ -359,9 +610,8 @@ impl RabbitMQ {
     }
 
     pub async fn ack(&mut self, delivery_tag: u64) -> AppResult<()> {
-        self.ensure_channel_is_usable(false).await?;
-
-        self.consume_channel
+        self.usable_channel(false)
+            .await?
             .basic_ack(delivery_tag, BasicAckOptions::default())
             .await?;
 
@@ -369,9 +619,8 @@ impl RabbitMQ {
     }
 
     pub async fn nack(&mut self, delivery_tag: u64, requeue: bool) -> AppResult<()> {
-        self.ensure_channel_is_usable(false).await?;
-
-        self.consume_channel
+        self.usable_channel(false)
+            .await?
             .basic_nack(
                 delivery_tag,
                 BasicNackOptions {
@@ -401,8 +650,13 @@ impl RabbitMQ {
     }
 
     pub async fn close_channels(&self, reply_code: ReplyCode, reply_text: &str) -> AppResult<()> {
-        self.publish_channel.close(reply_code, reply_text).await?;
-        self.consume_channel.close(reply_code, reply_text).await?;
+        for channel in [&self.publish_channel, &self.consume_channel]
+            .into_iter()
+            .flatten()
+        {
+            channel.close(reply_code, reply_text).await?;
+        }
+
         Ok(())
     }
 
@@ -411,13 +665,9 @@ impl RabbitMQ {
         self.setup_fn.is_some()
     }
 
-    async fn ensure_channel_is_usable(&mut self, is_publish_channel: bool) -> AppResult<()> {
+    /// Returns the publish or consume channel, opening or recreating it when needed
+    async fn usable_channel(&mut self, is_publish_channel: bool) -> AppResult<Channel> {
         loop {
-            let channel = match is_publish_channel {
-                true => &self.publish_channel,
-                false => &self.consume_channel,
-            };
-
             // Check if the connection is still valid before checking the channel
             let connection = self.conn_pool.get().await;
             if connection.is_err() {
@@ -426,6 +676,17 @@ impl RabbitMQ {
                 continue;
             }
 
+            let channel = match is_publish_channel {
+                true => &self.publish_channel,
+                false => &self.consume_channel,
+            };
+
+            let Some(channel) = channel else {
+                info!("Opening channel...");
+                self.recreate_channel(is_publish_channel).await?;
+                continue;
+            };
+
             let state = channel.status().state();
             match state {
                 ChannelState::Closed | ChannelState::Closing | ChannelState::Error => {
@@ -435,11 +696,9 @@ impl RabbitMQ {
                     );
                     self.recreate_channel(is_publish_channel).await?;
                 }
-                _ => break,
+                _ => return Ok(channel.clone()),
             }
         }
-
-        Ok(())
     }
 
     /// Calls the user-defined `setup_fn`
@@ -489,18 +748,23 @@ impl RabbitMQ {
         }
 
         let channel = match is_publish_channel {
-            true => {
-                self.publish_channel = connection.create_channel().await?;
-                &self.publish_channel
-            }
+            true => self
+                .publish_channel
+                .insert(connection.create_channel().await?),
             false => {
-                self.consume_channel = connection.create_channel().await?;
-                &self.consume_channel
+                let channel = connection.create_channel().await?;
+                Self::apply_prefetch(&channel, self.prefetch).await?;
+                self.consume_channel.insert(channel)
             }
         };
 
         info!("Channel({}) recreation completed", channel.id());
 
+        if let Some(topology) = &self.topology {
+            info!("Re-declaring topology...");
+            topology.declare(channel).await?;
+        }
+
         // Run the user-provided setup function
         self.setup().await?;
 
@@ -541,3 +805,7 @@ impl RabbitMQ {
         .into())
     }
 }
+
+async fn confirm_outcome(confirm: PublisherConfirm) -> AppResult<()> {
+    batch::confirmation_result(confirm.await?)
+}
@@ -0,0 +1,335 @@
+//! Declarative description of the exchanges, queues and bindings a service relies on.
+//!
+//! A [`Topology`] is declared once through [`RabbitMQ::declare_topology`](super::RabbitMQ::declare_topology)
+//! and declared again every time a channel is recreated, so it survives broker restarts
+//! without `exchange_declare`/`queue_declare` calls scattered around the code base.
+//!
+//! # Example
+//!
+//! ```rust
+//! use foxtive::rabbitmq::topology::{Exchange, Queue, Topology};
+//! use foxtive::rabbitmq::ExchangeKind;
+//! use std::time::Duration;
+//!
+//! let topology = Topology::new()
+//!     .exchange(Exchange::new("orders", ExchangeKind::Topic))
+//!     .exchange(Exchange::new("orders.dlx", ExchangeKind::Fanout))
+//!     .queue(
+//!         Queue::new("orders.created")
+//!             .dead_letter_exchange("orders.dlx")
+//!             .message_ttl(Duration::from_secs(60)),
+//!     )
+//!     .queue(Queue::new("orders.dead"))
+//!     .bind("orders.created", "orders", "order.created")
+//!     .bind("orders.dead", "orders.dlx", "");
+//! ```
+
+use crate::prelude::AppResult;
+use lapin::options::{ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions};
+use lapin::types::{AMQPValue, FieldTable, LongString};
+use lapin::{Channel, ExchangeKind};
+use std::time::Duration;
+use tracing::debug;
+
+/// Exchanges, queues and bindings, declared in that order
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    exchanges: Vec<Exchange>,
+    queues: Vec<Queue>,
+    bindings: Vec<Binding>,
+}
+
+/// An exchange to declare, durable by default
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    name: String,
+    kind: ExchangeKind,
+    options: ExchangeDeclareOptions,
+    arguments: FieldTable,
+}
+
+/// A queue to declare, durable by default
+#[derive(Debug, Clone)]
+pub struct Queue {
+    name: String,
+    options: QueueDeclareOptions,
+    arguments: FieldTable,
+}
+
+/// A binding of a queue to an exchange
+#[derive(Debug, Clone)]
+pub struct Binding {
+    queue: String,
+    exchange: String,
+    routing_key: String,
+    arguments: FieldTable,
+}
+
+impl Topology {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exchange(mut self, exchange: Exchange) -> Self {
+        self.exchanges.push(exchange);
+        self
+    }
+
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queues.push(queue);
+        self
+    }
+
+    /// Binds `queue` to `exchange` with `routing_key`
+    pub fn bind(self, queue: &str, exchange: &str, routing_key: &str) -> Self {
+        self.binding(Binding::new(queue, exchange, routing_key))
+    }
+
+    pub fn binding(mut self, binding: Binding) -> Self {
+        self.bindings.push(binding);
+        self
+    }
+
+    pub fn exchanges(&self) -> &[Exchange] {
+        &self.exchanges
+    }
+
+    pub fn queues(&self) -> &[Queue] {
+        &self.queues
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// Declares everything on `channel`.
+    ///
+    /// Declarations are idempotent as long as they match what already exists on the broker,
+    /// so this is safe to run on every (re)connection.
+    pub async fn declare(&self, channel: &Channel) -> AppResult<()> {
+        for exchange in &self.exchanges {
+            debug!("Declaring exchange '{}'", exchange.name);
+            channel
+                .exchange_declare(
+                    &exchange.name,
+                    exchange.kind.clone(),
+                    exchange.options,
+                    exchange.arguments.clone(),
+                )
+                .await?;
+        }
+
+        for queue in &self.queues {
+            debug!("Declaring queue '{}'", queue.name);
+            channel
+                .queue_declare(&queue.name, queue.options, queue.arguments.clone())
+                .await?;
+        }
+
+        for binding in &self.bindings {
+            debug!(
+                "Binding queue '{}' to exchange '{}' with '{}'",
+                binding.queue, binding.exchange, binding.routing_key
+            );
+            channel
+                .queue_bind(
+                    &binding.queue,
+                    &binding.exchange,
+                    &binding.routing_key,
+                    QueueBindOptions::default(),
+                    binding.arguments.clone(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Exchange {
+    pub fn new(name: &str, kind: ExchangeKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            options: ExchangeDeclareOptions {
+                durable: true,
+                ..ExchangeDeclareOptions::default()
+            },
+            arguments: FieldTable::default(),
+        }
+    }
+
+    pub fn options(mut self, options: ExchangeDeclareOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the exchange receiving messages this exchange can't route
+    pub fn alternate_exchange(self, exchange: &str) -> Self {
+        self.argument("alternate-exchange", long_string(exchange))
+    }
+
+    pub fn argument(mut self, key: &str, value: AMQPValue) -> Self {
+        self.arguments.insert(key.into(), value);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arguments(&self) -> &FieldTable {
+        &self.arguments
+    }
+}
+
+impl Queue {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            options: QueueDeclareOptions {
+                durable: true,
+                ..QueueDeclareOptions::default()
+            },
+            arguments: FieldTable::default(),
+        }
+    }
+
+    pub fn options(mut self, options: QueueDeclareOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the exchange rejected and expired messages are republished to
+    pub fn dead_letter_exchange(self, exchange: &str) -> Self {
+        self.argument("x-dead-letter-exchange", long_string(exchange))
+    }
+
+    /// Sets the routing key dead-lettered messages are republished with,
+    /// their original routing key is kept otherwise
+    pub fn dead_letter_routing_key(self, routing_key: &str) -> Self {
+        self.argument("x-dead-letter-routing-key", long_string(routing_key))
+    }
+
+    /// Sets how long a message may stay in the queue
+    pub fn message_ttl(self, ttl: Duration) -> Self {
+        self.argument("x-message-ttl", millis(ttl))
+    }
+
+    /// Sets how long the queue may stay unused before it's deleted
+    pub fn expires(self, after: Duration) -> Self {
+        self.argument("x-expires", millis(after))
+    }
+
+    /// Sets the maximum number of ready messages
+    pub fn max_length(self, max_length: u32) -> Self {
+        self.argument("x-max-length", AMQPValue::LongLongInt(max_length.into()))
+    }
+
+    pub fn argument(mut self, key: &str, value: AMQPValue) -> Self {
+        self.arguments.insert(key.into(), value);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arguments(&self) -> &FieldTable {
+        &self.arguments
+    }
+}
+
+impl Binding {
+    pub fn new(queue: &str, exchange: &str, routing_key: &str) -> Self {
+        Self {
+            queue: queue.to_string(),
+            exchange: exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            arguments: FieldTable::default(),
+        }
+    }
+
+    /// Adds a binding argument, e.g. the headers matched by a headers exchange
+    pub fn argument(mut self, key: &str, value: AMQPValue) -> Self {
+        self.arguments.insert(key.into(), value);
+        self
+    }
+}
+
+fn long_string(value: &str) -> AMQPValue {
+    AMQPValue::LongString(LongString::from(value))
+}
+
+fn millis(duration: Duration) -> AMQPValue {
+    AMQPValue::LongLongInt(i64::try_from(duration.as_millis()).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arg<'a>(table: &'a FieldTable, key: &str) -> Option<&'a AMQPValue> {
+        table.inner().get(key)
+    }
+
+    #[test]
+    fn test_queue_arguments() {
+        let queue = Queue::new("orders")
+            .dead_letter_exchange("orders.dlx")
+            .dead_letter_routing_key("dead")
+            .message_ttl(Duration::from_secs(30))
+            .expires(Duration::from_secs(3600))
+            .max_length(1000);
+
+        let args = queue.arguments();
+        assert_eq!(
+            arg(args, "x-dead-letter-exchange"),
+            Some(&long_string("orders.dlx"))
+        );
+        assert_eq!(
+            arg(args, "x-dead-letter-routing-key"),
+            Some(&long_string("dead"))
+        );
+        assert_eq!(
+            arg(args, "x-message-ttl"),
+            Some(&AMQPValue::LongLongInt(30_000))
+        );
+        assert_eq!(
+            arg(args, "x-expires"),
+            Some(&AMQPValue::LongLongInt(3_600_000))
+        );
+        assert_eq!(
+            arg(args, "x-max-length"),
+            Some(&AMQPValue::LongLongInt(1000))
+        );
+    }
+
+    #[test]
+    fn test_declarations_are_durable_by_default() {
+        assert!(Queue::new("orders").options.durable);
+        assert!(Exchange::new("orders", ExchangeKind::Topic).options.durable);
+    }
+
+    #[test]
+    fn test_topology_keeps_declaration_order() {
+        let topology = Topology::new()
+            .exchange(Exchange::new("a", ExchangeKind::Direct))
+            .exchange(Exchange::new("b", ExchangeKind::Fanout).alternate_exchange("a"))
+            .queue(Queue::new("q1"))
+            .queue(Queue::new("q2"))
+            .bind("q1", "a", "key")
+            .binding(Binding::new("q2", "b", ""));
+
+        let exchanges: Vec<_> = topology.exchanges().iter().map(Exchange::name).collect();
+        let queues: Vec<_> = topology.queues().iter().map(Queue::name).collect();
+        assert_eq!(exchanges, ["a", "b"]);
+        assert_eq!(queues, ["q1", "q2"]);
+        assert_eq!(topology.bindings().len(), 2);
+        assert_eq!(topology.bindings()[0].routing_key, "key");
+        assert_eq!(
+            arg(topology.exchanges()[1].arguments(), "alternate-exchange"),
+            Some(&long_string("a"))
+        );
+    }
+}
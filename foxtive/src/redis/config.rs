@@ -1,9 +1,50 @@
 pub use deadpool::managed::QueueMode;
 pub use deadpool_redis::{PoolConfig, Timeouts};
 
+use crate::setup::ConnectPolicy;
+use std::time::Duration;
+
 pub struct RedisConfig {
     pub(crate) dsn: String,
     pub(crate) pool_config: PoolConfig,
+    pub(crate) connect_policy: ConnectPolicy,
+    pub(crate) key_prefix: KeyPrefix,
+    pub(crate) tls: Option<TlsVerification>,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+    pub(crate) database: Option<i64>,
+    pub(crate) connection_timeout: Option<Duration>,
+    pub(crate) response_timeout: Option<Duration>,
+}
+
+/// How the certificate of a Redis server reached over TLS is verified
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TlsVerification {
+    /// The certificate must be trusted and match the host name
+    #[default]
+    Full,
+    /// The certificate must be trusted, whatever host name it is for
+    AcceptInvalidHostnames,
+    /// Any certificate is accepted, for development servers with self-signed certificates
+    None,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) enum KeyPrefix {
+    #[default]
+    None,
+    AppCode,
+    Custom(String),
+}
+
+impl KeyPrefix {
+    pub(crate) fn resolve<'a>(&'a self, app_code: &'a str) -> &'a str {
+        match self {
+            KeyPrefix::None => "",
+            KeyPrefix::AppCode => app_code,
+            KeyPrefix::Custom(prefix) => prefix,
+        }
+    }
 }
 
 impl RedisConfig {
@@ -11,6 +52,14 @@ impl RedisConfig {
         Self {
             dsn: dsn.to_string(),
             pool_config: PoolConfig::default(),
+            connect_policy: ConnectPolicy::Required,
+            key_prefix: KeyPrefix::None,
+            tls: None,
+            username: None,
+            password: None,
+            database: None,
+            connection_timeout: None,
+            response_timeout: None,
         }
     }
 
@@ -18,4 +67,89 @@ impl RedisConfig {
         self.pool_config = pool_config;
         self
     }
+
+    /// Sets how Redis is connected to while the state is created,
+    /// defaults to [`ConnectPolicy::Required`]
+    pub fn connect_policy(mut self, connect_policy: ConnectPolicy) -> Self {
+        self.connect_policy = connect_policy;
+        self
+    }
+    /// Prefixes the keys with the app code, e.g. `shop:user:1`
+    pub fn namespaced(mut self) -> Self {
+        self.key_prefix = KeyPrefix::AppCode;
+        self
+    }
+
+    /// Prefixes the keys with `prefix:` instead of the app code, so apps sharing a Redis
+    /// server don't collide
+    pub fn key_prefix(mut self, prefix: &str) -> Self {
+        self.key_prefix = KeyPrefix::Custom(prefix.to_string());
+        self
+    }
+
+    /// Uses keys as given, the default, for keys shared with other apps or written without a prefix
+    pub fn unprefixed(mut self) -> Self {
+        self.key_prefix = KeyPrefix::None;
+        self
+    }
+
+    /// Connects over TLS, as a `rediss://` DSN does, verifying the server certificate
+    /// as set by `verification`
+    pub fn tls(mut self, verification: TlsVerification) -> Self {
+        self.tls = Some(verification);
+        self
+    }
+
+    /// Authenticates as an ACL user, overriding the credentials of the DSN
+    pub fn credentials(mut self, username: &str, password: &str) -> Self {
+        self.username = Some(username.to_string());
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Authenticates with the `requirepass` password, overriding the one of the DSN
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Selects the database with this index, overriding the one of the DSN
+    pub fn database(mut self, index: i64) -> Self {
+        self.database = Some(index);
+        self
+    }
+
+    /// Fails connecting after `timeout`, instead of waiting for the OS to give up
+    pub fn connection_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_timeout = Some(timeout);
+        self
+    }
+
+    /// Fails commands not answered within `timeout`.
+    ///
+    /// Blocking commands sent by [`Redis::blpop`](crate::redis::Redis::blpop),
+    /// [`Redis::brpop`](crate::redis::Redis::brpop) and queue workers wait for their own
+    /// timeout instead, so a worker's `poll_timeout` may exceed this one.
+    pub fn response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = Some(timeout);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_unprefixed_by_default() {
+        let config = RedisConfig::create("redis://localhost");
+        assert_eq!(config.key_prefix.resolve("shop"), "");
+
+        let config = config.namespaced();
+        assert_eq!(config.key_prefix.resolve("shop"), "shop");
+
+        let config = config.key_prefix("billing");
+        assert_eq!(config.key_prefix.resolve("shop"), "billing");
+        assert_eq!(config.unprefixed().key_prefix.resolve("shop"), "");
+    }
 }
@@ -1,18 +1,132 @@
-use crate::redis::config::RedisConfig;
+use crate::redis::config::{RedisConfig, TlsVerification};
 use crate::results::AppResult;
 use anyhow::Error;
-use deadpool_redis::{Manager, Pool};
-use redis::Client;
+use deadpool::managed::Hook;
+use deadpool_redis::{Manager, Pool, Runtime};
+use redis::aio::MultiplexedConnection;
+use redis::{Client, ConnectionAddr, ConnectionInfo, IntoConnectionInfo};
 
 pub fn create_redis_connection(dsn: &str) -> AppResult<Client> {
     Client::open(dsn).map_err(Error::msg)
 }
 
 pub fn create_redis_conn_pool(config: RedisConfig) -> AppResult<Pool> {
-    let manager = Manager::new(config.dsn)?;
+    let manager = Manager::new(connection_info(&config)?)?;
 
-    Pool::builder(manager)
-        .config(config.pool_config)
-        .build()
-        .map_err(Error::msg)
+    let mut pool_config = config.pool_config;
+    if let Some(timeout) = config.connection_timeout {
+        pool_config.timeouts.create = Some(timeout);
+    }
+
+    let mut builder = Pool::builder(manager)
+        .config(pool_config)
+        .runtime(Runtime::Tokio1);
+
+    if let Some(timeout) = config.response_timeout {
+        builder = builder.post_create(Hook::sync_fn(move |conn: &mut MultiplexedConnection, _| {
+            conn.set_response_timeout(timeout);
+            Ok(())
+        }));
+    }
+
+    builder.build().map_err(Error::msg)
+}
+
+/// The DSN with the TLS, credentials and database settings of the config applied
+pub(crate) fn connection_info(config: &RedisConfig) -> AppResult<ConnectionInfo> {
+    let mut info = config.dsn.as_str().into_connection_info()?;
+
+    if let Some(verification) = config.tls {
+        let mut addr = match info.addr().clone() {
+            ConnectionAddr::Tcp(host, port) | ConnectionAddr::TcpTls { host, port, .. } => {
+                ConnectionAddr::TcpTls {
+                    host,
+                    port,
+                    insecure: verification == TlsVerification::None,
+                    tls_params: None,
+                }
+            }
+            _ => return Err(Error::msg("TLS can only be used with a Redis TCP address")),
+        };
+
+        if verification == TlsVerification::AcceptInvalidHostnames {
+            addr.set_danger_accept_invalid_hostnames(true);
+        }
+
+        info = info.set_addr(addr);
+    }
+
+    let mut settings = info.redis_settings().clone();
+    if let Some(username) = &config.username {
+        settings = settings.set_username(username);
+    }
+    if let Some(password) = &config.password {
+        settings = settings.set_password(password);
+    }
+    if let Some(database) = config.database {
+        settings = settings.set_db(database);
+    }
+
+    Ok(info.set_redis_settings(settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_connection_info_applies_tls_and_auth() {
+        let config = RedisConfig::create("redis://cache.internal:6380/1")
+            .tls(TlsVerification::None)
+            .credentials("app", "s3cret")
+            .database(4);
+
+        let info = connection_info(&config).unwrap();
+        assert!(matches!(
+            info.addr(),
+            ConnectionAddr::TcpTls { host, port: 6380, insecure: true, .. } if host == "cache.internal"
+        ));
+        assert_eq!(info.redis_settings().username(), Some("app"));
+        assert_eq!(info.redis_settings().password(), Some("s3cret"));
+        assert_eq!(info.redis_settings().db(), 4);
+    }
+
+    #[test]
+    fn test_dsn_settings_are_kept_by_default() {
+        let config = RedisConfig::create("redis://:pass@localhost/2");
+
+        let info = connection_info(&config).unwrap();
+        assert!(matches!(info.addr(), ConnectionAddr::Tcp(_, 6379)));
+        assert_eq!(info.redis_settings().password(), Some("pass"));
+        assert_eq!(info.redis_settings().db(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_is_created_with_timeouts() {
+        let config = RedisConfig::create("redis://localhost")
+            .connection_timeout(Duration::from_secs(1))
+            .response_timeout(Duration::from_secs(1));
+
+        let pool = create_redis_conn_pool(config).unwrap();
+        assert_eq!(pool.timeouts().create, Some(Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_blocking_commands_outlive_the_response_timeout() {
+        let config = RedisConfig::create("redis://127.0.0.1:6379")
+            .connection_timeout(Duration::from_secs(1))
+            .response_timeout(Duration::from_millis(200));
+        let redis = crate::redis::Redis::new(create_redis_conn_pool(config).unwrap());
+        if let Err(e) = redis.redis().await {
+            eprintln!("Skipping Redis test - no connection available: {e}");
+            return;
+        }
+
+        let popped: Option<(String, String)> = redis
+            .blpop("foxtive:test:blocking:empty", 0.5)
+            .await
+            .unwrap();
+        assert_eq!(popped, None);
+    }
 }
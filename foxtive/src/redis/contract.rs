@@ -0,0 +1,167 @@
+use crate::instrument::observe;
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use crate::results::redis_result::RedisResultToAppResult;
+use redis::AsyncCommands;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// The Redis operations services commonly depend on, implemented by [`Redis`] and,
+/// with the `test-utils` feature, by [`MockRedis`](crate::test_utils::MockRedis).
+///
+/// Values are stored as given, services depending on `Arc<dyn RedisContract>` instead of
+/// [`Redis`] can be unit tested without a Redis server.
+///
+/// # Example
+///
+/// ```no_run
+/// use foxtive::redis::RedisContract;
+/// use foxtive::prelude::AppResult;
+/// use std::sync::Arc;
+///
+/// struct Sessions {
+///     redis: Arc<dyn RedisContract>,
+/// }
+///
+/// impl Sessions {
+///     async fn user_id(&self, token: &str) -> AppResult<Option<u64>> {
+///         self.redis.get_json(&format!("session:{token}")).await
+///     }
+/// }
+///
+/// # fn sessions(redis: Arc<foxtive::redis::Redis>) -> Sessions {
+/// // `FOXTIVE.redis()` in production
+/// Sessions { redis }
+/// # }
+/// ```
+#[async_trait::async_trait]
+pub trait RedisContract: Send + Sync {
+    /// Gets the value stored under the key, `None` if the key doesn't exist
+    async fn get_raw(&self, key: &str) -> AppResult<Option<String>>;
+
+    /// Stores the value, expiring after `ttl` if given, millisecond precision
+    async fn set_raw(&self, key: &str, value: &str, ttl: Option<Duration>) -> AppResult<()>;
+
+    /// Stores the value only if the key doesn't exist, returns whether it was set
+    async fn set_nx_raw(&self, key: &str, value: &str) -> AppResult<bool>;
+
+    /// Returns the number of deleted keys
+    async fn delete(&self, key: &str) -> AppResult<i32>;
+
+    async fn exists(&self, key: &str) -> AppResult<bool>;
+
+    /// Returns the keys matching a glob pattern
+    async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>>;
+
+    /// Returns the number of deleted keys
+    async fn delete_by_pattern(&self, pattern: &str) -> AppResult<u32>;
+
+    /// Returns the number of subscribers that received the message
+    async fn publish_raw(&self, channel: &str, message: &str) -> AppResult<i32>;
+
+    /// Appends the value to a list, returns the length of the list
+    async fn rpush_raw(&self, key: &str, value: &str) -> AppResult<i32>;
+
+    /// Removes and returns the first value of a list, `None` if it's empty
+    async fn lpop_raw(&self, key: &str) -> AppResult<Option<String>>;
+}
+
+impl dyn RedisContract + '_ {
+    /// Stores a value serialized to JSON, readable with [`Redis::get_json`]
+    pub async fn set_json<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> AppResult<()> {
+        self.set_raw(key, &serde_json::to_string(value)?, ttl).await
+    }
+
+    /// Gets a value stored as JSON, `None` if the key doesn't exist
+    pub async fn get_json<T: DeserializeOwned>(&self, key: &str) -> AppResult<Option<T>> {
+        match self.get_raw(key).await? {
+            Some(content) => Ok(Some(serde_json::from_str(&content)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Publishes a value serialized to JSON, as [`Redis::publish`] does
+    pub async fn publish_json<T: Serialize + ?Sized>(
+        &self,
+        channel: &str,
+        data: &T,
+    ) -> AppResult<i32> {
+        self.publish_raw(channel, &serde_json::to_string(data)?)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl RedisContract for Redis {
+    async fn get_raw(&self, key: &str) -> AppResult<Option<String>> {
+        self.get(key).await
+    }
+
+    async fn set_raw(&self, key: &str, value: &str, ttl: Option<Duration>) -> AppResult<()> {
+        observe("redis", "set_raw", async {
+            let mut conn = self.redis().await?;
+            match ttl {
+                Some(ttl) => {
+                    // a zero expiry is rejected by Redis
+                    let millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+                    conn.pset_ex(self.key(key).as_ref(), value, millis)
+                        .await
+                        .into_app_result()
+                }
+                None => conn
+                    .set(self.key(key).as_ref(), value)
+                    .await
+                    .into_app_result(),
+            }
+        })
+        .await
+    }
+
+    async fn set_nx_raw(&self, key: &str, value: &str) -> AppResult<bool> {
+        self.set_nx(key, &value).await
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<i32> {
+        Redis::delete(self, key).await
+    }
+
+    async fn exists(&self, key: &str) -> AppResult<bool> {
+        Redis::exists(self, key).await
+    }
+
+    async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>> {
+        Redis::keys_by_pattern(self, pattern).await
+    }
+
+    async fn delete_by_pattern(&self, pattern: &str) -> AppResult<u32> {
+        Redis::delete_by_pattern(self, pattern).await
+    }
+
+    async fn publish_raw(&self, channel: &str, message: &str) -> AppResult<i32> {
+        observe("redis", "publish_raw", async {
+            let mut conn = self.redis().await?;
+            conn.publish(channel, message).await.into_app_result()
+        })
+        .await
+    }
+
+    async fn rpush_raw(&self, key: &str, value: &str) -> AppResult<i32> {
+        observe("redis", "rpush_raw", async {
+            let mut conn = self.redis().await?;
+            conn.rpush(self.key(key).as_ref(), value)
+                .await
+                .into_app_result()
+        })
+        .await
+    }
+
+    async fn lpop_raw(&self, key: &str) -> AppResult<Option<String>> {
+        self.lpop(key, None).await
+    }
+}
@@ -1,11 +1,47 @@
+//! # Redis Module
+//!
+//! A pooled Redis client, [`Redis`], reachable through the global state.
+//!
+//! ## Serialization
+//!
+//! Methods differ in how values are written, which matters when a value written by one
+//! is read by another:
+//!
+//! - [`Redis::set`], [`Redis::get`] and [`Redis::queue`] use the redis crate conversions,
+//!   `ToRedisArgs`/`FromRedisValue`, a `String` is stored as is
+//! - [`Redis::set_json`], [`Redis::get_json`], [`Redis::publish`], [`Redis::rpush`],
+//!   [`Redis::sadd`], [`Redis::zadd`] and [`Redis::lrem`] serialize to JSON, a `String`
+//!   is stored quoted
+//! - The pop and range methods return what is stored, JSON included, as a `FromRedisValue`
+//!
+//! Use the JSON methods for structured values and the raw ones for counters, flags
+//! and values shared with other tools.
+//!
+//! ## Key Prefix
+//!
+//! Keys are used as given by default. [`RedisConfig::namespaced`] prefixes them with the app
+//! code, so apps sharing a Redis server don't collide, and [`RedisConfig::key_prefix`] sets
+//! another prefix. Methods taking keys or patterns apply the prefix transparently and
+//! [`Redis::keys`] returns keys without it, so
+//! [`RedisCacheDriver`](crate::cache::drivers::RedisCacheDriver) and queues are namespaced too.
+//! Pub/sub channels, [`Redis::flush_db`] and [`Redis::flush_all`] are not, and
+//! [`Redis::raw`] gives a client using keys as given.
+//!
+//! [`RedisConfig::namespaced`]: config::RedisConfig::namespaced
+//! [`RedisConfig::key_prefix`]: config::RedisConfig::key_prefix
+
 use crate::FOXTIVE;
+use crate::instrument::observe;
 use crate::prelude::{AppResult, AppStateExt};
 use crate::redis::conn::create_redis_connection;
 use crate::results::redis_result::RedisResultToAppResult;
 use anyhow::Error;
 use futures_util::StreamExt;
+use redis::aio::MultiplexedConnection;
 use redis::{AsyncCommands, FromRedisValue, ToRedisArgs, ToSingleRedisArg};
 use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
 use std::future::Future;
 use std::num::{NonZeroU64, NonZeroUsize};
 use std::time::Duration;
@@ -15,45 +51,211 @@ use tracing::{error, info};
 
 pub mod config;
 pub mod conn;
+mod contract;
+
+pub use contract::RedisContract;
+
+const COMPARE_AND_SET: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then \
+     redis.call('SET', KEYS[1], ARGV[2]) return 1 end return 0";
 
 pub struct Redis {
     pool: deadpool_redis::Pool,
+    /// Prepended to every key, with a `:` separator
+    prefix: Option<String>,
 }
 
 impl Redis {
     pub fn new(pool: deadpool_redis::Pool) -> Self {
-        Self { pool }
+        Self { pool, prefix: None }
     }
 
+    /// Prepends `prefix:` to the keys, so apps sharing a Redis server don't collide.
+    /// An empty prefix disables it.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = (!prefix.is_empty()).then(|| format!("{prefix}:"));
+        self
+    }
+
+    /// The key prefix, without its `:` separator
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix
+            .as_deref()
+            .map(|prefix| &prefix[..prefix.len() - 1])
+    }
+
+    /// Client sharing this one's pool, using keys as given, for keys of other apps
+    /// or shared between them
+    pub fn raw(&self) -> Redis {
+        Redis::new(self.pool.clone())
+    }
+
+    /// A pooled connection, commands sent through it use keys as given, without the prefix
     pub async fn redis(&self) -> AppResult<deadpool_redis::Connection> {
         self.pool.get().await.map_err(Error::msg)
     }
 
-    /// Push a value to a Redis list
+    /// Push a value to a Redis list, written with `ToRedisArgs`, see [`Redis::rpush`] for JSON
     pub async fn queue<T>(&self, queue: &str, data: &T) -> AppResult<i32>
     where
         T: ToRedisArgs + Send + Sync,
     {
-        let mut conn = self.redis().await?;
-        conn.lpush(queue, data).await.into_app_result()
+        observe("redis", "queue", async {
+            let mut conn = self.redis().await?;
+            conn.lpush(self.key(queue).as_ref(), data)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
+    /// Sets a value written with `ToRedisArgs`, see [`Redis::set_json`] for structured values
     pub async fn set<T>(&self, key: &str, value: &T) -> AppResult<String>
     where
         T: ToSingleRedisArg + Send + Sync,
     {
-        let mut conn = self.redis().await?;
-        conn.set(key, value).await.into_app_result()
+        observe("redis", "set", async {
+            let mut conn = self.redis().await?;
+            conn.set(self.key(key).as_ref(), value)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
+    /// Gets a value read with `FromRedisValue`, see [`Redis::get_json`] for structured values
     pub async fn get<T: FromRedisValue>(&self, key: &str) -> AppResult<T> {
-        let mut conn = self.redis().await?;
-        conn.get(key).await.into_app_result()
+        observe("redis", "get", async {
+            let mut conn = self.redis().await?;
+            conn.get(self.key(key).as_ref()).await.into_app_result()
+        })
+        .await
+    }
+
+    /// Sets a value serialized to JSON, expiring after `ttl` if given, millisecond precision
+    ///
+    /// # Example
+    /// ```no_run
+    /// use foxtive::redis::Redis;
+    /// use serde::{Deserialize, Serialize};
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Session {
+    ///     user_id: u64,
+    /// }
+    ///
+    /// async fn example(redis: &Redis) -> foxtive::results::AppResult<()> {
+    ///     let session = Session { user_id: 7 };
+    ///     redis.set_json("session:abc", &session, Some(Duration::from_secs(3600))).await?;
+    ///
+    ///     let session: Option<Session> = redis.get_json("session:abc").await?;
+    ///     assert_eq!(session.map(|s| s.user_id), Some(7));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn set_json<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> AppResult<()> {
+        observe("redis", "set_json", async {
+            let content = serde_json::to_string(value)?;
+            let mut conn = self.redis().await?;
+
+            match ttl {
+                Some(ttl) => {
+                    // a zero expiry is rejected by Redis
+                    let millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+                    conn.pset_ex(self.key(key).as_ref(), content, millis)
+                        .await
+                        .into_app_result()
+                }
+                None => conn
+                    .set(self.key(key).as_ref(), content)
+                    .await
+                    .into_app_result(),
+            }
+        })
+        .await
+    }
+
+    /// Gets a value written with [`Redis::set_json`], `None` if the key doesn't exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored value isn't the JSON of a `T`
+    pub async fn get_json<T: DeserializeOwned>(&self, key: &str) -> AppResult<Option<T>> {
+        observe("redis", "get_json", async {
+            let mut conn = self.redis().await?;
+            let content: Option<String> =
+                conn.get(self.key(key).as_ref()).await.into_app_result()?;
+
+            match content {
+                Some(content) => Ok(Some(serde_json::from_str(&content)?)),
+                None => Ok(None),
+            }
+        })
+        .await
     }
 
     pub async fn delete(&self, key: &str) -> AppResult<i32> {
-        let mut conn = self.redis().await?;
-        conn.del(key).await.into_app_result()
+        observe("redis", "delete", async {
+            let mut conn = self.redis().await?;
+            conn.del(self.key(key).as_ref()).await.into_app_result()
+        })
+        .await
+    }
+
+    pub async fn exists(&self, key: &str) -> AppResult<bool> {
+        observe("redis", "exists", async {
+            let mut conn = self.redis().await?;
+            conn.exists(self.key(key).as_ref()).await.into_app_result()
+        })
+        .await
+    }
+
+    /// Sets the value only if the key doesn't exist, returns whether it was set
+    pub async fn set_nx<T>(&self, key: &str, value: &T) -> AppResult<bool>
+    where
+        T: ToSingleRedisArg + Send + Sync,
+    {
+        observe("redis", "set_nx", async {
+            let mut conn = self.redis().await?;
+            conn.set_nx(self.key(key).as_ref(), value)
+                .await
+                .into_app_result()
+        })
+        .await
+    }
+
+    /// Sets the value only if the key holds `current`, atomically, returns whether it was set
+    pub async fn compare_and_set<T>(&self, key: &str, current: T, value: T) -> AppResult<bool>
+    where
+        T: ToSingleRedisArg + Send + Sync,
+    {
+        observe("redis", "compare_and_set", async {
+            let mut conn = self.redis().await?;
+            redis::cmd("EVAL")
+                .arg(COMPARE_AND_SET)
+                .arg(1)
+                .arg(self.key(key).as_ref())
+                .arg(current)
+                .arg(value)
+                .query_async(&mut conn)
+                .await
+                .into_app_result()
+        })
+        .await
+    }
+
+    /// Gets the value and deletes the key atomically, requires Redis 6.2+
+    pub async fn get_del<T: FromRedisValue>(&self, key: &str) -> AppResult<T> {
+        observe("redis", "get_del", async {
+            let mut conn = self.redis().await?;
+            conn.get_del(self.key(key).as_ref()).await.into_app_result()
+        })
+        .await
     }
 
     /// Delete Redis keys matching a pattern.
@@ -64,20 +266,27 @@ impl Redis {
     /// # Returns
     /// * `AppResult<u32>` - The number of keys deleted
     pub async fn delete_by_pattern(&self, pattern: &str) -> AppResult<u32> {
-        let mut conn = self.redis().await?;
-        let keys: Vec<String> = conn.keys(pattern).await?;
+        observe("redis", "delete_by_pattern", async {
+            let mut conn = self.redis().await?;
+            let keys: Vec<String> = conn.keys(self.key_pattern(pattern)).await?;
 
-        if keys.is_empty() {
-            return Ok(0);
-        }
+            if keys.is_empty() {
+                return Ok(0);
+            }
 
-        conn.del(keys).await.into_app_result()
+            conn.del(keys).await.into_app_result()
+        })
+        .await
     }
 
+    /// Publishes a value serialized to JSON
     pub async fn publish<T: Serialize>(&self, channel: &str, data: &T) -> AppResult<i32> {
-        let content = serde_json::to_string(data)?;
-        let mut conn = self.redis().await?;
-        conn.publish(channel, content).await.into_app_result()
+        observe("redis", "publish", async {
+            let content = serde_json::to_string(data)?;
+            let mut conn = self.redis().await?;
+            conn.publish(channel, content).await.into_app_result()
+        })
+        .await
     }
 
     pub async fn rpop<V: FromRedisValue>(
@@ -85,15 +294,26 @@ impl Redis {
         key: &str,
         count: Option<NonZeroUsize>,
     ) -> AppResult<V> {
-        let mut conn = self.redis().await?;
-        conn.rpop(key, count).await.into_app_result()
+        observe("redis", "rpop", async {
+            let mut conn = self.redis().await?;
+            conn.rpop(self.key(key).as_ref(), count)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     // Right push (append to a list)
+    /// Push a value serialized to JSON to the end of a Redis list
     pub async fn rpush<T: Serialize>(&self, queue: &str, data: &T) -> AppResult<i32> {
-        let content = serde_json::to_string(data)?;
-        let mut conn = self.redis().await?;
-        conn.rpush(queue, content).await.into_app_result()
+        observe("redis", "rpush", async {
+            let content = serde_json::to_string(data)?;
+            let mut conn = self.redis().await?;
+            conn.rpush(self.key(queue).as_ref(), content)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     // Left pop (remove from the front of a list)
@@ -102,52 +322,92 @@ impl Redis {
         key: &str,
         count: Option<NonZeroUsize>,
     ) -> AppResult<V> {
-        let mut conn = self.redis().await?;
-        conn.lpop(key, count).await.into_app_result()
+        observe("redis", "lpop", async {
+            let mut conn = self.redis().await?;
+            conn.lpop(self.key(key).as_ref(), count)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
-    /// Add a value to a set
+    /// Add a value serialized to JSON to a set
     pub async fn sadd<T: Serialize>(&self, key: &str, value: &T) -> AppResult<i32> {
-        let content = serde_json::to_string(value)?;
-        let mut conn = self.redis().await?;
-        conn.sadd(key, content).await.into_app_result()
+        observe("redis", "sadd", async {
+            let content = serde_json::to_string(value)?;
+            let mut conn = self.redis().await?;
+            conn.sadd(self.key(key).as_ref(), content)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     /// Pop a random element from a set
     pub async fn spop<V: FromRedisValue>(&self, key: &str) -> AppResult<V> {
-        let mut conn = self.redis().await?;
-        conn.spop(key).await.into_app_result()
+        observe("redis", "spop", async {
+            let mut conn = self.redis().await?;
+            conn.spop(self.key(key).as_ref()).await.into_app_result()
+        })
+        .await
     }
 
-    /// Add a value to a sorted set with a score
+    /// Add a value serialized to JSON to a sorted set with a score
     pub async fn zadd<T: Serialize>(&self, key: &str, score: f64, value: &T) -> AppResult<i32> {
-        let content = serde_json::to_string(value)?;
-        let mut conn = self.redis().await?;
-        conn.zadd(key, score, content).await.into_app_result()
+        observe("redis", "zadd", async {
+            let content = serde_json::to_string(value)?;
+            let mut conn = self.redis().await?;
+            conn.zadd(self.key(key).as_ref(), score, content)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     /// Pop the lowest scoring element from a sorted set
     pub async fn zpopmin(&self, key: &str, count: isize) -> AppResult<Option<(String, f64)>> {
-        let mut conn = self.redis().await?;
-        conn.zpopmin(key, count).await.into_app_result()
+        observe("redis", "zpopmin", async {
+            let mut conn = self.redis().await?;
+            conn.zpopmin(self.key(key).as_ref(), count)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     /// Pop the highest scoring element from a sorted set
     pub async fn zpopmax(&self, key: &str, count: isize) -> AppResult<Option<(String, f64)>> {
-        let mut conn = self.redis().await?;
-        conn.zpopmax(key, count).await.into_app_result()
+        observe("redis", "zpopmax", async {
+            let mut conn = self.redis().await?;
+            conn.zpopmax(self.key(key).as_ref(), count)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     /// Blocking left pop (waits if list is empty)
     pub async fn blpop<V: FromRedisValue>(&self, key: &str, timeout: f64) -> AppResult<V> {
-        let mut conn = self.redis().await?;
-        conn.blpop(key, timeout).await.into_app_result()
+        observe("redis", "blpop", async {
+            let conn = self.redis().await?;
+            blocking(&conn, timeout)
+                .blpop(self.key(key).as_ref(), timeout)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     /// Blocking right pop (waits if list is empty)
     pub async fn brpop<V: FromRedisValue>(&self, key: &str, timeout: f64) -> AppResult<V> {
-        let mut conn = self.redis().await?;
-        conn.brpop(key, timeout).await.into_app_result()
+        observe("redis", "brpop", async {
+            let conn = self.redis().await?;
+            blocking(&conn, timeout)
+                .brpop(self.key(key).as_ref(), timeout)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
     /// Retrieve a range of elements from a list
@@ -157,36 +417,53 @@ impl Redis {
         start: isize,
         stop: isize,
     ) -> AppResult<Vec<T>> {
-        let mut conn = self.redis().await?;
-        conn.lrange(key, start, stop).await.into_app_result()
+        observe("redis", "lrange", async {
+            let mut conn = self.redis().await?;
+            conn.lrange(self.key(key).as_ref(), start, stop)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
-    /// Remove elements from a list
+    /// Remove elements equal to the JSON of `value` from a list
     pub async fn lrem<T: Serialize>(&self, key: &str, count: isize, value: &T) -> AppResult<i32> {
-        let content = serde_json::to_string(value)?;
-        let mut conn = self.redis().await?;
-        conn.lrem(key, count, content).await.into_app_result()
+        observe("redis", "lrem", async {
+            let content = serde_json::to_string(value)?;
+            let mut conn = self.redis().await?;
+            conn.lrem(self.key(key).as_ref(), count, content)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
-    /// Flush all keys in the database
+    /// Flush all keys of every database, the key prefix is ignored
     pub async fn flush_all(&self) -> AppResult<()> {
-        let mut conn = self.redis().await?;
-        redis::cmd("FLUSHALL")
-            .query_async(&mut *conn)
-            .await
-            .into_app_result()
+        observe("redis", "flush_all", async {
+            let mut conn = self.redis().await?;
+            redis::cmd("FLUSHALL")
+                .query_async(&mut *conn)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
-    /// Flush all keys in the database
+    /// Flush all keys in the database, the key prefix is ignored
     pub async fn flush_db(&self) -> AppResult<()> {
-        let mut conn = self.redis().await?;
-        redis::cmd("FLUSHDB")
-            .query_async(&mut *conn)
-            .await
-            .into_app_result()
+        observe("redis", "flush_db", async {
+            let mut conn = self.redis().await?;
+            redis::cmd("FLUSHDB")
+                .query_async(&mut *conn)
+                .await
+                .into_app_result()
+        })
+        .await
     }
 
-    /// Polls a Redis queue at a given interval and processes items using `func`
+    /// Polls a Redis queue of the global state's client at a given interval and processes
+    /// items using `func`, see [`Redis::poll`]
     ///
     /// # Arguments
     /// - `queue`: The Redis queue to poll
@@ -207,6 +484,26 @@ impl Redis {
     /// }
     /// ```
     pub async fn poll_queue<F, Fut>(
+        queue: String,
+        interval: Option<NonZeroU64>,
+        len: Option<NonZeroUsize>,
+        func: F,
+    ) where
+        F: FnMut(String) -> Fut + Send + Copy + 'static,
+        Fut: Future<Output = AppResult<()>> + Send + 'static,
+    {
+        FOXTIVE.redis().poll(queue, interval, len, func).await
+    }
+
+    /// Polls a Redis queue at a given interval and processes items using `func`
+    ///
+    /// # Arguments
+    /// - `queue`: The Redis queue to poll
+    /// - `interval`: The interval (in microseconds) between polls, defaults to 500ms
+    /// - `len`: The number of items to retrieve per poll, defaults to 1
+    /// - `func`: The async function to process each retrieved item
+    pub async fn poll<F, Fut>(
+        &self,
         queue: String,
         interval: Option<NonZeroU64>,
         len: Option<NonZeroUsize>,
@@ -221,7 +518,7 @@ impl Redis {
         ));
 
         loop {
-            match FOXTIVE.redis().rpop(&queue, len).await {
+            match self.rpop(&queue, len).await {
                 Ok(Some(item)) => {
                     let queue_clone = queue.clone();
                     Handle::current().spawn(async move {
@@ -293,7 +590,102 @@ impl Redis {
     /// # Returns
     /// - `AppResult<Vec<String>>`: A vector containing all matching keys
     pub async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>> {
-        let mut conn = self.redis().await?;
-        conn.keys(pattern).await.into_app_result()
+        observe("redis", "keys_by_pattern", async {
+            let mut conn = self.redis().await?;
+            let keys: Vec<String> = conn.keys(self.key_pattern(pattern)).await?;
+
+            Ok(match &self.prefix {
+                Some(prefix) => keys
+                    .into_iter()
+                    .filter_map(|key| key.strip_prefix(prefix.as_str()).map(str::to_string))
+                    .collect(),
+                None => keys,
+            })
+        })
+        .await
+    }
+
+    /// Full Redis key of `key`, with the prefix
+    pub fn key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match &self.prefix {
+            Some(prefix) => Cow::Owned(format!("{prefix}{key}")),
+            None => Cow::Borrowed(key),
+        }
+    }
+
+    /// Glob pattern matching `pattern` within the prefix, whose glob characters are escaped
+    fn key_pattern(&self, pattern: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => {
+                let mut escaped = String::with_capacity(prefix.len() + pattern.len());
+                for c in prefix.chars() {
+                    if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+                        escaped.push('\\');
+                    }
+                    escaped.push(c);
+                }
+                escaped.push_str(pattern);
+                escaped
+            }
+            None => pattern.to_string(),
+        }
+    }
+}
+
+/// How much longer than the command itself a blocking command may take to be answered
+const BLOCKING_MARGIN: Duration = Duration::from_secs(5);
+
+/// Response timeout of commands blocking until they get a result
+const BLOCK_FOREVER: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+/// A handle on `conn` for a command blocking up to `timeout` seconds, 0 blocking forever.
+///
+/// Its response timeout covers the wait, which the configured one would otherwise cut
+/// short, and is dropped with the handle so the pooled connection keeps its own.
+pub(crate) fn blocking(conn: &deadpool_redis::Connection, timeout: f64) -> MultiplexedConnection {
+    let wait = match timeout > 0.0 {
+        true => Duration::try_from_secs_f64(timeout).ok(),
+        false => None,
+    };
+
+    let mut handle = MultiplexedConnection::clone(conn);
+    handle.set_response_timeout(
+        wait.and_then(|wait| wait.checked_add(BLOCKING_MARGIN))
+            .unwrap_or(BLOCK_FOREVER),
+    );
+    handle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redis() -> Redis {
+        let pool = deadpool_redis::Config::from_url("redis://127.0.0.1:6379")
+            .create_pool(Some(deadpool_redis::Runtime::Tokio1))
+            .unwrap();
+        Redis::new(pool)
+    }
+
+    #[test]
+    fn test_keys_are_prefixed() {
+        let redis = redis().with_prefix("shop");
+
+        assert_eq!(redis.prefix(), Some("shop"));
+        assert_eq!(redis.key("user:1"), "shop:user:1");
+        assert_eq!(redis.key_pattern("user:*"), "shop:user:*");
+
+        let raw = redis.raw();
+        assert_eq!(raw.prefix(), None);
+        assert_eq!(raw.key("user:1"), "user:1");
+        assert_eq!(raw.key_pattern("user:*"), "user:*");
+    }
+
+    #[test]
+    fn test_prefix_glob_characters_are_escaped() {
+        let redis = redis().with_prefix("app[1]*");
+        assert_eq!(redis.key_pattern("*"), "app\\[1\\]\\*:*");
+
+        assert_eq!(redis.with_prefix("").prefix(), None);
     }
 }
@@ -0,0 +1,26 @@
+use crate::prelude::AppResult;
+use crate::saga::SagaRecord;
+
+/// Contract for implementing saga progress storage
+#[async_trait::async_trait]
+pub trait SagaStoreContract: Send + Sync {
+    /// Inserts or replaces the record of the same id
+    async fn save(&self, record: &SagaRecord) -> AppResult<()>;
+
+    async fn find(&self, id: &str) -> AppResult<Option<SagaRecord>>;
+
+    /// Unfinished sagas named in `sagas` last updated before `before` (Unix timestamp, in
+    /// milliseconds), least recently updated first.
+    ///
+    /// Sagas of other names are skipped rather than counted in the `limit`, so the ones an
+    /// orchestrator can't resume don't hold back the others.
+    async fn stale(&self, before: i64, sagas: &[&str], limit: usize) -> AppResult<Vec<SagaRecord>>;
+
+    /// Claims an unfinished saga for recovery by moving its update time from `seen`, as
+    /// returned by [`stale`](Self::stale), to `now`, atomically, returns whether it was
+    /// claimed.
+    ///
+    /// Once claimed, the saga isn't stale anymore and other claims of the same update
+    /// fail, so it is resumed by a single process.
+    async fn claim(&self, id: &str, seen: i64, now: i64) -> AppResult<bool>;
+}
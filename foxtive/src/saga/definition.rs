@@ -0,0 +1,141 @@
+use crate::prelude::AppResult;
+use crate::saga::contract::SagaStoreContract;
+use crate::saga::{SagaRecord, SagaStatus};
+use chrono::Utc;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use tracing::{error, warn};
+
+/// A step of a [`Saga`], operating on the saga's data `D`.
+///
+/// Changes made to the data are saved along with the saga's progress, so a step can
+/// leave what its compensation needs, e.g. the id of the payment to refund.
+#[async_trait::async_trait]
+pub trait SagaStep<D>: Send + Sync {
+    /// Name of the step, used in logs and recorded errors
+    fn name(&self) -> &'static str;
+
+    async fn execute(&self, data: &mut D) -> AppResult<()>;
+
+    /// Undoes `execute()`, called when a later step fails.
+    /// Does nothing by default, for steps without side effects.
+    async fn compensate(&self, data: &mut D) -> AppResult<()> {
+        let _ = data;
+        Ok(())
+    }
+}
+
+/// An ordered list of steps, registered with a
+/// [`SagaOrchestrator`](crate::saga::SagaOrchestrator) under its name.
+pub struct Saga<D> {
+    name: &'static str,
+    steps: Vec<Arc<dyn SagaStep<D>>>,
+}
+
+impl<D> Saga<D>
+where
+    D: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Appends a step, executed after the previous ones
+    pub fn step<S: SagaStep<D> + 'static>(mut self, step: S) -> Self {
+        self.steps.push(Arc::new(step));
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Drives the saga from the recorded progress to completion or full compensation,
+    /// saving the progress after every step.
+    ///
+    /// Returns the error of the failed step once compensated, and the error of the
+    /// compensation if it fails too.
+    pub(crate) async fn drive(
+        &self,
+        store: &dyn SagaStoreContract,
+        record: &mut SagaRecord,
+        data: &mut D,
+    ) -> AppResult<()> {
+        let mut failure = None;
+
+        while record.status == SagaStatus::Running {
+            let Some(step) = self.steps.get(record.step) else {
+                record.status = SagaStatus::Completed;
+                self.save(store, record, data).await?;
+                break;
+            };
+
+            match step.execute(data).await {
+                Ok(()) => record.step += 1,
+                Err(err) => {
+                    warn!(
+                        "[saga][{}] step '{}' of {} failed: {err}, compensating",
+                        self.name,
+                        step.name(),
+                        record.id
+                    );
+                    record.status = SagaStatus::Compensating;
+                    record.error = Some(format!("step '{}' failed: {err}", step.name()));
+                    failure = Some(err);
+                }
+            }
+
+            self.save(store, record, data).await?;
+        }
+
+        if record.status == SagaStatus::Compensating {
+            while record.step > 0 {
+                let step = &self.steps[record.step - 1];
+                if let Err(err) = step.compensate(data).await {
+                    error!(
+                        "[saga][{}] compensation of step '{}' of {} failed: {err}",
+                        self.name,
+                        step.name(),
+                        record.id
+                    );
+                    record.status = SagaStatus::Failed;
+                    record.error = Some(format!("compensation of '{}' failed: {err}", step.name()));
+                    self.save(store, record, data).await?;
+                    return Err(err);
+                }
+
+                record.step -= 1;
+                self.save(store, record, data).await?;
+            }
+
+            record.status = SagaStatus::Compensated;
+            self.save(store, record, data).await?;
+        }
+
+        match record.status {
+            SagaStatus::Completed => Ok(()),
+            _ => Err(failure.unwrap_or_else(|| {
+                anyhow::anyhow!(
+                    "saga {} {}",
+                    record.id,
+                    record.error.as_deref().unwrap_or("failed")
+                )
+            })),
+        }
+    }
+
+    async fn save(
+        &self,
+        store: &dyn SagaStoreContract,
+        record: &mut SagaRecord,
+        data: &D,
+    ) -> AppResult<()> {
+        record.data = serde_json::to_value(data)?;
+        record.updated_at = Utc::now().timestamp_millis();
+        store.save(record).await
+    }
+}
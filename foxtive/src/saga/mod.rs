@@ -0,0 +1,403 @@
+//! # Saga Module
+//!
+//! Runs operations spanning several services, such as placing an order (reserve the
+//! stock, charge the card, book the shipment), as sagas: every step has a compensating
+//! action, and when a step fails the steps that already ran are compensated in reverse
+//! order, so no half-placed order is left behind.
+//!
+//! The progress and data of a saga are saved to a [`SagaStoreContract`] after every
+//! step. Running under the supervisor, the [`SagaOrchestrator`] resumes the sagas left
+//! unfinished by a crashed process from their last saved step, steps and compensations
+//! thus run at least once and should be idempotent.
+//!
+//! ## Features
+//!
+//! This module requires the `saga` feature to be enabled, the Redis store additionally
+//! requires the `redis` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::prelude::AppResult;
+//! use foxtive::saga::stores::InMemorySagaStore;
+//! use foxtive::saga::{Saga, SagaOrchestrator, SagaStep};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct PlaceOrder {
+//!     order_id: u64,
+//!     payment_id: Option<String>,
+//! }
+//!
+//! struct ChargeCard;
+//!
+//! #[async_trait::async_trait]
+//! impl SagaStep<PlaceOrder> for ChargeCard {
+//!     fn name(&self) -> &'static str {
+//!         "charge-card"
+//!     }
+//!
+//!     async fn execute(&self, order: &mut PlaceOrder) -> AppResult<()> {
+//!         order.payment_id = Some(format!("pay_{}", order.order_id));
+//!         Ok(())
+//!     }
+//!
+//!     async fn compensate(&self, order: &mut PlaceOrder) -> AppResult<()> {
+//!         // refund order.payment_id
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let orchestrator = SagaOrchestrator::new("sagas", InMemorySagaStore::new())
+//!     .register(Saga::new("place-order").step(ChargeCard));
+//!
+//! let order = PlaceOrder { order_id: 42, payment_id: None };
+//! let order = orchestrator.start("place-order", order).await.unwrap();
+//! assert_eq!(order.payment_id.as_deref(), Some("pay_42"));
+//!
+//! // resume abandoned sagas in the background
+//! // runtime.register(orchestrator);
+//! # });
+//! ```
+
+pub mod contract;
+mod definition;
+mod orchestrator;
+pub mod stores;
+
+pub use contract::SagaStoreContract;
+pub use definition::{Saga, SagaStep};
+pub use orchestrator::SagaOrchestrator;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Stage of a saga, as kept by stores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SagaStatus {
+    /// Steps are being executed
+    Running,
+    /// A step failed, the executed steps are being compensated
+    Compensating,
+    /// Every step succeeded
+    Completed,
+    /// A step failed and the executed steps were compensated
+    Compensated,
+    /// A compensation failed, the saga needs manual intervention
+    Failed,
+}
+
+impl SagaStatus {
+    /// Whether the saga reached an end, and won't be resumed
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            SagaStatus::Completed | SagaStatus::Compensated | SagaStatus::Failed
+        )
+    }
+}
+
+/// Progress of a saga, saved after every step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SagaRecord {
+    pub id: String,
+    /// Name of the [`Saga`]
+    pub saga: String,
+    pub status: SagaStatus,
+    /// Number of executed steps, that aren't compensated yet
+    pub step: usize,
+    /// Data of the saga, as left by the last step
+    pub data: Value,
+    /// Error of the failed step, or compensation
+    pub error: Option<String>,
+    /// Unix timestamp, in milliseconds
+    pub created_at: i64,
+    /// Unix timestamp, in milliseconds
+    pub updated_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::AppResult;
+    use crate::saga::stores::InMemorySagaStore;
+    use std::time::Duration;
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct Order {
+        log: Vec<String>,
+        fail_at: Option<String>,
+        fail_compensation: bool,
+    }
+
+    struct Step(&'static str);
+
+    #[async_trait::async_trait]
+    impl SagaStep<Order> for Step {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn execute(&self, order: &mut Order) -> AppResult<()> {
+            if order.fail_at.as_deref() == Some(self.0) {
+                return Err(anyhow::anyhow!("{} is down", self.0));
+            }
+
+            order.log.push(format!("+{}", self.0));
+            Ok(())
+        }
+
+        async fn compensate(&self, order: &mut Order) -> AppResult<()> {
+            if order.fail_compensation {
+                return Err(anyhow::anyhow!("can't undo {}", self.0));
+            }
+
+            order.log.push(format!("-{}", self.0));
+            Ok(())
+        }
+    }
+
+    fn orchestrator() -> SagaOrchestrator {
+        let saga = Saga::new("place-order")
+            .step(Step("stock"))
+            .step(Step("payment"))
+            .step(Step("shipment"));
+
+        SagaOrchestrator::new("sagas", InMemorySagaStore::new())
+            .register(saga)
+            .stale_after(Duration::ZERO)
+    }
+
+    async fn records(orchestrator: &SagaOrchestrator) -> Vec<SagaRecord> {
+        orchestrator
+            .store()
+            .stale(i64::MAX, &["place-order"], 10)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_completed_saga_runs_every_step() {
+        let orchestrator = orchestrator();
+
+        let order = orchestrator
+            .start("place-order", Order::default())
+            .await
+            .unwrap();
+        assert_eq!(order.log, ["+stock", "+payment", "+shipment"]);
+        assert!(records(&orchestrator).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_start_returns_the_error_of_the_failed_step() {
+        let orchestrator = orchestrator();
+        let order = Order {
+            fail_at: Some("shipment".to_string()),
+            ..Order::default()
+        };
+
+        let err = orchestrator.start("place-order", order).await.unwrap_err();
+        assert_eq!(err.to_string(), "shipment is down");
+    }
+
+    #[tokio::test]
+    async fn test_compensated_progress_is_saved() {
+        let store = InMemorySagaStore::new();
+        let saga = Saga::new("place-order")
+            .step(Step("stock"))
+            .step(Step("payment"));
+        let mut record = SagaRecord {
+            id: "01".to_string(),
+            saga: "place-order".to_string(),
+            status: SagaStatus::Running,
+            step: 0,
+            data: Value::Null,
+            error: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+        let mut order = Order {
+            fail_at: Some("payment".to_string()),
+            ..Order::default()
+        };
+
+        assert!(saga.drive(&store, &mut record, &mut order).await.is_err());
+        assert_eq!(order.log, ["+stock", "-stock"]);
+
+        let saved = store.find("01").await.unwrap().unwrap();
+        assert_eq!(saved.status, SagaStatus::Compensated);
+        assert_eq!(saved.step, 0);
+        assert_eq!(
+            saved.error.as_deref(),
+            Some("step 'payment' failed: payment is down")
+        );
+        assert_eq!(saved.data["log"], serde_json::json!(["+stock", "-stock"]));
+    }
+
+    #[tokio::test]
+    async fn test_failed_compensation_needs_intervention() {
+        let store = InMemorySagaStore::new();
+        let saga = Saga::new("place-order")
+            .step(Step("stock"))
+            .step(Step("payment"));
+        let mut record = SagaRecord {
+            id: "02".to_string(),
+            saga: "place-order".to_string(),
+            status: SagaStatus::Running,
+            step: 0,
+            data: Value::Null,
+            error: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+        let mut order = Order {
+            fail_at: Some("payment".to_string()),
+            fail_compensation: true,
+            ..Order::default()
+        };
+
+        let err = saga
+            .drive(&store, &mut record, &mut order)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "can't undo stock");
+
+        let saved = store.find("02").await.unwrap().unwrap();
+        assert_eq!(saved.status, SagaStatus::Failed);
+        assert_eq!(saved.step, 1);
+    }
+
+    #[tokio::test]
+    async fn test_recover_resumes_abandoned_sagas() {
+        let orchestrator = orchestrator();
+        let abandoned = |id: &str, status, step, log: &[&str]| SagaRecord {
+            id: id.to_string(),
+            saga: "place-order".to_string(),
+            status,
+            step,
+            data: serde_json::json!({ "log": log, "fail_at": null, "fail_compensation": false }),
+            error: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        let store = orchestrator.store();
+        store
+            .save(&abandoned("03", SagaStatus::Running, 1, &["+stock"]))
+            .await
+            .unwrap();
+        store
+            .save(&abandoned(
+                "04",
+                SagaStatus::Compensating,
+                2,
+                &["+stock", "+payment"],
+            ))
+            .await
+            .unwrap();
+
+        // orchestrators sharing the store resume each saga once
+        let other = orchestrator.clone();
+        let (first, second) = tokio::join!(orchestrator.recover(), other.recover());
+        assert_eq!(first.unwrap() + second.unwrap(), 2);
+        assert!(records(&orchestrator).await.is_empty());
+
+        let resumed = orchestrator.find("03").await.unwrap().unwrap();
+        assert_eq!(resumed.status, SagaStatus::Completed);
+        assert_eq!(
+            resumed.data["log"],
+            serde_json::json!(["+stock", "+payment", "+shipment"])
+        );
+
+        let compensated = orchestrator.find("04").await.unwrap().unwrap();
+        assert_eq!(compensated.status, SagaStatus::Compensated);
+        assert_eq!(
+            compensated.data["log"],
+            serde_json::json!(["+stock", "+payment", "-payment", "-stock"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_sagas_do_not_hold_back_recovery() {
+        let orchestrator = orchestrator();
+        let abandoned = |id: String, saga: &str, updated_at| SagaRecord {
+            id,
+            saga: saga.to_string(),
+            status: SagaStatus::Running,
+            step: 0,
+            data: serde_json::json!({ "log": [], "fail_at": null, "fail_compensation": false }),
+            error: None,
+            created_at: 0,
+            updated_at,
+        };
+
+        // more abandoned sagas of another service than recovered per poll, all older
+        let store = orchestrator.store();
+        for index in 0..150 {
+            store
+                .save(&abandoned(format!("legacy-{index}"), "legacy", 0))
+                .await
+                .unwrap();
+        }
+        store
+            .save(&abandoned("07".to_string(), "place-order", 1))
+            .await
+            .unwrap();
+
+        assert_eq!(orchestrator.recover().await.unwrap(), 1);
+        let resumed = orchestrator.find("07").await.unwrap().unwrap();
+        assert_eq!(resumed.status, SagaStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_claims_are_exclusive() {
+        let store = InMemorySagaStore::new();
+        let record = SagaRecord {
+            id: "05".to_string(),
+            saga: "place-order".to_string(),
+            status: SagaStatus::Running,
+            step: 0,
+            data: Value::Null,
+            error: None,
+            created_at: 0,
+            updated_at: 10,
+        };
+        store.save(&record).await.unwrap();
+
+        assert!(!store.claim("05", 9, 20).await.unwrap());
+        assert!(store.claim("05", 10, 20).await.unwrap());
+        assert!(!store.claim("05", 10, 30).await.unwrap());
+        assert!(
+            store
+                .stale(15, &["place-order"], 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!store.claim("06", 10, 20).await.unwrap());
+
+        store
+            .save(&SagaRecord {
+                status: SagaStatus::Completed,
+                ..record
+            })
+            .await
+            .unwrap();
+        assert!(!store.claim("05", 10, 40).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_start_rejects_unknown_sagas_and_data_types() {
+        let orchestrator = orchestrator();
+
+        assert!(
+            orchestrator
+                .start("refund", Order::default())
+                .await
+                .is_err()
+        );
+        assert!(orchestrator.start("place-order", 42).await.is_err());
+    }
+}
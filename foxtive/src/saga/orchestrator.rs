@@ -0,0 +1,207 @@
+use crate::helpers::id;
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use crate::saga::contract::SagaStoreContract;
+use crate::saga::{Saga, SagaRecord, SagaStatus};
+use chrono::Utc;
+use foxtive_supervisor::SupervisedTask;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+/// Maximum number of stale sagas resumed per poll
+const RECOVER_BATCH: usize = 100;
+
+/// A [`Saga`] with its data type erased, so sagas of any data can be resumed from the store
+#[async_trait::async_trait]
+trait ErasedSaga: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    async fn resume(&self, store: &dyn SagaStoreContract, record: SagaRecord) -> AppResult<()>;
+}
+
+#[async_trait::async_trait]
+impl<D> ErasedSaga for Saga<D>
+where
+    D: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn resume(&self, store: &dyn SagaStoreContract, mut record: SagaRecord) -> AppResult<()> {
+        let mut data: D = serde_json::from_value(record.data.clone())?;
+        self.drive(store, &mut record, &mut data).await
+    }
+}
+
+/// Starts sagas and, running under the supervisor, resumes the ones left unfinished
+/// by a crashed process.
+///
+/// A saga is considered abandoned once its progress hasn't been saved for
+/// [`stale_after`](SagaOrchestrator::stale_after), it is then
+/// [claimed](SagaStoreContract::claim) by one of the orchestrators sharing the store and
+/// resumed from its last saved step, or compensation.
+#[derive(Clone)]
+pub struct SagaOrchestrator {
+    id: &'static str,
+    store: Arc<dyn SagaStoreContract>,
+    sagas: HashMap<&'static str, Arc<dyn ErasedSaga>>,
+    stale_after: Duration,
+    poll_interval: Duration,
+}
+
+impl SagaOrchestrator {
+    /// Creates an orchestrator resuming sagas abandoned for 5 minutes, checked every 30 seconds
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: unique id of the task within the supervisor
+    /// * `store`: where the progress of sagas is saved
+    pub fn new<S: SagaStoreContract + 'static>(id: &'static str, store: S) -> Self {
+        Self {
+            id,
+            store: Arc::new(store),
+            sagas: HashMap::new(),
+            stale_after: Duration::from_secs(5 * 60),
+            poll_interval: Duration::from_secs(30),
+        }
+    }
+
+    /// Registers a saga, replacing the one of the same name
+    pub fn register<D>(mut self, saga: Saga<D>) -> Self
+    where
+        D: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.sagas.insert(saga.name(), Arc::new(saga));
+        self
+    }
+
+    /// How long a saga may go without saving progress before it's resumed by recovery.
+    /// Should exceed the duration of the slowest step.
+    pub fn stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// How often the store is checked for abandoned sagas
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn store(&self) -> &Arc<dyn SagaStoreContract> {
+        &self.store
+    }
+
+    /// Runs the saga registered under `saga` on the data, returning the data as left by
+    /// its steps.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no saga of this data type is registered under the name,
+    /// the error of the failed step once the previous steps are compensated, and the
+    /// error of the compensation if it fails too
+    pub async fn start<D>(&self, saga: &str, mut data: D) -> AppResult<D>
+    where
+        D: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        let definition = self
+            .sagas
+            .get(saga)
+            .ok_or_else(|| internal_server_error!("Saga '{saga}' isn't registered"))?
+            .as_any()
+            .downcast_ref::<Saga<D>>()
+            .ok_or_else(|| internal_server_error!("Saga '{saga}' runs on another data type"))?;
+
+        let now = Utc::now().timestamp_millis();
+        let mut record = SagaRecord {
+            id: id::ulid(),
+            saga: saga.to_string(),
+            status: SagaStatus::Running,
+            step: 0,
+            data: serde_json::to_value(&data)?,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.store.save(&record).await?;
+
+        debug!("[saga][{saga}] started {}", record.id);
+        definition
+            .drive(self.store.as_ref(), &mut record, &mut data)
+            .await?;
+
+        Ok(data)
+    }
+
+    pub async fn find(&self, id: &str) -> AppResult<Option<SagaRecord>> {
+        self.store.find(id).await
+    }
+
+    /// Resumes abandoned sagas, returning how many were driven to the end.
+    ///
+    /// Sagas that fail again are compensated as usual, and counted.
+    pub async fn recover(&self) -> AppResult<usize> {
+        let stale_after = i64::try_from(self.stale_after.as_millis()).unwrap_or(i64::MAX);
+        let before = Utc::now().timestamp_millis().saturating_sub(stale_after);
+
+        let registered: Vec<&str> = self.sagas.keys().copied().collect();
+
+        let mut recovered = 0;
+        for mut record in self.store.stale(before, &registered, RECOVER_BATCH).await? {
+            let Some(saga) = self.sagas.get(record.saga.as_str()) else {
+                warn!(
+                    "[saga] can't resume {}, saga '{}' isn't registered",
+                    record.id, record.saga
+                );
+                continue;
+            };
+
+            let now = Utc::now().timestamp_millis();
+            if !self.store.claim(&record.id, record.updated_at, now).await? {
+                debug!(
+                    "[saga][{}] {} claimed by another process",
+                    record.saga, record.id
+                );
+                continue;
+            }
+            record.updated_at = now;
+
+            let (id, name) = (record.id.clone(), record.saga.clone());
+            warn!(
+                "[saga][{name}] resuming abandoned {id} ({:?})",
+                record.status
+            );
+            if let Err(err) = saga.resume(self.store.as_ref(), record).await {
+                error!("[saga][{name}] resumed {id} ended with an error: {err}");
+            }
+
+            recovered += 1;
+        }
+
+        Ok(recovered)
+    }
+}
+
+#[async_trait::async_trait]
+impl SupervisedTask for SagaOrchestrator {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> String {
+        "saga orchestrator".to_string()
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        loop {
+            self.recover().await?;
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
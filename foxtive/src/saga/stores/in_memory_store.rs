@@ -0,0 +1,59 @@
+use crate::prelude::AppResult;
+use crate::saga::SagaRecord;
+use crate::saga::contract::SagaStoreContract;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Keeps sagas in memory, they don't survive a restart, suitable for tests and development
+#[derive(Default)]
+pub struct InMemorySagaStore {
+    records: RwLock<HashMap<String, SagaRecord>>,
+}
+
+impl InMemorySagaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl SagaStoreContract for InMemorySagaStore {
+    async fn save(&self, record: &SagaRecord) -> AppResult<()> {
+        self.records
+            .write()
+            .unwrap()
+            .insert(record.id.clone(), record.clone());
+        Ok(())
+    }
+
+    async fn find(&self, id: &str) -> AppResult<Option<SagaRecord>> {
+        Ok(self.records.read().unwrap().get(id).cloned())
+    }
+
+    async fn stale(&self, before: i64, sagas: &[&str], limit: usize) -> AppResult<Vec<SagaRecord>> {
+        let mut stale: Vec<SagaRecord> = self
+            .records
+            .read()
+            .unwrap()
+            .values()
+            .filter(|record| !record.status.is_finished() && record.updated_at < before)
+            .filter(|record| sagas.contains(&record.saga.as_str()))
+            .cloned()
+            .collect();
+
+        stale.sort_by_key(|record| record.updated_at);
+        stale.truncate(limit);
+        Ok(stale)
+    }
+
+    async fn claim(&self, id: &str, seen: i64, now: i64) -> AppResult<bool> {
+        let mut records = self.records.write().unwrap();
+        match records.get_mut(id) {
+            Some(record) if !record.status.is_finished() && record.updated_at == seen => {
+                record.updated_at = now;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod in_memory_store;
+#[cfg(feature = "redis")]
+mod redis_store;
+
+pub use in_memory_store::InMemorySagaStore;
+#[cfg(feature = "redis")]
+pub use redis_store::RedisSagaStore;
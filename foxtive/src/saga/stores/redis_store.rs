@@ -0,0 +1,144 @@
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use crate::results::redis_result::RedisResultToAppResult;
+use crate::saga::SagaRecord;
+use crate::saga::contract::SagaStoreContract;
+use redis::AsyncCommands;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Moves the score of a member only if it is still the expected one
+const CLAIM: &str = "if tonumber(redis.call('ZSCORE', KEYS[1], ARGV[1])) == tonumber(ARGV[2]) then \
+     redis.call('ZADD', KEYS[1], ARGV[3], ARGV[1]) return 1 end return 0";
+
+/// Stores sagas as JSON under `{prefix}{id}`, with unfinished ones indexed by update
+/// time in the `{prefix}pending` sorted set.
+///
+/// Finished sagas are kept for 7 days by default.
+///
+/// The sorted set is the source of truth for update times, a saga is claimed by moving
+/// its score.
+pub struct RedisSagaStore {
+    redis: Arc<Redis>,
+    prefix: String,
+    retention: Option<Duration>,
+}
+
+impl RedisSagaStore {
+    /// Stores sagas under the `saga:` prefix
+    pub fn new(redis: Arc<Redis>) -> Self {
+        Self {
+            redis,
+            prefix: "saga:".to_string(),
+            retention: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        }
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// How long finished sagas are kept, `None` to keep them forever
+    pub fn retention(mut self, retention: Option<Duration>) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    fn key(&self, id: &str) -> String {
+        format!("{}{id}", self.prefix)
+    }
+
+    fn pending_key(&self) -> String {
+        self.redis.key(&self.key("pending")).into_owned()
+    }
+}
+
+#[async_trait::async_trait]
+impl SagaStoreContract for RedisSagaStore {
+    async fn save(&self, record: &SagaRecord) -> AppResult<()> {
+        let finished = record.status.is_finished();
+        let ttl = self.retention.filter(|_| finished);
+        self.redis
+            .set_json(&self.key(&record.id), record, ttl)
+            .await?;
+
+        let mut conn = self.redis.redis().await?;
+        let _: usize = match finished {
+            true => conn.zrem(self.pending_key(), &record.id).await,
+            false => {
+                conn.zadd(self.pending_key(), &record.id, record.updated_at)
+                    .await
+            }
+        }
+        .into_app_result()?;
+
+        Ok(())
+    }
+
+    async fn find(&self, id: &str) -> AppResult<Option<SagaRecord>> {
+        self.redis.get_json(&self.key(id)).await
+    }
+
+    async fn stale(&self, before: i64, sagas: &[&str], limit: usize) -> AppResult<Vec<SagaRecord>> {
+        let mut conn = self.redis.redis().await?;
+        let mut stale = Vec::with_capacity(limit);
+
+        // the index doesn't know the saga names, pages are read until enough match
+        let mut offset = 0;
+        while stale.len() < limit {
+            let pending: Vec<(String, i64)> = conn
+                .zrangebyscore_limit_withscores(
+                    self.pending_key(),
+                    "-inf",
+                    before,
+                    offset,
+                    limit as isize,
+                )
+                .await
+                .into_app_result()?;
+
+            let exhausted = pending.len() < limit;
+            offset += pending.len() as isize;
+
+            for (id, updated_at) in pending {
+                match self.find(&id).await? {
+                    Some(record) if sagas.contains(&record.saga.as_str()) => {
+                        stale.push(SagaRecord {
+                            updated_at,
+                            ..record
+                        })
+                    }
+                    Some(_) => {}
+                    None => {
+                        let _: usize =
+                            conn.zrem(self.pending_key(), &id).await.into_app_result()?;
+                        // the following members moved back by one
+                        offset -= 1;
+                    }
+                }
+            }
+
+            if exhausted {
+                break;
+            }
+        }
+
+        stale.truncate(limit);
+        Ok(stale)
+    }
+
+    async fn claim(&self, id: &str, seen: i64, now: i64) -> AppResult<bool> {
+        let mut conn = self.redis.redis().await?;
+        redis::cmd("EVAL")
+            .arg(CLAIM)
+            .arg(1)
+            .arg(self.pending_key())
+            .arg(id)
+            .arg(seen)
+            .arg(now)
+            .query_async(&mut conn)
+            .await
+            .into_app_result()
+    }
+}
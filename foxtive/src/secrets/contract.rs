@@ -0,0 +1,14 @@
+use crate::prelude::AppResult;
+
+/// Contract for implementing secret sources
+#[async_trait::async_trait]
+pub trait SecretsProvider: Send + Sync {
+    /// Short name of the provider used in logs and errors, e.g. `env` or `vault`
+    fn name(&self) -> &str;
+
+    /// Fetches the current value of a secret
+    ///
+    /// # Returns
+    /// - `AppResult<Option<String>>`: The value, or `None` if the provider does not hold the secret
+    async fn get(&self, name: &str) -> AppResult<Option<String>>;
+}
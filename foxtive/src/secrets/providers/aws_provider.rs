@@ -0,0 +1,222 @@
+use crate::helpers::aws_sigv4::{
+    self, ALGORITHM, amz_date, canonical_request, sha256_hex, signed_headers,
+};
+use crate::helpers::reqwest::ReqwestResponseError;
+use crate::prelude::AppResult;
+use crate::secrets::contract::SecretsProvider;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+
+const SERVICE: &str = "secretsmanager";
+const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+const TARGET: &str = "secretsmanager.GetSecretValue";
+
+/// Reads secrets from AWS Secrets Manager.
+///
+/// A secret name is either the secret id (name or ARN), returning the whole
+/// secret string, or `{id}#{key}` to read a single key of a JSON secret, e.g.
+/// `prod/my-app#db_password`.
+#[derive(Clone)]
+pub struct AwsSecretsManagerProvider {
+    region: String,
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    endpoint: String,
+    host: String,
+    client: Client,
+}
+
+impl AwsSecretsManagerProvider {
+    pub fn new(region: &str, access_key: &str, secret_key: &str) -> Self {
+        let provider = Self {
+            region: region.to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            session_token: None,
+            endpoint: String::new(),
+            host: String::new(),
+            client: Client::new(),
+        };
+
+        provider.endpoint(&format!("https://secretsmanager.{region}.amazonaws.com"))
+    }
+
+    /// Session token of temporary credentials, e.g. from an assumed role
+    pub fn session_token(mut self, token: &str) -> Self {
+        self.session_token = Some(token.to_string());
+        self
+    }
+
+    /// Overrides the service URL, for VPC endpoints or local emulators
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        self.host = endpoint
+            .split_once("://")
+            .map_or(endpoint, |(_, authority)| authority)
+            .to_string();
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Signed headers of a `GetSecretValue` request with the given body
+    fn headers(&self, now: DateTime<Utc>, body: &str) -> Vec<(&'static str, String)> {
+        let date = amz_date(now);
+        let mut headers = vec![
+            ("content-type", CONTENT_TYPE),
+            ("host", self.host.as_str()),
+            ("x-amz-date", date.as_str()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.as_str()));
+        }
+        headers.push(("x-amz-target", TARGET));
+
+        let request = canonical_request("POST", "/", "", &headers, &sha256_hex(body.as_bytes()));
+        let authorization = format!(
+            "{ALGORITHM} Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key,
+            aws_sigv4::scope(now, &self.region, SERVICE),
+            signed_headers(&headers),
+            aws_sigv4::signature(&self.secret_key, &self.region, SERVICE, now, &request)
+        );
+
+        headers
+            .into_iter()
+            .filter(|(name, _)| *name != "host")
+            .map(|(name, value)| (name, value.to_string()))
+            .chain([("authorization", authorization)])
+            .collect()
+    }
+}
+
+/// Picks the secret string, or one of its keys when `key` is given
+fn extract(body: &Value, key: Option<&str>) -> AppResult<Option<String>> {
+    let Some(secret) = body.get("SecretString").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+
+    let Some(key) = key else {
+        return Ok(Some(secret.to_string()));
+    };
+
+    let fields: Value = serde_json::from_str(secret)
+        .map_err(|err| anyhow::anyhow!("secret string is not a JSON object: {err}"))?;
+
+    Ok(match fields.get(key) {
+        Some(Value::String(value)) => Some(value.clone()),
+        Some(Value::Null) | None => None,
+        Some(value) => Some(value.to_string()),
+    })
+}
+
+#[async_trait]
+impl SecretsProvider for AwsSecretsManagerProvider {
+    fn name(&self) -> &str {
+        "aws-secrets-manager"
+    }
+
+    async fn get(&self, name: &str) -> AppResult<Option<String>> {
+        let (id, key) = match name.split_once('#') {
+            Some((id, key)) => (id, Some(key)),
+            None => (name, None),
+        };
+
+        let body = serde_json::json!({ "SecretId": id }).to_string();
+        let mut request = self.client.post(format!("{}/", self.endpoint));
+        for (header, value) in self.headers(Utc::now(), &body) {
+            request = request.header(header, value);
+        }
+
+        let response = request.body(body).send().await?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+
+        if status == StatusCode::BAD_REQUEST && text.contains("ResourceNotFoundException") {
+            return Ok(None);
+        }
+
+        if !status.is_success() {
+            return Err(ReqwestResponseError::make(status, text).into_anyhow());
+        }
+
+        extract(&serde_json::from_str(&text)?, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn test_signed_headers() {
+        let provider = AwsSecretsManagerProvider::new("eu-west-1", "AKID", "secret");
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let headers = provider.headers(now, r#"{"SecretId":"app"}"#);
+
+        let names = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "content-type",
+                "x-amz-date",
+                "x-amz-target",
+                "authorization"
+            ]
+        );
+
+        let authorization = &headers[3].1;
+        let prefix = "AWS4-HMAC-SHA256 Credential=AKID/20240102/eu-west-1/secretsmanager/aws4_request, \
+                      SignedHeaders=content-type;host;x-amz-date;x-amz-target, Signature=";
+        assert!(authorization.starts_with(prefix));
+        assert_eq!(authorization.len(), prefix.len() + 64);
+
+        let with_token = provider.session_token("token").headers(now, "{}");
+        let (name, authorization) = with_token.last().unwrap();
+        assert_eq!(*name, "authorization");
+        assert!(authorization.contains("x-amz-security-token;x-amz-target"));
+    }
+
+    #[test]
+    fn test_endpoint() {
+        let provider = AwsSecretsManagerProvider::new("us-east-1", "a", "b");
+        assert_eq!(provider.host, "secretsmanager.us-east-1.amazonaws.com");
+
+        let local = provider.endpoint("http://localhost:4566/");
+        assert_eq!(local.endpoint, "http://localhost:4566");
+        assert_eq!(local.host, "localhost:4566");
+    }
+
+    #[test]
+    fn test_extract() {
+        let body =
+            json!({ "Name": "app", "SecretString": r#"{"db_password":"pa55","port":5432}"# });
+
+        assert_eq!(
+            extract(&body, None).unwrap().as_deref(),
+            Some(r#"{"db_password":"pa55","port":5432}"#)
+        );
+        assert_eq!(
+            extract(&body, Some("db_password")).unwrap().as_deref(),
+            Some("pa55")
+        );
+        assert_eq!(
+            extract(&body, Some("port")).unwrap().as_deref(),
+            Some("5432")
+        );
+        assert_eq!(extract(&body, Some("missing")).unwrap(), None);
+        assert_eq!(extract(&json!({ "Name": "app" }), None).unwrap(), None);
+
+        let plain = json!({ "SecretString": "not json" });
+        assert!(extract(&plain, Some("key")).is_err());
+    }
+}
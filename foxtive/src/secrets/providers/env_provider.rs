@@ -0,0 +1,89 @@
+use crate::helpers::env;
+use crate::prelude::AppResult;
+use crate::secrets::contract::SecretsProvider;
+use async_trait::async_trait;
+use std::env::VarError;
+
+/// Reads secrets from environment variables, optionally prefixed with the app's env prefix.
+#[derive(Debug, Clone, Default)]
+pub struct EnvSecretsProvider {
+    prefix: Option<String>,
+}
+
+impl EnvSecretsProvider {
+    /// Reads the variable named exactly like the secret
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads `{prefix}_{name}` for the secret `name`
+    pub fn with_prefix(prefix: &str) -> Self {
+        Self {
+            prefix: Some(prefix.to_string()),
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{prefix}_{name}"),
+            None => name.to_string(),
+        }
+    }
+
+    fn get_in(&self, source: env::Source, name: &str) -> AppResult<Option<String>> {
+        match source(&self.key(name)) {
+            Ok(value) => Ok(Some(value)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(err) => Err(anyhow::anyhow!(
+                "secret '{name}' is not valid unicode: {err}"
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for EnvSecretsProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    async fn get(&self, name: &str) -> AppResult<Option<String>> {
+        self.get_in(&env::process_env, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_prefixed_variable() {
+        // an environment of its own, tests run in parallel with the process one
+        let source = |key: &str| match key {
+            "SECRETS_ENV_TEST_DB_PASSWORD" => Ok("pa55".to_string()),
+            _ => Err(VarError::NotPresent),
+        };
+
+        let provider = EnvSecretsProvider::with_prefix("SECRETS_ENV_TEST");
+        assert_eq!(
+            provider.get_in(&source, "DB_PASSWORD").unwrap().as_deref(),
+            Some("pa55")
+        );
+        assert_eq!(provider.get_in(&source, "MISSING").unwrap(), None);
+
+        let unprefixed = EnvSecretsProvider::new();
+        assert_eq!(
+            unprefixed
+                .get_in(&source, "SECRETS_ENV_TEST_DB_PASSWORD")
+                .unwrap()
+                .as_deref(),
+            Some("pa55")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_variable_is_none() {
+        let provider = EnvSecretsProvider::with_prefix("SECRETS_ENV_TEST_UNSET");
+        assert_eq!(provider.get("DB_PASSWORD").await.unwrap(), None);
+    }
+}
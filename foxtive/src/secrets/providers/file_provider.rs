@@ -0,0 +1,64 @@
+use crate::bad_request;
+use crate::prelude::AppResult;
+use crate::secrets::contract::SecretsProvider;
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Reads secrets mounted as files, one file per secret, as done by Docker
+/// (`/run/secrets`) and Kubernetes secret volumes.
+///
+/// Trailing line breaks are removed from the file content.
+#[derive(Debug, Clone)]
+pub struct FileSecretsProvider {
+    directory: PathBuf,
+}
+
+impl FileSecretsProvider {
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for FileSecretsProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn get(&self, name: &str) -> AppResult<Option<String>> {
+        let is_plain_name =
+            !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\']);
+        if !is_plain_name {
+            return Err(bad_request!("invalid secret name: {name}"));
+        }
+
+        match tokio::fs::read_to_string(self.directory.join(name)).await {
+            Ok(content) => Ok(Some(content.trim_end_matches(['\r', '\n']).to_string())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reads_mounted_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("app_key"), "s3cr3t\n").unwrap();
+
+        let provider = FileSecretsProvider::new(dir.path());
+        assert_eq!(
+            provider.get("app_key").await.unwrap().as_deref(),
+            Some("s3cr3t")
+        );
+        assert_eq!(provider.get("missing").await.unwrap(), None);
+        assert!(provider.get("../app_key").await.is_err());
+        assert!(provider.get("..").await.is_err());
+    }
+}
@@ -0,0 +1,13 @@
+#[cfg(feature = "secrets-aws")]
+mod aws_provider;
+mod env_provider;
+mod file_provider;
+#[cfg(feature = "secrets-vault")]
+mod vault_provider;
+
+#[cfg(feature = "secrets-aws")]
+pub use aws_provider::AwsSecretsManagerProvider;
+pub use env_provider::EnvSecretsProvider;
+pub use file_provider::FileSecretsProvider;
+#[cfg(feature = "secrets-vault")]
+pub use vault_provider::VaultSecretsProvider;
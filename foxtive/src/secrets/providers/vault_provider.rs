@@ -0,0 +1,131 @@
+use crate::helpers::reqwest::ReqwestResponseError;
+use crate::prelude::AppResult;
+use crate::secrets::contract::SecretsProvider;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+
+/// Reads secrets from a HashiCorp Vault KV version 2 secrets engine.
+///
+/// Every secret of the provider is a key of the same Vault secret, e.g. the
+/// `app_key` and `db_password` keys of `secret/data/my-app`.
+#[derive(Clone)]
+pub struct VaultSecretsProvider {
+    address: String,
+    token: String,
+    mount: String,
+    path: String,
+    namespace: Option<String>,
+    client: Client,
+}
+
+impl VaultSecretsProvider {
+    /// # Arguments
+    ///
+    /// * `address`: Vault server URL, e.g. `https://vault.internal:8200`
+    /// * `token`: token with read access to the secret
+    /// * `path`: path of the secret within the mount, e.g. `my-app`
+    pub fn new(address: &str, token: &str, path: &str) -> Self {
+        Self {
+            address: address.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            mount: "secret".to_string(),
+            path: path.trim_matches('/').to_string(),
+            namespace: None,
+            client: Client::new(),
+        }
+    }
+
+    /// Mount point of the KV engine, `secret` by default
+    pub fn mount(mut self, mount: &str) -> Self {
+        self.mount = mount.trim_matches('/').to_string();
+        self
+    }
+
+    /// Vault Enterprise namespace
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn url(&self) -> String {
+        format!("{}/v1/{}/data/{}", self.address, self.mount, self.path)
+    }
+}
+
+/// Extracts a key from a KV v2 read response, `{ "data": { "data": { ... } } }`
+fn extract(body: &Value, name: &str) -> Option<String> {
+    match body.pointer("/data/data")?.get(name)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for VaultSecretsProvider {
+    fn name(&self) -> &str {
+        "vault"
+    }
+
+    async fn get(&self, name: &str) -> AppResult<Option<String>> {
+        let mut request = self
+            .client
+            .get(self.url())
+            .header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ReqwestResponseError::make(status, body).into_anyhow());
+        }
+
+        let body: Value = serde_json::from_str(&response.text().await?)?;
+        Ok(extract(&body, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_url() {
+        let provider = VaultSecretsProvider::new("https://vault:8200/", "token", "/my-app/");
+        assert_eq!(provider.url(), "https://vault:8200/v1/secret/data/my-app");
+        assert_eq!(
+            provider.mount("kv").url(),
+            "https://vault:8200/v1/kv/data/my-app"
+        );
+    }
+
+    #[test]
+    fn test_extract() {
+        let body = json!({
+            "data": {
+                "data": { "app_key": "s3cr3t", "port": 5432, "unset": null },
+                "metadata": { "version": 3 }
+            }
+        });
+
+        assert_eq!(extract(&body, "app_key").as_deref(), Some("s3cr3t"));
+        assert_eq!(extract(&body, "port").as_deref(), Some("5432"));
+        assert_eq!(extract(&body, "unset"), None);
+        assert_eq!(extract(&body, "missing"), None);
+        assert_eq!(extract(&json!({}), "app_key"), None);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::prelude::AppResult;
+use crate::session::SessionData;
+use std::time::Duration;
+
+/// Contract for implementing session storage
+///
+/// Stores deal with unsigned session ids, signatures are checked by the
+/// [`SessionManager`](crate::session::SessionManager) beforehand.
+#[async_trait::async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Loads the data of a session, `None` if it doesn't exist or has expired
+    async fn load(&self, id: &str) -> AppResult<Option<SessionData>>;
+
+    /// Stores the data of a session, expiring after `ttl`
+    async fn save(&self, id: &str, data: &SessionData, ttl: Duration) -> AppResult<()>;
+
+    /// Removes a session
+    async fn destroy(&self, id: &str) -> AppResult<()>;
+}
@@ -0,0 +1,376 @@
+//! # Session Module
+//!
+//! Server-side sessions for web apps authenticating with cookies rather than JWTs.
+//!
+//! Session data is kept in a [`SessionStore`], Redis or any cache driver such as the
+//! in-memory one, and the client only holds the session id, signed so ids can't be
+//! forged. Sessions expire after a period of inactivity: with sliding expiration, the
+//! default, each load pushes the expiry back.
+//!
+//! ## Features
+//!
+//! This module requires the `session` feature to be enabled, the stores additionally
+//! require the `redis` or `cache` features.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::cache::drivers::InMemoryDriver;
+//! use foxtive::session::SessionManager;
+//! use foxtive::session::stores::CacheSessionStore;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let store = CacheSessionStore::new(Arc::new(InMemoryDriver::new()));
+//! let sessions = SessionManager::new(store, "session-secret").ttl(Duration::from_secs(1800));
+//!
+//! // login
+//! let mut session = sessions.start().unwrap();
+//! session.put("user_id", &42).unwrap();
+//! let cookie = sessions.save(&session).await.unwrap();
+//!
+//! // next request
+//! let session = sessions.load(&cookie).await.unwrap().unwrap();
+//! assert_eq!(session.get::<u64>("user_id").unwrap(), Some(42));
+//! # });
+//! ```
+
+pub mod contract;
+pub mod stores;
+
+pub use contract::SessionStore;
+
+use crate::helpers::hmac::{HashFunc, Hmac, constant_time_eq};
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// Data of a session, by key
+pub type SessionData = Map<String, Value>;
+
+/// Random bytes making up a session id
+const ID_BYTES: usize = 32;
+
+/// A session and its data, persisted with [`SessionManager::save`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    id: String,
+    data: SessionData,
+    is_new: bool,
+}
+
+impl Session {
+    /// The unsigned session id, as known to the store
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether the session was started rather than loaded
+    pub fn is_new(&self) -> bool {
+        self.is_new
+    }
+
+    /// Gets the value of `key`, `None` if absent
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a `T`
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> AppResult<Option<T>> {
+        match self.data.get(key) {
+            Some(value) => Ok(Some(serde_json::from_value(value.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put<T: Serialize>(&mut self, key: &str, value: &T) -> AppResult<()> {
+        self.data
+            .insert(key.to_string(), serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.data.contains_key(key)
+    }
+
+    /// Removes `key`, returning its value
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.data.remove(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn data(&self) -> &SessionData {
+        &self.data
+    }
+}
+
+/// Starts, loads and persists sessions, signing their ids.
+#[derive(Clone)]
+pub struct SessionManager {
+    store: Arc<dyn SessionStore>,
+    hmac: Hmac,
+    ttl: Duration,
+    sliding: bool,
+}
+
+impl SessionManager {
+    /// Creates a manager signing session ids with HMAC-SHA256, sessions expiring
+    /// after 2 hours of inactivity
+    pub fn new<S: SessionStore + 'static>(store: S, secret: &str) -> Self {
+        Self {
+            store: Arc::new(store),
+            hmac: Hmac::new(secret, HashFunc::Sha256),
+            ttl: Duration::from_secs(2 * 60 * 60),
+            sliding: true,
+        }
+    }
+
+    /// How long sessions live after being saved, or loaded with sliding expiration
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Whether loading a session pushes its expiry back, defaults to true
+    pub fn sliding(mut self, sliding: bool) -> Self {
+        self.sliding = sliding;
+        self
+    }
+
+    /// Starts an empty session with a new random id, stored once saved
+    pub fn start(&self) -> AppResult<Session> {
+        Ok(Session {
+            id: random_id()?,
+            data: SessionData::new(),
+            is_new: true,
+        })
+    }
+
+    /// Loads the session of a signed id, as returned by [`SessionManager::save`].
+    ///
+    /// Returns `None` if the signature is invalid or the session doesn't exist or expired.
+    pub async fn load(&self, signed_id: &str) -> AppResult<Option<Session>> {
+        let Some(id) = self.verify(signed_id)? else {
+            debug!("[session] rejected a session id with an invalid signature");
+            return Ok(None);
+        };
+
+        let Some(data) = self.store.load(id).await? else {
+            return Ok(None);
+        };
+
+        if self.sliding {
+            self.store.save(id, &data, self.ttl).await?;
+        }
+
+        Ok(Some(Session {
+            id: id.to_string(),
+            data,
+            is_new: false,
+        }))
+    }
+
+    /// Loads the session of a signed id if any and valid, starts a new one otherwise
+    pub async fn load_or_start(&self, signed_id: Option<&str>) -> AppResult<Session> {
+        match signed_id {
+            Some(signed_id) => match self.load(signed_id).await? {
+                Some(session) => Ok(session),
+                None => self.start(),
+            },
+            None => self.start(),
+        }
+    }
+
+    /// Persists the session, returning its signed id to hand to the client
+    pub async fn save(&self, session: &Session) -> AppResult<String> {
+        self.store
+            .save(&session.id, &session.data, self.ttl)
+            .await?;
+        self.sign(&session.id)
+    }
+
+    /// Removes the session from the store, e.g. on logout
+    pub async fn destroy(&self, session: Session) -> AppResult<()> {
+        self.store.destroy(&session.id).await
+    }
+
+    /// Moves the session to a new id, keeping its data, to prevent session fixation
+    /// when the user's privileges change, e.g. on login. Save it afterward.
+    pub async fn regenerate(&self, session: &mut Session) -> AppResult<()> {
+        if !session.is_new {
+            self.store.destroy(&session.id).await?;
+        }
+
+        session.id = random_id()?;
+        session.is_new = true;
+        Ok(())
+    }
+
+    /// Signed id, `{id}.{signature}`
+    pub fn sign(&self, id: &str) -> AppResult<String> {
+        Ok(format!("{id}.{}", self.hmac.hash(id)?))
+    }
+
+    /// The id of a signed id, `None` if the signature doesn't match
+    fn verify<'a>(&self, signed_id: &'a str) -> AppResult<Option<&'a str>> {
+        let Some((id, signature)) = signed_id.rsplit_once('.') else {
+            return Ok(None);
+        };
+
+        let expected = self.hmac.hash(id)?;
+        match constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+            true => Ok(Some(id)),
+            false => Ok(None),
+        }
+    }
+}
+
+fn random_id() -> AppResult<String> {
+    let mut bytes = [0u8; ID_BYTES];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| internal_server_error!("failed to generate a session id: {e}"))?;
+    Ok(hex::encode(bytes))
+}
+
+#[cfg(all(test, feature = "cache-in-memory"))]
+mod tests {
+    use super::*;
+    use crate::cache::drivers::InMemoryDriver;
+    use crate::session::stores::CacheSessionStore;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    fn manager() -> SessionManager {
+        let store = CacheSessionStore::new(Arc::new(InMemoryDriver::new()));
+        SessionManager::new(store, "secret")
+    }
+
+    /// A store whose time only moves when the returned clock is advanced
+    fn store_with_clock() -> (CacheSessionStore, Arc<AtomicI64>) {
+        let now = Arc::new(AtomicI64::new(1_000_000));
+        let clock = now.clone();
+        let store = CacheSessionStore::new(Arc::new(InMemoryDriver::new()))
+            .clock(move || clock.load(Ordering::SeqCst));
+        (store, now)
+    }
+
+    fn advance(clock: &AtomicI64, by: Duration) {
+        clock.fetch_add(by.as_millis() as i64, Ordering::SeqCst);
+    }
+
+    #[tokio::test]
+    async fn test_saved_session_is_loaded_with_its_data() {
+        let sessions = manager();
+
+        let mut session = sessions.start().unwrap();
+        session.put("user_id", &7).unwrap();
+        session.put("roles", &vec!["admin"]).unwrap();
+        let cookie = sessions.save(&session).await.unwrap();
+
+        let loaded = sessions.load(&cookie).await.unwrap().unwrap();
+        assert_eq!(loaded.id(), session.id());
+        assert!(!loaded.is_new());
+        assert_eq!(loaded.get::<u64>("user_id").unwrap(), Some(7));
+        assert_eq!(
+            loaded.get::<Vec<String>>("roles").unwrap(),
+            Some(vec!["admin".to_string()])
+        );
+        assert!(loaded.get::<String>("user_id").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_forged_or_foreign_ids_are_rejected() {
+        let sessions = manager();
+        let session = sessions.start().unwrap();
+        let cookie = sessions.save(&session).await.unwrap();
+
+        assert!(sessions.load(session.id()).await.unwrap().is_none());
+        assert!(
+            sessions
+                .load(&format!("{}.deadbeef", session.id()))
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let other = SessionManager::new(
+            CacheSessionStore::new(Arc::new(InMemoryDriver::new())),
+            "another-secret",
+        );
+        assert_ne!(other.sign(session.id()).unwrap(), cookie);
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_gone() {
+        let (store, clock) = store_with_clock();
+        let sessions = SessionManager::new(store, "secret").ttl(Duration::from_secs(60));
+        let cookie = sessions.save(&sessions.start().unwrap()).await.unwrap();
+
+        advance(&clock, Duration::from_secs(59));
+        assert!(sessions.load(&cookie).await.unwrap().is_some());
+
+        advance(&clock, Duration::from_secs(61));
+        assert!(sessions.load(&cookie).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_sessions_are_pruned() {
+        let (store, clock) = store_with_clock();
+        let data = SessionData::default();
+        store
+            .save("abandoned", &data, Duration::from_secs(30))
+            .await
+            .unwrap();
+        store
+            .save("active", &data, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        assert_eq!(store.prune().await.unwrap(), 0);
+        advance(&clock, Duration::from_secs(30));
+        assert_eq!(store.prune().await.unwrap(), 1);
+        assert!(store.load("active").await.unwrap().is_some());
+        assert_eq!(store.prune().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_loading_slides_the_expiry() {
+        let (store, clock) = store_with_clock();
+        let sessions = SessionManager::new(store, "secret").ttl(Duration::from_secs(60));
+        let cookie = sessions.save(&sessions.start().unwrap()).await.unwrap();
+
+        for _ in 0..3 {
+            advance(&clock, Duration::from_secs(40));
+            assert!(sessions.load(&cookie).await.unwrap().is_some());
+        }
+
+        advance(&clock, Duration::from_secs(60));
+        assert!(sessions.load(&cookie).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_regenerate_and_destroy() {
+        let sessions = manager();
+        let mut session = sessions.start().unwrap();
+        session.put("cart", &3).unwrap();
+        let old_cookie = sessions.save(&session).await.unwrap();
+
+        let mut loaded = sessions.load(&old_cookie).await.unwrap().unwrap();
+        sessions.regenerate(&mut loaded).await.unwrap();
+        let new_cookie = sessions.save(&loaded).await.unwrap();
+
+        assert!(sessions.load(&old_cookie).await.unwrap().is_none());
+        let loaded = sessions.load(&new_cookie).await.unwrap().unwrap();
+        assert_eq!(loaded.get::<u32>("cart").unwrap(), Some(3));
+
+        sessions.destroy(loaded).await.unwrap();
+        assert!(sessions.load(&new_cookie).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::cache::KeyPattern;
+use crate::cache::contract::CacheDriverContract;
+use crate::prelude::AppResult;
+use crate::session::SessionData;
+use crate::session::contract::SessionStore;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stores sessions in a cache driver, e.g. the in-memory one, under `{prefix}{id}`.
+///
+/// As explained for [`CacheDriverContract`], the expiry is stored along with the data.
+/// Expired sessions are removed when loaded, by drivers supporting expiry, and by
+/// [`prune`](Self::prune) for the others.
+pub struct CacheSessionStore {
+    driver: Arc<dyn CacheDriverContract>,
+    prefix: String,
+    /// Current Unix timestamp in milliseconds, the system time outside of tests
+    clock: Arc<dyn Fn() -> i64 + Send + Sync>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    data: SessionData,
+    /// Unix timestamp, in milliseconds
+    expires_at: i64,
+}
+
+impl CacheSessionStore {
+    /// Stores sessions under the `session:` prefix
+    pub fn new(driver: Arc<dyn CacheDriverContract>) -> Self {
+        Self {
+            driver,
+            prefix: "session:".to_string(),
+            clock: Arc::new(|| Utc::now().timestamp_millis()),
+        }
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Replaces the system time, so tests can expire sessions without waiting
+    #[cfg(test)]
+    pub(crate) fn clock(mut self, clock: impl Fn() -> i64 + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Removes the expired sessions, returning how many were removed
+    ///
+    /// Sessions abandoned on drivers that don't expire values are otherwise kept forever,
+    /// run it periodically with them.
+    pub async fn prune(&self) -> AppResult<usize> {
+        let now = (self.clock)();
+        let keys = self
+            .driver
+            .keys_by_pattern(&KeyPattern::prefix(&self.prefix))
+            .await?;
+
+        let mut pruned = 0;
+        for key in keys {
+            let Some(raw) = self.driver.get_raw(&key).await? else {
+                continue;
+            };
+
+            let expired = serde_json::from_str::<StoredSession>(&raw)
+                .is_ok_and(|stored| stored.expires_at <= now);
+            if expired {
+                pruned += self.driver.forget(&key).await? as usize;
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    fn key(&self, id: &str) -> String {
+        format!("{}{id}", self.prefix)
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for CacheSessionStore {
+    async fn load(&self, id: &str) -> AppResult<Option<SessionData>> {
+        let key = self.key(id);
+        let stored = match self.driver.get_raw(&key).await? {
+            Some(raw) => serde_json::from_str::<StoredSession>(&raw)?,
+            None => return Ok(None),
+        };
+
+        if stored.expires_at <= (self.clock)() {
+            self.driver.forget(&key).await?;
+            return Ok(None);
+        }
+
+        Ok(Some(stored.data))
+    }
+
+    async fn save(&self, id: &str, data: &SessionData, ttl: Duration) -> AppResult<()> {
+        let millis = i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX);
+        let stored = StoredSession {
+            data: data.clone(),
+            expires_at: (self.clock)().saturating_add(millis),
+        };
+
+        self.driver
+            .put_raw_for(&self.key(id), serde_json::to_string(&stored)?, ttl)
+            .await?;
+        Ok(())
+    }
+
+    async fn destroy(&self, id: &str) -> AppResult<()> {
+        self.driver.forget(&self.key(id)).await?;
+        Ok(())
+    }
+}
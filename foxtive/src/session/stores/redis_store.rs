@@ -0,0 +1,47 @@
+use crate::prelude::AppResult;
+use crate::redis::Redis;
+use crate::session::SessionData;
+use crate::session::contract::SessionStore;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stores sessions as JSON under `{prefix}{id}`, expired by Redis itself
+pub struct RedisSessionStore {
+    redis: Arc<Redis>,
+    prefix: String,
+}
+
+impl RedisSessionStore {
+    /// Stores sessions under the `session:` prefix
+    pub fn new(redis: Arc<Redis>) -> Self {
+        Self {
+            redis,
+            prefix: "session:".to_string(),
+        }
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    fn key(&self, id: &str) -> String {
+        format!("{}{id}", self.prefix)
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn load(&self, id: &str) -> AppResult<Option<SessionData>> {
+        self.redis.get_json(&self.key(id)).await
+    }
+
+    async fn save(&self, id: &str, data: &SessionData, ttl: Duration) -> AppResult<()> {
+        self.redis.set_json(&self.key(id), data, Some(ttl)).await
+    }
+
+    async fn destroy(&self, id: &str) -> AppResult<()> {
+        self.redis.delete(&self.key(id)).await?;
+        Ok(())
+    }
+}
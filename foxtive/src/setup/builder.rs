@@ -0,0 +1,362 @@
+use crate::Environment;
+use crate::results::AppResult;
+#[cfg(feature = "secrets")]
+use crate::secrets::Secrets;
+#[cfg(feature = "cache")]
+use crate::setup::CacheDriverSetup;
+use crate::setup::FoxtiveSetup;
+#[cfg(feature = "storage")]
+use crate::setup::StorageDriverSetup;
+use thiserror::Error;
+
+/// Returned by [`FoxtiveSetupBuilder::build`], lists the builder methods that must be
+/// called for the enabled features
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("incomplete Foxtive setup, missing: {}", .missing.join(", "))]
+pub struct IncompleteSetupError {
+    pub missing: Vec<&'static str>,
+}
+
+/// Builds a [`FoxtiveSetup`], created with [`FoxtiveSetup::builder`].
+///
+/// Only the settings of the enabled features are required, the environment defaults to
+/// [`Environment::Local`] and the environment variables prefix to `APP`.
+#[derive(Default)]
+pub struct FoxtiveSetupBuilder {
+    env: Environment,
+    env_prefix: Option<String>,
+    app: Option<(String, String)>,
+    app_key: Option<String>,
+    keys: Option<(String, String)>,
+
+    #[cfg(feature = "jwt")]
+    jwt: Option<(String, i64)>,
+    #[cfg(feature = "cipher")]
+    cipher_key_id: Option<u8>,
+    #[cfg(feature = "cipher")]
+    cipher_previous_keys: Vec<(u8, String)>,
+    #[cfg(feature = "templating")]
+    template_directory: Option<String>,
+    #[cfg(feature = "templating")]
+    template_setup: crate::templating::TemplateSetup,
+    #[cfg(feature = "database")]
+    db_config: Option<crate::database::DbConfig>,
+    #[cfg(feature = "redis")]
+    redis_config: Option<crate::redis::config::RedisConfig>,
+    #[cfg(feature = "rabbitmq")]
+    rmq_config: Option<crate::rabbitmq::config::RabbitmqConfig>,
+    #[cfg(feature = "cache")]
+    cache_driver_setup: Option<CacheDriverSetup>,
+    #[cfg(feature = "cache")]
+    cache_codec: crate::cache::CacheCodec,
+    #[cfg(feature = "cache")]
+    cache_without_namespace: bool,
+    #[cfg(feature = "storage")]
+    storage_driver_setup: Option<StorageDriverSetup>,
+    #[cfg(feature = "secrets")]
+    secrets: Secrets,
+}
+
+impl FoxtiveSetup {
+    pub fn builder() -> FoxtiveSetupBuilder {
+        FoxtiveSetupBuilder::default()
+    }
+}
+
+impl FoxtiveSetupBuilder {
+    /// Application code and display name
+    pub fn app(mut self, code: &str, name: &str) -> Self {
+        self.app = Some((code.to_string(), name.to_string()));
+        self
+    }
+
+    pub fn env(mut self, env: Environment) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Prefix of the application's environment variables, `APP` by default
+    pub fn env_prefix(mut self, env_prefix: &str) -> Self {
+        self.env_prefix = Some(env_prefix.to_string());
+        self
+    }
+
+    pub fn app_key(mut self, app_key: &str) -> Self {
+        self.app_key = Some(app_key.to_string());
+        self
+    }
+
+    /// Application's key pair, required with the `jwt` feature to sign tokens
+    pub fn keys(mut self, private_key: &str, public_key: &str) -> Self {
+        self.keys = Some((private_key.to_string(), public_key.to_string()));
+        self
+    }
+
+    /// Public key of the tokens' issuer and lifetime of the issued tokens, in minutes
+    #[cfg(feature = "jwt")]
+    pub fn with_jwt(mut self, iss_public_key: &str, token_lifetime: i64) -> Self {
+        self.jwt = Some((iss_public_key.to_string(), token_lifetime));
+        self
+    }
+
+    /// Id of the app key in the ciphertexts, 1 by default, to change along with the app key
+    #[cfg(feature = "cipher")]
+    pub fn with_cipher_key_id(mut self, key_id: u8) -> Self {
+        self.cipher_key_id = Some(key_id);
+        self
+    }
+
+    /// A previous app key, so data encrypted with it can still be decrypted
+    #[cfg(feature = "cipher")]
+    pub fn with_previous_cipher_key(mut self, key_id: u8, app_key: &str) -> Self {
+        self.cipher_previous_keys
+            .push((key_id, app_key.to_string()));
+        self
+    }
+
+    /// Glob of the Tera templates to load, e.g. `templates/**/*.tera.html`
+    #[cfg(feature = "templating")]
+    pub fn with_templates(mut self, directory: &str) -> Self {
+        self.template_directory = Some(directory.to_string());
+        self
+    }
+
+    /// Filters, functions, global values and hot reload of the templates
+    #[cfg(feature = "templating")]
+    pub fn with_template_setup(mut self, setup: crate::templating::TemplateSetup) -> Self {
+        self.template_setup = setup;
+        self
+    }
+
+    #[cfg(feature = "database")]
+    pub fn with_db(mut self, config: crate::database::DbConfig) -> Self {
+        self.db_config = Some(config);
+        self
+    }
+
+    #[cfg(feature = "redis")]
+    pub fn with_redis(mut self, config: crate::redis::config::RedisConfig) -> Self {
+        self.redis_config = Some(config);
+        self
+    }
+
+    #[cfg(feature = "rabbitmq")]
+    pub fn with_rabbitmq(mut self, config: crate::rabbitmq::config::RabbitmqConfig) -> Self {
+        self.rmq_config = Some(config);
+        self
+    }
+
+    #[cfg(feature = "cache")]
+    pub fn with_cache(mut self, setup: CacheDriverSetup) -> Self {
+        self.cache_driver_setup = Some(setup);
+        self
+    }
+
+    #[cfg(feature = "cache")]
+    pub fn with_cache_codec(mut self, codec: crate::cache::CacheCodec) -> Self {
+        self.cache_codec = codec;
+        self
+    }
+
+    /// Stores cache keys as given, instead of prefixed with `{app_code}:{env}:`.
+    ///
+    /// The Redis driver skips that prefix by itself when the Redis keys are prefixed.
+    #[cfg(feature = "cache")]
+    pub fn without_cache_namespace(mut self) -> Self {
+        self.cache_without_namespace = true;
+        self
+    }
+
+    #[cfg(feature = "storage")]
+    pub fn with_storage(mut self, setup: StorageDriverSetup) -> Self {
+        self.storage_driver_setup = Some(setup);
+        self
+    }
+
+    /// Resolves the `${secret:NAME}` placeholders, `Secrets::default()` by default
+    #[cfg(feature = "secrets")]
+    pub fn with_secrets(mut self, secrets: Secrets) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// # Errors
+    ///
+    /// Returns an [`IncompleteSetupError`] listing every missing setting
+    pub fn build(self) -> AppResult<FoxtiveSetup> {
+        let mut missing = vec![];
+        let mut require = |present: bool, name: &'static str| {
+            if !present {
+                missing.push(name);
+            }
+        };
+
+        require(self.app.is_some(), "app");
+        require(self.app_key.is_some(), "app_key");
+        #[cfg(feature = "jwt")]
+        {
+            require(self.keys.is_some(), "keys");
+            require(self.jwt.is_some(), "with_jwt");
+        }
+        #[cfg(feature = "templating")]
+        require(self.template_directory.is_some(), "with_templates");
+        #[cfg(feature = "database")]
+        require(self.db_config.is_some(), "with_db");
+        #[cfg(feature = "redis")]
+        require(self.redis_config.is_some(), "with_redis");
+        #[cfg(feature = "rabbitmq")]
+        require(self.rmq_config.is_some(), "with_rabbitmq");
+        #[cfg(feature = "cache")]
+        require(self.cache_driver_setup.is_some(), "with_cache");
+        #[cfg(feature = "storage")]
+        require(self.storage_driver_setup.is_some(), "with_storage");
+
+        if !missing.is_empty() {
+            return Err(IncompleteSetupError { missing }.into());
+        }
+
+        let (app_code, app_name) = required(self.app, "app")?;
+        let (private_key, public_key) = self.keys.unwrap_or_default();
+        #[cfg(feature = "jwt")]
+        let (jwt_iss_public_key, jwt_token_lifetime) = required(self.jwt, "with_jwt")?;
+
+        Ok(FoxtiveSetup {
+            env_prefix: self.env_prefix.unwrap_or_else(|| "APP".to_string()),
+            private_key,
+            public_key,
+            app_key: required(self.app_key, "app_key")?,
+            app_code,
+            app_name,
+            env: self.env,
+
+            #[cfg(feature = "jwt")]
+            jwt_iss_public_key,
+            #[cfg(feature = "jwt")]
+            jwt_token_lifetime,
+
+            #[cfg(feature = "cipher")]
+            cipher_key_id: self.cipher_key_id.unwrap_or(1),
+            #[cfg(feature = "cipher")]
+            cipher_previous_keys: self.cipher_previous_keys,
+
+            #[cfg(feature = "templating")]
+            template_directory: self.template_directory.unwrap_or_default(),
+            #[cfg(feature = "templating")]
+            template_setup: self.template_setup,
+
+            #[cfg(feature = "database")]
+            db_config: required(self.db_config, "with_db")?,
+
+            #[cfg(feature = "rabbitmq")]
+            rmq_config: required(self.rmq_config, "with_rabbitmq")?,
+
+            #[cfg(feature = "redis")]
+            redis_config: required(self.redis_config, "with_redis")?,
+
+            #[cfg(feature = "cache")]
+            cache_driver_setup: required(self.cache_driver_setup, "with_cache")?,
+
+            #[cfg(feature = "cache")]
+            cache_codec: self.cache_codec,
+
+            #[cfg(feature = "cache")]
+            cache_namespaced: !self.cache_without_namespace,
+
+            #[cfg(feature = "storage")]
+            storage_driver_setup: required(self.storage_driver_setup, "with_storage")?,
+
+            #[cfg(feature = "secrets")]
+            secrets: self.secrets,
+        })
+    }
+}
+
+/// Takes a setting out of the builder, `build` has already reported every missing one
+fn required<T>(value: Option<T>, name: &'static str) -> AppResult<T> {
+    value.ok_or_else(|| {
+        IncompleteSetupError {
+            missing: vec![name],
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_lists_missing_settings() {
+        let err = FoxtiveSetup::builder()
+            .env(Environment::Production)
+            .build()
+            .err()
+            .unwrap();
+        let err = err.downcast::<IncompleteSetupError>().unwrap();
+
+        assert_eq!(&err.missing[..2], &["app", "app_key"]);
+        #[cfg(feature = "redis")]
+        assert!(err.missing.contains(&"with_redis"));
+        assert!(
+            err.to_string()
+                .starts_with("incomplete Foxtive setup, missing: app, app_key")
+        );
+    }
+
+    #[cfg(all(
+        any(not(feature = "cache"), feature = "cache-in-memory"),
+        any(not(feature = "storage"), feature = "storage-local")
+    ))]
+    #[test]
+    fn test_build_with_enabled_features() {
+        let builder = FoxtiveSetup::builder()
+            .app("shop", "Shop")
+            .app_key("app-key")
+            .keys("private", "public")
+            .env(Environment::Staging);
+
+        #[cfg(feature = "jwt")]
+        let builder = builder.with_jwt("issuer-public", 60);
+        #[cfg(feature = "templating")]
+        let builder = builder.with_templates("templates/**/*");
+        #[cfg(feature = "database")]
+        let builder = builder.with_db(crate::database::DbConfig::create("postgres://localhost"));
+        #[cfg(feature = "redis")]
+        let builder = builder.with_redis(crate::redis::config::RedisConfig::create(
+            "redis://localhost",
+        ));
+        #[cfg(feature = "rabbitmq")]
+        let builder = builder.with_rabbitmq(crate::rabbitmq::config::RabbitmqConfig::create(
+            "amqp://localhost",
+        ));
+        #[cfg(feature = "cache-in-memory")]
+        let builder = builder.with_cache(CacheDriverSetup::InMemory(|| {
+            std::sync::Arc::new(crate::cache::drivers::InMemoryDriver::new())
+        }));
+        #[cfg(feature = "storage-local")]
+        let builder = builder.with_storage(StorageDriverSetup::Local(|| {
+            std::sync::Arc::new(crate::storage::drivers::LocalStorageDriver::new(
+                "storage",
+                "http://localhost",
+                "secret",
+            ))
+        }));
+
+        #[cfg(feature = "cipher")]
+        let builder = builder
+            .with_cipher_key_id(2)
+            .with_previous_cipher_key(1, "old-app-key");
+
+        let setup = builder.build().unwrap();
+        #[cfg(feature = "cipher")]
+        {
+            assert_eq!(setup.cipher_key_id, 2);
+            assert_eq!(setup.cipher_previous_keys, [(1, "old-app-key".to_string())]);
+        }
+        assert_eq!(setup.app_code, "shop");
+        assert_eq!(setup.app_name, "Shop");
+        assert_eq!(setup.env_prefix, "APP");
+        assert_eq!(setup.env, Environment::Staging);
+        assert_eq!(setup.private_key, "private");
+    }
+}
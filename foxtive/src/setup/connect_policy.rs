@@ -0,0 +1,119 @@
+use crate::results::AppResult;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How a service (database, Redis, RabbitMQ) is connected to while the state is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectPolicy {
+    /// Connect at boot, creating the state fails if the service is unreachable
+    #[default]
+    Required,
+    /// Don't connect at boot, the connection is established on first use
+    Lazy,
+    /// Boot without waiting for the service and keep trying to connect in the background,
+    /// waiting `initial_delay` after the first failure and doubling it up to `max_delay`
+    RetryWithBackoff {
+        initial_delay: Duration,
+        max_delay: Duration,
+    },
+}
+
+impl ConnectPolicy {
+    /// Retries in the background starting after 1 second, waiting at most 1 minute between attempts
+    pub fn retry_with_backoff() -> Self {
+        Self::RetryWithBackoff {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+
+    pub fn is_required(&self) -> bool {
+        matches!(self, Self::Required)
+    }
+
+    /// With [`ConnectPolicy::RetryWithBackoff`], calls `connect` in a background task until it succeeds
+    #[cfg_attr(
+        not(any(feature = "database", feature = "redis", feature = "rabbitmq")),
+        allow(dead_code)
+    )]
+    pub(crate) fn spawn_retry<F, Fut>(&self, service: &'static str, connect: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = AppResult<()>> + Send,
+    {
+        let Self::RetryWithBackoff {
+            initial_delay,
+            max_delay,
+        } = *self
+        else {
+            return;
+        };
+
+        tokio::spawn(async move {
+            let mut delay = initial_delay;
+            for attempt in 1.. {
+                match connect().await {
+                    Ok(()) => {
+                        info!("[{service}] connected on attempt {attempt}");
+                        return;
+                    }
+                    Err(err) => {
+                        warn!(
+                            "[{service}] connection attempt {attempt} failed: {err}, retrying in {delay:?}"
+                        );
+                        tokio::time::sleep(delay).await;
+                        delay = delay.saturating_mul(2).min(max_delay);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_spawn_retry_until_connected() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let policy = ConnectPolicy::RetryWithBackoff {
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(2),
+        };
+
+        let counter = attempts.clone();
+        policy.spawn_retry("test", move || {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                match attempt {
+                    3 => Ok(()),
+                    _ => Err(anyhow::anyhow!("unreachable")),
+                }
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_retry_ignores_other_policies() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        for policy in [ConnectPolicy::Required, ConnectPolicy::Lazy] {
+            let counter = attempts.clone();
+            policy.spawn_retry("test", move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Ok(()) }
+            });
+        }
+
+        tokio::task::yield_now().await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 0);
+        assert!(ConnectPolicy::default().is_required());
+    }
+}
@@ -3,6 +3,8 @@
 use crate::cache::{Cache, contract::CacheDriverContract};
 #[cfg(feature = "database")]
 use crate::database::create_db_pool;
+#[cfg(feature = "cipher")]
+use crate::helpers::cipher::Cipher;
 #[cfg(feature = "jwt")]
 use crate::helpers::jwt::Jwt;
 #[cfg(feature = "crypto")]
@@ -12,11 +14,20 @@ use crate::prelude::RabbitMQ;
 #[cfg(feature = "redis")]
 use crate::prelude::Redis;
 #[cfg(feature = "rabbitmq")]
+use crate::rabbitmq::RabbitMQOptions;
+#[cfg(feature = "rabbitmq")]
 use crate::rabbitmq::conn::create_rmq_conn_pool;
 #[cfg(feature = "redis")]
 use crate::redis::conn::create_redis_conn_pool;
 use crate::results::AppResult;
+#[cfg(feature = "secrets")]
+use crate::secrets::Secrets;
 use crate::setup::state::{FoxtiveHelpers, FoxtiveState};
+#[cfg(feature = "storage")]
+#[allow(unused_imports)]
+use crate::storage::{Storage, contract::StorageDriverContract};
+#[cfg(feature = "templating")]
+use crate::templating::TemplateSetup;
 use crate::{Environment, internal_server_error};
 use std::path::Path;
 #[allow(unused_imports)]
@@ -25,9 +36,19 @@ use std::sync::Arc;
 use tera::Tera;
 use tracing::{debug, info};
 
+mod builder;
+mod connect_policy;
 pub(crate) mod state;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod trace;
+mod trace_file;
 mod trace_layers;
+#[cfg(feature = "otel")]
+mod trace_otlp;
+
+pub use builder::{FoxtiveSetupBuilder, IncompleteSetupError};
+pub use connect_policy::ConnectPolicy;
 
 #[cfg(feature = "cache")]
 pub enum CacheDriverSetup {
@@ -37,6 +58,18 @@ pub enum CacheDriverSetup {
     Filesystem(fn() -> Arc<dyn CacheDriverContract>),
     #[cfg(feature = "cache-in-memory")]
     InMemory(fn() -> Arc<dyn CacheDriverContract>),
+    #[cfg(feature = "cache-memcached")]
+    Memcached(fn() -> Arc<dyn CacheDriverContract>),
+    #[cfg(feature = "cache-database")]
+    Database(fn(crate::database::DBPool) -> Arc<dyn CacheDriverContract>),
+}
+
+#[cfg(feature = "storage")]
+pub enum StorageDriverSetup {
+    #[cfg(feature = "storage-local")]
+    Local(fn() -> Arc<dyn StorageDriverContract>),
+    #[cfg(feature = "storage-s3")]
+    S3(fn() -> Arc<dyn StorageDriverContract>),
 }
 
 pub struct FoxtiveSetup {
@@ -54,8 +87,19 @@ pub struct FoxtiveSetup {
     #[cfg(feature = "jwt")]
     pub jwt_token_lifetime: i64,
 
+    /// Id of `app_key` in the ciphertexts, to change along with the app key
+    #[cfg(feature = "cipher")]
+    pub cipher_key_id: u8,
+    /// Previous app keys by id, still accepted when decrypting
+    #[cfg(feature = "cipher")]
+    pub cipher_previous_keys: Vec<(u8, String)>,
+
     #[cfg(feature = "templating")]
     pub template_directory: String,
+    /// Filters, functions and global values of the templates, `TemplateSetup::default()`
+    /// registers nothing
+    #[cfg(feature = "templating")]
+    pub template_setup: TemplateSetup,
 
     #[cfg(feature = "database")]
     pub db_config: crate::database::DbConfig,
@@ -68,8 +112,29 @@ pub struct FoxtiveSetup {
 
     #[cfg(feature = "cache")]
     pub cache_driver_setup: CacheDriverSetup,
+
+    /// Encoding of cached values, `CacheCodec::default()` stores plain JSON
+    #[cfg(feature = "cache")]
+    pub cache_codec: crate::cache::CacheCodec,
+
+    /// Prefixes cache keys with `{app_code}:{env}:`, see [`Cache::namespaced`]. Skipped for
+    /// the Redis driver when the Redis keys are prefixed already
+    #[cfg(feature = "cache")]
+    pub cache_namespaced: bool,
+
+    #[cfg(feature = "storage")]
+    pub storage_driver_setup: StorageDriverSetup,
+
+    /// Resolves the `${secret:NAME}` placeholders of the keys and connection strings,
+    /// `Secrets::default()` reads them from environment variables
+    #[cfg(feature = "secrets")]
+    pub secrets: Secrets,
 }
 
+/// Creates the state and sets it as the global `FOXTIVE` state.
+///
+/// Fails if the global state has already been set, use [`create_state`] to run
+/// several instances in the same process.
 pub async fn make_state(setup: FoxtiveSetup) -> AppResult<FoxtiveState> {
     debug!("Initializing Foxtive state for app: {}", setup.app_name);
     let foxtive = create_state(setup).await?;
@@ -83,49 +148,127 @@ pub async fn make_state(setup: FoxtiveSetup) -> AppResult<FoxtiveState> {
     Ok(foxtive)
 }
 
-async fn create_state(setup: FoxtiveSetup) -> AppResult<FoxtiveState> {
+/// Creates a state without setting the global `FOXTIVE` state.
+///
+/// The state can be passed around explicitly, e.g. one per tenant, and exposes the
+/// same accessors as `FOXTIVE` through [`AppStateExt`](crate::prelude::AppStateExt).
+pub async fn create_state(setup: FoxtiveSetup) -> AppResult<FoxtiveState> {
+    #[cfg(feature = "secrets")]
+    let (setup, secrets) = resolve_secrets(setup).await?;
+
     debug!("Creating helpers for app: {}", setup.app_code);
     let helpers = make_helpers(&setup);
 
     let env_prefix = setup.env_prefix;
 
     #[cfg(feature = "database")]
-    let database_pool = {
-        debug!("Initializing database pool");
-        create_db_pool(setup.db_config)
-    }?;
+    let (database_pool, database_connect_policy) = {
+        let policy = setup.db_config.connect_policy;
+        debug!("Initializing database pool ({policy:?})");
+        let pool = create_db_pool(setup.db_config)?;
+
+        let retry_pool = pool.clone();
+        policy.spawn_retry("database", move || {
+            let pool = retry_pool.clone();
+            async move {
+                crate::helpers::blk(move || pool.get().map(drop))
+                    .await?
+                    .map_err(anyhow::Error::msg)
+            }
+        });
 
-    #[cfg(feature = "redis")]
-    let (redis, redis_pool) = {
-        debug!("Initializing Redis connection pool");
+        (pool, policy)
+    };
 
+    #[cfg(feature = "redis")]
+    let (redis, redis_pool, redis_connect_policy) = {
+        let policy = setup.redis_config.connect_policy;
+        debug!("Initializing Redis connection pool ({policy:?})");
+
+        let key_prefix = setup
+            .redis_config
+            .key_prefix
+            .resolve(&setup.app_code)
+            .to_string();
         let redis_pool = create_redis_conn_pool(setup.redis_config)?;
-        let redis = Arc::new(Redis::new(redis_pool.clone()));
+        if policy.is_required() {
+            redis_pool.get().await?;
+        }
+
+        let retry_pool = redis_pool.clone();
+        policy.spawn_retry("redis", move || {
+            let pool = retry_pool.clone();
+            async move { pool.get().await.map(drop).map_err(anyhow::Error::msg) }
+        });
 
-        (redis, redis_pool)
+        let redis = Arc::new(Redis::new(redis_pool.clone()).with_prefix(&key_prefix));
+
+        (redis, redis_pool, policy)
     };
 
     #[cfg(feature = "rabbitmq")]
-    let (rabbitmq, rabbitmq_pool) = {
-        debug!("Initializing RabbitMQ connection pool");
+    let (rabbitmq, rabbitmq_pool, rabbitmq_connect_policy) = {
+        let policy = setup.rmq_config.connect_policy;
+        let options = RabbitMQOptions {
+            prefetch: setup.rmq_config.prefetch,
+            ..RabbitMQ::default_options()
+        };
+        debug!("Initializing RabbitMQ connection pool ({policy:?})");
         let rabbitmq_pool = create_rmq_conn_pool(setup.rmq_config).await?;
 
-        let rmq = Arc::new(tokio::sync::Mutex::new(
-            RabbitMQ::new(rabbitmq_pool.clone()).await?,
-        ));
-
-        (rmq, rabbitmq_pool)
+        let rmq = match policy.is_required() {
+            true => RabbitMQ::new_opt(rabbitmq_pool.clone(), options).await?,
+            false => RabbitMQ::new_lazy_opt(rabbitmq_pool.clone(), options),
+        };
+
+        let retry_pool = rabbitmq_pool.clone();
+        policy.spawn_retry("rabbitmq", move || {
+            let pool = retry_pool.clone();
+            async move { pool.get().await.map(drop).map_err(anyhow::Error::msg) }
+        });
+
+        (
+            Arc::new(tokio::sync::Mutex::new(rmq)),
+            rabbitmq_pool,
+            policy,
+        )
     };
 
     #[cfg(feature = "templating")]
-    let tera_templating = {
+    let (tera, templates) = {
         debug!(
             "Initializing Tera templating engine from: {}",
             setup.template_directory
         );
 
-        Tera::new(&setup.template_directory)
-    }?;
+        let hot_reload = setup.template_setup.hot_reload_interval();
+        let template_setup =
+            setup
+                .template_setup
+                .app_globals(&setup.app_name, &setup.app_code, setup.env.clone());
+        let templates = Arc::new(template_setup.apply(Tera::new(&setup.template_directory)?)?);
+
+        match hot_reload {
+            Some(interval) if setup.env.is_dev_like() => {
+                debug!("Watching templates for changes every {interval:?}");
+                templates.watch(&setup.template_directory, interval);
+            }
+            Some(_) => debug!("Template hot reload is disabled in {}", setup.env),
+            None => {}
+        }
+
+        let tera = templates.tera().clone();
+        (tera, templates)
+    };
+
+    // the Redis driver writes through the prefixed client, so its keys are already namespaced
+    #[cfg(feature = "cache")]
+    let driver_prefix: Option<String> = match &setup.cache_driver_setup {
+        #[cfg(feature = "cache-redis")]
+        CacheDriverSetup::Redis(_) => redis.prefix().map(str::to_string),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    };
 
     #[cfg(feature = "cache")]
     let cache_driver = {
@@ -146,6 +289,47 @@ async fn create_state(setup: FoxtiveSetup) -> AppResult<FoxtiveState> {
                 debug!("Using In-Memory cache driver");
                 setup_fn()
             }
+            #[cfg(feature = "cache-memcached")]
+            CacheDriverSetup::Memcached(setup_fn) => {
+                debug!("Using Memcached cache driver");
+                setup_fn()
+            }
+            #[cfg(feature = "cache-database")]
+            CacheDriverSetup::Database(setup_fn) => {
+                debug!("Using Database cache driver");
+                setup_fn(database_pool.clone())
+            }
+        }
+    };
+
+    #[cfg(feature = "cache")]
+    let cache = {
+        let cache = Cache::new(cache_driver).with_codec(setup.cache_codec);
+        match setup.cache_namespaced {
+            true => namespace_cache(
+                cache,
+                &setup.app_code,
+                setup.env.clone(),
+                driver_prefix.as_deref(),
+            ),
+            false => cache,
+        }
+    };
+
+    #[cfg(feature = "storage")]
+    let storage_driver = {
+        debug!("Setting up storage driver");
+        match setup.storage_driver_setup {
+            #[cfg(feature = "storage-local")]
+            StorageDriverSetup::Local(setup_fn) => {
+                debug!("Using Local storage driver");
+                setup_fn()
+            }
+            #[cfg(feature = "storage-s3")]
+            StorageDriverSetup::S3(setup_fn) => {
+                debug!("Using S3 storage driver");
+                setup_fn()
+            }
         }
     };
 
@@ -168,15 +352,27 @@ async fn create_state(setup: FoxtiveSetup) -> AppResult<FoxtiveState> {
         #[cfg(feature = "redis")]
         redis_pool,
         #[cfg(feature = "redis")]
+        redis_contract: redis.clone(),
+        #[cfg(feature = "redis")]
         redis,
+        #[cfg(feature = "redis")]
+        redis_connect_policy,
         #[cfg(feature = "database")]
         database: database_pool,
+        #[cfg(feature = "database")]
+        database_connect_policy,
         #[cfg(feature = "rabbitmq")]
         rabbitmq_pool,
         #[cfg(feature = "rabbitmq")]
+        rabbitmq_contract: rabbitmq.clone(),
+        #[cfg(feature = "rabbitmq")]
         rabbitmq,
+        #[cfg(feature = "rabbitmq")]
+        rabbitmq_connect_policy,
+        #[cfg(feature = "templating")]
+        tera,
         #[cfg(feature = "templating")]
-        tera: tera_templating,
+        templates,
 
         #[cfg(feature = "jwt")]
         jwt_iss_public_key: setup.jwt_iss_public_key,
@@ -185,10 +381,77 @@ async fn create_state(setup: FoxtiveSetup) -> AppResult<FoxtiveState> {
         jwt_token_lifetime: setup.jwt_token_lifetime,
 
         #[cfg(feature = "cache")]
-        cache: Arc::from(Cache::new(cache_driver)),
+        cache: Arc::from(cache),
+
+        #[cfg(feature = "storage")]
+        storage: Arc::from(Storage::new(storage_driver)),
+
+        #[cfg(feature = "events")]
+        events: Arc::new(crate::events::EventDispatcher::new()),
+
+        #[cfg(feature = "secrets")]
+        secrets,
     })
 }
 
+/// Replaces the secret placeholders of the setup and starts refreshing the secrets
+#[cfg(feature = "secrets")]
+async fn resolve_secrets(mut setup: FoxtiveSetup) -> AppResult<(FoxtiveSetup, Arc<Secrets>)> {
+    debug!("Resolving secrets: {:?}", setup.secrets);
+    let secrets = Arc::new(std::mem::take(&mut setup.secrets));
+
+    setup.app_key = secrets.resolve(&setup.app_key).await?;
+    setup.private_key = secrets.resolve(&setup.private_key).await?;
+    setup.public_key = secrets.resolve(&setup.public_key).await?;
+
+    #[cfg(feature = "jwt")]
+    {
+        setup.jwt_iss_public_key = secrets.resolve(&setup.jwt_iss_public_key).await?;
+    }
+
+    #[cfg(feature = "cipher")]
+    for (_, secret) in &mut setup.cipher_previous_keys {
+        *secret = secrets.resolve(secret).await?;
+    }
+
+    #[cfg(feature = "database")]
+    {
+        setup.db_config.dsn = secrets.resolve(&setup.db_config.dsn).await?;
+    }
+
+    #[cfg(feature = "redis")]
+    {
+        setup.redis_config.dsn = secrets.resolve(&setup.redis_config.dsn).await?;
+    }
+
+    #[cfg(feature = "rabbitmq")]
+    {
+        setup.rmq_config.dsn = secrets.resolve(&setup.rmq_config.dsn).await?;
+    }
+
+    secrets.spawn_refresh();
+
+    Ok((setup, secrets))
+}
+
+/// Namespaces the cache with `{app_code}:{env}:`, unless its driver already prefixes the keys
+/// with `driver_prefix`, which would prefix them twice
+#[cfg(feature = "cache")]
+fn namespace_cache(
+    cache: Cache,
+    app_code: &str,
+    env: Environment,
+    driver_prefix: Option<&str>,
+) -> Cache {
+    match driver_prefix {
+        Some(prefix) => {
+            debug!("Cache keys are already prefixed with '{prefix}:', skipping the namespace");
+            cache
+        }
+        None => cache.namespaced(app_code, env),
+    }
+}
+
 #[allow(unused_variables)]
 fn make_helpers(setup: &FoxtiveSetup) -> FoxtiveHelpers {
     #[cfg(feature = "crypto")]
@@ -210,9 +473,20 @@ fn make_helpers(setup: &FoxtiveSetup) -> FoxtiveHelpers {
         )
     };
 
+    #[cfg(feature = "cipher")]
+    let cipher_helper = {
+        debug!("Creating cipher helper with key {}", setup.cipher_key_id);
+        setup.cipher_previous_keys.iter().fold(
+            Cipher::new(setup.cipher_key_id, &setup.app_key),
+            |cipher, (key_id, secret)| cipher.with_previous_key(*key_id, secret),
+        )
+    };
+
     FoxtiveHelpers {
         #[cfg(feature = "jwt")]
         jwt: Arc::new(jwt_helper),
+        #[cfg(feature = "cipher")]
+        cipher: Arc::new(cipher_helper),
         #[cfg(feature = "crypto")]
         password: Arc::new(pwd_helper),
     }
@@ -249,3 +523,42 @@ pub fn load_environment_variables(service: &str) {
         dotenv::from_filename(filename).ok();
     }
 }
+
+#[cfg(all(test, feature = "cache-redis"))]
+mod tests {
+    use super::*;
+    use crate::cache::drivers::RedisCacheDriver;
+    use deadpool_redis::{Config, Runtime};
+
+    #[tokio::test]
+    async fn test_redis_cache_keys_are_prefixed_once() {
+        let redis_url = std::env::var("TEST_REDIS_DSN")
+            .or_else(|_| std::env::var("REDIS_DSN"))
+            .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let pool = Config::from_url(redis_url)
+            .create_pool(Some(Runtime::Tokio1))
+            .unwrap();
+        let redis = Arc::new(Redis::new(pool).with_prefix("shop"));
+        if let Err(e) = redis.flush_db().await {
+            eprintln!("Skipping Redis test - no connection available: {e}");
+            return;
+        }
+
+        let cache = Cache::new(Arc::new(RedisCacheDriver::new(redis.clone())));
+        let cache = namespace_cache(cache, "shop", Environment::Staging, redis.prefix());
+        cache.put("user:1", &"john").await.unwrap();
+
+        let mut keys = redis.raw().keys().await.unwrap();
+        keys.sort();
+        assert_eq!(keys, ["shop:user:1"]);
+        assert_eq!(
+            cache.get::<String>("user:1").await.unwrap().as_deref(),
+            Some("john")
+        );
+
+        let cache = Cache::new(Arc::new(RedisCacheDriver::new(Arc::new(redis.raw()))));
+        let cache = namespace_cache(cache, "shop", Environment::Staging, None);
+        cache.put("user:2", &"jane").await.unwrap();
+        assert!(redis.raw().exists("shop:staging:user:2").await.unwrap());
+    }
+}
@@ -3,6 +3,8 @@ use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
 use crate::Environment;
+#[cfg(feature = "cipher")]
+use crate::helpers::cipher::Cipher;
 #[cfg(feature = "jwt")]
 use crate::helpers::jwt::Jwt;
 #[cfg(feature = "crypto")]
@@ -38,17 +40,30 @@ pub struct FoxtiveState {
     #[cfg(feature = "database")]
     /// The database connection pool.
     pub(crate) database: crate::database::DBPool,
+    #[cfg(feature = "database")]
+    /// How the database was connected to at boot.
+    pub database_connect_policy: crate::setup::ConnectPolicy,
 
     #[cfg(feature = "templating")]
-    /// The Tera template engine.
+    /// The Tera template engine, as loaded at startup.
     pub(crate) tera: Tera,
 
+    #[cfg(feature = "templating")]
+    /// The Tera templates and their global values, reloaded on changes.
+    pub(crate) templates: Arc<crate::templating::Templates>,
+
     #[cfg(feature = "redis")]
     /// The Redis connection pool.
     pub(crate) redis_pool: deadpool_redis::Pool,
     #[cfg(feature = "redis")]
     /// The Redis client.
     pub(crate) redis: Arc<Redis>,
+    #[cfg(feature = "redis")]
+    /// The Redis client behind its contract, a mock in test states.
+    pub(crate) redis_contract: Arc<dyn crate::redis::RedisContract>,
+    #[cfg(feature = "redis")]
+    /// How Redis was connected to at boot.
+    pub redis_connect_policy: crate::setup::ConnectPolicy,
 
     #[cfg(feature = "rabbitmq")]
     /// The RabbitMQ connection pool.
@@ -56,6 +71,12 @@ pub struct FoxtiveState {
     #[cfg(feature = "rabbitmq")]
     /// The RabbitMQ client.
     pub rabbitmq: Arc<tokio::sync::Mutex<RabbitMQ>>,
+    #[cfg(feature = "rabbitmq")]
+    /// The RabbitMQ client behind its contract, a mock in test states.
+    pub(crate) rabbitmq_contract: Arc<dyn crate::rabbitmq::RabbitContract>,
+    #[cfg(feature = "rabbitmq")]
+    /// How RabbitMQ was connected to at boot.
+    pub rabbitmq_connect_policy: crate::setup::ConnectPolicy,
 
     /// The public key for the JWT issuer.
     #[cfg(feature = "jwt")]
@@ -69,6 +90,18 @@ pub struct FoxtiveState {
     /// The cache client.
     pub cache: Arc<crate::cache::Cache>,
 
+    #[cfg(feature = "storage")]
+    /// The object storage client.
+    pub storage: Arc<crate::storage::Storage>,
+
+    #[cfg(feature = "events")]
+    /// The in-process event dispatcher.
+    pub events: Arc<crate::events::EventDispatcher>,
+
+    #[cfg(feature = "secrets")]
+    /// The secrets resolved at boot, refreshed when configured.
+    pub secrets: Arc<crate::secrets::Secrets>,
+
     /// A collection of helper utilities.
     pub helpers: FoxtiveHelpers,
 }
@@ -77,6 +110,8 @@ pub struct FoxtiveState {
 pub struct FoxtiveHelpers {
     #[cfg(feature = "jwt")]
     pub jwt: Arc<Jwt>,
+    #[cfg(feature = "cipher")]
+    pub cipher: Arc<Cipher>,
     #[cfg(feature = "crypto")]
     pub password: Arc<Password>,
 }
@@ -92,34 +127,62 @@ impl FoxtiveState {
         self.redis.clone()
     }
 
+    #[cfg(feature = "redis")]
+    pub fn redis_contract(&self) -> Arc<dyn crate::redis::RedisContract> {
+        self.redis_contract.clone()
+    }
+
     #[cfg(feature = "rabbitmq")]
     pub fn rabbitmq(&self) -> Arc<tokio::sync::Mutex<RabbitMQ>> {
         Arc::clone(&self.rabbitmq)
     }
 
+    #[cfg(feature = "rabbitmq")]
+    pub fn rabbitmq_contract(&self) -> Arc<dyn crate::rabbitmq::RabbitContract> {
+        self.rabbitmq_contract.clone()
+    }
+
     pub fn title(&self, text: &str) -> String {
         format!("{} - {}", text, self.app_name)
     }
 
+    /// Renders `file` with the global values of the templates, adding the `.tera.html`
+    /// extension if it is missing
     #[cfg(feature = "templating")]
     pub fn render(
         &self,
         file: impl Into<String>,
         context: &Context,
     ) -> crate::results::AppResult<String> {
-        let mut file = file.into();
-        if !file.ends_with(".tera.html") {
-            file.push_str(".tera.html");
-        }
-
-        self.tera.render(&file, context).map_err(crate::Error::msg)
+        self.templates.render(file, context)
     }
 
     /// Get the Tera template engine.
+    ///
+    /// This is the engine as loaded at startup, templates reloaded since then are
+    /// rendered by [`render`](Self::render) and available from [`templates`](Self::templates).
     #[cfg(feature = "templating")]
     pub fn tera(&self) -> &Tera {
         &self.tera
     }
+
+    /// Get the templates, e.g. to reload them or to use the reloaded engine.
+    #[cfg(feature = "templating")]
+    pub fn templates(&self) -> Arc<crate::templating::Templates> {
+        self.templates.clone()
+    }
+
+    /// Checks the dependencies enabled in the state (database, Redis, RabbitMQ, cache)
+    /// and aggregates their health.
+    ///
+    /// Use [`HealthChecker::from_state`](crate::health::HealthChecker::from_state) to add
+    /// indicators of your own.
+    #[cfg(feature = "health")]
+    pub async fn health_report(&self) -> crate::health::HealthReport {
+        crate::health::HealthChecker::from_state(self)
+            .readiness()
+            .await
+    }
 }
 
 impl Debug for FoxtiveState {
@@ -0,0 +1,638 @@
+//! Building application state for tests, without a live stack.
+//!
+//! [`FoxtiveState::test`] returns a [`FoxtiveTestBuilder`] creating a state where:
+//!
+//! - the database, Redis and RabbitMQ clients point to local defaults and only connect
+//!   when a connection is requested ([`ConnectPolicy::Lazy`]), so code that never touches
+//!   them runs without servers
+//! - the cache uses the in-memory driver (or the filesystem one, in a temporary directory)
+//! - the storage uses a temporary directory when the `storage-local` feature is enabled
+//!
+//! [`FoxtiveState::install`] makes `FOXTIVE` resolve to the test state, without setting
+//! the global state, until the returned guard is dropped. The state is scoped to the Tokio
+//! runtime of the test, `#[tokio::test]` creating one per test, so tests run in parallel
+//! with their own state. Code running on the runtime sees it from any thread, including
+//! `spawn_blocking`, while threads spawned outside of it must enter it first with
+//! [`Handle::enter`](tokio::runtime::Handle::enter). Without a runtime, the state is
+//! scoped to the installing thread.
+//!
+//! ## Features
+//!
+//! This module requires the `testing` feature to be enabled, typically in `dev-dependencies`.
+//! It enables `test-utils` as well, for the mocks to pass to the builder.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::FOXTIVE;
+//! use foxtive::FoxtiveState;
+//! use foxtive::prelude::AppStateExt;
+//!
+//! fn page_title(text: &str) -> String {
+//!     FOXTIVE.app().title(text)
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let state = FoxtiveState::test().app_name("Shop").build().await.unwrap();
+//! let _guard = state.install();
+//!
+//! assert_eq!(page_title("Cart"), "Cart - Shop");
+//! # });
+//! ```
+
+#[cfg(feature = "cache")]
+use crate::cache::{Cache, contract::CacheDriverContract};
+#[cfg(feature = "cipher")]
+use crate::helpers::cipher::Cipher;
+#[cfg(feature = "jwt")]
+use crate::helpers::jwt::Jwt;
+#[cfg(feature = "crypto")]
+use crate::helpers::password::Password;
+#[cfg(feature = "rabbitmq")]
+use crate::prelude::RabbitMQ;
+#[cfg(feature = "redis")]
+use crate::prelude::Redis;
+use crate::results::AppResult;
+#[allow(unused_imports)]
+use crate::setup::ConnectPolicy;
+#[cfg(feature = "storage")]
+use crate::storage::{Storage, contract::StorageDriverContract};
+use crate::{Environment, FoxtiveHelpers, FoxtiveState};
+use std::collections::HashMap;
+#[allow(unused_imports)]
+use std::path::PathBuf;
+#[allow(unused_imports)]
+use std::sync::Arc;
+use std::sync::{LazyLock, RwLock};
+use std::thread::ThreadId;
+#[cfg(feature = "templating")]
+use tera::Tera;
+
+static INSTALLED: LazyLock<RwLock<HashMap<Scope, &'static FoxtiveState>>> =
+    LazyLock::new(Default::default);
+
+/// What an installed state is visible to, the runtime of the test or its thread when it has none
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Scope {
+    Runtime(tokio::runtime::Id),
+    Thread(ThreadId),
+}
+
+impl Scope {
+    fn current() -> Self {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => Scope::Runtime(handle.id()),
+            Err(_) => Scope::Thread(std::thread::current().id()),
+        }
+    }
+}
+
+/// The state installed for the current runtime or thread, if any
+pub(crate) fn installed() -> Option<&'static FoxtiveState> {
+    INSTALLED
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&Scope::current())
+        .copied()
+}
+
+/// Keeps a test state installed, the previously installed state of the same runtime or
+/// thread (if any) is restored when the guard is dropped.
+pub struct TestStateGuard {
+    scope: Scope,
+    state: &'static FoxtiveState,
+    previous: Option<&'static FoxtiveState>,
+}
+
+impl TestStateGuard {
+    pub fn state(&self) -> &'static FoxtiveState {
+        self.state
+    }
+}
+
+impl Drop for TestStateGuard {
+    fn drop(&mut self) {
+        let mut installed = INSTALLED.write().unwrap_or_else(|e| e.into_inner());
+        match self.previous {
+            Some(previous) => installed.insert(self.scope, previous),
+            None => installed.remove(&self.scope),
+        };
+    }
+}
+
+impl FoxtiveState {
+    /// Starts building a state for tests, see the [`testing`](crate::setup::testing) module
+    pub fn test() -> FoxtiveTestBuilder {
+        FoxtiveTestBuilder::default()
+    }
+
+    /// Makes `FOXTIVE` resolve to this state on the current Tokio runtime (or thread,
+    /// outside of a runtime) until the guard is dropped.
+    ///
+    /// The state is leaked to be usable as `FOXTIVE`, which is fine for tests.
+    pub fn install(self) -> TestStateGuard {
+        let scope = Scope::current();
+        let state: &'static FoxtiveState = Box::leak(Box::new(self));
+        let previous = INSTALLED
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(scope, state);
+
+        TestStateGuard {
+            scope,
+            state,
+            previous,
+        }
+    }
+}
+
+/// Builds a [`FoxtiveState`] for tests, created with [`FoxtiveState::test`].
+pub struct FoxtiveTestBuilder {
+    env: Environment,
+    env_prefix: String,
+    app_code: String,
+    app_name: String,
+    app_key: String,
+
+    #[cfg(feature = "database")]
+    database_dsn: Option<String>,
+    #[cfg(feature = "redis")]
+    redis_dsn: String,
+    #[cfg(feature = "redis")]
+    redis_contract: Option<Arc<dyn crate::redis::RedisContract>>,
+    #[cfg(feature = "rabbitmq")]
+    rabbitmq_dsn: String,
+    #[cfg(feature = "rabbitmq")]
+    rabbitmq_contract: Option<Arc<dyn crate::rabbitmq::RabbitContract>>,
+    #[cfg(feature = "templating")]
+    tera: Option<Tera>,
+    #[cfg(feature = "cache")]
+    cache_driver: Option<Arc<dyn CacheDriverContract>>,
+    #[cfg(feature = "storage")]
+    storage_driver: Option<Arc<dyn StorageDriverContract>>,
+    #[cfg(feature = "secrets")]
+    secrets: crate::secrets::Secrets,
+}
+
+impl Default for FoxtiveTestBuilder {
+    fn default() -> Self {
+        Self {
+            env: Environment::Local,
+            env_prefix: "TEST".to_string(),
+            app_code: "test".to_string(),
+            app_name: "Foxtive Test".to_string(),
+            app_key: "foxtive-test-app-key".to_string(),
+
+            #[cfg(feature = "database")]
+            database_dsn: None,
+            #[cfg(feature = "redis")]
+            redis_dsn: "redis://127.0.0.1:6379".to_string(),
+            #[cfg(feature = "redis")]
+            redis_contract: None,
+            #[cfg(feature = "rabbitmq")]
+            rabbitmq_dsn: "amqp://127.0.0.1:5672/%2f".to_string(),
+            #[cfg(feature = "rabbitmq")]
+            rabbitmq_contract: None,
+            #[cfg(feature = "templating")]
+            tera: None,
+            #[cfg(feature = "cache")]
+            cache_driver: None,
+            #[cfg(feature = "storage")]
+            storage_driver: None,
+            #[cfg(feature = "secrets")]
+            secrets: crate::secrets::Secrets::default(),
+        }
+    }
+}
+
+impl FoxtiveTestBuilder {
+    pub fn env(mut self, env: Environment) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn env_prefix(mut self, env_prefix: &str) -> Self {
+        self.env_prefix = env_prefix.to_string();
+        self
+    }
+
+    pub fn app_code(mut self, app_code: &str) -> Self {
+        self.app_code = app_code.to_string();
+        self
+    }
+
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.app_name = app_name.to_string();
+        self
+    }
+
+    pub fn app_key(mut self, app_key: &str) -> Self {
+        self.app_key = app_key.to_string();
+        self
+    }
+
+    /// Connects to a real database, e.g. a temporary one created for the test run.
+    ///
+    /// Without it, the pool points to `postgres://localhost/foxtive_test` and only
+    /// connects when a connection is requested.
+    #[cfg(feature = "database")]
+    pub fn database_dsn(mut self, dsn: &str) -> Self {
+        self.database_dsn = Some(dsn.to_string());
+        self
+    }
+
+    /// Redis server used when a connection is requested, `redis://127.0.0.1:6379` by default
+    #[cfg(feature = "redis")]
+    pub fn redis_dsn(mut self, dsn: &str) -> Self {
+        self.redis_dsn = dsn.to_string();
+        self
+    }
+
+    /// RabbitMQ broker used when a connection is requested, `amqp://127.0.0.1:5672/%2f` by default
+    #[cfg(feature = "rabbitmq")]
+    pub fn rabbitmq_dsn(mut self, dsn: &str) -> Self {
+        self.rabbitmq_dsn = dsn.to_string();
+        self
+    }
+
+    /// Replaces the client returned by `redis_contract()`, e.g. with a
+    /// [`MockRedis`](crate::test_utils::MockRedis) from the `test-utils` feature
+    #[cfg(feature = "redis")]
+    pub fn redis_contract(mut self, redis: Arc<dyn crate::redis::RedisContract>) -> Self {
+        self.redis_contract = Some(redis);
+        self
+    }
+
+    /// Replaces the client returned by `rabbitmq_contract()`, e.g. with a
+    /// [`MockRabbit`](crate::test_utils::MockRabbit) from the `test-utils` feature
+    #[cfg(feature = "rabbitmq")]
+    pub fn rabbitmq_contract(mut self, rabbitmq: Arc<dyn crate::rabbitmq::RabbitContract>) -> Self {
+        self.rabbitmq_contract = Some(rabbitmq);
+        self
+    }
+
+    /// Templates to render, no template is loaded by default
+    #[cfg(feature = "templating")]
+    pub fn templates(mut self, tera: Tera) -> Self {
+        self.tera = Some(tera);
+        self
+    }
+
+    /// Replaces the default cache driver, e.g. with a Redis one for integration tests
+    #[cfg(feature = "cache")]
+    pub fn cache_driver(mut self, driver: Arc<dyn CacheDriverContract>) -> Self {
+        self.cache_driver = Some(driver);
+        self
+    }
+
+    /// Replaces the default storage driver
+    #[cfg(feature = "storage")]
+    pub fn storage_driver(mut self, driver: Arc<dyn StorageDriverContract>) -> Self {
+        self.storage_driver = Some(driver);
+        self
+    }
+
+    /// Replaces the default secrets, read from environment variables
+    #[cfg(feature = "secrets")]
+    pub fn secrets(mut self, secrets: crate::secrets::Secrets) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    pub async fn build(self) -> AppResult<FoxtiveState> {
+        #[cfg(feature = "jwt")]
+        let (public_key, private_key) = Jwt::dummy_keys();
+        #[cfg(not(feature = "jwt"))]
+        let (public_key, private_key) = (String::new(), String::new());
+
+        let helpers = FoxtiveHelpers {
+            #[cfg(feature = "jwt")]
+            jwt: Arc::new(Jwt::new(public_key.clone(), private_key.clone(), 60)),
+            #[cfg(feature = "cipher")]
+            cipher: Arc::new(Cipher::new(1, &self.app_key)),
+            #[cfg(feature = "crypto")]
+            password: Arc::new(Password::new(self.app_key.clone())),
+        };
+
+        #[cfg(feature = "database")]
+        let (database, database_connect_policy) = {
+            let (dsn, policy) = match &self.database_dsn {
+                Some(dsn) => (dsn.as_str(), ConnectPolicy::Required),
+                None => ("postgres://localhost/foxtive_test", ConnectPolicy::Lazy),
+            };
+
+            let config = crate::database::DbConfig::create(dsn).connect_policy(policy);
+            (crate::database::create_db_pool(config)?, policy)
+        };
+
+        #[cfg(feature = "redis")]
+        let (redis, redis_pool) = {
+            let config = crate::redis::config::RedisConfig::create(&self.redis_dsn)
+                .connect_policy(ConnectPolicy::Lazy);
+            let redis_pool = crate::redis::conn::create_redis_conn_pool(config)?;
+            (Arc::new(Redis::new(redis_pool.clone())), redis_pool)
+        };
+
+        #[cfg(feature = "rabbitmq")]
+        let (rabbitmq, rabbitmq_pool) = {
+            let config = crate::rabbitmq::config::RabbitmqConfig::create(&self.rabbitmq_dsn)
+                .connect_policy(ConnectPolicy::Lazy);
+            let rabbitmq_pool = crate::rabbitmq::conn::create_rmq_conn_pool(config).await?;
+            let rabbitmq = RabbitMQ::new_lazy(rabbitmq_pool.clone());
+            (Arc::new(tokio::sync::Mutex::new(rabbitmq)), rabbitmq_pool)
+        };
+
+        #[cfg(feature = "cache")]
+        #[allow(unused_mut)]
+        let cache_driver = {
+            let mut driver = self.cache_driver;
+
+            #[cfg(feature = "cache-in-memory")]
+            if driver.is_none() {
+                driver = Some(Arc::new(crate::cache::drivers::InMemoryDriver::new()));
+            }
+
+            #[cfg(feature = "cache-filesystem")]
+            if driver.is_none() {
+                driver = Some(Arc::new(crate::cache::drivers::FilesystemCacheDriver::new(
+                    temp_dir("cache"),
+                )));
+            }
+
+            #[cfg(feature = "cache-redis")]
+            if driver.is_none() {
+                driver = Some(Arc::new(crate::cache::drivers::RedisCacheDriver::new(
+                    redis.clone(),
+                )));
+            }
+
+            driver.ok_or_else(|| {
+                anyhow::anyhow!("no cache driver enabled, set one with `cache_driver`")
+            })?
+        };
+
+        #[cfg(feature = "storage")]
+        #[allow(unused_mut)]
+        let storage_driver = {
+            let mut driver = self.storage_driver;
+
+            #[cfg(feature = "storage-local")]
+            if driver.is_none() {
+                driver = Some(Arc::new(crate::storage::drivers::LocalStorageDriver::new(
+                    temp_dir("storage"),
+                    "http://localhost/storage",
+                    &self.app_key,
+                )));
+            }
+
+            driver.ok_or_else(|| {
+                anyhow::anyhow!("no default storage driver, set one with `storage_driver`")
+            })?
+        };
+
+        #[cfg(feature = "templating")]
+        let templates = Arc::new(
+            crate::templating::TemplateSetup::new()
+                .app_globals(&self.app_name, &self.app_code, self.env.clone())
+                .apply(self.tera.unwrap_or_default())?,
+        );
+        #[cfg(feature = "templating")]
+        let tera = templates.tera().clone();
+
+        Ok(FoxtiveState {
+            helpers,
+            env: self.env,
+            app_env_prefix: self.env_prefix,
+            app_code: self.app_code,
+            app_name: self.app_name,
+            app_key: self.app_key,
+            app_public_key: public_key.clone(),
+            app_private_key: private_key,
+
+            #[cfg(feature = "redis")]
+            redis_pool,
+            #[cfg(feature = "redis")]
+            redis_contract: self.redis_contract.unwrap_or_else(|| redis.clone()),
+            #[cfg(feature = "redis")]
+            redis,
+            #[cfg(feature = "redis")]
+            redis_connect_policy: ConnectPolicy::Lazy,
+            #[cfg(feature = "database")]
+            database,
+            #[cfg(feature = "database")]
+            database_connect_policy,
+            #[cfg(feature = "rabbitmq")]
+            rabbitmq_pool,
+            #[cfg(feature = "rabbitmq")]
+            rabbitmq_contract: self.rabbitmq_contract.unwrap_or_else(|| rabbitmq.clone()),
+            #[cfg(feature = "rabbitmq")]
+            rabbitmq,
+            #[cfg(feature = "rabbitmq")]
+            rabbitmq_connect_policy: ConnectPolicy::Lazy,
+            #[cfg(feature = "templating")]
+            tera,
+            #[cfg(feature = "templating")]
+            templates,
+
+            #[cfg(feature = "jwt")]
+            jwt_iss_public_key: public_key,
+            #[cfg(feature = "jwt")]
+            jwt_token_lifetime: 60,
+
+            #[cfg(feature = "cache")]
+            cache: Arc::new(Cache::new(cache_driver)),
+
+            #[cfg(feature = "storage")]
+            storage: Arc::new(Storage::new(storage_driver)),
+
+            #[cfg(feature = "events")]
+            events: Arc::new(crate::events::EventDispatcher::new()),
+
+            #[cfg(feature = "secrets")]
+            secrets: Arc::new(self.secrets),
+        })
+    }
+}
+
+/// A unique directory under the system temporary directory
+#[allow(dead_code)]
+fn temp_dir(kind: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "foxtive-test-{kind}-{}",
+        crate::helpers::id::ulid()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FOXTIVE;
+    use crate::prelude::AppStateExt;
+
+    #[tokio::test]
+    async fn test_install_scopes_state_to_the_guard() {
+        assert!(!FOXTIVE.is_initialized());
+
+        let outer = FoxtiveState::test()
+            .app_name("Outer")
+            .build()
+            .await
+            .unwrap();
+        let outer = outer.install();
+        assert!(FOXTIVE.is_initialized());
+        assert_eq!(FOXTIVE.app().app_name, "Outer");
+
+        {
+            let inner = FoxtiveState::test()
+                .app_name("Inner")
+                .env(Environment::Staging)
+                .build()
+                .await
+                .unwrap();
+            let _inner = inner.install();
+            assert_eq!(FOXTIVE.app().title("Home"), "Home - Inner");
+            assert_eq!(FOXTIVE.env(), Environment::Staging);
+        }
+
+        assert_eq!(FOXTIVE.app().app_name, "Outer");
+        assert_eq!(outer.state().app_name, "Outer");
+
+        drop(outer);
+        assert!(!FOXTIVE.is_initialized());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_installed_state_is_visible_from_other_threads() {
+        let _guard = FoxtiveState::test()
+            .app_name("Shared")
+            .build()
+            .await
+            .unwrap()
+            .install();
+
+        let blocking = tokio::task::spawn_blocking(|| FOXTIVE.app().app_name.clone());
+        let spawned = tokio::spawn(async { FOXTIVE.app().app_name.clone() });
+        let runtime = tokio::runtime::Handle::current();
+        let thread = std::thread::spawn(move || {
+            let _runtime = runtime.enter();
+            FOXTIVE.app().app_name.clone()
+        });
+
+        assert_eq!(blocking.await.unwrap(), "Shared");
+        assert_eq!(spawned.await.unwrap(), "Shared");
+        assert_eq!(thread.join().unwrap(), "Shared");
+    }
+
+    #[test]
+    fn test_parallel_tests_see_their_own_state() {
+        let barrier = Arc::new(std::sync::Barrier::new(3));
+
+        let tests = ["First", "Second"].map(|name| {
+            let barrier = barrier.clone();
+            std::thread::spawn(move || {
+                let runtime = tokio::runtime::Runtime::new().unwrap();
+                runtime.block_on(async {
+                    let state = FoxtiveState::test().app_name(name).build().await.unwrap();
+                    let _guard = state.install();
+
+                    // both states are installed at the same time
+                    barrier.wait();
+                    let spawned = tokio::spawn(async { FOXTIVE.app().app_name.clone() });
+                    assert_eq!(spawned.await.unwrap(), name);
+                    barrier.wait();
+                });
+            })
+        });
+
+        barrier.wait();
+        // a thread that installed nothing doesn't see them
+        assert!(!FOXTIVE.is_initialized());
+        barrier.wait();
+
+        for test in tests {
+            test.join().unwrap();
+        }
+    }
+
+    #[cfg(feature = "cache-in-memory")]
+    #[tokio::test]
+    async fn test_default_cache_is_in_memory() {
+        let _guard = FoxtiveState::test().build().await.unwrap().install();
+
+        FOXTIVE.cache().put("greeting", &"hello").await.unwrap();
+        let value: Option<String> = FOXTIVE.cache().get("greeting").await.unwrap();
+        assert_eq!(value.as_deref(), Some("hello"));
+    }
+
+    #[cfg(feature = "cache-in-memory")]
+    #[tokio::test]
+    async fn test_explicit_states_are_isolated() {
+        let first = FoxtiveState::test()
+            .app_code("first")
+            .build()
+            .await
+            .unwrap();
+        let second = FoxtiveState::test()
+            .app_code("second")
+            .build()
+            .await
+            .unwrap();
+
+        first.cache().put("tenant", &"first").await.unwrap();
+        let value: Option<String> = second.cache().get("tenant").await.unwrap();
+
+        assert!(value.is_none());
+        assert_eq!(first.app_code(), "first");
+        assert_eq!(second.app_code(), "second");
+        assert!(!FOXTIVE.is_initialized());
+    }
+
+    #[cfg(feature = "templating")]
+    #[tokio::test]
+    async fn test_templates_have_app_globals() {
+        let mut tera = tera::Tera::default();
+        tera.add_raw_template("title.tera.html", "{{ app_name }} ({{ env }})")
+            .unwrap();
+
+        let state = FoxtiveState::test()
+            .app_name("Shop")
+            .templates(tera)
+            .build()
+            .await
+            .unwrap();
+
+        let title = state.render("title", &tera::Context::new()).unwrap();
+        assert_eq!(title, "Shop (local)");
+    }
+
+    #[cfg(feature = "redis")]
+    #[tokio::test]
+    async fn test_redis_pool_is_lazy() {
+        let state = FoxtiveState::test()
+            .redis_dsn("redis://127.0.0.1:1")
+            .build()
+            .await
+            .unwrap();
+
+        assert!(state.redis().redis().await.is_err());
+    }
+
+    #[cfg(all(feature = "redis", feature = "test-utils"))]
+    #[tokio::test]
+    async fn test_redis_contract_can_be_mocked() {
+        let mock = Arc::new(crate::test_utils::MockRedis::new());
+        let _guard = FoxtiveState::test()
+            .redis_contract(mock.clone())
+            .build()
+            .await
+            .unwrap()
+            .install();
+
+        FOXTIVE
+            .redis_contract()
+            .publish_json("events", &"signed-up")
+            .await
+            .unwrap();
+        assert_eq!(mock.published("events"), [r#""signed-up""#]);
+    }
+}
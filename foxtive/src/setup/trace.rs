@@ -1,10 +1,18 @@
 use crate::internal_server_error;
 use crate::prelude::{AppMessage, AppResult};
-use crate::setup::trace_layers::EventCallbackLayer;
+use crate::setup::trace_file::open_append;
+pub use crate::setup::trace_file::{RollingFile, Rotation};
+use crate::setup::trace_layers::{EventCallbackLayer, otel_layer};
+#[cfg(feature = "otel")]
+pub use crate::setup::trace_otlp::{OtlpConfig, shutdown_otlp};
+use std::io::Write;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tracing::Level;
+#[cfg(feature = "trace-non-blocking")]
+use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::filter::EnvFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
@@ -22,6 +30,12 @@ pub struct Tracing {
     pub include_thread_names: bool,
     pub enable_ansi: bool,
     pub on_logger_event: Option<TracingEventHandler>,
+    /// Writes the events from a background thread, see [`TracingGuard`]
+    #[cfg(feature = "trace-non-blocking")]
+    pub non_blocking: bool,
+    /// Exports spans over OTLP alongside the output layer
+    #[cfg(feature = "otel")]
+    pub otlp: Option<OtlpConfig>,
 }
 
 #[derive(Debug, Clone)]
@@ -37,11 +51,40 @@ pub enum OutputTarget {
     Stdout,
     Stderr,
     File(String),
+    RollingFile(RollingFile),
+}
+
+/// Returned by [`init_tracing_with_guard`], flushes the events buffered by the non-blocking
+/// writer when dropped.
+///
+/// Keep it alive until the application exits, e.g. `let _guard = init_tracing_with_guard(config)?;`
+/// in `main`, events written after it is dropped are lost.
+#[derive(Default)]
+#[must_use = "events buffered by the non-blocking writer are lost once the guard is dropped"]
+pub struct TracingGuard {
+    #[cfg(feature = "trace-non-blocking")]
+    _worker: Option<WorkerGuard>,
+}
+
+impl OutputTarget {
+    fn writer(self) -> std::io::Result<Box<dyn Write + Send>> {
+        Ok(match self {
+            OutputTarget::Stdout => Box::new(std::io::stdout()),
+            OutputTarget::Stderr => Box::new(std::io::stderr()),
+            OutputTarget::File(path) => Box::new(open_append(path.as_ref())?),
+            OutputTarget::RollingFile(file) => Box::new(file.open()?),
+        })
+    }
+
+    fn is_terminal(&self) -> bool {
+        matches!(self, OutputTarget::Stdout | OutputTarget::Stderr)
+    }
 }
 
 impl std::fmt::Debug for Tracing {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("TracingConfig")
+        let mut debug = f.debug_struct("TracingConfig");
+        debug
             .field("level", &self.level)
             .field("format", &self.format)
             .field("target", &self.target)
@@ -54,8 +97,15 @@ impl std::fmt::Debug for Tracing {
             .field(
                 "on_event",
                 &self.on_logger_event.as_ref().map(|_| "<callback>"),
-            )
-            .finish()
+            );
+
+        #[cfg(feature = "trace-non-blocking")]
+        debug.field("non_blocking", &self.non_blocking);
+
+        #[cfg(feature = "otel")]
+        debug.field("otlp", &self.otlp);
+
+        debug.finish()
     }
 }
 
@@ -105,6 +155,10 @@ impl Default for Tracing {
             include_thread_names: true,
             enable_ansi: true,
             on_logger_event: None,
+            #[cfg(feature = "trace-non-blocking")]
+            non_blocking: false,
+            #[cfg(feature = "otel")]
+            otlp: None,
         }
     }
 }
@@ -145,7 +199,51 @@ impl Default for Tracing {
 ///
 /// // init_tracing(config).expect("Failed to initialize tracing");
 /// ```
+///
+/// Log files can be rotated, see [`init_tracing_with_guard`] to write them without blocking
+/// the traced code:
+///
+/// ```rust
+/// use foxtive::setup::trace::{OutputTarget, RollingFile, Tracing};
+///
+/// let config = Tracing::default().with_output_target(OutputTarget::RollingFile(
+///     RollingFile::daily("logs", "app.log").max_files(14),
+/// ));
+/// ```
 pub fn init_tracing(config: Tracing) -> AppResult<()> {
+    #[cfg(feature = "trace-non-blocking")]
+    if config.non_blocking {
+        return Err(internal_server_error!(
+            "The non-blocking writer needs the guard returned by init_tracing_with_guard"
+        ));
+    }
+
+    init_tracing_with_guard(config).map(drop)
+}
+
+/// Initializes the tracing subscriber like [`init_tracing`], returning the [`TracingGuard`]
+/// flushing the non-blocking writer, which requires the `trace-non-blocking` feature.
+///
+/// ```rust
+/// use foxtive::setup::trace::{init_tracing_with_guard, OutputTarget, RollingFile, Tracing};
+///
+/// let config = Tracing::default().with_output_target(OutputTarget::RollingFile(
+///     RollingFile::daily("logs", "app.log").max_files(14),
+/// ));
+/// #[cfg(feature = "trace-non-blocking")]
+/// let config = config.with_non_blocking(true);
+///
+/// // let _guard = init_tracing_with_guard(config).expect("Failed to initialize tracing");
+/// ```
+pub fn init_tracing_with_guard(config: Tracing) -> AppResult<TracingGuard> {
+    let ansi = config.enable_ansi && config.target.is_terminal();
+    let (writer, guard) = make_writer(&config)?;
+
+    #[cfg(feature = "otel")]
+    let otel_tracer = config.otlp.as_ref().map(OtlpConfig::tracer).transpose()?;
+    #[cfg(not(feature = "otel"))]
+    let otel_tracer: Option<()> = None;
+
     macro_rules! init_subscriber {
         ($fmt_layer:expr) => {
             let env_filter = EnvFilter::try_from_default_env()
@@ -156,52 +254,20 @@ pub fn init_tracing(config: Tracing) -> AppResult<()> {
                     .with(EventCallbackLayer::new(on_logger_event))
                     .with(env_filter)
                     .with($fmt_layer)
+                    .with(otel_tracer.map(otel_layer))
                     .init();
             } else {
                 tracing_subscriber::registry()
                     .with(env_filter)
                     .with($fmt_layer)
+                    .with(otel_tracer.map(otel_layer))
                     .init();
             }
         };
     }
 
-    match (config.format, config.target) {
-        (OutputFormat::Json, OutputTarget::Stdout) => {
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .json()
-                    .with_current_span(true)
-                    .with_span_list(true)
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
-            );
-        }
-        (OutputFormat::Json, OutputTarget::Stderr) => {
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .json()
-                    .with_current_span(true)
-                    .with_span_list(true)
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
-                    .with_writer(std::io::stderr)
-            );
-        }
-        (OutputFormat::Json, OutputTarget::File(path)) => {
-            let file = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(path)?;
-
+    match config.format {
+        OutputFormat::Json => {
             init_subscriber!(
                 tracing_subscriber::fmt::layer()
                     .json()
@@ -212,23 +278,11 @@ pub fn init_tracing(config: Tracing) -> AppResult<()> {
                     .with_target(config.include_target)
                     .with_thread_ids(config.include_thread_ids)
                     .with_thread_names(config.include_thread_names)
-                    .with_ansi(false)
-                    .with_writer(file)
-            );
-        }
-        (OutputFormat::Pretty, OutputTarget::Stdout) => {
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .pretty()
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
+                    .with_ansi(ansi)
+                    .with_writer(writer)
             );
         }
-        (OutputFormat::Pretty, OutputTarget::Stderr) => {
+        OutputFormat::Pretty => {
             init_subscriber!(
                 tracing_subscriber::fmt::layer()
                     .pretty()
@@ -237,29 +291,11 @@ pub fn init_tracing(config: Tracing) -> AppResult<()> {
                     .with_target(config.include_target)
                     .with_thread_ids(config.include_thread_ids)
                     .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
-                    .with_writer(std::io::stderr)
+                    .with_ansi(ansi)
+                    .with_writer(writer)
             );
         }
-        (OutputFormat::Pretty, OutputTarget::File(path)) => {
-            let file = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(path)?;
-
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .pretty()
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(false)
-                    .with_writer(file)
-            );
-        }
-        (OutputFormat::Compact, OutputTarget::Stdout) => {
+        OutputFormat::Compact => {
             init_subscriber!(
                 tracing_subscriber::fmt::layer()
                     .compact()
@@ -268,83 +304,47 @@ pub fn init_tracing(config: Tracing) -> AppResult<()> {
                     .with_target(config.include_target)
                     .with_thread_ids(config.include_thread_ids)
                     .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
+                    .with_ansi(ansi)
+                    .with_writer(writer)
             );
         }
-        (OutputFormat::Compact, OutputTarget::Stderr) => {
+        OutputFormat::Full => {
             init_subscriber!(
                 tracing_subscriber::fmt::layer()
-                    .compact()
                     .with_file(config.include_file)
                     .with_line_number(config.include_line_number)
                     .with_target(config.include_target)
                     .with_thread_ids(config.include_thread_ids)
                     .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
-                    .with_writer(std::io::stderr)
+                    .with_ansi(ansi)
+                    .with_writer(writer)
             );
         }
-        (OutputFormat::Compact, OutputTarget::File(path)) => {
-            let file = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(path)?;
+    }
 
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .compact()
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(false)
-                    .with_writer(file)
-            );
-        }
-        (OutputFormat::Full, OutputTarget::Stdout) => {
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
-            );
-        }
-        (OutputFormat::Full, OutputTarget::Stderr) => {
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(config.enable_ansi)
-                    .with_writer(std::io::stderr)
-            );
-        }
-        (OutputFormat::Full, OutputTarget::File(path)) => {
-            let file = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(path)?;
+    Ok(guard)
+}
 
-            init_subscriber!(
-                tracing_subscriber::fmt::layer()
-                    .with_file(config.include_file)
-                    .with_line_number(config.include_line_number)
-                    .with_target(config.include_target)
-                    .with_thread_ids(config.include_thread_ids)
-                    .with_thread_names(config.include_thread_names)
-                    .with_ansi(false)
-                    .with_writer(file)
-            );
-        }
+/// Writer of the output layer, with the guard flushing it when it's non-blocking
+fn make_writer(config: &Tracing) -> AppResult<(BoxMakeWriter, TracingGuard)> {
+    let target = config.target.clone();
+
+    #[cfg(feature = "trace-non-blocking")]
+    if config.non_blocking {
+        let (writer, worker) = tracing_appender::non_blocking(target.writer()?);
+        let guard = TracingGuard {
+            _worker: Some(worker),
+        };
+        return Ok((BoxMakeWriter::new(writer), guard));
     }
 
-    Ok(())
+    let writer = match target {
+        OutputTarget::Stdout => BoxMakeWriter::new(std::io::stdout),
+        OutputTarget::Stderr => BoxMakeWriter::new(std::io::stderr),
+        target => BoxMakeWriter::new(Mutex::new(target.writer()?)),
+    };
+
+    Ok((writer, TracingGuard::default()))
 }
 
 impl Tracing {
@@ -364,6 +364,20 @@ impl Tracing {
         self
     }
 
+    /// Exports spans to an OpenTelemetry collector, see [`shutdown_otlp`]
+    #[cfg(feature = "otel")]
+    pub fn with_otlp(mut self, config: OtlpConfig) -> Self {
+        self.otlp = Some(config);
+        self
+    }
+
+    /// Writes the events from a background thread, see [`init_tracing_with_guard`]
+    #[cfg(feature = "trace-non-blocking")]
+    pub fn with_non_blocking(mut self, state: bool) -> Self {
+        self.non_blocking = state;
+        self
+    }
+
     pub fn with_level(mut self, level: Level) -> Self {
         self.level = level;
         self
@@ -438,6 +452,10 @@ impl Tracing {
             include_thread_names: false,
             enable_ansi: true,
             on_logger_event: None,
+            #[cfg(feature = "trace-non-blocking")]
+            non_blocking: false,
+            #[cfg(feature = "otel")]
+            otlp: None,
         }
     }
 
@@ -454,6 +472,10 @@ impl Tracing {
             include_thread_names: true,
             enable_ansi: true,
             on_logger_event: None,
+            #[cfg(feature = "trace-non-blocking")]
+            non_blocking: false,
+            #[cfg(feature = "otel")]
+            otlp: None,
         }
     }
 }
@@ -474,7 +496,10 @@ mod tests {
             let target = event.metadata().target();
             println!("Event callback triggered: level={level}, target={target}");
 
-            counter_clone.fetch_add(1, Ordering::SeqCst);
+            // the subscriber is global, other tests running concurrently emit events too
+            if target == module_path!() {
+                counter_clone.fetch_add(1, Ordering::SeqCst);
+            }
         });
 
         init_tracing(config).expect("Failed to initialize tracing");
@@ -524,6 +549,13 @@ mod tests {
         assert!(config.include_target);
     }
 
+    #[cfg(feature = "trace-non-blocking")]
+    #[test]
+    fn test_non_blocking() {
+        assert!(!Tracing::default().non_blocking);
+        assert!(Tracing::minimal().with_non_blocking(true).non_blocking);
+    }
+
     #[test]
     fn test_log_level() {
         let config = Tracing::default().with_level(Level::WARN);
@@ -0,0 +1,435 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+use tracing::warn;
+
+/// Suffix of the files rotated daily, the date of their content
+const DAILY_SUFFIX: &str = "%Y-%m-%d";
+
+/// Suffix of the files rotated by size, the time of the rotation
+const SIZE_SUFFIX: &str = "%Y-%m-%dT%H-%M-%S%.3f";
+
+/// When a [`RollingFile`] is moved aside and a new one is started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// At midnight UTC, rotated files are suffixed with the date, e.g. `app.log.2026-10-16`
+    Daily,
+    /// Before the file grows past the given number of bytes,
+    /// rotated files are suffixed with the time, e.g. `app.log.2026-10-16T14-05-09.120`
+    Size(u64),
+}
+
+/// Log file rotated daily or by size, written with [`OutputTarget::RollingFile`](crate::setup::trace::OutputTarget::RollingFile)
+#[derive(Debug, Clone)]
+pub struct RollingFile {
+    pub directory: PathBuf,
+    pub file_name: String,
+    pub rotation: Rotation,
+    /// Rotated files to keep, the oldest ones are deleted, all of them are kept by default
+    pub max_files: Option<usize>,
+    /// Gzips the rotated files in a background thread
+    #[cfg(feature = "trace-compress")]
+    pub compress: bool,
+}
+
+impl RollingFile {
+    pub fn daily(directory: impl Into<PathBuf>, file_name: &str) -> Self {
+        Self::new(directory.into(), file_name, Rotation::Daily)
+    }
+
+    pub fn size(directory: impl Into<PathBuf>, file_name: &str, max_bytes: u64) -> Self {
+        Self::new(directory.into(), file_name, Rotation::Size(max_bytes))
+    }
+
+    fn new(directory: PathBuf, file_name: &str, rotation: Rotation) -> Self {
+        Self {
+            directory,
+            file_name: file_name.to_string(),
+            rotation,
+            max_files: None,
+            #[cfg(feature = "trace-compress")]
+            compress: false,
+        }
+    }
+
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    #[cfg(feature = "trace-compress")]
+    pub fn compress(mut self, state: bool) -> Self {
+        self.compress = state;
+        self
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.directory.join(&self.file_name)
+    }
+
+    /// Opens the current file, creating the directory if needed
+    pub(crate) fn open(self) -> io::Result<RollingFileWriter> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        let file = open_append(&self.path())?;
+        let meta = file.metadata()?;
+
+        // a file left by a previous run belongs to the day it was last written
+        let period = meta
+            .modified()
+            .map(|time| DateTime::<Utc>::from(time).date_naive())
+            .unwrap_or_else(|_| Utc::now().date_naive());
+
+        Ok(RollingFileWriter {
+            config: self,
+            file,
+            size: meta.len(),
+            period,
+            housekeeping: None,
+        })
+    }
+}
+
+pub(crate) struct RollingFileWriter {
+    config: RollingFile,
+    file: File,
+    size: u64,
+    period: NaiveDate,
+    housekeeping: Option<Housekeeping>,
+}
+
+/// Compresses and prunes the rotated files one after the other, away from the writer
+///
+/// The writer never waits for it, the events it logs may need the writer's lock.
+struct Housekeeping {
+    rotated: Sender<PathBuf>,
+    thread: JoinHandle<()>,
+}
+
+impl Housekeeping {
+    fn spawn(config: RollingFile) -> Self {
+        let (sender, receiver) = mpsc::channel::<PathBuf>();
+        let thread = std::thread::spawn(move || {
+            for rotated in receiver {
+                #[cfg(feature = "trace-compress")]
+                if config.compress
+                    && let Err(err) = compress(&rotated)
+                {
+                    warn!("[tracing] failed to compress {}: {err}", rotated.display());
+                }
+
+                #[cfg(not(feature = "trace-compress"))]
+                let _ = rotated;
+
+                if let Err(err) = prune(&config) {
+                    warn!("[tracing] failed to delete old log files: {err}");
+                }
+            }
+        });
+
+        Self {
+            rotated: sender,
+            thread,
+        }
+    }
+}
+
+impl RollingFileWriter {
+    fn should_rotate(&self, incoming: u64) -> bool {
+        match self.config.rotation {
+            Rotation::Daily => Utc::now().date_naive() != self.period,
+            Rotation::Size(max_bytes) => self.size > 0 && self.size + incoming > max_bytes,
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let suffix = match self.config.rotation {
+            Rotation::Daily => self.period.format(DAILY_SUFFIX).to_string(),
+            Rotation::Size(_) => Utc::now().format(SIZE_SUFFIX).to_string(),
+        };
+
+        let path = self.config.path();
+        let rotated = available_path(&format!("{}.{suffix}", path.display()));
+        std::fs::rename(&path, &rotated)?;
+
+        self.file = open_append(&path)?;
+        self.size = 0;
+        self.period = Utc::now().date_naive();
+
+        let housekeeping = self
+            .housekeeping
+            .get_or_insert_with(|| Housekeeping::spawn(self.config.clone()));
+        let _ = housekeeping.rotated.send(rotated);
+
+        Ok(())
+    }
+}
+
+impl Write for RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.should_rotate(buf.len() as u64) {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for RollingFileWriter {
+    fn drop(&mut self) {
+        if let Some(Housekeeping { rotated, thread }) = self.housekeeping.take() {
+            // the thread stops once the rotated files queued so far are done
+            drop(rotated);
+            let _ = thread.join();
+        }
+    }
+}
+
+pub(crate) fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// `path`, or `path.1`, `path.2`... if a file, compressed or not, already exists there
+fn available_path(path: &str) -> PathBuf {
+    let taken = |path: &str| Path::new(path).exists() || Path::new(&format!("{path}.gz")).exists();
+
+    let mut candidate = path.to_string();
+    let mut counter = 1;
+    while taken(&candidate) {
+        candidate = format!("{path}.{counter}");
+        counter += 1;
+    }
+
+    PathBuf::from(candidate)
+}
+
+#[cfg(feature = "trace-compress")]
+fn compress(path: &Path) -> io::Result<()> {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    let gz_path = PathBuf::from(format!("{}.gz", path.display()));
+    let mut encoder = GzEncoder::new(File::create(&gz_path)?, Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+
+    std::fs::remove_file(path)
+}
+
+/// Deletes the oldest rotated files past [`RollingFile::max_files`]
+fn prune(config: &RollingFile) -> io::Result<()> {
+    let Some(max_files) = config.max_files else {
+        return Ok(());
+    };
+
+    let mut rotated = vec![];
+    for entry in std::fs::read_dir(&config.directory)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if let Some(order) = rotated_order(config, &name) {
+            rotated.push((order, name));
+        }
+    }
+
+    rotated.sort();
+
+    let excess = rotated.len().saturating_sub(max_files);
+    for (_, name) in &rotated[..excess] {
+        std::fs::remove_file(config.directory.join(name))?;
+    }
+
+    Ok(())
+}
+
+/// Where a file produced by the rotation stands among the rotated files, `None` for
+/// other files. Their names are `{file_name}.{suffix}`, followed by the counter added by
+/// [`available_path`] and `.gz` when compressed.
+fn rotated_order(config: &RollingFile, name: &str) -> Option<(String, u32)> {
+    let suffix = name.strip_prefix(&config.file_name)?.strip_prefix('.')?;
+    let suffix = suffix.strip_suffix(".gz").unwrap_or(suffix);
+
+    // both suffixes have a fixed length, the size one contains a dot
+    let (period, counter) = match config.rotation {
+        Rotation::Daily => {
+            let (period, counter) = suffix.split_at_checked(10)?;
+            NaiveDate::parse_from_str(period, DAILY_SUFFIX).ok()?;
+            (period, counter)
+        }
+        Rotation::Size(_) => {
+            let (period, counter) = suffix.split_at_checked(23)?;
+            NaiveDateTime::parse_from_str(period, SIZE_SUFFIX).ok()?;
+            (period, counter)
+        }
+    };
+
+    let counter = match counter {
+        "" => 0,
+        counter => {
+            let digits = counter.strip_prefix('.')?;
+            if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+            digits.parse().ok()?
+        }
+    };
+
+    Some((period.to_string(), counter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotated_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name != "app.log")
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_size_rotation_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RollingFile::size(dir.path(), "app.log", 10)
+            .max_files(2)
+            .open()
+            .unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        drop(writer);
+
+        let rotated = rotated_files(dir.path());
+        assert_eq!(rotated.len(), 2);
+
+        let content = std::fs::read_to_string(dir.path().join(&rotated[1])).unwrap();
+        assert_eq!(content, "third\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("app.log")).unwrap(),
+            "fourth\n"
+        );
+    }
+
+    #[test]
+    fn test_daily_rotation_uses_the_date_of_the_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RollingFile::daily(dir.path(), "app.log").open().unwrap();
+
+        writer.write_all(b"yesterday\n").unwrap();
+        writer.period = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        writer.write_all(b"today\n").unwrap();
+        drop(writer);
+
+        assert_eq!(rotated_files(dir.path()), ["app.log.2026-10-16"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("app.log")).unwrap(),
+            "today\n"
+        );
+    }
+
+    #[test]
+    fn test_prune_only_removes_rotated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let names = [
+            "app.log.2026-10-14",
+            "app.log.2026-10-15.gz",
+            "app.log.2026-10-15.1.gz",
+            "app.log.2026-10-16",
+            "app.log.backup",
+            "app.log.2026-10-13.bak",
+            "app.log.lock",
+        ];
+        for name in names {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let config = RollingFile::daily(dir.path(), "app.log").max_files(2);
+        prune(&config).unwrap();
+
+        assert_eq!(
+            rotated_files(dir.path()),
+            [
+                "app.log.2026-10-13.bak",
+                "app.log.2026-10-15.1.gz",
+                "app.log.2026-10-16",
+                "app.log.backup",
+                "app.log.lock",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rotated_order() {
+        let daily = RollingFile::daily("logs", "app.log");
+        assert_eq!(
+            rotated_order(&daily, "app.log.2026-10-16.10.gz"),
+            Some(("2026-10-16".to_string(), 10))
+        );
+        assert!(
+            rotated_order(&daily, "app.log.2026-10-16.2")
+                < rotated_order(&daily, "app.log.2026-10-16.10")
+        );
+        assert_eq!(rotated_order(&daily, "app.log.2026-13-01"), None);
+        assert_eq!(rotated_order(&daily, "app.log.2026-10-16.+1"), None);
+        assert_eq!(rotated_order(&daily, "other.log.2026-10-16"), None);
+
+        let size = RollingFile::size("logs", "app.log", 1024);
+        assert_eq!(
+            rotated_order(&size, "app.log.2026-10-16T10-20-30.123.gz"),
+            Some(("2026-10-16T10-20-30.123".to_string(), 0))
+        );
+        assert_eq!(rotated_order(&size, "app.log.2026-10-16"), None);
+    }
+
+    #[test]
+    fn test_available_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log.2026-10-16");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(available_path(path), PathBuf::from(path));
+
+        std::fs::write(format!("{path}.gz"), "").unwrap();
+        assert_eq!(available_path(path), PathBuf::from(format!("{path}.1")));
+    }
+
+    #[cfg(feature = "trace-compress")]
+    #[test]
+    fn test_rotated_files_are_compressed() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RollingFile::size(dir.path(), "app.log", 4)
+            .compress(true)
+            .open()
+            .unwrap();
+
+        writer.write_all(b"old\n").unwrap();
+        writer.write_all(b"new\n").unwrap();
+        drop(writer);
+
+        let rotated = rotated_files(dir.path());
+        assert_eq!(rotated.len(), 1);
+        assert!(rotated[0].ends_with(".gz"));
+
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(File::open(dir.path().join(&rotated[0])).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "old\n");
+    }
+}
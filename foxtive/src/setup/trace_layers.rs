@@ -1,4 +1,6 @@
 use std::sync::Arc;
+#[cfg(not(feature = "otel"))]
+use tracing_subscriber::layer::Identity;
 
 pub(crate) struct EventCallbackLayer {
     callback: Arc<dyn Fn(&tracing::Event<'_>) + Send + Sync + 'static>,
@@ -22,3 +24,19 @@ where
         (self.callback)(event);
     }
 }
+
+/// Forwards spans to the OTLP exporter's tracer
+#[cfg(feature = "otel")]
+pub(crate) fn otel_layer<S>(
+    tracer: opentelemetry_sdk::trace::SdkTracer,
+) -> impl tracing_subscriber::Layer<S>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(tracer)
+}
+
+#[cfg(not(feature = "otel"))]
+pub(crate) fn otel_layer(_: ()) -> Identity {
+    Identity::new()
+}
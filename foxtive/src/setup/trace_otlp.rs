@@ -0,0 +1,153 @@
+use crate::internal_server_error;
+use crate::prelude::AppResult;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{Sampler, SdkTracer, SdkTracerProvider};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Exports spans to an OpenTelemetry collector (Jaeger, Tempo...) over OTLP/HTTP
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    pub service_name: String,
+    /// Traces endpoint, `http://localhost:4318/v1/traces` by default
+    pub endpoint: String,
+    /// Share of the root spans to export, between 0.0 and 1.0, child spans follow their parent
+    pub sampling_ratio: f64,
+    pub timeout: Duration,
+    /// Sent with every export, e.g. an authorization header
+    pub headers: HashMap<String, String>,
+}
+
+impl OtlpConfig {
+    pub fn new(service_name: &str) -> Self {
+        Self {
+            service_name: service_name.to_string(),
+            endpoint: "http://localhost:4318/v1/traces".to_string(),
+            sampling_ratio: 1.0,
+            timeout: Duration::from_secs(10),
+            headers: HashMap::new(),
+        }
+    }
+
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    pub fn sampling_ratio(mut self, ratio: f64) -> Self {
+        self.sampling_ratio = ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    fn sampler(&self) -> Sampler {
+        let root = match self.sampling_ratio {
+            ratio if ratio >= 1.0 => Sampler::AlwaysOn,
+            ratio if ratio <= 0.0 => Sampler::AlwaysOff,
+            ratio => Sampler::TraceIdRatioBased(ratio),
+        };
+
+        Sampler::ParentBased(Box::new(root))
+    }
+
+    /// Creates the tracer provider, registered as the global one, and returns its tracer
+    pub(crate) fn tracer(&self) -> AppResult<SdkTracer> {
+        if PROVIDER.get().is_some() {
+            return Err(internal_server_error!(
+                "OTLP exporter is already initialized"
+            ));
+        }
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(&self.endpoint)
+            .with_timeout(self.timeout)
+            .with_headers(self.headers.clone())
+            .build()?;
+
+        let resource = Resource::builder()
+            .with_service_name(self.service_name.clone())
+            .build();
+
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_sampler(self.sampler())
+            .with_resource(resource)
+            .build();
+
+        let tracer = provider.tracer(self.service_name.clone());
+
+        PROVIDER
+            .set(provider.clone())
+            .map_err(|_| internal_server_error!("OTLP exporter is already initialized"))?;
+        opentelemetry::global::set_tracer_provider(provider);
+
+        Ok(tracer)
+    }
+}
+
+/// Exports the pending spans and stops the OTLP exporter, call it before the application exits
+pub fn shutdown_otlp() -> AppResult<()> {
+    match PROVIDER.get() {
+        Some(provider) => provider.shutdown().map_err(crate::Error::msg),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampler() {
+        let config = OtlpConfig::new("orders");
+        assert_eq!(
+            format!("{:?}", config.sampler()),
+            format!("{:?}", Sampler::ParentBased(Box::new(Sampler::AlwaysOn)))
+        );
+
+        let config = config.sampling_ratio(0.25);
+        assert_eq!(
+            format!("{:?}", config.sampler()),
+            format!(
+                "{:?}",
+                Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(0.25)))
+            )
+        );
+
+        assert_eq!(
+            OtlpConfig::new("orders").sampling_ratio(7.0).sampling_ratio,
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_tracer_is_created_without_collector() {
+        use opentelemetry::trace::Tracer;
+
+        let tracer = OtlpConfig::new("orders")
+            .endpoint("http://127.0.0.1:1/v1/traces")
+            .timeout(Duration::from_millis(50))
+            .tracer()
+            .unwrap();
+        tracer.in_span("checkout", |_| {});
+
+        assert!(OtlpConfig::new("orders").tracer().is_err());
+        // export failures are logged, the exporter still shuts down
+        assert!(shutdown_otlp().is_ok());
+    }
+}
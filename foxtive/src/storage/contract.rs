@@ -0,0 +1,41 @@
+use crate::prelude::AppResult;
+use std::time::Duration;
+
+/// Contract for implementing object storage drivers
+///
+/// Paths are `/` separated and relative to the root of the storage (directory or bucket),
+/// e.g. `avatars/42.png`.
+#[async_trait::async_trait]
+pub trait StorageDriverContract: Send + Sync {
+    /// Stores content at the given path, replacing any existing object
+    ///
+    /// # Parameters
+    /// - `path`: Object path
+    /// - `content`: Raw bytes to store
+    async fn put(&self, path: &str, content: Vec<u8>) -> AppResult<()>;
+
+    /// Retrieves the content stored at the given path
+    ///
+    /// # Returns
+    /// - `AppResult<Option<Vec<u8>>>`: The content if the object exists
+    async fn get(&self, path: &str) -> AppResult<Option<Vec<u8>>>;
+
+    /// Checks whether an object exists at the given path
+    async fn exists(&self, path: &str) -> AppResult<bool>;
+
+    /// Removes the object at the given path, succeeding if it does not exist
+    async fn delete(&self, path: &str) -> AppResult<()>;
+
+    /// Lists the paths of all objects starting with the given prefix, sorted
+    ///
+    /// # Parameters
+    /// - `prefix`: Path prefix to filter by, empty for every object
+    async fn list(&self, prefix: &str) -> AppResult<Vec<String>>;
+
+    /// Creates a URL granting temporary read access to the object, without credentials
+    ///
+    /// # Parameters
+    /// - `path`: Object path
+    /// - `ttl`: How long the URL remains valid
+    async fn presigned_url(&self, path: &str, ttl: Duration) -> AppResult<String>;
+}
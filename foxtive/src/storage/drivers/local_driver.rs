@@ -0,0 +1,309 @@
+use crate::bad_request;
+use crate::helpers::signer::Signer;
+use crate::results::AppResult;
+use crate::storage::contract::StorageDriverContract;
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs;
+
+/// Stores objects as files under a root directory.
+///
+/// Presigned URLs point to `base_url` and carry an HMAC signature, the handler serving
+/// them must check it with [`LocalStorageDriver::verify_url`].
+#[derive(Clone)]
+pub struct LocalStorageDriver {
+    root: Arc<PathBuf>,
+    base_url: String,
+    signer: Signer,
+}
+
+impl LocalStorageDriver {
+    /// # Arguments
+    ///
+    /// * `root`: directory objects are stored in, created on first write
+    /// * `base_url`: public URL files are served from, used to build presigned URLs
+    /// * `secret`: key used to sign URLs, typically the app key
+    pub fn new(root: impl AsRef<Path>, base_url: &str, secret: &str) -> Self {
+        Self {
+            root: Arc::new(root.as_ref().to_path_buf()),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            signer: Signer::new(secret),
+        }
+    }
+
+    /// Verifies a URL produced by [`StorageDriverContract::presigned_url`],
+    /// returning the object path it grants access to
+    pub fn verify_url(&self, url: &str) -> AppResult<String> {
+        self.signer.verify(url)?;
+
+        let path = url
+            .strip_prefix(&self.base_url)
+            .and_then(|rest| rest.split('?').next())
+            .map(|path| path.trim_start_matches('/'))
+            .ok_or_else(|| bad_request!("url does not belong to this storage"))?;
+        let path = decode_path(path)?;
+
+        self.resolve(&path)?;
+        Ok(path)
+    }
+
+    /// Returns the location of an object on disk
+    pub fn path_of(&self, path: &str) -> AppResult<PathBuf> {
+        self.resolve(path)
+    }
+
+    fn resolve(&self, path: &str) -> AppResult<PathBuf> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        let is_safe = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        if path.trim().is_empty() || !is_safe {
+            return Err(bad_request!("invalid storage path: {path}"));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl StorageDriverContract for LocalStorageDriver {
+    async fn put(&self, path: &str, content: Vec<u8>) -> AppResult<()> {
+        let file = self.resolve(path)?;
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // write next to the destination first, so readers never see partial content
+        let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+        let partial = file.with_file_name(format!(".{file_name}.{}.part", uuid::Uuid::new_v4()));
+        fs::write(&partial, content).await?;
+        if let Err(err) = fs::rename(&partial, &file).await {
+            let _ = fs::remove_file(&partial).await;
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> AppResult<Option<Vec<u8>>> {
+        match fs::read(self.resolve(path)?).await {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn exists(&self, path: &str) -> AppResult<bool> {
+        Ok(fs::try_exists(self.resolve(path)?).await?)
+    }
+
+    async fn delete(&self, path: &str) -> AppResult<()> {
+        match fs::remove_file(self.resolve(path)?).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> AppResult<Vec<String>> {
+        let mut paths = vec![];
+        let mut pending = vec![self.root.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    pending.push(entry.path());
+                    continue;
+                }
+
+                let path = entry.path();
+                let relative = path
+                    .strip_prefix(&*self.root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+
+                let name = entry.file_name().to_string_lossy().to_string();
+                let is_partial = name.starts_with('.') && name.ends_with(".part");
+
+                if file_type.is_file() && !is_partial && relative.starts_with(prefix) {
+                    paths.push(relative);
+                }
+            }
+        }
+
+        paths.sort();
+        Ok(paths)
+    }
+
+    async fn presigned_url(&self, path: &str, ttl: Duration) -> AppResult<String> {
+        self.resolve(path)?;
+        let path = encode_path(path.trim_start_matches('/'));
+        self.signer.sign(&format!("{}/{path}", self.base_url), ttl)
+    }
+}
+
+/// Percent-encodes each segment of an object path, so `?`, `#`, `&` and `%` in keys
+/// can't be taken for the query or fragment of the URL
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Decodes a path encoded by [`encode_path`]
+fn decode_path(path: &str) -> AppResult<String> {
+    let invalid = || bad_request!("invalid encoding in url path: {path}");
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3).ok_or_else(invalid)?;
+                let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                i += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn driver(root: &Path) -> LocalStorageDriver {
+        LocalStorageDriver::new(root, "https://cdn.example.com/files/", "secret")
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = driver(dir.path());
+
+        storage.put("a/b/c.txt", b"hello".to_vec()).await.unwrap();
+        assert!(storage.exists("a/b/c.txt").await.unwrap());
+        assert_eq!(
+            storage.get("a/b/c.txt").await.unwrap(),
+            Some(b"hello".to_vec())
+        );
+
+        storage.delete("a/b/c.txt").await.unwrap();
+        storage.delete("a/b/c.txt").await.unwrap();
+        assert!(!storage.exists("a/b/c.txt").await.unwrap());
+        assert_eq!(storage.get("a/b/c.txt").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_list_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = driver(dir.path());
+
+        for path in ["avatars/2.png", "avatars/1.png", "docs/readme.md"] {
+            storage.put(path, vec![1]).await.unwrap();
+        }
+
+        assert_eq!(
+            storage.list("avatars/").await.unwrap(),
+            vec!["avatars/1.png", "avatars/2.png"]
+        );
+        assert_eq!(storage.list("").await.unwrap().len(), 3);
+        assert!(
+            driver(&dir.path().join("missing"))
+                .list("")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_path_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = driver(dir.path());
+
+        assert!(storage.put("../escape.txt", vec![1]).await.is_err());
+        assert!(storage.get("a/../../etc/passwd").await.is_err());
+        assert!(storage.put("", vec![1]).await.is_err());
+        assert!(storage.put("/rooted.txt", vec![1]).await.is_ok());
+        assert!(dir.path().join("rooted.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_presigned_url_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = driver(dir.path());
+
+        let url = storage
+            .presigned_url("invoices/1.pdf", Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(url.starts_with("https://cdn.example.com/files/invoices/1.pdf?expires="));
+        assert_eq!(storage.verify_url(&url).unwrap(), "invoices/1.pdf");
+
+        let tampered = url.replace("1.pdf", "2.pdf");
+        assert!(storage.verify_url(&tampered).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_presigned_url_encodes_special_characters() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = driver(dir.path());
+
+        for path in ["q?a=1.pdf", "notes/#1.md", "a&b/c d.txt", "100%/é.png"] {
+            let url = storage
+                .presigned_url(path, Duration::from_secs(60))
+                .await
+                .unwrap();
+            assert_eq!(storage.verify_url(&url).unwrap(), path);
+        }
+
+        let url = storage
+            .presigned_url("q?a=1.pdf", Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(url.starts_with("https://cdn.example.com/files/q%3Fa%3D1.pdf?expires="));
+    }
+
+    #[tokio::test]
+    async fn test_put_leaves_no_partial_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = driver(dir.path());
+
+        let writes = (0..8).map(|i| storage.put("report.csv", vec![i; 1024]));
+        for result in futures::future::join_all(writes).await {
+            result.unwrap();
+        }
+
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["report.csv"]);
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg(feature = "storage-local")]
+mod local_driver;
+#[cfg(feature = "storage-s3")]
+mod s3_driver;
+
+#[cfg(feature = "storage-local")]
+pub use local_driver::LocalStorageDriver;
+
+#[cfg(feature = "storage-s3")]
+pub use s3_driver::{S3Config, S3StorageDriver};
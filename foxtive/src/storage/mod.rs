@@ -0,0 +1,104 @@
+//! # Storage Module
+//!
+//! A driver-based object storage abstraction, so that application code reads and writes
+//! files the same way whether they end up on the local disk or in an S3-compatible bucket
+//! (AWS S3, MinIO, Cloudflare R2, ...).
+//!
+//! ## Features
+//!
+//! This module requires at least one of the following features to be enabled:
+//! - `storage-local`
+//! - `storage-s3`
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use foxtive::storage::{Storage, drivers::LocalStorageDriver};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let driver = LocalStorageDriver::new("./storage", "https://example.com/files", "app-key");
+//!     let storage = Storage::new(Arc::new(driver));
+//!
+//!     storage.put("reports/2024.csv", "id,total\n1,100").await.unwrap();
+//!     let content = storage.get_string("reports/2024.csv").await.unwrap();
+//!
+//!     let url = storage
+//!         .presigned_url("reports/2024.csv", Duration::from_secs(300))
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+
+pub mod contract;
+pub mod drivers;
+
+use crate::prelude::AppResult;
+use crate::storage::contract::StorageDriverContract;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A storage interface wrapping a driver implementation.
+///
+/// The `Storage` struct provides a consistent interface regardless of where objects are kept.
+#[derive(Clone)]
+pub struct Storage {
+    driver: Arc<dyn StorageDriverContract>,
+}
+
+impl Storage {
+    /// Creates a new `Storage` instance with the specified driver.
+    pub fn new(driver: Arc<dyn StorageDriverContract>) -> Self {
+        Self { driver }
+    }
+
+    /// Returns a clone of the underlying driver.
+    pub fn driver(&self) -> Arc<dyn StorageDriverContract> {
+        Arc::clone(&self.driver)
+    }
+
+    /// Stores content at the given path, replacing any existing object.
+    pub async fn put(&self, path: &str, content: impl Into<Vec<u8>>) -> AppResult<()> {
+        self.driver.put(path, content.into()).await
+    }
+
+    /// Retrieves the raw content stored at the given path.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(bytes))` - The object exists
+    /// - `Ok(None)` - No object is stored at the path
+    pub async fn get(&self, path: &str) -> AppResult<Option<Vec<u8>>> {
+        self.driver.get(path).await
+    }
+
+    /// Retrieves the content stored at the given path as UTF-8 text.
+    pub async fn get_string(&self, path: &str) -> AppResult<Option<String>> {
+        match self.driver.get(path).await? {
+            Some(bytes) => Ok(Some(String::from_utf8(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Checks whether an object exists at the given path.
+    pub async fn exists(&self, path: &str) -> AppResult<bool> {
+        self.driver.exists(path).await
+    }
+
+    /// Removes the object at the given path.
+    pub async fn delete(&self, path: &str) -> AppResult<()> {
+        self.driver.delete(path).await
+    }
+
+    /// Lists the paths of all objects starting with the given prefix.
+    pub async fn list(&self, prefix: &str) -> AppResult<Vec<String>> {
+        self.driver.list(prefix).await
+    }
+
+    /// Creates a URL granting temporary read access to the object.
+    pub async fn presigned_url(&self, path: &str, ttl: Duration) -> AppResult<String> {
+        self.driver.presigned_url(path, ttl).await
+    }
+}
@@ -0,0 +1,317 @@
+//! # Templating Module
+//!
+//! Renders Tera templates with a set of global context values, and reloads them when
+//! the template files change during development.
+//!
+//! ## Features
+//!
+//! - Registration hooks for custom filters, functions and testers, see [`TemplateSetup`]
+//! - Global context values, `app_name`, `app_code` and `env` are always available
+//! - Hot reload of the templates in local and development environments
+//!
+//! This module requires the `templating` feature to be enabled.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::templating::{TemplateSetup, Templates};
+//! use foxtive::TemplateContext;
+//! use std::collections::HashMap;
+//! use tera::Tera;
+//!
+//! let mut tera = Tera::default();
+//! tera.add_raw_template("hello.tera.html", "{{ greeting | shout }}, {{ app_name }}!")
+//!     .unwrap();
+//!
+//! let setup = TemplateSetup::new()
+//!     .filter("shout", |value: &tera::Value, _: &HashMap<String, tera::Value>| {
+//!         Ok(tera::Value::String(value.as_str().unwrap_or_default().to_uppercase()))
+//!     })
+//!     .global("app_name", "Shop");
+//!
+//! let templates = setup.apply(tera).unwrap();
+//!
+//! let mut context = TemplateContext::new();
+//! context.insert("greeting", "hello");
+//! assert_eq!(templates.render("hello", &context).unwrap(), "HELLO, Shop!");
+//! ```
+
+use crate::Environment;
+use crate::results::AppResult;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+use std::time::{Duration, SystemTime};
+use tera::{Context, Tera};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+type ConfigureFn = Box<dyn FnOnce(&mut Tera) -> AppResult<()> + Send + Sync>;
+
+/// Customizes the Tera instance created by the setup, set as
+/// [`FoxtiveSetup::template_setup`](crate::setup::FoxtiveSetup::template_setup)
+#[derive(Default)]
+pub struct TemplateSetup {
+    configure: Vec<ConfigureFn>,
+    globals: Context,
+    hot_reload: Option<Duration>,
+}
+
+impl TemplateSetup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `configure` on the Tera instance once the templates are loaded,
+    /// e.g. to register testers or raw templates
+    pub fn configure<F>(mut self, configure: F) -> Self
+    where
+        F: FnOnce(&mut Tera) -> AppResult<()> + Send + Sync + 'static,
+    {
+        self.configure.push(Box::new(configure));
+        self
+    }
+
+    pub fn filter<F: tera::Filter + 'static>(self, name: &str, filter: F) -> Self {
+        let name = name.to_string();
+        self.configure(move |tera| {
+            tera.register_filter(&name, filter);
+            Ok(())
+        })
+    }
+
+    pub fn function<F: tera::Function + 'static>(self, name: &str, function: F) -> Self {
+        let name = name.to_string();
+        self.configure(move |tera| {
+            tera.register_function(&name, function);
+            Ok(())
+        })
+    }
+
+    /// Adds a value available to every template, the render context takes precedence
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value can't be serialized
+    pub fn global<T: Serialize + ?Sized>(mut self, key: &str, value: &T) -> Self {
+        self.globals.insert(key, value);
+        self
+    }
+
+    /// Reloads the templates when their files change, checking every `interval`.
+    ///
+    /// Only applies to the local and development environments.
+    pub fn hot_reload(mut self, interval: Duration) -> Self {
+        self.hot_reload = Some(interval);
+        self
+    }
+
+    /// Runs the hooks on `tera`, returning templates ready to render
+    pub fn apply(self, mut tera: Tera) -> AppResult<Templates> {
+        for configure in self.configure {
+            configure(&mut tera)?;
+        }
+
+        Ok(Templates {
+            tera: RwLock::new(tera),
+            globals: self.globals,
+        })
+    }
+
+    pub(crate) fn hot_reload_interval(&self) -> Option<Duration> {
+        self.hot_reload
+    }
+
+    /// Adds the application's globals, the ones set with [`TemplateSetup::global`] take precedence
+    pub(crate) fn app_globals(mut self, app_name: &str, app_code: &str, env: Environment) -> Self {
+        let mut globals = Context::new();
+        globals.insert("app_name", app_name);
+        globals.insert("app_code", app_code);
+        globals.insert("env", env.as_str());
+
+        globals.extend(self.globals);
+        self.globals = globals;
+        self
+    }
+}
+
+/// Tera templates rendered with global context values
+pub struct Templates {
+    tera: RwLock<Tera>,
+    globals: Context,
+}
+
+impl Templates {
+    pub fn new(tera: Tera) -> Self {
+        Self {
+            tera: RwLock::new(tera),
+            globals: Context::new(),
+        }
+    }
+
+    /// Renders `file`, adding the `.tera.html` extension if it is missing
+    pub fn render(&self, file: impl Into<String>, context: &Context) -> AppResult<String> {
+        let mut file = file.into();
+        if !file.ends_with(".tera.html") {
+            file.push_str(".tera.html");
+        }
+
+        let mut merged = self.globals.clone();
+        merged.extend(context.clone());
+
+        self.tera()
+            .render(&file, &merged)
+            .map_err(crate::Error::msg)
+    }
+
+    pub fn tera(&self) -> RwLockReadGuard<'_, Tera> {
+        self.tera.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn globals(&self) -> &Context {
+        &self.globals
+    }
+
+    /// Loads the templates again from the glob Tera was created with,
+    /// registered filters and functions are kept
+    pub fn reload(&self) -> AppResult<()> {
+        self.tera
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .full_reload()
+            .map_err(crate::Error::msg)
+    }
+
+    /// Polls the files matching `glob` every `interval`, reloading the templates whenever
+    /// one of them is modified, created or removed. Abort the returned handle to stop watching.
+    pub fn watch(self: &Arc<Self>, glob: &str, interval: Duration) -> JoinHandle<()> {
+        let templates = self.clone();
+        let root = glob_root(glob);
+        let mut files = fingerprint(&root);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                let current = fingerprint(&root);
+                if current != files {
+                    files = current;
+                    match templates.reload() {
+                        Ok(()) => info!("[templating] templates reloaded"),
+                        Err(err) => error!("[templating] failed to reload templates: {err:?}"),
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Directory of a glob, up to the first component containing a wildcard
+fn glob_root(glob: &str) -> PathBuf {
+    Path::new(glob)
+        .components()
+        .take_while(|component| {
+            let component = component.as_os_str().to_string_lossy();
+            !component.contains(['*', '?', '[', '{'])
+        })
+        .collect()
+}
+
+/// Paths, modification times and sizes of the files under `dir`
+fn fingerprint(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+
+            match meta.is_dir() {
+                true => dirs.push(entry.path()),
+                false => files.push((entry.path(), meta.modified().ok(), meta.len())),
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_dir() -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("foxtive-templates-{}", crate::helpers::id::ulid()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_render_context_overrides_globals() {
+        let mut tera = Tera::default();
+        tera.add_raw_template("page.tera.html", "{{ title }} - {{ app_name }}")
+            .unwrap();
+
+        let templates = TemplateSetup::new()
+            .global("app_name", "Shop")
+            .global("title", "Home")
+            .apply(tera)
+            .unwrap();
+
+        let mut context = Context::new();
+        assert_eq!(templates.render("page", &context).unwrap(), "Home - Shop");
+
+        context.insert("title", "Cart");
+        assert_eq!(
+            templates.render("page.tera.html", &context).unwrap(),
+            "Cart - Shop"
+        );
+    }
+
+    #[test]
+    fn test_configure_errors_are_returned() {
+        let result = TemplateSetup::new()
+            .configure(|tera| Ok(tera.add_raw_template("broken", "{{ unclosed")?))
+            .apply(Tera::default());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_glob_root() {
+        assert_eq!(
+            glob_root("templates/**/*.tera.html"),
+            Path::new("templates")
+        );
+        assert_eq!(glob_root("/srv/app/views/*"), Path::new("/srv/app/views"));
+    }
+
+    #[tokio::test]
+    async fn test_watch_reloads_changed_templates() {
+        let dir = template_dir();
+        let file = dir.join("home.tera.html");
+        std::fs::write(&file, "v1").unwrap();
+
+        let glob = format!("{}/**/*.tera.html", dir.display());
+        let templates = Arc::new(Templates::new(Tera::new(&glob).unwrap()));
+        let handle = templates.watch(&glob, Duration::from_millis(10));
+
+        std::fs::write(&file, "version 2").unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        assert_eq!(
+            templates.render("home", &Context::new()).unwrap(),
+            "version 2"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use crate::prelude::AppResult;
+use crate::rabbitmq::RabbitContract;
+use serde::de::DeserializeOwned;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A message published to [`MockRabbit`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedMessage {
+    pub exchange: String,
+    pub routing_key: String,
+    pub payload: Vec<u8>,
+    /// Set for delayed messages
+    pub delay: Option<Duration>,
+}
+
+impl PublishedMessage {
+    pub fn deserialize<T: DeserializeOwned>(&self) -> AppResult<T> {
+        Ok(serde_json::from_slice(&self.payload)?)
+    }
+}
+
+/// [`RabbitContract`] recording the published messages instead of sending them
+#[derive(Default)]
+pub struct MockRabbit {
+    published: Mutex<Vec<PublishedMessage>>,
+}
+
+impl MockRabbit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every published message, in order
+    pub fn published(&self) -> Vec<PublishedMessage> {
+        self.published.lock().unwrap().clone()
+    }
+
+    /// Messages published to the exchange with the routing key, in order
+    pub fn published_to(&self, exchange: &str, routing_key: &str) -> Vec<PublishedMessage> {
+        self.published
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|message| message.exchange == exchange && message.routing_key == routing_key)
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.published.lock().unwrap().clear();
+    }
+
+    fn record(&self, exchange: &str, routing_key: &str, payload: &[u8], delay: Option<Duration>) {
+        self.published.lock().unwrap().push(PublishedMessage {
+            exchange: exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            payload: payload.to_vec(),
+            delay,
+        });
+    }
+}
+
+#[async_trait::async_trait]
+impl RabbitContract for MockRabbit {
+    async fn publish(&self, exchange: &str, routing_key: &str, payload: &[u8]) -> AppResult<()> {
+        self.record(exchange, routing_key, payload, None);
+        Ok(())
+    }
+
+    async fn publish_delayed(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        payload: &[u8],
+        delay: Duration,
+    ) -> AppResult<()> {
+        self.record(exchange, routing_key, payload, Some(delay));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_published_messages() {
+        let mock = MockRabbit::new();
+        let rabbit: &dyn RabbitContract = &mock;
+
+        rabbit
+            .publish_json("orders", "order.placed", &42)
+            .await
+            .unwrap();
+        rabbit
+            .publish_delayed("orders", "order.expired", b"42", Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let placed = mock.published_to("orders", "order.placed");
+        assert_eq!(placed.len(), 1);
+        assert_eq!(placed[0].deserialize::<u64>().unwrap(), 42);
+        assert_eq!(placed[0].delay, None);
+
+        let expired = mock.published_to("orders", "order.expired");
+        assert_eq!(expired[0].delay, Some(Duration::from_secs(60)));
+
+        mock.clear();
+        assert!(mock.published().is_empty());
+    }
+}
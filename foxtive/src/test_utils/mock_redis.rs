@@ -0,0 +1,235 @@
+use crate::prelude::AppResult;
+use crate::redis::RedisContract;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct State {
+    /// Value and expiry of each key
+    values: HashMap<String, (String, Option<Instant>)>,
+    lists: HashMap<String, VecDeque<String>>,
+    /// Published messages, in order, with their channel
+    published: Vec<(String, String)>,
+}
+
+impl State {
+    fn purge_expired(&mut self) {
+        let now = Instant::now();
+        self.values
+            .retain(|_, (_, expires_at)| expires_at.is_none_or(|at| at > now));
+    }
+
+    fn keys(&mut self) -> Vec<String> {
+        self.purge_expired();
+        self.values
+            .keys()
+            .chain(self.lists.keys())
+            .cloned()
+            .collect()
+    }
+}
+
+/// In-memory [`RedisContract`], values are kept as given and lists are kept apart from them.
+///
+/// Patterns support the `*` and `?` wildcards and `\` escapes, not character classes.
+#[derive(Default)]
+pub struct MockRedis {
+    state: Mutex<State>,
+}
+
+impl MockRedis {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Messages published to the channel, in order
+    pub fn published(&self, channel: &str) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .published
+            .iter()
+            .filter(|(published_to, _)| published_to == channel)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+
+    /// Values of the list, in order
+    pub fn list(&self, key: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .lists
+            .get(key)
+            .map(|list| list.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Removes every key and published message
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = State::default();
+    }
+}
+
+#[async_trait::async_trait]
+impl RedisContract for MockRedis {
+    async fn get_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let mut state = self.state.lock().unwrap();
+        state.purge_expired();
+        Ok(state.values.get(key).map(|(value, _)| value.clone()))
+    }
+
+    async fn set_raw(&self, key: &str, value: &str, ttl: Option<Duration>) -> AppResult<()> {
+        let expires_at = ttl.map(|ttl| Instant::now() + ttl.max(Duration::from_millis(1)));
+        let mut state = self.state.lock().unwrap();
+        state.lists.remove(key);
+        state
+            .values
+            .insert(key.to_string(), (value.to_string(), expires_at));
+        Ok(())
+    }
+
+    async fn set_nx_raw(&self, key: &str, value: &str) -> AppResult<bool> {
+        let mut state = self.state.lock().unwrap();
+        state.purge_expired();
+        if state.values.contains_key(key) || state.lists.contains_key(key) {
+            return Ok(false);
+        }
+
+        state
+            .values
+            .insert(key.to_string(), (value.to_string(), None));
+        Ok(true)
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<i32> {
+        let mut state = self.state.lock().unwrap();
+        state.purge_expired();
+        let deleted = state.values.remove(key).is_some() || state.lists.remove(key).is_some();
+        Ok(i32::from(deleted))
+    }
+
+    async fn exists(&self, key: &str) -> AppResult<bool> {
+        let mut state = self.state.lock().unwrap();
+        state.purge_expired();
+        Ok(state.values.contains_key(key) || state.lists.contains_key(key))
+    }
+
+    async fn keys_by_pattern(&self, pattern: &str) -> AppResult<Vec<String>> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let mut keys = self.state.lock().unwrap().keys();
+        keys.retain(|key| glob_match(&pattern, &key.chars().collect::<Vec<_>>()));
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn delete_by_pattern(&self, pattern: &str) -> AppResult<u32> {
+        let keys = self.keys_by_pattern(pattern).await?;
+        let mut state = self.state.lock().unwrap();
+        for key in &keys {
+            state.values.remove(key);
+            state.lists.remove(key);
+        }
+        Ok(keys.len() as u32)
+    }
+
+    async fn publish_raw(&self, channel: &str, message: &str) -> AppResult<i32> {
+        let mut state = self.state.lock().unwrap();
+        state
+            .published
+            .push((channel.to_string(), message.to_string()));
+        // nobody subscribes to a mock
+        Ok(0)
+    }
+
+    async fn rpush_raw(&self, key: &str, value: &str) -> AppResult<i32> {
+        let mut state = self.state.lock().unwrap();
+        let list = state.lists.entry(key.to_string()).or_default();
+        list.push_back(value.to_string());
+        Ok(list.len() as i32)
+    }
+
+    async fn lpop_raw(&self, key: &str) -> AppResult<Option<String>> {
+        let mut state = self.state.lock().unwrap();
+        let Some(list) = state.lists.get_mut(key) else {
+            return Ok(None);
+        };
+
+        let value = list.pop_front();
+        // like Redis, an emptied list no longer exists
+        if list.is_empty() {
+            state.lists.remove(key);
+        }
+        Ok(value)
+    }
+}
+
+/// Matches the whole text against a glob of `*`, `?` and `\` escapes
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| glob_match(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && glob_match(rest, &text[1..]),
+        Some(('\\', [escaped, rest @ ..])) => {
+            text.first() == Some(escaped) && glob_match(rest, &text[1..])
+        }
+        Some((char, rest)) => text.first() == Some(char) && glob_match(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_values_and_expiry() {
+        let redis = MockRedis::new();
+        redis
+            .set_raw("a", "1", Some(Duration::from_millis(20)))
+            .await
+            .unwrap();
+        assert!(!redis.set_nx_raw("a", "2").await.unwrap());
+        assert_eq!(redis.get_raw("a").await.unwrap().as_deref(), Some("1"));
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(!redis.exists("a").await.unwrap());
+        assert!(redis.set_nx_raw("a", "2").await.unwrap());
+        assert_eq!(redis.delete("a").await.unwrap(), 1);
+        assert_eq!(redis.delete("a").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_patterns() {
+        let redis = MockRedis::new();
+        for key in ["user:1", "user:22", "order:1", "user*"] {
+            redis.set_raw(key, "x", None).await.unwrap();
+        }
+
+        assert_eq!(
+            redis.keys_by_pattern("user:*").await.unwrap(),
+            ["user:1", "user:22"]
+        );
+        assert_eq!(redis.keys_by_pattern("user:?").await.unwrap(), ["user:1"]);
+        assert_eq!(redis.keys_by_pattern("user\\*").await.unwrap(), ["user*"]);
+
+        assert_eq!(redis.delete_by_pattern("user*").await.unwrap(), 3);
+        assert_eq!(redis.keys_by_pattern("*").await.unwrap(), ["order:1"]);
+    }
+
+    #[tokio::test]
+    async fn test_lists_and_published_messages() {
+        let redis: Arc<dyn RedisContract> = Arc::new(MockRedis::new());
+        assert_eq!(redis.rpush_raw("jobs", "a").await.unwrap(), 1);
+        assert_eq!(redis.rpush_raw("jobs", "b").await.unwrap(), 2);
+        assert_eq!(redis.lpop_raw("jobs").await.unwrap().as_deref(), Some("a"));
+        assert_eq!(redis.lpop_raw("jobs").await.unwrap().as_deref(), Some("b"));
+        assert!(!redis.exists("jobs").await.unwrap());
+
+        let mock = MockRedis::new();
+        let redis: &dyn RedisContract = &mock;
+        redis.publish_json("events", &[1, 2]).await.unwrap();
+        assert_eq!(mock.published("events"), ["[1,2]"]);
+        assert!(mock.published("other").is_empty());
+    }
+}
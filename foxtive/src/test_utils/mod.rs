@@ -0,0 +1,44 @@
+//! # Test Utilities
+//!
+//! In-memory implementations of the contracts services depend on, so unit tests don't
+//! need a Redis server or a RabbitMQ broker:
+//!
+//! - [`MockRedis`] implements [`RedisContract`](crate::redis::RedisContract)
+//! - [`MockRabbit`] implements [`RabbitContract`](crate::rabbitmq::RabbitContract) and
+//!   records the published messages for assertions
+//! - [`Cassette`] records the responses of the `reqwest` helper's `HttpClient` to fixtures
+//!   and replays them
+//!
+//! ## Features
+//!
+//! This module requires the `test-utils` feature to be enabled, usually as a dev-dependency
+//! feature. The mocks additionally require the `redis` and `rabbitmq` features, the cassette
+//! the `reqwest` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use foxtive::redis::RedisContract;
+//! use foxtive::test_utils::MockRedis;
+//! use std::sync::Arc;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let redis: Arc<dyn RedisContract> = Arc::new(MockRedis::new());
+//!
+//! redis.set_json("user:1", &"Ada", None).await.unwrap();
+//! let name: Option<String> = redis.get_json("user:1").await.unwrap();
+//! assert_eq!(name.as_deref(), Some("Ada"));
+//! # });
+//! ```
+
+#[cfg(feature = "rabbitmq")]
+mod mock_rabbit;
+#[cfg(feature = "redis")]
+mod mock_redis;
+
+#[cfg(feature = "reqwest")]
+pub use crate::helpers::reqwest::{Cassette, CassetteMode};
+#[cfg(feature = "rabbitmq")]
+pub use mock_rabbit::{MockRabbit, PublishedMessage};
+#[cfg(feature = "redis")]
+pub use mock_redis::MockRedis;